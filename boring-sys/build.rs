@@ -1,4 +1,5 @@
 use fslock::LockFile;
+use std::collections::HashSet;
 use std::env;
 use std::ffi::OsString;
 use std::fs;
@@ -78,6 +79,28 @@ const CMAKE_PARAMS_APPLE: &[(&str, &[(&str, &str)])] = &[
             ("CMAKE_OSX_SYSROOT", "iphonesimulator"),
         ],
     ),
+    // tvOS
+    (
+        "aarch64-apple-tvos",
+        &[
+            ("CMAKE_OSX_ARCHITECTURES", "arm64"),
+            ("CMAKE_OSX_SYSROOT", "appletvos"),
+        ],
+    ),
+    (
+        "aarch64-apple-tvos-sim",
+        &[
+            ("CMAKE_OSX_ARCHITECTURES", "arm64"),
+            ("CMAKE_OSX_SYSROOT", "appletvsimulator"),
+        ],
+    ),
+    (
+        "x86_64-apple-tvos",
+        &[
+            ("CMAKE_OSX_ARCHITECTURES", "x86_64"),
+            ("CMAKE_OSX_SYSROOT", "appletvsimulator"),
+        ],
+    ),
     // macOS
     (
         "aarch64-apple-darwin",
@@ -115,13 +138,55 @@ fn get_apple_sdk_name() -> &'static str {
     panic!("cannot find SDK for {} in CMAKE_PARAMS_APPLE", target);
 }
 
+/// Returns `true` if the target is an iOS or tvOS simulator.
+///
+/// `x86_64` Apple mobile targets only exist as simulators.
+fn is_apple_simulator_target() -> bool {
+    let target = env::var("TARGET").unwrap();
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    target.ends_with("-sim") || arch == "x86_64"
+}
+
+/// Returns the path to the Android NDK, from `ANDROID_NDK_HOME` or, failing that, the
+/// `ANDROID_NDK_ROOT` and `ANDROID_NDK` variables used by other tools.
+fn get_android_ndk_home() -> PathBuf {
+    for var in ["ANDROID_NDK_HOME", "ANDROID_NDK_ROOT", "ANDROID_NDK"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+        if let Ok(path) = env::var(var) {
+            return PathBuf::from(path);
+        }
+    }
+    panic!("Please set ANDROID_NDK_HOME for Android build");
+}
+
+/// Returns the Android API level to build for, from `ANDROID_NATIVE_API_LEVEL` and defaulting
+/// to 21, the minimum level tested.
+fn get_android_api_level() -> String {
+    println!("cargo:rerun-if-env-changed=ANDROID_NATIVE_API_LEVEL");
+    env::var("ANDROID_NATIVE_API_LEVEL").unwrap_or_else(|_| "21".to_string())
+}
+
+/// Returns the path to the WASI SDK from `WASI_SDK_PATH`.
+fn get_wasi_sdk_path() -> PathBuf {
+    println!("cargo:rerun-if-env-changed=WASI_SDK_PATH");
+    env::var("WASI_SDK_PATH")
+        .map(PathBuf::from)
+        .expect("Please set WASI_SDK_PATH for WASI build")
+}
+
+/// Returns `true` if libssl is built and linked, which isn't the case for WASI, where only the
+/// cryptographic primitives are supported.
+fn is_ssl_supported() -> bool {
+    env::var("CARGO_CFG_TARGET_OS").unwrap() != "wasi"
+}
+
 /// Returns an absolute path to the BoringSSL source.
-fn get_boringssl_source_path() -> String {
-    #[cfg(feature = "fips")]
-    const SUBMODULE_DIR: &str = "boringssl-fips";
-    #[cfg(not(feature = "fips"))]
-    const SUBMODULE_DIR: &str = "boringssl";
+#[cfg(feature = "fips")]
+const SUBMODULE_DIR: &str = "boringssl-fips";
+#[cfg(not(feature = "fips"))]
+const SUBMODULE_DIR: &str = "boringssl";
 
+fn get_boringssl_source_path() -> String {
     static COPY_SOURCES: Once = Once::new();
 
     if let Ok(src_path) = env::var("BORING_BSSL_SOURCE_PATH") {
@@ -169,6 +234,10 @@ fn get_boringssl_source_path() -> String {
 /// See issue: https://github.com/alexcrichton/cmake-rs/issues/18
 fn get_boringssl_platform_output_path() -> String {
     if cfg!(target_env = "msvc") {
+        if cfg!(feature = "minimal") {
+            return "MinSizeRel".to_string();
+        }
+
         // Code under this branch should match the logic in cmake-rs
         let debug_env_var = env::var("DEBUG").expect("DEBUG variable not defined in env");
 
@@ -216,10 +285,7 @@ fn get_boringssl_cmake_config() -> cmake::Config {
         match os.as_ref() {
             "android" => {
                 // We need ANDROID_NDK_HOME to be set properly.
-                println!("cargo:rerun-if-env-changed=ANDROID_NDK_HOME");
-                let android_ndk_home = env::var("ANDROID_NDK_HOME")
-                    .expect("Please set ANDROID_NDK_HOME for Android build");
-                let android_ndk_home = std::path::Path::new(&android_ndk_home);
+                let android_ndk_home = get_android_ndk_home();
                 for (name, value) in cmake_params_android() {
                     eprintln!("android arch={} add {}={}", arch, name, value);
                     boringssl_cmake.define(name, value);
@@ -229,8 +295,7 @@ fn get_boringssl_cmake_config() -> cmake::Config {
                 eprintln!("android toolchain={}", toolchain_file);
                 boringssl_cmake.define("CMAKE_TOOLCHAIN_FILE", toolchain_file);
 
-                // 21 is the minimum level tested. You can give higher value.
-                boringssl_cmake.define("ANDROID_NATIVE_API_LEVEL", "21");
+                boringssl_cmake.define("ANDROID_NATIVE_API_LEVEL", get_android_api_level());
                 boringssl_cmake.define("ANDROID_STL", "c++_shared");
             }
 
@@ -241,20 +306,27 @@ fn get_boringssl_cmake_config() -> cmake::Config {
                 }
             }
 
-            "ios" => {
+            "ios" | "tvos" => {
                 for (name, value) in cmake_params_apple() {
-                    eprintln!("ios arch={} add {}={}", arch, name, value);
+                    eprintln!("{} arch={} add {}={}", os, arch, name, value);
                     boringssl_cmake.define(name, value);
                 }
 
                 // Bitcode is always on.
                 let bitcode_cflag = "-fembed-bitcode";
 
-                // Hack for Xcode 10.1.
-                let target_cflag = if arch == "x86_64" {
-                    "-target x86_64-apple-ios-simulator"
+                // The SDK alone doesn't tell clang to target the simulator, which matters for
+                // arm64, where the device and simulator share the architecture. The same flags
+                // must reach the assembler, or the objects it produces fail to link.
+                let target_cflag = if is_apple_simulator_target() {
+                    let clang_arch = if arch == "aarch64" {
+                        "arm64"
+                    } else {
+                        arch.as_str()
+                    };
+                    format!("-target {}-apple-{}-simulator", clang_arch, os)
                 } else {
-                    ""
+                    String::new()
                 };
 
                 let cflag = format!("{} {}", bitcode_cflag, target_cflag);
@@ -291,6 +363,10 @@ fn get_boringssl_cmake_config() -> cmake::Config {
                         pwd.join("cmake/armv7-linux.cmake").as_os_str(),
                     );
                 }
+                // Building for another libc on the host architecture, e.g. a static
+                // x86_64-unknown-linux-musl build on x86_64 glibc, only needs the target C
+                // compiler, which cmake picks up from `CC_<target>`.
+                _ if host.starts_with(&arch) => {}
                 _ => {
                     eprintln!(
                         "warning: no toolchain file configured by boring-sys for {}",
@@ -303,6 +379,21 @@ fn get_boringssl_cmake_config() -> cmake::Config {
         }
     }
 
+    if os == "wasi" {
+        // WASI has no threads, and no assembly implementations exist for wasm32, so only the
+        // portable C code is built, using the toolchain of the WASI SDK.
+        let wasi_sdk_path = get_wasi_sdk_path();
+        boringssl_cmake.define(
+            "CMAKE_TOOLCHAIN_FILE",
+            wasi_sdk_path.join("share/cmake/wasi-sdk.cmake"),
+        );
+        boringssl_cmake.define("WASI_SDK_PREFIX", &wasi_sdk_path);
+        boringssl_cmake.define("OPENSSL_NO_ASM", "YES");
+        boringssl_cmake
+            .cflag("-DOPENSSL_NO_THREADS_CORRUPT_MEMORY_AND_LEAK_SECRETS_IF_THREADED")
+            .cxxflag("-DOPENSSL_NO_THREADS_CORRUPT_MEMORY_AND_LEAK_SECRETS_IF_THREADED");
+    }
+
     boringssl_cmake
 }
 
@@ -387,9 +478,8 @@ fn get_extra_clang_args_for_bindgen() -> Vec<String> {
     let mut params = Vec::new();
 
     // Add platform-specific parameters.
-    #[allow(clippy::single_match)]
     match os.as_ref() {
-        "ios" | "macos" => {
+        "ios" | "tvos" | "macos" => {
             // When cross-compiling for Apple targets, tell bindgen to use SDK sysroot,
             // and *don't* use system headers of the host macOS.
             let sdk = get_apple_sdk_name();
@@ -414,9 +504,7 @@ fn get_extra_clang_args_for_bindgen() -> Vec<String> {
             params.push(sysroot);
         }
         "android" => {
-            let android_ndk_home = env::var("ANDROID_NDK_HOME")
-                .expect("Please set ANDROID_NDK_HOME for Android build");
-            let mut android_sysroot = std::path::PathBuf::from(android_ndk_home);
+            let mut android_sysroot = get_android_ndk_home();
             android_sysroot.extend(["toolchains", "llvm", "prebuilt"]);
             let toolchain = match pick_best_android_ndk_toolchain(&android_sysroot) {
                 Ok(toolchain) => toolchain,
@@ -436,6 +524,11 @@ fn get_extra_clang_args_for_bindgen() -> Vec<String> {
             // we'd already know from env::var.
             params.push(android_sysroot.into_os_string().into_string().unwrap());
         }
+        "wasi" => {
+            let sysroot = get_wasi_sdk_path().join("share/wasi-sysroot");
+            params.push("--sysroot".to_string());
+            params.push(sysroot.into_os_string().into_string().unwrap());
+        }
         _ => {}
     }
 
@@ -518,6 +611,28 @@ fn build_boring_from_sources() -> String {
         ensure_patches_applied().unwrap();
     }
 
+    if cfg!(feature = "prefix-symbols") {
+        // The symbols to prefix are those of an unprefixed build, which is kept in its own
+        // directory so that both builds stay incremental.
+        let out_dir = env::var("OUT_DIR").unwrap();
+        let mut cfg = get_boringssl_build_config();
+        cfg.out_dir(Path::new(&out_dir).join("unprefixed"));
+        let unprefixed_dir = build_boringssl_libraries(&mut cfg);
+
+        let symbols_path = write_prefix_symbols(&unprefixed_dir);
+
+        let mut cfg = get_boringssl_build_config();
+        cfg.define("BORINGSSL_PREFIX", get_symbol_prefix());
+        cfg.define("BORINGSSL_PREFIX_SYMBOLS", &symbols_path);
+        return build_boringssl_libraries(&mut cfg).display().to_string();
+    }
+
+    build_boringssl_libraries(&mut get_boringssl_build_config())
+        .display()
+        .to_string()
+}
+
+fn get_boringssl_build_config() -> cmake::Config {
     let mut cfg = get_boringssl_cmake_config();
 
     if cfg!(feature = "fuzzing") {
@@ -525,6 +640,11 @@ fn build_boring_from_sources() -> String {
             .cxxflag("-DBORINGSSL_UNSAFE_FUZZER_MODE");
     }
 
+    if cfg!(feature = "unsafe-deterministic-rng") {
+        cfg.cflag("-DBORINGSSL_UNSAFE_DETERMINISTIC_MODE")
+            .cxxflag("-DBORINGSSL_UNSAFE_DETERMINISTIC_MODE");
+    }
+
     if cfg!(feature = "fips") {
         let (clang, clangxx) = verify_fips_clang_version();
         cfg.define("CMAKE_C_COMPILER", clang);
@@ -537,16 +657,169 @@ fn build_boring_from_sources() -> String {
         cfg.define("FIPS", "1");
     }
 
-    cfg.build_target("ssl").build();
-    cfg.build_target("crypto").build().display().to_string()
+    if cfg!(feature = "minimal") {
+        // `OPENSSL_SMALL` trades speed for size, e.g. by dropping precomputed EC tables, and
+        // per-function sections let the linker discard whatever the binary doesn't use.
+        cfg.profile("MinSizeRel");
+        cfg.cflag("-DOPENSSL_SMALL").cxxflag("-DOPENSSL_SMALL");
+        if !cfg!(target_env = "msvc") {
+            cfg.cflag("-ffunction-sections -fdata-sections")
+                .cxxflag("-ffunction-sections -fdata-sections");
+        }
+    }
+
+    cfg
 }
 
-fn link_in_precompiled_bcm_o(bssl_dir: &str) {
-    println!("cargo:warning=linking in precompiled `bcm.o` module");
+fn build_boringssl_libraries(cfg: &mut cmake::Config) -> PathBuf {
+    if is_ssl_supported() {
+        cfg.build_target("ssl").build();
+    }
+    cfg.build_target("crypto").build()
+}
+
+/// Returns the prefix of all BoringSSL symbols with the `prefix-symbols` feature.
+///
+/// It includes the crate version, so that different versions of this crate can be linked into the
+/// same binary as well.
+fn get_symbol_prefix() -> String {
+    format!(
+        "boring_sys_{}",
+        env!("CARGO_PKG_VERSION").replace(['.', '-'], "_")
+    )
+}
+
+fn get_prefix_symbols_path() -> PathBuf {
+    Path::new(&env::var("OUT_DIR").unwrap()).join("boringssl_prefix_symbols.txt")
+}
+
+/// Lists the symbols defined by the static libraries built in `bssl_dir` into the file expected by
+/// BoringSSL's `BORINGSSL_PREFIX_SYMBOLS` CMake option, and returns its path.
+fn write_prefix_symbols(bssl_dir: &Path) -> PathBuf {
+    let mut libs = vec![];
+    find_static_libs(&bssl_dir.join("build"), &mut libs).unwrap();
+
+    if libs.is_empty() {
+        panic!(
+            "no BoringSSL static libraries were found in `{}` to collect symbols from",
+            bssl_dir.display()
+        );
+    }
+
+    // NOTE: Mach-O symbols have a leading underscore which is not part of the C name.
+    let is_apple = env::var("CARGO_CFG_TARGET_VENDOR").unwrap() == "apple";
+
+    let mut symbols = vec![];
+    for lib in &libs {
+        let out = run_command(
+            Command::new("nm")
+                .args(["--defined-only", "--extern-only", "--format=posix"])
+                .arg(lib),
+        )
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to list the symbols of `{}`; the `prefix-symbols` feature requires `nm`: {}",
+                lib.display(),
+                e
+            )
+        });
+
+        for line in String::from_utf8(out.stdout).unwrap().lines() {
+            let name = match line.split_whitespace().next() {
+                Some(name) => name,
+                None => continue,
+            };
+            let name = if is_apple {
+                name.strip_prefix('_').unwrap_or(name)
+            } else {
+                name
+            };
+
+            if !name.ends_with(':') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                symbols.push(name.to_string());
+            }
+        }
+    }
+
+    symbols.sort();
+    symbols.dedup();
+
+    let path = get_prefix_symbols_path();
+    fs::write(&path, symbols.join("\n") + "\n").unwrap();
+
+    path
+}
+
+fn find_static_libs(dir: &Path, libs: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
 
+        if path.is_dir() {
+            find_static_libs(&path, libs)?;
+        } else if path
+            .file_name()
+            .is_some_and(|name| name == "libcrypto.a" || name == "libssl.a")
+        {
+            libs.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Points the generated bindings at the prefixed symbols of a `prefix-symbols` build.
+#[derive(Debug)]
+struct PrefixSymbols {
+    prefix: String,
+    symbols: HashSet<String>,
+}
+
+impl bindgen::callbacks::ParseCallbacks for PrefixSymbols {
+    fn generated_link_name_override(
+        &self,
+        item: bindgen::callbacks::ItemInfo<'_>,
+    ) -> Option<String> {
+        if self.symbols.contains(item.name) {
+            Some(format!("{}_{}", self.prefix, item.name))
+        } else {
+            None
+        }
+    }
+}
+
+fn link_in_precompiled_bcm_o(bssl_dir: &str) {
     let bcm_o_src_path = env::var("BORING_SSL_PRECOMPILED_BCM_O")
         .expect("`fips-link-precompiled` requires `BORING_SSL_PRECOMPILED_BCM_O` env variable to be specified");
 
+    if !Path::new(&bcm_o_src_path).is_file() {
+        panic!(
+            "`BORING_SSL_PRECOMPILED_BCM_O` points at `{}`, which is not a file",
+            bcm_o_src_path
+        );
+    }
+
+    let sha256 = sha256_file(&bcm_o_src_path);
+
+    if let Ok(expected) = env::var("BORING_SSL_PRECOMPILED_BCM_O_SHA256") {
+        if !expected.trim().eq_ignore_ascii_case(&sha256) {
+            panic!(
+                "precompiled FIPS module `{}` has SHA-256 {}, but \
+                 `BORING_SSL_PRECOMPILED_BCM_O_SHA256` expects {}",
+                bcm_o_src_path,
+                sha256,
+                expected.trim()
+            );
+        }
+    }
+
+    println!(
+        "cargo:warning=linking in precompiled FIPS module `{}` (SHA-256 {})",
+        bcm_o_src_path, sha256
+    );
+    // Exported under a distinct name: `option_env!` would otherwise pick up the user-provided
+    // variable when this code path is not taken.
+    println!("cargo:rustc-env=BORING_SYS_LINKED_BCM_O_SHA256={}", sha256);
+
     let libcrypto_path = PathBuf::from(bssl_dir)
         .join("build/crypto/libcrypto.a")
         .canonicalize()
@@ -556,10 +829,11 @@ fn link_in_precompiled_bcm_o(bssl_dir: &str) {
 
     let bcm_o_dst_path = PathBuf::from(bssl_dir).join("build/bcm-fips.o");
 
-    fs::copy(bcm_o_src_path, &bcm_o_dst_path).unwrap();
+    fs::copy(&bcm_o_src_path, &bcm_o_dst_path).unwrap();
 
     // check that fips module is named as expected
-    let out = run_command(Command::new("ar").args(["t", &libcrypto_path, "bcm.o"])).unwrap();
+    let out = run_command(Command::new("ar").args(["t", &libcrypto_path, "bcm.o"]))
+        .unwrap_or_else(|e| panic!("failed to list the members of `{}`: {}", libcrypto_path, e));
 
     assert_eq!(
         String::from_utf8(out.stdout).unwrap().trim(),
@@ -578,13 +852,41 @@ fn link_in_precompiled_bcm_o(bssl_dir: &str) {
         &libcrypto_path,
         bcm_o_dst_path.display().to_string().as_str(),
     ]))
-    .unwrap();
+    .unwrap_or_else(|e| {
+        panic!(
+            "failed to insert the precompiled FIPS module into `{}`: {}",
+            libcrypto_path, e
+        )
+    });
+}
+
+/// Returns the lowercase hex SHA-256 digest of the file at `path`, using `sha256sum` or, where it
+/// is unavailable such as on macOS, `shasum`.
+fn sha256_file(path: &str) -> String {
+    let out = run_command(Command::new("sha256sum").arg(path))
+        .or_else(|_| run_command(Command::new("shasum").args(["-a", "256", path])))
+        .unwrap_or_else(|e| panic!("failed to compute the SHA-256 digest of `{}`: {}", path, e));
+
+    String::from_utf8(out.stdout)
+        .unwrap()
+        .split_whitespace()
+        .next()
+        .expect("unexpected output of SHA-256 tool")
+        .to_ascii_lowercase()
 }
 
 fn check_feature_compatibility() {
     #[cfg(all(feature = "fips", feature = "rpk"))]
     compile_error!("`fips` and `rpk` features are mutually exclusive");
 
+    // The precompiled FIPS module defines unprefixed symbols, which can't be renamed without
+    // invalidating its integrity check.
+    #[cfg(all(feature = "fips-link-precompiled", feature = "prefix-symbols"))]
+    compile_error!("`fips-link-precompiled` and `prefix-symbols` features are mutually exclusive");
+
+    #[cfg(all(feature = "prefix-symbols", target_env = "msvc"))]
+    compile_error!("the `prefix-symbols` feature is not supported on MSVC targets");
+
     let no_patches_enabled = cfg!(feature = "no-patches");
     let is_external_native_lib_source =
         env::var("BORING_BSSL_PATH").is_err() && env::var("BORING_BSSL_SOURCE_PATH").is_err();
@@ -598,23 +900,27 @@ fn check_feature_compatibility() {
 
     let features_with_patches_enabled = cfg!(any(feature = "rpk", feature = "pq-experimental"));
     let patches_required = features_with_patches_enabled && !no_patches_enabled;
-    let build_from_sources_required = cfg!(feature = "fips-link-precompiled") || patches_required;
+    let build_from_sources_required = cfg!(any(
+        feature = "fips-link-precompiled",
+        feature = "unsafe-deterministic-rng",
+        feature = "prefix-symbols",
+        feature = "minimal"
+    )) || patches_required;
     let is_precompiled_native_lib = env::var("BORING_BSSL_PATH").is_ok();
 
     if is_precompiled_native_lib && build_from_sources_required {
-        panic!("precompiled BoringSSL was provided, so FIPS configuration or optional patches can't be applied");
+        panic!("precompiled BoringSSL was provided, so FIPS configuration, deterministic RNG, symbol prefixing, a minimal build or optional patches can't be applied");
     }
-}
-
-fn main() {
-    println!("cargo:rerun-if-env-changed=BORING_BSSL_PATH");
-    println!("cargo:rerun-if-env-changed=BORING_BSSL_INCLUDE_PATH");
-    println!("cargo:rerun-if-env-changed=BORING_BSSL_SOURCE_PATH");
-    println!("cargo:rerun-if-env-changed=BORING_SSL_PRECOMPILED_BCM_O");
-    println!("cargo:rerun-if-env-changed=BORINGSSL_BUILD_DIR");
 
-    check_feature_compatibility();
+    if cfg!(feature = "pkg-config") && is_external_native_lib_source && build_from_sources_required
+    {
+        panic!("the `pkg-config` feature links a system BoringSSL, so FIPS configuration, deterministic RNG, symbol prefixing, a minimal build or optional patches can't be applied");
+    }
+}
 
+/// Links BoringSSL from `BORING_BSSL_PATH`, or builds it from sources, and returns the path to its
+/// headers.
+fn link_boringssl() -> String {
     let bssl_dir = env::var("BORING_BSSL_PATH").unwrap_or_else(|_| build_boring_from_sources());
     let build_path = get_boringssl_platform_output_path();
 
@@ -643,9 +949,11 @@ fn main() {
     }
 
     println!("cargo:rustc-link-lib=static=crypto");
-    println!("cargo:rustc-link-lib=static=ssl");
+    if is_ssl_supported() {
+        println!("cargo:rustc-link-lib=static=ssl");
+    }
 
-    let include_path = env::var("BORING_BSSL_INCLUDE_PATH").unwrap_or_else(|_| {
+    env::var("BORING_BSSL_INCLUDE_PATH").unwrap_or_else(|_| {
         if let Ok(bssl_path) = env::var("BORING_BSSL_PATH") {
             return format!("{}/include", bssl_path);
         }
@@ -657,7 +965,239 @@ fn main() {
         } else {
             format!("{}/src/include", &src_path)
         }
-    });
+    })
+}
+
+/// A BoringSSL installation discovered with `pkg-config`.
+struct SystemBoringSsl {
+    link_dirs: Vec<String>,
+    include_dir: String,
+}
+
+/// Looks up a system BoringSSL with `pkg-config` if the `pkg-config` feature is enabled and no
+/// prebuilt binaries or sources were provided explicitly.
+///
+/// The package name defaults to `boringssl` and can be overridden with
+/// `BORING_BSSL_PKG_CONFIG_NAME`.
+fn find_system_boringssl() -> Option<SystemBoringSsl> {
+    if !cfg!(feature = "pkg-config")
+        || env::var("BORING_BSSL_PATH").is_ok()
+        || env::var("BORING_BSSL_SOURCE_PATH").is_ok()
+    {
+        return None;
+    }
+
+    let name = env::var("BORING_BSSL_PKG_CONFIG_NAME").unwrap_or_else(|_| "boringssl".to_string());
+
+    let pkg_config = |args: &[&str]| {
+        let out =
+            run_command(Command::new("pkg-config").args(args).arg(&name)).unwrap_or_else(|e| {
+                panic!(
+                    "the `pkg-config` feature is enabled, but BoringSSL package `{}` was not \
+                     found: {}. Set `BORING_BSSL_PKG_CONFIG_NAME` to the name of the package, or \
+                     use `BORING_BSSL_PATH` to point at prebuilt binaries instead",
+                    name, e
+                )
+            });
+        String::from_utf8(out.stdout).unwrap()
+    };
+
+    let link_dirs = pkg_config(&["--libs-only-L"])
+        .split_whitespace()
+        .filter_map(|flag| flag.strip_prefix("-L"))
+        .map(str::to_string)
+        .collect();
+
+    let include_dir = env::var("BORING_BSSL_INCLUDE_PATH")
+        .unwrap_or_else(|_| pkg_config(&["--variable=includedir"]).trim().to_string());
+
+    Some(SystemBoringSsl {
+        link_dirs,
+        include_dir,
+    })
+}
+
+/// Links a BoringSSL discovered by [`find_system_boringssl`] and returns the path to its headers.
+fn link_system_boringssl(system: SystemBoringSsl) -> String {
+    for dir in &system.link_dirs {
+        println!("cargo:rustc-link-search=native={}", dir);
+    }
+
+    if is_ssl_supported() {
+        println!("cargo:rustc-link-lib=ssl");
+    }
+    println!("cargo:rustc-link-lib=crypto");
+
+    system.include_dir
+}
+
+/// Functions which the `boring` crate depends on and which are only present in sufficiently
+/// recent BoringSSL, checked to give a clear error instead of a link failure or a wall of
+/// compilation errors in `boring` when an incompatible library is provided.
+const REQUIRED_FUNCTIONS: &[&str] = &[
+    "BORINGSSL_self_test",
+    "EVP_AEAD_CTX_seal",
+    "SSL_CTX_set_grease_enabled",
+    "SSL_CTX_set_select_certificate_cb",
+    "SSL_get_curve_id",
+];
+
+/// Checks that the headers at `include_path` belong to a BoringSSL compatible with this crate.
+fn check_bindings(bindings: &str, include_path: &str) {
+    if !bindings.contains("pub const BORINGSSL_API_VERSION") {
+        panic!(
+            "the headers at `{}` are not BoringSSL headers (`BORINGSSL_API_VERSION` is not \
+             defined); make sure `BORING_BSSL_INCLUDE_PATH` or the `pkg-config` package points \
+             at BoringSSL rather than OpenSSL",
+            include_path
+        );
+    }
+
+    let missing = REQUIRED_FUNCTIONS
+        .iter()
+        .filter(|name| !bindings.contains(&format!("pub fn {}(", name)))
+        .copied()
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        panic!(
+            "the BoringSSL headers at `{}` are missing functions required by this crate: {}; \
+             the provided BoringSSL is likely too old",
+            include_path,
+            missing.join(", ")
+        );
+    }
+}
+
+/// Features of this crate which change how BoringSSL is built or linked.
+const BUILD_FEATURES: &[(&str, bool)] = &[
+    ("fips", cfg!(feature = "fips")),
+    (
+        "fips-link-precompiled",
+        cfg!(feature = "fips-link-precompiled"),
+    ),
+    ("rpk", cfg!(feature = "rpk")),
+    ("pq-experimental", cfg!(feature = "pq-experimental")),
+    (
+        "unsafe-deterministic-rng",
+        cfg!(feature = "unsafe-deterministic-rng"),
+    ),
+    ("prefix-symbols", cfg!(feature = "prefix-symbols")),
+    ("minimal", cfg!(feature = "minimal")),
+    ("no-patches", cfg!(feature = "no-patches")),
+];
+
+/// Returns the capabilities of the linked BoringSSL, which dependent crates read from
+/// `DEP_BORINGSSL_CAPABILITIES`.
+fn get_boringssl_capabilities() -> Vec<&'static str> {
+    let capabilities = [
+        ("ssl", is_ssl_supported()),
+        (
+            "fips",
+            cfg!(any(feature = "fips", feature = "fips-link-precompiled")),
+        ),
+        ("rpk", cfg!(feature = "rpk")),
+        ("pq_experimental", cfg!(feature = "pq-experimental")),
+        ("minimal", cfg!(feature = "minimal")),
+    ];
+
+    capabilities
+        .iter()
+        .filter(|(_, supported)| *supported)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Returns how the linked BoringSSL was obtained.
+fn get_boringssl_source(system: bool) -> &'static str {
+    if system {
+        "pkg-config"
+    } else if env::var("BORING_BSSL_PATH").is_ok() {
+        "prebuilt"
+    } else if env::var("BORING_BSSL_SOURCE_PATH").is_ok() {
+        "source-path"
+    } else {
+        "vendored"
+    }
+}
+
+/// Returns the commit of the BoringSSL sources, if it can be determined.
+///
+/// `BORING_BSSL_COMMIT` takes precedence, which allows recording the commit of prebuilt binaries
+/// or of sources which are not a git checkout.
+fn get_boringssl_commit(source: &str) -> Option<String> {
+    if let Ok(commit) = env::var("BORING_BSSL_COMMIT") {
+        return Some(commit);
+    }
+
+    let src_path = match source {
+        "vendored" => Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("deps")
+            .join(SUBMODULE_DIR),
+        "source-path" => PathBuf::from(env::var("BORING_BSSL_SOURCE_PATH").unwrap()),
+        _ => return None,
+    };
+
+    // NOTE: without this check, an uninitialized submodule would report the commit of the
+    // enclosing repository.
+    if !src_path.join(".git").exists() {
+        return None;
+    }
+
+    let out = run_command(
+        Command::new("git")
+            .arg("-C")
+            .arg(&src_path)
+            .args(["rev-parse", "HEAD"]),
+    )
+    .ok()?;
+
+    Some(String::from_utf8(out.stdout).ok()?.trim().to_string())
+}
+
+/// Records the BoringSSL commit, source and build features for `boring_sys::BORINGSSL_*`.
+fn emit_build_info(source: &str) {
+    if let Some(commit) = get_boringssl_commit(source) {
+        println!("cargo:rustc-env=BORING_BSSL_COMMIT={}", commit);
+    }
+
+    let features = BUILD_FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+
+    println!("cargo:rustc-env=BORING_BSSL_SOURCE={}", source);
+    println!(
+        "cargo:rustc-env=BORING_BSSL_FEATURES={}",
+        features.join(",")
+    );
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=BORING_BSSL_PATH");
+    println!("cargo:rerun-if-env-changed=BORING_BSSL_INCLUDE_PATH");
+    println!("cargo:rerun-if-env-changed=BORING_BSSL_SOURCE_PATH");
+    println!("cargo:rerun-if-env-changed=BORING_SSL_PRECOMPILED_BCM_O");
+    println!("cargo:rerun-if-env-changed=BORING_SSL_PRECOMPILED_BCM_O_SHA256");
+    println!("cargo:rerun-if-env-changed=BORINGSSL_BUILD_DIR");
+    println!("cargo:rerun-if-env-changed=BORING_BSSL_PKG_CONFIG_NAME");
+    println!("cargo:rerun-if-env-changed=PKG_CONFIG_PATH");
+    println!("cargo:rerun-if-env-changed=BORING_BSSL_COMMIT");
+
+    check_feature_compatibility();
+
+    let system = find_system_boringssl();
+    emit_build_info(get_boringssl_source(system.is_some()));
+    println!(
+        "cargo:capabilities={}",
+        get_boringssl_capabilities().join(",")
+    );
+
+    let include_path = match system {
+        Some(system) => link_system_boringssl(system),
+        None => link_boringssl(),
+    };
 
     let mut builder = bindgen::Builder::default()
         .derive_copy(true)
@@ -694,6 +1234,7 @@ fn main() {
     }
 
     let headers = [
+        "aead.h",
         "aes.h",
         "asn1_mac.h",
         "asn1t.h",
@@ -716,6 +1257,7 @@ fn main() {
         "opensslv.h",
         "ossl_typ.h",
         "pkcs12.h",
+        "pkcs7.h",
         "poly1305.h",
         "rand.h",
         "rc4.h",
@@ -735,7 +1277,25 @@ fn main() {
         );
     }
 
+    // Declared in BoringSSL's internal `crypto/fipsmodule/rand/internal.h`, which is not
+    // installed with the public headers, but the symbol is exported from `libcrypto`.
+    builder = builder.header_contents(
+        "rand_internal.h",
+        "#include <openssl/base.h>\n\
+         OPENSSL_EXPORT void RAND_bytes_with_additional_data(\
+         uint8_t *out, size_t out_len, const uint8_t user_additional_data[32]);\n",
+    );
+
+    if cfg!(feature = "prefix-symbols") {
+        let symbols = fs::read_to_string(get_prefix_symbols_path()).unwrap();
+        builder = builder.parse_callbacks(Box::new(PrefixSymbols {
+            prefix: get_symbol_prefix(),
+            symbols: symbols.lines().map(str::to_string).collect(),
+        }));
+    }
+
     let bindings = builder.generate().expect("Unable to generate bindings");
+    check_bindings(&bindings.to_string(), &include_path);
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
     bindings
         .write_to_file(out_path.join("bindings.rs"))
@@ -65,6 +65,22 @@ const_fn! {
     }
 }
 
+/// The SHA-256 digest, in lowercase hex, of the precompiled FIPS module linked in with the
+/// `fips-link-precompiled` feature, or `None` if no precompiled module was linked.
+pub const PRECOMPILED_BCM_O_SHA256: Option<&str> = option_env!("BORING_SYS_LINKED_BCM_O_SHA256");
+
+/// The git commit of the BoringSSL sources, or `None` if it could not be determined.
+///
+/// It can be set explicitly with the `BORING_BSSL_COMMIT` environment variable at build time,
+/// e.g. for prebuilt binaries.
+pub const BORINGSSL_COMMIT: Option<&str> = option_env!("BORING_BSSL_COMMIT");
+
+/// How the linked BoringSSL was obtained: `vendored`, `source-path`, `prebuilt` or `pkg-config`.
+pub const BORINGSSL_SOURCE: &str = env!("BORING_BSSL_SOURCE");
+
+/// The comma-separated features of this crate which affected how BoringSSL was built.
+pub const BORINGSSL_FEATURES: &str = env!("BORING_BSSL_FEATURES");
+
 pub fn init() {
     use std::ptr;
     use std::sync::Once;
@@ -72,9 +88,14 @@ pub fn init() {
     // explicitly initialize to work around https://github.com/openssl/openssl/issues/3505
     static INIT: Once = Once::new();
 
-    let init_options = OPENSSL_INIT_LOAD_SSL_STRINGS;
+    // libssl isn't built for WASI, so only the crypto library is initialized there.
+    #[cfg(target_os = "wasi")]
+    INIT.call_once(|| unsafe { CRYPTO_library_init() });
 
+    #[cfg(not(target_os = "wasi"))]
     INIT.call_once(|| {
+        let init_options = OPENSSL_INIT_LOAD_SSL_STRINGS;
+
         assert_eq!(
             unsafe { OPENSSL_init_ssl(init_options.try_into().unwrap(), ptr::null_mut()) },
             1
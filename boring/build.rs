@@ -0,0 +1,16 @@
+/// Capabilities which `boring-sys` may report for the BoringSSL it links.
+const CAPABILITIES: &[&str] = &["ssl", "fips", "rpk", "pq_experimental", "minimal"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    for capability in CAPABILITIES {
+        println!("cargo:rustc-check-cfg=cfg(boringssl_{})", capability);
+    }
+
+    // Set by `boring-sys`, see its `links` key.
+    let capabilities = std::env::var("DEP_BORINGSSL_CAPABILITIES").unwrap_or_default();
+    for capability in capabilities.split(',').filter(|c| !c.is_empty()) {
+        println!("cargo:rustc-cfg=boringssl_{}", capability);
+    }
+}
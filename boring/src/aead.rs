@@ -0,0 +1,477 @@
+//! Authenticated encryption with associated data.
+//!
+//! This exposes BoringSSL's `EVP_AEAD` interface, which unlike [`symm`](crate::symm) processes
+//! each message in a single call and supports AEADs without an `EVP_CIPHER` counterpart, such as
+//! AES-GCM-SIV and XChaCha20-Poly1305.
+//!
+//! AES-GCM-SIV is resistant to nonce misuse: repeating a nonce only reveals whether the same
+//! message was encrypted twice. The other algorithms lose all security if a nonce is ever reused
+//! with the same key.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use boring::aead::{Aead, AeadCtx};
+//!
+//! let aead = Aead::chacha20_poly1305();
+//! let key = [0x42; 32];
+//! let nonce = [0x24; 12];
+//! let ctx = AeadCtx::new(aead, &key).unwrap();
+//!
+//! let mut ciphertext = vec![0; 5 + aead.max_overhead()];
+//! let len = ctx.seal(&nonce, b"header", b"hello", &mut ciphertext).unwrap();
+//! ciphertext.truncate(len);
+//!
+//! let mut plaintext = vec![0; ciphertext.len()];
+//! let len = ctx.open(&nonce, b"header", &ciphertext, &mut plaintext).unwrap();
+//! assert_eq!(&plaintext[..len], b"hello");
+//! ```
+use crate::ffi;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::{cvt, cvt_p};
+
+/// An AEAD algorithm.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Aead(*const ffi::EVP_AEAD);
+
+unsafe impl Sync for Aead {}
+unsafe impl Send for Aead {}
+
+impl Aead {
+    /// AES-128 in Galois Counter Mode, with a 16-byte key and a 12-byte nonce.
+    pub fn aes_128_gcm() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_128_gcm()) }
+    }
+
+    /// AES-256 in Galois Counter Mode, with a 32-byte key and a 12-byte nonce.
+    pub fn aes_256_gcm() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_256_gcm()) }
+    }
+
+    /// AES-128 in GCM-SIV mode, with a 16-byte key and a 12-byte nonce.
+    ///
+    /// This mode is resistant to nonce misuse.
+    pub fn aes_128_gcm_siv() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_128_gcm_siv()) }
+    }
+
+    /// AES-256 in GCM-SIV mode, with a 32-byte key and a 12-byte nonce.
+    ///
+    /// This mode is resistant to nonce misuse.
+    pub fn aes_256_gcm_siv() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_aes_256_gcm_siv()) }
+    }
+
+    /// ChaCha20-Poly1305, as specified in RFC 8439, with a 32-byte key and a 12-byte nonce.
+    pub fn chacha20_poly1305() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_chacha20_poly1305()) }
+    }
+
+    /// XChaCha20-Poly1305, with a 32-byte key and a 24-byte nonce.
+    ///
+    /// The longer nonce can safely be chosen at random.
+    pub fn xchacha20_poly1305() -> Aead {
+        unsafe { Aead(ffi::EVP_aead_xchacha20_poly1305()) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_ptr(&self) -> *const ffi::EVP_AEAD {
+        self.0
+    }
+
+    /// Returns the length of keys, in bytes.
+    ///
+    /// This corresponds to [`EVP_AEAD_key_length`].
+    ///
+    /// [`EVP_AEAD_key_length`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_key_length
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn key_len(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_key_length(self.0) }
+    }
+
+    /// Returns the length of nonces, in bytes.
+    ///
+    /// This corresponds to [`EVP_AEAD_nonce_length`].
+    ///
+    /// [`EVP_AEAD_nonce_length`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_nonce_length
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn nonce_len(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_nonce_length(self.0) }
+    }
+
+    /// Returns the maximum number of bytes sealing adds to a message.
+    ///
+    /// This corresponds to [`EVP_AEAD_max_overhead`].
+    ///
+    /// [`EVP_AEAD_max_overhead`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_max_overhead
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn max_overhead(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_max_overhead(self.0) }
+    }
+
+    /// Returns the maximum length of tags, in bytes.
+    ///
+    /// This corresponds to [`EVP_AEAD_max_tag_len`].
+    ///
+    /// [`EVP_AEAD_max_tag_len`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_max_tag_len
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn max_tag_len(&self) -> usize {
+        unsafe { ffi::EVP_AEAD_max_tag_len(self.0) }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::EVP_AEAD_CTX;
+    fn drop = ffi::EVP_AEAD_CTX_free;
+
+    /// An AEAD algorithm initialized with a key.
+    ///
+    /// A context may be used to seal and open any number of messages, concurrently.
+    pub struct AeadCtx;
+}
+
+impl AeadCtx {
+    /// Creates a context using the default tag length of the algorithm.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_new`].
+    ///
+    /// [`EVP_AEAD_CTX_new`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_new
+    pub fn new(aead: Aead, key: &[u8]) -> Result<AeadCtx, ErrorStack> {
+        AeadCtx::new_with_tag_len(aead, key, ffi::EVP_AEAD_DEFAULT_TAG_LENGTH as usize)
+    }
+
+    /// Creates a context producing tags of `tag_len` bytes.
+    ///
+    /// Not all algorithms support truncated tags.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_new`].
+    ///
+    /// [`EVP_AEAD_CTX_new`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_new
+    pub fn new_with_tag_len(aead: Aead, key: &[u8], tag_len: usize) -> Result<AeadCtx, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::EVP_AEAD_CTX_new(
+                aead.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+                tag_len,
+            ))
+            .map(|p| AeadCtx::from_ptr(p))
+        }
+    }
+}
+
+impl AeadCtxRef {
+    /// Encrypts and authenticates `input`, and authenticates `ad`, writing the ciphertext
+    /// followed by the tag to `out`.
+    ///
+    /// `out` must be at least `input.len()` plus [`Aead::max_overhead`] bytes long. Returns the
+    /// number of bytes written.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_seal`].
+    ///
+    /// [`EVP_AEAD_CTX_seal`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_seal
+    pub fn seal(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        input: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, ErrorStack> {
+        unsafe {
+            let mut out_len = 0;
+            cvt(ffi::EVP_AEAD_CTX_seal(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut out_len,
+                out.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                input.as_ptr(),
+                input.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+            Ok(out_len)
+        }
+    }
+
+    /// Authenticates `input` and `ad`, and decrypts `input`, writing the plaintext to `out`.
+    ///
+    /// `input` is the ciphertext followed by the tag, as produced by [`seal`](Self::seal). `out`
+    /// must be at least as long as `input`. Returns the number of bytes written.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_open`].
+    ///
+    /// [`EVP_AEAD_CTX_open`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_open
+    pub fn open(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        input: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, ErrorStack> {
+        unsafe {
+            let mut out_len = 0;
+            cvt(ffi::EVP_AEAD_CTX_open(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut out_len,
+                out.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                input.as_ptr(),
+                input.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+            Ok(out_len)
+        }
+    }
+
+    /// Encrypts `in_out` in place, and writes the tag authenticating it and `ad` to `tag`.
+    ///
+    /// `tag` must be at least [`Aead::max_overhead`] bytes long. Returns the length of the tag.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_seal_scatter`].
+    ///
+    /// [`EVP_AEAD_CTX_seal_scatter`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_seal_scatter
+    pub fn seal_in_place_detached(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        in_out: &mut [u8],
+        tag: &mut [u8],
+    ) -> Result<usize, ErrorStack> {
+        unsafe {
+            let mut tag_len = 0;
+            cvt(ffi::EVP_AEAD_CTX_seal_scatter(
+                self.as_ptr(),
+                in_out.as_mut_ptr(),
+                tag.as_mut_ptr(),
+                &mut tag_len,
+                tag.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                in_out.as_ptr(),
+                in_out.len(),
+                ptr::null(),
+                0,
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+            Ok(tag_len)
+        }
+    }
+
+    /// Checks `tag` against `in_out` and `ad`, and decrypts `in_out` in place.
+    ///
+    /// On failure, the contents of `in_out` are unspecified.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_open_gather`].
+    ///
+    /// [`EVP_AEAD_CTX_open_gather`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_open_gather
+    pub fn open_in_place_detached(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        in_out: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_AEAD_CTX_open_gather(
+                self.as_ptr(),
+                in_out.as_mut_ptr(),
+                nonce.as_ptr(),
+                nonce.len(),
+                in_out.as_ptr(),
+                in_out.len(),
+                tag.as_ptr(),
+                tag.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Encrypts `input` to `out` and `extra_input` to the start of `tag`, followed by the tag
+    /// authenticating both and `ad`.
+    ///
+    /// This allows the end of a message to be sealed into the same buffer as the tag. `out` must
+    /// be exactly as long as `input`, and `tag` at least `extra_input.len()` plus
+    /// [`Aead::max_overhead`] bytes long. Returns the number of bytes written to `tag`.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_seal_scatter`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` and `input` have different lengths.
+    ///
+    /// [`EVP_AEAD_CTX_seal_scatter`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_seal_scatter
+    pub fn seal_scatter(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        input: &[u8],
+        extra_input: &[u8],
+        out: &mut [u8],
+        tag: &mut [u8],
+    ) -> Result<usize, ErrorStack> {
+        assert_eq!(out.len(), input.len());
+
+        unsafe {
+            let mut tag_len = 0;
+            cvt(ffi::EVP_AEAD_CTX_seal_scatter(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                tag.as_mut_ptr(),
+                &mut tag_len,
+                tag.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                input.as_ptr(),
+                input.len(),
+                extra_input.as_ptr(),
+                extra_input.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))?;
+            Ok(tag_len)
+        }
+    }
+
+    /// Checks `tag` against `input` and `ad`, and decrypts `input` to `out`.
+    ///
+    /// `out` must be exactly as long as `input`.
+    ///
+    /// This corresponds to [`EVP_AEAD_CTX_open_gather`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` and `input` have different lengths.
+    ///
+    /// [`EVP_AEAD_CTX_open_gather`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/aead.h.html#EVP_AEAD_CTX_open_gather
+    pub fn open_gather(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        input: &[u8],
+        tag: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), ErrorStack> {
+        assert_eq!(out.len(), input.len());
+
+        unsafe {
+            cvt(ffi::EVP_AEAD_CTX_open_gather(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                nonce.as_ptr(),
+                nonce.len(),
+                input.as_ptr(),
+                input.len(),
+                tag.as_ptr(),
+                tag.len(),
+                ad.as_ptr(),
+                ad.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The GCM specification, test cases 1 and 2.
+    #[test]
+    fn aes_128_gcm() {
+        let aead = Aead::aes_128_gcm();
+        assert_eq!(aead.key_len(), 16);
+        assert_eq!(aead.nonce_len(), 12);
+        assert_eq!(aead.max_overhead(), 16);
+
+        let ctx = AeadCtx::new(aead, &[0; 16]).unwrap();
+        let nonce = [0; 12];
+
+        let mut out = [0; 16];
+        assert_eq!(ctx.seal(&nonce, &[], &[], &mut out).unwrap(), 16);
+        assert_eq!(hex::encode(out), "58e2fccefa7e3061367f1d57a4e7455a");
+
+        let mut out = [0; 32];
+        assert_eq!(ctx.seal(&nonce, &[], &[0; 16], &mut out).unwrap(), 32);
+        assert_eq!(
+            hex::encode(out),
+            "0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf"
+        );
+
+        let mut plaintext = [0xff; 32];
+        assert_eq!(ctx.open(&nonce, &[], &out, &mut plaintext).unwrap(), 16);
+        assert_eq!(plaintext[..16], [0; 16]);
+
+        out[0] ^= 1;
+        assert!(ctx.open(&nonce, &[], &out, &mut plaintext).is_err());
+    }
+
+    #[test]
+    fn in_place_detached() {
+        for aead in [
+            Aead::aes_128_gcm(),
+            Aead::aes_256_gcm(),
+            Aead::aes_128_gcm_siv(),
+            Aead::aes_256_gcm_siv(),
+            Aead::chacha20_poly1305(),
+            Aead::xchacha20_poly1305(),
+        ] {
+            let ctx = AeadCtx::new(aead, &vec![7; aead.key_len()]).unwrap();
+            let nonce = vec![9; aead.nonce_len()];
+            let message = b"a message to seal in place";
+
+            let mut in_out = message.to_vec();
+            let mut tag = vec![0; aead.max_overhead()];
+            let tag_len = ctx
+                .seal_in_place_detached(&nonce, b"ad", &mut in_out, &mut tag)
+                .unwrap();
+            tag.truncate(tag_len);
+            assert_ne!(&in_out[..], &message[..]);
+
+            let mut out = vec![0; message.len() + aead.max_overhead()];
+            let len = ctx.seal(&nonce, b"ad", message, &mut out).unwrap();
+            assert_eq!(out[..len], [&in_out[..], &tag[..]].concat());
+
+            assert!(ctx
+                .open_in_place_detached(&nonce, b"other", &mut in_out.clone(), &tag)
+                .is_err());
+            ctx.open_in_place_detached(&nonce, b"ad", &mut in_out, &tag)
+                .unwrap();
+            assert_eq!(&in_out[..], &message[..]);
+        }
+    }
+
+    #[test]
+    fn scatter_gather() {
+        let aead = Aead::chacha20_poly1305();
+        let ctx = AeadCtx::new(aead, &[1; 32]).unwrap();
+        let nonce = [2; 12];
+
+        let mut out = [0; 8];
+        let mut tag = [0; 4 + 16];
+        let tag_len = ctx
+            .seal_scatter(&nonce, b"", b"12345678", b"9abc", &mut out, &mut tag)
+            .unwrap();
+        assert_eq!(tag_len, 20);
+
+        let mut sealed = vec![0; 12 + 16];
+        ctx.seal(&nonce, b"", b"123456789abc", &mut sealed).unwrap();
+        assert_eq!(sealed, [&out[..], &tag[..]].concat());
+
+        let mut plaintext = [0; 12];
+        ctx.open_gather(&nonce, b"", &sealed[..12], &sealed[12..], &mut plaintext)
+            .unwrap();
+        assert_eq!(&plaintext, b"123456789abc");
+    }
+}
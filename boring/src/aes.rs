@@ -37,6 +37,23 @@
 //! assert_eq!(&orig_key[..], &key_to_wrap[..]);
 //! ```
 //!
+//! ## Key wrapping with padding
+//! ```rust
+//! use boring::aes::{AesKey, unwrap_key_padded, wrap_key_padded};
+//!
+//! let kek = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F";
+//! let key_to_wrap = b"a key of any length";
+//!
+//! let enc_key = AesKey::new_encrypt(kek).unwrap();
+//! let mut ciphertext = [0u8; 32];
+//! let len = wrap_key_padded(&enc_key, &mut ciphertext, key_to_wrap).unwrap();
+//! let dec_key = AesKey::new_decrypt(kek).unwrap();
+//! let mut orig_key = [0u8; 24];
+//! let orig_len = unwrap_key_padded(&dec_key, &mut orig_key, &ciphertext[..len]).unwrap();
+//!
+//! assert_eq!(&orig_key[..orig_len], &key_to_wrap[..]);
+//! ```
+//!
 use crate::ffi;
 use libc::{c_int, c_uint, size_t};
 use std::mem::MaybeUninit;
@@ -177,6 +194,62 @@ pub fn unwrap_key(
     }
 }
 
+/// Wrap a key of any length, according to [RFC 5649](https://tools.ietf.org/html/rfc5649)
+///
+/// * `key`: The key-encrypting-key to use. Must be a encrypting key
+/// * `out`: The output buffer to store the ciphertext
+/// * `in_`: The input buffer, storing the key to be wrapped
+///
+/// `out` must be at least as long as `in_` rounded up to a multiple of 8, plus 8 bytes.
+///
+/// Returns the number of bytes written into `out`
+pub fn wrap_key_padded(key: &AesKey, out: &mut [u8], in_: &[u8]) -> Result<usize, KeyError> {
+    unsafe {
+        let mut written = 0;
+        let r = ffi::AES_wrap_key_padded(
+            &key.0,
+            out.as_mut_ptr(),
+            &mut written,
+            out.len(),
+            in_.as_ptr(),
+            in_.len(),
+        );
+        if r == 1 {
+            Ok(written)
+        } else {
+            Err(KeyError(()))
+        }
+    }
+}
+
+/// Unwrap a key, according to [RFC 5649](https://tools.ietf.org/html/rfc5649)
+///
+/// * `key`: The key-encrypting-key to decrypt the wrapped key. Must be a decrypting key
+/// * `out`: The buffer to write the unwrapped key to
+/// * `in_`: The input ciphertext
+///
+/// `out` must be at least 8 bytes shorter than `in_`.
+///
+/// Returns the number of bytes written into `out`
+pub fn unwrap_key_padded(key: &AesKey, out: &mut [u8], in_: &[u8]) -> Result<usize, KeyError> {
+    unsafe {
+        let mut written = 0;
+        let r = ffi::AES_unwrap_key_padded(
+            &key.0,
+            out.as_mut_ptr(),
+            &mut written,
+            out.len(),
+            in_.as_ptr(),
+            in_.len(),
+        );
+        if r == 1 {
+            Ok(written)
+        } else {
+            Err(KeyError(()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use hex::FromHex;
@@ -207,4 +280,38 @@ mod test {
         );
         assert_eq!(&unwrapped[..], &key_data[..]);
     }
+
+    // from the RFC https://tools.ietf.org/html/rfc5649#section-6
+    #[test]
+    fn test_wrap_unwrap_padded() {
+        let raw_key = Vec::from_hex("5840DF6E29B02AF1AB493B705BF16EA1AE8338F4DCC176A8").unwrap();
+        let enc_key = AesKey::new_encrypt(&raw_key).unwrap();
+        let dec_key = AesKey::new_decrypt(&raw_key).unwrap();
+
+        for (key_data, expected_ciphertext) in [
+            (
+                "C37B7E6492584340BED12207808941155068F738",
+                "138BDEAA9B8FA7FC61F97742E72248EE5AE6AE5360D1AE6A5F54F373FA543B6A",
+            ),
+            ("466F7250617369", "AFBEB0F07DFBF5419200F2CCB50BB24F"),
+        ] {
+            let key_data = Vec::from_hex(key_data).unwrap();
+            let expected_ciphertext = Vec::from_hex(expected_ciphertext).unwrap();
+
+            let mut wrapped = [0; 40];
+            let wrapped_len = wrap_key_padded(&enc_key, &mut wrapped, &key_data).unwrap();
+            let wrapped = &mut wrapped[..wrapped_len];
+            assert_eq!(&wrapped[..], &expected_ciphertext[..]);
+
+            let mut unwrapped = [0; 32];
+            let len = unwrap_key_padded(&dec_key, &mut unwrapped, wrapped).unwrap();
+            assert_eq!(&unwrapped[..len], &key_data[..]);
+
+            wrapped[0] ^= 1;
+            assert!(unwrap_key_padded(&dec_key, &mut unwrapped, wrapped).is_err());
+        }
+
+        let mut wrapped = [0; 16];
+        assert!(wrap_key_padded(&enc_key, &mut wrapped, &[0; 9]).is_err());
+    }
 }
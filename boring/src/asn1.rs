@@ -33,6 +33,7 @@ use std::fmt;
 use std::ptr;
 use std::slice;
 use std::str;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::bio::MemBio;
 use crate::bn::{BigNum, BigNumRef};
@@ -222,8 +223,46 @@ impl Asn1TimeRef {
 
         Ok(Ordering::Equal)
     }
+
+    /// Returns the time in seconds since the UNIX epoch, which is negative for earlier times.
+    pub fn to_unix(&self) -> Result<i64, ErrorStack> {
+        let epoch = Asn1Time::from_unix(0)?;
+        let diff = epoch.diff(self)?;
+
+        Ok(i64::from(diff.days) * SECS_PER_DAY + i64::from(diff.secs))
+    }
+
+    /// Converts the time to a `SystemTime`.
+    pub fn to_system_time(&self) -> Result<SystemTime, ErrorStack> {
+        let secs = self.to_unix()?;
+        let offset = Duration::from_secs(secs.unsigned_abs());
+
+        Ok(if secs < 0 {
+            UNIX_EPOCH - offset
+        } else {
+            UNIX_EPOCH + offset
+        })
+    }
+
+    /// Converts the time to a `chrono` UTC date and time.
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> Result<chrono::DateTime<chrono::Utc>, ErrorStack> {
+        let secs = self.to_unix()?;
+
+        Ok(chrono::DateTime::from_timestamp(secs, 0).expect("BUG: ASN.1 time out of range"))
+    }
+
+    /// Converts the time to a `time` date and time, in UTC.
+    #[cfg(feature = "time")]
+    pub fn to_offset_date_time(&self) -> Result<time::OffsetDateTime, ErrorStack> {
+        let secs = self.to_unix()?;
+
+        Ok(time::OffsetDateTime::from_unix_timestamp(secs).expect("BUG: ASN.1 time out of range"))
+    }
 }
 
+const SECS_PER_DAY: i64 = 60 * 60 * 24;
+
 impl PartialEq for Asn1TimeRef {
     fn eq(&self, other: &Asn1TimeRef) -> bool {
         self.diff(other)
@@ -322,6 +361,55 @@ impl Asn1Time {
         }
     }
 
+    /// Creates a new time from a number of seconds since the UNIX epoch, independently of the
+    /// size of `time_t`.
+    ///
+    /// This corresponds to [`ASN1_TIME_adj`].
+    ///
+    /// [`ASN1_TIME_adj`]: https://www.openssl.org/docs/man1.1.0/crypto/ASN1_TIME_adj.html
+    fn from_posix(secs: i64) -> Result<Asn1Time, ErrorStack> {
+        ffi::init();
+
+        // Out of range days are clamped, which BoringSSL then rejects.
+        let days = secs.div_euclid(SECS_PER_DAY);
+        let days = c_int::try_from(days).unwrap_or(if days < 0 { c_int::MIN } else { c_int::MAX });
+        let secs = secs.rem_euclid(SECS_PER_DAY) as c_long;
+
+        unsafe {
+            let handle = cvt_p(ffi::ASN1_TIME_adj(ptr::null_mut(), 0, days, secs))?;
+            Ok(Asn1Time::from_ptr(handle))
+        }
+    }
+
+    /// Creates a new time from a `SystemTime`, truncated to the second.
+    pub fn from_system_time(time: SystemTime) -> Result<Asn1Time, ErrorStack> {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_secs()).unwrap_or(i64::MAX),
+            Err(e) => {
+                let until = e.duration();
+                let secs = i64::try_from(until.as_secs()).unwrap_or(i64::MAX);
+                // Earlier times are rounded down to the previous second.
+                -secs - i64::from(until.subsec_nanos() > 0)
+            }
+        };
+
+        Asn1Time::from_posix(secs)
+    }
+
+    /// Creates a new time from a `chrono` date and time, truncated to the second.
+    #[cfg(feature = "chrono")]
+    pub fn from_chrono<Tz: chrono::TimeZone>(
+        time: &chrono::DateTime<Tz>,
+    ) -> Result<Asn1Time, ErrorStack> {
+        Asn1Time::from_posix(time.timestamp())
+    }
+
+    /// Creates a new time from a `time` date and time, truncated to the second.
+    #[cfg(feature = "time")]
+    pub fn from_offset_date_time(time: time::OffsetDateTime) -> Result<Asn1Time, ErrorStack> {
+        Asn1Time::from_posix(time.unix_timestamp())
+    }
+
     /// Creates a new time corresponding to the specified ASN1 time string.
     ///
     /// This corresponds to [`ASN1_TIME_set_string`].
@@ -423,6 +511,47 @@ impl Asn1StringRef {
         unsafe { slice::from_raw_parts(ASN1_STRING_get0_data(self.as_ptr()), self.len()) }
     }
 
+    /// Returns the type of the string.
+    ///
+    /// This corresponds to [`ASN1_STRING_type`].
+    ///
+    /// [`ASN1_STRING_type`]: https://www.openssl.org/docs/man1.1.0/crypto/ASN1_STRING_type.html
+    pub fn type_(&self) -> Asn1Type {
+        unsafe { Asn1Type(ffi::ASN1_STRING_type(self.as_ptr())) }
+    }
+
+    /// Decodes the string according to its type.
+    ///
+    /// Unlike [`as_utf8`], this keeps embedded NUL characters, and checks that the contents are
+    /// valid for the type: UTF-8 for `UTF8String`, UCS-2 for `BMPString`, UCS-4 for
+    /// `UniversalString` and ASCII for `IA5String`, `PrintableString`, `NumericString` and
+    /// `VisibleString`. `T61String` is decoded as Latin-1, as BoringSSL does.
+    ///
+    /// Returns `None` if the contents are invalid, or if the type is not a character string.
+    ///
+    /// [`as_utf8`]: struct.Asn1StringRef.html#method.as_utf8
+    pub fn decode(&self) -> Option<String> {
+        let bytes = self.as_slice();
+
+        match self.type_() {
+            Asn1Type::UTF8STRING => String::from_utf8(bytes.to_vec()).ok(),
+            Asn1Type::BMPSTRING => decode_ucs(bytes, 2),
+            Asn1Type::UNIVERSALSTRING => decode_ucs(bytes, 4),
+            Asn1Type::IA5STRING
+            | Asn1Type::PRINTABLESTRING
+            | Asn1Type::NUMERICSTRING
+            | Asn1Type::VISIBLESTRING => {
+                if bytes.is_ascii() {
+                    Some(bytes.iter().map(|&b| char::from(b)).collect())
+                } else {
+                    None
+                }
+            }
+            Asn1Type::T61STRING => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+            _ => None,
+        }
+    }
+
     /// Returns the number of bytes in the string.
     pub fn len(&self) -> usize {
         unsafe { ffi::ASN1_STRING_length(self.as_ptr()) as usize }
@@ -434,6 +563,21 @@ impl Asn1StringRef {
     }
 }
 
+/// Decodes big-endian code points of `width` bytes.
+fn decode_ucs(bytes: &[u8], width: usize) -> Option<String> {
+    if bytes.len() % width != 0 {
+        return None;
+    }
+
+    bytes
+        .chunks(width)
+        .map(|unit| {
+            let code_point = unit.iter().fold(0, |acc, &b| (acc << 8) | u32::from(b));
+            char::from_u32(code_point)
+        })
+        .collect()
+}
+
 impl fmt::Debug for Asn1StringRef {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self.as_utf8() {
@@ -470,6 +614,27 @@ impl Asn1Integer {
     pub fn from_bn(bn: &BigNumRef) -> Result<Self, ErrorStack> {
         bn.to_asn1_integer()
     }
+
+    /// Creates an integer from an unsigned 64-bit value.
+    ///
+    /// This corresponds to [`ASN1_INTEGER_set_uint64`].
+    ///
+    /// [`ASN1_INTEGER_set_uint64`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/asn1.h.html#ASN1_INTEGER_set_uint64
+    pub fn from_u64(value: u64) -> Result<Self, ErrorStack> {
+        ffi::init();
+
+        unsafe {
+            let integer = Asn1Integer::from_ptr(cvt_p(ffi::ASN1_INTEGER_new())?);
+            cvt(ffi::ASN1_INTEGER_set_uint64(integer.as_ptr(), value))?;
+            Ok(integer)
+        }
+    }
+
+    /// Creates a non-negative integer from its big-endian bytes, such as a certificate serial
+    /// number.
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self, ErrorStack> {
+        BigNum::from_slice(bytes)?.to_asn1_integer()
+    }
 }
 
 impl Asn1IntegerRef {
@@ -495,6 +660,36 @@ impl Asn1IntegerRef {
         }
     }
 
+    /// Returns the value of the integer as an unsigned 64-bit value.
+    ///
+    /// Fails if the integer is negative or does not fit.
+    ///
+    /// This corresponds to [`ASN1_INTEGER_get_uint64`].
+    ///
+    /// [`ASN1_INTEGER_get_uint64`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/asn1.h.html#ASN1_INTEGER_get_uint64
+    pub fn to_u64(&self) -> Result<u64, ErrorStack> {
+        let mut value = 0;
+        unsafe {
+            cvt(ffi::ASN1_INTEGER_get_uint64(&mut value, self.as_ptr()))?;
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the big-endian bytes of the absolute value of the integer, without leading zeros.
+    ///
+    /// Zero yields an empty vector. See [`is_negative`] for the sign.
+    ///
+    /// [`is_negative`]: struct.Asn1IntegerRef.html#method.is_negative
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>, ErrorStack> {
+        Ok(self.to_bn()?.to_vec())
+    }
+
+    /// Returns `true` if the integer is negative.
+    pub fn is_negative(&self) -> bool {
+        unsafe { ffi::ASN1_STRING_type(self.as_ptr()) == ffi::V_ASN1_NEG_INTEGER as c_int }
+    }
+
     /// Sets the ASN.1 value to the value of a signed 32-bit integer, for larger numbers
     /// see [`bn`].
     ///
@@ -583,6 +778,39 @@ impl Asn1ObjectRef {
     pub fn nid(&self) -> Nid {
         unsafe { Nid::from_raw(ffi::OBJ_obj2nid(self.as_ptr())) }
     }
+
+    /// Returns the dotted decimal representation of the OID, such as `2.5.29.15`.
+    ///
+    /// Unlike the `Display` implementation, this never uses the name of the object, including
+    /// one registered with [`Nid::create`].
+    ///
+    /// This corresponds to [`OBJ_obj2txt`] with `always_return_oid` set.
+    ///
+    /// [`OBJ_obj2txt`]: https://www.openssl.org/docs/man1.1.0/crypto/OBJ_obj2txt.html
+    pub fn to_oid_string(&self) -> String {
+        unsafe {
+            let len = ffi::OBJ_obj2txt(ptr::null_mut(), 0, self.as_ptr(), 1);
+            let mut buf = vec![0u8; len.max(0) as usize + 1];
+            let len = ffi::OBJ_obj2txt(
+                buf.as_mut_ptr() as *mut _,
+                buf.len() as c_int,
+                self.as_ptr(),
+                1,
+            );
+            buf.truncate(len.max(0) as usize);
+            String::from_utf8(buf).unwrap()
+        }
+    }
+
+    to_der! {
+        /// Serializes the object identifier into its DER encoding.
+        ///
+        /// This corresponds to [`i2d_ASN1_OBJECT`].
+        ///
+        /// [`i2d_ASN1_OBJECT`]: https://www.openssl.org/docs/man1.1.0/crypto/i2d_ASN1_OBJECT.html
+        to_der,
+        ffi::i2d_ASN1_OBJECT
+    }
 }
 
 impl fmt::Display for Asn1ObjectRef {
@@ -690,6 +918,126 @@ mod tests {
         assert!(c_ref < a_ref);
     }
 
+    #[test]
+    fn time_unix() {
+        let time = Asn1Time::from_str("20240229123456Z").unwrap();
+        assert_eq!(time.to_unix().unwrap(), 1709210096);
+
+        let before_epoch = Asn1Time::from_str("19691231235959Z").unwrap();
+        assert_eq!(before_epoch.to_unix().unwrap(), -1);
+    }
+
+    #[test]
+    fn time_system_time() {
+        let system_time = UNIX_EPOCH + Duration::new(1709210096, 500_000_000);
+        let time = Asn1Time::from_system_time(system_time).unwrap();
+        assert_eq!(time, Asn1Time::from_str("20240229123456Z").unwrap());
+        assert_eq!(
+            time.to_system_time().unwrap(),
+            UNIX_EPOCH + Duration::from_secs(1709210096)
+        );
+
+        let system_time = UNIX_EPOCH - Duration::from_millis(500);
+        let time = Asn1Time::from_system_time(system_time).unwrap();
+        assert_eq!(time.to_unix().unwrap(), -1);
+        assert_eq!(
+            time.to_system_time().unwrap(),
+            UNIX_EPOCH - Duration::from_secs(1)
+        );
+
+        let far_future = UNIX_EPOCH + Duration::from_secs(1 << 40);
+        assert!(Asn1Time::from_system_time(far_future).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn time_chrono() {
+        let date_time = chrono::DateTime::from_timestamp(1709210096, 0).unwrap();
+        let time = Asn1Time::from_chrono(&date_time).unwrap();
+        assert_eq!(time, Asn1Time::from_str("20240229123456Z").unwrap());
+        assert_eq!(time.to_chrono().unwrap(), date_time);
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn time_offset_date_time() {
+        let date_time = time::OffsetDateTime::from_unix_timestamp(1709210096).unwrap();
+        let time = Asn1Time::from_offset_date_time(date_time).unwrap();
+        assert_eq!(time, Asn1Time::from_str("20240229123456Z").unwrap());
+        assert_eq!(time.to_offset_date_time().unwrap(), date_time);
+    }
+
+    #[test]
+    fn integer_u64() {
+        let integer = Asn1Integer::from_u64(u64::MAX).unwrap();
+        assert_eq!(integer.to_u64().unwrap(), u64::MAX);
+        assert_eq!(integer.to_be_bytes().unwrap(), [0xff; 8]);
+        assert!(!integer.is_negative());
+
+        let negative = BigNum::from_dec_str("-1")
+            .unwrap()
+            .to_asn1_integer()
+            .unwrap();
+        assert!(negative.is_negative());
+        assert!(negative.to_u64().is_err());
+        assert_eq!(negative.to_be_bytes().unwrap(), [1]);
+    }
+
+    #[test]
+    fn integer_be_bytes() {
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        let integer = Asn1Integer::from_be_bytes(&bytes).unwrap();
+        assert_eq!(integer.to_be_bytes().unwrap(), bytes);
+        assert!(integer.to_u64().is_err());
+
+        let zero = Asn1Integer::from_be_bytes(&[0, 0]).unwrap();
+        assert_eq!(zero.to_u64().unwrap(), 0);
+        assert!(zero.to_be_bytes().unwrap().is_empty());
+    }
+
+    fn string(type_: Asn1Type, data: &[u8]) -> Asn1String {
+        unsafe {
+            let string = Asn1String::from_ptr(ffi::ASN1_STRING_type_new(type_.as_raw()));
+            ffi::ASN1_STRING_set(string.as_ptr(), data.as_ptr() as *const _, data.len() as _);
+            string
+        }
+    }
+
+    #[test]
+    fn string_decode() {
+        let utf8 = string(Asn1Type::UTF8STRING, "h\u{e9}llo\0!".as_bytes());
+        assert_eq!(utf8.type_(), Asn1Type::UTF8STRING);
+        assert_eq!(utf8.decode().unwrap(), "h\u{e9}llo\0!");
+
+        let bmp = string(Asn1Type::BMPSTRING, b"\x00h\x00\xe9");
+        assert_eq!(bmp.decode().unwrap(), "h\u{e9}");
+        assert!(string(Asn1Type::BMPSTRING, b"\x00h\x00").decode().is_none());
+        assert!(string(Asn1Type::BMPSTRING, b"\xd8\x00").decode().is_none());
+
+        let universal = string(Asn1Type::UNIVERSALSTRING, b"\x00\x01\xf6\x00");
+        assert_eq!(universal.decode().unwrap(), "\u{1f600}");
+
+        assert_eq!(
+            string(Asn1Type::IA5STRING, b"a@b.example")
+                .decode()
+                .unwrap(),
+            "a@b.example"
+        );
+        assert!(string(Asn1Type::IA5STRING, b"\xe9").decode().is_none());
+        assert_eq!(
+            string(Asn1Type::T61STRING, b"\xe9").decode().unwrap(),
+            "\u{e9}"
+        );
+        assert!(string(Asn1Type::OCTET_STRING, b"abc").decode().is_none());
+    }
+
+    #[test]
+    fn object_to_oid_string() {
+        let object = Asn1Object::from_str("2.5.29.15").unwrap();
+        assert_eq!(object.to_string(), "X509v3 Key Usage");
+        assert_eq!(object.to_oid_string(), "2.5.29.15");
+    }
+
     #[test]
     fn object_from_str() {
         let object = Asn1Object::from_str("2.16.840.1.101.3.4.2.1").unwrap();
@@ -24,11 +24,11 @@
 //! [`BIGNUM`]: https://wiki.openssl.org/index.php/Manual:Bn_internal(3)
 use crate::ffi;
 use foreign_types::{ForeignType, ForeignTypeRef};
-use libc::{c_int, size_t};
+use libc::{c_int, c_void, size_t};
 use std::cmp::Ordering;
 use std::ffi::CString;
 use std::ops::{Add, Deref, Div, Mul, Neg, Rem, Shl, Shr, Sub};
-use std::{fmt, ptr};
+use std::{fmt, mem, ptr};
 
 use crate::asn1::Asn1Integer;
 use crate::error::ErrorStack;
@@ -81,6 +81,33 @@ impl BigNumContext {
     }
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::BN_MONT_CTX;
+    fn drop = ffi::BN_MONT_CTX_free;
+
+    /// Precomputed values for Montgomery multiplication modulo a fixed odd modulus.
+    ///
+    /// Creating a context is relatively expensive; it can be reused across any number of
+    /// [`BigNumRef::mod_exp_mont`] and [`BigNumRef::mod_exp_mont_consttime`] calls with the same
+    /// modulus.
+    pub struct BigNumMontContext;
+}
+
+impl BigNumMontContext {
+    /// Returns a new `BigNumMontContext` for the odd modulus `m`.
+    ///
+    /// This corresponds to [`BN_MONT_CTX_new_for_modulus`].
+    ///
+    /// [`BN_MONT_CTX_new_for_modulus`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bn.h.html#BN_MONT_CTX_new_for_modulus
+    pub fn new(m: &BigNumRef, ctx: &mut BigNumContextRef) -> Result<BigNumMontContext, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::BN_MONT_CTX_new_for_modulus(m.as_ptr(), ctx.as_ptr()))
+                .map(|p| BigNumMontContext::from_ptr(p))
+        }
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::BIGNUM;
     fn drop = ffi::BN_free;
@@ -466,6 +493,56 @@ impl BigNumRef {
         }
     }
 
+    /// Generates a prime number, placing it in `self`, reporting progress to `callback`.
+    ///
+    /// This behaves like [`generate_prime`], but calls `callback` with an event and a counter
+    /// as candidates are generated and tested. The event is `0` when a candidate is generated and
+    /// `1` after each primality test round. Returning `false` from `callback` aborts the
+    /// generation with an error.
+    ///
+    /// [`generate_prime`]: #method.generate_prime
+    ///
+    /// OpenSSL documentation at [`BN_generate_prime_ex`]
+    ///
+    /// [`BN_generate_prime_ex`]: https://www.openssl.org/docs/man1.1.0/crypto/BN_generate_prime_ex.html
+    pub fn generate_prime_with_callback<F>(
+        &mut self,
+        bits: i32,
+        safe: bool,
+        add: Option<&BigNumRef>,
+        rem: Option<&BigNumRef>,
+        mut callback: F,
+    ) -> Result<(), ErrorStack>
+    where
+        F: FnMut(i32, i32) -> bool,
+    {
+        unsafe extern "C" fn call<F>(event: c_int, n: c_int, cb: *mut ffi::BN_GENCB) -> c_int
+        where
+            F: FnMut(i32, i32) -> bool,
+        {
+            let callback = &mut *((*cb).arg as *mut F);
+            callback(event, n) as c_int
+        }
+
+        unsafe {
+            let mut cb: ffi::BN_GENCB = mem::zeroed();
+            ffi::BN_GENCB_set(
+                &mut cb,
+                Some(call::<F>),
+                &mut callback as *mut F as *mut c_void,
+            );
+            cvt(ffi::BN_generate_prime_ex(
+                self.as_ptr(),
+                bits as c_int,
+                safe as c_int,
+                add.map(|n| n.as_ptr()).unwrap_or(ptr::null_mut()),
+                rem.map(|n| n.as_ptr()).unwrap_or(ptr::null_mut()),
+                &mut cb,
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Places the result of `a * b` in `self`.
     /// [`core::ops::Mul`] is also implemented for `BigNumRef`.
     ///
@@ -734,7 +811,74 @@ impl BigNumRef {
         }
     }
 
+    /// Places the result of `a^p mod m` in `self`, using Montgomery multiplication.
+    ///
+    /// `m` must be odd. If `mont` is given, it must have been created for `m`, and saves
+    /// recomputing it on each call.
+    ///
+    /// This corresponds to [`BN_mod_exp_mont`].
+    ///
+    /// [`BN_mod_exp_mont`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bn.h.html#BN_mod_exp_mont
+    pub fn mod_exp_mont(
+        &mut self,
+        a: &BigNumRef,
+        p: &BigNumRef,
+        m: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+        mont: Option<&BigNumMontContextRef>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::BN_mod_exp_mont(
+                self.as_ptr(),
+                a.as_ptr(),
+                p.as_ptr(),
+                m.as_ptr(),
+                ctx.as_ptr(),
+                mont.map_or(ptr::null(), |mont| mont.as_ptr()),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Places the result of `a^p mod m` in `self`, in time independent of the value of `p`.
+    ///
+    /// This should be used when `p` is secret. `m` must be odd and `a` must be less than `m`.
+    /// If `mont` is given, it must have been created for `m`.
+    ///
+    /// BoringSSL does not support OpenSSL's `BN_FLG_CONSTTIME` flag; constant-time operations
+    /// are instead selected by calling the dedicated functions.
+    ///
+    /// This corresponds to [`BN_mod_exp_mont_consttime`].
+    ///
+    /// [`BN_mod_exp_mont_consttime`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bn.h.html#BN_mod_exp_mont_consttime
+    pub fn mod_exp_mont_consttime(
+        &mut self,
+        a: &BigNumRef,
+        p: &BigNumRef,
+        m: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+        mont: Option<&BigNumMontContextRef>,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::BN_mod_exp_mont_consttime(
+                self.as_ptr(),
+                a.as_ptr(),
+                p.as_ptr(),
+                m.as_ptr(),
+                ctx.as_ptr(),
+                mont.map_or(ptr::null(), |mont| mont.as_ptr()),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Places the inverse of `a` modulo `n` in `self`.
+    ///
+    /// Returns an error if `a` has no inverse modulo `n`.
+    ///
+    /// OpenSSL documentation at [`BN_mod_inverse`]
+    ///
+    /// [`BN_mod_inverse`]: https://www.openssl.org/docs/man1.1.0/crypto/BN_mod_inverse.html
     pub fn mod_inverse(
         &mut self,
         a: &BigNumRef,
@@ -1253,7 +1397,7 @@ impl Neg for BigNum {
 
 #[cfg(test)]
 mod tests {
-    use crate::bn::{BigNum, BigNumContext};
+    use crate::bn::{BigNum, BigNumContext, BigNumMontContext};
 
     #[test]
     fn test_to_from_slice() {
@@ -1305,4 +1449,70 @@ mod tests {
         assert!(p.is_prime(100, &mut ctx).unwrap());
         assert!(p.is_prime_fasttest(100, &mut ctx, true).unwrap());
     }
+
+    #[test]
+    fn test_mod_exp_mont() {
+        let mut ctx = BigNumContext::new().unwrap();
+        // 2^127 - 1
+        let m = BigNum::from_dec_str("170141183460469231731687303715884105727").unwrap();
+        let a = BigNum::from_u32(0x1234_5678).unwrap();
+        let p = BigNum::from_dec_str("98765432109876543210").unwrap();
+
+        let mut expected = BigNum::new().unwrap();
+        expected.mod_exp(&a, &p, &m, &mut ctx).unwrap();
+
+        let mont = BigNumMontContext::new(&m, &mut ctx).unwrap();
+        let mut r = BigNum::new().unwrap();
+        r.mod_exp_mont(&a, &p, &m, &mut ctx, None).unwrap();
+        assert_eq!(r, expected);
+        r.mod_exp_mont(&a, &p, &m, &mut ctx, Some(&mont)).unwrap();
+        assert_eq!(r, expected);
+        r.mod_exp_mont_consttime(&a, &p, &m, &mut ctx, Some(&mont))
+            .unwrap();
+        assert_eq!(r, expected);
+
+        // Fermat's little theorem
+        let mut p = m.to_owned().unwrap();
+        p.sub_word(1).unwrap();
+        r.mod_exp_mont_consttime(&a, &p, &m, &mut ctx, None)
+            .unwrap();
+        assert_eq!(r, BigNum::from_u32(1).unwrap());
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        let mut ctx = BigNumContext::new().unwrap();
+        let n = BigNum::from_u32(1_000_003).unwrap();
+        let a = BigNum::from_u32(12345).unwrap();
+
+        let mut inverse = BigNum::new().unwrap();
+        inverse.mod_inverse(&a, &n, &mut ctx).unwrap();
+        let mut product = BigNum::new().unwrap();
+        product.mod_mul(&a, &inverse, &n, &mut ctx).unwrap();
+        assert_eq!(product, BigNum::from_u32(1).unwrap());
+
+        let n = BigNum::from_u32(10).unwrap();
+        let a = BigNum::from_u32(4).unwrap();
+        assert!(inverse.mod_inverse(&a, &n, &mut ctx).is_err());
+    }
+
+    #[test]
+    fn test_generate_prime_with_callback() {
+        let mut events = vec![];
+        let mut p = BigNum::new().unwrap();
+        p.generate_prime_with_callback(256, false, None, None, |event, _| {
+            events.push(event);
+            true
+        })
+        .unwrap();
+        assert_eq!(p.num_bits(), 256);
+        assert!(events.contains(&0));
+
+        let mut ctx = BigNumContext::new().unwrap();
+        assert!(p.is_prime(64, &mut ctx).unwrap());
+
+        assert!(p
+            .generate_prime_with_callback(256, false, None, None, |_, _| false)
+            .is_err());
+    }
 }
@@ -0,0 +1,222 @@
+//! AES-CMAC, as specified in [RFC 4493].
+//!
+//! [RFC 4493]: https://datatracker.ietf.org/doc/html/rfc4493
+//!
+//! # Examples
+//!
+//! ```rust
+//! use boring::cmac::{self, Cmac};
+//!
+//! let key = [0x42; 16];
+//!
+//! let mut cmac = Cmac::new(&key).unwrap();
+//! cmac.update(b"hello, ").unwrap();
+//! cmac.update(b"world!").unwrap();
+//! let tag = cmac.finish().unwrap();
+//!
+//! assert_eq!(tag, cmac::aes_cmac(&key, b"hello, world!").unwrap());
+//! ```
+use crate::ffi;
+use std::io::{self, Write};
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::symm::Cipher;
+use crate::{cvt, cvt_p};
+
+/// The length of an AES-CMAC tag.
+pub const TAG_LEN: usize = 16;
+
+/// Computes the AES-CMAC tag of `data`.
+///
+/// `key` must be 16 or 32 bytes long, selecting AES-128 or AES-256.
+///
+/// This corresponds to [`AES_CMAC`].
+///
+/// [`AES_CMAC`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/cmac.h.html#AES_CMAC
+pub fn aes_cmac(key: &[u8], data: &[u8]) -> Result<[u8; TAG_LEN], ErrorStack> {
+    unsafe {
+        ffi::init();
+        let mut tag = [0; TAG_LEN];
+        cvt(ffi::AES_CMAC(
+            tag.as_mut_ptr(),
+            key.as_ptr(),
+            key.len(),
+            data.as_ptr(),
+            data.len(),
+        ))?;
+        Ok(tag)
+    }
+}
+
+fn invalid_key_length() -> ErrorStack {
+    unsafe {
+        ffi::ERR_put_error(
+            ffi::ERR_LIB_CIPHER.0 as _,
+            0,
+            ffi::CIPHER_R_INVALID_KEY_LENGTH as _,
+            concat!(file!(), "\0").as_ptr() as *const _,
+            line!() as _,
+        );
+    }
+    ErrorStack::get()
+}
+
+/// A type which computes AES-CMAC tags incrementally.
+pub struct Cmac(*mut ffi::CMAC_CTX);
+
+unsafe impl Sync for Cmac {}
+unsafe impl Send for Cmac {}
+
+impl Drop for Cmac {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::CMAC_CTX_free(self.0);
+        }
+    }
+}
+
+impl Cmac {
+    /// Creates a new `Cmac` with `key`.
+    ///
+    /// `key` must be 16 or 32 bytes long, selecting AES-128 or AES-256.
+    ///
+    /// This corresponds to [`CMAC_Init`].
+    ///
+    /// [`CMAC_Init`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/cmac.h.html#CMAC_Init
+    pub fn new(key: &[u8]) -> Result<Cmac, ErrorStack> {
+        let cipher = match key.len() {
+            16 => Cipher::aes_128_cbc(),
+            32 => Cipher::aes_256_cbc(),
+            _ => return Err(invalid_key_length()),
+        };
+
+        unsafe {
+            ffi::init();
+            let cmac = Cmac(cvt_p(ffi::CMAC_CTX_new())?);
+            cvt(ffi::CMAC_Init(
+                cmac.0,
+                key.as_ptr() as *const _,
+                key.len(),
+                cipher.as_ptr(),
+                ptr::null_mut(),
+            ))?;
+            Ok(cmac)
+        }
+    }
+
+    /// Feeds data into the `Cmac`.
+    ///
+    /// This corresponds to [`CMAC_Update`].
+    ///
+    /// [`CMAC_Update`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/cmac.h.html#CMAC_Update
+    pub fn update(&mut self, data: &[u8]) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::CMAC_Update(self.0, data.as_ptr(), data.len())).map(|_| ()) }
+    }
+
+    /// Returns the tag of the data fed so far, and resets the `Cmac` for a new message with the
+    /// same key.
+    ///
+    /// This corresponds to [`CMAC_Final`].
+    ///
+    /// [`CMAC_Final`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/cmac.h.html#CMAC_Final
+    pub fn finish(&mut self) -> Result<[u8; TAG_LEN], ErrorStack> {
+        unsafe {
+            let mut tag = [0; TAG_LEN];
+            let mut len = 0;
+            cvt(ffi::CMAC_Final(self.0, tag.as_mut_ptr(), &mut len))?;
+            debug_assert_eq!(len, TAG_LEN);
+            cvt(ffi::CMAC_Reset(self.0))?;
+            Ok(tag)
+        }
+    }
+}
+
+impl Write for Cmac {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4493, section 4
+    #[test]
+    fn aes_128_cmac() {
+        let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let data = hex::decode(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(aes_cmac(&key, &[]).unwrap()),
+            "bb1d6929e95937287fa37d129b756746"
+        );
+        assert_eq!(
+            hex::encode(aes_cmac(&key, &data[..16]).unwrap()),
+            "070a16b46b4d4144f79bdd9dd04a287c"
+        );
+        assert_eq!(
+            hex::encode(aes_cmac(&key, &data[..40]).unwrap()),
+            "dfa66747de9ae63030ca32611497c827"
+        );
+
+        let mut cmac = Cmac::new(&key).unwrap();
+        for chunk in data.chunks(7) {
+            cmac.update(chunk).unwrap();
+        }
+        assert_eq!(
+            hex::encode(cmac.finish().unwrap()),
+            "51f0bebf7e3b9d92fc49741779363cfe"
+        );
+
+        cmac.update(&data[..16]).unwrap();
+        assert_eq!(
+            hex::encode(cmac.finish().unwrap()),
+            "070a16b46b4d4144f79bdd9dd04a287c"
+        );
+    }
+
+    // NIST SP 800-38B, appendix D.3
+    #[test]
+    fn aes_256_cmac() {
+        let key = hex::decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+            .unwrap();
+        let data = hex::decode("6bc1bee22e409f96e93d7e117393172a").unwrap();
+
+        assert_eq!(
+            hex::encode(aes_cmac(&key, &data).unwrap()),
+            "28a7023f452e8f82bd4bf28d8c37c35c"
+        );
+
+        let mut cmac = Cmac::new(&key).unwrap();
+        cmac.write_all(&data).unwrap();
+        assert_eq!(
+            hex::encode(cmac.finish().unwrap()),
+            "28a7023f452e8f82bd4bf28d8c37c35c"
+        );
+    }
+
+    #[test]
+    fn invalid_key_length() {
+        assert!(aes_cmac(&[0; 24], b"").is_err());
+        assert!(Cmac::new(&[0; 24]).is_err());
+        assert!(Cmac::new(&[]).is_err());
+
+        let e = Cmac::new(&[0; 20]).err().unwrap();
+        assert_eq!(
+            e.errors()[0].reason_code(),
+            ffi::CIPHER_R_INVALID_KEY_LENGTH as i32
+        );
+    }
+}
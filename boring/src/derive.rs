@@ -1,11 +1,30 @@
 //! Shared secret derivation.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use boring::derive;
+//! use boring::hash::MessageDigest;
+//! use boring::pkey::{Id, PKey};
+//!
+//! let alice = PKey::generate_x25519().unwrap();
+//! let bob = PKey::generate_x25519().unwrap();
+//! let bob_public = PKey::public_key_from_raw_bytes(&bob.raw_public_key().unwrap(), Id::X25519)
+//!     .unwrap();
+//!
+//! let mut key = [0; 32];
+//! let digest = MessageDigest::sha256();
+//! derive::shared_secret_hkdf(&alice, &bob_public, digest, b"", b"demo", &mut key).unwrap();
+//! ```
 use crate::ffi;
 use foreign_types::ForeignTypeRef;
 use std::marker::PhantomData;
 use std::ptr;
 
 use crate::error::ErrorStack;
-use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::hash::MessageDigest;
+use crate::hkdf;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKeyRef};
 use crate::{cvt, cvt_p};
 
 /// A type used to derive a shared secret between two keys.
@@ -101,6 +120,51 @@ impl<'a> Deriver<'a> {
     }
 }
 
+/// Derives the shared secret of an ECDH or X25519 key agreement between `key` and `peer`.
+///
+/// Elliptic curve peer keys are checked to be valid points on the same curve as `key`, and X25519
+/// agreements resulting in the all-zero secret, caused by small-order peer keys, are rejected.
+///
+/// The secret should not be used as a key directly; see [`shared_secret_hkdf`].
+pub fn shared_secret<T, U>(key: &PKeyRef<T>, peer: &PKeyRef<U>) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPrivate,
+    U: HasPublic,
+{
+    if peer.id() == Id::EC {
+        peer.ec_key()?.check_key()?;
+    }
+
+    let mut deriver = Deriver::new(key)?;
+    deriver.set_peer(peer)?;
+    deriver.derive_to_vec()
+}
+
+/// Derives keying material from the shared secret of a key agreement between `key` and `peer`,
+/// using HKDF with `digest`, `salt` and `info`.
+///
+/// The intermediate shared secret is cleared from memory before returning. See [`shared_secret`]
+/// and [`hkdf::hkdf`].
+pub fn shared_secret_hkdf<T, U>(
+    key: &PKeyRef<T>,
+    peer: &PKeyRef<U>,
+    digest: MessageDigest,
+    salt: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack>
+where
+    T: HasPrivate,
+    U: HasPublic,
+{
+    let mut secret = shared_secret(key, peer)?;
+    let res = hkdf::hkdf(digest, &secret, salt, info, out);
+    unsafe {
+        ffi::OPENSSL_cleanse(secret.as_mut_ptr() as *mut _, secret.len());
+    }
+    res
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -130,4 +194,53 @@ mod test {
         let shared = deriver.derive_to_vec().unwrap();
         assert!(!shared.is_empty());
     }
+
+    #[test]
+    fn shared_secret_x25519() {
+        let alice = PKey::generate_x25519().unwrap();
+        let bob = PKey::generate_x25519().unwrap();
+
+        let secret = shared_secret(&alice, &bob).unwrap();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(secret, shared_secret(&bob, &alice).unwrap());
+
+        let mut key = [0; 16];
+        shared_secret_hkdf(
+            &alice,
+            &bob,
+            MessageDigest::sha256(),
+            b"salt",
+            b"info",
+            &mut key,
+        )
+        .unwrap();
+        let mut expected = [0; 16];
+        hkdf::hkdf(
+            MessageDigest::sha256(),
+            &secret,
+            b"salt",
+            b"info",
+            &mut expected,
+        )
+        .unwrap();
+        assert_eq!(key, expected);
+
+        let small_order = PKey::public_key_from_raw_bytes(&[0; 32], Id::X25519).unwrap();
+        assert!(shared_secret(&alice, &small_order).is_err());
+    }
+
+    #[test]
+    fn shared_secret_ec() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let alice = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let bob = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        assert_eq!(
+            shared_secret(&alice, &bob).unwrap(),
+            shared_secret(&bob, &alice).unwrap()
+        );
+
+        let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+        let other = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        assert!(shared_secret(&alice, &other).is_err());
+    }
 }
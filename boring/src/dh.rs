@@ -82,7 +82,8 @@ impl Dh<Params> {
 
 use crate::ffi::DH_set0_pqg;
 
-#[cfg(test)]
+// The tests install parameters into an `SslContext`, which isn't available without libssl.
+#[cfg(all(test, boringssl_ssl))]
 mod tests {
     use crate::bn::BigNum;
     use crate::dh::Dh;
@@ -21,7 +21,7 @@ use libc::c_int;
 use std::fmt;
 use std::ptr;
 
-use crate::bn::{BigNumContextRef, BigNumRef};
+use crate::bn::{BigNum, BigNumContext, BigNumContextRef, BigNumRef};
 use crate::error::ErrorStack;
 use crate::nid::Nid;
 use crate::pkey::{HasParams, HasPrivate, HasPublic, Params, Private, Public};
@@ -121,6 +121,33 @@ impl EcGroup {
             cvt_p(ffi::EC_GROUP_new_by_curve_name(nid.as_raw())).map(|p| EcGroup::from_ptr(p))
         }
     }
+
+    /// Returns a custom curve over the prime field of order `p`, following the formula
+    /// `y^2 mod p = x^3 + ax + b mod p`.
+    ///
+    /// The group has no generator until [`EcGroupRef::set_generator`] is called. Custom curves
+    /// are slower than the named ones, and should only be used where a protocol requires them.
+    ///
+    /// This corresponds to [`EC_GROUP_new_curve_GFp`].
+    ///
+    /// [`EC_GROUP_new_curve_GFp`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ec.h.html#EC_GROUP_new_curve_GFp
+    pub fn from_components(
+        p: &BigNumRef,
+        a: &BigNumRef,
+        b: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<EcGroup, ErrorStack> {
+        unsafe {
+            init();
+            cvt_p(ffi::EC_GROUP_new_curve_GFp(
+                p.as_ptr(),
+                a.as_ptr(),
+                b.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|p| EcGroup::from_ptr(p))
+        }
+    }
 }
 
 impl EcGroupRef {
@@ -220,6 +247,31 @@ impl EcGroupRef {
         }
     }
 
+    /// Sets the generator of a custom curve created with [`EcGroup::from_components`], along
+    /// with its `order` and `cofactor`.
+    ///
+    /// BoringSSL only supports a cofactor of one, and the generator may only be set once.
+    ///
+    /// This corresponds to [`EC_GROUP_set_generator`].
+    ///
+    /// [`EC_GROUP_set_generator`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ec.h.html#EC_GROUP_set_generator
+    pub fn set_generator(
+        &mut self,
+        generator: &EcPointRef,
+        order: &BigNumRef,
+        cofactor: &BigNumRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EC_GROUP_set_generator(
+                self.as_ptr(),
+                generator.as_ptr(),
+                order.as_ptr(),
+                cofactor.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Sets the flag determining if the group corresponds to a named curve or must be explicitly
     /// parameterized.
     ///
@@ -283,6 +335,28 @@ impl EcPointRef {
         }
     }
 
+    /// Computes `a + a`, storing the result in `self`.
+    ///
+    /// OpenSSL documentation at [`EC_POINT_dbl`]
+    ///
+    /// [`EC_POINT_dbl`]: https://www.openssl.org/docs/man1.1.0/crypto/EC_POINT_add.html
+    pub fn dbl(
+        &mut self,
+        group: &EcGroupRef,
+        a: &EcPointRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EC_POINT_dbl(
+                group.as_ptr(),
+                self.as_ptr(),
+                a.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Computes `q * m`, storing the result in `self`.
     ///
     /// OpenSSL documentation at [`EC_POINT_mul`]
@@ -441,6 +515,88 @@ impl EcPointRef {
         }
     }
 
+    /// Determines if this point is the point at infinity.
+    ///
+    /// OpenSSL documentation at [`EC_POINT_is_at_infinity`]
+    ///
+    /// [`EC_POINT_is_at_infinity`]: https://www.openssl.org/docs/man1.1.0/crypto/EC_POINT_is_at_infinity.html
+    pub fn is_infinity(&self, group: &EcGroupRef) -> bool {
+        unsafe { ffi::EC_POINT_is_at_infinity(group.as_ptr(), self.as_ptr()) == 1 }
+    }
+
+    /// Determines if this point is on the curve.
+    ///
+    /// OpenSSL documentation at [`EC_POINT_is_on_curve`]
+    ///
+    /// [`EC_POINT_is_on_curve`]: https://www.openssl.org/docs/man1.1.0/crypto/EC_POINT_is_on_curve.html
+    pub fn is_on_curve(
+        &self,
+        group: &EcGroupRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<bool, ErrorStack> {
+        unsafe {
+            let res = cvt_n(ffi::EC_POINT_is_on_curve(
+                group.as_ptr(),
+                self.as_ptr(),
+                ctx.as_ptr(),
+            ))?;
+            Ok(res == 1)
+        }
+    }
+
+    /// Sets the affine coordinates of this point on a curve over a prime field.
+    ///
+    /// Returns an error if the resulting point is not on the curve.
+    ///
+    /// OpenSSL documentation at [`EC_POINT_set_affine_coordinates_GFp`]
+    ///
+    /// [`EC_POINT_set_affine_coordinates_GFp`]: https://www.openssl.org/docs/man1.1.0/crypto/EC_POINT_set_affine_coordinates_GFp.html
+    pub fn set_affine_coordinates_gfp(
+        &mut self,
+        group: &EcGroupRef,
+        x: &BigNumRef,
+        y: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EC_POINT_set_affine_coordinates_GFp(
+                group.as_ptr(),
+                self.as_ptr(),
+                x.as_ptr(),
+                y.as_ptr(),
+                ctx.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets this point to the point of a curve over a prime field with affine coordinate `x`,
+    /// choosing the `y` coordinate whose least significant bit is `y_bit`.
+    ///
+    /// This decompresses a point given its compressed form.
+    ///
+    /// OpenSSL documentation at [`EC_POINT_set_compressed_coordinates_GFp`]
+    ///
+    /// [`EC_POINT_set_compressed_coordinates_GFp`]: https://www.openssl.org/docs/man1.1.0/crypto/EC_POINT_set_compressed_coordinates_GFp.html
+    pub fn set_compressed_coordinates_gfp(
+        &mut self,
+        group: &EcGroupRef,
+        x: &BigNumRef,
+        y_bit: bool,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EC_POINT_set_compressed_coordinates_GFp(
+                group.as_ptr(),
+                self.as_ptr(),
+                x.as_ptr(),
+                y_bit as c_int,
+                ctx.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Place affine coordinates of a curve over a prime field in the provided
     /// `x` and `y` `BigNum`s
     ///
@@ -563,6 +719,25 @@ where
             BigNumRef::from_ptr(ptr as *mut _)
         }
     }
+
+    /// Serializes the private scalar as a big-endian integer, zero-padded to the byte length of
+    /// the group order.
+    pub fn private_key_to_bytes(&self) -> Result<Vec<u8>, ErrorStack> {
+        let len = (self.group().order_bits() as usize + 7) / 8;
+        self.private_key().to_vec_padded(len)
+    }
+
+    /// Serializes the key pair in the ANSI X9.63 format: the uncompressed public point followed
+    /// by the private scalar as returned by [`EcKeyRef::private_key_to_bytes`].
+    ///
+    /// This is the format used by Apple's `SecKeyCopyExternalRepresentation` for EC keys.
+    pub fn private_key_to_x963(&self, ctx: &mut BigNumContextRef) -> Result<Vec<u8>, ErrorStack> {
+        let mut out =
+            self.public_key()
+                .to_bytes(self.group(), PointConversionForm::UNCOMPRESSED, ctx)?;
+        out.extend_from_slice(&self.private_key_to_bytes()?);
+        Ok(out)
+    }
 }
 
 impl<T> EcKeyRef<T>
@@ -807,6 +982,42 @@ impl EcKey<Private> {
         }
     }
 
+    /// Constructs a public/private key pair from a big-endian private scalar, computing the
+    /// public key.
+    pub fn private_key_from_bytes(
+        group: &EcGroupRef,
+        bytes: &[u8],
+    ) -> Result<EcKey<Private>, ErrorStack> {
+        let private_number = BigNum::from_slice(bytes)?;
+        let ctx = BigNumContext::new()?;
+        let mut public_key = EcPoint::new(group)?;
+        public_key.mul_generator(group, &private_number, &ctx)?;
+        let key = EcKey::from_private_components(group, &private_number, &public_key)?;
+        key.check_key()?;
+        Ok(key)
+    }
+
+    /// Deserializes a key pair in the ANSI X9.63 format: the uncompressed public point followed
+    /// by the big-endian private scalar.
+    ///
+    /// The public point is checked against the private scalar. This accepts keys exported by
+    /// Apple's Secure Enclave and `SecKeyCopyExternalRepresentation`.
+    pub fn private_key_from_x963(
+        group: &EcGroupRef,
+        bytes: &[u8],
+        ctx: &mut BigNumContextRef,
+    ) -> Result<EcKey<Private>, ErrorStack> {
+        let scalar_len = (group.order_bits() as usize + 7) / 8;
+        let split = bytes.len().saturating_sub(scalar_len);
+        let (public_key, private_number) = bytes.split_at(split);
+
+        let public_key = EcPoint::from_bytes(group, public_key, ctx)?;
+        let private_number = BigNum::from_slice(private_number)?;
+        let key = EcKey::from_private_components(group, &private_number, &public_key)?;
+        key.check_key()?;
+        Ok(key)
+    }
+
     private_key_from_pem! {
         /// Deserializes a private key from a PEM-encoded ECPrivateKey structure.
         ///
@@ -1014,4 +1225,151 @@ mod test {
         assert_eq!(xbn2, xbn);
         assert_eq!(ybn2, ybn);
     }
+
+    #[test]
+    fn point_arithmetic() {
+        let group = EcGroup::from_curve_name(Nid::SECP224R1).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let gen = group.generator();
+        assert!(gen.is_on_curve(&group, &mut ctx).unwrap());
+        assert!(!gen.is_infinity(&group));
+
+        let mut doubled = EcPoint::new(&group).unwrap();
+        doubled.dbl(&group, gen, &mut ctx).unwrap();
+        let mut sum = EcPoint::new(&group).unwrap();
+        sum.add(&group, gen, gen, &mut ctx).unwrap();
+        let mut product = EcPoint::new(&group).unwrap();
+        product
+            .mul_generator(&group, &BigNum::from_u32(2).unwrap(), &ctx)
+            .unwrap();
+        assert!(doubled.eq(&group, &sum, &mut ctx).unwrap());
+        assert!(doubled.eq(&group, &product, &mut ctx).unwrap());
+
+        let mut order = BigNum::new().unwrap();
+        group.order(&mut order, &mut ctx).unwrap();
+        let mut infinity = EcPoint::new(&group).unwrap();
+        infinity.mul(&group, gen, &order, &ctx).unwrap();
+        assert!(infinity.is_infinity(&group));
+    }
+
+    #[test]
+    fn point_decompress() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+
+        let compressed = key
+            .public_key()
+            .to_bytes(&group, PointConversionForm::COMPRESSED, &mut ctx)
+            .unwrap();
+        let x = BigNum::from_slice(&compressed[1..]).unwrap();
+
+        let mut point = EcPoint::new(&group).unwrap();
+        point
+            .set_compressed_coordinates_gfp(&group, &x, compressed[0] == 3, &mut ctx)
+            .unwrap();
+        assert!(point.eq(&group, key.public_key(), &mut ctx).unwrap());
+
+        let uncompressed = point
+            .to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)
+            .unwrap();
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(uncompressed[0], 4);
+        assert_eq!(uncompressed[1..33], compressed[1..]);
+
+        let y = BigNum::from_slice(&uncompressed[33..]).unwrap();
+        point
+            .set_affine_coordinates_gfp(&group, &x, &y, &mut ctx)
+            .unwrap();
+        assert!(point.eq(&group, key.public_key(), &mut ctx).unwrap());
+
+        let y = &y + &BigNum::from_u32(1).unwrap();
+        assert!(point
+            .set_affine_coordinates_gfp(&group, &x, &y, &mut ctx)
+            .is_err());
+    }
+
+    #[test]
+    fn custom_curve() {
+        let bn = |hex: &str| BigNum::from_hex_str(hex).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+
+        // The P-256 parameters
+        let mut group = EcGroup::from_components(
+            &bn("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff"),
+            &bn("ffffffff00000001000000000000000000000000fffffffffffffffffffffffc"),
+            &bn("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+            &mut ctx,
+        )
+        .unwrap();
+        let mut generator = EcPoint::new(&group).unwrap();
+        generator
+            .set_affine_coordinates_gfp(
+                &group,
+                &bn("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+                &bn("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+                &mut ctx,
+            )
+            .unwrap();
+        group
+            .set_generator(
+                &generator,
+                &bn("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+                &BigNum::from_u32(1).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(group.order_bits(), 256);
+
+        let key = EcKey::generate(&group).unwrap();
+        key.check_key().unwrap();
+
+        let named = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let bytes = key
+            .public_key()
+            .to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)
+            .unwrap();
+        let point = EcPoint::from_bytes(&named, &bytes, &mut ctx).unwrap();
+        assert!(point.is_on_curve(&named, &mut ctx).unwrap());
+    }
+
+    // RFC 6979, appendix A.2.5
+    #[test]
+    fn private_key_bytes_x963() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let scalar =
+            Vec::from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")
+                .unwrap();
+
+        let key = EcKey::private_key_from_bytes(&group, &scalar).unwrap();
+        assert_eq!(key.private_key_to_bytes().unwrap(), scalar);
+
+        let x963 = key.private_key_to_x963(&mut ctx).unwrap();
+        assert_eq!(
+            hex::encode(&x963),
+            "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6\
+             7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299\
+             c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"
+        );
+
+        let key2 = EcKey::private_key_from_x963(&group, &x963, &mut ctx).unwrap();
+        assert_eq!(
+            key2.private_key_to_der().unwrap(),
+            key.private_key_to_der().unwrap()
+        );
+
+        let mut tampered = x963.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(EcKey::private_key_from_x963(&group, &tampered, &mut ctx).is_err());
+        assert!(EcKey::private_key_from_x963(&group, &x963[..32], &mut ctx).is_err());
+    }
+
+    #[test]
+    fn private_key_bytes_padded() {
+        let group = EcGroup::from_curve_name(Nid::SECP521R1).unwrap();
+        let key = EcKey::private_key_from_bytes(&group, &[1]).unwrap();
+        let bytes = key.private_key_to_bytes().unwrap();
+        assert_eq!(bytes.len(), 66);
+        assert_eq!(bytes[65], 1);
+    }
 }
@@ -7,7 +7,7 @@ use std::mem;
 use std::ptr;
 
 use crate::bn::{BigNum, BigNumRef};
-use crate::ec::EcKeyRef;
+use crate::ec::{EcGroupRef, EcKeyRef};
 use crate::error::ErrorStack;
 use crate::pkey::{HasPrivate, HasPublic};
 use crate::{cvt_n, cvt_p};
@@ -60,6 +60,21 @@ impl EcdsaSig {
         }
     }
 
+    /// Decodes a signature in the fixed-size raw form used by JOSE, COSE and WebAuthn.
+    ///
+    /// The raw form is the concatenation of `r` and `s`, each encoded as a big-endian integer
+    /// padded to the byte length of the order of `group`.
+    pub fn from_raw(raw: &[u8], group: &EcGroupRef) -> Result<EcdsaSig, ErrorStack> {
+        let len = raw_component_len(group);
+        if raw.len() != 2 * len {
+            return Err(bad_signature());
+        }
+
+        let r = BigNum::from_slice(&raw[..len])?;
+        let s = BigNum::from_slice(&raw[len..])?;
+        EcdsaSig::from_private_components(r, s)
+    }
+
     from_der! {
         /// Decodes a DER-encoded ECDSA signature.
         ///
@@ -84,6 +99,16 @@ impl EcdsaSigRef {
         ffi::i2d_ECDSA_SIG
     }
 
+    /// Encodes the signature in the fixed-size raw form used by JOSE, COSE and WebAuthn.
+    ///
+    /// See [`EcdsaSig::from_raw`].
+    pub fn to_raw(&self, group: &EcGroupRef) -> Result<Vec<u8>, ErrorStack> {
+        let len = raw_component_len(group);
+        let mut raw = self.r().to_vec_padded(len)?;
+        raw.extend(self.s().to_vec_padded(len)?);
+        Ok(raw)
+    }
+
     /// Verifies if the signature is a valid ECDSA signature using the given public key.
     ///
     /// OpenSSL documentation at [`ECDSA_do_verify`]
@@ -133,3 +158,93 @@ impl EcdsaSigRef {
 }
 
 use crate::ffi::{ECDSA_SIG_get0, ECDSA_SIG_set0};
+
+fn raw_component_len(group: &EcGroupRef) -> usize {
+    (group.order_bits() as usize + 7) / 8
+}
+
+fn bad_signature() -> ErrorStack {
+    unsafe {
+        ffi::ERR_put_error(
+            ffi::ERR_LIB_ECDSA.0 as _,
+            0,
+            ffi::ECDSA_R_BAD_SIGNATURE as _,
+            concat!(file!(), "\0").as_ptr() as *const _,
+            line!() as _,
+        );
+    }
+    ErrorStack::get()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::sha::sha256;
+
+    // RFC 6979, appendix A.2.5, with SHA-256 and the message "sample"
+    const P256_X: &str = "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6";
+    const P256_Y: &str = "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+    const P256_R: &str = "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716";
+    const P256_S: &str = "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8";
+
+    #[test]
+    fn raw_p256() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let x = BigNum::from_slice(&hex::decode(P256_X).unwrap()).unwrap();
+        let y = BigNum::from_slice(&hex::decode(P256_Y).unwrap()).unwrap();
+        let key = EcKey::from_public_key_affine_coordinates(&group, &x, &y).unwrap();
+
+        let raw = hex::decode(format!("{}{}", P256_R, P256_S)).unwrap();
+        let sig = EcdsaSig::from_raw(&raw, &group).unwrap();
+        assert_eq!(hex::encode(sig.r().to_vec()), P256_R);
+        assert_eq!(hex::encode(sig.s().to_vec()), P256_S);
+        assert!(sig.verify(&sha256(b"sample"), &key).unwrap());
+        assert!(!sig.verify(&sha256(b"test"), &key).unwrap());
+
+        let der = sig.to_der().unwrap();
+        assert_eq!(
+            hex::encode(&der),
+            format!("3046022100{}022100{}", P256_R, P256_S)
+        );
+        let sig = EcdsaSig::from_der(&der).unwrap();
+        assert_eq!(sig.to_raw(&group).unwrap(), raw);
+
+        assert!(EcdsaSig::from_raw(&raw[1..], &group).is_err());
+    }
+
+    #[test]
+    fn raw_padding() {
+        let group = EcGroup::from_curve_name(Nid::SECP521R1).unwrap();
+        let r = BigNum::from_u32(1).unwrap();
+        let s = BigNum::from_u32(2).unwrap();
+        let sig = EcdsaSig::from_private_components(r, s).unwrap();
+
+        let raw = sig.to_raw(&group).unwrap();
+        assert_eq!(raw.len(), 2 * 66);
+        assert_eq!(raw[65], 1);
+        assert_eq!(raw[131], 2);
+        assert!(raw[..65].iter().chain(&raw[66..131]).all(|&b| b == 0));
+
+        let sig = EcdsaSig::from_raw(&raw, &group).unwrap();
+        assert_eq!(sig.r(), &*BigNum::from_u32(1).unwrap());
+        assert_eq!(sig.s(), &*BigNum::from_u32(2).unwrap());
+    }
+
+    #[test]
+    fn sign_verify_raw() {
+        let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let digest = sha256(b"hello");
+
+        let raw = EcdsaSig::sign(&digest, &key)
+            .unwrap()
+            .to_raw(&group)
+            .unwrap();
+        assert_eq!(raw.len(), 96);
+        let sig = EcdsaSig::from_raw(&raw, &group).unwrap();
+        assert!(sig.verify(&digest, &key).unwrap());
+    }
+}
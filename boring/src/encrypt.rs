@@ -0,0 +1,432 @@
+//! Message encryption.
+//!
+//! The [`Encrypter`] allows for encryption of data given a public key. The [`Decrypter`] can be
+//! used with the corresponding private key to decrypt the data.
+//!
+//! # Examples
+//!
+//! Encrypt and decrypt data given an RSA keypair, using OAEP with SHA-256 and a label:
+//!
+//! ```rust
+//! use boring::encrypt::{Decrypter, Encrypter};
+//! use boring::hash::MessageDigest;
+//! use boring::pkey::PKey;
+//! use boring::rsa::{Padding, Rsa};
+//!
+//! let keypair = Rsa::generate(2048).unwrap();
+//! let keypair = PKey::from_rsa(keypair).unwrap();
+//!
+//! let data = b"hello, world!";
+//!
+//! let mut encrypter = Encrypter::new(&keypair).unwrap();
+//! encrypter.set_rsa_padding(Padding::PKCS1_OAEP).unwrap();
+//! encrypter.set_rsa_oaep_md(MessageDigest::sha256()).unwrap();
+//! encrypter.set_rsa_oaep_label(b"label").unwrap();
+//! let mut encrypted = vec![0; encrypter.encrypt_len(data).unwrap()];
+//! let len = encrypter.encrypt(data, &mut encrypted).unwrap();
+//! encrypted.truncate(len);
+//!
+//! let mut decrypter = Decrypter::new(&keypair).unwrap();
+//! decrypter.set_rsa_padding(Padding::PKCS1_OAEP).unwrap();
+//! decrypter.set_rsa_oaep_md(MessageDigest::sha256()).unwrap();
+//! decrypter.set_rsa_oaep_label(b"label").unwrap();
+//! let mut decrypted = vec![0; decrypter.decrypt_len(&encrypted).unwrap()];
+//! let len = decrypter.decrypt(&encrypted, &mut decrypted).unwrap();
+//! decrypted.truncate(len);
+//!
+//! assert_eq!(&decrypted[..], &data[..]);
+//! ```
+use crate::ffi;
+use foreign_types::ForeignTypeRef;
+use std::marker::PhantomData;
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::rsa::Padding;
+use crate::{cvt, cvt_p};
+
+/// A type which encrypts data.
+pub struct Encrypter<'a> {
+    pctx: *mut ffi::EVP_PKEY_CTX,
+    _p: PhantomData<&'a ()>,
+}
+
+unsafe impl<'a> Sync for Encrypter<'a> {}
+unsafe impl<'a> Send for Encrypter<'a> {}
+
+impl<'a> Drop for Encrypter<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::EVP_PKEY_CTX_free(self.pctx);
+        }
+    }
+}
+
+impl<'a> Encrypter<'a> {
+    /// Creates a new `Encrypter`.
+    ///
+    /// This corresponds to [`EVP_PKEY_encrypt_init`].
+    ///
+    /// [`EVP_PKEY_encrypt_init`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_encrypt_init.html
+    pub fn new<T>(pkey: &'a PKeyRef<T>) -> Result<Encrypter<'a>, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe {
+            ffi::init();
+
+            let pctx = cvt_p(ffi::EVP_PKEY_CTX_new(pkey.as_ptr(), ptr::null_mut()))?;
+            let encrypter = Encrypter {
+                pctx,
+                _p: PhantomData,
+            };
+            cvt(ffi::EVP_PKEY_encrypt_init(pctx))?;
+
+            Ok(encrypter)
+        }
+    }
+
+    /// Returns the RSA padding mode in use.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to `EVP_PKEY_CTX_get_rsa_padding`.
+    pub fn rsa_padding(&self) -> Result<Padding, ErrorStack> {
+        rsa_padding(self.pctx)
+    }
+
+    /// Sets the RSA padding mode.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_padding`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_padding`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_padding.html
+    pub fn set_rsa_padding(&mut self, padding: Padding) -> Result<(), ErrorStack> {
+        set_rsa_padding(self.pctx, padding)
+    }
+
+    /// Sets the RSA MGF1 algorithm.
+    ///
+    /// This is only useful for RSA keys using OAEP padding, and defaults to the OAEP digest.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_mgf1_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_mgf1_md`]: https://www.openssl.org/docs/manmaster/man7/RSA-PSS.html
+    pub fn set_rsa_mgf1_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        set_rsa_mgf1_md(self.pctx, md)
+    }
+
+    /// Sets the RSA OAEP digest.
+    ///
+    /// This is only useful for RSA keys using OAEP padding, and defaults to SHA-1.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_oaep_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_oaep_md`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set_rsa_oaep_md.html
+    pub fn set_rsa_oaep_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        set_rsa_oaep_md(self.pctx, md)
+    }
+
+    /// Sets the RSA OAEP label.
+    ///
+    /// This is only useful for RSA keys using OAEP padding, and defaults to an empty label.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set0_rsa_oaep_label`].
+    ///
+    /// [`EVP_PKEY_CTX_set0_rsa_oaep_label`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set0_rsa_oaep_label.html
+    pub fn set_rsa_oaep_label(&mut self, label: &[u8]) -> Result<(), ErrorStack> {
+        set_rsa_oaep_label(self.pctx, label)
+    }
+
+    /// Returns the length of the buffer needed to encrypt `from`.
+    pub fn encrypt_len(&self, from: &[u8]) -> Result<usize, ErrorStack> {
+        let mut written = 0;
+        unsafe {
+            cvt(ffi::EVP_PKEY_encrypt(
+                self.pctx,
+                ptr::null_mut(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+
+        Ok(written)
+    }
+
+    /// Encrypts `from` into `to`, returning the number of bytes written.
+    ///
+    /// `to` should be at least [`encrypt_len`](Self::encrypt_len) bytes long.
+    ///
+    /// This corresponds to [`EVP_PKEY_encrypt`].
+    ///
+    /// [`EVP_PKEY_encrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_encrypt.html
+    pub fn encrypt(&self, from: &[u8], to: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mut written = to.len();
+        unsafe {
+            cvt(ffi::EVP_PKEY_encrypt(
+                self.pctx,
+                to.as_mut_ptr(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+
+        Ok(written)
+    }
+}
+
+/// A type which decrypts data.
+pub struct Decrypter<'a> {
+    pctx: *mut ffi::EVP_PKEY_CTX,
+    _p: PhantomData<&'a ()>,
+}
+
+unsafe impl<'a> Sync for Decrypter<'a> {}
+unsafe impl<'a> Send for Decrypter<'a> {}
+
+impl<'a> Drop for Decrypter<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::EVP_PKEY_CTX_free(self.pctx);
+        }
+    }
+}
+
+impl<'a> Decrypter<'a> {
+    /// Creates a new `Decrypter`.
+    ///
+    /// This corresponds to [`EVP_PKEY_decrypt_init`].
+    ///
+    /// [`EVP_PKEY_decrypt_init`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_decrypt_init.html
+    pub fn new<T>(pkey: &'a PKeyRef<T>) -> Result<Decrypter<'a>, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            ffi::init();
+
+            let pctx = cvt_p(ffi::EVP_PKEY_CTX_new(pkey.as_ptr(), ptr::null_mut()))?;
+            let decrypter = Decrypter {
+                pctx,
+                _p: PhantomData,
+            };
+            cvt(ffi::EVP_PKEY_decrypt_init(pctx))?;
+
+            Ok(decrypter)
+        }
+    }
+
+    /// Returns the RSA padding mode in use.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to `EVP_PKEY_CTX_get_rsa_padding`.
+    pub fn rsa_padding(&self) -> Result<Padding, ErrorStack> {
+        rsa_padding(self.pctx)
+    }
+
+    /// Sets the RSA padding mode.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_padding`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_padding`]: https://www.openssl.org/docs/man1.1.0/crypto/EVP_PKEY_CTX_set_rsa_padding.html
+    pub fn set_rsa_padding(&mut self, padding: Padding) -> Result<(), ErrorStack> {
+        set_rsa_padding(self.pctx, padding)
+    }
+
+    /// Sets the RSA MGF1 algorithm.
+    ///
+    /// This is only useful for RSA keys using OAEP padding, and defaults to the OAEP digest.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_mgf1_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_mgf1_md`]: https://www.openssl.org/docs/manmaster/man7/RSA-PSS.html
+    pub fn set_rsa_mgf1_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        set_rsa_mgf1_md(self.pctx, md)
+    }
+
+    /// Sets the RSA OAEP digest.
+    ///
+    /// This is only useful for RSA keys using OAEP padding, and defaults to SHA-1.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set_rsa_oaep_md`].
+    ///
+    /// [`EVP_PKEY_CTX_set_rsa_oaep_md`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set_rsa_oaep_md.html
+    pub fn set_rsa_oaep_md(&mut self, md: MessageDigest) -> Result<(), ErrorStack> {
+        set_rsa_oaep_md(self.pctx, md)
+    }
+
+    /// Sets the RSA OAEP label.
+    ///
+    /// This is only useful for RSA keys using OAEP padding, and defaults to an empty label.
+    ///
+    /// This corresponds to [`EVP_PKEY_CTX_set0_rsa_oaep_label`].
+    ///
+    /// [`EVP_PKEY_CTX_set0_rsa_oaep_label`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_CTX_set0_rsa_oaep_label.html
+    pub fn set_rsa_oaep_label(&mut self, label: &[u8]) -> Result<(), ErrorStack> {
+        set_rsa_oaep_label(self.pctx, label)
+    }
+
+    /// Returns the length of the buffer needed to decrypt `from`.
+    pub fn decrypt_len(&self, from: &[u8]) -> Result<usize, ErrorStack> {
+        let mut written = 0;
+        unsafe {
+            cvt(ffi::EVP_PKEY_decrypt(
+                self.pctx,
+                ptr::null_mut(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+
+        Ok(written)
+    }
+
+    /// Decrypts `from` into `to`, returning the number of bytes written.
+    ///
+    /// `to` should be at least [`decrypt_len`](Self::decrypt_len) bytes long.
+    ///
+    /// This corresponds to [`EVP_PKEY_decrypt`].
+    ///
+    /// [`EVP_PKEY_decrypt`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_decrypt.html
+    pub fn decrypt(&self, from: &[u8], to: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mut written = to.len();
+        unsafe {
+            cvt(ffi::EVP_PKEY_decrypt(
+                self.pctx,
+                to.as_mut_ptr(),
+                &mut written,
+                from.as_ptr(),
+                from.len(),
+            ))?;
+        }
+
+        Ok(written)
+    }
+}
+
+fn rsa_padding(pctx: *mut ffi::EVP_PKEY_CTX) -> Result<Padding, ErrorStack> {
+    unsafe {
+        let mut pad = 0;
+        cvt(ffi::EVP_PKEY_CTX_get_rsa_padding(pctx, &mut pad)).map(|_| Padding::from_raw(pad))
+    }
+}
+
+fn set_rsa_padding(pctx: *mut ffi::EVP_PKEY_CTX, padding: Padding) -> Result<(), ErrorStack> {
+    unsafe { cvt(ffi::EVP_PKEY_CTX_set_rsa_padding(pctx, padding.as_raw())).map(|_| ()) }
+}
+
+fn set_rsa_mgf1_md(pctx: *mut ffi::EVP_PKEY_CTX, md: MessageDigest) -> Result<(), ErrorStack> {
+    unsafe {
+        cvt(ffi::EVP_PKEY_CTX_set_rsa_mgf1_md(
+            pctx,
+            md.as_ptr() as *mut _,
+        ))
+        .map(|_| ())
+    }
+}
+
+fn set_rsa_oaep_md(pctx: *mut ffi::EVP_PKEY_CTX, md: MessageDigest) -> Result<(), ErrorStack> {
+    unsafe {
+        cvt(ffi::EVP_PKEY_CTX_set_rsa_oaep_md(
+            pctx,
+            md.as_ptr() as *mut _,
+        ))
+        .map(|_| ())
+    }
+}
+
+fn set_rsa_oaep_label(pctx: *mut ffi::EVP_PKEY_CTX, label: &[u8]) -> Result<(), ErrorStack> {
+    unsafe {
+        // The context takes ownership of the label, which must be allocated by BoringSSL.
+        let p = cvt_p(ffi::OPENSSL_malloc(label.len()))?;
+        ptr::copy_nonoverlapping(label.as_ptr(), p as *mut u8, label.len());
+
+        cvt(ffi::EVP_PKEY_CTX_set0_rsa_oaep_label(
+            pctx,
+            p as *mut _,
+            label.len(),
+        ))
+        .map(|_| ())
+        .map_err(|e| {
+            ffi::OPENSSL_free(p);
+            e
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::pkey::PKey;
+    use crate::rsa::Rsa;
+
+    fn encrypt(pkey: &PKeyRef<impl HasPublic>, md: MessageDigest, label: &[u8]) -> Vec<u8> {
+        let mut encrypter = Encrypter::new(pkey).unwrap();
+        encrypter.set_rsa_padding(Padding::PKCS1_OAEP).unwrap();
+        encrypter.set_rsa_oaep_md(md).unwrap();
+        encrypter.set_rsa_mgf1_md(md).unwrap();
+        encrypter.set_rsa_oaep_label(label).unwrap();
+        assert_eq!(encrypter.rsa_padding().unwrap(), Padding::PKCS1_OAEP);
+
+        let data = b"hello, world!";
+        let mut out = vec![0; encrypter.encrypt_len(data).unwrap()];
+        let len = encrypter.encrypt(data, &mut out).unwrap();
+        out.truncate(len);
+        out
+    }
+
+    fn decrypt(
+        pkey: &PKeyRef<impl HasPrivate>,
+        md: MessageDigest,
+        label: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, ErrorStack> {
+        let mut decrypter = Decrypter::new(pkey).unwrap();
+        decrypter.set_rsa_padding(Padding::PKCS1_OAEP).unwrap();
+        decrypter.set_rsa_oaep_md(md).unwrap();
+        decrypter.set_rsa_mgf1_md(md).unwrap();
+        decrypter.set_rsa_oaep_label(label).unwrap();
+
+        let mut out = vec![0; decrypter.decrypt_len(data).unwrap()];
+        let len = decrypter.decrypt(data, &mut out)?;
+        out.truncate(len);
+        Ok(out)
+    }
+
+    #[test]
+    fn rsa_oaep() {
+        let key = include_bytes!("../test/rsa.pem");
+        let pkey = PKey::from_rsa(Rsa::private_key_from_pem(key).unwrap()).unwrap();
+
+        let encrypted = encrypt(&pkey, MessageDigest::sha256(), b"label");
+        assert_eq!(
+            decrypt(&pkey, MessageDigest::sha256(), b"label", &encrypted).unwrap(),
+            b"hello, world!"
+        );
+
+        assert!(decrypt(&pkey, MessageDigest::sha256(), b"other", &encrypted).is_err());
+        assert!(decrypt(&pkey, MessageDigest::sha1(), b"label", &encrypted).is_err());
+    }
+
+    #[test]
+    fn rsa_oaep_empty_label() {
+        let key = include_bytes!("../test/rsa.pem");
+        let pkey = PKey::from_rsa(Rsa::private_key_from_pem(key).unwrap()).unwrap();
+
+        let encrypted = encrypt(&pkey, MessageDigest::sha1(), b"");
+        assert_eq!(
+            decrypt(&pkey, MessageDigest::sha1(), b"", &encrypted).unwrap(),
+            b"hello, world!"
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Envelope encryption.
+//!
+//! [`Seal`] encrypts data with a random symmetric key, and encrypts that key to one or more
+//! recipient RSA public keys using OAEP. Each recipient can then use [`Open`] with their private
+//! key, the encrypted key addressed to them and the IV to decrypt the data.
+//!
+//! [`SealWriter`] and [`OpenWriter`] wrap a [`Write`] so that large inputs can be streamed
+//! through the cipher without buffering them in memory.
+//!
+//! The ciphertext is not authenticated, so the envelope should be signed if it travels over an
+//! untrusted channel.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use boring::envelope::{Open, OpenWriter, Seal, SealWriter};
+//! use boring::pkey::PKey;
+//! use boring::rsa::Rsa;
+//! use boring::symm::Cipher;
+//! use std::io::Write;
+//!
+//! let alice = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+//! let bob = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+//! let cipher = Cipher::aes_256_cbc();
+//!
+//! let seal = Seal::new(cipher, &[alice.clone(), bob.clone()]).unwrap();
+//! let iv = seal.iv().unwrap().to_vec();
+//! let bob_key = seal.encrypted_keys()[1].clone();
+//!
+//! let mut writer = SealWriter::new(seal, vec![]);
+//! writer.write_all(b"a very large backup").unwrap();
+//! let encrypted = writer.finish().unwrap();
+//!
+//! let open = Open::new(cipher, &bob, &bob_key, Some(&iv)).unwrap();
+//! let mut writer = OpenWriter::new(open, vec![]);
+//! writer.write_all(&encrypted).unwrap();
+//! let decrypted = writer.finish().unwrap();
+//!
+//! assert_eq!(decrypted, b"a very large backup");
+//! ```
+use std::io::{self, Write};
+
+use crate::encrypt::{Decrypter, Encrypter};
+use crate::error::ErrorStack;
+use crate::memcmp::SecretBox;
+use crate::pkey::{HasPrivate, HasPublic, PKey, PKeyRef};
+use crate::rand::rand_bytes;
+use crate::rsa::Padding;
+use crate::symm::{Cipher, Crypter, Mode};
+
+/// The largest amount of input passed through the cipher by a single `write` call.
+const MAX_CHUNK_LEN: usize = 16 * 1024;
+
+/// Encrypts data to one or more recipients.
+pub struct Seal {
+    crypter: Crypter,
+    block_size: usize,
+    iv: Option<Vec<u8>>,
+    encrypted_keys: Vec<Vec<u8>>,
+}
+
+impl Seal {
+    /// Creates a new `Seal`, generating a random key and IV for `cipher`.
+    ///
+    /// The key is encrypted to each of `pub_keys`, which must be RSA keys, using OAEP. `cipher`
+    /// must not be an AEAD cipher such as AES GCM.
+    pub fn new<T>(cipher: Cipher, pub_keys: &[PKey<T>]) -> Result<Seal, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        let mut key = SecretBox::new(cipher.key_len());
+        rand_bytes(&mut key)?;
+
+        let iv = match cipher.iv_len() {
+            Some(len) => {
+                let mut iv = vec![0; len];
+                rand_bytes(&mut iv)?;
+                Some(iv)
+            }
+            None => None,
+        };
+
+        let encrypted_keys = pub_keys
+            .iter()
+            .map(|pub_key| encrypt_key(pub_key, &key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let crypter = Crypter::new(cipher, Mode::Encrypt, &key, iv.as_deref())?;
+
+        Ok(Seal {
+            crypter,
+            block_size: cipher.block_size(),
+            iv,
+            encrypted_keys,
+        })
+    }
+
+    /// Returns the randomly generated IV, or `None` if the cipher does not use one.
+    pub fn iv(&self) -> Option<&[u8]> {
+        self.iv.as_deref()
+    }
+
+    /// Returns the symmetric key encrypted to each recipient, in the order they were passed to
+    /// [`Seal::new`].
+    pub fn encrypted_keys(&self) -> &[Vec<u8>] {
+        &self.encrypted_keys
+    }
+
+    /// Feeds data from `input` through the cipher, writing encrypted bytes into `output`.
+    ///
+    /// The number of bytes written to `output` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output.len() < input.len() + block_size`, where `block_size` is the block size
+    /// of the cipher.
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, ErrorStack> {
+        self.crypter.update(input, output)
+    }
+
+    /// Finishes the encryption process, writing any remaining data to `output`.
+    ///
+    /// The number of bytes written to `output` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output.len() < block_size`, where `block_size` is the block size of the cipher.
+    pub fn finalize(&mut self, output: &mut [u8]) -> Result<usize, ErrorStack> {
+        self.crypter.finalize(output)
+    }
+}
+
+/// Decrypts data encrypted by a [`Seal`].
+pub struct Open {
+    crypter: Crypter,
+    block_size: usize,
+}
+
+impl Open {
+    /// Creates a new `Open` from the recipient's private key, the encrypted key addressed to it
+    /// and the IV of the [`Seal`].
+    pub fn new<T>(
+        cipher: Cipher,
+        priv_key: &PKeyRef<T>,
+        encrypted_key: &[u8],
+        iv: Option<&[u8]>,
+    ) -> Result<Open, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        let mut decrypter = Decrypter::new(priv_key)?;
+        decrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+        let mut key = SecretBox::new(decrypter.decrypt_len(encrypted_key)?);
+        let len = decrypter.decrypt(encrypted_key, &mut key)?;
+
+        let crypter = Crypter::new(cipher, Mode::Decrypt, &key[..len], iv)?;
+
+        Ok(Open {
+            crypter,
+            block_size: cipher.block_size(),
+        })
+    }
+
+    /// Feeds data from `input` through the cipher, writing decrypted bytes into `output`.
+    ///
+    /// The number of bytes written to `output` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output.len() < input.len() + block_size`, where `block_size` is the block size
+    /// of the cipher.
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, ErrorStack> {
+        self.crypter.update(input, output)
+    }
+
+    /// Finishes the decryption process, writing any remaining data to `output`.
+    ///
+    /// The number of bytes written to `output` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output.len() < block_size`, where `block_size` is the block size of the cipher.
+    pub fn finalize(&mut self, output: &mut [u8]) -> Result<usize, ErrorStack> {
+        self.crypter.finalize(output)
+    }
+}
+
+/// A writer which encrypts data with a [`Seal`] and writes it to an inner writer.
+///
+/// [`SealWriter::finish`] must be called once all data has been written to flush the final block.
+pub struct SealWriter<W> {
+    seal: Seal,
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> SealWriter<W> {
+    /// Creates a new `SealWriter`.
+    pub fn new(seal: Seal, inner: W) -> SealWriter<W> {
+        SealWriter {
+            seal,
+            inner,
+            buf: vec![],
+        }
+    }
+
+    /// Returns a shared reference to the `Seal`, to retrieve its IV and encrypted keys.
+    pub fn seal(&self) -> &Seal {
+        &self.seal
+    }
+
+    /// Returns a shared reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes the final block of ciphertext and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.buf.resize(self.seal.block_size.max(1), 0);
+        let len = self.seal.finalize(&mut self.buf)?;
+        self.inner.write_all(&self.buf[..len])?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for SealWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let input = &buf[..buf.len().min(MAX_CHUNK_LEN)];
+        self.buf.resize(input.len() + self.seal.block_size, 0);
+        let len = self.seal.update(input, &mut self.buf)?;
+        self.inner.write_all(&self.buf[..len])?;
+        Ok(input.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer which decrypts data with an [`Open`] and writes it to an inner writer.
+///
+/// [`OpenWriter::finish`] must be called once all data has been written to flush the final block
+/// and check its padding.
+pub struct OpenWriter<W> {
+    open: Open,
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> OpenWriter<W> {
+    /// Creates a new `OpenWriter`.
+    pub fn new(open: Open, inner: W) -> OpenWriter<W> {
+        OpenWriter {
+            open,
+            inner,
+            buf: vec![],
+        }
+    }
+
+    /// Returns a shared reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes the final block of plaintext and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.buf.resize(self.open.block_size.max(1), 0);
+        let len = self.open.finalize(&mut self.buf)?;
+        self.inner.write_all(&self.buf[..len])?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for OpenWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let input = &buf[..buf.len().min(MAX_CHUNK_LEN)];
+        self.buf.resize(input.len() + self.open.block_size, 0);
+        let len = self.open.update(input, &mut self.buf)?;
+        self.inner.write_all(&self.buf[..len])?;
+        Ok(input.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn encrypt_key<T>(pub_key: &PKeyRef<T>, key: &[u8]) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPublic,
+{
+    let mut encrypter = Encrypter::new(pub_key)?;
+    encrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+    let mut encrypted = vec![0; encrypter.encrypt_len(key)?];
+    let len = encrypter.encrypt(key, &mut encrypted)?;
+    encrypted.truncate(len);
+    Ok(encrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsa::Rsa;
+
+    #[test]
+    fn seal_open() {
+        let alice = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let bob = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let cipher = Cipher::aes_128_cbc();
+        let data = b"hello, world!";
+
+        let mut seal = Seal::new(cipher, &[alice.clone(), bob.clone()]).unwrap();
+        assert_eq!(seal.encrypted_keys().len(), 2);
+        let mut encrypted = vec![0; data.len() + cipher.block_size()];
+        let mut len = seal.update(data, &mut encrypted).unwrap();
+        len += seal.finalize(&mut encrypted[len..]).unwrap();
+        encrypted.truncate(len);
+
+        for (pkey, encrypted_key) in [&alice, &bob].into_iter().zip(seal.encrypted_keys()) {
+            let mut open = Open::new(cipher, pkey, encrypted_key, seal.iv()).unwrap();
+            let mut decrypted = vec![0; encrypted.len() + cipher.block_size()];
+            let mut len = open.update(&encrypted, &mut decrypted).unwrap();
+            len += open.finalize(&mut decrypted[len..]).unwrap();
+            assert_eq!(&decrypted[..len], data);
+        }
+
+        assert!(Open::new(cipher, &alice, &seal.encrypted_keys()[1], seal.iv()).is_err());
+    }
+
+    #[test]
+    fn streaming() {
+        let pkey = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let cipher = Cipher::aes_256_cbc();
+        let data = (0..100_000).map(|i| i as u8).collect::<Vec<_>>();
+
+        let seal = Seal::new(cipher, &[pkey.clone()]).unwrap();
+        let iv = seal.iv().unwrap().to_vec();
+        let encrypted_key = seal.encrypted_keys()[0].clone();
+        let mut writer = SealWriter::new(seal, vec![]);
+        for chunk in data.chunks(1000) {
+            writer.write_all(chunk).unwrap();
+        }
+        let encrypted = writer.finish().unwrap();
+        assert_eq!(encrypted.len(), (data.len() / 16 + 1) * 16);
+
+        let open = Open::new(cipher, &pkey, &encrypted_key, Some(&iv)).unwrap();
+        let mut writer = OpenWriter::new(open, vec![]);
+        for chunk in encrypted.chunks(999) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), data);
+    }
+}
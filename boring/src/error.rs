@@ -15,7 +15,7 @@
 //!     Err(e) => println!("Parsing Error: {:?}", e),
 //! }
 //! ```
-use libc::{c_char, c_uint};
+use libc::{c_char, c_int, c_uint};
 use std::borrow::Cow;
 use std::error;
 use std::ffi::CStr;
@@ -55,6 +55,21 @@ impl ErrorStack {
     pub fn errors(&self) -> &[Error] {
         &self.0
     }
+
+    /// Returns `true` if any error in the stack is of the given kind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boring::error::ErrorKind;
+    /// use boring::x509::X509;
+    ///
+    /// let err = X509::from_pem(b"not a certificate").unwrap_err();
+    /// assert!(err.contains(ErrorKind::NoStartLine));
+    /// ```
+    pub fn contains(&self, kind: ErrorKind) -> bool {
+        self.0.iter().any(|err| err.kind() == kind)
+    }
 }
 
 impl fmt::Display for ErrorStack {
@@ -221,6 +236,43 @@ impl Error {
         self.line as u32
     }
 
+    /// Returns the library which reported the error.
+    pub fn library_kind(&self) -> Library {
+        Library::from_raw(self.library_code())
+    }
+
+    /// Returns the raw code of the library which reported the error, one of the `ERR_LIB_*`
+    /// constants.
+    ///
+    /// This corresponds to `ERR_GET_LIB`.
+    pub fn library_code(&self) -> c_int {
+        ffi::ERR_GET_LIB(self.code)
+    }
+
+    /// Returns the raw reason code of the error, specific to the library which reported it.
+    ///
+    /// This corresponds to `ERR_GET_REASON`.
+    pub fn reason_code(&self) -> c_int {
+        ffi::ERR_GET_REASON(self.code)
+    }
+
+    /// Returns the kind of the error, if it is one of the commonly handled ones.
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::from_raw(self.library_code(), self.reason_code())
+    }
+
+    /// Returns the source file and line which encountered the error, if known.
+    ///
+    /// Unlike [`Error::file`], this does not panic if the location was not recorded.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn location(&self) -> Option<(&'static str, u32)> {
+        if self.file.is_null() {
+            return None;
+        }
+
+        Some((self.file(), self.line as u32))
+    }
+
     /// Returns additional data describing the error.
     #[allow(clippy::option_as_ref_deref)]
     pub fn data(&self) -> Option<&str> {
@@ -264,3 +316,227 @@ impl fmt::Display for Error {
 }
 
 impl error::Error for Error {}
+
+/// A library of BoringSSL which reports errors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Library {
+    /// Operating system calls.
+    Sys,
+    /// Big numbers.
+    Bn,
+    /// RSA.
+    Rsa,
+    /// Finite field Diffie-Hellman.
+    Dh,
+    /// The `EVP` interface, e.g. `PKey` operations.
+    Evp,
+    /// PEM encoding.
+    Pem,
+    /// X.509 certificates.
+    X509,
+    /// ASN.1 encoding.
+    Asn1,
+    /// Elliptic curves.
+    Ec,
+    /// TLS.
+    Ssl,
+    /// I/O abstraction.
+    Bio,
+    /// PKCS #7.
+    Pkcs7,
+    /// PKCS #8 and PKCS #12.
+    Pkcs8,
+    /// X.509 certificate extensions.
+    X509v3,
+    /// ECDSA.
+    Ecdsa,
+    /// Message digests.
+    Digest,
+    /// Symmetric ciphers.
+    Cipher,
+    /// Any other library, identified by its raw code.
+    Other(c_int),
+}
+
+impl Library {
+    fn from_raw(code: c_int) -> Library {
+        let libraries = [
+            (ffi::ERR_LIB_SYS.0, Library::Sys),
+            (ffi::ERR_LIB_BN.0, Library::Bn),
+            (ffi::ERR_LIB_RSA.0, Library::Rsa),
+            (ffi::ERR_LIB_DH.0, Library::Dh),
+            (ffi::ERR_LIB_EVP.0, Library::Evp),
+            (ffi::ERR_LIB_PEM.0, Library::Pem),
+            (ffi::ERR_LIB_X509.0, Library::X509),
+            (ffi::ERR_LIB_ASN1.0, Library::Asn1),
+            (ffi::ERR_LIB_EC.0, Library::Ec),
+            (ffi::ERR_LIB_SSL.0, Library::Ssl),
+            (ffi::ERR_LIB_BIO.0, Library::Bio),
+            (ffi::ERR_LIB_PKCS7.0, Library::Pkcs7),
+            (ffi::ERR_LIB_PKCS8.0, Library::Pkcs8),
+            (ffi::ERR_LIB_X509V3.0, Library::X509v3),
+            (ffi::ERR_LIB_ECDSA.0, Library::Ecdsa),
+            (ffi::ERR_LIB_DIGEST.0, Library::Digest),
+            (ffi::ERR_LIB_CIPHER.0, Library::Cipher),
+        ];
+
+        libraries
+            .iter()
+            .find(|(raw, _)| *raw as c_int == code)
+            .map_or(Library::Other(code), |(_, library)| *library)
+    }
+}
+
+/// The kind of an [`Error`], for the errors which programs commonly need to handle.
+///
+/// This allows matching on errors without relying on their text, which may change between
+/// versions of BoringSSL.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The peer's certificate chain could not be verified.
+    CertificateVerifyFailed,
+    /// The peer sent a record with an unexpected protocol version, usually because it doesn't
+    /// speak TLS at all.
+    WrongVersionNumber,
+    /// The peer sent a plaintext HTTP request to a TLS endpoint.
+    HttpRequest,
+    /// A record failed to decrypt or authenticate.
+    BadRecordMac,
+    /// The peers don't support any common cipher suite.
+    NoSharedCipher,
+    /// The peers don't support any common protocol version.
+    UnsupportedProtocol,
+    /// The peer rejected the handshake with a `handshake_failure` alert.
+    HandshakeFailureAlert,
+    /// The peer rejected the handshake with a `protocol_version` alert.
+    ProtocolVersionAlert,
+    /// The client did not send a certificate although one was required.
+    PeerDidNotReturnCertificate,
+    /// A private key operation, such as one from a custom private key method, failed.
+    PrivateKeyOperationFailed,
+    /// The server's random value carried the TLS 1.3 downgrade signal, indicating that an
+    /// attacker forced the negotiation of an older protocol version.
+    ///
+    /// BoringSSL always enforces the signal, so clients fail the handshake instead of completing
+    /// it.
+    Tls13Downgrade,
+    /// The client signaled a fallback connection with `TLS_FALLBACK_SCSV` while the server
+    /// supports a higher protocol version.
+    InappropriateFallback,
+    /// The server rejected a fallback connection with an `inappropriate_fallback` alert.
+    InappropriateFallbackAlert,
+    /// No PEM data was found in the input.
+    NoStartLine,
+    /// Decryption failed, e.g. because of a wrong key or bad padding.
+    BadDecrypt,
+    /// Any other error.
+    Other,
+}
+
+impl ErrorKind {
+    fn from_raw(library: c_int, reason: c_int) -> ErrorKind {
+        let kinds = [
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_CERTIFICATE_VERIFY_FAILED,
+                ErrorKind::CertificateVerifyFailed,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_WRONG_VERSION_NUMBER,
+                ErrorKind::WrongVersionNumber,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_HTTP_REQUEST,
+                ErrorKind::HttpRequest,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_DECRYPTION_FAILED_OR_BAD_RECORD_MAC,
+                ErrorKind::BadRecordMac,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_NO_SHARED_CIPHER,
+                ErrorKind::NoSharedCipher,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_UNSUPPORTED_PROTOCOL,
+                ErrorKind::UnsupportedProtocol,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_SSLV3_ALERT_HANDSHAKE_FAILURE,
+                ErrorKind::HandshakeFailureAlert,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_TLSV1_ALERT_PROTOCOL_VERSION,
+                ErrorKind::ProtocolVersionAlert,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_PEER_DID_NOT_RETURN_A_CERTIFICATE,
+                ErrorKind::PeerDidNotReturnCertificate,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_PRIVATE_KEY_OPERATION_FAILED,
+                ErrorKind::PrivateKeyOperationFailed,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_TLS13_DOWNGRADE,
+                ErrorKind::Tls13Downgrade,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_INAPPROPRIATE_FALLBACK,
+                ErrorKind::InappropriateFallback,
+            ),
+            (
+                ffi::ERR_LIB_SSL.0,
+                ffi::SSL_R_TLSV1_ALERT_INAPPROPRIATE_FALLBACK,
+                ErrorKind::InappropriateFallbackAlert,
+            ),
+            (
+                ffi::ERR_LIB_PEM.0,
+                ffi::PEM_R_NO_START_LINE,
+                ErrorKind::NoStartLine,
+            ),
+            (
+                ffi::ERR_LIB_CIPHER.0,
+                ffi::CIPHER_R_BAD_DECRYPT,
+                ErrorKind::BadDecrypt,
+            ),
+        ];
+
+        kinds
+            .iter()
+            .find(|(lib, raw, _)| *lib as c_int == library && *raw == reason)
+            .map_or(ErrorKind::Other, |(_, _, kind)| *kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x509::X509;
+
+    #[test]
+    fn kind() {
+        let err = X509::from_pem(b"garbage").unwrap_err();
+        let first = &err.errors()[0];
+
+        assert_eq!(first.library_kind(), Library::Pem);
+        assert_eq!(first.kind(), ErrorKind::NoStartLine);
+        assert_eq!(first.reason_code(), ffi::PEM_R_NO_START_LINE);
+        assert!(first.location().is_some());
+        assert!(err.contains(ErrorKind::NoStartLine));
+        assert!(!err.contains(ErrorKind::BadDecrypt));
+    }
+}
@@ -4,6 +4,15 @@
 //!
 //! [OpenSSL's documentation]: https://www.openssl.org/docs/fips/UserGuide-2.0.pdf
 use crate::ffi;
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt;
+
+use crate::cvt;
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::symm::Cipher;
 
 /// Determines if the library is running in the FIPS 140-2 mode of operation.
 ///
@@ -12,6 +21,158 @@ pub fn enabled() -> bool {
     unsafe { ffi::FIPS_mode() != 0 }
 }
 
+/// Returns the name of the FIPS module, such as `BoringCrypto`.
+///
+/// This corresponds to `FIPS_module_name`.
+pub fn module_name() -> &'static str {
+    unsafe {
+        CStr::from_ptr(ffi::FIPS_module_name())
+            .to_str()
+            .expect("should be UTF-8")
+    }
+}
+
+/// Returns the version of the FIPS module, or 0 if the library is not running in FIPS mode.
+///
+/// This corresponds to `FIPS_version`.
+pub fn version() -> u32 {
+    unsafe { ffi::FIPS_version() }
+}
+
+/// Returns the SHA-256 digest, in lowercase hex, of the precompiled FIPS module linked in with
+/// the `fips-link-precompiled` feature, or `None` if none was linked.
+///
+/// This can be recorded as evidence of which certified module a binary contains.
+pub fn precompiled_module_sha256() -> Option<&'static str> {
+    ffi::PRECOMPILED_BCM_O_SHA256
+}
+
+/// Runs the known-answer self tests of the FIPS module.
+///
+/// The tests run automatically when the module is loaded; this allows running them again on
+/// demand, e.g. to produce compliance evidence.
+///
+/// This corresponds to `BORINGSSL_self_test`.
+pub fn self_test() -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+        cvt(ffi::BORINGSSL_self_test()).map(|_| ())
+    }
+}
+
+/// Checks that algorithms are approved before they are used in FIPS mode.
+///
+/// The guard records whether the library was running in FIPS mode when it was created. When it
+/// was, its methods return an error for algorithms which are not FIPS-approved; otherwise, all
+/// algorithms are accepted.
+///
+/// # Examples
+///
+/// ```
+/// use boring::fips::FipsGuard;
+/// use boring::hash::MessageDigest;
+/// use boring::symm::Cipher;
+///
+/// let guard = FipsGuard::new();
+/// let cipher = guard.cipher(Cipher::aes_256_gcm()).unwrap();
+/// let digest = guard.digest(MessageDigest::sha256()).unwrap();
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct FipsGuard {
+    active: bool,
+}
+
+impl Default for FipsGuard {
+    fn default() -> FipsGuard {
+        FipsGuard::new()
+    }
+}
+
+impl FipsGuard {
+    /// Creates a guard, active if the library is running in FIPS mode.
+    pub fn new() -> FipsGuard {
+        FipsGuard { active: enabled() }
+    }
+
+    /// Returns `true` if the guard rejects non-approved algorithms.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns `cipher` if it may be used.
+    ///
+    /// The approved ciphers are AES-128, AES-192 and AES-256 in the ECB, CBC, CTR, OFB and GCM
+    /// modes.
+    pub fn cipher(&self, cipher: Cipher) -> Result<Cipher, NotApproved> {
+        let approved = [
+            Cipher::aes_128_ecb(),
+            Cipher::aes_128_cbc(),
+            Cipher::aes_128_ctr(),
+            Cipher::aes_128_ofb(),
+            Cipher::aes_128_gcm(),
+            Cipher::aes_192_ecb(),
+            Cipher::aes_192_cbc(),
+            Cipher::aes_192_ctr(),
+            Cipher::aes_192_ofb(),
+            Cipher::aes_192_gcm(),
+            Cipher::aes_256_ecb(),
+            Cipher::aes_256_cbc(),
+            Cipher::aes_256_ctr(),
+            Cipher::aes_256_ofb(),
+            Cipher::aes_256_gcm(),
+        ];
+
+        if !self.active || approved.contains(&cipher) {
+            Ok(cipher)
+        } else {
+            let nid = unsafe { ffi::EVP_CIPHER_nid(cipher.as_ptr()) };
+            Err(NotApproved(Nid::from_raw(nid)))
+        }
+    }
+
+    /// Returns `digest` if it may be used.
+    ///
+    /// The approved digests are SHA-1, SHA-224, SHA-256, SHA-384, SHA-512 and SHA-512/256.
+    pub fn digest(&self, digest: MessageDigest) -> Result<MessageDigest, NotApproved> {
+        let approved = [
+            Nid::SHA1,
+            Nid::SHA224,
+            Nid::SHA256,
+            Nid::SHA384,
+            Nid::SHA512,
+            Nid::SHA512_256,
+        ];
+
+        if !self.active || approved.contains(&digest.type_()) {
+            Ok(digest)
+        } else {
+            Err(NotApproved(digest.type_()))
+        }
+    }
+}
+
+/// An error returned by [`FipsGuard`] for an algorithm which is not FIPS-approved.
+#[derive(Debug)]
+pub struct NotApproved(Nid);
+
+impl NotApproved {
+    /// Returns the identifier of the rejected algorithm.
+    pub fn nid(&self) -> Nid {
+        self.0
+    }
+}
+
+impl fmt::Display for NotApproved {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.short_name() {
+            Ok(name) => write!(fmt, "{} is not approved in FIPS mode", name),
+            Err(_) => fmt.write_str("algorithm is not approved in FIPS mode"),
+        }
+    }
+}
+
+impl Error for NotApproved {}
+
 #[test]
 fn is_enabled() {
     #[cfg(any(feature = "fips", feature = "fips-link-precompiled"))]
@@ -19,3 +180,41 @@ fn is_enabled() {
     #[cfg(not(any(feature = "fips", feature = "fips-link-precompiled")))]
     assert!(!enabled());
 }
+
+#[test]
+fn guard() {
+    let guard = FipsGuard::new();
+    assert_eq!(guard.is_active(), enabled());
+
+    guard.cipher(Cipher::aes_128_gcm()).unwrap();
+    guard.digest(MessageDigest::sha384()).unwrap();
+
+    let rc4 = guard.cipher(Cipher::rc4());
+    let md5 = guard.digest(MessageDigest::md5());
+    if guard.is_active() {
+        assert_eq!(rc4.unwrap_err().nid(), Nid::RC4);
+        assert_eq!(md5.unwrap_err().nid(), Nid::MD5);
+    } else {
+        rc4.unwrap();
+        md5.unwrap();
+    }
+}
+
+#[test]
+fn precompiled_module() {
+    #[cfg(feature = "fips-link-precompiled")]
+    assert_eq!(precompiled_module_sha256().unwrap().len(), 64);
+    #[cfg(not(feature = "fips-link-precompiled"))]
+    assert!(precompiled_module_sha256().is_none());
+}
+
+#[test]
+fn module() {
+    assert!(!module_name().is_empty());
+    if enabled() {
+        assert_ne!(version(), 0);
+        self_test().unwrap();
+    } else {
+        assert_eq!(version(), 0);
+    }
+}
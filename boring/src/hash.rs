@@ -1,5 +1,6 @@
 use crate::ffi;
 use std::convert::TryInto;
+use std::ffi::CString;
 use std::fmt;
 use std::io;
 use std::io::prelude::*;
@@ -40,6 +41,28 @@ impl MessageDigest {
         }
     }
 
+    /// Returns the `MessageDigest` with the given short or long name, such as `"SHA256"`.
+    ///
+    /// This corresponds to [`EVP_get_digestbyname`].
+    ///
+    /// [`EVP_get_digestbyname`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/digest.h.html#EVP_get_digestbyname
+    pub fn from_name(name: &str) -> Option<MessageDigest> {
+        let name = CString::new(name).ok()?;
+        unsafe {
+            let ptr = ffi::EVP_get_digestbyname(name.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(MessageDigest(ptr))
+            }
+        }
+    }
+
+    /// MD4. This is insecure and only provided for legacy protocols.
+    pub fn md4() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_md4()) }
+    }
+
     pub fn md5() -> MessageDigest {
         unsafe { MessageDigest(ffi::EVP_md5()) }
     }
@@ -68,6 +91,20 @@ impl MessageDigest {
         unsafe { MessageDigest(ffi::EVP_sha512_256()) }
     }
 
+    /// The concatenation of MD5 and SHA-1, as used in the TLS 1.0 and 1.1 PRF and signatures.
+    pub fn md5_sha1() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_md5_sha1()) }
+    }
+
+    /// BLAKE2b with a 256-bit output.
+    ///
+    /// This corresponds to [`EVP_blake2b256`].
+    ///
+    /// [`EVP_blake2b256`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/digest.h.html#EVP_blake2b256
+    pub fn blake2b256() -> MessageDigest {
+        unsafe { MessageDigest(ffi::EVP_blake2b256()) }
+    }
+
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn as_ptr(&self) -> *const ffi::EVP_MD {
         self.0
@@ -79,6 +116,12 @@ impl MessageDigest {
         unsafe { ffi::EVP_MD_size(self.0) }
     }
 
+    /// The block size of the digest in bytes.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn block_size(&self) -> usize {
+        unsafe { ffi::EVP_MD_block_size(self.0) }
+    }
+
     /// The name of the digest.
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn type_(&self) -> Nid {
@@ -486,6 +529,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_md4() {
+        let tests = [("616263", "a448017aaf21d8525fc10ae87aa6729d")];
+
+        for test in tests.iter() {
+            hash_test(MessageDigest::md4(), test);
+        }
+    }
+
+    #[test]
+    fn test_md5_sha1() {
+        let tests = [(
+            "616263",
+            "900150983cd24fb0d6963f7d28e17f72a9993e364706816aba3e25717850c26c9cd0d89d",
+        )];
+
+        for test in tests.iter() {
+            hash_test(MessageDigest::md5_sha1(), test);
+        }
+    }
+
+    #[test]
+    fn test_blake2b256() {
+        let tests = [(
+            "616263",
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+        )];
+
+        for test in tests.iter() {
+            hash_test(MessageDigest::blake2b256(), test);
+        }
+        assert_eq!(MessageDigest::blake2b256().size(), 32);
+        assert_eq!(MessageDigest::blake2b256().block_size(), 128);
+    }
+
+    #[test]
+    fn from_name() {
+        assert_eq!(
+            MessageDigest::from_name("SHA256").unwrap().as_ptr(),
+            MessageDigest::sha256().as_ptr()
+        );
+        assert_eq!(
+            MessageDigest::from_name("sha512").unwrap().as_ptr(),
+            MessageDigest::sha512().as_ptr()
+        );
+        assert!(MessageDigest::from_name("foobar").is_none());
+    }
+
     #[test]
     fn from_nid() {
         assert_eq!(
@@ -0,0 +1,173 @@
+//! HMAC-based key derivation, as specified in [RFC 5869].
+//!
+//! [RFC 5869]: https://datatracker.ietf.org/doc/html/rfc5869
+//!
+//! # Examples
+//!
+//! ```rust
+//! use boring::hash::MessageDigest;
+//! use boring::hkdf;
+//!
+//! let mut key = [0; 32];
+//! hkdf::hkdf(
+//!     MessageDigest::sha256(),
+//!     b"input keying material",
+//!     b"salt",
+//!     b"context",
+//!     &mut key,
+//! )
+//! .unwrap();
+//! ```
+use crate::ffi;
+
+use crate::cvt;
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+
+/// Derives `key.len()` bytes of keying material from `secret`, `salt` and `info`.
+///
+/// This performs both the extract and expand steps. `salt` and `info` may be empty.
+///
+/// Returns an error if `key` is longer than 255 times the output size of `digest`.
+///
+/// This corresponds to [`HKDF`].
+///
+/// [`HKDF`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/hkdf.h.html#HKDF
+pub fn hkdf(
+    digest: MessageDigest,
+    secret: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    key: &mut [u8],
+) -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+        cvt(ffi::HKDF(
+            key.as_mut_ptr(),
+            key.len(),
+            digest.as_ptr(),
+            secret.as_ptr(),
+            secret.len(),
+            salt.as_ptr(),
+            salt.len(),
+            info.as_ptr(),
+            info.len(),
+        ))
+        .map(|_| ())
+    }
+}
+
+/// Computes a pseudorandom key from `secret` and `salt`.
+///
+/// The key is as long as the output of `digest`. `salt` may be empty.
+///
+/// This corresponds to [`HKDF_extract`].
+///
+/// [`HKDF_extract`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/hkdf.h.html#HKDF_extract
+pub fn extract(digest: MessageDigest, secret: &[u8], salt: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    unsafe {
+        ffi::init();
+        let mut prk = vec![0; ffi::EVP_MAX_MD_SIZE as usize];
+        let mut len = 0;
+        cvt(ffi::HKDF_extract(
+            prk.as_mut_ptr(),
+            &mut len,
+            digest.as_ptr(),
+            secret.as_ptr(),
+            secret.len(),
+            salt.as_ptr(),
+            salt.len(),
+        ))?;
+        prk.truncate(len);
+        Ok(prk)
+    }
+}
+
+/// Derives `key.len()` bytes of keying material from the pseudorandom key `prk` and `info`.
+///
+/// `prk` should be the output of [`extract`], or another uniformly random key at least as long as
+/// the output of `digest`. `info` may be empty.
+///
+/// Returns an error if `key` is longer than 255 times the output size of `digest`.
+///
+/// This corresponds to [`HKDF_expand`].
+///
+/// [`HKDF_expand`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/hkdf.h.html#HKDF_expand
+pub fn expand(
+    digest: MessageDigest,
+    prk: &[u8],
+    info: &[u8],
+    key: &mut [u8],
+) -> Result<(), ErrorStack> {
+    unsafe {
+        ffi::init();
+        cvt(ffi::HKDF_expand(
+            key.as_mut_ptr(),
+            key.len(),
+            digest.as_ptr(),
+            prk.as_ptr(),
+            prk.len(),
+            info.as_ptr(),
+            info.len(),
+        ))
+        .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 5869, appendix A.1
+    #[test]
+    fn hkdf_sha256() {
+        let secret = [0x0b; 22];
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+
+        let prk = extract(MessageDigest::sha256(), &secret, &salt).unwrap();
+        assert_eq!(
+            hex::encode(&prk),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+        );
+
+        let okm =
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+
+        let mut key = [0; 42];
+        expand(MessageDigest::sha256(), &prk, &info, &mut key).unwrap();
+        assert_eq!(hex::encode(key), okm);
+
+        let mut key = [0; 42];
+        hkdf(MessageDigest::sha256(), &secret, &salt, &info, &mut key).unwrap();
+        assert_eq!(hex::encode(key), okm);
+    }
+
+    // RFC 5869, appendix A.3
+    #[test]
+    fn hkdf_sha256_empty_salt_and_info() {
+        let secret = [0x0b; 22];
+
+        let prk = extract(MessageDigest::sha256(), &secret, &[]).unwrap();
+        assert_eq!(
+            hex::encode(prk),
+            "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04"
+        );
+
+        let mut key = [0; 42];
+        hkdf(MessageDigest::sha256(), &secret, &[], &[], &mut key).unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+    }
+
+    #[test]
+    fn hkdf_output_too_long() {
+        let mut key = vec![0; 255 * 32 + 1];
+        assert!(hkdf(MessageDigest::sha256(), b"secret", b"", b"", &mut key).is_err());
+
+        let mut key = vec![0; 255 * 32];
+        hkdf(MessageDigest::sha256(), b"secret", b"", b"", &mut key).unwrap();
+    }
+}
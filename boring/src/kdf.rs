@@ -0,0 +1,353 @@
+//! Key derivation.
+//!
+//! This module provides the password-based scrypt, as specified in [RFC 7914], and PBKDF2, as
+//! specified in [RFC 8018]. Parameters are validated before calling into BoringSSL, so that
+//! invalid or excessively expensive settings are reported as a [`KdfError`] describing the
+//! problem.
+//!
+//! It also provides the ANSI X9.63 KDF, as specified in [SEC 1] section 3.6.1, which derives keys
+//! from elliptic curve Diffie-Hellman shared secrets in ECIES schemes.
+//!
+//! [RFC 7914]: https://datatracker.ietf.org/doc/html/rfc7914
+//! [RFC 8018]: https://datatracker.ietf.org/doc/html/rfc8018
+//! [SEC 1]: https://www.secg.org/sec1-v2.pdf
+//!
+//! # Examples
+//!
+//! ```rust
+//! use boring::kdf::{self, ScryptParams};
+//!
+//! let params = ScryptParams::new(1 << 14, 8, 1).unwrap();
+//! let mut key = [0; 32];
+//! kdf::scrypt(b"password", b"salt", &params, &mut key).unwrap();
+//! ```
+use std::error::Error;
+use std::fmt;
+
+use crate::error::ErrorStack;
+use crate::hash::{Hasher, MessageDigest};
+use crate::pkcs5;
+
+/// The memory limit used by BoringSSL when none is specified, in bytes.
+const DEFAULT_MAX_MEMORY: u64 = 32 * 1024 * 1024;
+
+/// The maximum value of `r * p`, as specified in RFC 7914.
+const MAX_PR: u64 = (1 << 30) - 1;
+
+/// Cost parameters for [`scrypt`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScryptParams {
+    n: u64,
+    r: u64,
+    p: u64,
+    max_memory: u64,
+}
+
+impl ScryptParams {
+    /// Creates parameters with CPU/memory cost `n`, block size `r` and parallelization `p`.
+    ///
+    /// `n` must be a power of two greater than 1 and less than `2^(16 * r)`, and `r` and `p`
+    /// must be nonzero with `r * p < 2^30`.
+    ///
+    /// The memory limit defaults to 32 MiB; see [`ScryptParams::set_max_memory`].
+    pub fn new(n: u64, r: u64, p: u64) -> Result<ScryptParams, KdfError> {
+        if n < 2 || !n.is_power_of_two() {
+            return Err(KdfError::InvalidParameters(
+                "N must be a power of two greater than 1",
+            ));
+        }
+        if r == 0 || p == 0 {
+            return Err(KdfError::InvalidParameters("r and p must be nonzero"));
+        }
+        if p > MAX_PR / r {
+            return Err(KdfError::InvalidParameters("r * p must be less than 2^30"));
+        }
+        if 16 * r <= 63 && n >= 1 << (16 * r) {
+            return Err(KdfError::InvalidParameters(
+                "N must be less than 2^(16 * r)",
+            ));
+        }
+
+        Ok(ScryptParams {
+            n,
+            r,
+            p,
+            max_memory: DEFAULT_MAX_MEMORY,
+        })
+    }
+
+    /// Returns the CPU/memory cost parameter `N`.
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+
+    /// Returns the block size parameter `r`.
+    pub fn r(&self) -> u64 {
+        self.r
+    }
+
+    /// Returns the parallelization parameter `p`.
+    pub fn p(&self) -> u64 {
+        self.p
+    }
+
+    /// Returns the maximum amount of memory, in bytes, that key derivation may use.
+    pub fn max_memory(&self) -> u64 {
+        self.max_memory
+    }
+
+    /// Sets the maximum amount of memory, in bytes, that key derivation may use.
+    ///
+    /// [`scrypt`] fails with [`KdfError::MemoryLimitExceeded`] if the parameters require more.
+    pub fn set_max_memory(&mut self, max_memory: u64) -> &mut ScryptParams {
+        self.max_memory = max_memory;
+        self
+    }
+
+    /// Returns the amount of memory, in bytes, that key derivation with these parameters uses.
+    ///
+    /// Returns `None` if the amount does not fit in a `u64`.
+    pub fn memory_required(&self) -> Option<u64> {
+        self.n
+            .checked_add(self.p)?
+            .checked_add(1)?
+            .checked_mul(self.r)?
+            .checked_mul(128)
+    }
+}
+
+/// Derives `key.len()` bytes of keying material from `pass` and `salt` using scrypt.
+///
+/// This validates the memory limit and calls [`pkcs5::scrypt`].
+///
+/// This corresponds to [`EVP_PBE_scrypt`].
+///
+/// [`EVP_PBE_scrypt`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/evp.h.html#EVP_PBE_scrypt
+pub fn scrypt(
+    pass: &[u8],
+    salt: &[u8],
+    params: &ScryptParams,
+    key: &mut [u8],
+) -> Result<(), KdfError> {
+    let required = params.memory_required();
+    if required.is_none_or(|required| required > params.max_memory) {
+        return Err(KdfError::MemoryLimitExceeded {
+            required,
+            limit: params.max_memory,
+        });
+    }
+
+    pkcs5::scrypt(
+        pass,
+        salt,
+        params.n,
+        params.r,
+        params.p,
+        usize::try_from(params.max_memory).unwrap_or(usize::MAX),
+        key,
+    )?;
+
+    Ok(())
+}
+
+/// Derives `key.len()` bytes of keying material from `pass` and `salt` using PBKDF2 with HMAC
+/// and `digest`.
+///
+/// `iterations` must be nonzero. Otherwise, this is equivalent to [`pkcs5::pbkdf2_hmac`].
+///
+/// This corresponds to [`PKCS5_PBKDF2_HMAC`].
+///
+/// [`PKCS5_PBKDF2_HMAC`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/evp.h.html#PKCS5_PBKDF2_HMAC
+pub fn pbkdf2_hmac(
+    pass: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    digest: MessageDigest,
+    key: &mut [u8],
+) -> Result<(), KdfError> {
+    if iterations == 0 {
+        return Err(KdfError::InvalidParameters(
+            "the iteration count must be nonzero",
+        ));
+    }
+
+    pkcs5::pbkdf2_hmac(pass, salt, iterations as usize, digest, key)?;
+
+    Ok(())
+}
+
+/// Derives `key.len()` bytes of keying material from the shared secret `z` and `shared_info`
+/// using the ANSI X9.63 KDF with `digest`.
+///
+/// The output must be shorter than `2^32 - 1` digest lengths.
+pub fn x963_kdf(
+    digest: MessageDigest,
+    z: &[u8],
+    shared_info: &[u8],
+    key: &mut [u8],
+) -> Result<(), KdfError> {
+    let max_len = (u32::MAX as u64 - 1) * digest.size() as u64;
+    if key.len() as u64 > max_len {
+        return Err(KdfError::InvalidParameters(
+            "the output must be shorter than 2^32 - 1 digest lengths",
+        ));
+    }
+
+    for (counter, chunk) in (1u32..).zip(key.chunks_mut(digest.size())) {
+        let mut hasher = Hasher::new(digest)?;
+        hasher.update(z)?;
+        hasher.update(&counter.to_be_bytes())?;
+        hasher.update(shared_info)?;
+        let block = hasher.finish()?;
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+
+    Ok(())
+}
+
+/// An error deriving a key.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum KdfError {
+    /// The parameters are invalid.
+    InvalidParameters(&'static str),
+    /// The parameters require more memory than allowed.
+    MemoryLimitExceeded {
+        /// The amount of memory required, in bytes, or `None` if it does not fit in a `u64`.
+        required: Option<u64>,
+        /// The memory limit, in bytes.
+        limit: u64,
+    },
+    /// BoringSSL failed to derive the key.
+    Stack(ErrorStack),
+}
+
+impl From<ErrorStack> for KdfError {
+    fn from(e: ErrorStack) -> KdfError {
+        KdfError::Stack(e)
+    }
+}
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KdfError::InvalidParameters(reason) => write!(fmt, "invalid parameters: {}", reason),
+            KdfError::MemoryLimitExceeded {
+                required: Some(required),
+                limit,
+            } => write!(
+                fmt,
+                "key derivation requires {} bytes of memory, more than the limit of {}",
+                required, limit
+            ),
+            KdfError::MemoryLimitExceeded {
+                required: None,
+                limit,
+            } => write!(
+                fmt,
+                "key derivation requires more memory than the limit of {} bytes",
+                limit
+            ),
+            KdfError::Stack(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl Error for KdfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            KdfError::Stack(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 7914, section 12
+    #[test]
+    fn scrypt_rfc7914() {
+        let params = ScryptParams::new(16, 1, 1).unwrap();
+        let mut key = [0; 64];
+        scrypt(b"", b"", &params, &mut key).unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+             fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+
+        let mut params = ScryptParams::new(1024, 8, 16).unwrap();
+        assert_eq!(params.memory_required(), Some(128 * 8 * (1024 + 16 + 1)));
+        params.set_max_memory(params.memory_required().unwrap());
+        let mut key = [0; 64];
+        scrypt(b"password", b"NaCl", &params, &mut key).unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+             2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+    }
+
+    #[test]
+    fn scrypt_params() {
+        assert!(ScryptParams::new(1, 8, 1).is_err());
+        assert!(ScryptParams::new(1000, 8, 1).is_err());
+        assert!(ScryptParams::new(1024, 0, 1).is_err());
+        assert!(ScryptParams::new(1024, 8, 0).is_err());
+        assert!(ScryptParams::new(1024, 1 << 15, 1 << 15).is_err());
+        assert!(ScryptParams::new(1 << 16, 1, 1).is_err());
+        assert!(ScryptParams::new(1 << 15, 1, 1).is_ok());
+    }
+
+    #[test]
+    fn scrypt_memory_limit() {
+        let mut params = ScryptParams::new(1 << 20, 8, 1).unwrap();
+        let mut key = [0; 32];
+        match scrypt(b"password", b"salt", &params, &mut key) {
+            Err(KdfError::MemoryLimitExceeded { required, limit }) => {
+                assert_eq!(required, params.memory_required());
+                assert_eq!(limit, 32 * 1024 * 1024);
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        let mut params = ScryptParams::new(16, 1, 1).unwrap();
+        params.set_max_memory(1024);
+        assert!(scrypt(b"password", b"salt", &params, &mut key).is_err());
+        params.set_max_memory(128 * (16 + 1 + 1));
+        scrypt(b"password", b"salt", &params, &mut key).unwrap();
+    }
+
+    // RFC 6070, section 2
+    #[test]
+    fn pbkdf2_hmac_sha1() {
+        let mut key = [0; 20];
+        pbkdf2_hmac(b"password", b"salt", 4096, MessageDigest::sha1(), &mut key).unwrap();
+        assert_eq!(hex::encode(key), "4b007901b765489abead49d926f721d065a429c1");
+
+        assert!(pbkdf2_hmac(b"password", b"salt", 0, MessageDigest::sha1(), &mut key).is_err());
+    }
+
+    // NIST CAVS ANSI X9.63 KDF vectors
+    #[test]
+    fn x963_kdf_sha256() {
+        let z = hex::decode("96c05619d56c328ab95fe84b18264b08725b85e33fd34f08").unwrap();
+        let mut key = [0; 16];
+        x963_kdf(MessageDigest::sha256(), &z, &[], &mut key).unwrap();
+        assert_eq!(hex::encode(key), "443024c3dae66b95e6f5670601558f71");
+
+        let z = hex::decode("22518b10e70f2a3f243810ae3254139efbee04aa57c7af7d").unwrap();
+        let shared_info = hex::decode("75eef81aa3041e33b80971203d2c0c52").unwrap();
+        let mut key = [0; 128];
+        x963_kdf(MessageDigest::sha256(), &z, &shared_info, &mut key).unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "c498af77161cc59f2962b9a713e2b215152d139766ce34a776df11866a69bf2e\
+             52a13d9c7c6fc878c50c5ea0bc7b00e0da2447cfd874f6cf92f30d0097111485\
+             500c90c3af8b487872d04685d14c8d1dc8d7fa08beb0ce0ababc11f0bd496269\
+             142d43525a78e5bc79a17f59676a5706dc54d54d4d1f0bd7e386128ec26afc21"
+        );
+    }
+}
@@ -30,6 +30,58 @@
 //! In alternative a different path for the BoringSSL source code directory can be specified by setting `BORING_BSSL_SOURCE_PATH` which will automatically be compiled during the build process.
 //!
 //! _Warning_: When providing a different version of BoringSSL make sure to use a compatible one, the crate relies on the presence of certain functions.
+//! The build checks that the headers define `BORINGSSL_API_VERSION` and declare the functions the crate depends on,
+//! and fails with an error naming any missing ones.
+//!
+//! ## Linking a system BoringSSL
+//!
+//! Large workspaces may want to share a single BoringSSL build between crates. With the `pkg-config` feature enabled,
+//! and neither `BORING_BSSL_PATH` nor `BORING_BSSL_SOURCE_PATH` set, the crate asks `pkg-config` for the `boringssl`
+//! package and links against it instead of building BoringSSL. A different package name can be given with
+//! `BORING_BSSL_PKG_CONFIG_NAME`, and `BORING_BSSL_INCLUDE_PATH` overrides the include directory it reports.
+//!
+//! The libraries are linked as found, statically or dynamically. Features which require building BoringSSL from
+//! sources, such as `fips-link-precompiled`, `rpk` or `pq-experimental`, can't be combined with a system BoringSSL.
+//!
+//! ## Avoiding symbol conflicts with OpenSSL
+//!
+//! BoringSSL exports many of the same symbol names as OpenSSL, so a binary that also links OpenSSL, for
+//! instance through another C dependency, may call into the wrong library and crash. The `prefix-symbols`
+//! feature builds BoringSSL with every symbol prefixed by `boring_sys_<version>_` and points the bindings at
+//! the prefixed names, so both libraries can coexist. Symbols are listed with `nm`, which must be available at
+//! build time. The feature requires building BoringSSL from sources and isn't supported on MSVC targets or
+//! with `fips-link-precompiled`.
+//!
+//! ## Minimal builds
+//!
+//! Only libcrypto and libssl are ever built, without BoringSSL's tests, tools or `decrepit` library. For embedded
+//! images where binary size matters, the `minimal` feature additionally builds them optimized for size: with the
+//! `MinSizeRel` CMake profile, `OPENSSL_SMALL`, which drops precomputed tables at some cost in speed, and with
+//! per-function sections so that the linker can discard unused code. The API is unchanged.
+//!
+//! `boring-sys` reports what the linked BoringSSL provides to this crate, which enables the corresponding
+//! `boringssl_ssl`, `boringssl_fips`, `boringssl_rpk`, `boringssl_pq_experimental` and `boringssl_minimal` cfgs.
+//! For example, the `ssl` module is only available with `boringssl_ssl`.
+//!
+//! ## Cross-compilation
+//!
+//! When building BoringSSL from sources for another target, the build script configures CMake for
+//! it:
+//!
+//! - Android: the NDK is located through `ANDROID_NDK_HOME`, `ANDROID_NDK_ROOT` or `ANDROID_NDK`,
+//!   and the API level defaults to 21 and can be raised with `ANDROID_NATIVE_API_LEVEL`.
+//! - iOS and tvOS: the SDK is selected from the target, and simulator targets pass the simulator
+//!   target triple to both the C compiler and the assembler.
+//! - Linux: 32-bit x86, ARMv7 and AArch64 use bundled toolchain files, while other libcs on the host
+//!   architecture, such as `x86_64-unknown-linux-musl`, only need `CC_<target>` to point at the
+//!   target's C compiler.
+//!
+//! ## WASI
+//!
+//! The crate can be built for `wasm32-wasi` with the [WASI SDK](https://github.com/WebAssembly/wasi-sdk), whose
+//! location must be given by `WASI_SDK_PATH`. Only BoringSSL's portable C code is built, without thread support, so
+//! the library must not be used from several threads. libssl isn't built, so the `ssl` module and APIs tied to it,
+//! such as `X509StoreContext::ssl_idx`, are unavailable; the cryptographic primitives work as on other targets.
 //!
 //! ## Building with a FIPS-validated module
 //!
@@ -49,6 +101,11 @@
 //! compilation feature and provide a `BORING_SSL_PRECOMPILED_BCM_O` env variable with a path to the
 //! precompiled FIPS-validated `bcm.o` module.
 //!
+//! To make sure the expected certified module is linked, set `BORING_SSL_PRECOMPILED_BCM_O_SHA256` to
+//! its SHA-256 digest; the build fails if the file's digest differs. The build reports the path and
+//! digest of the linked module as a warning, and [`fips::precompiled_module_sha256`] returns the
+//! digest at runtime. Headers matching the module can be provided with `BORING_BSSL_INCLUDE_PATH`.
+//!
 //! # Optional patches
 //!
 //! ## Raw Public Key
@@ -56,6 +113,22 @@
 //! The crate can be compiled with [RawPublicKey](https://datatracker.ietf.org/doc/html/rfc7250)
 //! support by turning on `rpk` compilation feature.
 //!
+//! # Platform trust store
+//!
+//! The trusted root certificates of the platform can be loaded into an `X509Store` by turning on
+//! the `native-roots` compilation feature, which enables the [`trust`] module.
+//!
+//! Alternatively, the `webpki-roots` compilation feature bundles the Mozilla root certificates in
+//! the binary, and enables [`SslConnectorBuilder::use_webpki_roots`].
+//!
+//! [`SslConnectorBuilder::use_webpki_roots`]: ssl::SslConnectorBuilder::use_webpki_roots
+//!
+//! # PKCS#11 keys
+//!
+//! Private keys held by an HSM or another PKCS#11 token can be used by handshakes, without an
+//! async runtime, by turning on the `pkcs11` compilation feature, which enables
+//! `ssl::Pkcs11PrivateKey`.
+//!
 //! ## Experimental post-quantum cryptography
 //!
 //! The crate can be compiled with [post-quantum cryptography](https://blog.cloudflare.com/post-quantum-for-all/)
@@ -100,37 +173,64 @@ mod macros;
 mod bio;
 #[macro_use]
 mod util;
+pub mod aead;
 pub mod aes;
 pub mod asn1;
 pub mod base64;
 pub mod bn;
+pub mod cmac;
 pub mod conf;
 pub mod derive;
 pub mod dh;
 pub mod dsa;
 pub mod ec;
 pub mod ecdsa;
+pub mod encrypt;
+pub mod envelope;
 pub mod error;
 pub mod ex_data;
 pub mod fips;
 pub mod hash;
+pub mod hkdf;
+pub mod kdf;
 pub mod memcmp;
 pub mod nid;
+pub mod pem;
 pub mod pkcs12;
 pub mod pkcs5;
+pub mod pkcs7;
 pub mod pkey;
+pub mod poly1305;
 pub mod rand;
 pub mod rsa;
+pub mod sct;
 pub mod sha;
 pub mod sign;
 pub mod srtp;
+#[cfg(boringssl_ssl)]
 pub mod ssl;
 pub mod stack;
 pub mod string;
 pub mod symm;
+#[cfg(feature = "native-roots")]
+pub mod trust;
+pub mod trust_token;
 pub mod version;
 pub mod x509;
 
+/// Returns metadata about the BoringSSL linked into this crate: its version, the commit it was
+/// built from, the build features and the FIPS status.
+///
+/// # Examples
+///
+/// ```
+/// let info = boring::version();
+/// println!("{} ({:?})", info.version, info.commit);
+/// ```
+pub fn version() -> version::VersionInfo {
+    version::info()
+}
+
 fn cvt_p<T>(r: *mut T) -> Result<*mut T, ErrorStack> {
     if r.is_null() {
         Err(ErrorStack::get())
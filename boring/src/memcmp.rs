@@ -1,4 +1,4 @@
-//! Utilities to safely compare cryptographic values.
+//! Utilities to safely compare and store cryptographic values.
 //!
 //! Extra care must be taken when comparing values in
 //! cryptographic code. If done incorrectly, it can lead
@@ -8,7 +8,8 @@
 //! cryptosystem.
 //!
 //! The utilities in this module are designed to be resistant
-//! to this type of attack.
+//! to this type of attack. [`SecretBox`] additionally wipes secret
+//! bytes from memory once they are no longer needed.
 //!
 //! # Examples
 //!
@@ -31,6 +32,9 @@
 //! ```
 use crate::ffi;
 use libc::size_t;
+use std::fmt;
+use std::hint::black_box;
+use std::ops::{Deref, DerefMut};
 
 /// Returns `true` iff `a` and `b` contain the same bytes.
 ///
@@ -73,9 +77,136 @@ pub fn eq(a: &[u8], b: &[u8]) -> bool {
     ret == 0
 }
 
+/// Returns `true` iff `a` and `b` are equal, in constant time.
+pub fn eq_u64(a: u64, b: u64) -> bool {
+    let x = black_box(a ^ b);
+    // The top bit of `x | -x` is set iff `x` is nonzero.
+    ((x | x.wrapping_neg()) >> 63) == 0
+}
+
+/// Returns `a` if `choice` is `true` and `b` otherwise, in constant time.
+pub fn select_u8(choice: bool, a: u8, b: u8) -> u8 {
+    let mask = black_box(choice as u8).wrapping_neg();
+    (mask & a) | (!mask & b)
+}
+
+/// Copies `a` into `out` if `choice` is `true` and `b` otherwise, in constant time.
+///
+/// # Panics
+///
+/// This function will panic if `a`, `b` and `out` do not have the same length.
+pub fn select(choice: bool, a: &[u8], b: &[u8], out: &mut [u8]) {
+    assert!(a.len() == b.len() && a.len() == out.len());
+    let mask = black_box(choice as u8).wrapping_neg();
+    for ((out, a), b) in out.iter_mut().zip(a).zip(b) {
+        *out = (mask & a) | (!mask & b);
+    }
+}
+
+/// A heap-allocated byte buffer which is zeroed when dropped.
+///
+/// Comparisons between `SecretBox`es are performed in constant time, and the contents are not
+/// printed by its `Debug` implementation.
+///
+/// # Examples
+///
+/// ```
+/// use boring::memcmp::SecretBox;
+/// use boring::rand::rand_bytes;
+///
+/// let mut key = SecretBox::new(32);
+/// rand_bytes(&mut key).unwrap();
+/// ```
+pub struct SecretBox(Box<[u8]>);
+
+impl SecretBox {
+    /// Returns a zeroed buffer of `len` bytes.
+    pub fn new(len: usize) -> SecretBox {
+        SecretBox(vec![0; len].into_boxed_slice())
+    }
+
+    /// Returns a buffer containing a copy of `data`.
+    pub fn from_slice(data: &[u8]) -> SecretBox {
+        SecretBox(data.into())
+    }
+}
+
+impl From<Vec<u8>> for SecretBox {
+    /// Moves `data` into a new buffer, zeroing the original allocation.
+    fn from(mut data: Vec<u8>) -> SecretBox {
+        let secret = SecretBox::from_slice(&data);
+        data.resize(data.capacity(), 0);
+        cleanse(&mut data);
+        secret
+    }
+}
+
+impl Drop for SecretBox {
+    fn drop(&mut self) {
+        cleanse(&mut self.0);
+    }
+}
+
+impl Deref for SecretBox {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretBox {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl AsRef<[u8]> for SecretBox {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for SecretBox {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl Clone for SecretBox {
+    fn clone(&self) -> SecretBox {
+        SecretBox::from_slice(&self.0)
+    }
+}
+
+impl PartialEq for SecretBox {
+    fn eq(&self, other: &SecretBox) -> bool {
+        self.0.len() == other.0.len() && eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SecretBox {}
+
+impl fmt::Debug for SecretBox {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "SecretBox([REDACTED; {}])", self.0.len())
+    }
+}
+
+/// Overwrites `buf` with zeroes in a way the compiler will not optimize away.
+///
+/// This corresponds to [`OPENSSL_cleanse`].
+///
+/// [`OPENSSL_cleanse`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/mem.h.html#OPENSSL_cleanse
+pub fn cleanse(buf: &mut [u8]) {
+    unsafe {
+        ffi::OPENSSL_cleanse(buf.as_mut_ptr() as *mut _, buf.len());
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::eq;
+    use super::*;
 
     #[test]
     fn test_eq() {
@@ -89,4 +220,41 @@ mod tests {
     fn test_diff_lens() {
         eq(&[], &[1]);
     }
+
+    #[test]
+    fn test_eq_u64() {
+        assert!(eq_u64(0, 0));
+        assert!(eq_u64(u64::MAX, u64::MAX));
+        assert!(!eq_u64(0, 1 << 63));
+        assert!(!eq_u64(1, 0));
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(select_u8(true, 0xaa, 0x55), 0xaa);
+        assert_eq!(select_u8(false, 0xaa, 0x55), 0x55);
+
+        let mut out = [0; 3];
+        select(true, &[1, 2, 3], &[4, 5, 6], &mut out);
+        assert_eq!(out, [1, 2, 3]);
+        select(false, &[1, 2, 3], &[4, 5, 6], &mut out);
+        assert_eq!(out, [4, 5, 6]);
+    }
+
+    #[test]
+    fn test_secret_box() {
+        let mut a = SecretBox::new(4);
+        assert_eq!(&*a, &[0; 4]);
+        a.copy_from_slice(&[1, 2, 3, 4]);
+
+        let b = SecretBox::from(vec![1, 2, 3, 4]);
+        assert_eq!(a, b);
+        assert_ne!(a, SecretBox::from_slice(&[1, 2, 3]));
+        assert_ne!(a, SecretBox::from_slice(&[1, 2, 3, 5]));
+        assert_eq!(format!("{:?}", a), "SecretBox([REDACTED; 4])");
+
+        let mut buf = [1, 2, 3];
+        cleanse(&mut buf);
+        assert_eq!(buf, [0; 3]);
+    }
 }
@@ -2,8 +2,9 @@
 use crate::ffi;
 use libc::{c_char, c_int};
 
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::str;
+use std::sync::Mutex;
 
 use crate::cvt_p;
 use crate::error::ErrorStack;
@@ -103,6 +104,45 @@ impl Nid {
         }
     }
 
+    /// Registers an object identifier with a short and a long name, returning its `Nid`.
+    ///
+    /// This lets private extensions and attributes be displayed by name, e.g. by
+    /// [`X509Ref::to_text`] and the `Display` implementation of [`Asn1ObjectRef`], and looked up
+    /// by name with [`Asn1Object::from_str`]. Registrations are global to the process and cannot
+    /// be undone. If `oid` is already known, its `Nid` is returned and the names are ignored.
+    ///
+    /// This corresponds to [`OBJ_create`].
+    ///
+    /// [`X509Ref::to_text`]: crate::x509::X509Ref::to_text
+    /// [`Asn1ObjectRef`]: crate::asn1::Asn1ObjectRef
+    /// [`Asn1Object::from_str`]: crate::asn1::Asn1Object::from_str
+    /// [`OBJ_create`]: https://www.openssl.org/docs/man1.1.0/crypto/OBJ_create.html
+    pub fn create(oid: &str, short_name: &str, long_name: &str) -> Result<Nid, ErrorStack> {
+        // Serializes the lookup and the registration, so that an OID is only registered once.
+        static CREATE_LOCK: Mutex<()> = Mutex::new(());
+
+        let oid = CString::new(oid).unwrap();
+        let short_name = CString::new(short_name).unwrap();
+        let long_name = CString::new(long_name).unwrap();
+
+        unsafe {
+            ffi::init();
+            let _guard = CREATE_LOCK.lock().unwrap();
+
+            let obj = cvt_p(ffi::OBJ_txt2obj(oid.as_ptr(), 1))?;
+            let nid = ffi::OBJ_obj2nid(obj);
+            ffi::ASN1_OBJECT_free(obj);
+            if nid != ffi::NID_undef {
+                return Ok(Nid(nid));
+            }
+
+            match ffi::OBJ_create(oid.as_ptr(), short_name.as_ptr(), long_name.as_ptr()) {
+                ffi::NID_undef => Err(ErrorStack::get()),
+                nid => Ok(Nid(nid)),
+            }
+        }
+    }
+
     pub const UNDEF: Nid = Nid(ffi::NID_undef);
     pub const ITU_T: Nid = Nid(ffi::NID_itu_t);
     pub const ISO: Nid = Nid(ffi::NID_iso);
@@ -1050,6 +1090,14 @@ impl Nid {
     pub const AES_128_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_128_cbc_hmac_sha1);
     pub const AES_192_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_192_cbc_hmac_sha1);
     pub const AES_256_CBC_HMAC_SHA1: Nid = Nid(ffi::NID_aes_256_cbc_hmac_sha1);
+    pub const KX_RSA: Nid = Nid(ffi::NID_kx_rsa);
+    pub const KX_ECDHE: Nid = Nid(ffi::NID_kx_ecdhe);
+    pub const KX_PSK: Nid = Nid(ffi::NID_kx_psk);
+    pub const KX_ANY: Nid = Nid(ffi::NID_kx_any);
+    pub const AUTH_RSA: Nid = Nid(ffi::NID_auth_rsa);
+    pub const AUTH_ECDSA: Nid = Nid(ffi::NID_auth_ecdsa);
+    pub const AUTH_PSK: Nid = Nid(ffi::NID_auth_psk);
+    pub const AUTH_ANY: Nid = Nid(ffi::NID_auth_any);
 }
 
 #[cfg(test)]
@@ -1063,6 +1111,23 @@ mod test {
         assert_eq!(algs.pkey, Nid::RSAENCRYPTION);
     }
 
+    #[test]
+    fn create() {
+        let nid = Nid::create("1.3.6.1.4.1.55555.1", "testExt", "Test Extension").unwrap();
+        assert_ne!(nid, Nid::UNDEF);
+        assert_eq!(nid.short_name().unwrap(), "testExt");
+        assert_eq!(nid.long_name().unwrap(), "Test Extension");
+        assert_eq!(
+            Nid::create("1.3.6.1.4.1.55555.1", "other", "Other").unwrap(),
+            nid
+        );
+        assert_eq!(
+            Nid::create("2.5.29.15", "other", "Other").unwrap(),
+            Nid::KEY_USAGE
+        );
+        assert!(Nid::create("not an oid", "bad", "Bad").is_err());
+    }
+
     #[test]
     fn test_long_name_conversion() {
         let common_name = Nid::COMMONNAME;
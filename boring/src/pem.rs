@@ -0,0 +1,306 @@
+//! Parsing of PEM bundles.
+//!
+//! Servers are commonly configured with a single PEM file holding a certificate chain and its
+//! private key, and trust stores are distributed as PEM files mixing certificates and CRLs. The
+//! functions in this module split such bundles into typed items, in the order they appear.
+//!
+//! # Example
+//!
+//! ```rust
+//! use boring::pem::{self, PemItem};
+//!
+//! let mut bundle = include_bytes!("../test/cert.pem").to_vec();
+//! bundle.extend_from_slice(include_bytes!("../test/key.pem"));
+//!
+//! let items = pem::parse_bundle(&bundle).unwrap();
+//! assert!(matches!(items[0], PemItem::Certificate(_)));
+//! assert!(matches!(items[1], PemItem::PrivateKey(_)));
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str;
+
+use crate::error::ErrorStack;
+use crate::pkey::{PKey, Private};
+use crate::x509::{X509Crl, X509};
+
+/// An item of a PEM bundle.
+#[derive(Debug)]
+pub enum PemItem {
+    /// A certificate, from a `CERTIFICATE` block.
+    Certificate(X509),
+    /// A certificate revocation list, from an `X509 CRL` block.
+    Crl(X509Crl),
+    /// A private key, from a `PRIVATE KEY`, `ENCRYPTED PRIVATE KEY`, `RSA PRIVATE KEY` or
+    /// `EC PRIVATE KEY` block.
+    PrivateKey(PKey<Private>),
+    /// A block of another type.
+    Other {
+        /// The label of the block, e.g. `DH PARAMETERS`.
+        label: String,
+        /// The block itself, including its `BEGIN` and `END` lines.
+        pem: Vec<u8>,
+    },
+}
+
+/// Parses the items of a PEM bundle.
+///
+/// Encrypted private keys are rejected; see [`parse_bundle_with_passphrase`].
+pub fn parse_bundle(pem: &[u8]) -> Result<Vec<PemItem>, PemError> {
+    parse(pem, None)
+}
+
+/// Parses the items of a PEM bundle, decrypting private keys with `passphrase`.
+///
+/// Both PKCS #8 `ENCRYPTED PRIVATE KEY` blocks and legacy encrypted blocks with a `Proc-Type`
+/// header are supported.
+pub fn parse_bundle_with_passphrase(
+    pem: &[u8],
+    passphrase: &[u8],
+) -> Result<Vec<PemItem>, PemError> {
+    parse(pem, Some(passphrase))
+}
+
+fn parse(pem: &[u8], passphrase: Option<&[u8]>) -> Result<Vec<PemItem>, PemError> {
+    let mut items = vec![];
+
+    for block in blocks(pem)? {
+        let invalid = |error| PemError::Invalid {
+            line: block.line,
+            label: block.label.to_string(),
+            error,
+        };
+
+        let item = match block.label {
+            "CERTIFICATE" => PemItem::Certificate(X509::from_pem(block.pem).map_err(invalid)?),
+            "X509 CRL" => PemItem::Crl(X509Crl::from_pem(block.pem).map_err(invalid)?),
+            "PRIVATE KEY" | "ENCRYPTED PRIVATE KEY" | "RSA PRIVATE KEY" | "EC PRIVATE KEY" => {
+                let key = match passphrase {
+                    Some(passphrase) => {
+                        PKey::private_key_from_pem_passphrase(block.pem, passphrase)
+                    }
+                    None => PKey::private_key_from_pem(block.pem),
+                };
+                PemItem::PrivateKey(key.map_err(invalid)?)
+            }
+            label => PemItem::Other {
+                label: label.to_string(),
+                pem: block.pem.to_vec(),
+            },
+        };
+
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+struct Block<'a> {
+    // The 1-based number of the `BEGIN` line.
+    line: usize,
+    label: &'a str,
+    pem: &'a [u8],
+}
+
+// Splits a PEM bundle into blocks, ignoring text outside of them.
+fn blocks(pem: &[u8]) -> Result<Vec<Block<'_>>, PemError> {
+    let mut blocks = vec![];
+    // The line number, label and offset of the block being read.
+    let mut current: Option<(usize, &str, usize)> = None;
+    let mut offset = 0;
+
+    for (i, line) in pem.split_inclusive(|&b| b == b'\n').enumerate() {
+        let start = offset;
+        offset += line.len();
+
+        let text = match str::from_utf8(line) {
+            Ok(text) => text.trim_end(),
+            Err(_) => continue,
+        };
+
+        match current {
+            None => {
+                if let Some(label) = text
+                    .strip_prefix("-----BEGIN ")
+                    .and_then(|s| s.strip_suffix("-----"))
+                {
+                    current = Some((i + 1, label, start));
+                }
+            }
+            Some((line, label, start)) => {
+                let end = text
+                    .strip_prefix("-----END ")
+                    .and_then(|s| s.strip_suffix("-----"));
+
+                if end == Some(label) {
+                    blocks.push(Block {
+                        line,
+                        label,
+                        pem: &pem[start..offset],
+                    });
+                    current = None;
+                } else if end.is_some() || text.starts_with("-----BEGIN ") {
+                    return Err(PemError::Unterminated {
+                        line,
+                        label: label.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    match current {
+        Some((line, label, _)) => Err(PemError::Unterminated {
+            line,
+            label: label.to_string(),
+        }),
+        None => Ok(blocks),
+    }
+}
+
+/// An error loading a PEM bundle.
+#[derive(Debug)]
+pub enum PemError {
+    /// A block is missing its `END` line.
+    Unterminated {
+        /// The number of the `BEGIN` line of the block, starting at 1.
+        line: usize,
+        /// The label of the block.
+        label: String,
+    },
+    /// A block could not be parsed.
+    Invalid {
+        /// The number of the `BEGIN` line of the block, starting at 1.
+        line: usize,
+        /// The label of the block.
+        label: String,
+        /// The underlying error.
+        error: ErrorStack,
+    },
+    /// The bundle has no certificate.
+    MissingCertificate,
+    /// The bundle has no private key.
+    MissingPrivateKey,
+    /// The bundle could not be read.
+    Io(io::Error),
+    /// The items of the bundle could not be used.
+    Stack(ErrorStack),
+}
+
+impl PemError {
+    /// Returns the number of the `BEGIN` line of the block which caused the error, starting at 1.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            PemError::Unterminated { line, .. } | PemError::Invalid { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorStack> for PemError {
+    fn from(e: ErrorStack) -> PemError {
+        PemError::Stack(e)
+    }
+}
+
+impl From<io::Error> for PemError {
+    fn from(e: io::Error) -> PemError {
+        PemError::Io(e)
+    }
+}
+
+impl fmt::Display for PemError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PemError::Unterminated { line, label } => {
+                write!(fmt, "unterminated {} block at line {}", label, line)
+            }
+            PemError::Invalid { line, label, error } => {
+                write!(fmt, "invalid {} block at line {}: {}", label, line, error)
+            }
+            PemError::MissingCertificate => fmt.write_str("no certificate found"),
+            PemError::MissingPrivateKey => fmt.write_str("no private key found"),
+            PemError::Io(e) => fmt::Display::fmt(e, fmt),
+            PemError::Stack(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl Error for PemError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PemError::Invalid { error, .. } => Some(error),
+            PemError::Io(e) => Some(e),
+            PemError::Stack(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::MessageDigest;
+    use crate::symm::Cipher;
+
+    use super::*;
+
+    #[test]
+    fn mixed_bundle() {
+        let key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+
+        let mut bundle = b"leading text\n".to_vec();
+        bundle.extend_from_slice(include_bytes!("../test/certs.pem"));
+        bundle.extend_from_slice(
+            &key.private_key_to_pem_pkcs8_passphrase(Cipher::aes_256_cbc(), b"mypass")
+                .unwrap(),
+        );
+        bundle.extend_from_slice(include_bytes!("../test/dhparams.pem"));
+
+        assert!(matches!(
+            parse_bundle(&bundle),
+            Err(PemError::Invalid { ref label, .. }) if label == "ENCRYPTED PRIVATE KEY"
+        ));
+
+        let items = parse_bundle_with_passphrase(&bundle, b"mypass").unwrap();
+        assert_eq!(items.len(), 4);
+
+        let certs = X509::stack_from_pem(include_bytes!("../test/certs.pem")).unwrap();
+        for (item, cert) in items.iter().zip(&certs) {
+            match item {
+                PemItem::Certificate(item) => assert_eq!(
+                    item.digest(MessageDigest::sha256()).unwrap().as_ref(),
+                    cert.digest(MessageDigest::sha256()).unwrap().as_ref()
+                ),
+                item => panic!("unexpected item: {:?}", item),
+            }
+        }
+
+        match &items[2] {
+            PemItem::PrivateKey(item) => assert!(item.public_eq(&key)),
+            item => panic!("unexpected item: {:?}", item),
+        }
+        match &items[3] {
+            PemItem::Other { label, .. } => assert_eq!(label, "DH PARAMETERS"),
+            item => panic!("unexpected item: {:?}", item),
+        }
+    }
+
+    #[test]
+    fn error_location() {
+        let mut bundle = include_bytes!("../test/cert.pem").to_vec();
+        let lines = bundle.iter().filter(|&&b| b == b'\n').count();
+        bundle.extend_from_slice(b"-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n");
+
+        let err = parse_bundle(&bundle).unwrap_err();
+        assert!(matches!(err, PemError::Invalid { .. }));
+        assert_eq!(err.line(), Some(lines + 1));
+
+        let mut bundle = include_bytes!("../test/cert.pem").to_vec();
+        bundle.extend_from_slice(b"-----BEGIN X509 CRL-----\nAAAA\n");
+        let err = parse_bundle(&bundle).unwrap_err();
+        assert!(matches!(err, PemError::Unterminated { .. }));
+        assert_eq!(err.line(), Some(lines + 1));
+    }
+}
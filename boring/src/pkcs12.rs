@@ -114,17 +114,40 @@ pub struct Pkcs12Builder {
 
 impl Pkcs12Builder {
     /// The encryption algorithm that should be used for the key
+    ///
+    /// In addition to the PKCS #12 password-based encryption schemes, such as
+    /// `Nid::PBE_WITHSHA1AND3_KEY_TRIPLEDES_CBC`, cipher NIDs such as `Nid::AES_256_CBC` are
+    /// accepted and select PBES2 with that cipher.
     pub fn key_algorithm(&mut self, nid: Nid) -> &mut Self {
         self.nid_key = nid;
         self
     }
 
     /// The encryption algorithm that should be used for the cert
+    ///
+    /// See [`Pkcs12Builder::key_algorithm`] for the supported algorithms.
     pub fn cert_algorithm(&mut self, nid: Nid) -> &mut Self {
         self.nid_cert = nid;
         self
     }
 
+    /// Uses PBES2 with AES-256-CBC to encrypt both the key and the certificates.
+    ///
+    /// Unlike the defaults, which are kept for compatibility with legacy consumers, these
+    /// algorithms are accepted by current Java and Windows releases.
+    pub fn aes_256_cbc(&mut self) -> &mut Self {
+        self.key_algorithm(Nid::AES_256_CBC)
+            .cert_algorithm(Nid::AES_256_CBC)
+    }
+
+    /// Stores the certificates unencrypted.
+    ///
+    /// The archive is still authenticated with a MAC derived from the password.
+    pub fn no_cert_encryption(&mut self) -> &mut Self {
+        self.nid_cert = Nid::from_raw(-1);
+        self
+    }
+
     /// Key iteration count, default is 2048 as of this writing
     pub fn key_iter(&mut self, iter: u32) -> &mut Self {
         self.iter = iter as c_int;
@@ -135,6 +158,9 @@ impl Pkcs12Builder {
     ///
     /// Old implementations don't understand MAC iterations greater than 1, (pre 1.0.1?), if such
     /// compatibility is required this should be set to 1.
+    ///
+    /// BoringSSL does not support omitting the MAC, which would leave a password-protected
+    /// archive unauthenticated.
     pub fn mac_iter(&mut self, mac_iter: u32) -> &mut Self {
         self.mac_iter = mac_iter as c_int;
         self
@@ -284,4 +310,58 @@ mod test {
         );
         assert!(parsed.pkey.public_eq(&pkey));
     }
+
+    #[test]
+    fn create_aes_256_cbc() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let pkey = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        let ca = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+
+        let mut chain = Stack::new().unwrap();
+        chain.push(ca.clone()).unwrap();
+
+        let mut pkcs12_builder = Pkcs12::builder();
+        pkcs12_builder
+            .aes_256_cbc()
+            .key_iter(100_000)
+            .mac_iter(100_000)
+            .ca(chain);
+        let pkcs12 = pkcs12_builder
+            .build("mypass", "foobar.com", &pkey, &cert)
+            .unwrap();
+        let der = pkcs12.to_der().unwrap();
+
+        let pkcs12 = Pkcs12::from_der(&der).unwrap();
+        assert!(pkcs12.parse("wrongpass").is_err());
+        let parsed = pkcs12.parse("mypass").unwrap();
+
+        assert_eq!(
+            &*parsed.cert.digest(MessageDigest::sha256()).unwrap(),
+            &*cert.digest(MessageDigest::sha256()).unwrap()
+        );
+        assert!(parsed.pkey.public_eq(&pkey));
+
+        let chain = parsed.chain.unwrap();
+        assert_eq!(chain.len(), 1);
+        assert_eq!(
+            &*chain[0].digest(MessageDigest::sha256()).unwrap(),
+            &*ca.digest(MessageDigest::sha256()).unwrap()
+        );
+    }
+
+    #[test]
+    fn create_unencrypted_certs() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let pkey = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+
+        let mut pkcs12_builder = Pkcs12::builder();
+        pkcs12_builder.no_cert_encryption();
+        let pkcs12 = pkcs12_builder
+            .build("mypass", "foobar.com", &pkey, &cert)
+            .unwrap();
+
+        let pkcs12 = Pkcs12::from_der(&pkcs12.to_der().unwrap()).unwrap();
+        let parsed = pkcs12.parse("mypass").unwrap();
+        assert!(parsed.pkey.public_eq(&pkey));
+    }
 }
@@ -0,0 +1,225 @@
+//! PKCS #7 SignedData structures.
+//!
+//! BoringSSL only implements the subset of PKCS #7 used for certificate distribution and simple
+//! signatures: parsing SignedData structures, producing "certs-only" bundles (commonly
+//! distributed as `.p7b` files), and producing detached signatures without signed attributes.
+//! Verifying signatures, attached signatures and EnvelopedData are not supported, and neither is
+//! CMS.
+
+use crate::ffi;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use std::ptr;
+
+use crate::bio::MemBioSlice;
+use crate::error::ErrorStack;
+use crate::pkey::{HasPrivate, PKeyRef};
+use crate::stack::StackRef;
+use crate::x509::{X509Crl, X509Ref, X509};
+use crate::{cvt_p, init};
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::PKCS7;
+    fn drop = ffi::PKCS7_free;
+
+    /// A PKCS #7 SignedData structure.
+    pub struct Pkcs7;
+}
+
+impl Pkcs7 {
+    from_pem! {
+        /// Deserializes a PEM-encoded PKCS #7 structure.
+        ///
+        /// The input should have a header of `-----BEGIN PKCS7-----`.
+        ///
+        /// This corresponds to [`PEM_read_bio_PKCS7`].
+        ///
+        /// [`PEM_read_bio_PKCS7`]: https://www.openssl.org/docs/man1.0.2/crypto/PEM_read_bio_PKCS7.html
+        from_pem,
+        Pkcs7,
+        ffi::PEM_read_bio_PKCS7
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded PKCS #7 structure.
+        ///
+        /// This corresponds to [`d2i_PKCS7`].
+        ///
+        /// [`d2i_PKCS7`]: https://www.openssl.org/docs/man1.1.0/crypto/d2i_PKCS7.html
+        from_der,
+        Pkcs7,
+        ffi::d2i_PKCS7,
+        ::libc::c_long
+    }
+
+    /// Bundles certificates into a SignedData structure with no content and no signatures.
+    ///
+    /// This is the "certs-only" format commonly used to distribute certificate chains.
+    ///
+    /// This corresponds to [`PKCS7_sign`].
+    ///
+    /// [`PKCS7_sign`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/pkcs7.h.html#PKCS7_sign
+    pub fn from_certificates(certs: &StackRef<X509>) -> Result<Pkcs7, ErrorStack> {
+        unsafe {
+            init();
+            cvt_p(ffi::PKCS7_sign(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                certs.as_ptr(),
+                ptr::null_mut(),
+                ffi::PKCS7_DETACHED as _,
+            ))
+            .map(|p| Pkcs7::from_ptr(p))
+        }
+    }
+
+    /// Signs `data` with `pkey`, producing a detached signature.
+    ///
+    /// The signature has no signed attributes, and the structure includes neither `data` nor any
+    /// certificates; the signer is identified by the issuer and serial number of `signcert`.
+    ///
+    /// This corresponds to [`PKCS7_sign`].
+    ///
+    /// [`PKCS7_sign`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/pkcs7.h.html#PKCS7_sign
+    pub fn sign_detached<T>(
+        signcert: &X509Ref,
+        pkey: &PKeyRef<T>,
+        data: &[u8],
+    ) -> Result<Pkcs7, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            init();
+            let bio = MemBioSlice::new(data)?;
+            let flags =
+                ffi::PKCS7_NOATTR | ffi::PKCS7_BINARY | ffi::PKCS7_NOCERTS | ffi::PKCS7_DETACHED;
+
+            cvt_p(ffi::PKCS7_sign(
+                signcert.as_ptr(),
+                pkey.as_ptr(),
+                ptr::null_mut(),
+                bio.as_ptr(),
+                flags as _,
+            ))
+            .map(|p| Pkcs7::from_ptr(p))
+        }
+    }
+}
+
+impl Pkcs7Ref {
+    to_pem! {
+        /// Serializes the structure into a PEM-encoded PKCS #7 structure.
+        ///
+        /// The output will have a header of `-----BEGIN PKCS7-----`.
+        ///
+        /// This corresponds to [`PEM_write_bio_PKCS7`].
+        ///
+        /// [`PEM_write_bio_PKCS7`]: https://www.openssl.org/docs/man1.0.2/crypto/PEM_write_bio_PKCS7.html
+        to_pem,
+        ffi::PEM_write_bio_PKCS7
+    }
+
+    to_der! {
+        /// Serializes the structure into a DER-encoded PKCS #7 structure.
+        ///
+        /// This corresponds to [`i2d_PKCS7`].
+        ///
+        /// [`i2d_PKCS7`]: https://www.openssl.org/docs/man1.0.2/crypto/i2d_PKCS7.html
+        to_der,
+        ffi::i2d_PKCS7
+    }
+
+    /// Determines if the structure is a SignedData structure.
+    ///
+    /// This corresponds to [`PKCS7_type_is_signed`].
+    ///
+    /// [`PKCS7_type_is_signed`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/pkcs7.h.html#PKCS7_type_is_signed
+    pub fn is_signed(&self) -> bool {
+        unsafe { ffi::PKCS7_type_is_signed(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the certificates included in a SignedData structure.
+    pub fn certificates(&self) -> Option<&StackRef<X509>> {
+        unsafe {
+            let signed = self.signed()?;
+            let certs = (*signed).cert;
+            if certs.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(certs))
+            }
+        }
+    }
+
+    /// Returns the CRLs included in a SignedData structure.
+    pub fn crls(&self) -> Option<&StackRef<X509Crl>> {
+        unsafe {
+            let signed = self.signed()?;
+            let crls = (*signed).crl;
+            if crls.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(crls))
+            }
+        }
+    }
+
+    fn signed(&self) -> Option<*mut ffi::PKCS7_SIGNED> {
+        if !self.is_signed() {
+            return None;
+        }
+
+        let signed = unsafe { (*self.as_ptr()).d.sign };
+        if signed.is_null() {
+            None
+        } else {
+            Some(signed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::MessageDigest;
+    use crate::pkey::PKey;
+    use crate::stack::Stack;
+    use crate::x509::X509;
+
+    use super::*;
+
+    #[test]
+    fn certificates_bundle() {
+        let certs = X509::stack_from_pem(include_bytes!("../test/certs.pem")).unwrap();
+        let mut stack = Stack::new().unwrap();
+        for cert in &certs {
+            stack.push(cert.clone()).unwrap();
+        }
+
+        let bundle = Pkcs7::from_certificates(&stack).unwrap();
+        let bundle = Pkcs7::from_der(&bundle.to_der().unwrap()).unwrap();
+        let bundle = Pkcs7::from_pem(&bundle.to_pem().unwrap()).unwrap();
+        assert!(bundle.is_signed());
+
+        let parsed = bundle.certificates().unwrap();
+        assert_eq!(parsed.len(), certs.len());
+        for (parsed, cert) in parsed.iter().zip(&certs) {
+            assert_eq!(
+                parsed.digest(MessageDigest::sha256()).unwrap().as_ref(),
+                cert.digest(MessageDigest::sha256()).unwrap().as_ref()
+            );
+        }
+    }
+
+    #[test]
+    fn sign_detached() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+
+        let signature = Pkcs7::sign_detached(&cert, &key, b"hello world").unwrap();
+        let signature = Pkcs7::from_der(&signature.to_der().unwrap()).unwrap();
+        assert!(signature.is_signed());
+        assert!(signature
+            .certificates()
+            .is_none_or(|certs| certs.is_empty()));
+    }
+}
@@ -53,7 +53,11 @@ use crate::dh::Dh;
 use crate::dsa::Dsa;
 use crate::ec::EcKey;
 use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::rand::rand_bytes;
 use crate::rsa::Rsa;
+use crate::sign::{Signer, Verifier};
+use crate::symm::Cipher;
 use crate::util::{invoke_passwd_cb, CallbackState};
 use crate::{cvt, cvt_p};
 
@@ -244,6 +248,51 @@ where
     {
         unsafe { ffi::EVP_PKEY_cmp(self.as_ptr(), other.as_ptr()) == 1 }
     }
+
+    /// Returns the raw public key of an Ed25519 or X25519 key.
+    ///
+    /// This corresponds to [`EVP_PKEY_get_raw_public_key`].
+    ///
+    /// [`EVP_PKEY_get_raw_public_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/evp.h.html#EVP_PKEY_get_raw_public_key
+    pub fn raw_public_key(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_PKEY_get_raw_public_key(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut len,
+            ))?;
+            let mut buf = vec![0u8; len];
+            cvt(ffi::EVP_PKEY_get_raw_public_key(
+                self.as_ptr(),
+                buf.as_mut_ptr(),
+                &mut len,
+            ))?;
+            buf.truncate(len);
+            Ok(buf)
+        }
+    }
+
+    /// Verifies a signature of `data` in a single step.
+    ///
+    /// This is intended for Ed25519 keys, which sign messages without a separate digest. Other
+    /// key types should use [`PKeyRef::verify`].
+    pub fn verify_message(&self, data: &[u8], signature: &[u8]) -> Result<bool, ErrorStack> {
+        Verifier::new_without_digest(self)?.verify_oneshot(signature, data)
+    }
+
+    /// Verifies a signature of `data` hashed with `digest` in a single step.
+    ///
+    /// RSA keys use PKCS#1 v1.5 padding. To verify data as it is streamed, or to use other
+    /// options, use a [`Verifier`](crate::sign::Verifier).
+    pub fn verify(
+        &self,
+        digest: MessageDigest,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, ErrorStack> {
+        Verifier::new(digest, self)?.verify_oneshot(signature, data)
+    }
 }
 
 impl<T> PKeyRef<T>
@@ -297,6 +346,103 @@ where
         private_key_to_der_pkcs8_passphrase,
         ffi::i2d_PKCS8PrivateKey_bio
     }
+
+    /// Serializes the private key to a DER-encoded PKCS#8 EncryptedPrivateKeyInfo structure,
+    /// using PBES2 with `cipher` and a key derived from `passphrase` with PBKDF2.
+    ///
+    /// A random 16-byte salt is generated, and `iterations` sets the PBKDF2 iteration count. Unlike
+    /// [`PKeyRef::private_key_to_der_pkcs8_passphrase`], which uses a legacy default count, this
+    /// produces keys that current tooling such as OpenSSL 3 accepts without complaint when used
+    /// with AES and a few hundred thousand iterations. The result can be parsed with
+    /// [`PKey::private_key_from_pkcs8_passphrase`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `passphrase` is longer than `c_int::max_value()` or `iterations` is zero or
+    /// greater than `c_int::max_value()`.
+    ///
+    /// This corresponds to [`PKCS8_encrypt`].
+    ///
+    /// [`PKCS8_encrypt`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/pkcs8.h.html#PKCS8_encrypt
+    pub fn private_key_to_pkcs8_passphrase(
+        &self,
+        cipher: Cipher,
+        passphrase: &[u8],
+        iterations: u32,
+    ) -> Result<Vec<u8>, ErrorStack> {
+        assert!(passphrase.len() <= c_int::max_value() as usize);
+        assert!(iterations != 0 && iterations <= c_int::max_value() as u32);
+
+        let mut salt = [0; 16];
+        rand_bytes(&mut salt)?;
+
+        unsafe {
+            let p8inf = cvt_p(ffi::EVP_PKEY2PKCS8(self.as_ptr()))?;
+            let sig = ffi::PKCS8_encrypt(
+                -1,
+                cipher.as_ptr(),
+                passphrase.as_ptr() as *const _,
+                passphrase.len() as c_int,
+                salt.as_ptr(),
+                salt.len(),
+                iterations as c_int,
+                p8inf,
+            );
+            ffi::PKCS8_PRIV_KEY_INFO_free(p8inf);
+            let sig = cvt_p(sig)?;
+
+            let res = (|| {
+                let len = cvt(ffi::i2d_X509_SIG(sig, ptr::null_mut()))?;
+                let mut buf = vec![0; len as usize];
+                cvt(ffi::i2d_X509_SIG(sig, &mut buf.as_mut_ptr()))?;
+                Ok(buf)
+            })();
+            ffi::X509_SIG_free(sig);
+            res
+        }
+    }
+
+    /// Returns the raw private key of an Ed25519 or X25519 key.
+    ///
+    /// For Ed25519 keys this is the 32-byte seed.
+    ///
+    /// This corresponds to [`EVP_PKEY_get_raw_private_key`].
+    ///
+    /// [`EVP_PKEY_get_raw_private_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/evp.h.html#EVP_PKEY_get_raw_private_key
+    pub fn raw_private_key(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_PKEY_get_raw_private_key(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut len,
+            ))?;
+            let mut buf = vec![0u8; len];
+            cvt(ffi::EVP_PKEY_get_raw_private_key(
+                self.as_ptr(),
+                buf.as_mut_ptr(),
+                &mut len,
+            ))?;
+            buf.truncate(len);
+            Ok(buf)
+        }
+    }
+
+    /// Signs `data` in a single step.
+    ///
+    /// This is intended for Ed25519 keys, which sign messages without a separate digest. Other
+    /// key types should use [`PKeyRef::sign`].
+    pub fn sign_message(&self, data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        Signer::new_without_digest(self)?.sign_oneshot_to_vec(data)
+    }
+
+    /// Signs `data` hashed with `digest` in a single step.
+    ///
+    /// RSA keys use PKCS#1 v1.5 padding. To sign data as it is streamed, or to use other options,
+    /// use a [`Signer`](crate::sign::Signer).
+    pub fn sign(&self, digest: MessageDigest, data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        Signer::new(digest, self)?.sign_oneshot_to_vec(data)
+    }
 }
 
 impl<T> fmt::Debug for PKey<T> {
@@ -308,6 +454,7 @@ impl<T> fmt::Debug for PKey<T> {
             Id::EC => "EC",
             Id::ED25519 => "Ed25519",
             Id::ED448 => "Ed448",
+            Id::X25519 => "X25519",
             _ => "unknown",
         };
         fmt.debug_struct("PKey").field("algorithm", &alg).finish()
@@ -405,6 +552,52 @@ impl PKey<Private> {
         ::libc::c_long
     }
 
+    /// Generates a new Ed25519 key.
+    pub fn generate_ed25519() -> Result<PKey<Private>, ErrorStack> {
+        PKey::generate(Id::ED25519)
+    }
+
+    /// Generates a new X25519 key.
+    pub fn generate_x25519() -> Result<PKey<Private>, ErrorStack> {
+        PKey::generate(Id::X25519)
+    }
+
+    fn generate(id: Id) -> Result<PKey<Private>, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let ctx = cvt_p(ffi::EVP_PKEY_CTX_new_id(id.as_raw(), ptr::null_mut()))?;
+            let mut key = ptr::null_mut();
+            let res = cvt(ffi::EVP_PKEY_keygen_init(ctx))
+                .and_then(|_| cvt(ffi::EVP_PKEY_keygen(ctx, &mut key)));
+            ffi::EVP_PKEY_CTX_free(ctx);
+            res.map(|_| PKey::from_ptr(key))
+        }
+    }
+
+    /// Creates a private key from its raw representation.
+    ///
+    /// Only [`Id::ED25519`] and [`Id::X25519`] keys are supported; Ed448 and X448 are not
+    /// implemented by BoringSSL. For Ed25519 keys, `bytes` is the 32-byte seed.
+    ///
+    /// This corresponds to [`EVP_PKEY_new_raw_private_key`].
+    ///
+    /// [`EVP_PKEY_new_raw_private_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/evp.h.html#EVP_PKEY_new_raw_private_key
+    pub fn private_key_from_raw_bytes(
+        bytes: &[u8],
+        key_type: Id,
+    ) -> Result<PKey<Private>, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::EVP_PKEY_new_raw_private_key(
+                key_type.as_raw(),
+                ptr::null_mut(),
+                bytes.as_ptr(),
+                bytes.len(),
+            ))
+            .map(|p| PKey::from_ptr(p))
+        }
+    }
+
     /// Deserializes a DER-formatted PKCS#8 unencrypted private key.
     ///
     /// This method is mainly for interoperability reasons. Encrypted keyfiles should be preferred.
@@ -499,13 +692,38 @@ impl PKey<Public> {
         ffi::d2i_PUBKEY,
         ::libc::c_long
     }
+
+    /// Creates a public key from its raw representation.
+    ///
+    /// Only [`Id::ED25519`] and [`Id::X25519`] keys are supported; Ed448 and X448 are not
+    /// implemented by BoringSSL.
+    ///
+    /// This corresponds to [`EVP_PKEY_new_raw_public_key`].
+    ///
+    /// [`EVP_PKEY_new_raw_public_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/evp.h.html#EVP_PKEY_new_raw_public_key
+    pub fn public_key_from_raw_bytes(
+        bytes: &[u8],
+        key_type: Id,
+    ) -> Result<PKey<Public>, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::EVP_PKEY_new_raw_public_key(
+                key_type.as_raw(),
+                ptr::null_mut(),
+                bytes.as_ptr(),
+                bytes.len(),
+            ))
+            .map(|p| PKey::from_ptr(p))
+        }
+    }
 }
 
 use crate::ffi::EVP_PKEY_up_ref;
 
 #[cfg(test)]
 mod tests {
-    use crate::ec::EcKey;
+    use crate::derive::Deriver;
+    use crate::ec::{EcGroup, EcKey};
     use crate::nid::Nid;
     use crate::rsa::Rsa;
     use crate::symm::Cipher;
@@ -523,6 +741,42 @@ mod tests {
         assert!(PKey::private_key_from_pem_passphrase(&pem, b"fizzbuzz").is_err());
     }
 
+    #[test]
+    fn test_private_key_to_pkcs8_passphrase() {
+        let key = include_bytes!("../test/key.pem");
+        let key = PKey::private_key_from_pem(key).unwrap();
+
+        let der = key
+            .private_key_to_pkcs8_passphrase(Cipher::aes_256_cbc(), b"mypass", 10_000)
+            .unwrap();
+        // The PBES2 and AES-256-CBC object identifiers.
+        for oid in ["06092a864886f70d01050d", "060960864801650304012a"] {
+            let oid = hex::decode(oid).unwrap();
+            assert!(der.windows(oid.len()).any(|w| w == oid));
+        }
+
+        let parsed = PKey::private_key_from_pkcs8_passphrase(&der, b"mypass").unwrap();
+        assert!(parsed.public_eq(&key));
+        assert!(PKey::private_key_from_pkcs8_passphrase(&der, b"wrong").is_err());
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let rsa = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        for key in [rsa, ec] {
+            let signature = key.sign(MessageDigest::sha256(), b"hello, world!").unwrap();
+            assert!(key
+                .verify(MessageDigest::sha256(), b"hello, world!", &signature)
+                .unwrap());
+            assert!(!key
+                .verify(MessageDigest::sha256(), b"hello, world?", &signature)
+                .unwrap_or(false));
+        }
+    }
+
     #[test]
     fn test_unencrypted_pkcs8() {
         let key = include_bytes!("../test/pkcs8-nocrypt.der");
@@ -615,4 +869,58 @@ mod tests {
         assert_eq!(pkey.id(), Id::EC);
         assert!(pkey.rsa().is_err());
     }
+
+    #[test]
+    fn test_ed25519_raw() {
+        // RFC 8032, section 7.1, test 1
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+            .unwrap();
+        let public =
+            hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+                .unwrap();
+        let signature = hex::decode(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        )
+        .unwrap();
+
+        let key = PKey::private_key_from_raw_bytes(&seed, Id::ED25519).unwrap();
+        assert_eq!(key.id(), Id::ED25519);
+        assert_eq!(key.raw_private_key().unwrap(), seed);
+        assert_eq!(key.raw_public_key().unwrap(), public);
+        assert_eq!(key.sign_message(b"").unwrap(), signature);
+
+        let key = PKey::public_key_from_raw_bytes(&public, Id::ED25519).unwrap();
+        assert!(key.verify_message(b"", &signature).unwrap());
+        assert!(!key.verify_message(b"x", &signature).unwrap());
+
+        assert!(PKey::public_key_from_raw_bytes(&public[1..], Id::ED25519).is_err());
+    }
+
+    #[test]
+    fn test_x25519_raw() {
+        let alice = PKey::generate_x25519().unwrap();
+        let bob = PKey::generate_x25519().unwrap();
+        assert_eq!(alice.raw_private_key().unwrap().len(), 32);
+
+        let bob_public =
+            PKey::public_key_from_raw_bytes(&bob.raw_public_key().unwrap(), Id::X25519).unwrap();
+        let alice_public =
+            PKey::public_key_from_raw_bytes(&alice.raw_public_key().unwrap(), Id::X25519).unwrap();
+
+        let mut deriver = Deriver::new(&alice).unwrap();
+        deriver.set_peer(&bob_public).unwrap();
+        let alice_secret = deriver.derive_to_vec().unwrap();
+
+        let mut deriver = Deriver::new(&bob).unwrap();
+        deriver.set_peer(&alice_public).unwrap();
+        assert_eq!(alice_secret, deriver.derive_to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_ed25519_generate() {
+        let key = PKey::generate_ed25519().unwrap();
+        let signature = key.sign_message(b"hello").unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(key.verify_message(b"hello", &signature).unwrap());
+    }
 }
@@ -0,0 +1,64 @@
+//! The Poly1305 one-time authenticator, as specified in [RFC 8439].
+//!
+//! A Poly1305 key must only ever be used to authenticate a single message. Reusing a key allows
+//! an attacker to forge tags; it is usually derived per message from a cipher such as ChaCha20.
+//! For general purpose authenticated encryption, use [`aead`](crate::aead) instead.
+//!
+//! [RFC 8439]: https://datatracker.ietf.org/doc/html/rfc8439
+use crate::ffi;
+use std::mem::MaybeUninit;
+
+use crate::memcmp;
+
+/// The length of a Poly1305 key.
+pub const KEY_LEN: usize = 32;
+
+/// The length of a Poly1305 tag.
+pub const TAG_LEN: usize = 16;
+
+/// Computes the Poly1305 tag of `data` with the one-time key `key`.
+///
+/// This corresponds to [`CRYPTO_poly1305_init`], [`CRYPTO_poly1305_update`] and
+/// [`CRYPTO_poly1305_finish`].
+///
+/// [`CRYPTO_poly1305_init`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/poly1305.h.html#CRYPTO_poly1305_init
+/// [`CRYPTO_poly1305_update`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/poly1305.h.html#CRYPTO_poly1305_update
+/// [`CRYPTO_poly1305_finish`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/poly1305.h.html#CRYPTO_poly1305_finish
+pub fn poly1305(key: &[u8; KEY_LEN], data: &[u8]) -> [u8; TAG_LEN] {
+    unsafe {
+        let mut state = MaybeUninit::<ffi::poly1305_state>::uninit();
+        ffi::CRYPTO_poly1305_init(state.as_mut_ptr(), key.as_ptr());
+        ffi::CRYPTO_poly1305_update(state.as_mut_ptr(), data.as_ptr(), data.len());
+        let mut tag = [0; TAG_LEN];
+        ffi::CRYPTO_poly1305_finish(state.as_mut_ptr(), tag.as_mut_ptr());
+        tag
+    }
+}
+
+/// Returns `true` if `tag` is the Poly1305 tag of `data` with the one-time key `key`.
+///
+/// The comparison is performed in constant time.
+pub fn verify(key: &[u8; KEY_LEN], data: &[u8], tag: &[u8]) -> bool {
+    tag.len() == TAG_LEN && memcmp::eq(&poly1305(key, data), tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8439, section 2.5.2
+    #[test]
+    fn rfc8439() {
+        let key = hex::decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .unwrap();
+        let key: &[u8; KEY_LEN] = key[..].try_into().unwrap();
+        let data = b"Cryptographic Forum Research Group";
+
+        let tag = poly1305(key, data);
+        assert_eq!(hex::encode(tag), "a8061dc1305136c6c22b8baf0c0127a9");
+
+        assert!(verify(key, data, &tag));
+        assert!(!verify(key, b"Cryptographic Forum Research Grouq", &tag));
+        assert!(!verify(key, data, &tag[..15]));
+    }
+}
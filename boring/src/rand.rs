@@ -40,13 +40,56 @@ pub fn rand_bytes(buf: &mut [u8]) -> Result<(), ErrorStack> {
     }
 }
 
+/// Fill buffer with cryptographically strong pseudo-random bytes, mixing `additional_data` into
+/// the generator.
+///
+/// The additional data is hashed into the output of the DRBG, in addition to the entropy it
+/// already draws from the operating system. It does not need to be secret or random, but should
+/// differ between calls, e.g. a counter or timestamp, to provide defense in depth against a
+/// compromised entropy source.
+///
+/// This corresponds to `RAND_bytes_with_additional_data`.
+pub fn rand_bytes_with_additional_data(buf: &mut [u8], additional_data: &[u8; 32]) {
+    unsafe {
+        ffi::init();
+        ffi::RAND_bytes_with_additional_data(buf.as_mut_ptr(), buf.len(), additional_data.as_ptr());
+    }
+}
+
+/// Resets the deterministic generator to its initial state.
+///
+/// With the `unsafe-deterministic-rng` feature, BoringSSL is built to derive all random output
+/// from a fixed seed rather than from the operating system. After this call, the generator
+/// produces the same sequence of bytes as after process start, so tests can reset it before
+/// each case to make the keys, nonces and handshakes they generate reproducible.
+///
+/// This corresponds to [`RAND_reset_for_fuzzing`].
+///
+/// [`RAND_reset_for_fuzzing`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/rand.h.html#RAND_reset_for_fuzzing
+#[cfg(feature = "unsafe-deterministic-rng")]
+pub fn reset_deterministic_rng() {
+    unsafe {
+        ffi::init();
+        ffi::RAND_reset_for_fuzzing();
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::rand_bytes;
+    use super::*;
 
     #[test]
     fn test_rand_bytes() {
         let mut buf = [0; 32];
         rand_bytes(&mut buf).unwrap();
     }
+
+    #[test]
+    fn test_rand_bytes_with_additional_data() {
+        let mut a = [0; 32];
+        let mut b = [0; 32];
+        rand_bytes_with_additional_data(&mut a, &[1; 32]);
+        rand_bytes_with_additional_data(&mut b, &[1; 32]);
+        assert_ne!(a, b);
+    }
 }
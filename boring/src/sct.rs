@@ -0,0 +1,350 @@
+//! Certificate Transparency signed certificate timestamps.
+//!
+//! Servers can deliver signed certificate timestamps (SCTs) in the TLS handshake, proving that
+//! their certificate was submitted to Certificate Transparency logs, as described in
+//! [RFC 6962]. This module parses the `SignedCertificateTimestampList` structure returned by
+//! [`SslRef::signed_cert_timestamp_list`] and verifies the SCTs it contains against a set of
+//! known logs.
+//!
+//! Only SCTs delivered in the TLS handshake or in a stapled OCSP response are supported, as those
+//! sign the final certificate. SCTs embedded in the certificate itself sign the precertificate
+//! and cannot be verified with this module.
+//!
+//! [RFC 6962]: https://datatracker.ietf.org/doc/html/rfc6962
+//! [`SslRef::signed_cert_timestamp_list`]: ../ssl/struct.SslRef.html#method.signed_cert_timestamp_list
+use std::convert::TryInto;
+use std::error;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::ErrorStack;
+use crate::hash::{hash, MessageDigest};
+use crate::pkey::{PKey, PKeyRef, Public};
+use crate::sign::Verifier;
+use crate::x509::X509Ref;
+
+/// The version of a signed certificate timestamp.
+const SCT_VERSION_V1: u8 = 0;
+/// The `certificate_timestamp` signature type.
+const SIGNATURE_TYPE_CERTIFICATE_TIMESTAMP: u8 = 0;
+/// The `x509_entry` log entry type.
+const LOG_ENTRY_TYPE_X509: u16 = 0;
+
+/// An error encountered while parsing signed certificate timestamps.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SctError {
+    /// The encoding is malformed.
+    Malformed,
+    /// The SCT uses a version other than v1.
+    UnsupportedVersion(u8),
+}
+
+impl fmt::Display for SctError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SctError::Malformed => fmt.write_str("malformed signed certificate timestamp"),
+            SctError::UnsupportedVersion(version) => write!(
+                fmt,
+                "unsupported signed certificate timestamp version {}",
+                version
+            ),
+        }
+    }
+}
+
+impl error::Error for SctError {}
+
+/// A Certificate Transparency log.
+#[derive(Clone)]
+pub struct CtLog {
+    id: [u8; 32],
+    key: PKey<Public>,
+    description: String,
+}
+
+impl CtLog {
+    /// Creates a log from its public key.
+    ///
+    /// The log ID is the SHA-256 hash of the DER-encoded SubjectPublicKeyInfo.
+    pub fn new(key: PKey<Public>, description: &str) -> Result<CtLog, ErrorStack> {
+        let spki = key.public_key_to_der()?;
+        let mut id = [0; 32];
+        id.copy_from_slice(&hash(MessageDigest::sha256(), &spki)?);
+
+        Ok(CtLog {
+            id,
+            key,
+            description: description.to_owned(),
+        })
+    }
+
+    /// Returns the log ID.
+    pub fn id(&self) -> &[u8; 32] {
+        &self.id
+    }
+
+    /// Returns the public key of the log.
+    pub fn key(&self) -> &PKeyRef<Public> {
+        &self.key
+    }
+
+    /// Returns the description of the log.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// The outcome of verifying a signed certificate timestamp.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SctStatus {
+    /// The SCT was issued by a known log and its signature is valid.
+    Valid,
+    /// The SCT was issued by a log that is not in the log list.
+    UnknownLog,
+    /// The SCT's signature does not match the certificate.
+    InvalidSignature,
+}
+
+/// A signed certificate timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCertificateTimestamp {
+    version: u8,
+    log_id: [u8; 32],
+    timestamp: u64,
+    extensions: Vec<u8>,
+    hash_algorithm: u8,
+    signature_algorithm: u8,
+    signature: Vec<u8>,
+}
+
+impl SignedCertificateTimestamp {
+    /// Parses a TLS-encoded `SignedCertificateTimestampList`.
+    pub fn parse_list(list: &[u8]) -> Result<Vec<SignedCertificateTimestamp>, SctError> {
+        let mut reader = Reader(list);
+        let mut scts = reader.u16_prefixed()?;
+        reader.finish()?;
+
+        let mut out = vec![];
+        while !scts.0.is_empty() {
+            out.push(SignedCertificateTimestamp::parse(scts.u16_prefixed()?.0)?);
+        }
+
+        if out.is_empty() {
+            return Err(SctError::Malformed);
+        }
+
+        Ok(out)
+    }
+
+    /// Parses a TLS-encoded `SignedCertificateTimestamp`.
+    pub fn parse(sct: &[u8]) -> Result<SignedCertificateTimestamp, SctError> {
+        let mut reader = Reader(sct);
+
+        let version = reader.u8()?;
+        if version != SCT_VERSION_V1 {
+            return Err(SctError::UnsupportedVersion(version));
+        }
+
+        let log_id = reader.bytes(32)?.try_into().unwrap();
+        let timestamp = u64::from_be_bytes(reader.bytes(8)?.try_into().unwrap());
+        let extensions = reader.u16_prefixed()?.0.to_vec();
+        let hash_algorithm = reader.u8()?;
+        let signature_algorithm = reader.u8()?;
+        let signature = reader.u16_prefixed()?.0.to_vec();
+        reader.finish()?;
+
+        Ok(SignedCertificateTimestamp {
+            version,
+            log_id,
+            timestamp,
+            extensions,
+            hash_algorithm,
+            signature_algorithm,
+            signature,
+        })
+    }
+
+    /// Returns the ID of the log which issued the SCT.
+    pub fn log_id(&self) -> &[u8; 32] {
+        &self.log_id
+    }
+
+    /// Returns the time at which the SCT was issued, in milliseconds since the UNIX epoch.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Returns the time at which the SCT was issued.
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp)
+    }
+
+    /// Returns the raw SCT extensions.
+    pub fn extensions(&self) -> &[u8] {
+        &self.extensions
+    }
+
+    /// Returns the TLS `HashAlgorithm` and `SignatureAlgorithm` codes of the signature.
+    pub fn signature_algorithm(&self) -> (u8, u8) {
+        (self.hash_algorithm, self.signature_algorithm)
+    }
+
+    /// Returns the signature of the log.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Returns the log in `logs` which issued this SCT, if any.
+    pub fn find_log<'a>(&self, logs: &'a [CtLog]) -> Option<&'a CtLog> {
+        logs.iter().find(|log| log.id == self.log_id)
+    }
+
+    /// Verifies the SCT against the certificate it was delivered with.
+    pub fn verify(&self, cert: &X509Ref, logs: &[CtLog]) -> Result<SctStatus, ErrorStack> {
+        let log = match self.find_log(logs) {
+            Some(log) => log,
+            None => return Ok(SctStatus::UnknownLog),
+        };
+
+        let cert = cert.to_der()?;
+        let cert_len = (cert.len() as u32).to_be_bytes();
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &log.key)?;
+        verifier.update(&[self.version, SIGNATURE_TYPE_CERTIFICATE_TIMESTAMP])?;
+        verifier.update(&self.timestamp.to_be_bytes())?;
+        verifier.update(&LOG_ENTRY_TYPE_X509.to_be_bytes())?;
+        verifier.update(&cert_len[1..])?;
+        verifier.update(&cert)?;
+        verifier.update(&(self.extensions.len() as u16).to_be_bytes())?;
+        verifier.update(&self.extensions)?;
+
+        match verifier.verify(&self.signature) {
+            Ok(true) => Ok(SctStatus::Valid),
+            // A malformed signature is reported as an error by some key types.
+            Ok(false) | Err(_) => Ok(SctStatus::InvalidSignature),
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], SctError> {
+        if self.0.len() < len {
+            return Err(SctError::Malformed);
+        }
+
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, SctError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16_prefixed(&mut self) -> Result<Reader<'a>, SctError> {
+        let len = u16::from_be_bytes(self.bytes(2)?.try_into().unwrap());
+        self.bytes(len as usize).map(Reader)
+    }
+
+    fn finish(&self) -> Result<(), SctError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(SctError::Malformed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::pkey::PKey;
+    use crate::sign::Signer;
+    use crate::x509::X509;
+
+    fn encode_sct(log_id: &[u8; 32], timestamp: u64, signature: &[u8]) -> Vec<u8> {
+        let mut sct = vec![SCT_VERSION_V1];
+        sct.extend_from_slice(log_id);
+        sct.extend_from_slice(&timestamp.to_be_bytes());
+        sct.extend_from_slice(&[0, 0]);
+        // sha256, ecdsa
+        sct.extend_from_slice(&[4, 3]);
+        sct.extend_from_slice(&(signature.len() as u16).to_be_bytes());
+        sct.extend_from_slice(signature);
+        sct
+    }
+
+    fn encode_list(scts: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = vec![];
+        for sct in scts {
+            body.extend_from_slice(&(sct.len() as u16).to_be_bytes());
+            body.extend_from_slice(sct);
+        }
+
+        let mut list = (body.len() as u16).to_be_bytes().to_vec();
+        list.extend_from_slice(&body);
+        list
+    }
+
+    #[test]
+    fn parse_and_verify() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let public = PKey::public_key_from_der(&key.public_key_to_der().unwrap()).unwrap();
+        let log = CtLog::new(public, "test log").unwrap();
+        let timestamp: u64 = 1_600_000_000_000;
+
+        let der = cert.to_der().unwrap();
+        let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+        signer.update(&[0, 0]).unwrap();
+        signer.update(&timestamp.to_be_bytes()).unwrap();
+        signer.update(&[0, 0]).unwrap();
+        signer
+            .update(&(der.len() as u32).to_be_bytes()[1..])
+            .unwrap();
+        signer.update(&der).unwrap();
+        signer.update(&[0, 0]).unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+
+        let list = encode_list(&[
+            encode_sct(log.id(), timestamp, &signature),
+            encode_sct(&[0; 32], timestamp, &signature),
+        ]);
+        let scts = SignedCertificateTimestamp::parse_list(&list).unwrap();
+        assert_eq!(scts.len(), 2);
+        assert_eq!(scts[0].timestamp(), timestamp);
+        assert_eq!(scts[0].signature_algorithm(), (4, 3));
+
+        let logs = [log];
+        assert_eq!(scts[0].verify(&cert, &logs).unwrap(), SctStatus::Valid);
+        assert_eq!(scts[1].verify(&cert, &logs).unwrap(), SctStatus::UnknownLog);
+
+        let tampered = encode_list(&[encode_sct(logs[0].id(), timestamp + 1, &signature)]);
+        let scts = SignedCertificateTimestamp::parse_list(&tampered).unwrap();
+        assert_eq!(
+            scts[0].verify(&cert, &logs).unwrap(),
+            SctStatus::InvalidSignature
+        );
+    }
+
+    #[test]
+    fn parse_malformed() {
+        assert_eq!(
+            SignedCertificateTimestamp::parse_list(&[]),
+            Err(SctError::Malformed)
+        );
+        assert_eq!(
+            SignedCertificateTimestamp::parse_list(&[0, 0]),
+            Err(SctError::Malformed)
+        );
+        assert_eq!(
+            SignedCertificateTimestamp::parse_list(&[0, 3, 0, 1, 1]),
+            Err(SctError::UnsupportedVersion(1))
+        );
+    }
+}
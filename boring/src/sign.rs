@@ -50,6 +50,7 @@ use crate::{cvt, cvt_p};
 use crate::ffi::{EVP_MD_CTX_free, EVP_MD_CTX_new};
 
 /// Salt lengths that must be used with `set_rsa_pss_saltlen`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct RsaPssSaltlen(c_int);
 
 impl RsaPssSaltlen {
@@ -68,6 +69,8 @@ impl RsaPssSaltlen {
     pub const DIGEST_LENGTH: RsaPssSaltlen = RsaPssSaltlen(-1);
     /// The salt length is set to the maximum permissible value.
     /// Corresponds to the special value `-2`.
+    ///
+    /// When verifying, this accepts any salt length, which is recovered from the signature.
     pub const MAXIMUM_LENGTH: RsaPssSaltlen = RsaPssSaltlen(-2);
 }
 
@@ -186,6 +189,19 @@ impl<'a> Signer<'a> {
         }
     }
 
+    /// Returns the RSA PSS salt length.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to `EVP_PKEY_CTX_get_rsa_pss_saltlen`.
+    pub fn rsa_pss_saltlen(&self) -> Result<RsaPssSaltlen, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_PKEY_CTX_get_rsa_pss_saltlen(self.pctx, &mut len))
+                .map(|_| RsaPssSaltlen(len))
+        }
+    }
+
     /// Sets the RSA PSS salt length.
     ///
     /// This is only useful for RSA keys.
@@ -205,7 +221,7 @@ impl<'a> Signer<'a> {
 
     /// Sets the RSA MGF1 algorithm.
     ///
-    /// This is only useful for RSA keys.
+    /// This is only useful for RSA keys using PSS padding, and defaults to the signature digest.
     ///
     /// This corresponds to [`EVP_PKEY_CTX_set_rsa_mgf1_md`].
     ///
@@ -461,6 +477,19 @@ impl<'a> Verifier<'a> {
         }
     }
 
+    /// Returns the RSA PSS salt length.
+    ///
+    /// This is only useful for RSA keys.
+    ///
+    /// This corresponds to `EVP_PKEY_CTX_get_rsa_pss_saltlen`.
+    pub fn rsa_pss_saltlen(&self) -> Result<RsaPssSaltlen, ErrorStack> {
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_PKEY_CTX_get_rsa_pss_saltlen(self.pctx, &mut len))
+                .map(|_| RsaPssSaltlen(len))
+        }
+    }
+
     /// Sets the RSA PSS salt length.
     ///
     /// This is only useful for RSA keys.
@@ -480,7 +509,7 @@ impl<'a> Verifier<'a> {
 
     /// Sets the RSA MGF1 algorithm.
     ///
-    /// This is only useful for RSA keys.
+    /// This is only useful for RSA keys using PSS padding, and defaults to the signature digest.
     ///
     /// This corresponds to [`EVP_PKEY_CTX_set_rsa_mgf1_md`].
     ///
@@ -577,6 +606,7 @@ use crate::ffi::EVP_DigestVerifyFinal;
 mod test {
     use super::RsaPssSaltlen;
     use hex::{self, FromHex};
+    use std::io;
 
     use crate::ec::{EcGroup, EcKey};
     use crate::hash::MessageDigest;
@@ -613,6 +643,28 @@ mod test {
         assert_eq!(hex::encode(result), SIGNATURE);
     }
 
+    #[test]
+    fn rsa_sign_streaming() {
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+        let input = Vec::from_hex(INPUT).unwrap();
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+        io::copy(&mut &input[..], &mut signer).unwrap();
+        assert_eq!(hex::encode(signer.sign_to_vec().unwrap()), SIGNATURE);
+
+        let signature = pkey.sign(MessageDigest::sha256(), &input).unwrap();
+        assert_eq!(hex::encode(&signature), SIGNATURE);
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+        io::copy(&mut &input[..], &mut verifier).unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+        assert!(pkey
+            .verify(MessageDigest::sha256(), &input, &signature)
+            .unwrap());
+    }
+
     #[test]
     fn rsa_verify_ok() {
         let key = include_bytes!("../test/rsa.pem");
@@ -677,4 +729,35 @@ mod test {
         verifier.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
         assert!(verifier.verify(&signature).unwrap());
     }
+
+    #[test]
+    fn rsa_pss_saltlen() {
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+        signer.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        signer
+            .set_rsa_pss_saltlen(RsaPssSaltlen::custom(20))
+            .unwrap();
+        assert_eq!(signer.rsa_pss_saltlen().unwrap(), RsaPssSaltlen::custom(20));
+        signer.set_rsa_mgf1_md(MessageDigest::sha1()).unwrap();
+        signer.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+
+        let verify = |saltlen, mgf1_md| {
+            let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+            verifier.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+            verifier.set_rsa_pss_saltlen(saltlen).unwrap();
+            verifier.set_rsa_mgf1_md(mgf1_md).unwrap();
+            verifier.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+            verifier.verify(&signature).unwrap()
+        };
+
+        assert!(verify(RsaPssSaltlen::custom(20), MessageDigest::sha1()));
+        assert!(verify(RsaPssSaltlen::MAXIMUM_LENGTH, MessageDigest::sha1()));
+        assert!(!verify(RsaPssSaltlen::DIGEST_LENGTH, MessageDigest::sha1()));
+        assert!(!verify(RsaPssSaltlen::custom(20), MessageDigest::sha256()));
+    }
 }
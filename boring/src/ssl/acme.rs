@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::asn1::{Asn1Object, Asn1Time};
+use crate::bn::{BigNum, MsbOption};
+use crate::ec::{EcGroup, EcKey};
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pkey::{PKey, PKeyRef, Private};
+use crate::sha::sha256;
+use crate::ssl::sni::normalize;
+use crate::ssl::{
+    select_next_proto, AlpnError, ClientHello, ExtensionType, NameType, SelectCertError,
+    SslContext, SslContextBuilder, SslMethod, SslVersion,
+};
+use crate::x509::extension::SubjectAlternativeName;
+use crate::x509::{X509Extension, X509NameBuilder, X509Ref, X509};
+
+/// The `acme-tls/1` protocol of TLS-ALPN-01 challenges, in ALPN wire format.
+pub const ACME_TLS_ALPN_PROTOCOL: &[u8] = b"\x0aacme-tls/1";
+
+/// The object identifier of the `id-pe-acmeIdentifier` certificate extension.
+const ACME_IDENTIFIER_OID: &str = "1.3.6.1.5.5.7.1.31";
+
+/// The certificates answering ACME TLS-ALPN-01 challenges, defined in [RFC 8737].
+///
+/// A client validating a challenge connects to the domain and offers `acme-tls/1` as its only
+/// application protocol. Once installed with [`SslContextBuilder::set_acme_challenges`], such
+/// connections are served the challenge certificate of the requested domain, if any, while every
+/// other connection is handled by the context as usual.
+///
+/// Cloning an `AcmeChallenges` yields a handle to the same certificates, so challenges can be
+/// added and removed while the context is in use.
+///
+/// [RFC 8737]: https://datatracker.ietf.org/doc/html/rfc8737
+#[derive(Clone, Default)]
+pub struct AcmeChallenges {
+    contexts: Arc<RwLock<HashMap<String, SslContext>>>,
+}
+
+impl AcmeChallenges {
+    /// Creates a set without challenges.
+    pub fn new() -> AcmeChallenges {
+        AcmeChallenges::default()
+    }
+
+    /// Answers the challenge for `domain` with a certificate built by
+    /// [`acme_tls_alpn_certificate`] from `key_authorization` and a new P-256 key.
+    pub fn insert(&self, domain: &str, key_authorization: &str) -> Result<(), ErrorStack> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+        let cert = acme_tls_alpn_certificate(domain, key_authorization, &key)?;
+
+        self.insert_certificate(domain, &cert, &key)
+    }
+
+    /// Answers the challenge for `domain` with the given certificate and private key, replacing
+    /// any previous challenge for it.
+    pub fn insert_certificate(
+        &self,
+        domain: &str,
+        cert: &X509Ref,
+        key: &PKeyRef<Private>,
+    ) -> Result<(), ErrorStack> {
+        let mut ctx = SslContext::builder(SslMethod::tls())?;
+        ctx.set_min_proto_version(Some(SslVersion::TLS1_2))?;
+        ctx.set_certificate(cert)?;
+        ctx.set_private_key(key)?;
+        ctx.check_private_key()?;
+        ctx.set_alpn_select_callback(|_, client| {
+            select_next_proto(ACME_TLS_ALPN_PROTOCOL, client).ok_or(AlpnError::ALERT_FATAL)
+        });
+        // Keeps the servername callback of the original context, such as an SNI router, from
+        // replacing this context.
+        ctx.set_servername_callback(|_, _| Ok(()));
+
+        let mut contexts = self.contexts.write().unwrap();
+        contexts.insert(normalize(domain), ctx.build());
+
+        Ok(())
+    }
+
+    /// Stops answering the challenge for `domain`, returning whether there was one.
+    pub fn remove(&self, domain: &str) -> bool {
+        let mut contexts = self.contexts.write().unwrap();
+        contexts.remove(&normalize(domain)).is_some()
+    }
+
+    /// Serves the challenge certificate if the ClientHello is a TLS-ALPN-01 challenge, returning
+    /// whether it is one.
+    ///
+    /// This is called by the select certificate callback installed with
+    /// [`SslContextBuilder::set_acme_challenges`], and can be called from a custom select
+    /// certificate callback instead. Challenges for domains without a challenge certificate are
+    /// aborted.
+    pub fn select(&self, client_hello: &mut ClientHello<'_>) -> Result<bool, SelectCertError> {
+        if !is_acme_tls_alpn(client_hello) {
+            return Ok(false);
+        }
+
+        let ctx = client_hello
+            .servername(NameType::HOST_NAME)
+            .and_then(|name| {
+                let contexts = self.contexts.read().unwrap();
+                contexts.get(&normalize(name)).cloned()
+            })
+            .ok_or(SelectCertError::ERROR)?;
+
+        client_hello
+            .ssl_mut()
+            .set_ssl_context(&ctx)
+            .map_err(|_| SelectCertError::ERROR)?;
+
+        Ok(true)
+    }
+}
+
+/// Returns whether the only application protocol offered by the client is `acme-tls/1`.
+fn is_acme_tls_alpn(client_hello: &ClientHello<'_>) -> bool {
+    let alpn = client_hello.get_extension(ExtensionType::APPLICATION_LAYER_PROTOCOL_NEGOTIATION);
+
+    match alpn {
+        Some([0, len, protocols @ ..]) => {
+            usize::from(*len) == protocols.len() && protocols == ACME_TLS_ALPN_PROTOCOL
+        }
+        _ => false,
+    }
+}
+
+/// Builds the self-signed certificate answering a TLS-ALPN-01 challenge for `domain`.
+///
+/// The certificate holds `domain` as its only subject alternative name, and the SHA-256 digest
+/// of `key_authorization` in a critical `id-pe-acmeIdentifier` extension, as required by
+/// [RFC 8737]. It is signed with `key` and valid for 7 days.
+///
+/// [RFC 8737]: https://datatracker.ietf.org/doc/html/rfc8737#section-3
+pub fn acme_tls_alpn_certificate(
+    domain: &str,
+    key_authorization: &str,
+    key: &PKeyRef<Private>,
+) -> Result<X509, ErrorStack> {
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_text("CN", domain)?;
+    let name = name.build();
+
+    let mut serial = BigNum::new()?;
+    serial.rand(128, MsbOption::MAYBE_ZERO, false)?;
+
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    builder.set_serial_number(&serial.to_asn1_integer()?)?;
+    builder.set_subject_name(&name)?;
+    builder.set_issuer_name(&name)?;
+    builder.set_not_before(&Asn1Time::days_from_now(0)?)?;
+    builder.set_not_after(&Asn1Time::days_from_now(7)?)?;
+    builder.set_pubkey(key)?;
+
+    let alt_name = SubjectAlternativeName::new()
+        .dns(domain)
+        .build(&builder.x509v3_context(None, None))?;
+    builder.append_extension(alt_name)?;
+
+    // Authorization ::= OCTET STRING (SIZE (32))
+    let mut authorization = vec![0x04, 0x20];
+    authorization.extend_from_slice(&sha256(key_authorization.as_bytes()));
+    let oid = Asn1Object::from_str(ACME_IDENTIFIER_OID)?;
+    builder.append_extension(X509Extension::new_from_der(&oid, true, &authorization)?)?;
+
+    builder.sign(key, MessageDigest::sha256())?;
+
+    Ok(builder.build())
+}
+
+impl SslContextBuilder {
+    /// Answers ACME TLS-ALPN-01 challenges with the certificates of `challenges`.
+    ///
+    /// Connections offering `acme-tls/1` as their only application protocol switch to the
+    /// context of the challenge for their server name, and are aborted if there is none. Other
+    /// connections are not affected.
+    ///
+    /// This replaces any callback set with [`SslContextBuilder::set_select_certificate_callback`].
+    /// [`AcmeChallenges::select`] can be called from a custom callback instead.
+    pub fn set_acme_challenges(&mut self, challenges: AcmeChallenges) {
+        self.set_select_certificate_callback(move |mut client_hello| {
+            challenges.select(&mut client_hello).map(|_| ())
+        });
+    }
+}
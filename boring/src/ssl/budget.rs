@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::ssl::ClientHello;
+
+/// A budget of handshakes shared by the connections of one client, such as those from the same IP
+/// address, to mitigate handshake floods.
+///
+/// A server attaches the budget of each connection's client with
+/// [`SslRef::set_handshake_budget`] before the handshake, and enforces it by calling
+/// [`HandshakeBudget::admit`] from [`SslContextBuilder::set_dos_protection_callback`]. Each
+/// handshake then takes one unit from the budget, and once it is exhausted, handshakes are
+/// rejected right after the ClientHello, before any expensive public key operation. How and when
+/// the budget is replenished, e.g. periodically with [`HandshakeBudget::add`], is up to the server.
+///
+/// Cloning a budget yields a handle to the same budget.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{HandshakeBudget, Ssl, SslAcceptor, SslMethod};
+/// use std::collections::HashMap;
+/// use std::net::TcpListener;
+///
+/// let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+/// acceptor.set_dos_protection_callback(|client_hello| HandshakeBudget::admit(&client_hello));
+/// let acceptor = acceptor.build();
+///
+/// let mut budgets = HashMap::new();
+/// let listener = TcpListener::bind("0.0.0.0:443").unwrap();
+///
+/// for stream in listener.incoming() {
+///     let stream = stream.unwrap();
+///     let ip = stream.peer_addr().unwrap().ip();
+///     let budget = budgets
+///         .entry(ip)
+///         .or_insert_with(|| HandshakeBudget::new(100))
+///         .clone();
+///
+///     let mut ssl = Ssl::new(acceptor.context()).unwrap();
+///     ssl.set_handshake_budget(budget);
+///     let _ = ssl.accept(stream);
+/// }
+/// ```
+///
+/// [`SslRef::set_handshake_budget`]: struct.SslRef.html#method.set_handshake_budget
+/// [`SslContextBuilder::set_dos_protection_callback`]: struct.SslContextBuilder.html#method.set_dos_protection_callback
+#[derive(Debug, Clone, Default)]
+pub struct HandshakeBudget(Arc<AtomicUsize>);
+
+impl HandshakeBudget {
+    /// Creates a budget allowing the given number of handshakes.
+    pub fn new(handshakes: usize) -> HandshakeBudget {
+        HandshakeBudget(Arc::new(AtomicUsize::new(handshakes)))
+    }
+
+    /// Returns the number of handshakes left.
+    pub fn remaining(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Adds handshakes to the budget.
+    pub fn add(&self, handshakes: usize) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                Some(remaining.saturating_add(handshakes))
+            });
+    }
+
+    /// Takes one handshake from the budget, returning `false` if it is exhausted.
+    pub fn try_take(&self) -> bool {
+        self.0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Takes one handshake from the budget attached to the connection of `client_hello`,
+    /// returning whether the handshake may continue.
+    ///
+    /// Connections without a budget are always admitted.
+    pub fn admit(client_hello: &ClientHello<'_>) -> bool {
+        client_hello
+            .ssl()
+            .handshake_budget()
+            .is_none_or(HandshakeBudget::try_take)
+    }
+}
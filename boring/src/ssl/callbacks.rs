@@ -1,12 +1,18 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+use super::stats;
 use super::{
-    AlpnError, ClientHello, PrivateKeyMethod, PrivateKeyMethodError, SelectCertError, SniError,
-    Ssl, SslAlert, SslContext, SslContextRef, SslRef, SslSession, SslSessionRef,
-    SslSignatureAlgorithm, SESSION_CTX_INDEX,
+    AlpnError, CertCallbackError, CertificateRequest, ClientHello, HandshakeDoneCallback,
+    HandshakeOutcome, HandshakeOutcomeCallback, HandshakeStart, PrivateKeyMethod,
+    PrivateKeyMethodError, SelectCertError, SniError, Ssl, SslAlert, SslContext, SslContextRef,
+    SslRef, SslSession, SslSessionRef, SslSignatureAlgorithm, SslVerifyError, TicketKeyManager,
+    SESSION_CTX_INDEX,
 };
 use crate::error::ErrorStack;
 use crate::ffi;
+use crate::hash::MessageDigest;
+use crate::rand::rand_bytes;
+use crate::symm::Cipher;
 use crate::x509::{X509StoreContext, X509StoreContextRef};
 use foreign_types::ForeignType;
 use foreign_types::ForeignTypeRef;
@@ -18,6 +24,7 @@ use std::ptr;
 use std::slice;
 use std::str;
 use std::sync::Arc;
+use std::time::Instant;
 
 pub extern "C" fn raw_verify<F>(preverify_ok: c_int, x509_ctx: *mut ffi::X509_STORE_CTX) -> c_int
 where
@@ -150,6 +157,63 @@ where
     callback(preverify_ok != 0, ctx) as c_int
 }
 
+pub(super) unsafe extern "C" fn raw_custom_verify<F>(
+    ssl: *mut ffi::SSL,
+    out_alert: *mut u8,
+) -> ffi::ssl_verify_result_t
+where
+    F: Fn(&mut SslRef) -> Result<(), SslVerifyError> + 'static + Sync + Send,
+{
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+    let out_alert = unsafe { &mut *out_alert };
+
+    // The callback is configured on the connection when it is created, so it must be looked up
+    // in the original context even if the SNI callback has switched to another one since.
+    let session_ctx = ssl
+        .ex_data(*SESSION_CTX_INDEX)
+        .expect("BUG: session context missing")
+        .clone();
+    let callback = session_ctx
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: custom verify callback missing");
+
+    custom_verify_result(callback(ssl), out_alert)
+}
+
+pub(super) unsafe extern "C" fn ssl_raw_custom_verify<F>(
+    ssl: *mut ffi::SSL,
+    out_alert: *mut u8,
+) -> ffi::ssl_verify_result_t
+where
+    F: Fn(&mut SslRef) -> Result<(), SslVerifyError> + 'static + Sync + Send,
+{
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+    let out_alert = unsafe { &mut *out_alert };
+
+    let callback = ssl
+        .ex_data(Ssl::cached_ex_index::<Arc<F>>())
+        .expect("BUG: ssl custom verify callback missing")
+        .clone();
+
+    custom_verify_result(callback(ssl), out_alert)
+}
+
+fn custom_verify_result(
+    result: Result<(), SslVerifyError>,
+    out_alert: &mut u8,
+) -> ffi::ssl_verify_result_t {
+    match result {
+        Ok(()) => ffi::ssl_verify_result_t::ssl_verify_ok,
+        Err(SslVerifyError::Invalid(alert)) => {
+            *out_alert = alert.0 as u8;
+            ffi::ssl_verify_result_t::ssl_verify_invalid
+        }
+        Err(SslVerifyError::Retry) => ffi::ssl_verify_result_t::ssl_verify_retry,
+    }
+}
+
 pub(super) unsafe extern "C" fn raw_sni<F>(
     ssl: *mut ffi::SSL,
     al: *mut c_int,
@@ -234,6 +298,47 @@ where
     }
 }
 
+pub(super) unsafe extern "C" fn raw_cert<F>(ssl: *mut ffi::SSL, _: *mut c_void) -> c_int
+where
+    F: Fn(&mut SslRef) -> Result<(), CertCallbackError> + 'static + Sync + Send,
+{
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+
+    // The request is only exposed by BoringSSL during the handshake.
+    if !ssl.is_server() {
+        let request = CertificateRequest::new(ssl);
+        ssl.set_ex_data(Ssl::cached_ex_index::<CertificateRequest>(), request);
+    }
+
+    let ssl_context = ssl.ssl_context().to_owned();
+    let callback = ssl_context
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: cert callback missing");
+
+    match callback(ssl) {
+        Ok(()) => 1,
+        Err(e) => e.0,
+    }
+}
+
+pub(super) unsafe extern "C" fn raw_dos_protection<F>(
+    client_hello: *const ffi::SSL_CLIENT_HELLO,
+) -> c_int
+where
+    F: Fn(ClientHello<'_>) -> bool + Sync + Send + 'static,
+{
+    // SAFETY: boring provides valid inputs.
+    let client_hello = ClientHello(unsafe { &*client_hello });
+
+    let ssl_context = client_hello.ssl().ssl_context().to_owned();
+    let callback = ssl_context
+        .ex_data(SslContext::cached_ex_index::<F>())
+        .expect("BUG: dos protection callback missing");
+
+    callback(client_hello) as c_int
+}
+
 pub(super) unsafe extern "C" fn raw_tlsext_status<F>(ssl: *mut ffi::SSL, _: *mut c_void) -> c_int
 where
     F: Fn(&mut SslRef) -> Result<bool, ErrorStack> + 'static + Sync + Send,
@@ -368,6 +473,47 @@ where
     callback(ssl, line);
 }
 
+pub(super) unsafe extern "C" fn raw_info(ssl: *const ffi::SSL, type_: c_int, value: c_int) {
+    // SAFETY: boring provides valid inputs, and the connection is not otherwise borrowed while
+    // its info callback runs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl as *mut _) };
+
+    stats::record(ssl, type_, value);
+
+    let wants_outcome = ssl
+        .ssl_context()
+        .ex_data(SslContext::cached_ex_index::<HandshakeOutcomeCallback>())
+        .is_some();
+
+    if type_ & ffi::SSL_CB_HANDSHAKE_START as c_int != 0 && wants_outcome {
+        let index = Ssl::cached_ex_index::<HandshakeStart>();
+        let start = HandshakeStart(Instant::now());
+
+        match ssl.ex_data_mut(index) {
+            Some(slot) => *slot = start,
+            None => ssl.set_ex_data(index, start),
+        }
+    }
+
+    if type_ & ffi::SSL_CB_HANDSHAKE_DONE as c_int == 0 {
+        return;
+    }
+
+    let ctx = ssl.ssl_context();
+
+    if let Some(callback) = ctx.ex_data(SslContext::cached_ex_index::<HandshakeDoneCallback>()) {
+        (callback.0)(ssl);
+    }
+
+    if let Some(callback) = ctx.ex_data(SslContext::cached_ex_index::<HandshakeOutcomeCallback>()) {
+        let started = ssl
+            .ex_data(Ssl::cached_ex_index::<HandshakeStart>())
+            .map(|start| start.0);
+
+        (callback.0)(ssl, &HandshakeOutcome::new(ssl, started));
+    }
+}
+
 pub(super) unsafe extern "C" fn raw_sign<M>(
     ssl: *mut ffi::SSL,
     out: *mut u8,
@@ -446,7 +592,97 @@ where
         .ex_data(SslContext::cached_ex_index::<M>())
         .expect("BUG: private key method missing");
 
-    match callback(method, ssl, output) {
+    private_key_result(callback(method, ssl, output), max_out, out_len)
+}
+
+pub(super) unsafe extern "C" fn ssl_raw_sign<M>(
+    ssl: *mut ffi::SSL,
+    out: *mut u8,
+    out_len: *mut usize,
+    max_out: usize,
+    signature_algorithm: u16,
+    in_: *const u8,
+    in_len: usize,
+) -> ffi::ssl_private_key_result_t
+where
+    M: PrivateKeyMethod,
+{
+    // SAFETY: boring provides valid inputs.
+    let input = unsafe { slice::from_raw_parts(in_, in_len) };
+
+    let signature_algorithm = SslSignatureAlgorithm(signature_algorithm);
+
+    let callback = |method: &M, ssl: &mut _, output: &mut _| {
+        method.sign(ssl, input, signature_algorithm, output)
+    };
+
+    // SAFETY: boring provides valid inputs.
+    unsafe { ssl_raw_private_key_callback(ssl, out, out_len, max_out, callback) }
+}
+
+pub(super) unsafe extern "C" fn ssl_raw_decrypt<M>(
+    ssl: *mut ffi::SSL,
+    out: *mut u8,
+    out_len: *mut usize,
+    max_out: usize,
+    in_: *const u8,
+    in_len: usize,
+) -> ffi::ssl_private_key_result_t
+where
+    M: PrivateKeyMethod,
+{
+    // SAFETY: boring provides valid inputs.
+    let input = unsafe { slice::from_raw_parts(in_, in_len) };
+
+    let callback = |method: &M, ssl: &mut _, output: &mut _| method.decrypt(ssl, input, output);
+
+    // SAFETY: boring provides valid inputs.
+    unsafe { ssl_raw_private_key_callback(ssl, out, out_len, max_out, callback) }
+}
+
+pub(super) unsafe extern "C" fn ssl_raw_complete<M>(
+    ssl: *mut ffi::SSL,
+    out: *mut u8,
+    out_len: *mut usize,
+    max_out: usize,
+) -> ffi::ssl_private_key_result_t
+where
+    M: PrivateKeyMethod,
+{
+    // SAFETY: boring provides valid inputs.
+    unsafe { ssl_raw_private_key_callback::<M>(ssl, out, out_len, max_out, M::complete) }
+}
+
+unsafe fn ssl_raw_private_key_callback<M>(
+    ssl: *mut ffi::SSL,
+    out: *mut u8,
+    out_len: *mut usize,
+    max_out: usize,
+    callback: impl FnOnce(&M, &mut SslRef, &mut [u8]) -> Result<usize, PrivateKeyMethodError>,
+) -> ffi::ssl_private_key_result_t
+where
+    M: PrivateKeyMethod,
+{
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr_mut(ssl) };
+    let output = unsafe { slice::from_raw_parts_mut(out, max_out) };
+    let out_len = unsafe { &mut *out_len };
+
+    // The method is passed `&mut SslRef`, through which it could replace itself.
+    let method = ssl
+        .ex_data(Ssl::cached_ex_index::<Arc<M>>())
+        .expect("BUG: ssl private key method missing")
+        .clone();
+
+    private_key_result(callback(&method, ssl, output), max_out, out_len)
+}
+
+fn private_key_result(
+    result: Result<usize, PrivateKeyMethodError>,
+    max_out: usize,
+    out_len: &mut usize,
+) -> ffi::ssl_private_key_result_t {
+    match result {
         Ok(written) => {
             assert!(written <= max_out);
 
@@ -454,6 +690,98 @@ where
 
             ffi::ssl_private_key_result_t::ssl_private_key_success
         }
-        Err(err) => err.0,
+        Err(err) => err.into_raw(),
     }
 }
+
+pub(super) unsafe extern "C" fn raw_ticket_key(
+    ssl: *mut ffi::SSL,
+    key_name: *mut u8,
+    iv: *mut u8,
+    evp_ctx: *mut ffi::EVP_CIPHER_CTX,
+    hmac_ctx: *mut ffi::HMAC_CTX,
+    encrypt: c_int,
+) -> c_int {
+    // SAFETY: boring provides valid inputs.
+    let ssl = unsafe { SslRef::from_ptr(ssl) };
+    let key_name = unsafe { slice::from_raw_parts_mut(key_name, 16) };
+    let iv = unsafe { slice::from_raw_parts_mut(iv, 16) };
+
+    let manager = ssl
+        .ex_data(*SESSION_CTX_INDEX)
+        .expect("BUG: session context missing")
+        .ex_data(SslContext::cached_ex_index::<TicketKeyManager>())
+        .expect("BUG: ticket key manager missing");
+
+    let encrypt = encrypt == 1;
+
+    let (key, renew) = if encrypt {
+        let key = match manager.current_key() {
+            Ok(key) => key,
+            Err(_) => return -1,
+        };
+
+        if rand_bytes(iv).is_err() {
+            return -1;
+        }
+
+        key_name.copy_from_slice(key.name());
+
+        (key, false)
+    } else {
+        match manager.find_key(key_name) {
+            Ok(Some(found)) => found,
+            Ok(None) => return 0,
+            Err(_) => return -1,
+        }
+    };
+
+    let cipher = Cipher::aes_128_cbc();
+    let digest = MessageDigest::sha256();
+
+    // SAFETY: boring provides valid contexts, and the key and IV sizes match the cipher.
+    unsafe {
+        let init = if encrypt {
+            ffi::EVP_EncryptInit_ex
+        } else {
+            ffi::EVP_DecryptInit_ex
+        };
+
+        if init(
+            evp_ctx,
+            cipher.as_ptr(),
+            ptr::null_mut(),
+            key.aes_key().as_ptr(),
+            iv.as_ptr(),
+        ) != 1
+        {
+            return -1;
+        }
+
+        if ffi::HMAC_Init_ex(
+            hmac_ctx,
+            key.hmac_key().as_ptr() as *const c_void,
+            key.hmac_key().len(),
+            digest.as_ptr(),
+            ptr::null_mut(),
+        ) != 1
+        {
+            return -1;
+        }
+    }
+
+    if renew {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(feature = "unsafe-deterministic-rng")]
+pub(super) unsafe extern "C" fn raw_deterministic_time(
+    _ssl: *const ffi::SSL,
+    out_clock: *mut ffi::timeval,
+) {
+    (*out_clock).tv_sec = super::DETERMINISTIC_TIME as _;
+    (*out_clock).tv_usec = 0;
+}
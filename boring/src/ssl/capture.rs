@@ -0,0 +1,111 @@
+use std::cmp;
+use std::ffi::c_void;
+
+use crate::ssl::{
+    ClientHello, ConnectConfiguration, Error, ErrorCode, MemSsl, SelectCertError, Ssl, SslContext,
+    SslMethod,
+};
+
+const BUFFER_SIZE: usize = 16 * 1024;
+
+/// The visitor of [`ClientHello::replay`], stored in the `Ssl` for the duration of the
+/// handshake step parsing the ClientHello.
+struct Visitor(*mut c_void);
+
+// SAFETY: the pointer is only dereferenced by the thread which stored it, before it returns.
+unsafe impl Send for Visitor {}
+unsafe impl Sync for Visitor {}
+
+impl ConnectConfiguration {
+    /// Returns the ClientHello this configuration sends when connecting to `domain`.
+    ///
+    /// The ClientHello is serialized exactly as it would be sent on the wire, TLS record headers
+    /// included, without connecting to any peer. It can be inspected offline with
+    /// [`ClientHello::replay`], for instance to check that a configuration keeps the same
+    /// fingerprint across upgrades.
+    ///
+    /// Random values such as the client random, key shares and GREASE values differ between
+    /// captures.
+    pub fn capture_client_hello(self, domain: &str) -> Result<Vec<u8>, Error> {
+        let mut ssl = self.into_ssl(domain)?;
+        ssl.set_connect_state();
+
+        #[cfg(feature = "kx-safe-default")]
+        ssl.client_set_default_curves_list();
+
+        let mut client = MemSsl::new(ssl, BUFFER_SIZE)?;
+        client.ssl_mut().do_handshake_step()?;
+
+        let mut records = vec![0; client.bio().pending()];
+        let len = client.bio_mut().read(&mut records);
+        records.truncate(len);
+
+        Ok(records)
+    }
+}
+
+impl ClientHello<'_> {
+    /// Parses a ClientHello captured with [`ConnectConfiguration::capture_client_hello`], or
+    /// off the wire, and calls `f` with it.
+    ///
+    /// `records` holds the TLS records carrying the ClientHello. They are fed to a throwaway
+    /// server session, whose handshake is aborted as soon as the ClientHello is parsed, so the
+    /// view passed to `f` is the one a server callback such as
+    /// [`SslContextBuilder::set_select_certificate_callback`] would see.
+    ///
+    /// An error with the [`ErrorCode::WANT_READ`] code is returned if `records` does not hold a
+    /// complete ClientHello.
+    ///
+    /// [`SslContextBuilder::set_select_certificate_callback`]: crate::ssl::SslContextBuilder::set_select_certificate_callback
+    pub fn replay<F, R>(records: &[u8], f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&ClientHello<'_>) -> R,
+    {
+        let mut ctx = SslContext::builder(SslMethod::tls())?;
+        ctx.set_select_certificate_callback(|client_hello| {
+            if let Some(visitor) = client_hello
+                .ssl()
+                .ex_data(Ssl::cached_ex_index::<Visitor>())
+            {
+                // SAFETY: the visitor outlives the handshake step, see below.
+                let visit = unsafe { &mut *visitor.0.cast::<&mut dyn FnMut(&ClientHello<'_>)>() };
+                visit(&client_hello);
+            }
+
+            Err(SelectCertError::ERROR)
+        });
+        let ctx = ctx.build();
+
+        let mut f = Some(f);
+        let mut result = None;
+        let mut visit = |client_hello: &ClientHello<'_>| {
+            if let Some(f) = f.take() {
+                result = Some(f(client_hello));
+            }
+        };
+        let mut visit: &mut dyn FnMut(&ClientHello<'_>) = &mut visit;
+
+        let mut ssl = Ssl::new(&ctx)?;
+        ssl.set_accept_state();
+        ssl.set_ex_data(
+            Ssl::cached_ex_index::<Visitor>(),
+            Visitor((&mut visit as *mut &mut dyn FnMut(&ClientHello<'_>)).cast()),
+        );
+
+        let mut server = MemSsl::new(ssl, cmp::max(records.len(), BUFFER_SIZE))?;
+        server.bio_mut().write(records);
+
+        // The select certificate callback is called synchronously, while `visit` is borrowed.
+        let step = server.ssl_mut().do_handshake_step();
+        drop(server);
+
+        match (result, step) {
+            (Some(result), _) => Ok(result),
+            (None, Err(e)) => Err(e),
+            (None, Ok(_)) => Err(Error {
+                code: ErrorCode::WANT_READ,
+                cause: None,
+            }),
+        }
+    }
+}
@@ -1,13 +1,22 @@
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
 
 use crate::dh::Dh;
 use crate::error::ErrorStack;
+use crate::ffi;
+#[cfg(not(feature = "kx-safe-default"))]
+use crate::ssl::SslCurve;
 use crate::ssl::{
     HandshakeError, Ssl, SslContext, SslContextBuilder, SslContextRef, SslMethod, SslMode,
-    SslOptions, SslRef, SslStream, SslVerifyMode,
+    SslOptions, SslRef, SslStats, SslStream, SslVerifyMode, SslVersion, StatusType,
 };
 use crate::version;
+use crate::x509::store::X509StoreBuilder;
+use crate::x509::{X509StoreContext, X509StoreContextRef, X509VerifyResult, X509};
 use std::net::IpAddr;
 
 use super::MidHandshakeSslStream;
@@ -133,6 +142,8 @@ impl SslConnector {
             ssl,
             sni: true,
             verify_hostname: true,
+            ocsp_stapling_policy: OcspStaplingPolicy::Ignore,
+            require_alpn: false,
         })
     }
 
@@ -151,6 +162,51 @@ impl SslConnector {
 pub struct SslConnectorBuilder(SslContextBuilder);
 
 impl SslConnectorBuilder {
+    /// Trusts exactly the given root certificates, replacing the default verify paths.
+    ///
+    /// This configures trust entirely in memory, e.g. from certificates embedded in the binary
+    /// and parsed with [`X509::stack_from_pem`] or [`X509::from_der`], for clients running
+    /// without a system certificate directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use boring::ssl::{SslConnector, SslMethod};
+    /// use boring::x509::X509;
+    ///
+    /// # const ROOTS_PEM: &[u8] = b"";
+    /// let roots = X509::stack_from_pem(ROOTS_PEM).unwrap();
+    ///
+    /// let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    /// connector.set_trusted_certs(roots).unwrap();
+    /// ```
+    pub fn set_trusted_certs<I>(&mut self, certs: I) -> Result<(), ErrorStack>
+    where
+        I: IntoIterator<Item = X509>,
+    {
+        let mut store = X509StoreBuilder::new()?;
+        for cert in certs {
+            store.add_cert(cert)?;
+        }
+        self.set_cert_store(store.build());
+
+        Ok(())
+    }
+
+    /// Trusts exactly the Mozilla root certificates bundled by the `webpki-root-certs` crate,
+    /// replacing the default verify paths.
+    ///
+    /// Requires the `webpki-roots` Cargo feature.
+    #[cfg(feature = "webpki-roots")]
+    pub fn use_webpki_roots(&mut self) -> Result<(), ErrorStack> {
+        let certs = webpki_root_certs::TLS_SERVER_ROOT_CERTS
+            .iter()
+            .map(|cert| X509::from_der(cert))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.set_trusted_certs(certs)
+    }
+
     /// Consumes the builder, returning an `SslConnector`.
     pub fn build(self) -> SslConnector {
         SslConnector(self.0.build())
@@ -171,11 +227,37 @@ impl DerefMut for SslConnectorBuilder {
     }
 }
 
+/// A function validating the OCSP response stapled by the server.
+///
+/// It is called with the DER-encoded response and the verification context of the server's leaf
+/// certificate, and returns whether the response is acceptable, e.g. whether it reports the
+/// certificate as good and is not expired.
+pub type OcspValidator = dyn Fn(&[u8], &X509StoreContextRef) -> bool + 'static + Sync + Send;
+
+/// How a client treats the OCSP response stapled by the server.
+#[derive(Clone, Default)]
+pub enum OcspStaplingPolicy {
+    /// Stapled responses are neither requested nor required.
+    #[default]
+    Ignore,
+    /// A stapled response is requested, and certificate verification fails if the server does not
+    /// provide one.
+    ///
+    /// The response is neither parsed nor validated: its status, validity period and the
+    /// certificate it is about are not checked. Use [`OcspStaplingPolicy::Validate`] to check them.
+    RequirePresent,
+    /// A stapled response is requested, and certificate verification fails if the server does not
+    /// provide one or if the validator rejects it.
+    Validate(Arc<OcspValidator>),
+}
+
 /// A type which allows for configuration of a client-side TLS session before connection.
 pub struct ConnectConfiguration {
     ssl: Ssl,
     sni: bool,
     verify_hostname: bool,
+    ocsp_stapling_policy: OcspStaplingPolicy,
+    require_alpn: bool,
 }
 
 impl ConnectConfiguration {
@@ -211,6 +293,44 @@ impl ConnectConfiguration {
         self.verify_hostname = verify_hostname;
     }
 
+    /// A builder-style version of `set_ocsp_stapling_policy`.
+    pub fn ocsp_stapling_policy(mut self, policy: OcspStaplingPolicy) -> ConnectConfiguration {
+        self.set_ocsp_stapling_policy(policy);
+        self
+    }
+
+    /// Configures how the OCSP response stapled by the server is checked when connecting.
+    ///
+    /// Any policy other than [`OcspStaplingPolicy::Ignore`] requests a stapled response and
+    /// enforces it while verifying the server's certificate, after any verify callback set on the
+    /// context or the connection.
+    ///
+    /// Defaults to [`OcspStaplingPolicy::Ignore`].
+    pub fn set_ocsp_stapling_policy(&mut self, policy: OcspStaplingPolicy) {
+        self.ocsp_stapling_policy = policy;
+    }
+
+    /// A builder-style version of `set_require_alpn`.
+    pub fn require_alpn(mut self, require_alpn: bool) -> ConnectConfiguration {
+        self.set_require_alpn(require_alpn);
+        self
+    }
+
+    /// Configures whether the handshake fails if the server does not select one of the protocols
+    /// offered with [`SslContextBuilder::set_alpn_protos`] or [`SslRef::set_alpn_protos`].
+    ///
+    /// By default, a server which ignores ALPN is silently accepted, leaving the client to fall
+    /// back to a default protocol. Deployments which only speak e.g. HTTP/2 can enable this to get
+    /// a hard failure instead.
+    ///
+    /// The check is made once the handshake completes, so that resumed sessions, which skip
+    /// certificate verification, are checked too.
+    ///
+    /// Defaults to `false`.
+    pub fn set_require_alpn(&mut self, require_alpn: bool) {
+        self.require_alpn = require_alpn;
+    }
+
     /// Returns an [`Ssl`] configured to connect to the provided domain.
     ///
     /// The domain is used for SNI (if it is not an IP address) and hostname verification if enabled.
@@ -229,6 +349,19 @@ impl ConnectConfiguration {
             setup_verify_hostname(&mut self.ssl, domain)?;
         }
 
+        match self.ocsp_stapling_policy {
+            OcspStaplingPolicy::Ignore => {}
+            OcspStaplingPolicy::RequirePresent => setup_verify_ocsp(&mut self.ssl, None)?,
+            OcspStaplingPolicy::Validate(validator) => {
+                setup_verify_ocsp(&mut self.ssl, Some(validator))?
+            }
+        }
+
+        if self.require_alpn {
+            self.ssl
+                .set_ex_data(Ssl::cached_ex_index::<AlpnRequired>(), AlpnRequired);
+        }
+
         Ok(self.ssl)
     }
 
@@ -370,6 +503,23 @@ impl SslAcceptor {
         Ok(SslAcceptorBuilder(ctx))
     }
 
+    /// Creates a new builder configured to connect to modern clients, which all support TLS 1.3.
+    ///
+    /// This corresponds to the modern configuration of version 5 of Mozilla's server side TLS
+    /// recommendations, see [`SecurityProfileBuilder::mozilla_modern`].
+    pub fn mozilla_modern_v5(method: SslMethod) -> Result<SslAcceptorBuilder, ErrorStack> {
+        SecurityProfileBuilder::mozilla_modern().acceptor(method)
+    }
+
+    /// Creates a new builder configured to connect to legacy clients, down to TLS 1.0.
+    ///
+    /// This corresponds to the old configuration of version 5 of Mozilla's server side TLS
+    /// recommendations, see [`SecurityProfileBuilder::mozilla_old`]. It should only be used when
+    /// such clients must be supported.
+    pub fn mozilla_old_v5(method: SslMethod) -> Result<SslAcceptorBuilder, ErrorStack> {
+        SecurityProfileBuilder::mozilla_old().acceptor(method)
+    }
+
     /// Initiates a server-side TLS handshake on a stream.
     ///
     /// See [`Ssl::setup_accept`] for more details.
@@ -404,6 +554,14 @@ impl SslAcceptor {
     pub fn context(&self) -> &SslContextRef {
         &self.0
     }
+
+    /// Returns a snapshot of the handshake counters of the acceptor, if enabled with
+    /// [`SslContextBuilder::enable_stats`].
+    ///
+    /// See [`SslContextRef::stats`].
+    pub fn stats(&self) -> Option<SslStats> {
+        self.0.stats()
+    }
 }
 
 /// A builder for `SslAcceptor`s.
@@ -430,10 +588,280 @@ impl DerefMut for SslAcceptorBuilder {
     }
 }
 
+/// Mozilla's cipher suites for the intermediate configuration.
+///
+/// BoringSSL doesn't implement finite-field Diffie-Hellman, so the `DHE` suites are ignored.
+const MOZILLA_INTERMEDIATE_CIPHERS: &str =
+    "ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:ECDHE-ECDSA-AES256-GCM-SHA384:\
+     ECDHE-RSA-AES256-GCM-SHA384:ECDHE-ECDSA-CHACHA20-POLY1305:ECDHE-RSA-CHACHA20-POLY1305:\
+     DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:DHE-RSA-CHACHA20-POLY1305";
+
+/// Mozilla's cipher suites for the old configuration.
+///
+/// Suites which BoringSSL doesn't implement are ignored.
+const MOZILLA_OLD_CIPHERS: &str =
+    "ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:ECDHE-ECDSA-AES256-GCM-SHA384:\
+     ECDHE-RSA-AES256-GCM-SHA384:ECDHE-ECDSA-CHACHA20-POLY1305:ECDHE-RSA-CHACHA20-POLY1305:\
+     DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:DHE-RSA-CHACHA20-POLY1305:\
+     ECDHE-ECDSA-AES128-SHA256:ECDHE-RSA-AES128-SHA256:ECDHE-ECDSA-AES128-SHA:ECDHE-RSA-AES128-SHA:\
+     ECDHE-ECDSA-AES256-SHA384:ECDHE-RSA-AES256-SHA384:ECDHE-ECDSA-AES256-SHA:ECDHE-RSA-AES256-SHA:\
+     DHE-RSA-AES128-SHA256:DHE-RSA-AES256-SHA256:AES128-GCM-SHA256:AES256-GCM-SHA384:AES128-SHA256:\
+     AES256-SHA256:AES128-SHA:AES256-SHA:DES-CBC3-SHA";
+
+/// Mozilla's curves for all configurations.
+#[cfg(not(feature = "kx-safe-default"))]
+const MOZILLA_CURVES: &[SslCurve] = &[SslCurve::X25519, SslCurve::SECP256R1, SslCurve::SECP384R1];
+
+/// A builder for the TLS settings of a server or client, such as protocol versions, cipher suites
+/// and curves.
+///
+/// It starts either from BoringSSL's defaults, with [`SecurityProfileBuilder::new`], or from one
+/// of the configurations of version 5.7 of Mozilla's [server side TLS recommendations][docs],
+/// which can then be adjusted. For example, the old configuration's cipher suites without TLS 1.0
+/// and 1.1 are:
+///
+/// ```
+/// use boring::ssl::{SecurityProfileBuilder, SslMethod, SslVersion};
+///
+/// let acceptor = SecurityProfileBuilder::mozilla_old()
+///     .min_version(Some(SslVersion::TLS1_2))
+///     .acceptor(SslMethod::tls())
+///     .unwrap();
+/// ```
+///
+/// [docs]: https://wiki.mozilla.org/Security/Server_Side_TLS
+#[derive(Debug, Clone)]
+pub struct SecurityProfileBuilder {
+    min_version: Option<SslVersion>,
+    max_version: Option<SslVersion>,
+    cipher_list: Option<String>,
+    #[cfg(not(feature = "kx-safe-default"))]
+    curves: Option<Vec<SslCurve>>,
+    server_cipher_preference: bool,
+    session_tickets: bool,
+}
+
+impl Default for SecurityProfileBuilder {
+    fn default() -> SecurityProfileBuilder {
+        SecurityProfileBuilder::new()
+    }
+}
+
+impl SecurityProfileBuilder {
+    /// Creates a builder which keeps BoringSSL's defaults.
+    pub fn new() -> SecurityProfileBuilder {
+        SecurityProfileBuilder {
+            min_version: None,
+            max_version: None,
+            cipher_list: None,
+            #[cfg(not(feature = "kx-safe-default"))]
+            curves: None,
+            server_cipher_preference: false,
+            session_tickets: true,
+        }
+    }
+
+    /// Returns Mozilla's modern configuration, for clients which all support TLS 1.3.
+    ///
+    /// Only TLS 1.3 is enabled, whose cipher suites BoringSSL doesn't allow configuring.
+    pub fn mozilla_modern() -> SecurityProfileBuilder {
+        SecurityProfileBuilder::new()
+            .min_version(Some(SslVersion::TLS1_3))
+            .mozilla_curves()
+    }
+
+    /// Returns Mozilla's intermediate configuration, the recommended default for general purpose
+    /// servers.
+    ///
+    /// TLS 1.2 and 1.3 are enabled, with AEAD cipher suites and forward secrecy only.
+    pub fn mozilla_intermediate() -> SecurityProfileBuilder {
+        SecurityProfileBuilder::new()
+            .min_version(Some(SslVersion::TLS1_2))
+            .cipher_list(MOZILLA_INTERMEDIATE_CIPHERS)
+            .mozilla_curves()
+    }
+
+    /// Returns Mozilla's old configuration, for services which must support very old clients.
+    ///
+    /// TLS 1.0 through 1.3 are enabled, with CBC and 3DES cipher suites, and the server picks the
+    /// cipher suite.
+    pub fn mozilla_old() -> SecurityProfileBuilder {
+        SecurityProfileBuilder::new()
+            .min_version(Some(SslVersion::TLS1))
+            .cipher_list(MOZILLA_OLD_CIPHERS)
+            .server_cipher_preference(true)
+            .mozilla_curves()
+    }
+
+    #[cfg(not(feature = "kx-safe-default"))]
+    fn mozilla_curves(self) -> SecurityProfileBuilder {
+        self.curves(MOZILLA_CURVES)
+    }
+
+    // NOTE: with the `kx-*` features, the curves are chosen at compile time instead.
+    #[cfg(feature = "kx-safe-default")]
+    fn mozilla_curves(self) -> SecurityProfileBuilder {
+        self
+    }
+
+    /// Sets the minimum supported protocol version, or the lowest version BoringSSL supports if
+    /// `None`.
+    pub fn min_version(mut self, version: Option<SslVersion>) -> SecurityProfileBuilder {
+        self.min_version = version;
+        self
+    }
+
+    /// Sets the maximum supported protocol version, or the highest version BoringSSL supports if
+    /// `None`.
+    pub fn max_version(mut self, version: Option<SslVersion>) -> SecurityProfileBuilder {
+        self.max_version = version;
+        self
+    }
+
+    /// Sets the TLS 1.2 and earlier cipher suites, in OpenSSL's cipher list format.
+    ///
+    /// See [`SslContextBuilder::set_cipher_list`].
+    pub fn cipher_list(mut self, cipher_list: &str) -> SecurityProfileBuilder {
+        self.cipher_list = Some(cipher_list.to_string());
+        self
+    }
+
+    /// Sets the supported curves, in order of preference.
+    #[cfg(not(feature = "kx-safe-default"))]
+    pub fn curves(mut self, curves: &[SslCurve]) -> SecurityProfileBuilder {
+        self.curves = Some(curves.to_vec());
+        self
+    }
+
+    /// Sets whether the server, rather than the client, picks the cipher suite by its own order
+    /// of preference.
+    ///
+    /// Defaults to `false`.
+    pub fn server_cipher_preference(mut self, enabled: bool) -> SecurityProfileBuilder {
+        self.server_cipher_preference = enabled;
+        self
+    }
+
+    /// Sets whether stateless session resumption with session tickets is enabled.
+    ///
+    /// Defaults to `true`.
+    pub fn session_tickets(mut self, enabled: bool) -> SecurityProfileBuilder {
+        self.session_tickets = enabled;
+        self
+    }
+
+    /// Applies the settings to `ctx`.
+    pub fn apply(&self, ctx: &mut SslContextBuilder) -> Result<(), ErrorStack> {
+        ctx.set_min_proto_version(self.min_version)?;
+        ctx.set_max_proto_version(self.max_version)?;
+
+        if let Some(cipher_list) = &self.cipher_list {
+            ctx.set_cipher_list(cipher_list)?;
+        }
+
+        #[cfg(not(feature = "kx-safe-default"))]
+        if let Some(curves) = &self.curves {
+            ctx.set_curves(curves)?;
+        }
+
+        if self.server_cipher_preference {
+            ctx.set_options(SslOptions::CIPHER_SERVER_PREFERENCE);
+        } else {
+            ctx.clear_options(SslOptions::CIPHER_SERVER_PREFERENCE);
+        }
+
+        if self.session_tickets {
+            ctx.clear_options(SslOptions::NO_TICKET);
+        } else {
+            ctx.set_options(SslOptions::NO_TICKET);
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new `SslAcceptor` builder with these settings.
+    pub fn acceptor(&self, method: SslMethod) -> Result<SslAcceptorBuilder, ErrorStack> {
+        let mut ctx = ctx(ContextType::WithMethod(method))?;
+        self.apply(&mut ctx)?;
+        Ok(SslAcceptorBuilder(ctx))
+    }
+}
+
 fn setup_verify(ctx: &mut SslContextBuilder) {
     ctx.set_verify(SslVerifyMode::PEER);
 }
 
+/// Installs a verify callback which checks, along with the leaf certificate, the stapled OCSP
+/// response with its optional validator.
+///
+/// The callback previously set on the connection, which defaults to the one of its context, is
+/// still called first.
+fn setup_verify_ocsp(
+    ssl: &mut SslRef,
+    validator: Option<Arc<OcspValidator>>,
+) -> Result<(), ErrorStack> {
+    ssl.set_status_type(StatusType::OCSP)?;
+
+    let previous = unsafe { ffi::SSL_get_verify_callback(ssl.as_ptr()) };
+    let mode = ssl.verify_mode() | SslVerifyMode::PEER;
+
+    ssl.set_verify_callback(mode, move |preverify_ok, x509_ctx| {
+        let preverify_ok = match previous {
+            Some(previous) => unsafe { previous(preverify_ok as c_int, x509_ctx.as_ptr()) != 0 },
+            None => preverify_ok,
+        };
+
+        // Only the leaf certificate is covered by the stapled response.
+        if !preverify_ok || x509_ctx.error_depth() != 0 {
+            return preverify_ok;
+        }
+
+        let ssl_idx = X509StoreContext::ssl_idx().expect("BUG: store context ssl index missing");
+
+        let ok = match x509_ctx.ex_data(ssl_idx).and_then(|ssl| ssl.ocsp_status()) {
+            Some(response) => validator
+                .as_ref()
+                .is_none_or(|validator| validator(response, x509_ctx)),
+            None => false,
+        };
+
+        if !ok {
+            x509_ctx.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+        }
+
+        ok
+    });
+
+    Ok(())
+}
+
+/// Marks a connection whose handshake fails if the server does not select an ALPN protocol.
+struct AlpnRequired;
+
+/// Fails the completed handshake of `ssl` if it requires ALPN and the server did not select a
+/// protocol.
+pub(super) fn check_alpn(ssl: &SslRef) -> Result<(), ErrorStack> {
+    if ssl
+        .ex_data(Ssl::cached_ex_index::<AlpnRequired>())
+        .is_none()
+        || ssl.selected_alpn_protocol().is_some()
+    {
+        return Ok(());
+    }
+
+    unsafe {
+        ffi::ERR_put_error(
+            ffi::ERR_LIB_SSL.0 as _,
+            0,
+            ffi::SSL_R_NO_APPLICATION_PROTOCOL as _,
+            concat!(file!(), "\0").as_ptr() as *const _,
+            line!() as _,
+        );
+    }
+
+    Err(ErrorStack::get())
+}
+
 fn setup_verify_hostname(ssl: &mut SslRef, domain: &str) -> Result<(), ErrorStack> {
     use crate::x509::verify::X509CheckFlags;
 
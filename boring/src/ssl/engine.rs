@@ -0,0 +1,222 @@
+use crate::ssl::{Error, ErrorCode, HandshakeState, MemSsl, Ssl, SslRef};
+
+/// A TLS session as a state machine which consumes and produces ciphertext in memory.
+///
+/// The engine performs no I/O: the caller feeds the ciphertext received from the peer with
+/// [`SslEngine::process_inbound`], and sends the ciphertext collected with
+/// [`SslEngine::take_outbound`]. [`SslEngine::wants_read`] and [`SslEngine::wants_write`] tell
+/// which of the two the engine is waiting for. This lets event loops and completion-based
+/// runtimes such as io_uring drive TLS without `Read` and `Write` adapters.
+///
+/// The handshake is driven automatically; application data is exchanged with
+/// [`SslEngine::read_plaintext`] and [`SslEngine::write_plaintext`] once it completed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{Ssl, SslConnector, SslEngine, SslMethod};
+///
+/// # fn send(_: &[u8]) {}
+/// # fn recv() -> Vec<u8> { vec![] }
+/// let connector = SslConnector::builder(SslMethod::tls()).unwrap().build();
+/// let ssl = connector.configure().unwrap().into_ssl("example.com").unwrap();
+/// let mut engine = SslEngine::connect(ssl).unwrap();
+///
+/// let mut outbound = vec![];
+/// while engine.is_handshaking() {
+///     engine.take_outbound(&mut outbound).unwrap();
+///     send(&outbound);
+///     outbound.clear();
+///
+///     if engine.wants_read() {
+///         engine.process_inbound(&recv()).unwrap();
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SslEngine {
+    mem: MemSsl,
+    handshaking: bool,
+    want_read: bool,
+    closed: bool,
+}
+
+impl SslEngine {
+    /// The number of bytes of ciphertext buffered in each direction by default, that of a full
+    /// TLS record.
+    pub const DEFAULT_BUFFER_SIZE: usize = 16 * 1024;
+
+    /// Creates an engine for the client side of a session, and starts the handshake.
+    pub fn connect(ssl: Ssl) -> Result<SslEngine, Error> {
+        SslEngine::connect_with_buffer_size(ssl, SslEngine::DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Creates an engine for the server side of a session, and starts the handshake.
+    pub fn accept(ssl: Ssl) -> Result<SslEngine, Error> {
+        SslEngine::accept_with_buffer_size(ssl, SslEngine::DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Like [`SslEngine::connect`], but buffers up to `buffer_size` bytes of ciphertext in each
+    /// direction.
+    ///
+    /// Both buffers are allocated once used and kept for the lifetime of the engine, so servers
+    /// handling many mostly idle sessions can use smaller buffers to save memory. Records larger
+    /// than the buffers are then fed and taken out in several calls.
+    pub fn connect_with_buffer_size(mut ssl: Ssl, buffer_size: usize) -> Result<SslEngine, Error> {
+        ssl.set_connect_state();
+        SslEngine::new(ssl, buffer_size)
+    }
+
+    /// Like [`SslEngine::accept`], but buffers up to `buffer_size` bytes of ciphertext in each
+    /// direction.
+    ///
+    /// See [`SslEngine::connect_with_buffer_size`].
+    pub fn accept_with_buffer_size(mut ssl: Ssl, buffer_size: usize) -> Result<SslEngine, Error> {
+        ssl.set_accept_state();
+        SslEngine::new(ssl, buffer_size)
+    }
+
+    fn new(ssl: Ssl, buffer_size: usize) -> Result<SslEngine, Error> {
+        let mut engine = SslEngine {
+            mem: MemSsl::new(ssl, buffer_size)?,
+            handshaking: true,
+            want_read: false,
+            closed: false,
+        };
+        engine.handshake()?;
+
+        Ok(engine)
+    }
+
+    /// Returns a shared reference to the `Ssl` object.
+    pub fn ssl(&self) -> &SslRef {
+        self.mem.ssl()
+    }
+
+    /// Returns a mutable reference to the `Ssl` object.
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        self.mem.ssl_mut()
+    }
+
+    /// Returns `true` until the handshake completed.
+    pub fn is_handshaking(&self) -> bool {
+        self.handshaking
+    }
+
+    /// Returns `true` if the engine is waiting for ciphertext from the peer.
+    pub fn wants_read(&self) -> bool {
+        self.want_read && !self.closed
+    }
+
+    /// Returns `true` if the engine has ciphertext to send to the peer.
+    pub fn wants_write(&self) -> bool {
+        self.mem.bio().pending() > 0
+    }
+
+    /// Returns `true` once the peer closed the session with a `close_notify` alert.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Feeds ciphertext received from the peer, advancing the handshake if it is in progress.
+    ///
+    /// Returns the number of bytes consumed. Once the inbound buffer is full, the remaining
+    /// bytes must be fed again after application data was read with
+    /// [`SslEngine::read_plaintext`].
+    pub fn process_inbound(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let mut consumed = 0;
+
+        while consumed < data.len() {
+            let written = self.mem.bio_mut().write(&data[consumed..]);
+            if written == 0 {
+                break;
+            }
+            consumed += written;
+            self.want_read = false;
+
+            if self.handshaking {
+                self.handshake()?;
+            }
+        }
+
+        Ok(consumed)
+    }
+
+    /// Appends the ciphertext to send to the peer to `out`, returning its length.
+    pub fn take_outbound(&mut self, out: &mut Vec<u8>) -> Result<usize, Error> {
+        let start = out.len();
+
+        loop {
+            let pending = self.mem.bio().pending();
+            if pending == 0 {
+                break;
+            }
+
+            let len = out.len();
+            out.resize(len + pending, 0);
+            let read = self.mem.bio_mut().read(&mut out[len..]);
+            out.truncate(len + read);
+
+            // A flight larger than the outbound buffer is written once it was drained.
+            if self.handshaking {
+                self.handshake()?;
+            }
+        }
+
+        Ok(out.len() - start)
+    }
+
+    /// Reads decrypted application data.
+    ///
+    /// Returns `Ok(0)` if no data is available, either because more ciphertext is needed, as
+    /// reported by [`SslEngine::wants_read`], or because the peer closed the session, as
+    /// reported by [`SslEngine::is_closed`].
+    pub fn read_plaintext(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        match self.mem.ssl_read(buf) {
+            Ok(n) => Ok(n),
+            Err(ref e) if e.code() == ErrorCode::WANT_READ => {
+                self.want_read = true;
+                Ok(0)
+            }
+            Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => {
+                self.closed = true;
+                Ok(0)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Encrypts application data, returning the number of bytes consumed.
+    ///
+    /// Returns `Ok(0)` if the outbound buffer is full, in which case the same data must be
+    /// written again once the ciphertext was taken out with [`SslEngine::take_outbound`].
+    pub fn write_plaintext(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        match self.mem.ssl_write(buf) {
+            Ok(n) => Ok(n),
+            Err(ref e) if e.code() == ErrorCode::WANT_WRITE => Ok(0),
+            Err(ref e) if e.code() == ErrorCode::WANT_READ => {
+                self.want_read = true;
+                Ok(0)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sends a `close_notify` alert to the peer.
+    pub fn close(&mut self) -> Result<(), Error> {
+        self.mem.shutdown().map(|_| ())
+    }
+
+    fn handshake(&mut self) -> Result<(), Error> {
+        match self.mem.ssl_mut().do_handshake_step()? {
+            HandshakeState::Complete => {
+                self.handshaking = false;
+                self.want_read = false;
+            }
+            HandshakeState::WantRead => self.want_read = true,
+            _ => self.want_read = false,
+        }
+
+        Ok(())
+    }
+}
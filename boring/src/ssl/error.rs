@@ -38,6 +38,19 @@ impl ErrorCode {
 
     pub const PENDING_TICKET: ErrorCode = ErrorCode(ffi::SSL_ERROR_PENDING_TICKET);
 
+    /// A custom certificate verification callback asked to be retried.
+    pub const WANT_CERTIFICATE_VERIFY: ErrorCode =
+        ErrorCode(ffi::SSL_ERROR_WANT_CERTIFICATE_VERIFY);
+
+    /// The server requested a renegotiation, which must be performed with `SslRef::renegotiate`.
+    pub const WANT_RENEGOTIATE: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_RENEGOTIATE);
+
+    /// The server rejected the early data sent by the client.
+    ///
+    /// The rejected data was not processed, and must be sent again after calling
+    /// `SslRef::reset_early_data_reject`.
+    pub const EARLY_DATA_REJECTED: ErrorCode = ErrorCode(ffi::SSL_ERROR_EARLY_DATA_REJECTED);
+
     /// A non-recoverable IO error occurred.
     pub const SYSCALL: ErrorCode = ErrorCode(ffi::SSL_ERROR_SYSCALL);
 
@@ -103,8 +116,23 @@ impl Error {
                 | ErrorCode::PENDING_CERTIFICATE
                 | ErrorCode::WANT_PRIVATE_KEY_OPERATION
                 | ErrorCode::PENDING_TICKET
+                | ErrorCode::WANT_CERTIFICATE_VERIFY
         )
     }
+
+    /// Returns `true` if retrying the operation may succeed.
+    ///
+    /// This is the case if the operation [would block](Error::would_block) or was interrupted
+    /// by a signal, but not for protocol or certificate errors, which retrying won't fix.
+    pub fn is_retryable(&self) -> bool {
+        self.would_block()
+            || self.io_error().is_some_and(|err| {
+                matches!(
+                    err.kind(),
+                    io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock
+                )
+            })
+    }
 }
 
 impl From<ErrorStack> for Error {
@@ -165,6 +193,29 @@ pub enum HandshakeError<S> {
     WouldBlock(MidHandshakeSslStream<S>),
 }
 
+impl<S> HandshakeError<S> {
+    /// Returns the reason given by the private key method which failed the handshake, if any.
+    ///
+    /// See [`PrivateKeyMethodError::failure`].
+    ///
+    /// [`PrivateKeyMethodError::failure`]: crate::ssl::PrivateKeyMethodError::failure
+    pub fn private_key_failure_reason(&self) -> Option<&str> {
+        let stack = match self {
+            HandshakeError::SetupFailure(e) => e,
+            HandshakeError::Failure(s) | HandshakeError::WouldBlock(s) => s.error().ssl_error()?,
+        };
+
+        stack
+            .errors()
+            .iter()
+            .filter(|e| {
+                e.library_code() == ffi::ERR_LIB_SSL.0 as c_int
+                    && e.reason_code() == ffi::SSL_R_PRIVATE_KEY_OPERATION_FAILED as c_int
+            })
+            .find_map(|e| e.data())
+    }
+}
+
 impl<S: fmt::Debug> StdError for HandshakeError<S> {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
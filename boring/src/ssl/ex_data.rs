@@ -0,0 +1,62 @@
+use libc::c_int;
+use once_cell::sync::Lazy;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::ErrorStack;
+use crate::ex_data::Index;
+use crate::ssl::Ssl;
+
+// Kept apart from the indices cached for the crate's own data, so that applications storing a
+// type also used internally do not clobber it.
+static TYPED_INDEXES: Lazy<Mutex<HashMap<TypeId, c_int>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A registry of extra data indices, allocating one [`Index`] per Rust type.
+///
+/// Per-connection state is usually attached to an [`Ssl`] with an index created once by
+/// [`Ssl::new_ex_index`] and stored in a global. This registry does that bookkeeping, so that each
+/// type of state has its own slot, as used by [`SslRef::set`], [`SslRef::get`],
+/// [`SslRef::get_mut`] and [`SslRef::take`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{Ssl, SslContext, SslMethod};
+///
+/// struct RequestId(u64);
+///
+/// let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+/// let mut ssl = Ssl::new(&ctx).unwrap();
+/// ssl.set(RequestId(42)).unwrap();
+///
+/// assert_eq!(ssl.get::<RequestId>().map(|id| id.0), Some(42));
+/// ```
+///
+/// [`SslRef::set`]: crate::ssl::SslRef::set
+/// [`SslRef::get`]: crate::ssl::SslRef::get
+/// [`SslRef::get_mut`]: crate::ssl::SslRef::get_mut
+/// [`SslRef::take`]: crate::ssl::SslRef::take
+pub struct TypedExData(());
+
+impl TypedExData {
+    /// Returns the index of the slot holding values of type `T`, allocating it on first use.
+    pub fn index<T>() -> Result<Index<Ssl, T>, ErrorStack>
+    where
+        T: 'static + Sync + Send,
+    {
+        let mut indexes = TYPED_INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+
+        let idx = match indexes.get(&TypeId::of::<T>()) {
+            Some(&idx) => idx,
+            None => {
+                let idx = Ssl::new_ex_index::<T>()?.as_raw();
+                indexes.insert(TypeId::of::<T>(), idx);
+                idx
+            }
+        };
+
+        unsafe { Ok(Index::from_raw(idx)) }
+    }
+}
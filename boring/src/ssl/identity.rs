@@ -0,0 +1,249 @@
+use foreign_types::ForeignType;
+use libc::c_long;
+use std::cmp;
+use std::fmt;
+use std::ptr;
+use std::sync::Arc;
+
+use crate::error::ErrorStack;
+use crate::pkcs12::Pkcs12;
+use crate::pkey::{PKey, PKeyRef, Private};
+use crate::ssl::{
+    PrivateKeyMethod, PrivateKeyMethodError, SslContextBuilder, SslRef, SslSignatureAlgorithm,
+};
+use crate::x509::{X509Ref, X509};
+use crate::{cvt_p, ffi};
+
+/// A certificate chain along with its private key, or the method signing with it.
+///
+/// An identity is installed on a connector, for client authentication, or on an acceptor with
+/// [`SslContextBuilder::set_identity`]. Cloning it is cheap, so the same identity can be
+/// installed on several contexts.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{Identity, SslAcceptor, SslConnector, SslMethod};
+///
+/// let identity = Identity::from_pem(
+///     &std::fs::read("chain.pem").unwrap(),
+///     &std::fs::read("key.pem").unwrap(),
+/// )
+/// .unwrap();
+///
+/// let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+/// connector.set_identity(&identity).unwrap();
+///
+/// let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+/// acceptor.set_identity(&identity).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Identity {
+    cert: X509,
+    chain: Vec<X509>,
+    key: IdentityKey,
+}
+
+#[derive(Clone)]
+enum IdentityKey {
+    Key(PKey<Private>),
+    Method(Arc<dyn PrivateKeyMethod>),
+}
+
+impl Identity {
+    /// Creates an identity from a leaf certificate, the chain sent along with it, ordered from
+    /// the leaf's issuer towards the root, and its private key.
+    pub fn new(cert: X509, chain: Vec<X509>, key: PKey<Private>) -> Identity {
+        Identity {
+            cert,
+            chain,
+            key: IdentityKey::Key(key),
+        }
+    }
+
+    /// Like [`Identity::new`], but signs with a [`PrivateKeyMethod`] rather than a private key
+    /// held in memory.
+    pub fn with_private_key_method<M>(cert: X509, chain: Vec<X509>, method: M) -> Identity
+    where
+        M: PrivateKeyMethod,
+    {
+        Identity {
+            cert,
+            chain,
+            key: IdentityKey::Method(Arc::new(method)),
+        }
+    }
+
+    /// Loads an identity from a list of PEM-encoded certificates, starting with the leaf, and a
+    /// PEM-encoded private key.
+    pub fn from_pem(chain: &[u8], key: &[u8]) -> Result<Identity, ErrorStack> {
+        let cert = X509::from_pem(chain)?;
+        let chain = X509::stack_from_pem(chain)?.into_iter().skip(1).collect();
+        let key = PKey::private_key_from_pem(key)?;
+
+        Ok(Identity::new(cert, chain, key))
+    }
+
+    /// Loads an identity from a DER-encoded PKCS#12 archive.
+    pub fn from_pkcs12(der: &[u8], pass: &str) -> Result<Identity, ErrorStack> {
+        let parsed = Pkcs12::from_der(der)?.parse(pass)?;
+        let chain = parsed.chain.into_iter().flatten().collect();
+
+        Ok(Identity::new(parsed.cert, chain, parsed.pkey))
+    }
+
+    /// Loads an identity from an X.509 SVID, as returned by the SPIFFE Workload API.
+    ///
+    /// `svid` holds the concatenated DER-encoded certificates of the SVID, starting with the
+    /// leaf, and `key` its PKCS#8 DER-encoded private key, as in the `x509_svid` and
+    /// `x509_svid_key` fields of an `X509SVID` message.
+    pub fn from_x509_svid(svid: &[u8], key: &[u8]) -> Result<Identity, ErrorStack> {
+        let mut certs = certs_from_der(svid)?.into_iter();
+        let cert = certs.next().expect("BUG: no certificate parsed");
+        let key = PKey::private_key_from_pkcs8(key)?;
+
+        Ok(Identity::new(cert, certs.collect(), key))
+    }
+
+    /// Returns the leaf certificate.
+    pub fn certificate(&self) -> &X509Ref {
+        &self.cert
+    }
+
+    /// Returns the certificates sent along with the leaf certificate.
+    pub fn chain(&self) -> &[X509] {
+        &self.chain
+    }
+
+    /// Returns the private key, or `None` if the identity signs with a [`PrivateKeyMethod`].
+    pub fn private_key(&self) -> Option<&PKeyRef<Private>> {
+        match &self.key {
+            IdentityKey::Key(key) => Some(&**key),
+            IdentityKey::Method(_) => None,
+        }
+    }
+
+    /// Returns the SPIFFE ID of the leaf certificate, if it is an X.509 SVID.
+    ///
+    /// See [`X509Ref::spiffe_id`].
+    pub fn spiffe_id(&self) -> Option<String> {
+        self.cert.spiffe_id()
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Identity")
+            .field("cert", &self.cert)
+            .field("chain", &self.chain)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SslContextBuilder {
+    /// Sets the certificate chain and the private key, or private key method, of the context.
+    ///
+    /// This replaces the certificate and key set with [`SslContextBuilder::set_certificate`] and
+    /// [`SslContextBuilder::set_private_key`]. Chain certificates are added to those set with
+    /// [`SslContextBuilder::add_extra_chain_cert`], so this should be called on a context
+    /// without any.
+    pub fn set_identity(&mut self, identity: &Identity) -> Result<(), ErrorStack> {
+        self.set_certificate(&identity.cert)?;
+
+        for cert in &identity.chain {
+            self.add_extra_chain_cert(cert.clone())?;
+        }
+
+        match &identity.key {
+            IdentityKey::Key(key) => {
+                self.set_private_key(key)?;
+                self.check_private_key()
+            }
+            IdentityKey::Method(method) => {
+                self.set_private_key_method(SharedPrivateKeyMethod(method.clone()));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl SslRef {
+    /// Like [`SslContextBuilder::set_identity`], but for a single connection.
+    ///
+    /// This can be called from a certificate callback set with
+    /// [`SslContextBuilder::set_cert_callback`], such as to pick a client certificate once the
+    /// server requested one.
+    pub fn set_identity(&mut self, identity: &Identity) -> Result<(), ErrorStack> {
+        self.set_certificate(&identity.cert)?;
+
+        for cert in &identity.chain {
+            self.add_chain_cert(cert)?;
+        }
+
+        match &identity.key {
+            IdentityKey::Key(key) => {
+                self.set_private_key(key)?;
+                self.check_private_key()
+            }
+            IdentityKey::Method(method) => {
+                self.set_private_key_method(SharedPrivateKeyMethod(method.clone()));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A private key method shared by the contexts and connections an identity was installed on.
+struct SharedPrivateKeyMethod(Arc<dyn PrivateKeyMethod>);
+
+impl PrivateKeyMethod for SharedPrivateKeyMethod {
+    fn sign(
+        &self,
+        ssl: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError> {
+        self.0.sign(ssl, input, signature_algorithm, output)
+    }
+
+    fn decrypt(
+        &self,
+        ssl: &mut SslRef,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError> {
+        self.0.decrypt(ssl, input, output)
+    }
+
+    fn complete(
+        &self,
+        ssl: &mut SslRef,
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError> {
+        self.0.complete(ssl, output)
+    }
+}
+
+/// Parses one or more concatenated DER-encoded certificates.
+fn certs_from_der(mut der: &[u8]) -> Result<Vec<X509>, ErrorStack> {
+    ffi::init();
+
+    let mut certs = vec![];
+    loop {
+        unsafe {
+            let mut p = der.as_ptr();
+            let len = cmp::min(der.len(), c_long::max_value() as usize) as c_long;
+            let cert = X509::from_ptr(cvt_p(ffi::d2i_X509(ptr::null_mut(), &mut p, len))?);
+
+            der = &der[p.offset_from(der.as_ptr()) as usize..];
+            certs.push(cert);
+        }
+
+        if der.is_empty() {
+            break;
+        }
+    }
+
+    Ok(certs)
+}
@@ -0,0 +1,257 @@
+use crate::ffi;
+use foreign_types::ForeignType;
+use libc::c_int;
+use std::cmp;
+use std::ptr;
+
+use crate::cvt;
+use crate::error::ErrorStack;
+use crate::ssl::error::InnerError;
+use crate::ssl::{Error, ErrorCode, ShutdownResult, Ssl, SslRef};
+
+/// A TLS session reading and writing its ciphertext through in-memory buffers.
+///
+/// The session is attached to one end of a BIO pair, and the other end, a [`MemBio`], is left to
+/// the caller, who moves the ciphertext between it and the peer however they see fit. This
+/// decouples the session from any I/O, as needed by sans-io protocol stacks and fuzzers.
+///
+/// Operations on the session return [`ErrorCode::WANT_READ`] when it needs more ciphertext from
+/// the peer, which is then passed to [`MemBio::write`]. Ciphertext for the peer is taken out
+/// with [`MemBio::read`] after each operation.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{HandshakeState, MemSsl, Ssl, SslContext, SslMethod};
+///
+/// let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+/// let mut ssl = Ssl::new(&ctx).unwrap();
+/// ssl.set_connect_state();
+///
+/// let mut client = MemSsl::new(ssl, 16 * 1024).unwrap();
+/// let state = client.ssl_mut().do_handshake_step().unwrap();
+/// assert_eq!(state, HandshakeState::WantRead);
+///
+/// let mut client_hello = vec![0; client.bio().pending()];
+/// client.bio_mut().read(&mut client_hello);
+/// // Send `client_hello` to the server, and write its response with `client.bio_mut().write`.
+/// ```
+#[derive(Debug)]
+pub struct MemSsl {
+    ssl: Ssl,
+    bio: MemBio,
+}
+
+// SAFETY: the two ends of the BIO pair share unsynchronized state, but both are only reachable
+// through `&mut MemSsl`.
+unsafe impl Send for MemSsl {}
+
+impl MemSsl {
+    /// Attaches `ssl` to a new BIO pair, buffering up to `buffer_size` bytes of ciphertext in
+    /// each direction.
+    ///
+    /// This corresponds to [`BIO_new_bio_pair`] and [`SSL_set_bio`].
+    ///
+    /// [`BIO_new_bio_pair`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bio.h.html#BIO_new_bio_pair
+    /// [`SSL_set_bio`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_bio
+    pub fn new(ssl: Ssl, buffer_size: usize) -> Result<MemSsl, ErrorStack> {
+        unsafe {
+            let mut internal = ptr::null_mut();
+            let mut external = ptr::null_mut();
+            cvt(ffi::BIO_new_bio_pair(
+                &mut internal,
+                buffer_size,
+                &mut external,
+                buffer_size,
+            ))?;
+
+            ffi::SSL_set_bio(ssl.as_ptr(), internal, internal);
+
+            Ok(MemSsl {
+                ssl,
+                bio: MemBio(external),
+            })
+        }
+    }
+
+    /// Returns a shared reference to the `Ssl` object.
+    pub fn ssl(&self) -> &SslRef {
+        &self.ssl
+    }
+
+    /// Returns a mutable reference to the `Ssl` object.
+    ///
+    /// The handshake can be driven with [`SslRef::do_handshake_step`].
+    pub fn ssl_mut(&mut self) -> &mut SslRef {
+        &mut self.ssl
+    }
+
+    /// Returns a shared reference to the caller's end of the BIO pair.
+    pub fn bio(&self) -> &MemBio {
+        &self.bio
+    }
+
+    /// Returns a mutable reference to the caller's end of the BIO pair.
+    pub fn bio_mut(&mut self) -> &mut MemBio {
+        &mut self.bio
+    }
+
+    /// Reads decrypted application data.
+    ///
+    /// This corresponds to [`SSL_read`].
+    ///
+    /// [`SSL_read`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_read
+    pub fn ssl_read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        // See `SslStream::ssl_read` for why we short-circuit on zero-length buffers.
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let ret = self.ssl.read(buf);
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Encrypts application data.
+    ///
+    /// This corresponds to [`SSL_write`].
+    ///
+    /// [`SSL_write`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_write
+    pub fn ssl_write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let ret = self.ssl.write(buf);
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Shuts down the session, sending a `close_notify` alert.
+    ///
+    /// This corresponds to [`SSL_shutdown`].
+    ///
+    /// [`SSL_shutdown`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_shutdown
+    pub fn shutdown(&mut self) -> Result<ShutdownResult, Error> {
+        match unsafe { ffi::SSL_shutdown(self.ssl.as_ptr()) } {
+            0 => Ok(ShutdownResult::Sent),
+            1 => Ok(ShutdownResult::Received),
+            n => Err(self.make_error(n)),
+        }
+    }
+
+    fn make_error(&mut self, ret: c_int) -> Error {
+        let code = self.ssl.get_error(ret);
+
+        let cause = match code {
+            ErrorCode::SSL | ErrorCode::SYSCALL => {
+                let errors = ErrorStack::get();
+                if errors.errors().is_empty() {
+                    None
+                } else {
+                    Some(InnerError::Ssl(errors))
+                }
+            }
+            _ => None,
+        };
+
+        Error { code, cause }
+    }
+}
+
+/// The caller's end of the BIO pair of a [`MemSsl`].
+///
+/// Writing to it feeds ciphertext received from the peer to the session, and reading from it
+/// takes out the ciphertext the session produced for the peer.
+#[derive(Debug)]
+pub struct MemBio(*mut ffi::BIO);
+
+impl Drop for MemBio {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::BIO_free(self.0);
+        }
+    }
+}
+
+impl MemBio {
+    /// Writes ciphertext received from the peer, returning the number of bytes accepted.
+    ///
+    /// Fewer bytes than given are accepted if the buffer is full, in which case the session must
+    /// consume some of them first.
+    ///
+    /// This corresponds to [`BIO_write`].
+    ///
+    /// [`BIO_write`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bio.h.html#BIO_write
+    pub fn write(&mut self, data: &[u8]) -> usize {
+        if data.is_empty() {
+            return 0;
+        }
+
+        let len = cmp::min(c_int::max_value() as usize, data.len()) as c_int;
+        let ret = unsafe { ffi::BIO_write(self.0, data.as_ptr().cast(), len) };
+
+        if ret > 0 {
+            ret as usize
+        } else {
+            0
+        }
+    }
+
+    /// Reads ciphertext to be sent to the peer, returning the number of bytes read.
+    ///
+    /// This corresponds to [`BIO_read`].
+    ///
+    /// [`BIO_read`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bio.h.html#BIO_read
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+
+        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
+        let ret = unsafe { ffi::BIO_read(self.0, buf.as_mut_ptr().cast(), len) };
+
+        if ret > 0 {
+            ret as usize
+        } else {
+            0
+        }
+    }
+
+    /// Returns the number of bytes of ciphertext waiting to be read.
+    ///
+    /// This corresponds to [`BIO_ctrl_pending`].
+    ///
+    /// [`BIO_ctrl_pending`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bio.h.html#BIO_ctrl_pending
+    pub fn pending(&self) -> usize {
+        unsafe { ffi::BIO_ctrl_pending(self.0) }
+    }
+
+    /// Returns the number of bytes of ciphertext which can currently be written.
+    ///
+    /// This corresponds to [`BIO_ctrl_get_write_guarantee`].
+    ///
+    /// [`BIO_ctrl_get_write_guarantee`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bio.h.html#BIO_ctrl_get_write_guarantee
+    pub fn write_guarantee(&self) -> usize {
+        unsafe { ffi::BIO_ctrl_get_write_guarantee(self.0) }
+    }
+
+    /// Signals that the peer will not send more ciphertext.
+    ///
+    /// Once the written ciphertext is consumed, the session sees the end of the stream.
+    ///
+    /// This corresponds to [`BIO_shutdown_wr`].
+    ///
+    /// [`BIO_shutdown_wr`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/bio.h.html#BIO_shutdown_wr
+    pub fn shutdown(&mut self) {
+        unsafe {
+            ffi::BIO_shutdown_wr(self.0);
+        }
+    }
+}
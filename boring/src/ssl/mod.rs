@@ -62,6 +62,7 @@ use foreign_types::{ForeignType, ForeignTypeRef, Opaque};
 use libc::{c_char, c_int, c_long, c_uchar, c_uint, c_void};
 use once_cell::sync::Lazy;
 use std::any::TypeId;
+use std::borrow::Cow;
 use std::cmp;
 use std::collections::HashMap;
 use std::convert::TryInto;
@@ -78,34 +79,73 @@ use std::ptr::{self, NonNull};
 use std::slice;
 use std::str;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::dh::DhRef;
 use crate::ec::EcKeyRef;
 use crate::error::ErrorStack;
 use crate::ex_data::Index;
+use crate::hash::MessageDigest;
 use crate::nid::Nid;
+use crate::pem::{self, PemError, PemItem};
 use crate::pkey::{HasPrivate, PKeyRef, Params, Private};
+use crate::sct::SignedCertificateTimestamp;
 use crate::srtp::{SrtpProtectionProfile, SrtpProtectionProfileRef};
 use crate::ssl::bio::BioMethod;
 use crate::ssl::callbacks::*;
 use crate::ssl::error::InnerError;
-use crate::stack::{Stack, StackRef};
-use crate::x509::store::{X509Store, X509StoreBuilderRef, X509StoreRef};
+use crate::ssl::stats::StatsCounters;
+use crate::stack::{Stack, StackRef, Stackable};
+use crate::x509::store::{X509Store, X509StoreBuilder, X509StoreBuilderRef, X509StoreRef};
 use crate::x509::verify::X509VerifyParamRef;
-use crate::x509::{X509Name, X509Ref, X509StoreContextRef, X509VerifyResult, X509};
+use crate::x509::{
+    X509Name, X509Ref, X509StoreContext, X509StoreContextRef, X509VerifyResult, X509,
+};
 use crate::{cvt, cvt_0i, cvt_n, cvt_p, init};
 
+pub use crate::ssl::acme::{acme_tls_alpn_certificate, AcmeChallenges, ACME_TLS_ALPN_PROTOCOL};
+pub use crate::ssl::budget::HandshakeBudget;
 pub use crate::ssl::connector::{
-    ConnectConfiguration, SslAcceptor, SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
+    ConnectConfiguration, OcspStaplingPolicy, OcspValidator, SecurityProfileBuilder, SslAcceptor,
+    SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
 };
+pub use crate::ssl::engine::SslEngine;
 pub use crate::ssl::error::{Error, ErrorCode, HandshakeError};
-
+pub use crate::ssl::ex_data::TypedExData;
+pub use crate::ssl::identity::Identity;
+pub use crate::ssl::mem::{MemBio, MemSsl};
+pub use crate::ssl::outcome::{
+    CertificateRequest, ClientAuthentication, HandshakeOutcome, ResumptionMechanism,
+};
+pub use crate::ssl::pinning::{PinError, PinSet};
+#[cfg(feature = "pkcs11")]
+pub use crate::ssl::pkcs11::{Pkcs11Config, Pkcs11Error, Pkcs11PrivateKey};
+pub use crate::ssl::reload::{FileWatcher, ReloadableAcceptor};
+pub use crate::ssl::sni::{SniRouter, SniRoutes};
+pub use crate::ssl::stats::{HandshakeFailure, SslStats};
+pub use crate::ssl::ticket::{TicketKey, TicketKeyManager};
+
+mod acme;
 mod bio;
+mod budget;
 mod callbacks;
+mod capture;
 mod connector;
+mod engine;
 mod error;
+mod ex_data;
+mod identity;
+mod mem;
+mod outcome;
+mod pinning;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+mod reload;
+mod sni;
+mod stats;
 #[cfg(test)]
 mod test;
+mod ticket;
 
 bitflags! {
     /// Options controlling the behavior of an `SslContext`.
@@ -226,6 +266,14 @@ bitflags! {
         ///
         /// Do not use this unless you know what you're doing!
         const SEND_FALLBACK_SCSV = ffi::SSL_MODE_SEND_FALLBACK_SCSV as _;
+
+        /// Allows a client to send application data before receiving the server's `Finished`
+        /// message in a TLS 1.2 handshake.
+        ///
+        /// False Start is only used when the connection negotiates ALPN and a forward-secure AEAD
+        /// cipher. While it is in progress, `SslRef::in_false_start` returns `true` and the
+        /// handshake completes during a subsequent read.
+        const ENABLE_FALSE_START = ffi::SSL_MODE_ENABLE_FALSE_START as _;
     }
 }
 
@@ -425,6 +473,15 @@ static SESSION_CTX_INDEX: Lazy<Index<Ssl, SslContext>> = Lazy::new(|| Ssl::new_e
 static RPK_FLAG_INDEX: Lazy<Index<SslContext, bool>> =
     Lazy::new(|| SslContext::new_ex_index().unwrap());
 
+type HandshakeDoneFn = dyn Fn(&SslRef) + Sync + Send;
+type HandshakeOutcomeFn = dyn Fn(&SslRef, &HandshakeOutcome) + Sync + Send;
+
+struct HandshakeDoneCallback(Box<HandshakeDoneFn>);
+struct HandshakeOutcomeCallback(Box<HandshakeOutcomeFn>);
+
+/// The time at which the current handshake of an `Ssl` started.
+struct HandshakeStart(Instant);
+
 unsafe extern "C" fn free_data_box<T>(
     _parent: *mut c_void,
     ptr: *mut c_void,
@@ -459,6 +516,8 @@ pub struct SslAlert(c_int);
 impl SslAlert {
     /// Alert 112 - `unrecognized_name`.
     pub const UNRECOGNIZED_NAME: SslAlert = SslAlert(ffi::SSL_AD_UNRECOGNIZED_NAME);
+    /// Alert 42 - `bad_certificate`.
+    pub const BAD_CERTIFICATE: SslAlert = SslAlert(ffi::SSL_AD_BAD_CERTIFICATE);
     pub const ILLEGAL_PARAMETER: SslAlert = SslAlert(ffi::SSL_AD_ILLEGAL_PARAMETER);
     pub const DECODE_ERROR: SslAlert = SslAlert(ffi::SSL_AD_DECODE_ERROR);
 }
@@ -469,8 +528,18 @@ pub struct AlpnError(c_int);
 
 impl AlpnError {
     /// Terminate the handshake with a fatal alert.
+    ///
+    /// BoringSSL sends a `no_application_protocol` alert.
     pub const ALERT_FATAL: AlpnError = AlpnError(ffi::SSL_TLSEXT_ERR_ALERT_FATAL);
 
+    /// Terminate the handshake with a `no_application_protocol` alert, as required by [RFC 7301]
+    /// when none of the protocols offered by the client is supported.
+    ///
+    /// This is equivalent to [`AlpnError::ALERT_FATAL`].
+    ///
+    /// [RFC 7301]: https://datatracker.ietf.org/doc/html/rfc7301#section-3.2
+    pub const NO_APPLICATION_PROTOCOL: AlpnError = AlpnError(ffi::SSL_TLSEXT_ERR_ALERT_FATAL);
+
     /// Do not select a protocol, but continue the handshake.
     pub const NOACK: AlpnError = AlpnError(ffi::SSL_TLSEXT_ERR_NOACK);
 }
@@ -487,6 +556,32 @@ impl SelectCertError {
     pub const RETRY: Self = Self(ffi::ssl_select_cert_result_t::ssl_select_cert_retry);
 }
 
+/// An error returned from a certificate callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CertCallbackError(c_int);
+
+impl CertCallbackError {
+    /// A fatal error occured and the handshake should be terminated.
+    pub const ERROR: Self = Self(0);
+
+    /// The operation could not be completed and should be retried later.
+    ///
+    /// The handshake fails with [`ErrorCode::WANT_X509_LOOKUP`] in the meantime.
+    pub const RETRY: Self = Self(-1);
+}
+
+/// An error returned from a custom certificate verification callback.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SslVerifyError {
+    /// The certificate is rejected, and the handshake is aborted with the alert.
+    Invalid(SslAlert),
+    /// The verification could not be completed yet, and the callback is called again once the
+    /// handshake is resumed.
+    ///
+    /// The handshake fails with [`ErrorCode::WANT_CERTIFICATE_VERIFY`] in the meantime.
+    Retry,
+}
+
 /// Extension types, to be used with `ClientHello::get_extension`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct ExtensionType(u16);
@@ -532,6 +627,43 @@ impl From<u16> for ExtensionType {
     }
 }
 
+/// How a client responds to a server's request to renegotiate.
+///
+/// Renegotiation only exists in TLS 1.2 and earlier. BoringSSL servers never renegotiate and
+/// reject renegotiation attempts from clients with a `no_renegotiation` alert.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslRenegotiateMode(ffi::ssl_renegotiate_mode_t);
+
+impl SslRenegotiateMode {
+    /// Renegotiation requests are rejected, failing the connection. This is the default.
+    pub const NEVER: Self = Self(ffi::ssl_renegotiate_mode_t::ssl_renegotiate_never);
+
+    /// A single renegotiation is accepted.
+    pub const ONCE: Self = Self(ffi::ssl_renegotiate_mode_t::ssl_renegotiate_once);
+
+    /// Any number of renegotiations are accepted.
+    pub const FREELY: Self = Self(ffi::ssl_renegotiate_mode_t::ssl_renegotiate_freely);
+
+    /// Renegotiation requests are silently ignored.
+    pub const IGNORE: Self = Self(ffi::ssl_renegotiate_mode_t::ssl_renegotiate_ignore);
+
+    /// Renegotiation requests make reads fail with `ErrorCode::WANT_RENEGOTIATE`, and the
+    /// renegotiation is performed by calling `SslRef::renegotiate`.
+    pub const EXPLICIT: Self = Self(ffi::ssl_renegotiate_mode_t::ssl_renegotiate_explicit);
+}
+
+/// Whether a TLS 1.3 `KeyUpdate` message asks the peer to update its own keys in return.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SslKeyUpdate(c_int);
+
+impl SslKeyUpdate {
+    /// Only the sending keys are updated.
+    pub const NOT_REQUESTED: Self = Self(ffi::SSL_KEY_UPDATE_NOT_REQUESTED as c_int);
+
+    /// The peer is asked to update its sending keys too.
+    pub const REQUESTED: Self = Self(ffi::SSL_KEY_UPDATE_REQUESTED as c_int);
+}
+
 /// An SSL/TLS protocol version.
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct SslVersion(u16);
@@ -697,6 +829,25 @@ extern "C" fn rpk_verify_failure_callback(
     ffi::ssl_verify_result_t::ssl_verify_invalid
 }
 
+fn server_end_point(cert: &X509Ref) -> Result<Vec<u8>, ErrorStack> {
+    let digest = cert
+        .signature_algorithm()
+        .object()
+        .nid()
+        .signature_algorithms()
+        .map(|algs| algs.digest)
+        .filter(|&nid| nid != Nid::MD5 && nid != Nid::SHA1)
+        .and_then(MessageDigest::from_nid)
+        .unwrap_or_else(MessageDigest::sha256);
+
+    Ok(cert.digest(digest)?.to_vec())
+}
+
+/// The current time, in seconds since the UNIX epoch, seen by BoringSSL in contexts configured
+/// with [`SslContextBuilder::set_deterministic_for_testing`].
+#[cfg(feature = "unsafe-deterministic-rng")]
+pub const DETERMINISTIC_TIME: u64 = 1_600_000_000;
+
 /// A builder for `SslContext`s.
 pub struct SslContextBuilder {
     ctx: SslContext,
@@ -771,6 +922,92 @@ impl SslContextBuilder {
         }
     }
 
+    /// Creates a new `SslContextBuilder` configured like an existing context.
+    ///
+    /// This lets a server derive a context differing in a few settings from one already in use,
+    /// e.g. to be swapped in with [`ReloadableAcceptor::replace`], as a context cannot be changed
+    /// once it is shared with connections.
+    ///
+    /// The options, mode, protocol versions, cipher list, verification mode and depth, certificate,
+    /// private key, extra chain certificates and session cache mode are copied. The trusted
+    /// certificates and verification parameters of the certificate store are copied into a new
+    /// store, so that changes to either store do not affect the other. CRLs, callbacks, extra
+    /// data, ALPN protocols and other settings BoringSSL does not expose are not copied, and must
+    /// be configured again.
+    ///
+    /// The cipher list is copied as a flat list, so equal-preference groups are lost.
+    pub fn from_context(method: SslMethod, ctx: &SslContextRef) -> Result<Self, ErrorStack> {
+        #[cfg(feature = "rpk")]
+        assert!(!ctx.is_rpk(), "This API is not supported for RPK");
+
+        let mut builder = SslContextBuilder::new(method)?;
+
+        unsafe {
+            let options = SslOptions::from_bits_retain(ffi::SSL_CTX_get_options(ctx.as_ptr()));
+            builder.clear_options(builder.options());
+            builder.set_options(options);
+
+            builder.set_mode(SslMode::from_bits_retain(ffi::SSL_CTX_get_mode(
+                ctx.as_ptr(),
+            )));
+
+            cvt(ffi::SSL_CTX_set_min_proto_version(
+                builder.as_ptr(),
+                ffi::SSL_CTX_get_min_proto_version(ctx.as_ptr()),
+            ))?;
+            cvt(ffi::SSL_CTX_set_max_proto_version(
+                builder.as_ptr(),
+                ffi::SSL_CTX_get_max_proto_version(ctx.as_ptr()),
+            ))?;
+
+            builder.set_session_cache_mode(SslSessionCacheMode::from_bits_retain(
+                ffi::SSL_CTX_get_session_cache_mode(ctx.as_ptr()),
+            ));
+
+            let depth = ffi::SSL_CTX_get_verify_depth(ctx.as_ptr());
+            ffi::SSL_CTX_set_verify_depth(builder.as_ptr(), depth);
+        }
+
+        let mut store = X509StoreBuilder::new()?;
+        for object in ctx.cert_store().objects() {
+            if let Some(cert) = object.x509() {
+                store.add_cert(cert.to_owned())?;
+            }
+        }
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1(
+                store.param_mut().as_ptr(),
+                ffi::X509_STORE_get0_param(ctx.cert_store().as_ptr()),
+            ))?;
+        }
+        builder.set_cert_store(store.build());
+
+        if let Some(ciphers) = ctx.ciphers() {
+            let list = ciphers
+                .iter()
+                .map(|cipher| cipher.name())
+                .collect::<Vec<_>>()
+                .join(":");
+            if !list.is_empty() {
+                builder.set_cipher_list(&list)?;
+            }
+        }
+
+        builder.set_verify(ctx.verify_mode());
+
+        if let Some(cert) = ctx.certificate() {
+            builder.set_certificate(cert)?;
+        }
+        if let Some(key) = ctx.private_key() {
+            builder.set_private_key(key)?;
+        }
+        for cert in ctx.extra_chain_certs() {
+            builder.add_extra_chain_cert(cert.to_owned())?;
+        }
+
+        Ok(builder)
+    }
+
     /// Creates an `SslContextBuilder` from a pointer to a raw OpenSSL value.
     ///
     /// # Safety
@@ -840,6 +1077,33 @@ impl SslContextBuilder {
         }
     }
 
+    /// Configures certificate verification for new connections with a callback replacing
+    /// BoringSSL's built-in chain verification.
+    ///
+    /// The callback is passed the connection, whose peer certificate chain can be examined with
+    /// [`SslRef::peer_cert_chain`], and decides on its own whether the peer is trusted, e.g. by
+    /// comparing its certificate to a pinned one. The certificate store is not consulted.
+    ///
+    /// This corresponds to [`SSL_CTX_set_custom_verify`].
+    ///
+    /// [`SSL_CTX_set_custom_verify`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_custom_verify
+    pub fn set_custom_verify_callback<F>(&mut self, mode: SslVerifyMode, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<(), SslVerifyError> + 'static + Sync + Send,
+    {
+        #[cfg(feature = "rpk")]
+        assert!(!self.is_rpk, "This API is not supported for RPK");
+
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_custom_verify(
+                self.as_ptr(),
+                mode.bits() as c_int,
+                Some(raw_custom_verify::<F>),
+            );
+        }
+    }
+
     /// Configures the server name indication (SNI) callback for new connections.
     ///
     /// SNI is used to allow a single server to handle requests for multiple domains, each of which
@@ -869,6 +1133,31 @@ impl SslContextBuilder {
         }
     }
 
+    /// Routes connections to an `SslContext` based on the requested server name.
+    ///
+    /// The context selected by `router` replaces this one for the connection. If no route
+    /// matches and the router has no default context, the handshake is aborted with an
+    /// `unrecognized_name` alert.
+    ///
+    /// This replaces any callback set with [`set_servername_callback`].
+    ///
+    /// [`set_servername_callback`]: #method.set_servername_callback
+    pub fn set_sni_router(&mut self, router: SniRouter) {
+        self.set_servername_callback(move |ssl, alert| {
+            match router.resolve(ssl.servername(NameType::HOST_NAME)) {
+                Some(ctx) => {
+                    ssl.set_ssl_context(&ctx)
+                        .map_err(|_| SniError::ALERT_FATAL)?;
+                    Ok(())
+                }
+                None => {
+                    *alert = SslAlert::UNRECOGNIZED_NAME;
+                    Err(SniError::ALERT_FATAL)
+                }
+            }
+        });
+    }
+
     /// Sets the certificate verification depth.
     ///
     /// If the peer's certificate chain is longer than this value, verification will fail.
@@ -885,6 +1174,32 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the maximum size of the records sent. Values outside of 512 to 16384 bytes are
+    /// clamped to that range.
+    ///
+    /// This corresponds to [`SSL_CTX_set_max_send_fragment`].
+    ///
+    /// [`SSL_CTX_set_max_send_fragment`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_max_send_fragment
+    pub fn set_max_send_fragment(&mut self, max_send_fragment: usize) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_max_send_fragment(
+                self.as_ptr(),
+                max_send_fragment,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the maximum size of a handshake message the peer may send, such as a ClientHello or
+    /// a certificate chain, which bounds the memory a handshake may use. Defaults to 100 KiB.
+    ///
+    /// This corresponds to [`SSL_CTX_set_max_cert_list`].
+    ///
+    /// [`SSL_CTX_set_max_cert_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_max_cert_list
+    pub fn set_max_cert_list(&mut self, max_cert_list: usize) {
+        unsafe { ffi::SSL_CTX_set_max_cert_list(self.as_ptr(), max_cert_list) }
+    }
+
     /// Sets a custom certificate store for verifying peer certificates.
     ///
     /// This corresponds to [`SSL_CTX_set0_verify_cert_store`].
@@ -1168,11 +1483,72 @@ impl SslContextBuilder {
         unsafe { cvt(ffi::SSL_CTX_use_PrivateKey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
     }
 
+    /// Configures the certificate chain and private key from a combined PEM bundle, as accepted
+    /// by nginx and HAProxy.
+    ///
+    /// The first certificate of the bundle is used as the leaf certificate, and the remaining
+    /// ones are appended to the certificate chain. The first private key is used, and must match
+    /// the leaf certificate. Other blocks are ignored.
+    ///
+    /// Encrypted private keys are decrypted with `passphrase` if provided.
+    ///
+    /// See [`pem::parse_bundle`] for details on parsing.
+    pub fn set_pem_bundle(
+        &mut self,
+        pem: &[u8],
+        passphrase: Option<&[u8]>,
+    ) -> Result<(), PemError> {
+        let items = match passphrase {
+            Some(passphrase) => pem::parse_bundle_with_passphrase(pem, passphrase)?,
+            None => pem::parse_bundle(pem)?,
+        };
+
+        let mut certs = vec![];
+        let mut key = None;
+        for item in items {
+            match item {
+                PemItem::Certificate(cert) => certs.push(cert),
+                PemItem::PrivateKey(k) if key.is_none() => key = Some(k),
+                _ => {}
+            }
+        }
+
+        let mut certs = certs.into_iter();
+        let leaf = certs.next().ok_or(PemError::MissingCertificate)?;
+        let key = key.ok_or(PemError::MissingPrivateKey)?;
+
+        self.set_certificate(&leaf)?;
+        for cert in certs {
+            self.add_extra_chain_cert(cert)?;
+        }
+        self.set_private_key(&key)?;
+        self.check_private_key()?;
+
+        Ok(())
+    }
+
+    /// Configures the certificate chain and private key from a combined PEM bundle file.
+    ///
+    /// See [`set_pem_bundle`](Self::set_pem_bundle).
+    pub fn set_pem_bundle_file<P: AsRef<Path>>(
+        &mut self,
+        file: P,
+        passphrase: Option<&[u8]>,
+    ) -> Result<(), PemError> {
+        let pem = std::fs::read(file)?;
+        self.set_pem_bundle(&pem, passphrase)
+    }
+
     /// Sets the list of supported ciphers for protocols before TLSv1.3.
     ///
-    /// The `set_ciphersuites` method controls the cipher suites for TLSv1.3.
+    /// The cipher suites for TLSv1.3 are not configurable in BoringSSL: `TLS_AES_128_GCM_SHA256`,
+    /// `TLS_AES_256_GCM_SHA384` and `TLS_CHACHA20_POLY1305_SHA256` are always enabled, and
+    /// ChaCha20-Poly1305 is preferred when either peer lacks hardware AES support.
     ///
-    /// See [`ciphers`] for details on the format.
+    /// See [`ciphers`] for details on the format. Ciphers enclosed in brackets and separated by
+    /// `|`, e.g. `[ECDHE-ECDSA-AES128-GCM-SHA256|ECDHE-ECDSA-CHACHA20-POLY1305]`, are of equal
+    /// preference: when [`SslOptions::CIPHER_SERVER_PREFERENCE`] is set, the server picks among
+    /// them according to the client's preference.
     ///
     /// This corresponds to [`SSL_CTX_set_cipher_list`].
     ///
@@ -1189,6 +1565,23 @@ impl SslContextBuilder {
         }
     }
 
+    /// Like [`set_cipher_list`], but fails if the list contains unknown ciphers.
+    ///
+    /// This corresponds to [`SSL_CTX_set_strict_cipher_list`].
+    ///
+    /// [`set_cipher_list`]: #method.set_cipher_list
+    /// [`SSL_CTX_set_strict_cipher_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_strict_cipher_list
+    pub fn set_strict_cipher_list(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
+        let cipher_list = CString::new(cipher_list).unwrap();
+        unsafe {
+            cvt(ffi::SSL_CTX_set_strict_cipher_list(
+                self.as_ptr(),
+                cipher_list.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Sets the options used by the context, returning the old set.
     ///
     /// This corresponds to [`SSL_CTX_set_options`].
@@ -1397,6 +1790,54 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets a callback deciding whether a handshake may continue, to protect servers against
+    /// denial of service.
+    ///
+    /// It is called with the ClientHello once the resumption decision has been made, but before
+    /// any expensive public key operation, and the handshake is aborted if it returns `false`.
+    /// [`HandshakeBudget::admit`] can be used to limit the handshakes of each client.
+    ///
+    /// This corresponds to [`SSL_CTX_set_dos_protection_cb`].
+    ///
+    /// [`SSL_CTX_set_dos_protection_cb`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_dos_protection_cb
+    pub fn set_dos_protection_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(ClientHello<'_>) -> bool + Sync + Send + 'static,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_dos_protection_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_dos_protection::<F>),
+            );
+        }
+    }
+
+    /// Sets a callback that is called when the certificate of a connection is needed, so that it
+    /// can be configured on the connection.
+    ///
+    /// On the client side, it is called once the server requested a certificate. The callback can
+    /// choose one with [`SslRef::client_ca_list`] and [`SslRef::peer_verify_algorithms`], and
+    /// install it with [`SslRef::set_identity`], or install none to continue without a
+    /// certificate. On the server side, it is called once the ClientHello was processed.
+    ///
+    /// This corresponds to [`SSL_CTX_set_cert_cb`].
+    ///
+    /// [`SSL_CTX_set_cert_cb`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_cert_cb
+    pub fn set_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<(), CertCallbackError> + Sync + Send + 'static,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_cert_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_cert::<F>),
+                ptr::null_mut(),
+            );
+        }
+    }
+
     /// Configures a custom private key method on the context.
     ///
     /// See [`PrivateKeyMethod`] for more details.
@@ -1455,6 +1896,20 @@ impl SslContextBuilder {
         unsafe { X509StoreBuilderRef::from_ptr_mut(ffi::SSL_CTX_get_cert_store(self.as_ptr())) }
     }
 
+    /// Returns a mutable reference to the X509 verification configuration.
+    ///
+    /// It is the default of the `Ssl`s created from the context.
+    ///
+    /// This corresponds to [`SSL_CTX_get0_param`].
+    ///
+    /// [`SSL_CTX_get0_param`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_CTX_get0_param.html
+    pub fn verify_param_mut(&mut self) -> &mut X509VerifyParamRef {
+        #[cfg(feature = "rpk")]
+        assert!(!self.is_rpk, "This API is not supported for RPK");
+
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::SSL_CTX_get0_param(self.as_ptr())) }
+    }
+
     /// Sets the callback dealing with OCSP stapling.
     ///
     /// On the client side, this callback is responsible for validating the OCSP status response
@@ -1627,6 +2082,78 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets a callback invoked when a handshake fully completes.
+    ///
+    /// On false-started connections, this happens once the server's `Finished` message is
+    /// processed, after the connection first became available for writing. This lets clients
+    /// measure the completion of the handshake separately from the time to first write.
+    ///
+    /// This corresponds to [`SSL_CTX_set_info_callback`], reacting to `SSL_CB_HANDSHAKE_DONE`.
+    ///
+    /// [`SSL_CTX_set_info_callback`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_info_callback
+    pub fn set_handshake_done_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(
+                SslContext::cached_ex_index::<HandshakeDoneCallback>(),
+                HandshakeDoneCallback(Box::new(callback)),
+            );
+            ffi::SSL_CTX_set_info_callback(self.as_ptr(), Some(callbacks::raw_info));
+        }
+    }
+
+    /// Sets a callback invoked with a summary of each completed handshake.
+    ///
+    /// The [`HandshakeOutcome`] tells whether the session was resumed and how, whether early data
+    /// was accepted, and which key exchange group and signature algorithm were used, so that
+    /// resumption ratios and similar metrics can be exported without parsing logs.
+    ///
+    /// The callback is invoked at the same time as the one set with
+    /// [`SslContextBuilder::set_handshake_done_callback`], and both can be used together.
+    ///
+    /// This corresponds to [`SSL_CTX_set_info_callback`], reacting to `SSL_CB_HANDSHAKE_START`
+    /// and `SSL_CB_HANDSHAKE_DONE`.
+    ///
+    /// [`SSL_CTX_set_info_callback`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_info_callback
+    pub fn set_handshake_outcome_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, &HandshakeOutcome) + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(
+                SslContext::cached_ex_index::<HandshakeOutcomeCallback>(),
+                HandshakeOutcomeCallback(Box::new(callback)),
+            );
+            ffi::SSL_CTX_set_info_callback(self.as_ptr(), Some(callbacks::raw_info));
+        }
+    }
+
+    /// Enables counters of the handshakes of connections made with the context, retrieved with
+    /// [`SslContextRef::stats`].
+    ///
+    /// Handshakes started, completed, resumed and failed by [`HandshakeFailure`] class are
+    /// counted, along with the protocol versions and ciphers negotiated, so that common
+    /// monitoring does not need a callback. Connections are counted on the context they were
+    /// created with, even if another one, e.g. from an [`SniRouter`], is selected during the
+    /// handshake; that context must then keep the info callback installed, by enabling its own
+    /// counters or setting a handshake callback.
+    ///
+    /// This corresponds to [`SSL_CTX_set_info_callback`], reacting to `SSL_CB_HANDSHAKE_START`,
+    /// `SSL_CB_HANDSHAKE_DONE`, `SSL_CB_ACCEPT_EXIT` and `SSL_CB_CONNECT_EXIT`.
+    ///
+    /// [`SSL_CTX_set_info_callback`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_info_callback
+    pub fn enable_stats(&mut self) {
+        unsafe {
+            self.set_ex_data(
+                SslContext::cached_ex_index::<StatsCounters>(),
+                StatsCounters::default(),
+            );
+            ffi::SSL_CTX_set_info_callback(self.as_ptr(), Some(callbacks::raw_info));
+        }
+    }
+
     /// Sets the session caching mode use for connections made with the context.
     ///
     /// Returns the previous session caching mode.
@@ -1673,6 +2200,42 @@ impl SslContextBuilder {
         unsafe { ffi::SSL_CTX_sess_set_cache_size(self.as_ptr(), size.into()).into() }
     }
 
+    /// Sets the keys used to encrypt and decrypt session tickets.
+    ///
+    /// The 48 bytes are made of a 16-byte key name, a 16-byte HMAC secret and a 16-byte AES key.
+    /// Servers sharing a session ticket key can resume each other's sessions.
+    ///
+    /// This corresponds to [`SSL_CTX_set_tlsext_ticket_keys`].
+    ///
+    /// [`SSL_CTX_set_tlsext_ticket_keys`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_tlsext_ticket_keys
+    pub fn set_ticket_keys(&mut self, keys: &[u8; 48]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_tlsext_ticket_keys(
+                self.as_ptr(),
+                keys.as_ptr() as *const c_void,
+                keys.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Uses a [`TicketKeyManager`] to encrypt and decrypt session tickets.
+    ///
+    /// This replaces any keys set with [`SslContextBuilder::set_ticket_keys`]. Tickets encrypted
+    /// with a key the manager no longer knows are rejected, and the client falls back to a full
+    /// handshake.
+    ///
+    /// This corresponds to [`SSL_CTX_set_tlsext_ticket_key_cb`].
+    ///
+    /// [`SSL_CTX_set_tlsext_ticket_key_cb`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_tlsext_ticket_key_cb
+    pub fn set_ticket_key_manager(&mut self, manager: TicketKeyManager) {
+        self.set_ex_data(SslContext::cached_ex_index::<TicketKeyManager>(), manager);
+
+        unsafe {
+            ffi::SSL_CTX_set_tlsext_ticket_key_cb(self.as_ptr(), Some(raw_ticket_key));
+        }
+    }
+
     /// Sets the context's supported signature algorithms.
     ///
     /// This corresponds to [`SSL_CTX_set1_sigalgs_list`].
@@ -1695,6 +2258,48 @@ impl SslContextBuilder {
         unsafe { ffi::SSL_CTX_set_grease_enabled(self.as_ptr(), enabled as _) }
     }
 
+    /// Makes the handshakes of connections created from the context reproducible, for
+    /// differential fuzzing and golden transcript tests.
+    ///
+    /// GREASE and extension permutation are disabled, and the current time seen by BoringSSL is
+    /// fixed to [`DETERMINISTIC_TIME`] seconds after the UNIX epoch. Along with the
+    /// deterministic random number generator of the `unsafe-deterministic-rng` feature, reset
+    /// with [`reset_deterministic_rng`] before each handshake, the same handshake then produces
+    /// the same bytes on every run.
+    ///
+    /// This must never be used outside of tests.
+    ///
+    /// This corresponds to [`SSL_CTX_set_grease_enabled`], [`SSL_CTX_set_permute_extensions`]
+    /// and [`SSL_CTX_set_current_time_cb`].
+    ///
+    /// [`reset_deterministic_rng`]: crate::rand::reset_deterministic_rng
+    /// [`SSL_CTX_set_grease_enabled`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_grease_enabled
+    /// [`SSL_CTX_set_permute_extensions`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_permute_extensions
+    /// [`SSL_CTX_set_current_time_cb`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_current_time_cb
+    #[cfg(feature = "unsafe-deterministic-rng")]
+    pub fn set_deterministic_for_testing(&mut self) {
+        unsafe {
+            ffi::SSL_CTX_set_grease_enabled(self.as_ptr(), 0);
+            ffi::SSL_CTX_set_permute_extensions(self.as_ptr(), 0);
+            ffi::SSL_CTX_set_current_time_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_deterministic_time),
+            );
+        }
+    }
+
+    /// Sets whether early data (0-RTT) is enabled for connections created from the context.
+    ///
+    /// Clients send early data when resuming a session which allows it, and servers accept it
+    /// and issue sessions allowing it.
+    ///
+    /// This corresponds to [`SSL_CTX_set_early_data_enabled`].
+    ///
+    /// [`SSL_CTX_set_early_data_enabled`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_early_data_enabled
+    pub fn set_early_data_enabled(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_CTX_set_early_data_enabled(self.as_ptr(), enabled as _) }
+    }
+
     /// Sets the context's supported signature verification algorithms.
     ///
     /// This corresponds to [`SSL_CTX_set_verify_algorithm_prefs`]
@@ -1714,6 +2319,25 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets the context's supported signature algorithms for signing with its own private key.
+    ///
+    /// This corresponds to [`SSL_CTX_set_signing_algorithm_prefs`]
+    ///
+    /// [`SSL_CTX_set_signing_algorithm_prefs`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_set_signing_algorithm_prefs
+    pub fn set_signing_algorithm_prefs(
+        &mut self,
+        prefs: &[SslSignatureAlgorithm],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt_0i(ffi::SSL_CTX_set_signing_algorithm_prefs(
+                self.as_ptr(),
+                prefs.as_ptr() as *const _,
+                prefs.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Enables SCT requests on all client SSL handshakes.
     ///
     /// This corresponds to [`SSL_CTX_enable_signed_cert_timestamps`]
@@ -1723,16 +2347,64 @@ impl SslContextBuilder {
         unsafe { ffi::SSL_CTX_enable_signed_cert_timestamps(self.as_ptr()) }
     }
 
-    /// Enables OCSP stapling on all client SSL handshakes.
+    /// Enforces a Certificate Transparency policy on the server's certificate.
     ///
-    /// This corresponds to [`SSL_CTX_enable_ocsp_stapling`]
+    /// This requests signed certificate timestamps (SCTs) from the server, and calls the callback
+    /// with the connection, the server's leaf certificate and the SCTs delivered in the handshake
+    /// once the certificate chain has been verified. Returning `false` fails certificate
+    /// verification. SCTs can be checked against a list of known logs with
+    /// [`SignedCertificateTimestamp::verify`].
     ///
-    /// [`SSL_CTX_enable_ocsp_stapling`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_enable_ocsp_stapling
-    pub fn enable_ocsp_stapling(&mut self) {
-        unsafe { ffi::SSL_CTX_enable_ocsp_stapling(self.as_ptr()) }
-    }
-
-    /// Sets the context's supported curves.
+    /// This replaces any callback set with [`set_verify_callback`] and sets the verify mode to
+    /// [`SslVerifyMode::PEER`].
+    ///
+    /// [`SignedCertificateTimestamp::verify`]: ../sct/struct.SignedCertificateTimestamp.html#method.verify
+    /// [`set_verify_callback`]: #method.set_verify_callback
+    pub fn set_ct_policy_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&SslRef, &X509Ref, &[SignedCertificateTimestamp]) -> bool + 'static + Sync + Send,
+    {
+        self.enable_signed_cert_timestamps();
+        self.set_verify_callback(SslVerifyMode::PEER, move |preverify_ok, x509_ctx| {
+            // SCTs cover the leaf certificate, which is verified last.
+            if !preverify_ok || x509_ctx.error_depth() != 0 {
+                return preverify_ok;
+            }
+
+            let ssl_idx =
+                X509StoreContext::ssl_idx().expect("BUG: store context ssl index missing");
+            let ok = match (x509_ctx.ex_data(ssl_idx), x509_ctx.current_cert()) {
+                (Some(ssl), Some(cert)) => {
+                    match ssl
+                        .signed_cert_timestamp_list()
+                        .map(SignedCertificateTimestamp::parse_list)
+                    {
+                        Some(Ok(scts)) => callback(ssl, cert, &scts),
+                        Some(Err(_)) => false,
+                        None => callback(ssl, cert, &[]),
+                    }
+                }
+                _ => false,
+            };
+
+            if !ok {
+                x509_ctx.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+            }
+
+            ok
+        });
+    }
+
+    /// Enables OCSP stapling on all client SSL handshakes.
+    ///
+    /// This corresponds to [`SSL_CTX_enable_ocsp_stapling`]
+    ///
+    /// [`SSL_CTX_enable_ocsp_stapling`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_enable_ocsp_stapling
+    pub fn enable_ocsp_stapling(&mut self) {
+        unsafe { ffi::SSL_CTX_enable_ocsp_stapling(self.as_ptr()) }
+    }
+
+    /// Sets the context's supported curves.
     ///
     /// This corresponds to [`SSL_CTX_set1_curves`]
     ///
@@ -1836,6 +2508,22 @@ impl SslContext {
 }
 
 impl SslContextRef {
+    /// Returns the ciphers enabled for protocols before TLSv1.3, in order of preference.
+    ///
+    /// This corresponds to [`SSL_CTX_get_ciphers`].
+    ///
+    /// [`SSL_CTX_get_ciphers`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CTX_get_ciphers
+    pub fn ciphers(&self) -> Option<&StackRef<SslCipher>> {
+        unsafe {
+            let ptr = ffi::SSL_CTX_get_ciphers(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(ptr))
+            }
+        }
+    }
+
     /// Returns the certificate associated with this `SslContext`, if present.
     ///
     /// This corresponds to [`SSL_CTX_get0_certificate`].
@@ -1911,6 +2599,13 @@ impl SslContextRef {
         }
     }
 
+    /// Returns a snapshot of the handshake counters of the context, or `None` if they were not
+    /// enabled with [`SslContextBuilder::enable_stats`].
+    pub fn stats(&self) -> Option<SslStats> {
+        self.ex_data(SslContext::cached_ex_index::<StatsCounters>())
+            .map(StatsCounters::snapshot)
+    }
+
     /// Adds a session to the context's cache.
     ///
     /// Returns `true` if the session was successfully added to the cache, and `false` if it was already present.
@@ -1990,6 +2685,27 @@ pub struct CipherBits {
     pub algorithm: i32,
 }
 
+/// Channel binding values for a connection.
+///
+/// See [`SslRef::channel_bindings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelBindings {
+    /// The `tls-exporter` channel binding defined in [RFC 9266].
+    ///
+    /// It is only available for TLS 1.3 connections, or TLS 1.2 connections using the extended
+    /// master secret extension.
+    ///
+    /// [RFC 9266]: https://datatracker.ietf.org/doc/html/rfc9266
+    pub tls_exporter: Option<Vec<u8>>,
+
+    /// The `tls-server-end-point` channel binding defined in [RFC 5929].
+    ///
+    /// It is only available if the server presented a certificate.
+    ///
+    /// [RFC 5929]: https://datatracker.ietf.org/doc/html/rfc5929
+    pub tls_server_end_point: Option<Vec<u8>>,
+}
+
 #[repr(transparent)]
 pub struct ClientHello<'ssl>(&'ssl ffi::SSL_CLIENT_HELLO);
 
@@ -2025,6 +2741,13 @@ impl ClientHello<'_> {
         self.ssl().servername(type_)
     }
 
+    /// Returns the raw ClientHello message, without the handshake header.
+    ///
+    /// Its size can be checked to reject oversized ClientHellos early.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.0.client_hello, self.0.client_hello_len) }
+    }
+
     /// Returns the version sent by the client in its Client Hello record.
     pub fn client_version(&self) -> SslVersion {
         SslVersion(self.0.version)
@@ -2164,6 +2887,94 @@ impl SslCipherRef {
             Some(Nid::from_raw(n))
         }
     }
+
+    /// Returns the NID of the cipher's bulk encryption digest, or `None` for AEAD ciphers.
+    ///
+    /// This corresponds to [`SSL_CIPHER_get_digest_nid`].
+    ///
+    /// [`SSL_CIPHER_get_digest_nid`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_get_digest_nid
+    pub fn digest_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_digest_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
+        }
+    }
+
+    /// Returns the NID of the cipher's key exchange, such as [`Nid::KX_ECDHE`].
+    ///
+    /// TLS 1.3 ciphers return [`Nid::KX_ANY`].
+    ///
+    /// This corresponds to [`SSL_CIPHER_get_kx_nid`].
+    ///
+    /// [`SSL_CIPHER_get_kx_nid`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_get_kx_nid
+    pub fn kx_nid(&self) -> Nid {
+        Nid::from_raw(unsafe { ffi::SSL_CIPHER_get_kx_nid(self.as_ptr()) })
+    }
+
+    /// Returns the NID of the cipher's authentication, such as [`Nid::AUTH_RSA`].
+    ///
+    /// TLS 1.3 ciphers return [`Nid::AUTH_ANY`].
+    ///
+    /// This corresponds to [`SSL_CIPHER_get_auth_nid`].
+    ///
+    /// [`SSL_CIPHER_get_auth_nid`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_get_auth_nid
+    pub fn auth_nid(&self) -> Nid {
+        Nid::from_raw(unsafe { ffi::SSL_CIPHER_get_auth_nid(self.as_ptr()) })
+    }
+
+    /// Returns the name of the cipher's key exchange, such as `ECDHE_RSA`.
+    ///
+    /// This corresponds to [`SSL_CIPHER_get_kx_name`].
+    ///
+    /// [`SSL_CIPHER_get_kx_name`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_get_kx_name
+    pub fn kx_name(&self) -> &'static str {
+        unsafe {
+            let ptr = ffi::SSL_CIPHER_get_kx_name(self.as_ptr());
+            CStr::from_ptr(ptr).to_str().unwrap()
+        }
+    }
+
+    /// Returns whether the cipher uses an AEAD for bulk encryption.
+    ///
+    /// This corresponds to [`SSL_CIPHER_is_aead`].
+    ///
+    /// [`SSL_CIPHER_is_aead`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_is_aead
+    pub fn is_aead(&self) -> bool {
+        unsafe { ffi::SSL_CIPHER_is_aead(self.as_ptr()) == 1 }
+    }
+
+    /// Returns the two-byte identifier of the cipher suite on the wire.
+    ///
+    /// This corresponds to [`SSL_CIPHER_get_protocol_id`].
+    ///
+    /// [`SSL_CIPHER_get_protocol_id`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_get_protocol_id
+    pub fn protocol_id(&self) -> u16 {
+        unsafe { ffi::SSL_CIPHER_get_protocol_id(self.as_ptr()) }
+    }
+
+    /// Returns the minimum protocol version the cipher can be used with.
+    ///
+    /// This corresponds to [`SSL_CIPHER_get_min_version`].
+    ///
+    /// [`SSL_CIPHER_get_min_version`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_get_min_version
+    pub fn min_version(&self) -> SslVersion {
+        SslVersion(unsafe { ffi::SSL_CIPHER_get_min_version(self.as_ptr()) })
+    }
+
+    /// Returns the maximum protocol version the cipher can be used with.
+    ///
+    /// This corresponds to [`SSL_CIPHER_get_max_version`].
+    ///
+    /// [`SSL_CIPHER_get_max_version`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_CIPHER_get_max_version
+    pub fn max_version(&self) -> SslVersion {
+        SslVersion(unsafe { ffi::SSL_CIPHER_get_max_version(self.as_ptr()) })
+    }
+}
+
+impl Stackable for SslCipher {
+    type StackType = ffi::stack_st_SSL_CIPHER;
 }
 
 foreign_type_and_impl_send_sync! {
@@ -2275,6 +3086,128 @@ impl SslSessionRef {
         }
     }
 
+    /// Returns the cipher negotiated in the session.
+    ///
+    /// This corresponds to [`SSL_SESSION_get0_cipher`].
+    ///
+    /// [`SSL_SESSION_get0_cipher`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_SESSION_get0_cipher
+    pub fn cipher(&self) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_SESSION_get0_cipher(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(SslCipherRef::from_ptr(ptr as *mut _))
+            }
+        }
+    }
+
+    /// Returns `true` if the session may be offered for resumption.
+    ///
+    /// Sessions from full TLS 1.3 handshakes are not resumable; only those received afterwards in
+    /// session tickets are.
+    ///
+    /// This corresponds to [`SSL_SESSION_is_resumable`].
+    ///
+    /// [`SSL_SESSION_is_resumable`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_SESSION_is_resumable
+    pub fn is_resumable(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_is_resumable(self.as_ptr()) != 0 }
+    }
+
+    /// Returns `true` if the session has a ticket.
+    ///
+    /// This corresponds to [`SSL_SESSION_has_ticket`].
+    ///
+    /// [`SSL_SESSION_has_ticket`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_SESSION_has_ticket
+    pub fn has_ticket(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_has_ticket(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the lifetime of the session ticket advertised by the server, in seconds.
+    ///
+    /// This corresponds to [`SSL_SESSION_get_ticket_lifetime_hint`].
+    ///
+    /// [`SSL_SESSION_get_ticket_lifetime_hint`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_SESSION_get_ticket_lifetime_hint
+    #[allow(clippy::useless_conversion)]
+    pub fn ticket_lifetime_hint(&self) -> u32 {
+        unsafe { ffi::SSL_SESSION_get_ticket_lifetime_hint(self.as_ptr()) as u32 }
+    }
+
+    /// Returns the maximum amount of early data the server accepts when resuming the session, in
+    /// bytes.
+    ///
+    /// A value of zero means the session cannot be used to send early data.
+    ///
+    /// This corresponds to [`SSL_SESSION_get_max_early_data`].
+    ///
+    /// [`SSL_SESSION_get_max_early_data`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_SESSION_get_max_early_data
+    pub fn max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_SESSION_get_max_early_data(self.as_ptr()) }
+    }
+
+    /// Returns the pre-shared key a TLS 1.3 session is resumed with, or `None` for sessions of
+    /// earlier versions.
+    ///
+    /// The key is derived from the resumption master secret of the connection which received the
+    /// session and the nonce of its ticket, which BoringSSL keeps instead of the resumption master
+    /// secret itself. Sessions of earlier versions are resumed with their master key, see
+    /// [`SslSessionRef::master_key`].
+    ///
+    /// This corresponds to [`SSL_SESSION_get_master_key`].
+    ///
+    /// [`SSL_SESSION_get_master_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_SESSION_get_master_key
+    pub fn resumption_psk(&self) -> Option<Vec<u8>> {
+        if self.protocol_version() != SslVersion::TLS1_3 {
+            return None;
+        }
+
+        let mut psk = vec![0; self.master_key_len()];
+        let len = self.master_key(&mut psk);
+        psk.truncate(len);
+
+        Some(psk)
+    }
+
+    /// Returns the value added by the client to the age of the session's TLS 1.3 ticket to
+    /// obfuscate it, or `None` if the session has no TLS 1.3 ticket.
+    ///
+    /// Both ends of a connection must agree on it for the server to compute the age of the
+    /// ticket, so a mismatch shows up as a large [`SslRef::ticket_age_skew`].
+    ///
+    /// BoringSSL has no accessor for it, so it is read from the serialized session.
+    pub fn ticket_age_add(&self) -> Option<u32> {
+        // ticketAgeAdd [21] OCTET STRING (SIZE (4)) OPTIONAL
+        const TICKET_AGE_ADD_TAG: u32 = 0xa0 | 21;
+
+        let der = self.to_der().ok()?;
+        let (_, mut fields, _) = der_element(&der)?;
+
+        while !fields.is_empty() {
+            let (tag, contents, rest) = der_element(fields)?;
+            if tag == TICKET_AGE_ADD_TAG {
+                return match der_element(contents)? {
+                    (0x04, &[a, b, c, d], _) => Some(u32::from_be_bytes([a, b, c, d])),
+                    _ => None,
+                };
+            }
+
+            fields = rest;
+        }
+
+        None
+    }
+
+    /// Returns `true` if the session's timeout has elapsed, per the system clock.
+    ///
+    /// Sessions loaded from a persistent cache should be discarded once expired.
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        self.time().saturating_add(u64::from(self.timeout())) <= now
+    }
+
     to_der! {
         /// Serializes the session into a DER-encoded structure.
         ///
@@ -2286,6 +3219,95 @@ impl SslSessionRef {
     }
 }
 
+/// Splits the first element off DER-encoded `der`, returning its tag, its contents and the
+/// elements after it.
+///
+/// Tags spanning several bytes are returned with their bytes concatenated.
+fn der_element(der: &[u8]) -> Option<(u32, &[u8], &[u8])> {
+    let (&first, mut rest) = der.split_first()?;
+    let mut tag = u32::from(first);
+
+    if first & 0x1f == 0x1f {
+        loop {
+            let (&byte, tail) = rest.split_first()?;
+            tag = tag.checked_mul(0x100)? | u32::from(byte);
+            rest = tail;
+
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+
+    let (&len, tail) = rest.split_first()?;
+    rest = tail;
+
+    let len = if len & 0x80 == 0 {
+        usize::from(len)
+    } else {
+        let n = usize::from(len & 0x7f);
+        if n == 0 || n > mem::size_of::<usize>() || rest.len() < n {
+            return None;
+        }
+
+        let (bytes, tail) = rest.split_at(n);
+        rest = tail;
+        bytes
+            .iter()
+            .fold(0, |len, &byte| len << 8 | usize::from(byte))
+    };
+
+    if rest.len() < len {
+        return None;
+    }
+
+    let (contents, rest) = rest.split_at(len);
+    Some((tag, contents, rest))
+}
+
+/// The reason a session was rejected by [`SslRef::set_session_checked`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SessionError {
+    /// The session cannot be resumed.
+    NotResumable,
+    /// The session's timeout has elapsed.
+    Expired,
+    /// The session's protocol version is not enabled on the connection.
+    Version(SslVersion),
+    /// The session's cipher is not enabled on the connection.
+    Cipher,
+    /// BoringSSL failed to set the session.
+    Ssl(ErrorStack),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::NotResumable => fmt.write_str("the session is not resumable"),
+            SessionError::Expired => fmt.write_str("the session has expired"),
+            SessionError::Version(version) => {
+                write!(
+                    fmt,
+                    "the session's protocol version {} is not enabled",
+                    version
+                )
+            }
+            SessionError::Cipher => fmt.write_str("the session's cipher is not enabled"),
+            SessionError::Ssl(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SessionError::Ssl(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::SSL;
     fn drop = ffi::SSL_free;
@@ -2488,6 +3510,11 @@ impl SslRef {
         unsafe { ffi::SSL_read(self.as_ptr(), buf.as_ptr() as *mut c_void, len) }
     }
 
+    fn peek(&mut self, buf: &mut [u8]) -> c_int {
+        let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
+        unsafe { ffi::SSL_peek(self.as_ptr(), buf.as_ptr() as *mut c_void, len) }
+    }
+
     fn write(&mut self, buf: &[u8]) -> c_int {
         let len = cmp::min(c_int::max_value() as usize, buf.len()) as c_int;
         unsafe { ffi::SSL_write(self.as_ptr(), buf.as_ptr() as *const c_void, len) }
@@ -2541,6 +3568,43 @@ impl SslRef {
             .expect("invalid default server curves list");
     }
 
+    /// Like [`SslContextBuilder::set_mode`].
+    ///
+    /// A client retrying a failed handshake with a lower maximum protocol version should set
+    /// [`SslMode::SEND_FALLBACK_SCSV`] on the retried connection only, so that servers can detect
+    /// a version downgrade forced by an attacker.
+    ///
+    /// This corresponds to [`SSL_set_mode`].
+    ///
+    /// [`SSL_set_mode`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_mode
+    pub fn set_mode(&mut self, mode: SslMode) -> SslMode {
+        unsafe {
+            let bits = ffi::SSL_set_mode(self.as_ptr(), mode.bits());
+            SslMode::from_bits_retain(bits)
+        }
+    }
+
+    /// Clears modes of the session, returning the new mode.
+    ///
+    /// This corresponds to [`SSL_clear_mode`].
+    ///
+    /// [`SSL_clear_mode`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_clear_mode
+    pub fn clear_mode(&mut self, mode: SslMode) -> SslMode {
+        unsafe {
+            let bits = ffi::SSL_clear_mode(self.as_ptr(), mode.bits());
+            SslMode::from_bits_retain(bits)
+        }
+    }
+
+    /// Returns the mode of the session.
+    ///
+    /// This corresponds to [`SSL_get_mode`].
+    ///
+    /// [`SSL_get_mode`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_mode
+    pub fn mode(&self) -> SslMode {
+        unsafe { SslMode::from_bits_retain(ffi::SSL_get_mode(self.as_ptr())) }
+    }
+
     /// Like [`SslContextBuilder::set_verify`].
     ///
     /// This corresponds to [`SSL_set_verify`].
@@ -2600,65 +3664,262 @@ impl SslRef {
         }
     }
 
-    /// Like [`SslContextBuilder::set_tmp_dh`].
-    ///
-    /// This corresponds to [`SSL_set_tmp_dh`].
+    /// Like [`SslContextBuilder::set_custom_verify_callback`].
     ///
-    /// [`SslContextBuilder::set_tmp_dh`]: struct.SslContextBuilder.html#method.set_tmp_dh
-    /// [`SSL_set_tmp_dh`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_set_tmp_dh.html
-    pub fn set_tmp_dh(&mut self, dh: &DhRef<Params>) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_set_tmp_dh(self.as_ptr(), dh.as_ptr()) as c_int).map(|_| ()) }
-    }
-
-    /// Like [`SslContextBuilder::set_tmp_ecdh`].
+    /// This lets connections sharing a context verify their peers differently, e.g. by pinning
+    /// the certificate expected from each destination.
     ///
-    /// This corresponds to `SSL_set_tmp_ecdh`.
+    /// This corresponds to [`SSL_set_custom_verify`].
     ///
-    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
-    pub fn set_tmp_ecdh(&mut self, key: &EcKeyRef<Params>) -> Result<(), ErrorStack> {
-        unsafe { cvt(ffi::SSL_set_tmp_ecdh(self.as_ptr(), key.as_ptr()) as c_int).map(|_| ()) }
-    }
+    /// [`SSL_set_custom_verify`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_custom_verify
+    pub fn set_custom_verify_callback<F>(&mut self, mode: SslVerifyMode, callback: F)
+    where
+        F: Fn(&mut SslRef) -> Result<(), SslVerifyError> + 'static + Sync + Send,
+    {
+        #[cfg(feature = "rpk")]
+        assert!(
+            !self.ssl_context().is_rpk(),
+            "This API is not supported for RPK"
+        );
 
-    /// Like [`SslContextBuilder::set_alpn_protos`].
-    ///
-    /// This corresponds to [`SSL_set_alpn_protos`].
-    ///
-    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
-    /// [`SSL_set_alpn_protos`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_alpn_protos.html
-    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
         unsafe {
-            #[cfg_attr(
-                not(any(feature = "fips", feature = "fips-link-precompiled")),
-                allow(clippy::unnecessary_cast)
-            )]
-            {
-                assert!(protocols.len() <= ProtosLen::max_value() as usize);
-            }
-            let r = ffi::SSL_set_alpn_protos(
+            // The callback is passed `&mut SslRef`, through which it could replace itself.
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
+            ffi::SSL_set_custom_verify(
                 self.as_ptr(),
-                protocols.as_ptr(),
-                protocols.len() as ProtosLen,
+                mode.bits() as c_int,
+                Some(ssl_raw_custom_verify::<F>),
             );
-            // fun fact, SSL_set_alpn_protos has a reversed return code D:
-            if r == 0 {
-                Ok(())
-            } else {
-                Err(ErrorStack::get())
-            }
         }
     }
 
-    /// Returns the current cipher if the session is active.
+    /// Like [`SslContextBuilder::set_client_ca_list`].
     ///
-    /// This corresponds to [`SSL_get_current_cipher`].
+    /// This corresponds to [`SSL_set_client_CA_list`].
     ///
-    /// [`SSL_get_current_cipher`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_current_cipher.html
-    pub fn current_cipher(&self) -> Option<&SslCipherRef> {
-        unsafe {
-            let ptr = ffi::SSL_get_current_cipher(self.as_ptr());
+    /// [`SslContextBuilder::set_client_ca_list`]: struct.SslContextBuilder.html#method.set_client_ca_list
+    /// [`SSL_set_client_CA_list`]: https://www.openssl.org/docs/manmaster/man3/SSL_set_client_CA_list.html
+    pub fn set_client_ca_list(&mut self, list: Stack<X509Name>) {
+        #[cfg(feature = "rpk")]
+        assert!(
+            !self.ssl_context().is_rpk(),
+            "This API is not supported for RPK"
+        );
 
-            if ptr.is_null() {
-                None
+        unsafe {
+            ffi::SSL_set_client_CA_list(self.as_ptr(), list.as_ptr());
+            mem::forget(list);
+        }
+    }
+
+    /// Returns the list of CA names.
+    ///
+    /// On the server side, this is the list of CA names that will be sent to the client when
+    /// requesting a certificate. On the client side, this is the list of CA names sent by the
+    /// server in its certificate request, and is only available during the handshake, e.g. from
+    /// within a certificate selection callback.
+    ///
+    /// This corresponds to [`SSL_get_client_CA_list`].
+    ///
+    /// [`SSL_get_client_CA_list`]: https://www.openssl.org/docs/manmaster/man3/SSL_get_client_CA_list.html
+    pub fn client_ca_list(&self) -> Option<&StackRef<X509Name>> {
+        #[cfg(feature = "rpk")]
+        assert!(
+            !self.ssl_context().is_rpk(),
+            "This API is not supported for RPK"
+        );
+
+        unsafe {
+            let ptr = ffi::SSL_get_client_CA_list(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_verify_algorithm_prefs`].
+    ///
+    /// On the server side, this constrains the signature algorithms accepted for client
+    /// certificates, which are advertised in the certificate request.
+    ///
+    /// This corresponds to [`SSL_set_verify_algorithm_prefs`].
+    ///
+    /// [`SslContextBuilder::set_verify_algorithm_prefs`]: struct.SslContextBuilder.html#method.set_verify_algorithm_prefs
+    /// [`SSL_set_verify_algorithm_prefs`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_verify_algorithm_prefs
+    pub fn set_verify_algorithm_prefs(
+        &mut self,
+        prefs: &[SslSignatureAlgorithm],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt_0i(ffi::SSL_set_verify_algorithm_prefs(
+                self.as_ptr(),
+                prefs.as_ptr() as *const _,
+                prefs.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_signing_algorithm_prefs`].
+    ///
+    /// On the client side, this constrains the signature algorithms used when signing with the
+    /// client certificate.
+    ///
+    /// This corresponds to [`SSL_set_signing_algorithm_prefs`].
+    ///
+    /// [`SslContextBuilder::set_signing_algorithm_prefs`]: struct.SslContextBuilder.html#method.set_signing_algorithm_prefs
+    /// [`SSL_set_signing_algorithm_prefs`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_signing_algorithm_prefs
+    pub fn set_signing_algorithm_prefs(
+        &mut self,
+        prefs: &[SslSignatureAlgorithm],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt_0i(ffi::SSL_set_signing_algorithm_prefs(
+                self.as_ptr(),
+                prefs.as_ptr() as *const _,
+                prefs.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_cipher_list`].
+    ///
+    /// The cipher suites for TLSv1.3 are not configurable in BoringSSL.
+    ///
+    /// This corresponds to [`SSL_set_cipher_list`].
+    ///
+    /// [`SslContextBuilder::set_cipher_list`]: struct.SslContextBuilder.html#method.set_cipher_list
+    /// [`SSL_set_cipher_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_cipher_list
+    pub fn set_cipher_list(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
+        let cipher_list = CString::new(cipher_list).unwrap();
+        unsafe {
+            cvt(ffi::SSL_set_cipher_list(
+                self.as_ptr(),
+                cipher_list.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_strict_cipher_list`].
+    ///
+    /// This corresponds to [`SSL_set_strict_cipher_list`].
+    ///
+    /// [`SslContextBuilder::set_strict_cipher_list`]: struct.SslContextBuilder.html#method.set_strict_cipher_list
+    /// [`SSL_set_strict_cipher_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_strict_cipher_list
+    pub fn set_strict_cipher_list(&mut self, cipher_list: &str) -> Result<(), ErrorStack> {
+        let cipher_list = CString::new(cipher_list).unwrap();
+        unsafe {
+            cvt(ffi::SSL_set_strict_cipher_list(
+                self.as_ptr(),
+                cipher_list.as_ptr() as *const _,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_curves`].
+    ///
+    /// This lets connections sharing a context offer different key exchange groups, e.g. to
+    /// require post-quantum key exchange with some peers only.
+    ///
+    /// This corresponds to [`SSL_set1_curves`].
+    ///
+    /// [`SslContextBuilder::set_curves`]: struct.SslContextBuilder.html#method.set_curves
+    /// [`SSL_set1_curves`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set1_curves
+    //
+    // See `SslContextBuilder::set_curves` for why this is unavailable with the "kx-*" flags.
+    #[cfg(not(feature = "kx-safe-default"))]
+    pub fn set_curves(&mut self, curves: &[SslCurve]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt_0i(ffi::SSL_set1_curves(
+                self.as_ptr(),
+                curves.as_ptr() as *const _,
+                curves.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_tmp_dh`].
+    ///
+    /// This corresponds to [`SSL_set_tmp_dh`].
+    ///
+    /// [`SslContextBuilder::set_tmp_dh`]: struct.SslContextBuilder.html#method.set_tmp_dh
+    /// [`SSL_set_tmp_dh`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_set_tmp_dh.html
+    pub fn set_tmp_dh(&mut self, dh: &DhRef<Params>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_tmp_dh(self.as_ptr(), dh.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_tmp_ecdh`].
+    ///
+    /// This corresponds to `SSL_set_tmp_ecdh`.
+    ///
+    /// [`SslContextBuilder::set_tmp_ecdh`]: struct.SslContextBuilder.html#method.set_tmp_ecdh
+    pub fn set_tmp_ecdh(&mut self, key: &EcKeyRef<Params>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_tmp_ecdh(self.as_ptr(), key.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_alpn_protos`].
+    ///
+    /// This corresponds to [`SSL_set_alpn_protos`].
+    ///
+    /// [`SslContextBuilder::set_alpn_protos`]: struct.SslContextBuilder.html#method.set_alpn_protos
+    /// [`SSL_set_alpn_protos`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_set_alpn_protos.html
+    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            #[cfg_attr(
+                not(any(feature = "fips", feature = "fips-link-precompiled")),
+                allow(clippy::unnecessary_cast)
+            )]
+            {
+                assert!(protocols.len() <= ProtosLen::max_value() as usize);
+            }
+            let r = ffi::SSL_set_alpn_protos(
+                self.as_ptr(),
+                protocols.as_ptr(),
+                protocols.len() as ProtosLen,
+            );
+            // fun fact, SSL_set_alpn_protos has a reversed return code D:
+            if r == 0 {
+                Ok(())
+            } else {
+                Err(ErrorStack::get())
+            }
+        }
+    }
+
+    /// Like [`SslContextRef::ciphers`].
+    ///
+    /// This corresponds to [`SSL_get_ciphers`].
+    ///
+    /// [`SslContextRef::ciphers`]: struct.SslContextRef.html#method.ciphers
+    /// [`SSL_get_ciphers`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_ciphers
+    pub fn ciphers(&self) -> Option<&StackRef<SslCipher>> {
+        unsafe {
+            let ptr = ffi::SSL_get_ciphers(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the current cipher if the session is active.
+    ///
+    /// This corresponds to [`SSL_get_current_cipher`].
+    ///
+    /// [`SSL_get_current_cipher`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get_current_cipher.html
+    pub fn current_cipher(&self) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_get_current_cipher(self.as_ptr());
+
+            if ptr.is_null() {
+                None
             } else {
                 Some(SslCipherRef::from_ptr(ptr as *mut _))
             }
@@ -2730,6 +3991,14 @@ impl SslRef {
         }
     }
 
+    /// Returns the SPIFFE ID of the peer's certificate, if it is an X.509 SVID.
+    ///
+    /// The certificate must have been verified, for instance against the trust bundle of its
+    /// trust domain, for the ID to be trusted. See [`X509Ref::spiffe_id`].
+    pub fn peer_spiffe_id(&self) -> Option<String> {
+        self.peer_certificate()?.spiffe_id()
+    }
+
     /// Returns the certificate chain of the peer, if present.
     ///
     /// On the client side, the chain includes the leaf certificate, but on the server side it does
@@ -2755,6 +4024,129 @@ impl SslRef {
         }
     }
 
+    /// Returns the verified certificate chain of the peer, including the leaf certificate.
+    ///
+    /// Unlike [`SslRef::peer_cert_chain`], this is the chain built during verification up to a
+    /// trusted root, rather than the chain presented by the peer. It is only available if
+    /// verification succeeded.
+    ///
+    /// This corresponds to [`SSL_get0_verified_chain`].
+    ///
+    /// [`SSL_get0_verified_chain`]: https://www.openssl.org/docs/man1.1.0/ssl/SSL_get0_verified_chain.html
+    pub fn verified_chain(&self) -> Option<&StackRef<X509>> {
+        #[cfg(feature = "rpk")]
+        assert!(
+            !self.ssl_context().is_rpk(),
+            "This API is not supported for RPK"
+        );
+
+        unsafe {
+            let ptr = ffi::SSL_get0_verified_chain(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the signature algorithm used by the peer to sign the handshake, if any.
+    ///
+    /// This corresponds to [`SSL_get_peer_signature_algorithm`].
+    ///
+    /// [`SSL_get_peer_signature_algorithm`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_peer_signature_algorithm
+    pub fn peer_signature_algorithm(&self) -> Option<SslSignatureAlgorithm> {
+        unsafe {
+            let sigalg = ffi::SSL_get_peer_signature_algorithm(self.as_ptr());
+            if sigalg == 0 {
+                None
+            } else {
+                Some(SslSignatureAlgorithm(sigalg))
+            }
+        }
+    }
+
+    /// Returns the signature algorithms accepted by the peer, in its order of preference.
+    ///
+    /// On the client side, these are the algorithms listed in the certificate request of the
+    /// server, and are only available during the handshake, e.g. from within a certificate
+    /// callback. On the server side, these are the algorithms of the ClientHello.
+    ///
+    /// This corresponds to [`SSL_get0_peer_verify_algorithms`].
+    ///
+    /// [`SSL_get0_peer_verify_algorithms`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get0_peer_verify_algorithms
+    pub fn peer_verify_algorithms(&self) -> &[SslSignatureAlgorithm] {
+        unsafe {
+            let mut ptr = ptr::null();
+            let len = ffi::SSL_get0_peer_verify_algorithms(self.as_ptr(), &mut ptr);
+            if ptr.is_null() || len == 0 {
+                &[]
+            } else {
+                slice::from_raw_parts(ptr as *const SslSignatureAlgorithm, len)
+            }
+        }
+    }
+
+    /// On the client side, returns the parameters of the certificate request sent by the server,
+    /// if any.
+    ///
+    /// Requests are only recorded by the callback set with
+    /// [`SslContextBuilder::set_cert_callback`], so this is always `None` for connections whose
+    /// context has none, including those of an [`SslConnector`] or a plain [`SslContext`]. To
+    /// record requests without otherwise handling them, set a callback doing nothing:
+    ///
+    /// ```
+    /// # use boring::ssl::{SslConnector, SslMethod};
+    /// let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    /// connector.set_cert_callback(|_| Ok(()));
+    /// ```
+    ///
+    /// Unlike [`SslRef::client_ca_list`] and [`SslRef::peer_verify_algorithms`], the request
+    /// remains available after the handshake.
+    ///
+    /// [`SslConnector`]: crate::ssl::SslConnector
+    pub fn certificate_request(&self) -> Option<&CertificateRequest> {
+        self.ex_data(Ssl::cached_ex_index::<CertificateRequest>())
+    }
+
+    /// Returns `true` if the server requested a certificate from the client.
+    ///
+    /// On the server side, this is `true` if the handshake was not resumed and the verify mode
+    /// includes [`SslVerifyMode::PEER`]. On the client side, this is
+    /// [`SslRef::certificate_request`]`().is_some()`, and so requires a certificate callback.
+    ///
+    /// BoringSSL does not implement TLS 1.3 post-handshake authentication, so certificates are
+    /// only requested during the handshake, or during a TLS 1.2 renegotiation.
+    pub fn certificate_requested(&self) -> bool {
+        if self.is_server() {
+            !self.session_reused() && self.verify_mode().contains(SslVerifyMode::PEER)
+        } else {
+            self.certificate_request().is_some()
+        }
+    }
+
+    /// Returns the name of the key exchange group negotiated for the connection, if any.
+    ///
+    /// This corresponds to [`SSL_get_curve_id`] and [`SSL_get_curve_name`].
+    ///
+    /// [`SSL_get_curve_id`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_curve_id
+    /// [`SSL_get_curve_name`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_curve_name
+    pub fn negotiated_group(&self) -> Option<&'static str> {
+        unsafe {
+            let curve_id = ffi::SSL_get_curve_id(self.as_ptr());
+            if curve_id == 0 {
+                return None;
+            }
+
+            let ptr = ffi::SSL_get_curve_name(curve_id);
+            if ptr.is_null() {
+                return None;
+            }
+
+            str::from_utf8(CStr::from_ptr(ptr as *const _).to_bytes()).ok()
+        }
+    }
+
     /// Like [`SslContext::certificate`].
     ///
     /// This corresponds to `SSL_get_certificate`.
@@ -2793,6 +4185,15 @@ impl SslRef {
         }
     }
 
+    /// Like [`SslContextBuilder::check_private_key`].
+    ///
+    /// This corresponds to [`SSL_check_private_key`].
+    ///
+    /// [`SSL_check_private_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_check_private_key
+    pub fn check_private_key(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_check_private_key(self.as_ptr())).map(|_| ()) }
+    }
+
     #[deprecated(since = "0.10.5", note = "renamed to `version_str`")]
     pub fn version(&self) -> &str {
         self.version_str()
@@ -2921,6 +4322,42 @@ impl SslRef {
         unsafe { ffi::SSL_pending(self.as_ptr()) as usize }
     }
 
+    /// Determines if the session has buffered data, either decrypted or not yet processed.
+    ///
+    /// Unlike [`SslRef::pending`], this accounts for records that were read from the underlying
+    /// stream but not yet decrypted.
+    ///
+    /// This corresponds to [`SSL_has_pending`].
+    ///
+    /// [`SSL_has_pending`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_has_pending
+    pub fn has_pending(&self) -> bool {
+        unsafe { ffi::SSL_has_pending(self.as_ptr()) != 0 }
+    }
+
+    /// Sets the read-ahead flag of the session.
+    ///
+    /// BoringSSL never reads past the end of the current TLS record, regardless of this flag: bytes
+    /// following the TLS session, e.g. after a `close_notify`, remain in the underlying stream and
+    /// can be recovered with [`SslStream::into_inner`]. The flag is kept for compatibility.
+    ///
+    /// This corresponds to [`SSL_set_read_ahead`].
+    ///
+    /// [`SSL_set_read_ahead`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_read_ahead
+    pub fn set_read_ahead(&mut self, read_ahead: bool) {
+        unsafe {
+            ffi::SSL_set_read_ahead(self.as_ptr(), read_ahead as c_int);
+        }
+    }
+
+    /// Returns the read-ahead flag of the session.
+    ///
+    /// This corresponds to [`SSL_get_read_ahead`].
+    ///
+    /// [`SSL_get_read_ahead`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_read_ahead
+    pub fn read_ahead(&self) -> bool {
+        unsafe { ffi::SSL_get_read_ahead(self.as_ptr()) != 0 }
+    }
+
     /// Returns the servername sent by the client via Server Name Indication (SNI).
     ///
     /// It is only useful on the server side.
@@ -3088,13 +4525,172 @@ impl SslRef {
         }
     }
 
-    /// Sets the session to be used.
-    ///
-    /// This should be called before the handshake to attempt to reuse a previously established
-    /// session. If the server is not willing to reuse the session, a new one will be transparently
-    /// negotiated.
+    /// Attaches a private key operation running outside of the handshake to the connection.
     ///
-    /// This corresponds to [`SSL_set_session`].
+    /// See [`PendingOperation`] for details.
+    pub fn set_pending_private_key_operation(&mut self, operation: PendingOperation) {
+        let index = Ssl::cached_ex_index::<Option<PendingOperation>>();
+
+        match self.ex_data_mut(index) {
+            Some(slot) => *slot = Some(operation),
+            None => self.set_ex_data(index, Some(operation)),
+        }
+    }
+
+    /// Attaches the handshake budget of the connection's client.
+    ///
+    /// See [`HandshakeBudget`] for details.
+    pub fn set_handshake_budget(&mut self, budget: HandshakeBudget) {
+        let index = Ssl::cached_ex_index::<HandshakeBudget>();
+
+        match self.ex_data_mut(index) {
+            Some(slot) => *slot = budget,
+            None => self.set_ex_data(index, budget),
+        }
+    }
+
+    /// Returns the handshake budget attached to the connection, if any.
+    pub fn handshake_budget(&self) -> Option<&HandshakeBudget> {
+        self.ex_data(Ssl::cached_ex_index::<HandshakeBudget>())
+    }
+
+    /// Suspends the handshake from the certificate selection callback until the returned token
+    /// is used to resume or abort it.
+    ///
+    /// The callback must then return [`SelectCertError::RETRY`], and call
+    /// [`SslRef::handshake_resumption`] when it is called again. See [`ResumeToken`] for details.
+    pub fn suspend_handshake(&mut self) -> ResumeToken {
+        let token = ResumeToken::default();
+        let index = Ssl::cached_ex_index::<Option<ResumeToken>>();
+
+        match self.ex_data_mut(index) {
+            Some(slot) => *slot = Some(token.clone()),
+            None => self.set_ex_data(index, Some(token.clone())),
+        }
+
+        token
+    }
+
+    /// Returns the token of the suspended handshake, if any.
+    pub fn resume_token(&self) -> Option<&ResumeToken> {
+        self.ex_data(Ssl::cached_ex_index::<Option<ResumeToken>>())
+            .and_then(Option::as_ref)
+    }
+
+    /// Returns the outcome of the suspended handshake, to be returned from the certificate
+    /// selection callback, or `None` if the handshake was not suspended.
+    ///
+    /// The outcome is [`SelectCertError::RETRY`] while no decision was made. Once one was, the
+    /// token is detached from the connection.
+    pub fn handshake_resumption(&mut self) -> Option<Result<(), SelectCertError>> {
+        let slot = self.ex_data_mut(Ssl::cached_ex_index::<Option<ResumeToken>>())?;
+
+        let resume = match slot {
+            Some(token) => token.decision(),
+            None => return None,
+        };
+
+        let result = match resume {
+            Some(true) => Ok(()),
+            Some(false) => Err(SelectCertError::ERROR),
+            None => return Some(Err(SelectCertError::RETRY)),
+        };
+
+        *slot = None;
+
+        Some(result)
+    }
+
+    /// Returns the private key operation attached to the connection, if any.
+    pub fn pending_private_key_operation(&self) -> Option<&PendingOperation> {
+        self.ex_data(Ssl::cached_ex_index::<Option<PendingOperation>>())
+            .and_then(Option::as_ref)
+    }
+
+    /// Completes the private key operation attached to the connection.
+    ///
+    /// This is meant to be returned from [`PrivateKeyMethod::complete`]. It writes the output of
+    /// the operation into `output` and detaches it from the connection if it has completed, and
+    /// returns [`PrivateKeyMethodError::RETRY`] otherwise. It fails if no operation is attached
+    /// or if its output does not fit in `output`.
+    pub fn private_key_operation_complete(
+        &mut self,
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError> {
+        let slot = self
+            .ex_data_mut(Ssl::cached_ex_index::<Option<PendingOperation>>())
+            .ok_or(PrivateKeyMethodError::FAILURE)?;
+
+        let result = match slot {
+            Some(operation) => operation.take_result(),
+            None => return Err(PrivateKeyMethodError::FAILURE),
+        };
+
+        let result = match result {
+            Some(result) => result,
+            None => return Err(PrivateKeyMethodError::RETRY),
+        };
+
+        *slot = None;
+
+        let bytes = result?;
+        let written = output
+            .get_mut(..bytes.len())
+            .ok_or(PrivateKeyMethodError::FAILURE)?;
+        written.copy_from_slice(&bytes);
+
+        Ok(bytes.len())
+    }
+
+    /// Returns the channel binding values of the connection.
+    ///
+    /// These are used by authentication mechanisms such as SCRAM-PLUS to bind credentials to the
+    /// TLS connection they are sent over. They are only meaningful once the handshake completed.
+    ///
+    /// The `tls-server-end-point` value hashes the server's certificate with the digest of its
+    /// signature algorithm, using SHA-256 if that is MD5 or SHA-1, or if the algorithm does not
+    /// specify a digest.
+    pub fn channel_bindings(&self) -> Result<ChannelBindings, ErrorStack> {
+        const TLS_EXPORTER_LABEL: &str = "EXPORTER-Channel-Binding";
+
+        let extms = unsafe { ffi::SSL_get_extms_support(self.as_ptr()) == 1 };
+        let tls_exporter = if extms || self.version2() == Some(SslVersion::TLS1_3) {
+            let mut out = vec![0; 32];
+            self.export_keying_material(&mut out, TLS_EXPORTER_LABEL, None)?;
+            Some(out)
+        } else {
+            None
+        };
+
+        #[cfg(feature = "rpk")]
+        let rpk = self.ssl_context().is_rpk();
+
+        #[cfg(not(feature = "rpk"))]
+        let rpk = false;
+
+        let tls_server_end_point = if rpk {
+            None
+        } else if self.is_server() {
+            self.certificate().map(server_end_point).transpose()?
+        } else {
+            self.peer_certificate()
+                .map(|cert| server_end_point(&cert))
+                .transpose()?
+        };
+
+        Ok(ChannelBindings {
+            tls_exporter,
+            tls_server_end_point,
+        })
+    }
+
+    /// Sets the session to be used.
+    ///
+    /// This should be called before the handshake to attempt to reuse a previously established
+    /// session. If the server is not willing to reuse the session, a new one will be transparently
+    /// negotiated.
+    ///
+    /// This corresponds to [`SSL_set_session`].
     ///
     /// # Safety
     ///
@@ -3106,6 +4702,71 @@ impl SslRef {
         cvt(ffi::SSL_set_session(self.as_ptr(), session.as_ptr())).map(|_| ())
     }
 
+    /// Like [`SslRef::set_session`], but first checks that the session can be used on this
+    /// connection.
+    ///
+    /// The session must be resumable, unexpired, and have a protocol version and cipher enabled
+    /// on this connection. This makes it suitable for sessions deserialized with
+    /// [`SslSession::from_der`], e.g. from a persistent cache, which BoringSSL would otherwise
+    /// only reject once the handshake has started, if at all.
+    ///
+    /// # Safety
+    ///
+    /// The context the session was established with is not checked, by this method nor by
+    /// BoringSSL on the client side. As for [`SslRef::set_session`], the caller is responsible
+    /// for ensuring that the session is associated with the same `SslContext` as this `Ssl`, or
+    /// with one handling certificates the same way, e.g. not with a raw public key context if
+    /// this one uses X.509 certificates.
+    ///
+    /// [`SslRef::set_session`]: struct.SslRef.html#method.set_session
+    /// [`SslSession::from_der`]: struct.SslSession.html#method.from_der
+    pub unsafe fn set_session_checked(
+        &mut self,
+        session: &SslSessionRef,
+    ) -> Result<(), SessionError> {
+        if !session.is_resumable() {
+            return Err(SessionError::NotResumable);
+        }
+
+        if session.is_expired() {
+            return Err(SessionError::Expired);
+        }
+
+        let version = session.protocol_version();
+        let (min, max, is_dtls) = unsafe {
+            (
+                ffi::SSL_get_min_proto_version(self.as_ptr()),
+                ffi::SSL_get_max_proto_version(self.as_ptr()),
+                ffi::SSL_is_dtls(self.as_ptr()) != 0,
+            )
+        };
+        // DTLS version numbers decrease as the protocol version increases.
+        let enabled = if is_dtls {
+            max <= version.0 && version.0 <= min
+        } else {
+            min <= version.0 && version.0 <= max
+        };
+        if !enabled {
+            return Err(SessionError::Version(version));
+        }
+
+        let cipher_enabled = match (session.cipher(), self.ciphers()) {
+            // TLS 1.3 ciphers are not configurable.
+            (Some(_), _) if version == SslVersion::TLS1_3 => true,
+            (Some(cipher), Some(ciphers)) => ciphers
+                .iter()
+                .any(|c| c.protocol_id() == cipher.protocol_id()),
+            _ => false,
+        };
+        if !cipher_enabled {
+            return Err(SessionError::Cipher);
+        }
+
+        // SAFETY: the session's parameters were checked against this connection above, and the
+        // caller guarantees that it is associated with a compatible context.
+        unsafe { self.set_session(session).map_err(SessionError::Ssl) }
+    }
+
     /// Determines if the session provided to `set_session` was successfully reused.
     ///
     /// This corresponds to [`SSL_session_reused`].
@@ -3115,6 +4776,136 @@ impl SslRef {
         unsafe { ffi::SSL_session_reused(self.as_ptr()) != 0 }
     }
 
+    /// Sets whether early data (0-RTT) is enabled for the connection.
+    ///
+    /// With early data enabled, a client resuming a session which allows it completes the
+    /// handshake as soon as the ClientHello was sent, and data written until the server responds
+    /// is sent as early data. If the server rejects it, the next read or write fails with
+    /// [`ErrorCode::EARLY_DATA_REJECTED`].
+    ///
+    /// This corresponds to [`SSL_set_early_data_enabled`].
+    ///
+    /// [`SSL_set_early_data_enabled`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_early_data_enabled
+    pub fn set_early_data_enabled(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_set_early_data_enabled(self.as_ptr(), enabled as _) }
+    }
+
+    /// Determines if the client is sending early data, the server not having responded yet.
+    ///
+    /// This corresponds to [`SSL_in_early_data`].
+    ///
+    /// [`SSL_in_early_data`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_in_early_data
+    pub fn in_early_data(&self) -> bool {
+        unsafe { ffi::SSL_in_early_data(self.as_ptr()) != 0 }
+    }
+
+    /// Determines if early data was accepted by the server.
+    ///
+    /// This corresponds to [`SSL_early_data_accepted`].
+    ///
+    /// [`SSL_early_data_accepted`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_early_data_accepted
+    pub fn early_data_accepted(&self) -> bool {
+        unsafe { ffi::SSL_early_data_accepted(self.as_ptr()) != 0 }
+    }
+
+    /// Resets the connection after the server rejected early data, so that the handshake can
+    /// continue.
+    ///
+    /// This must be called after an operation failed with [`ErrorCode::EARLY_DATA_REJECTED`].
+    /// The rejected data must then be written again, once the handshake completed.
+    ///
+    /// This corresponds to [`SSL_reset_early_data_reject`].
+    ///
+    /// [`SSL_reset_early_data_reject`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_reset_early_data_reject
+    pub fn reset_early_data_reject(&mut self) {
+        unsafe { ffi::SSL_reset_early_data_reject(self.as_ptr()) }
+    }
+
+    /// Returns whether a resumed TLS 1.3 connection performed a key exchange along with its
+    /// pre-shared key, with the `psk_dhe_ke` mode, for forward secrecy.
+    ///
+    /// BoringSSL only offers and accepts `psk_dhe_ke`, so this is `false` only for connections
+    /// which are not resumed TLS 1.3 connections, or whose peer is not BoringSSL and did not
+    /// negotiate a key exchange group.
+    ///
+    /// This corresponds to [`SSL_session_reused`] and [`SSL_get_curve_id`].
+    ///
+    /// [`SSL_session_reused`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_session_reused
+    /// [`SSL_get_curve_id`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_curve_id
+    pub fn resumed_with_psk_dhe(&self) -> bool {
+        self.session_reused()
+            && self.version2() == Some(SslVersion::TLS1_3)
+            && unsafe { ffi::SSL_get_curve_id(self.as_ptr()) } != 0
+    }
+
+    /// Returns the difference, in seconds, between the age of the ticket reported by the client
+    /// of a resumed TLS 1.3 connection and the age computed by the server.
+    ///
+    /// A large skew points at clocks out of sync, or at tickets issued by a server with a
+    /// different notion of time, such as one in another datacenter. It is only meaningful on the
+    /// server side.
+    ///
+    /// This corresponds to [`SSL_get_ticket_age_skew`].
+    ///
+    /// [`SSL_get_ticket_age_skew`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_ticket_age_skew
+    pub fn ticket_age_skew(&self) -> i32 {
+        unsafe { ffi::SSL_get_ticket_age_skew(self.as_ptr()) }
+    }
+
+    /// Sets how the client responds to the server's renegotiation requests.
+    ///
+    /// This has no effect on servers, which always reject renegotiation.
+    ///
+    /// This corresponds to [`SSL_set_renegotiate_mode`].
+    ///
+    /// [`SSL_set_renegotiate_mode`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_renegotiate_mode
+    pub fn set_renegotiate_mode(&mut self, mode: SslRenegotiateMode) {
+        unsafe { ffi::SSL_set_renegotiate_mode(self.as_ptr(), mode.0) }
+    }
+
+    /// Performs a renegotiation requested by the server.
+    ///
+    /// This may only be called with [`SslRenegotiateMode::EXPLICIT`], after a read failed with
+    /// `ErrorCode::WANT_RENEGOTIATE`.
+    ///
+    /// This corresponds to [`SSL_renegotiate`].
+    ///
+    /// [`SSL_renegotiate`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_renegotiate
+    pub fn renegotiate(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_renegotiate(self.as_ptr())).map(|_| ()) }
+    }
+
+    /// Determines if a renegotiation is in progress.
+    ///
+    /// This corresponds to [`SSL_renegotiate_pending`].
+    ///
+    /// [`SSL_renegotiate_pending`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_renegotiate_pending
+    pub fn renegotiate_pending(&self) -> bool {
+        unsafe { ffi::SSL_renegotiate_pending(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the number of renegotiations completed on the connection.
+    ///
+    /// This corresponds to [`SSL_total_renegotiations`].
+    ///
+    /// [`SSL_total_renegotiations`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_total_renegotiations
+    pub fn total_renegotiations(&self) -> u32 {
+        unsafe { ffi::SSL_total_renegotiations(self.as_ptr()) as u32 }
+    }
+
+    /// Queues a TLS 1.3 `KeyUpdate` message, rotating the keys used to send data.
+    ///
+    /// The message is sent with the next write, or flushed by writing an empty buffer. Long-lived
+    /// connections can call this periodically to limit the amount of data protected by a single
+    /// key. Fails if the connection does not use TLS 1.3 or the handshake is not complete.
+    ///
+    /// This corresponds to [`SSL_key_update`].
+    ///
+    /// [`SSL_key_update`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_key_update
+    pub fn key_update(&mut self, request: SslKeyUpdate) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_key_update(self.as_ptr(), request.0)).map(|_| ()) }
+    }
+
     /// Sets the status response a client wishes the server to reply with.
     ///
     /// This corresponds to [`SSL_set_tlsext_status_type`].
@@ -3126,6 +4917,49 @@ impl SslRef {
         }
     }
 
+    /// Returns the signed certificate timestamp list sent by the server, if present.
+    ///
+    /// The list is in the TLS `SignedCertificateTimestampList` format and can be parsed with
+    /// [`SignedCertificateTimestamp::parse_list`].
+    ///
+    /// This corresponds to [`SSL_get0_signed_cert_timestamp_list`].
+    ///
+    /// [`SignedCertificateTimestamp::parse_list`]: ../sct/struct.SignedCertificateTimestamp.html#method.parse_list
+    /// [`SSL_get0_signed_cert_timestamp_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get0_signed_cert_timestamp_list
+    pub fn signed_cert_timestamp_list(&self) -> Option<&[u8]> {
+        unsafe {
+            let mut p = ptr::null();
+            let mut len = 0;
+            ffi::SSL_get0_signed_cert_timestamp_list(self.as_ptr(), &mut p, &mut len);
+
+            if len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(p, len))
+            }
+        }
+    }
+
+    /// Sets the signed certificate timestamp list to be sent to the client.
+    ///
+    /// It can be set from the callback set with
+    /// [`SslContextBuilder::set_select_certificate_callback`], so that the list matches the
+    /// certificate chosen for the connection.
+    ///
+    /// This corresponds to [`SSL_set_signed_cert_timestamp_list`].
+    ///
+    /// [`SSL_set_signed_cert_timestamp_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_signed_cert_timestamp_list
+    pub fn set_signed_cert_timestamp_list(&mut self, list: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_set_signed_cert_timestamp_list(
+                self.as_ptr(),
+                list.as_ptr(),
+                list.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Returns the server's OCSP response, if present.
     ///
     /// This corresponds to [`SSL_get_tlsext_status_ocsp_resp`].
@@ -3146,6 +4980,11 @@ impl SslRef {
 
     /// Sets the OCSP response to be returned to the client.
     ///
+    /// Like [`SslRef::set_signed_cert_timestamp_list`], it can be set from the callback set with
+    /// [`SslContextBuilder::set_select_certificate_callback`], so that the response matches the
+    /// certificate chosen for the connection. The response is only sent to clients requesting
+    /// it.
+    ///
     /// This corresponds to [`SSL_set_tlsext_status_ocsp_resp`].
     ///
     /// [`SSL_set_tlsext_status_ocsp_resp`]: https://www.openssl.org/docs/man1.0.2/ssl/SSL_set_tlsext_status_type.html
@@ -3223,36 +5062,228 @@ impl SslRef {
         }
     }
 
+    /// Stores a value of type `T`, replacing any value of the same type stored before.
+    ///
+    /// Values are kept in the slot allocated for their type by [`TypedExData`], and are dropped
+    /// along with the `Ssl`.
+    pub fn set<T>(&mut self, data: T) -> Result<(), ErrorStack>
+    where
+        T: 'static + Sync + Send,
+    {
+        let index = TypedExData::index::<T>()?;
+        match self.ex_data_mut(index) {
+            Some(slot) => *slot = data,
+            None => self.set_ex_data(index, data),
+        }
+
+        Ok(())
+    }
+
+    /// Returns a reference to the value of type `T` stored with [`SslRef::set`].
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: 'static + Sync + Send,
+    {
+        self.ex_data(TypedExData::index::<T>().ok()?)
+    }
+
+    /// Returns a mutable reference to the value of type `T` stored with [`SslRef::set`].
+    pub fn get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static + Sync + Send,
+    {
+        self.ex_data_mut(TypedExData::index::<T>().ok()?)
+    }
+
+    /// Removes and returns the value of type `T` stored with [`SslRef::set`].
+    pub fn take<T>(&mut self) -> Option<T>
+    where
+        T: 'static + Sync + Send,
+    {
+        let index = TypedExData::index::<T>().ok()?;
+
+        unsafe {
+            let data = ffi::SSL_get_ex_data(self.as_ptr(), index.as_raw());
+            if data.is_null() {
+                return None;
+            }
+
+            ffi::SSL_set_ex_data(self.as_ptr(), index.as_raw(), ptr::null_mut());
+            Some(*Box::from_raw(data as *mut T))
+        }
+    }
+
     /// Copies the contents of the last Finished message sent to the peer into the provided buffer.
     ///
     /// The total size of the message is returned, so this can be used to determine the size of the
     /// buffer required.
     ///
-    /// This corresponds to `SSL_get_finished`.
-    pub fn finished(&self, buf: &mut [u8]) -> usize {
-        unsafe { ffi::SSL_get_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len()) }
+    /// This corresponds to `SSL_get_finished`.
+    pub fn finished(&self, buf: &mut [u8]) -> usize {
+        unsafe { ffi::SSL_get_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len()) }
+    }
+
+    /// Copies the contents of the last Finished message received from the peer into the provided
+    /// buffer.
+    ///
+    /// The total size of the message is returned, so this can be used to determine the size of the
+    /// buffer required.
+    ///
+    /// This corresponds to `SSL_get_peer_finished`.
+    pub fn peer_finished(&self, buf: &mut [u8]) -> usize {
+        unsafe {
+            ffi::SSL_get_peer_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len())
+        }
+    }
+
+    /// Returns the `tls-unique` channel binding defined in [RFC 5929], which is the first
+    /// Finished message of the most recent handshake.
+    ///
+    /// It binds application messages to the handshake of TLS 1.2 connections. It is not
+    /// defined for TLS 1.3, where [`SslRef::export_keying_material`] should be used instead, and
+    /// it is only secure for TLS 1.2 connections using the extended master secret extension, or
+    /// which did not resume a session.
+    ///
+    /// This corresponds to [`SSL_get_tls_unique`].
+    ///
+    /// [RFC 5929]: https://datatracker.ietf.org/doc/html/rfc5929
+    /// [`SSL_get_tls_unique`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_tls_unique
+    pub fn tls_unique(&self) -> Result<Vec<u8>, ErrorStack> {
+        // Finished messages are at most 12 bytes in TLS 1.2, and 36 bytes in SSL 3.0.
+        let mut buf = vec![0; 64];
+        let mut len = 0;
+
+        unsafe {
+            cvt(ffi::SSL_get_tls_unique(
+                self.as_ptr(),
+                buf.as_mut_ptr(),
+                &mut len,
+                buf.len(),
+            ))?;
+        }
+
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    /// Determines if the initial handshake has been completed.
+    ///
+    /// This corresponds to [`SSL_is_init_finished`].
+    ///
+    /// [`SSL_is_init_finished`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_is_init_finished.html
+    pub fn is_init_finished(&self) -> bool {
+        unsafe { ffi::SSL_is_init_finished(self.as_ptr()) != 0 }
+    }
+
+    /// Configures the session as the client side of a connection.
+    ///
+    /// This corresponds to [`SSL_set_connect_state`].
+    ///
+    /// [`SSL_set_connect_state`]: https://www.openssl.org/docs/manmaster/man3/SSL_set_connect_state.html
+    pub fn set_connect_state(&mut self) {
+        unsafe { ffi::SSL_set_connect_state(self.as_ptr()) }
+    }
+
+    /// Configures the session as the server side of a connection.
+    ///
+    /// This corresponds to [`SSL_set_accept_state`].
+    ///
+    /// [`SSL_set_accept_state`]: https://www.openssl.org/docs/manmaster/man3/SSL_set_accept_state.html
+    pub fn set_accept_state(&mut self) {
+        unsafe { ffi::SSL_set_accept_state(self.as_ptr()) }
+    }
+
+    /// Returns what the last operation on the session is blocked on, if anything.
+    ///
+    /// This corresponds to [`SSL_want`].
+    ///
+    /// [`SSL_want`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_want
+    pub fn want(&self) -> Option<HandshakeState> {
+        let code = unsafe { ErrorCode::from_raw(ffi::SSL_want(self.as_ptr())) };
+
+        HandshakeState::from_error_code(code)
+    }
+
+    /// Advances the handshake as far as possible without blocking.
+    ///
+    /// This lets event loops drive the handshake without an [`SslStream`], e.g. when the session
+    /// reads and writes through a BIO managed by the caller. The side of the connection must have
+    /// been configured with [`SslRef::set_connect_state`] or [`SslRef::set_accept_state`]. Once
+    /// the event the returned state waits for occurred, e.g. the transport became readable or an
+    /// asynchronous callback completed, call this method again until it returns
+    /// [`HandshakeState::Complete`].
+    ///
+    /// Fatal errors, including the peer closing the connection, are returned as `Err`.
+    ///
+    /// This corresponds to [`SSL_do_handshake`] and [`SSL_get_error`].
+    ///
+    /// [`SSL_do_handshake`]: https://www.openssl.org/docs/manmaster/man3/SSL_do_handshake.html
+    /// [`SSL_get_error`]: https://www.openssl.org/docs/manmaster/man3/SSL_get_error.html
+    pub fn do_handshake_step(&mut self) -> Result<HandshakeState, Error> {
+        let ret = unsafe { ffi::SSL_do_handshake(self.as_ptr()) };
+        if ret > 0 {
+            return Ok(HandshakeState::Complete);
+        }
+
+        let code = self.get_error(ret);
+
+        if let Some(state) = HandshakeState::from_error_code(code) {
+            return Ok(state);
+        }
+
+        let errors = ErrorStack::get();
+        let cause = if errors.errors().is_empty() {
+            None
+        } else {
+            Some(InnerError::Ssl(errors))
+        };
+
+        Err(Error { code, cause })
+    }
+
+    /// Determines if the client is in a False Start.
+    ///
+    /// The connection can be written to, but the handshake has not completed yet.
+    ///
+    /// This corresponds to [`SSL_in_false_start`].
+    ///
+    /// [`SSL_in_false_start`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_in_false_start
+    pub fn in_false_start(&self) -> bool {
+        unsafe { ffi::SSL_in_false_start(self.as_ptr()) != 0 }
     }
 
-    /// Copies the contents of the last Finished message received from the peer into the provided
-    /// buffer.
+    /// Sets the maximum size of the records sent. Values outside of 512 to 16384 bytes are
+    /// clamped to that range.
     ///
-    /// The total size of the message is returned, so this can be used to determine the size of the
-    /// buffer required.
+    /// This corresponds to [`SSL_set_max_send_fragment`].
     ///
-    /// This corresponds to `SSL_get_peer_finished`.
-    pub fn peer_finished(&self, buf: &mut [u8]) -> usize {
+    /// [`SSL_set_max_send_fragment`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_max_send_fragment
+    pub fn set_max_send_fragment(&mut self, max_send_fragment: usize) -> Result<(), ErrorStack> {
         unsafe {
-            ffi::SSL_get_peer_finished(self.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len())
+            cvt(ffi::SSL_set_max_send_fragment(
+                self.as_ptr(),
+                max_send_fragment,
+            ))
+            .map(|_| ())
         }
     }
 
-    /// Determines if the initial handshake has been completed.
+    /// Sets the maximum size of a handshake message the peer may send.
     ///
-    /// This corresponds to [`SSL_is_init_finished`].
+    /// This corresponds to [`SSL_set_max_cert_list`].
     ///
-    /// [`SSL_is_init_finished`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_is_init_finished.html
-    pub fn is_init_finished(&self) -> bool {
-        unsafe { ffi::SSL_is_init_finished(self.as_ptr()) != 0 }
+    /// [`SSL_set_max_cert_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_max_cert_list
+    pub fn set_max_cert_list(&mut self, max_cert_list: usize) {
+        unsafe { ffi::SSL_set_max_cert_list(self.as_ptr(), max_cert_list) }
+    }
+
+    /// Returns the maximum size of a handshake message the peer may send.
+    ///
+    /// This corresponds to [`SSL_get_max_cert_list`].
+    ///
+    /// [`SSL_get_max_cert_list`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_get_max_cert_list
+    pub fn max_cert_list(&self) -> usize {
+        unsafe { ffi::SSL_get_max_cert_list(self.as_ptr()) }
     }
 
     /// Sets the MTU used for DTLS connections.
@@ -3262,6 +5293,54 @@ impl SslRef {
         unsafe { cvt(ffi::SSL_set_mtu(self.as_ptr(), mtu as c_uint) as c_int).map(|_| ()) }
     }
 
+    /// Sets the initial duration of the DTLS retransmission timer.
+    ///
+    /// The timer doubles on each retransmission. The default is one second.
+    ///
+    /// This corresponds to [`DTLSv1_set_initial_timeout_duration`].
+    ///
+    /// [`DTLSv1_set_initial_timeout_duration`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#DTLSv1_set_initial_timeout_duration
+    pub fn set_dtls_initial_timeout(&mut self, timeout: Duration) {
+        let millis = timeout.as_millis().try_into().unwrap_or(c_uint::MAX);
+
+        unsafe { ffi::DTLSv1_set_initial_timeout_duration(self.as_ptr(), millis) }
+    }
+
+    /// Returns the time left until the DTLS retransmission timer expires, or `None` if it is not
+    /// running.
+    ///
+    /// Once it expires, [`SslRef::dtls_handle_timeout`] must be called.
+    ///
+    /// This corresponds to [`DTLSv1_get_timeout`].
+    ///
+    /// [`SslRef::dtls_handle_timeout`]: struct.SslRef.html#method.dtls_handle_timeout
+    /// [`DTLSv1_get_timeout`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#DTLSv1_get_timeout
+    pub fn dtls_timeout(&self) -> Option<Duration> {
+        unsafe {
+            let mut timeout = mem::zeroed::<ffi::timeval>();
+            if ffi::DTLSv1_get_timeout(self.as_ptr(), &mut timeout) == 0 {
+                return None;
+            }
+
+            Some(Duration::new(
+                timeout.tv_sec as u64,
+                timeout.tv_usec as u32 * 1000,
+            ))
+        }
+    }
+
+    /// Handles the expiry of the DTLS retransmission timer by retransmitting the last flight.
+    ///
+    /// Returns `false` if the timer had not expired. An error is returned if too many
+    /// retransmissions happened without progress, or if the retransmission failed.
+    ///
+    /// This corresponds to [`DTLSv1_handle_timeout`].
+    ///
+    /// [`DTLSv1_handle_timeout`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#DTLSv1_handle_timeout
+    pub fn dtls_handle_timeout(&mut self) -> Result<bool, ErrorStack> {
+        unsafe { cvt_n(ffi::DTLSv1_handle_timeout(self.as_ptr())).map(|r| r > 0) }
+    }
+
     /// Sets the certificate.
     ///
     /// This corresponds to [`SSL_use_certificate`].
@@ -3274,6 +5353,50 @@ impl SslRef {
 
         Ok(())
     }
+
+    /// Adds a certificate to the chain sent along with the certificate of the connection.
+    ///
+    /// This corresponds to [`SSL_add1_chain_cert`].
+    ///
+    /// [`SSL_add1_chain_cert`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_add1_chain_cert
+    pub fn add_chain_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_add1_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int).map(|_| ()) }
+    }
+
+    /// Sets the private key.
+    ///
+    /// This corresponds to [`SSL_use_PrivateKey`].
+    ///
+    /// [`SSL_use_PrivateKey`]: https://www.openssl.org/docs/man1.1.1/man3/SSL_use_PrivateKey.html
+    pub fn set_private_key<T>(&mut self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { cvt(ffi::SSL_use_PrivateKey(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Like [`SslContextBuilder::set_private_key_method`].
+    ///
+    /// This corresponds to [`SSL_set_private_key_method`].
+    ///
+    /// [`SSL_set_private_key_method`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_set_private_key_method
+    pub fn set_private_key_method<M>(&mut self, method: M)
+    where
+        M: PrivateKeyMethod,
+    {
+        unsafe {
+            self.set_ex_data(Ssl::cached_ex_index::<Arc<M>>(), Arc::new(method));
+
+            ffi::SSL_set_private_key_method(
+                self.as_ptr(),
+                &ffi::SSL_PRIVATE_KEY_METHOD {
+                    sign: Some(callbacks::ssl_raw_sign::<M>),
+                    decrypt: Some(callbacks::ssl_raw_decrypt::<M>),
+                    complete: Some(callbacks::ssl_raw_complete::<M>),
+                },
+            )
+        }
+    }
 }
 
 /// An SSL stream midway through the handshake process.
@@ -3332,7 +5455,13 @@ impl<S> MidHandshakeSslStream<S> {
     pub fn handshake(mut self) -> Result<SslStream<S>, HandshakeError<S>> {
         let ret = unsafe { ffi::SSL_do_handshake(self.stream.ssl.as_ptr()) };
         if ret > 0 {
-            Ok(self.stream)
+            match self.stream.check_handshake() {
+                Ok(()) => Ok(self.stream),
+                Err(error) => {
+                    self.error = error;
+                    Err(HandshakeError::Failure(self))
+                }
+            }
         } else {
             self.error = self.stream.make_error(ret);
             match self.error.would_block() {
@@ -3433,6 +5562,50 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Like `ssl_read`, but does not consume the data that is read.
+    ///
+    /// A subsequent `ssl_read` or `ssl_peek` returns the same data.
+    ///
+    /// This corresponds to [`SSL_peek`].
+    ///
+    /// [`SSL_peek`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_peek
+    pub fn ssl_peek(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        // See `ssl_read` for why we short-circuit on zero-length buffers
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let ret = self.ssl.peek(buf);
+        if ret > 0 {
+            Ok(ret as usize)
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
+    /// Reads data without consuming it, returning `Ok(0)` at the end of the session.
+    ///
+    /// This is the `io::Read` counterpart of [`SslStream::ssl_peek`]. It lets a caller inspect
+    /// the beginning of the application data, e.g. to detect the protocol spoken over TLS, before
+    /// handing the stream to the code that consumes it.
+    pub fn peek(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.ssl_peek(buf) {
+                Ok(n) => return Ok(n),
+                Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => return Ok(0),
+                Err(ref e) if e.code() == ErrorCode::SYSCALL && e.io_error().is_none() => {
+                    return Ok(0);
+                }
+                Err(ref e) if e.code() == ErrorCode::WANT_READ && e.io_error().is_none() => {}
+                Err(e) => {
+                    return Err(e
+                        .into_io_error()
+                        .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e)));
+                }
+            }
+        }
+    }
+
     /// Like `write`, but returns an `ssl::Error` rather than an `io::Error`.
     ///
     /// It is particularly useful with a nonblocking socket, where the error value will identify if
@@ -3455,6 +5628,33 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Writes the TLS records generated by BoringSSL but not written to the underlying stream yet.
+    ///
+    /// These are records whose write would have blocked, handshake messages generated while
+    /// reading, or a `KeyUpdate` message queued with [`SslRef::key_update`]. Unlike
+    /// [`Write::flush`], this does not flush the underlying stream itself.
+    ///
+    /// After an [`ssl_write`] failed with [`ErrorCode::WANT_WRITE`], that call must be retried
+    /// first.
+    ///
+    /// This corresponds to [`SSL_write`] with an empty buffer.
+    ///
+    /// [`ssl_write`]: SslStream::ssl_write
+    /// [`SSL_write`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#SSL_write
+    pub fn flush_records(&mut self) -> Result<(), Error> {
+        // Nothing can be written once a close_notify alert was sent.
+        if self.get_shutdown().contains(ShutdownState::SENT) {
+            return Ok(());
+        }
+
+        let ret = unsafe { ffi::SSL_write(self.ssl.as_ptr(), ptr::null(), 0) };
+        if ret >= 0 {
+            Ok(())
+        } else {
+            Err(self.make_error(ret))
+        }
+    }
+
     /// Shuts down the session.
     ///
     /// The shutdown process consists of two steps. The first step sends a close notify message to
@@ -3508,7 +5708,7 @@ impl<S: Read + Write> SslStream<S> {
     pub fn connect(&mut self) -> Result<(), Error> {
         let ret = unsafe { ffi::SSL_connect(self.ssl.as_ptr()) };
         if ret > 0 {
-            Ok(())
+            self.check_handshake()
         } else {
             Err(self.make_error(ret))
         }
@@ -3536,7 +5736,7 @@ impl<S: Read + Write> SslStream<S> {
     pub fn do_handshake(&mut self) -> Result<(), Error> {
         let ret = unsafe { ffi::SSL_do_handshake(self.ssl.as_ptr()) };
         if ret > 0 {
-            Ok(())
+            self.check_handshake()
         } else {
             Err(self.make_error(ret))
         }
@@ -3544,6 +5744,15 @@ impl<S: Read + Write> SslStream<S> {
 }
 
 impl<S> SslStream<S> {
+    /// Runs the checks made once the handshake completes, such as the one enabled with
+    /// [`ConnectConfiguration::set_require_alpn`].
+    fn check_handshake(&self) -> Result<(), Error> {
+        connector::check_alpn(&self.ssl).map_err(|e| Error {
+            code: ErrorCode::SSL,
+            cause: Some(InnerError::Ssl(e)),
+        })
+    }
+
     fn make_error(&mut self, ret: c_int) -> Error {
         self.check_panic();
 
@@ -3584,6 +5793,36 @@ impl<S> SslStream<S> {
         unsafe { bio::take_stream::<S>(self.ssl.get_raw_rbio()) }
     }
 
+    /// Converts the SslStream to the underlying data stream, along with the application data
+    /// which was decrypted but not read yet.
+    ///
+    /// BoringSSL does not read past the end of the TLS record it is processing, so the bytes
+    /// the peer sent after its last record, e.g. after its `close_notify` alert, are still
+    /// unread in the underlying stream. This allows handing the stream over to another protocol.
+    pub fn into_parts(mut self) -> (S, Vec<u8>) {
+        let mut buffered = Vec::new();
+
+        loop {
+            let pending = self.ssl.pending();
+            if pending == 0 {
+                break;
+            }
+
+            let start = buffered.len();
+            buffered.resize(start + pending, 0);
+
+            // Pending data is read without touching the underlying stream.
+            let ret = self.ssl.read(&mut buffered[start..]);
+            if ret <= 0 {
+                buffered.truncate(start);
+                break;
+            }
+            buffered.truncate(start + ret as usize);
+        }
+
+        (self.into_inner(), buffered)
+    }
+
     /// Returns a shared reference to the underlying stream.
     pub fn get_ref(&self) -> &S {
         unsafe {
@@ -3614,6 +5853,14 @@ impl<S> SslStream<S> {
     pub fn ssl_mut(&mut self) -> &mut SslRef {
         &mut self.ssl
     }
+
+    /// Returns the number of decrypted bytes that can be read without reading from the
+    /// underlying stream.
+    ///
+    /// See [`SslRef::pending`].
+    pub fn pending(&self) -> usize {
+        self.ssl.pending()
+    }
 }
 
 impl<S: Read + Write> Read for SslStream<S> {
@@ -3762,11 +6009,15 @@ where
     pub fn handshake(self) -> Result<SslStream<S>, HandshakeError<S>> {
         let mut stream = self.inner;
         let ret = unsafe { ffi::SSL_do_handshake(stream.ssl.as_ptr()) };
-        if ret > 0 {
-            Ok(stream)
+        let result = if ret > 0 {
+            stream.check_handshake()
         } else {
-            let error = stream.make_error(ret);
-            match error.would_block() {
+            Err(stream.make_error(ret))
+        };
+
+        match result {
+            Ok(()) => Ok(stream),
+            Err(error) => match error.would_block() {
                 true => Err(HandshakeError::WouldBlock(MidHandshakeSslStream {
                     stream,
                     error,
@@ -3775,7 +6026,7 @@ where
                     stream,
                     error,
                 })),
-            }
+            },
         }
     }
 }
@@ -3807,6 +6058,13 @@ impl<S> SslStreamBuilder<S> {
         &self.inner.ssl
     }
 
+    /// Sets the read-ahead flag of the stream.
+    ///
+    /// See [`SslRef::set_read_ahead`].
+    pub fn set_read_ahead(&mut self, read_ahead: bool) {
+        self.inner.ssl.set_read_ahead(read_ahead);
+    }
+
     /// Set the DTLS MTU size.
     ///
     /// It will be ignored if the value is smaller than the minimum packet size
@@ -3823,6 +6081,54 @@ impl<S> SslStreamBuilder<S> {
     }
 }
 
+/// The progress of a handshake driven with [`SslRef::do_handshake_step`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HandshakeState {
+    /// The handshake is complete.
+    Complete,
+
+    /// The handshake is blocked until the transport is readable.
+    WantRead,
+
+    /// The handshake is blocked until the transport is writable.
+    WantWrite,
+
+    /// The handshake is blocked on a certificate lookup.
+    WantX509Lookup,
+
+    /// The handshake is blocked on a session lookup.
+    PendingSession,
+
+    /// The handshake is blocked on the certificate selection callback.
+    PendingCertificate,
+
+    /// The handshake is blocked on a private key operation.
+    WantPrivateKeyOperation,
+
+    /// The handshake is blocked on a session ticket decryption.
+    PendingTicket,
+
+    /// The handshake is blocked on a custom certificate verification callback.
+    WantCertificateVerify,
+}
+
+impl HandshakeState {
+    fn from_error_code(code: ErrorCode) -> Option<HandshakeState> {
+        match code {
+            ErrorCode::WANT_READ => Some(HandshakeState::WantRead),
+            ErrorCode::WANT_WRITE => Some(HandshakeState::WantWrite),
+            ErrorCode::WANT_X509_LOOKUP => Some(HandshakeState::WantX509Lookup),
+            ErrorCode::PENDING_SESSION => Some(HandshakeState::PendingSession),
+            ErrorCode::PENDING_CERTIFICATE => Some(HandshakeState::PendingCertificate),
+            ErrorCode::WANT_PRIVATE_KEY_OPERATION => Some(HandshakeState::WantPrivateKeyOperation),
+            ErrorCode::PENDING_TICKET => Some(HandshakeState::PendingTicket),
+            ErrorCode::WANT_CERTIFICATE_VERIFY => Some(HandshakeState::WantCertificateVerify),
+            _ => None,
+        }
+    }
+}
+
 /// The result of a shutdown request.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ShutdownResult {
@@ -3904,15 +6210,278 @@ pub trait PrivateKeyMethod: Send + Sync + 'static {
 }
 
 /// An error returned from a private key method.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct PrivateKeyMethodError(ffi::ssl_private_key_result_t);
+///
+/// A fatal error may carry a reason, such as an unsupported signature algorithm, an unavailable
+/// key or a timed out backend. It is pushed onto the error stack as the data of a
+/// `PRIVATE_KEY_OPERATION_FAILED` error, so that it is part of the [`Error`] the handshake fails
+/// with, rather than lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateKeyMethodError {
+    result: ffi::ssl_private_key_result_t,
+    reason: Option<Cow<'static, str>>,
+}
 
 impl PrivateKeyMethodError {
     /// A fatal error occured and the handshake should be terminated.
-    pub const FAILURE: Self = Self(ffi::ssl_private_key_result_t::ssl_private_key_failure);
+    pub const FAILURE: Self = Self {
+        result: ffi::ssl_private_key_result_t::ssl_private_key_failure,
+        reason: None,
+    };
 
     /// The operation could not be completed and should be retried later.
-    pub const RETRY: Self = Self(ffi::ssl_private_key_result_t::ssl_private_key_retry);
+    pub const RETRY: Self = Self {
+        result: ffi::ssl_private_key_result_t::ssl_private_key_retry,
+        reason: None,
+    };
+
+    /// Returns a fatal error terminating the handshake, for the given reason.
+    pub fn failure(reason: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            reason: Some(reason.into()),
+            ..Self::FAILURE
+        }
+    }
+
+    /// Returns `true` if the operation should be retried later.
+    pub fn is_retry(&self) -> bool {
+        self.result == ffi::ssl_private_key_result_t::ssl_private_key_retry
+    }
+
+    /// Returns the reason of a fatal error, if one was given.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// Pushes the reason, if any, onto the error stack, and returns the raw result.
+    fn into_raw(self) -> ffi::ssl_private_key_result_t {
+        if let Some(reason) = &self.reason {
+            // NOTE: the data is copied by `ERR_add_error_data`.
+            let data = CString::new(reason.replace('\0', "")).unwrap();
+
+            unsafe {
+                ffi::ERR_put_error(
+                    ffi::ERR_LIB_SSL.0 as _,
+                    0,
+                    ffi::SSL_R_PRIVATE_KEY_OPERATION_FAILED as _,
+                    concat!(file!(), "\0").as_ptr() as *const _,
+                    line!() as _,
+                );
+                ffi::ERR_add_error_data(1, data.as_ptr());
+            }
+        }
+
+        self.result
+    }
+}
+
+impl fmt::Display for PrivateKeyMethodError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match (self.is_retry(), &self.reason) {
+            (true, _) => fmt.write_str("the private key operation should be retried"),
+            (false, Some(reason)) => write!(fmt, "the private key operation failed: {}", reason),
+            (false, None) => fmt.write_str("the private key operation failed"),
+        }
+    }
+}
+
+impl std::error::Error for PrivateKeyMethodError {}
+
+type PendingOperationResult = Result<Vec<u8>, PrivateKeyMethodError>;
+
+/// A private key operation running outside of the handshake.
+///
+/// This lets a [`PrivateKeyMethod`] hand the actual signing or decryption off to another thread
+/// or to a remote service without depending on a particular async runtime:
+///
+/// 1. [`PrivateKeyMethod::sign`] or [`PrivateKeyMethod::decrypt`] starts the operation, attaches
+///    a `PendingOperation` to the connection with [`SslRef::set_pending_private_key_operation`],
+///    and returns [`PrivateKeyMethodError::RETRY`]. The handshake then fails with
+///    [`ErrorCode::WANT_PRIVATE_KEY_OPERATION`].
+/// 2. Whoever performs the operation calls [`PendingOperation::complete`] with its output, which
+///    runs the notification registered with [`PendingOperation::on_complete`]. An event loop
+///    typically uses it to wake itself up, e.g. by writing to an eventfd or a pipe.
+/// 3. The event loop retries the handshake, which calls [`PrivateKeyMethod::complete`], which
+///    should simply return [`SslRef::private_key_operation_complete`].
+///
+/// [`ErrorCode::WANT_PRIVATE_KEY_OPERATION`]: struct.ErrorCode.html#associatedconstant.WANT_PRIVATE_KEY_OPERATION
+#[derive(Clone, Default)]
+pub struct PendingOperation(Arc<PendingOperationInner>);
+
+#[derive(Default)]
+struct PendingOperationInner {
+    result: Mutex<Option<PendingOperationResult>>,
+    #[allow(clippy::type_complexity)]
+    notify: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl PendingOperation {
+    /// Creates a new pending operation.
+    pub fn new() -> PendingOperation {
+        PendingOperation::default()
+    }
+
+    /// Completes the operation with the given output, or error.
+    ///
+    /// Completing an operation more than once has no effect.
+    pub fn complete(&self, result: Result<Vec<u8>, PrivateKeyMethodError>) {
+        {
+            let mut slot = self.0.result.lock().unwrap();
+            if slot.is_some() {
+                return;
+            }
+            *slot = Some(result);
+        }
+
+        if let Some(notify) = self.0.notify.lock().unwrap().take() {
+            notify();
+        }
+    }
+
+    /// Returns whether the operation has completed.
+    pub fn is_complete(&self) -> bool {
+        self.0.result.lock().unwrap().is_some()
+    }
+
+    /// Registers a function called once the operation completes.
+    ///
+    /// If the operation has already completed, `notify` is called immediately.
+    pub fn on_complete<F>(&self, notify: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        *self.0.notify.lock().unwrap() = Some(Box::new(notify));
+
+        if self.is_complete() {
+            if let Some(notify) = self.0.notify.lock().unwrap().take() {
+                notify();
+            }
+        }
+    }
+
+    fn take_result(&self) -> Option<PendingOperationResult> {
+        self.0.result.lock().unwrap().take()
+    }
+}
+
+/// A token resuming a handshake suspended with [`SslRef::suspend_handshake`].
+///
+/// This lets a policy engine running outside of the handshake, e.g. on another thread or behind
+/// a DNS lookup, decide whether a handshake may proceed once the ClientHello was received,
+/// without depending on a particular async runtime:
+///
+/// 1. The certificate selection callback calls [`SslRef::handshake_resumption`], and returns
+///    its outcome if there is one. Otherwise, it suspends the handshake with
+///    [`SslRef::suspend_handshake`], hands the token to the policy engine, and returns
+///    [`SelectCertError::RETRY`]. The handshake then fails with
+///    [`ErrorCode::PENDING_CERTIFICATE`].
+/// 2. The policy engine calls [`ResumeToken::resume`] or [`ResumeToken::abort`], which runs
+///    the notification registered with [`ResumeToken::on_decided`].
+/// 3. The event loop retries the handshake, which calls the certificate selection callback
+///    again, where [`SslRef::handshake_resumption`] now returns the decision.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{NameType, SelectCertError, SslAcceptor, SslMethod};
+/// use std::thread;
+///
+/// # fn allowed(_: &str) -> bool { true }
+/// let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+/// acceptor.set_select_certificate_callback(|mut client_hello| {
+///     if let Some(result) = client_hello.ssl_mut().handshake_resumption() {
+///         return result;
+///     }
+///
+///     let name = client_hello
+///         .servername(NameType::HOST_NAME)
+///         .ok_or(SelectCertError::ERROR)?
+///         .to_owned();
+///     let token = client_hello.ssl_mut().suspend_handshake();
+///
+///     thread::spawn(move || {
+///         if allowed(&name) {
+///             token.resume();
+///         } else {
+///             token.abort();
+///         }
+///     });
+///
+///     Err(SelectCertError::RETRY)
+/// });
+/// ```
+///
+/// [`ErrorCode::PENDING_CERTIFICATE`]: struct.ErrorCode.html#associatedconstant.PENDING_CERTIFICATE
+#[derive(Clone, Default)]
+pub struct ResumeToken(Arc<ResumeTokenInner>);
+
+#[derive(Default)]
+struct ResumeTokenInner {
+    decision: Mutex<Option<bool>>,
+    #[allow(clippy::type_complexity)]
+    notify: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl ResumeToken {
+    /// Lets the handshake proceed.
+    ///
+    /// Deciding more than once has no effect.
+    pub fn resume(&self) {
+        self.decide(true);
+    }
+
+    /// Makes the handshake fail.
+    ///
+    /// Deciding more than once has no effect.
+    pub fn abort(&self) {
+        self.decide(false);
+    }
+
+    /// Returns whether a decision was made.
+    pub fn is_decided(&self) -> bool {
+        self.decision().is_some()
+    }
+
+    /// Registers a function called once a decision is made.
+    ///
+    /// If a decision was already made, `notify` is called immediately.
+    pub fn on_decided<F>(&self, notify: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        *self.0.notify.lock().unwrap() = Some(Box::new(notify));
+
+        if self.is_decided() {
+            if let Some(notify) = self.0.notify.lock().unwrap().take() {
+                notify();
+            }
+        }
+    }
+
+    fn decide(&self, resume: bool) {
+        {
+            let mut decision = self.0.decision.lock().unwrap();
+            if decision.is_some() {
+                return;
+            }
+            *decision = Some(resume);
+        }
+
+        if let Some(notify) = self.0.notify.lock().unwrap().take() {
+            notify();
+        }
+    }
+
+    fn decision(&self) -> Option<bool> {
+        *self.0.decision.lock().unwrap()
+    }
+}
+
+impl fmt::Debug for ResumeToken {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ResumeToken")
+            .field("decision", &self.decision())
+            .finish()
+    }
 }
 
 use crate::ffi::{SSL_CTX_up_ref, SSL_SESSION_get_master_key, SSL_SESSION_up_ref, SSL_is_server};
@@ -0,0 +1,254 @@
+use std::ptr;
+use std::slice;
+use std::time::{Duration, Instant};
+
+use crate::error::ErrorStack;
+use crate::ffi;
+use crate::ssl::{SslOptions, SslRef, SslSessionCacheMode, SslSignatureAlgorithm, SslVersion};
+use crate::stack::StackRef;
+use crate::x509::X509Name;
+use foreign_types::ForeignTypeRef;
+
+/// How a resumed session was found.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResumptionMechanism {
+    /// The session was resumed from a session ticket, as always in TLS 1.3.
+    Ticket,
+    /// The session was resumed from a session cache, by session ID.
+    SessionId,
+    /// The session was resumed, but BoringSSL does not expose how.
+    ///
+    /// This is reported by TLS 1.2 servers accepting both tickets and session IDs.
+    Unknown,
+}
+
+/// Whether the client authenticated itself with a certificate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ClientAuthentication {
+    /// The server did not request a client certificate.
+    NotRequested,
+    /// The server requested a client certificate, but the client did not send one.
+    NoCertificate,
+    /// The client sent a certificate.
+    ///
+    /// For resumed sessions, the certificate was sent in the handshake which established the
+    /// session.
+    Certificate,
+}
+
+impl ClientAuthentication {
+    pub(super) fn new(ssl: &SslRef) -> ClientAuthentication {
+        let requested = ssl.certificate_requested();
+        let sent = if ssl.is_server() {
+            ssl.peer_certificate().is_some()
+        } else {
+            requested && ssl.certificate().is_some()
+        };
+
+        match (requested, sent) {
+            (_, true) => ClientAuthentication::Certificate,
+            (true, false) => ClientAuthentication::NoCertificate,
+            (false, false) => ClientAuthentication::NotRequested,
+        }
+    }
+}
+
+/// The parameters of a certificate request received by a client, returned by
+/// [`SslRef::certificate_request`].
+///
+/// BoringSSL does not implement TLS 1.3 post-handshake authentication, so a server can only
+/// request a certificate during the handshake, or during a TLS 1.2 renegotiation. In the latter
+/// case, this describes the most recent request.
+#[derive(Debug, Clone)]
+pub struct CertificateRequest {
+    ca_names: Vec<Vec<u8>>,
+    signature_algorithms: Vec<SslSignatureAlgorithm>,
+    certificate_types: Vec<u8>,
+}
+
+impl CertificateRequest {
+    pub(super) fn new(ssl: &SslRef) -> CertificateRequest {
+        let ca_names = unsafe {
+            let ptr = ffi::SSL_get_client_CA_list(ssl.as_ptr());
+            if ptr.is_null() {
+                vec![]
+            } else {
+                StackRef::<X509Name>::from_ptr(ptr)
+                    .iter()
+                    .filter_map(|name| name.to_der().ok())
+                    .collect()
+            }
+        };
+
+        let certificate_types = unsafe {
+            let mut ptr = ptr::null();
+            let len = ffi::SSL_get0_certificate_types(ssl.as_ptr(), &mut ptr);
+            if ptr.is_null() || len == 0 {
+                vec![]
+            } else {
+                slice::from_raw_parts(ptr, len).to_vec()
+            }
+        };
+
+        CertificateRequest {
+            ca_names,
+            signature_algorithms: ssl.peer_verify_algorithms().to_vec(),
+            certificate_types,
+        }
+    }
+
+    /// Returns the DER encodings of the names of the CAs accepted by the server.
+    ///
+    /// An empty list means the server accepts certificates from any CA.
+    pub fn ca_names_der(&self) -> impl ExactSizeIterator<Item = &[u8]> {
+        self.ca_names.iter().map(|name| &name[..])
+    }
+
+    /// Returns the names of the CAs accepted by the server.
+    ///
+    /// An empty list means the server accepts certificates from any CA.
+    pub fn ca_names(&self) -> Result<Vec<X509Name>, ErrorStack> {
+        self.ca_names
+            .iter()
+            .map(|name| X509Name::from_der(name))
+            .collect()
+    }
+
+    /// Returns the signature algorithms accepted by the server, in its order of preference.
+    pub fn signature_algorithms(&self) -> &[SslSignatureAlgorithm] {
+        &self.signature_algorithms
+    }
+
+    /// Returns the `ClientCertificateType` values accepted by the server.
+    ///
+    /// TLS 1.3 removed these from certificate requests, so this is empty for TLS 1.3
+    /// connections.
+    pub fn certificate_types(&self) -> &[u8] {
+        &self.certificate_types
+    }
+}
+
+/// A summary of a completed handshake, passed to the callback set with
+/// [`SslContextBuilder::set_handshake_outcome_callback`].
+///
+/// This lets a fleet export metrics such as its resumption ratio or the share of handshakes
+/// using a given key exchange group.
+///
+/// [`SslContextBuilder::set_handshake_outcome_callback`]: crate::ssl::SslContextBuilder::set_handshake_outcome_callback
+#[derive(Debug, Clone)]
+pub struct HandshakeOutcome {
+    is_server: bool,
+    version: Option<SslVersion>,
+    resumption: Option<ResumptionMechanism>,
+    early_data_accepted: bool,
+    group: Option<&'static str>,
+    peer_signature_algorithm: Option<SslSignatureAlgorithm>,
+    client_authentication: ClientAuthentication,
+    duration: Option<Duration>,
+}
+
+impl HandshakeOutcome {
+    pub(super) fn new(ssl: &SslRef, started: Option<Instant>) -> HandshakeOutcome {
+        let version = ssl.version2();
+
+        let resumption = if !ssl.session_reused() {
+            None
+        } else if version == Some(SslVersion::TLS1_3) {
+            Some(ResumptionMechanism::Ticket)
+        } else if !ssl.is_server() {
+            match ssl.session() {
+                Some(session) if session.has_ticket() => Some(ResumptionMechanism::Ticket),
+                _ => Some(ResumptionMechanism::SessionId),
+            }
+        } else {
+            server_resumption_mechanism(ssl)
+        };
+
+        HandshakeOutcome {
+            is_server: ssl.is_server(),
+            version,
+            resumption,
+            early_data_accepted: ssl.early_data_accepted(),
+            group: ssl.negotiated_group(),
+            peer_signature_algorithm: ssl.peer_signature_algorithm(),
+            client_authentication: ClientAuthentication::new(ssl),
+            duration: started.map(|started| started.elapsed()),
+        }
+    }
+
+    /// Returns `true` for the server side of the connection.
+    pub fn is_server(&self) -> bool {
+        self.is_server
+    }
+
+    /// Returns the negotiated protocol version.
+    pub fn version(&self) -> Option<SslVersion> {
+        self.version
+    }
+
+    /// Returns `true` if a previous session was resumed instead of performing a full handshake.
+    pub fn is_resumed(&self) -> bool {
+        self.resumption.is_some()
+    }
+
+    /// Returns how the session was resumed, or `None` for a full handshake.
+    pub fn resumption(&self) -> Option<ResumptionMechanism> {
+        self.resumption
+    }
+
+    /// Returns `true` if the server accepted early data sent by the client.
+    pub fn early_data_accepted(&self) -> bool {
+        self.early_data_accepted
+    }
+
+    /// Returns the name of the negotiated key exchange group, if any.
+    ///
+    /// See [`SslRef::negotiated_group`].
+    pub fn group(&self) -> Option<&'static str> {
+        self.group
+    }
+
+    /// Returns the signature algorithm used by the peer to sign the handshake, if any.
+    ///
+    /// See [`SslRef::peer_signature_algorithm`].
+    pub fn peer_signature_algorithm(&self) -> Option<SslSignatureAlgorithm> {
+        self.peer_signature_algorithm
+    }
+
+    /// Returns whether the client authenticated itself with a certificate.
+    ///
+    /// On the client side, requests are only seen with a certificate callback, see
+    /// [`SslRef::certificate_requested`].
+    pub fn client_authentication(&self) -> ClientAuthentication {
+        self.client_authentication
+    }
+
+    /// Returns the time elapsed since the handshake started.
+    ///
+    /// This includes the time spent waiting for the peer and for asynchronous callbacks. It is
+    /// `None` if the callback was set after the handshake started.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+}
+
+fn server_resumption_mechanism(ssl: &SslRef) -> Option<ResumptionMechanism> {
+    let ctx = ssl.ssl_context();
+    let (cache_mode, options) = unsafe {
+        (
+            SslSessionCacheMode::from_bits_retain(ffi::SSL_CTX_get_session_cache_mode(
+                ctx.as_ptr(),
+            )),
+            SslOptions::from_bits_retain(ffi::SSL_get_options(ssl.as_ptr())),
+        )
+    };
+    let tickets = !options.contains(SslOptions::NO_TICKET);
+
+    match (cache_mode.contains(SslSessionCacheMode::SERVER), tickets) {
+        (false, _) => Some(ResumptionMechanism::Ticket),
+        (true, false) => Some(ResumptionMechanism::SessionId),
+        (true, true) => Some(ResumptionMechanism::Unknown),
+    }
+}
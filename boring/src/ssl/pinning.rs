@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+use crate::base64;
+use crate::stack::StackRef;
+use crate::x509::{X509StoreContextRef, X509VerifyResult, X509};
+
+/// A set of public key pins.
+///
+/// A pin is the SHA-256 digest of the DER-encoded SubjectPublicKeyInfo of a certificate, as
+/// returned by [`X509Ref::spki_sha256`], and is usually written in base64 as in HPKP.
+///
+/// A certificate chain matches the set if any of its certificates has a pinned key. Pinning the
+/// key of an intermediate or root CA therefore allows the leaf certificate to be reissued without
+/// updating the pins.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{PinSet, SslConnector, SslMethod, SslVerifyMode};
+///
+/// let pins = PinSet::from_base64(["r/mIkG3eEpVdm+u/ko/cwxzOMo1bk4TyHIlByibiA5E="]).unwrap();
+///
+/// let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+/// connector.set_verify_callback(SslVerifyMode::PEER, move |preverify_ok, ctx| {
+///     pins.verify(preverify_ok, ctx)
+/// });
+/// ```
+///
+/// [`X509Ref::spki_sha256`]: crate::x509::X509Ref::spki_sha256
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinSet {
+    pins: HashSet<[u8; 32]>,
+}
+
+impl PinSet {
+    /// Creates an empty set.
+    ///
+    /// An empty set matches no chain.
+    pub fn new() -> PinSet {
+        PinSet::default()
+    }
+
+    /// Creates a set from base64-encoded pins.
+    pub fn from_base64<I, S>(pins: I) -> Result<PinSet, PinError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut set = PinSet::new();
+        for pin in pins {
+            set.insert_base64(pin.as_ref())?;
+        }
+        Ok(set)
+    }
+
+    /// Adds a pin.
+    pub fn insert(&mut self, pin: [u8; 32]) {
+        self.pins.insert(pin);
+    }
+
+    /// Adds a base64-encoded pin.
+    pub fn insert_base64(&mut self, pin: &str) -> Result<(), PinError> {
+        let pin = base64::decode_block(pin).map_err(|_| PinError(()))?;
+        let pin = pin.try_into().map_err(|_| PinError(()))?;
+        self.insert(pin);
+        Ok(())
+    }
+
+    /// Returns `true` if the set contains no pins.
+    pub fn is_empty(&self) -> bool {
+        self.pins.is_empty()
+    }
+
+    /// Returns `true` if a certificate of `chain` has a pinned key.
+    pub fn matches(&self, chain: &StackRef<X509>) -> bool {
+        chain.iter().any(|cert| {
+            cert.spki_sha256()
+                .is_some_and(|pin| self.pins.contains(&*pin))
+        })
+    }
+
+    /// Checks the chain being verified against the set.
+    ///
+    /// This is meant to be called from a callback installed with
+    /// [`SslContextBuilder::set_verify_callback`] or [`SslRef::set_verify_callback`], forwarding
+    /// its arguments. The chain is only checked once it has been built and verified, when the
+    /// callback is invoked for the leaf certificate; the handshake fails with
+    /// [`X509VerifyResult::APPLICATION_VERIFICATION`] if no pin matches.
+    ///
+    /// [`SslContextBuilder::set_verify_callback`]: crate::ssl::SslContextBuilder::set_verify_callback
+    /// [`SslRef::set_verify_callback`]: crate::ssl::SslRef::set_verify_callback
+    pub fn verify(&self, preverify_ok: bool, ctx: &mut X509StoreContextRef) -> bool {
+        if !preverify_ok || ctx.error_depth() != 0 {
+            return preverify_ok;
+        }
+
+        if ctx.chain().is_some_and(|chain| self.matches(chain)) {
+            return true;
+        }
+
+        ctx.set_error(X509VerifyResult::APPLICATION_VERIFICATION);
+        false
+    }
+}
+
+/// An error parsing a public key pin.
+#[derive(Debug)]
+pub struct PinError(());
+
+impl fmt::Display for PinError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("invalid public key pin: expected a base64-encoded SHA-256 digest")
+    }
+}
+
+impl Error for PinError {}
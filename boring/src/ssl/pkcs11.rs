@@ -0,0 +1,536 @@
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::rsa::{PkcsMgfType, PkcsPssParams};
+use cryptoki::mechanism::{Mechanism, MechanismType};
+use cryptoki::object::{Attribute, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::slot::Slot;
+use cryptoki::types::AuthPin;
+use std::error::Error;
+use std::fmt;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+
+use crate::bn::BigNum;
+use crate::ecdsa::EcdsaSig;
+use crate::hash::{hash, MessageDigest};
+use crate::ssl::{PrivateKeyMethod, PrivateKeyMethodError, SslRef, SslSignatureAlgorithm};
+
+/// Where to find a private key in a PKCS#11 module, and how to use it.
+///
+/// The token is selected by slot ID or by label, and defaults to the first slot holding a token.
+/// The key is selected by label and by ID, and defaults to the only private key of the token.
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    module: PathBuf,
+    slot_id: Option<u64>,
+    token_label: Option<String>,
+    pin: Option<String>,
+    key_label: Option<String>,
+    key_id: Option<Vec<u8>>,
+    max_sessions: usize,
+}
+
+impl fmt::Debug for Pkcs11Config {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Pkcs11Config")
+            .field("module", &self.module)
+            .field("slot_id", &self.slot_id)
+            .field("token_label", &self.token_label)
+            .field("pin", &self.pin.as_ref().map(|_| "..."))
+            .field("key_label", &self.key_label)
+            .field("key_id", &self.key_id)
+            .field("max_sessions", &self.max_sessions)
+            .finish()
+    }
+}
+
+impl Pkcs11Config {
+    /// Creates a configuration loading the PKCS#11 module at `module`, using up to 4 sessions.
+    pub fn new(module: impl AsRef<Path>) -> Pkcs11Config {
+        Pkcs11Config {
+            module: module.as_ref().to_owned(),
+            slot_id: None,
+            token_label: None,
+            pin: None,
+            key_label: None,
+            key_id: None,
+            max_sessions: 4,
+        }
+    }
+
+    /// Selects the token in the slot `slot_id`.
+    pub fn slot_id(mut self, slot_id: u64) -> Pkcs11Config {
+        self.slot_id = Some(slot_id);
+        self
+    }
+
+    /// Selects the token labeled `label`.
+    pub fn token_label(mut self, label: impl Into<String>) -> Pkcs11Config {
+        self.token_label = Some(label.into());
+        self
+    }
+
+    /// Logs in as the normal user of the token with `pin`.
+    pub fn pin(mut self, pin: impl Into<String>) -> Pkcs11Config {
+        self.pin = Some(pin.into());
+        self
+    }
+
+    /// Selects the private key labeled `label`.
+    pub fn key_label(mut self, label: impl Into<String>) -> Pkcs11Config {
+        self.key_label = Some(label.into());
+        self
+    }
+
+    /// Selects the private key whose `CKA_ID` is `id`.
+    pub fn key_id(mut self, id: impl Into<Vec<u8>>) -> Pkcs11Config {
+        self.key_id = Some(id.into());
+        self
+    }
+
+    /// Sets the number of sessions opened on the token, and thus of concurrent operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_sessions` is zero.
+    pub fn max_sessions(mut self, max_sessions: usize) -> Pkcs11Config {
+        assert!(max_sessions > 0, "at least one session is required");
+        self.max_sessions = max_sessions;
+        self
+    }
+}
+
+/// A [`PrivateKeyMethod`] performing the private key operations of handshakes with a key held by
+/// a PKCS#11 token, such as an HSM.
+///
+/// Operations block the thread driving the handshake until the token answers, which suits
+/// servers handling each connection on its own thread. Sessions are opened on demand, up to
+/// [`Pkcs11Config::max_sessions`], and reused by later operations; once they are all in use,
+/// handshakes wait for one to be released.
+///
+/// [`Pkcs11PrivateKey::sign_to_vec`] and [`Pkcs11PrivateKey::decrypt_to_vec`] perform the same
+/// operations outside of a handshake, e.g. on the blocking thread pool of an async runtime.
+///
+/// Signatures use the RSA PKCS#1, RSA-PSS and ECDSA algorithms with SHA-256, SHA-384 or SHA-512.
+/// Decryption, which is only used by the RSA key exchange of TLS 1.2 and earlier, uses raw RSA.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{Identity, Pkcs11Config, Pkcs11PrivateKey, SslAcceptor, SslMethod};
+/// use boring::x509::X509;
+///
+/// let config = Pkcs11Config::new("/usr/lib/softhsm/libsofthsm2.so")
+///     .token_label("tls")
+///     .pin("1234")
+///     .key_label("server");
+/// let key = Pkcs11PrivateKey::open(&config).unwrap();
+///
+/// let chain = X509::stack_from_pem(&std::fs::read("chain.pem").unwrap()).unwrap();
+/// let mut chain = chain.into_iter();
+/// let identity = Identity::with_private_key_method(chain.next().unwrap(), chain.collect(), key);
+///
+/// let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+/// acceptor.set_identity(&identity).unwrap();
+/// ```
+pub struct Pkcs11PrivateKey {
+    sessions: Mutex<SessionPool>,
+    released: Condvar,
+    slot: Slot,
+    key: ObjectHandle,
+    max_sessions: usize,
+    // Dropped last, as it finalizes the module.
+    pkcs11: Pkcs11,
+}
+
+struct SessionPool {
+    idle: Vec<Session>,
+    open: usize,
+}
+
+/// Returns a session to the pool of its key when dropped, even if the operation using it
+/// panicked.
+struct SessionGuard<'a> {
+    key: &'a Pkcs11PrivateKey,
+    session: Option<Session>,
+}
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(session) = self.session.take() {
+            self.key.sessions.lock().unwrap().idle.push(session);
+        }
+        self.key.released.notify_one();
+    }
+}
+
+impl Deref for SessionGuard<'_> {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        self.session.as_ref().unwrap()
+    }
+}
+
+impl Pkcs11PrivateKey {
+    /// Loads the module of `config`, logs in to its token and finds its private key.
+    pub fn open(config: &Pkcs11Config) -> Result<Pkcs11PrivateKey, Pkcs11Error> {
+        let pkcs11 = Pkcs11::new(&config.module)
+            .map_err(|e| Pkcs11Error::new("failed to load the PKCS#11 module", e))?;
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(|e| Pkcs11Error::new("failed to initialize the PKCS#11 module", e))?;
+
+        let slot = find_slot(&pkcs11, config)?;
+        let session = pkcs11
+            .open_ro_session(slot)
+            .map_err(|e| Pkcs11Error::new("failed to open a session", e))?;
+
+        // The login state is shared by all the sessions of the token.
+        if let Some(pin) = &config.pin {
+            session
+                .login(UserType::User, Some(&AuthPin::new(pin.clone())))
+                .map_err(|e| Pkcs11Error::new("failed to log in to the token", e))?;
+        }
+
+        let key = find_key(&session, config)?;
+
+        Ok(Pkcs11PrivateKey {
+            sessions: Mutex::new(SessionPool {
+                idle: vec![session],
+                open: 1,
+            }),
+            released: Condvar::new(),
+            slot,
+            key,
+            max_sessions: config.max_sessions,
+            pkcs11,
+        })
+    }
+
+    /// Signs `input` for a handshake which negotiated `signature_algorithm`.
+    ///
+    /// This blocks until the token answers.
+    pub fn sign_to_vec(
+        &self,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+    ) -> Result<Vec<u8>, PrivateKeyMethodError> {
+        let (scheme, digest) = signature_params(signature_algorithm).ok_or_else(|| {
+            PrivateKeyMethodError::failure("the signature algorithm is not supported")
+        })?;
+        let hashed =
+            hash(digest, input).map_err(|e| PrivateKeyMethodError::failure(e.to_string()))?;
+
+        let (hash_alg, mgf, salt_len, pkcs1) = match digest.size() {
+            32 => (
+                MechanismType::SHA256,
+                PkcsMgfType::MGF1_SHA256,
+                32.into(),
+                Mechanism::Sha256RsaPkcs,
+            ),
+            48 => (
+                MechanismType::SHA384,
+                PkcsMgfType::MGF1_SHA384,
+                48.into(),
+                Mechanism::Sha384RsaPkcs,
+            ),
+            _ => (
+                MechanismType::SHA512,
+                PkcsMgfType::MGF1_SHA512,
+                64.into(),
+                Mechanism::Sha512RsaPkcs,
+            ),
+        };
+        // RSASSA-PKCS1-v1_5 uses the mechanisms hashing the message themselves, which spares
+        // building the `DigestInfo` structure `CKM_RSA_PKCS` expects.
+        let (mechanism, data) = match scheme {
+            Scheme::RsaPkcs1 => (pkcs1, input),
+            Scheme::RsaPss => {
+                let params = PkcsPssParams {
+                    hash_alg,
+                    mgf,
+                    s_len: salt_len,
+                };
+                (Mechanism::RsaPkcsPss(params), &*hashed)
+            }
+            Scheme::Ecdsa => (Mechanism::Ecdsa, &*hashed),
+        };
+
+        let signature = self.with_session(|session| {
+            session
+                .sign(&mechanism, self.key, data)
+                .map_err(|e| PrivateKeyMethodError::failure(format!("failed to sign: {}", e)))
+        })?;
+
+        match scheme {
+            // Tokens return the concatenation of `r` and `s`, rather than their DER encoding.
+            Scheme::Ecdsa => {
+                ecdsa_to_der(&signature).map_err(|e| PrivateKeyMethodError::failure(e.to_string()))
+            }
+            _ => Ok(signature),
+        }
+    }
+
+    /// Decrypts `input` with raw RSA, for the RSA key exchange of TLS 1.2 and earlier.
+    ///
+    /// This blocks until the token answers.
+    pub fn decrypt_to_vec(&self, input: &[u8]) -> Result<Vec<u8>, PrivateKeyMethodError> {
+        self.with_session(|session| {
+            session
+                .decrypt(&Mechanism::RsaX509, self.key, input)
+                .map_err(|e| PrivateKeyMethodError::failure(format!("failed to decrypt: {}", e)))
+        })
+    }
+
+    fn with_session<T>(
+        &self,
+        operation: impl FnOnce(&Session) -> Result<T, PrivateKeyMethodError>,
+    ) -> Result<T, PrivateKeyMethodError> {
+        let session = SessionGuard {
+            key: self,
+            session: Some(self.take_session()?),
+        };
+
+        operation(&session)
+    }
+
+    fn take_session(&self) -> Result<Session, PrivateKeyMethodError> {
+        let mut pool = self.sessions.lock().unwrap();
+
+        loop {
+            if let Some(session) = pool.idle.pop() {
+                return Ok(session);
+            }
+
+            if pool.open < self.max_sessions {
+                pool.open += 1;
+                drop(pool);
+
+                return self.pkcs11.open_ro_session(self.slot).map_err(|e| {
+                    self.sessions.lock().unwrap().open -= 1;
+                    self.released.notify_one();
+                    PrivateKeyMethodError::failure(format!("failed to open a session: {}", e))
+                });
+            }
+
+            pool = self.released.wait(pool).unwrap();
+        }
+    }
+}
+
+impl PrivateKeyMethod for Pkcs11PrivateKey {
+    fn sign(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError> {
+        let signature = self.sign_to_vec(input, signature_algorithm)?;
+
+        write_output(&signature, output)
+    }
+
+    fn decrypt(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError> {
+        let plaintext = self.decrypt_to_vec(input)?;
+
+        write_output(&plaintext, output)
+    }
+
+    fn complete(&self, _: &mut SslRef, _: &mut [u8]) -> Result<usize, PrivateKeyMethodError> {
+        // Operations complete synchronously, and are never retried.
+        Err(PrivateKeyMethodError::FAILURE)
+    }
+}
+
+fn find_slot(pkcs11: &Pkcs11, config: &Pkcs11Config) -> Result<Slot, Pkcs11Error> {
+    let slots = pkcs11
+        .get_slots_with_token()
+        .map_err(|e| Pkcs11Error::new("failed to list the slots", e))?;
+
+    for slot in slots {
+        if config.slot_id.is_some_and(|id| slot.id() != id) {
+            continue;
+        }
+
+        if let Some(label) = &config.token_label {
+            let info = pkcs11
+                .get_token_info(slot)
+                .map_err(|e| Pkcs11Error::new("failed to get the token information", e))?;
+
+            if info.label() != label {
+                continue;
+            }
+        }
+
+        return Ok(slot);
+    }
+
+    Err(Pkcs11Error::msg("no matching token was found"))
+}
+
+fn find_key(session: &Session, config: &Pkcs11Config) -> Result<ObjectHandle, Pkcs11Error> {
+    let mut template = vec![Attribute::Class(ObjectClass::PRIVATE_KEY)];
+    if let Some(label) = &config.key_label {
+        template.push(Attribute::Label(label.as_bytes().to_vec()));
+    }
+    if let Some(id) = &config.key_id {
+        template.push(Attribute::Id(id.clone()));
+    }
+
+    let keys = session
+        .find_objects(&template)
+        .map_err(|e| Pkcs11Error::new("failed to find the private key", e))?;
+
+    match keys[..] {
+        [key] => Ok(key),
+        [] => Err(Pkcs11Error::msg("no matching private key was found")),
+        _ => Err(Pkcs11Error::msg("several private keys match")),
+    }
+}
+
+enum Scheme {
+    RsaPkcs1,
+    RsaPss,
+    Ecdsa,
+}
+
+fn signature_params(signature_algorithm: SslSignatureAlgorithm) -> Option<(Scheme, MessageDigest)> {
+    let params = match signature_algorithm {
+        SslSignatureAlgorithm::RSA_PKCS1_SHA256 => (Scheme::RsaPkcs1, MessageDigest::sha256()),
+        SslSignatureAlgorithm::RSA_PKCS1_SHA384 => (Scheme::RsaPkcs1, MessageDigest::sha384()),
+        SslSignatureAlgorithm::RSA_PKCS1_SHA512 => (Scheme::RsaPkcs1, MessageDigest::sha512()),
+        SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256 => (Scheme::RsaPss, MessageDigest::sha256()),
+        SslSignatureAlgorithm::RSA_PSS_RSAE_SHA384 => (Scheme::RsaPss, MessageDigest::sha384()),
+        SslSignatureAlgorithm::RSA_PSS_RSAE_SHA512 => (Scheme::RsaPss, MessageDigest::sha512()),
+        SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256 => (Scheme::Ecdsa, MessageDigest::sha256()),
+        SslSignatureAlgorithm::ECDSA_SECP384R1_SHA384 => (Scheme::Ecdsa, MessageDigest::sha384()),
+        SslSignatureAlgorithm::ECDSA_SECP521R1_SHA512 => (Scheme::Ecdsa, MessageDigest::sha512()),
+        _ => return None,
+    };
+
+    Some(params)
+}
+
+fn ecdsa_to_der(raw: &[u8]) -> Result<Vec<u8>, crate::error::ErrorStack> {
+    let (r, s) = raw.split_at(raw.len() / 2);
+
+    EcdsaSig::from_private_components(BigNum::from_slice(r)?, BigNum::from_slice(s)?)?.to_der()
+}
+
+fn write_output(data: &[u8], output: &mut [u8]) -> Result<usize, PrivateKeyMethodError> {
+    let output = output
+        .get_mut(..data.len())
+        .ok_or_else(|| PrivateKeyMethodError::failure("the output buffer is too small"))?;
+    output.copy_from_slice(data);
+
+    Ok(data.len())
+}
+
+/// An error loading a [`Pkcs11PrivateKey`].
+#[derive(Debug)]
+pub struct Pkcs11Error {
+    reason: &'static str,
+    source: Option<cryptoki::error::Error>,
+}
+
+impl Pkcs11Error {
+    fn new(reason: &'static str, source: cryptoki::error::Error) -> Pkcs11Error {
+        Pkcs11Error {
+            reason,
+            source: Some(source),
+        }
+    }
+
+    fn msg(reason: &'static str) -> Pkcs11Error {
+        Pkcs11Error {
+            reason,
+            source: None,
+        }
+    }
+
+    /// Returns a description of the error.
+    pub fn reason(&self) -> &str {
+        self.reason
+    }
+}
+
+impl fmt::Display for Pkcs11Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match &self.source {
+            Some(source) => write!(fmt, "{}: {}", self.reason, source),
+            None => fmt.write_str(self.reason),
+        }
+    }
+}
+
+impl Error for Pkcs11Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|e| e as _)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+
+    use super::*;
+
+    #[test]
+    fn signature_params_supported() {
+        let cases = [
+            (SslSignatureAlgorithm::RSA_PKCS1_SHA256, 32),
+            (SslSignatureAlgorithm::RSA_PSS_RSAE_SHA384, 48),
+            (SslSignatureAlgorithm::ECDSA_SECP521R1_SHA512, 64),
+        ];
+
+        for (signature_algorithm, digest_size) in cases {
+            let (_, digest) = signature_params(signature_algorithm).unwrap();
+            assert_eq!(digest.size(), digest_size);
+        }
+
+        assert!(matches!(
+            signature_params(SslSignatureAlgorithm::RSA_PKCS1_SHA256),
+            Some((Scheme::RsaPkcs1, _))
+        ));
+        assert!(matches!(
+            signature_params(SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256),
+            Some((Scheme::RsaPss, _))
+        ));
+        assert!(matches!(
+            signature_params(SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256),
+            Some((Scheme::Ecdsa, _))
+        ));
+    }
+
+    #[test]
+    fn signature_params_unsupported() {
+        assert!(signature_params(SslSignatureAlgorithm::RSA_PKCS1_SHA1).is_none());
+        assert!(signature_params(SslSignatureAlgorithm::ECDSA_SHA1).is_none());
+        assert!(signature_params(SslSignatureAlgorithm::ED25519).is_none());
+    }
+
+    #[test]
+    fn ecdsa_to_der_roundtrip() {
+        for nid in [Nid::X9_62_PRIME256V1, Nid::SECP384R1, Nid::SECP521R1] {
+            let group = EcGroup::from_curve_name(nid).unwrap();
+            let key = EcKey::generate(&group).unwrap();
+            let digest = hash(MessageDigest::sha256(), b"hello world").unwrap();
+
+            let signature = EcdsaSig::sign(&digest, &key).unwrap();
+            let der = ecdsa_to_der(&signature.to_raw(&group).unwrap()).unwrap();
+            assert_eq!(der, signature.to_der().unwrap());
+
+            let parsed = EcdsaSig::from_der(&der).unwrap();
+            assert!(parsed.verify(&digest, &key).unwrap());
+        }
+    }
+}
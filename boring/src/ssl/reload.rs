@@ -0,0 +1,153 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use crate::error::ErrorStack;
+use crate::ssl::{HandshakeError, MidHandshakeSslStream, SslAcceptor, SslStream};
+
+type Configure = dyn Fn() -> Result<SslAcceptor, ErrorStack> + Sync + Send;
+
+/// An `SslAcceptor` whose configuration can be replaced while it is in use.
+///
+/// The acceptor is built by a configuration function, which typically loads the certificate
+/// chain, private key and session ticket keys from disk. Calling [`ReloadableAcceptor::reload`]
+/// runs it again and atomically swaps in the result. Only handshakes started afterwards use the
+/// new configuration; established connections keep the one they were accepted with.
+///
+/// Cloning a `ReloadableAcceptor` yields a handle to the same acceptor.
+#[derive(Clone)]
+pub struct ReloadableAcceptor {
+    current: Arc<RwLock<SslAcceptor>>,
+    configure: Arc<Configure>,
+}
+
+impl ReloadableAcceptor {
+    /// Creates a new acceptor, using `configure` to build its initial configuration.
+    pub fn new<F>(configure: F) -> Result<ReloadableAcceptor, ErrorStack>
+    where
+        F: Fn() -> Result<SslAcceptor, ErrorStack> + 'static + Sync + Send,
+    {
+        let acceptor = configure()?;
+
+        Ok(ReloadableAcceptor {
+            current: Arc::new(RwLock::new(acceptor)),
+            configure: Arc::new(configure),
+        })
+    }
+
+    /// Returns the acceptor currently in use.
+    pub fn acceptor(&self) -> SslAcceptor {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Rebuilds the configuration and swaps it in.
+    ///
+    /// If the configuration function fails, the current configuration is kept and the error is
+    /// returned.
+    pub fn reload(&self) -> Result<(), ErrorStack> {
+        let acceptor = (self.configure)()?;
+        self.replace(acceptor);
+
+        Ok(())
+    }
+
+    /// Swaps in the given acceptor, returning the previous one.
+    pub fn replace(&self, acceptor: SslAcceptor) -> SslAcceptor {
+        let mut current = self.current.write().unwrap();
+        std::mem::replace(&mut *current, acceptor)
+    }
+
+    /// Initiates a server-side TLS session on a stream, using the current configuration.
+    ///
+    /// See [`SslAcceptor::setup_accept`].
+    pub fn setup_accept<S>(&self, stream: S) -> Result<MidHandshakeSslStream<S>, ErrorStack>
+    where
+        S: Read + Write,
+    {
+        self.acceptor().setup_accept(stream)
+    }
+
+    /// Initiates a server-side TLS session on a stream, using the current configuration.
+    ///
+    /// See [`SslAcceptor::accept`].
+    pub fn accept<S>(&self, stream: S) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: Read + Write,
+    {
+        self.acceptor().accept(stream)
+    }
+
+    /// Reloads the configuration whenever one of the given files is modified.
+    ///
+    /// The files are polled for changes to their modification time every `interval`, on a
+    /// background thread that runs until the returned [`FileWatcher`] is dropped. Failed reloads,
+    /// e.g. because a certificate and its key were not both replaced yet, keep the current
+    /// configuration and are retried on every poll until one succeeds.
+    pub fn watch<I>(&self, paths: I, interval: Duration) -> FileWatcher
+    where
+        I: IntoIterator,
+        I::Item: Into<PathBuf>,
+    {
+        let paths = paths.into_iter().map(Into::into).collect::<Vec<PathBuf>>();
+        let stop = Arc::new(AtomicBool::new(false));
+        let acceptor = self.clone();
+
+        let handle = thread::spawn({
+            let stop = stop.clone();
+
+            move || {
+                let mut last = modified(&paths);
+                let mut pending = false;
+
+                while !stop.load(Ordering::SeqCst) {
+                    thread::park_timeout(interval);
+
+                    let current = modified(&paths);
+                    if current != last {
+                        last = current;
+                        pending = true;
+                    }
+
+                    if pending && acceptor.reload().is_ok() {
+                        pending = false;
+                    }
+                }
+            }
+        });
+
+        FileWatcher {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+fn modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// A background task reloading a [`ReloadableAcceptor`] when files change.
+///
+/// Dropping the watcher stops it.
+pub struct FileWatcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
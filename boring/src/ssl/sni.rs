@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::ssl::SslContext;
+
+/// A table mapping server names to `SslContext`s.
+///
+/// Patterns are either exact host names (`example.com`) or wildcards covering a single leftmost
+/// label (`*.example.com`). Exact matches take precedence over wildcard matches, and the default
+/// context, if any, is used when nothing else matches. Host names are compared
+/// case-insensitively, ignoring a trailing dot.
+#[derive(Clone, Default)]
+pub struct SniRoutes {
+    exact: HashMap<String, SslContext>,
+    wildcard: HashMap<String, SslContext>,
+    default: Option<SslContext>,
+}
+
+impl SniRoutes {
+    /// Creates an empty table.
+    pub fn new() -> SniRoutes {
+        SniRoutes::default()
+    }
+
+    /// Adds a route for the given host name pattern, replacing any previous route for it.
+    pub fn insert(&mut self, pattern: &str, ctx: SslContext) -> &mut SniRoutes {
+        let pattern = normalize(pattern);
+
+        match pattern.strip_prefix("*.") {
+            Some(suffix) => self.wildcard.insert(suffix.to_owned(), ctx),
+            None => self.exact.insert(pattern, ctx),
+        };
+
+        self
+    }
+
+    /// Sets the context used when no route matches, or when the client sent no server name.
+    pub fn set_default(&mut self, ctx: SslContext) -> &mut SniRoutes {
+        self.default = Some(ctx);
+        self
+    }
+
+    /// Returns the context for the given server name, falling back to the default context.
+    pub fn lookup(&self, servername: Option<&str>) -> Option<&SslContext> {
+        servername
+            .and_then(|name| self.lookup_name(&normalize(name)))
+            .or(self.default.as_ref())
+    }
+
+    fn lookup_name(&self, name: &str) -> Option<&SslContext> {
+        if let Some(ctx) = self.exact.get(name) {
+            return Some(ctx);
+        }
+
+        let (label, suffix) = name.split_once('.')?;
+
+        if label.is_empty() {
+            return None;
+        }
+
+        self.wildcard.get(suffix)
+    }
+}
+
+pub(super) fn normalize(name: &str) -> String {
+    name.strip_suffix('.').unwrap_or(name).to_ascii_lowercase()
+}
+
+/// Routes incoming connections to an `SslContext` based on the server name they request.
+///
+/// The routing table can be replaced at any time with [`SniRouter::set_routes`], affecting all
+/// handshakes started afterwards. Cloning a router yields a handle to the same table.
+///
+/// A router is installed with [`SslContextBuilder::set_sni_router`].
+///
+/// [`SslContextBuilder::set_sni_router`]: struct.SslContextBuilder.html#method.set_sni_router
+#[derive(Clone, Default)]
+pub struct SniRouter {
+    routes: Arc<RwLock<Arc<SniRoutes>>>,
+}
+
+impl SniRouter {
+    /// Creates a router using the given routing table.
+    pub fn new(routes: SniRoutes) -> SniRouter {
+        SniRouter {
+            routes: Arc::new(RwLock::new(Arc::new(routes))),
+        }
+    }
+
+    /// Returns the current routing table.
+    pub fn routes(&self) -> Arc<SniRoutes> {
+        self.routes.read().unwrap().clone()
+    }
+
+    /// Atomically replaces the routing table, returning the previous one.
+    ///
+    /// Handshakes that already selected a context are not affected.
+    pub fn set_routes(&self, routes: SniRoutes) -> Arc<SniRoutes> {
+        let mut guard = self.routes.write().unwrap();
+        std::mem::replace(&mut *guard, Arc::new(routes))
+    }
+
+    /// Returns the context for the given server name, according to the current routing table.
+    pub fn resolve(&self, servername: Option<&str>) -> Option<SslContext> {
+        self.routes().lookup(servername).cloned()
+    }
+}
@@ -0,0 +1,254 @@
+use libc::c_int;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::ffi;
+use crate::ssl::{ErrorCode, Ssl, SslContext, SslRef, SslVersion, SESSION_CTX_INDEX};
+use foreign_types::ForeignTypeRef;
+
+/// The class of a failed handshake, as counted by [`SslStats`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HandshakeFailure {
+    /// The peer's certificate chain could not be verified, or the client did not send a
+    /// certificate although one was required.
+    Certificate,
+    /// The peers have no cipher suite, protocol version, key exchange group or signature
+    /// algorithm in common.
+    Negotiation,
+    /// The peer aborted the handshake with a fatal alert.
+    PeerAlert,
+    /// The peer sent a malformed or unexpected message, or did not speak TLS at all.
+    Protocol,
+    /// The transport failed, or the peer closed the connection.
+    Io,
+    /// Any other failure, such as an error returned by a callback.
+    Other,
+}
+
+impl HandshakeFailure {
+    const ALL: [HandshakeFailure; 6] = [
+        HandshakeFailure::Certificate,
+        HandshakeFailure::Negotiation,
+        HandshakeFailure::PeerAlert,
+        HandshakeFailure::Protocol,
+        HandshakeFailure::Io,
+        HandshakeFailure::Other,
+    ];
+
+    /// Classifies the failure of a handshake step which returned `ret`, or returns `None` if
+    /// the step only needs to be retried.
+    fn classify(ssl: &SslRef, ret: c_int) -> Option<HandshakeFailure> {
+        let code = ErrorCode::from_raw(unsafe { ffi::SSL_get_error(ssl.as_ptr(), ret) });
+
+        match code {
+            ErrorCode::SSL => {}
+            ErrorCode::SYSCALL | ErrorCode::ZERO_RETURN => return Some(HandshakeFailure::Io),
+            _ => return None,
+        }
+
+        let packed = unsafe { ffi::ERR_peek_error() };
+        if ffi::ERR_GET_LIB(packed) != ffi::ERR_LIB_SSL.0 as c_int {
+            return Some(HandshakeFailure::Other);
+        }
+
+        let failure = match ffi::ERR_GET_REASON(packed) {
+            ffi::SSL_R_CERTIFICATE_VERIFY_FAILED | ffi::SSL_R_PEER_DID_NOT_RETURN_A_CERTIFICATE => {
+                HandshakeFailure::Certificate
+            }
+            ffi::SSL_R_NO_SHARED_CIPHER
+            | ffi::SSL_R_UNSUPPORTED_PROTOCOL
+            | ffi::SSL_R_NO_SHARED_GROUP
+            | ffi::SSL_R_NO_COMMON_SIGNATURE_ALGORITHMS
+            | ffi::SSL_R_INAPPROPRIATE_FALLBACK => HandshakeFailure::Negotiation,
+            reason if reason >= ffi::SSL_AD_REASON_OFFSET as c_int => HandshakeFailure::PeerAlert,
+            _ => HandshakeFailure::Protocol,
+        };
+
+        Some(failure)
+    }
+
+    fn index(self) -> usize {
+        HandshakeFailure::ALL
+            .iter()
+            .position(|failure| *failure == self)
+            .unwrap()
+    }
+}
+
+/// A snapshot of the counters of an [`SslContext`], returned by [`SslContextRef::stats`].
+///
+/// [`SslContextRef::stats`]: crate::ssl::SslContextRef::stats
+#[derive(Debug, Clone)]
+pub struct SslStats {
+    accepts: u64,
+    connects: u64,
+    completed: u64,
+    resumed: u64,
+    failures: Vec<(HandshakeFailure, u64)>,
+    versions: Vec<(SslVersion, u64)>,
+    ciphers: Vec<(&'static str, u64)>,
+}
+
+impl SslStats {
+    /// Returns the number of handshakes started as a server.
+    pub fn accepts(&self) -> u64 {
+        self.accepts
+    }
+
+    /// Returns the number of handshakes started as a client.
+    pub fn connects(&self) -> u64 {
+        self.connects
+    }
+
+    /// Returns the number of completed handshakes.
+    pub fn completed(&self) -> u64 {
+        self.completed
+    }
+
+    /// Returns the number of completed handshakes which resumed a session.
+    pub fn resumed(&self) -> u64 {
+        self.resumed
+    }
+
+    /// Returns the number of failed handshakes of each class, including those which never
+    /// happened.
+    pub fn failures(&self) -> &[(HandshakeFailure, u64)] {
+        &self.failures
+    }
+
+    /// Returns the total number of failed handshakes.
+    pub fn total_failures(&self) -> u64 {
+        self.failures.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Returns the number of completed handshakes for each negotiated protocol version, by
+    /// increasing version.
+    pub fn versions(&self) -> &[(SslVersion, u64)] {
+        &self.versions
+    }
+
+    /// Returns the number of completed handshakes for each negotiated cipher, by name.
+    ///
+    /// See [`SslCipherRef::name`].
+    ///
+    /// [`SslCipherRef::name`]: crate::ssl::SslCipherRef::name
+    pub fn ciphers(&self) -> &[(&'static str, u64)] {
+        &self.ciphers
+    }
+}
+
+/// The counters of a context, stored in its ex data.
+#[derive(Default)]
+pub(super) struct StatsCounters {
+    accepts: AtomicU64,
+    connects: AtomicU64,
+    completed: AtomicU64,
+    resumed: AtomicU64,
+    failures: [AtomicU64; HandshakeFailure::ALL.len()],
+    histograms: Mutex<Histograms>,
+}
+
+#[derive(Default)]
+struct Histograms {
+    versions: HashMap<u16, u64>,
+    ciphers: HashMap<&'static str, u64>,
+}
+
+/// Marks a connection whose failure was already counted, as BoringSSL fails again each time the
+/// handshake is retried.
+struct FailureCounted;
+
+impl StatsCounters {
+    pub(super) fn snapshot(&self) -> SslStats {
+        let failures = HandshakeFailure::ALL
+            .iter()
+            .map(|failure| {
+                let count = self.failures[failure.index()].load(Ordering::Relaxed);
+                (*failure, count)
+            })
+            .collect();
+
+        let histograms = self.histograms.lock().unwrap();
+
+        let mut versions: Vec<_> = histograms
+            .versions
+            .iter()
+            .map(|(version, count)| (SslVersion(*version), *count))
+            .collect();
+        versions.sort_by_key(|(version, _)| version.0);
+
+        let mut ciphers: Vec<_> = histograms
+            .ciphers
+            .iter()
+            .map(|(cipher, count)| (*cipher, *count))
+            .collect();
+        ciphers.sort();
+
+        SslStats {
+            accepts: self.accepts.load(Ordering::Relaxed),
+            connects: self.connects.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            resumed: self.resumed.load(Ordering::Relaxed),
+            failures,
+            versions,
+            ciphers,
+        }
+    }
+
+    fn handshake_done(&self, ssl: &SslRef) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        if ssl.session_reused() {
+            self.resumed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut histograms = self.histograms.lock().unwrap();
+        if let Some(version) = ssl.version2() {
+            *histograms.versions.entry(version.0).or_default() += 1;
+        }
+        if let Some(cipher) = ssl.current_cipher() {
+            *histograms.ciphers.entry(cipher.name()).or_default() += 1;
+        }
+    }
+}
+
+/// Updates the counters of the context a connection was created with, from its info callback.
+pub(super) fn record(ssl: &mut SslRef, type_: c_int, value: c_int) {
+    let exit = (ffi::SSL_CB_ACCEPT_EXIT | ffi::SSL_CB_CONNECT_EXIT) as c_int;
+    let events = ffi::SSL_CB_HANDSHAKE_START as c_int | ffi::SSL_CB_HANDSHAKE_DONE as c_int | exit;
+    if type_ & events == 0 {
+        return;
+    }
+
+    let Some(ctx) = ssl.ex_data(*SESSION_CTX_INDEX).cloned() else {
+        return;
+    };
+    let Some(stats) = ctx.ex_data(SslContext::cached_ex_index::<StatsCounters>()) else {
+        return;
+    };
+
+    if type_ & ffi::SSL_CB_HANDSHAKE_START as c_int != 0 {
+        if ssl.is_server() {
+            stats.accepts.fetch_add(1, Ordering::Relaxed);
+        } else {
+            stats.connects.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    if type_ & ffi::SSL_CB_HANDSHAKE_DONE as c_int != 0 {
+        stats.handshake_done(ssl);
+    }
+
+    if type_ & exit != 0 && value <= 0 {
+        let index = Ssl::cached_ex_index::<FailureCounted>();
+        if ssl.ex_data(index).is_some() {
+            return;
+        }
+
+        if let Some(failure) = HandshakeFailure::classify(ssl, value) {
+            stats.failures[failure.index()].fetch_add(1, Ordering::Relaxed);
+            ssl.set_ex_data(index, FailureCounted);
+        }
+    }
+}
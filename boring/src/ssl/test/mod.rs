@@ -13,26 +13,39 @@ use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::Path;
 use std::process::{Child, ChildStdin, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+use foreign_types::{ForeignType, ForeignTypeRef};
+
+use crate::base64;
 use crate::dh::Dh;
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pem::PemError;
 use crate::pkey::PKey;
+use crate::sha::sha256;
 use crate::srtp::SrtpProfileId;
 use crate::ssl;
 use crate::ssl::test::server::Server;
 use crate::ssl::SslVersion;
 use crate::ssl::{
-    Error, ExtensionType, HandshakeError, MidHandshakeSslStream, ShutdownResult, ShutdownState,
-    Ssl, SslAcceptor, SslAcceptorBuilder, SslConnector, SslContext, SslContextBuilder, SslFiletype,
-    SslMethod, SslOptions, SslSessionCacheMode, SslStream, SslStreamBuilder, SslVerifyMode,
-    StatusType,
+    AcmeChallenges, CertCallbackError, ClientAuthentication, ClientHello, Error, ErrorCode,
+    ExtensionType, HandshakeBudget, HandshakeError, HandshakeFailure, HandshakeState, Identity,
+    MemSsl, MidHandshakeSslStream, OcspStaplingPolicy, PinSet, ReloadableAcceptor,
+    ResumptionMechanism, SecurityProfileBuilder, SessionError, ShutdownResult, ShutdownState,
+    SniRouter, SniRoutes, Ssl, SslAcceptor, SslAcceptorBuilder, SslAlert, SslConnector, SslContext,
+    SslContextBuilder, SslEngine, SslFiletype, SslKeyUpdate, SslMethod, SslMode, SslOptions,
+    SslRef, SslRenegotiateMode, SslSession, SslSessionCacheMode, SslSignatureAlgorithm, SslStream,
+    SslStreamBuilder, SslVerifyError, SslVerifyMode, StatusType, TicketKeyManager,
+    ACME_TLS_ALPN_PROTOCOL,
 };
+use crate::stack::Stack;
 use crate::x509::store::X509StoreBuilder;
 use crate::x509::verify::X509CheckFlags;
-use crate::x509::{X509Name, X509StoreContext, X509VerifyResult, X509};
+use crate::x509::{X509Name, X509StoreContext, X509StoreContextRef, X509VerifyResult, X509};
 
 mod private_key_method;
 mod server;
@@ -205,6 +218,80 @@ fn verify_callback() {
     assert!(CALLED_BACK.load(Ordering::SeqCst));
 }
 
+#[test]
+fn custom_verify_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_custom_verify_callback(SslVerifyMode::PEER, |ssl| {
+            CALLED_BACK.store(true, Ordering::SeqCst);
+            assert!(ssl.peer_certificate().is_some());
+            Ok(())
+        });
+
+    client.connect();
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn custom_verify_callback_swapped_ctx() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_custom_verify_callback(SslVerifyMode::PEER, |_| {
+            CALLED_BACK.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+
+    let mut client = client.build().builder();
+
+    let ctx = SslContextBuilder::new(SslMethod::tls()).unwrap().build();
+    client.ssl().set_ssl_context(&ctx).unwrap();
+
+    client.connect();
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn ssl_custom_verify_callback_pins_certificate() {
+    let pinned = X509::from_pem(CERT).unwrap().to_der().unwrap();
+    let verify = move |ssl: &mut SslRef| {
+        let cert = ssl.peer_certificate().unwrap();
+        if cert.to_der().unwrap() == pinned {
+            Ok(())
+        } else {
+            Err(SslVerifyError::Invalid(SslAlert::BAD_CERTIFICATE))
+        }
+    };
+
+    let server = Server::builder().build();
+    let mut client = server.client().build().builder();
+    client
+        .ssl()
+        .set_custom_verify_callback(SslVerifyMode::PEER, verify);
+    client.connect();
+
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    client
+        .ssl()
+        .set_custom_verify_callback(SslVerifyMode::PEER, |_| {
+            Err(SslVerifyError::Invalid(SslAlert::BAD_CERTIFICATE))
+        });
+    client.connect_err();
+}
+
 #[test]
 fn ssl_verify_callback() {
     static CALLED_BACK: AtomicBool = AtomicBool::new(false);
@@ -227,6 +314,45 @@ fn ssl_verify_callback() {
     assert!(CALLED_BACK.load(Ordering::SeqCst));
 }
 
+#[test]
+fn cipher_properties() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_strict_cipher_list("ECDHE-RSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-SHA")
+        .unwrap();
+    let ctx = ctx.build();
+
+    let ciphers = ctx.ciphers().unwrap();
+    assert_eq!(ciphers.len(), 2);
+
+    let aead = &ciphers[0];
+    assert_eq!(aead.name(), "ECDHE-RSA-AES128-GCM-SHA256");
+    assert!(aead.is_aead());
+    assert_eq!(aead.kx_nid(), Nid::KX_ECDHE);
+    assert_eq!(aead.auth_nid(), Nid::AUTH_RSA);
+    assert_eq!(aead.kx_name(), "ECDHE_RSA");
+    assert_eq!(aead.digest_nid(), None);
+    assert_eq!(aead.protocol_id(), 0xc02f);
+    assert_eq!(aead.min_version(), SslVersion::TLS1_2);
+    assert_eq!(aead.bits().secret, 128);
+
+    let cbc = &ciphers[1];
+    assert!(!cbc.is_aead());
+    assert_eq!(cbc.digest_nid(), Some(Nid::SHA1));
+    assert_eq!(cbc.protocol_id(), 0xc013);
+
+    let ssl = Ssl::new(&ctx).unwrap();
+    assert_eq!(ssl.ciphers().unwrap().len(), 2);
+}
+
+#[test]
+fn strict_cipher_list() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_cipher_list("ECDHE-RSA-AES128-GCM-SHA256:BOGUS-CIPHER")
+        .unwrap();
+    ctx.set_strict_cipher_list("ECDHE-RSA-AES128-GCM-SHA256:BOGUS-CIPHER")
+        .unwrap_err();
+}
+
 #[test]
 fn get_ctx_options() {
     let ctx = SslContext::builder(SslMethod::tls()).unwrap();
@@ -257,6 +383,63 @@ fn zero_length_buffers() {
     assert_eq!(s.read(&mut []).unwrap(), 0);
 }
 
+#[test]
+fn peek() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        s.write_all(b"hello").unwrap();
+        s.shutdown().unwrap();
+        s.get_mut().write_all(b"plain").unwrap();
+    });
+    let server = server.build();
+
+    let mut s = server.client().connect();
+
+    let mut buf = [0; 5];
+    let n = s.peek(&mut buf).unwrap();
+    assert!(n > 0);
+    assert_eq!(&buf[..n], &b"hello"[..n]);
+    assert!(s.pending() >= 5 - n);
+
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+    assert_eq!(s.read(&mut buf).unwrap(), 0);
+
+    // Bytes following the TLS session are left in the underlying stream.
+    let mut stream = s.into_inner();
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"plain");
+}
+
+#[test]
+fn do_handshake_step() {
+    let server = Server::builder().build();
+
+    let stream = server.connect_tcp();
+    stream.set_nonblocking(true).unwrap();
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut s = SslStream::new(Ssl::new(&ctx).unwrap(), stream).unwrap();
+    s.ssl_mut().set_connect_state();
+    assert_eq!(s.ssl().want(), None);
+
+    loop {
+        match s.ssl_mut().do_handshake_step().unwrap() {
+            HandshakeState::Complete => break,
+            state @ (HandshakeState::WantRead | HandshakeState::WantWrite) => {
+                assert_eq!(s.ssl().want(), Some(state));
+                thread::sleep(Duration::from_millis(1));
+            }
+            state => panic!("unexpected handshake state {:?}", state),
+        }
+    }
+
+    assert!(s.ssl().is_init_finished());
+
+    s.get_ref().set_nonblocking(false).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
 #[test]
 fn peer_certificate() {
     let server = Server::builder().build();
@@ -270,6 +453,28 @@ fn peer_certificate() {
     );
 }
 
+#[test]
+fn handshake_parameters() {
+    let server = Server::builder().build();
+
+    let mut client = server.client_with_root_ca();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+
+    let s = client.connect();
+    let ssl = s.ssl();
+
+    assert!(ssl.peer_signature_algorithm().is_some());
+    assert!(ssl.negotiated_group().is_some());
+
+    let chain = ssl.verified_chain().unwrap();
+    assert_eq!(chain.len(), 2);
+    let fingerprint = chain[0].digest(MessageDigest::sha1()).unwrap();
+    assert_eq!(
+        hex::encode(fingerprint),
+        "59172d9313e84459bcff27f967e79e6e9217e584"
+    );
+}
+
 #[test]
 fn pending() {
     let mut server = Server::builder();
@@ -296,6 +501,16 @@ fn state() {
     );
 }
 
+#[test]
+fn dtls_timeout_not_running() {
+    let ctx = SslContext::builder(SslMethod::dtls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_dtls_initial_timeout(Duration::from_millis(100));
+
+    assert_eq!(ssl.dtls_timeout(), None);
+    assert!(!ssl.dtls_handle_timeout().unwrap());
+}
+
 /// Tests that when both the client as well as the server use SRTP and their
 /// lists of supported protocols have an overlap -- with only ONE protocol
 /// being valid for both.
@@ -478,6 +693,31 @@ fn test_alpn_server_unilateral() {
     assert_eq!(None, s.ssl().selected_alpn_protocol());
 }
 
+#[test]
+fn fallback_scsv_rejected() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_mode(SslMode::SEND_FALLBACK_SCSV);
+    assert!(client.ssl().mode().contains(SslMode::SEND_FALLBACK_SCSV));
+
+    let HandshakeError::Failure(mid_handshake) = client.connect_err() else {
+        panic!("should be Failure");
+    };
+    assert!(mid_handshake
+        .error()
+        .ssl_error()
+        .unwrap()
+        .contains(crate::error::ErrorKind::InappropriateFallbackAlert));
+}
+
 #[test]
 fn test_select_cert_ok() {
     let mut server = Server::builder();
@@ -503,6 +743,70 @@ fn test_select_cert_error() {
     client.connect_err();
 }
 
+fn suspending_server(resume: bool) -> Server {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_select_certificate_callback(move |mut client_hello| {
+            if let Some(result) = client_hello.ssl_mut().handshake_resumption() {
+                return result;
+            }
+
+            let token = client_hello.ssl_mut().suspend_handshake();
+            thread::spawn(move || {
+                if resume {
+                    token.resume();
+                } else {
+                    token.abort();
+                }
+            });
+
+            Err(ssl::SelectCertError::RETRY)
+        });
+
+    server.err_cb(move |error| {
+        let HandshakeError::WouldBlock(mid_handshake) = error else {
+            panic!("should be WouldBlock");
+        };
+
+        assert_eq!(mid_handshake.error().code(), ErrorCode::PENDING_CERTIFICATE);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        mid_handshake
+            .ssl()
+            .resume_token()
+            .unwrap()
+            .on_decided(move || tx.send(()).unwrap());
+        rx.recv().unwrap();
+
+        match mid_handshake.handshake() {
+            Ok(mut socket) => {
+                assert!(resume);
+                assert!(socket.ssl().resume_token().is_none());
+                socket.write_all(&[0]).unwrap();
+            }
+            Err(HandshakeError::Failure(_)) => assert!(!resume),
+            Err(_) => panic!("unexpected handshake error"),
+        }
+    });
+
+    server.build()
+}
+
+#[test]
+fn test_select_cert_suspend_resume() {
+    let server = suspending_server(true);
+
+    server.client().connect();
+}
+
+#[test]
+fn test_select_cert_suspend_abort() {
+    let server = suspending_server(false);
+
+    server.client().connect_err();
+}
+
 #[test]
 fn test_select_cert_unknown_extension() {
     let mut server = Server::builder();
@@ -692,6 +996,79 @@ fn add_extra_chain_cert() {
     ctx.add_extra_chain_cert(cert).unwrap();
 }
 
+#[test]
+fn pin_set() {
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let leaf = X509::from_pem(CERT).unwrap();
+    let root_pin = base64::encode_block(&root.spki_sha256().unwrap());
+
+    let pins = PinSet::from_base64([&root_pin]).unwrap();
+    assert!(pins.matches(&stack_of(&[&leaf, &root])));
+    assert!(!pins.matches(&stack_of(&[&leaf])));
+    assert!(PinSet::from_base64(["AAAA"]).is_err());
+
+    let leaf_pin = leaf.spki_sha256().unwrap()[..].try_into().unwrap();
+
+    for (pin, ok) in [(leaf_pin, true), ([0; 32], false)] {
+        let mut server = Server::builder();
+        if !ok {
+            server.should_error();
+        }
+        let server = server.build();
+
+        let mut pins = PinSet::new();
+        pins.insert(pin);
+
+        let mut client = server.client_with_root_ca();
+        // Aug 1st, 2020, while test/cert.pem is valid.
+        client.ctx().verify_param_mut().set_time(1596240000);
+        client
+            .ctx()
+            .set_verify_callback(SslVerifyMode::PEER, move |preverify_ok, ctx| {
+                pins.verify(preverify_ok, ctx)
+            });
+
+        if ok {
+            client.connect();
+        } else {
+            client.connect_err();
+        }
+    }
+}
+
+fn stack_of(certs: &[&X509]) -> Stack<X509> {
+    let mut stack = Stack::new().unwrap();
+    for cert in certs {
+        stack.push((*cert).clone()).unwrap();
+    }
+    stack
+}
+
+#[test]
+fn pem_bundle() {
+    let mut bundle = KEY.to_vec();
+    bundle.extend_from_slice(CERT);
+    bundle.extend_from_slice(ROOT_CERT);
+
+    let mut server = Server::builder();
+    server.ctx().set_pem_bundle(&bundle, None).unwrap();
+    let server = server.build();
+
+    let s = server.client().connect();
+    let chain = s.ssl().peer_cert_chain().unwrap();
+    assert_eq!(chain.len(), 2);
+    assert_eq!(
+        chain.get(1).unwrap().to_der().unwrap(),
+        X509::from_pem(ROOT_CERT).unwrap().to_der().unwrap()
+    );
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    assert!(matches!(
+        ctx.set_pem_bundle(CERT, None),
+        Err(PemError::MissingPrivateKey)
+    ));
+}
+
 #[test]
 fn verify_valid_hostname() {
     let server = Server::builder().build();
@@ -728,6 +1105,25 @@ fn verify_invalid_hostname() {
     client.connect_err();
 }
 
+#[test]
+fn verify_invalid_hostname_context_param() {
+    let mut server = Server::builder();
+
+    server.should_error();
+
+    let server = server.build();
+    let mut client = server.client_with_root_ca();
+
+    client.ctx().set_verify(SslVerifyMode::PEER);
+    client
+        .ctx()
+        .verify_param_mut()
+        .set_host("bogus.com")
+        .unwrap();
+
+    client.connect_err();
+}
+
 #[test]
 fn connector_valid_hostname() {
     let server = Server::builder().build();
@@ -838,56 +1234,621 @@ fn test_mozilla_server(new: fn(SslMethod) -> Result<SslAcceptorBuilder, ErrorSta
 }
 
 #[test]
-fn connector_client_server_mozilla_intermediate() {
-    test_mozilla_server(SslAcceptor::mozilla_intermediate);
-}
+fn reloadable_acceptor() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
 
-#[test]
-fn connector_client_server_mozilla_modern() {
-    test_mozilla_server(SslAcceptor::mozilla_modern);
-}
+    let acceptor = ReloadableAcceptor::new(|| {
+        let key = PKey::private_key_from_pem(KEY).unwrap();
+        let cert = X509::from_pem(CERT).unwrap();
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+        acceptor.set_private_key(&key)?;
+        acceptor.set_certificate(&cert)?;
+        acceptor.set_ticket_keys(&[0; 48])?;
+        Ok(acceptor.build())
+    })
+    .unwrap();
+
+    let before = acceptor.acceptor();
+    acceptor.reload().unwrap();
+    assert_ne!(
+        acceptor.acceptor().context().as_ptr(),
+        before.context().as_ptr()
+    );
 
-#[test]
-fn connector_client_server_mozilla_intermediate_v5() {
-    test_mozilla_server(SslAcceptor::mozilla_intermediate_v5);
-}
+    let t = thread::spawn(move || {
+        let stream = listener.accept().unwrap().0;
+        let mut stream = acceptor.accept(stream).unwrap();
 
-#[test]
-fn shutdown() {
-    let mut server = Server::builder();
-    server.io_cb(|mut s| {
-        assert_eq!(s.read(&mut [0]).unwrap(), 0);
-        assert_eq!(s.shutdown().unwrap(), ShutdownResult::Received);
+        stream.write_all(b"hello").unwrap();
     });
-    let server = server.build();
 
-    let mut s = server.client().connect();
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    let connector = connector.build();
 
-    assert_eq!(s.get_shutdown(), ShutdownState::empty());
-    assert_eq!(s.shutdown().unwrap(), ShutdownResult::Sent);
-    assert_eq!(s.get_shutdown(), ShutdownState::SENT);
-    assert_eq!(s.shutdown().unwrap(), ShutdownResult::Received);
-    assert_eq!(
-        s.get_shutdown(),
-        ShutdownState::SENT | ShutdownState::RECEIVED
-    );
-}
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let mut stream = connector.connect("foobar.com", stream).unwrap();
 
-#[test]
-fn client_ca_list() {
-    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
-    assert_eq!(names.len(), 1);
+    let mut buf = [0; 5];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(b"hello", &buf);
 
-    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    ctx.set_client_ca_list(names);
+    t.join().unwrap();
 }
 
 #[test]
-fn cert_store() {
-    let server = Server::builder().build();
-
-    let mut client = server.client();
-    let cert = X509::from_pem(ROOT_CERT).unwrap();
+fn identity() {
+    let identity = Identity::from_pem(CERT, KEY).unwrap();
+    assert_eq!(
+        identity.certificate().to_der().unwrap(),
+        X509::from_pem(CERT).unwrap().to_der().unwrap()
+    );
+    assert!(identity.chain().is_empty());
+    assert!(identity.private_key().is_some());
+    assert_eq!(identity.spiffe_id(), None);
+
+    let svid = Identity::from_x509_svid(
+        &identity.certificate().to_der().unwrap(),
+        &identity
+            .private_key()
+            .unwrap()
+            .private_key_to_der_pkcs8()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        svid.certificate().to_der().unwrap(),
+        identity.certificate().to_der().unwrap()
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let server_identity = identity.clone();
+    let t = thread::spawn(move || {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_identity(&server_identity).unwrap();
+        acceptor.set_ca_file("test/root-ca.pem").unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        let acceptor = acceptor.build();
+        let stream = listener.accept().unwrap().0;
+        let mut stream = acceptor.accept(stream).unwrap();
+
+        assert!(stream.ssl().peer_certificate().is_some());
+        assert_eq!(stream.ssl().peer_spiffe_id(), None);
+        stream.write_all(b"hello").unwrap();
+    });
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_identity(&svid).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    let connector = connector.build();
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let mut stream = connector.connect("foobar.com", stream).unwrap();
+
+    let mut buf = [0; 5];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(b"hello", &buf);
+
+    t.join().unwrap();
+}
+
+#[test]
+fn cert_callback() {
+    let mut server = Server::builder();
+    server.ctx().set_ca_file("test/root-ca.pem").unwrap();
+    server
+        .ctx()
+        .set_client_ca_list(X509Name::load_client_ca_file("test/root-ca.pem").unwrap());
+    server
+        .ctx()
+        .set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    server.io_cb(|mut stream| {
+        assert!(stream.ssl().peer_certificate().is_some());
+        stream.write_all(&[0]).unwrap();
+    });
+    let server = server.build();
+
+    let retried = Arc::new(AtomicBool::new(false));
+    let mut client = server.client();
+    client.ctx().set_cert_callback({
+        let retried = retried.clone();
+
+        move |ssl| {
+            assert!(!ssl.is_server());
+            assert_eq!(ssl.client_ca_list().unwrap().len(), 1);
+            assert!(ssl
+                .peer_verify_algorithms()
+                .contains(&SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256));
+
+            if !retried.swap(true, Ordering::SeqCst) {
+                return Err(CertCallbackError::RETRY);
+            }
+
+            ssl.set_identity(&Identity::from_pem(CERT, KEY).unwrap())
+                .unwrap();
+            Ok(())
+        }
+    });
+
+    let HandshakeError::WouldBlock(mid_handshake) = client.connect_err() else {
+        panic!("should be WouldBlock");
+    };
+    assert_eq!(mid_handshake.error().code(), ErrorCode::WANT_X509_LOOKUP);
+
+    let mut stream = mid_handshake.handshake().unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+    assert!(retried.load(Ordering::SeqCst));
+}
+
+#[test]
+fn cert_callback_without_certificate() {
+    let mut server = Server::builder();
+    server.ctx().set_verify(SslVerifyMode::PEER);
+    server.io_cb(|mut stream| {
+        assert!(stream.ssl().peer_certificate().is_none());
+        stream.write_all(&[0]).unwrap();
+    });
+    let server = server.build();
+
+    let called = Arc::new(AtomicBool::new(false));
+    let mut client = server.client();
+    client.ctx().set_cert_callback({
+        let called = called.clone();
+
+        move |_| {
+            called.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    });
+
+    client.connect();
+    assert!(called.load(Ordering::SeqCst));
+}
+
+#[test]
+fn certificate_request() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_client_ca_list(X509Name::load_client_ca_file("test/root-ca.pem").unwrap());
+    server.ctx().set_verify(SslVerifyMode::PEER);
+    server.io_cb(|mut stream| {
+        assert!(stream.ssl().certificate_requested());
+        assert!(stream.ssl().certificate_request().is_none());
+        stream.write_all(&[0]).unwrap();
+    });
+    let server = server.build();
+
+    let outcomes = Arc::new(Mutex::new(vec![]));
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    client.ctx().set_cert_callback(|_| Ok(()));
+    client.ctx().set_handshake_outcome_callback({
+        let outcomes = outcomes.clone();
+
+        move |_, outcome| outcomes.lock().unwrap().push(outcome.clone())
+    });
+
+    let mut stream = client.connect();
+    stream.read_exact(&mut [0]).unwrap();
+
+    let request = stream.ssl().certificate_request().unwrap();
+    assert_eq!(request.ca_names().unwrap().len(), 1);
+    assert_eq!(request.ca_names_der().len(), 1);
+    assert!(request
+        .signature_algorithms()
+        .contains(&SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256));
+    assert!(!request.certificate_types().is_empty());
+    assert!(stream.ssl().certificate_requested());
+
+    let outcomes = outcomes.lock().unwrap();
+    assert_eq!(
+        outcomes[0].client_authentication(),
+        ClientAuthentication::NoCertificate
+    );
+}
+
+#[test]
+fn certificate_request_not_sent() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client.ctx().set_cert_callback(|_| Ok(()));
+
+    let stream = client.connect();
+    assert!(stream.ssl().certificate_request().is_none());
+    assert!(!stream.ssl().certificate_requested());
+}
+
+#[test]
+fn connector_client_server_mozilla_intermediate() {
+    test_mozilla_server(SslAcceptor::mozilla_intermediate);
+}
+
+#[test]
+fn connector_client_server_mozilla_modern() {
+    test_mozilla_server(SslAcceptor::mozilla_modern);
+}
+
+#[test]
+fn connector_client_server_mozilla_intermediate_v5() {
+    test_mozilla_server(SslAcceptor::mozilla_intermediate_v5);
+}
+
+#[test]
+fn connector_client_server_mozilla_modern_v5() {
+    test_mozilla_server(SslAcceptor::mozilla_modern_v5);
+}
+
+#[test]
+fn connector_client_server_mozilla_old_v5() {
+    test_mozilla_server(SslAcceptor::mozilla_old_v5);
+}
+
+#[test]
+fn connector_client_server_custom_profile() {
+    test_mozilla_server(|method| {
+        SecurityProfileBuilder::mozilla_old()
+            .min_version(Some(SslVersion::TLS1_2))
+            .max_version(Some(SslVersion::TLS1_2))
+            .session_tickets(false)
+            .acceptor(method)
+    });
+}
+
+#[test]
+fn security_profile_versions() {
+    let mut acceptor = SecurityProfileBuilder::mozilla_modern()
+        .acceptor(SslMethod::tls())
+        .unwrap();
+    assert_eq!(acceptor.min_proto_version(), Some(SslVersion::TLS1_3));
+
+    let mut acceptor = SecurityProfileBuilder::mozilla_intermediate()
+        .session_tickets(false)
+        .acceptor(SslMethod::tls())
+        .unwrap();
+    assert_eq!(acceptor.min_proto_version(), Some(SslVersion::TLS1_2));
+    assert!(acceptor.options().contains(SslOptions::NO_TICKET));
+}
+
+#[test]
+fn context_builder_from_context() {
+    let mut builder = SslContext::builder(SslMethod::tls()).unwrap();
+    builder.set_certificate_chain_file("test/cert.pem").unwrap();
+    builder
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    builder.set_options(SslOptions::NO_TICKET);
+    builder
+        .set_min_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    builder.set_verify(SslVerifyMode::PEER);
+    builder.set_session_cache_mode(SslSessionCacheMode::OFF);
+    builder.set_ca_file("test/root-ca.pem").unwrap();
+    let ctx = builder.build();
+
+    let mut builder = SslContextBuilder::from_context(SslMethod::tls(), &ctx).unwrap();
+    assert!(builder.options().contains(SslOptions::NO_TICKET));
+    assert_eq!(builder.min_proto_version(), Some(SslVersion::TLS1_2));
+    builder
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let cert = X509::from_pem(CERT).unwrap();
+    builder.cert_store_mut().add_cert(cert).unwrap();
+    let derived = builder.build();
+
+    assert_eq!(derived.verify_mode(), SslVerifyMode::PEER);
+    assert_eq!(
+        derived.certificate().unwrap().to_der().unwrap(),
+        ctx.certificate().unwrap().to_der().unwrap()
+    );
+    assert!(derived
+        .private_key()
+        .unwrap()
+        .public_eq(ctx.private_key().unwrap()));
+    assert_ne!(derived.cert_store().as_ptr(), ctx.cert_store().as_ptr());
+    assert_eq!(derived.cert_store().objects().len(), 2);
+    assert_eq!(
+        derived.ciphers().unwrap().len(),
+        ctx.ciphers().unwrap().len()
+    );
+
+    // The original context is unaffected.
+    assert_eq!(ctx.cert_store().objects().len(), 1);
+    let mut original = SslContextBuilder::from_context(SslMethod::tls(), &ctx).unwrap();
+    assert_eq!(original.min_proto_version(), Some(SslVersion::TLS1_2));
+    assert_eq!(original.max_proto_version(), Some(SslVersion::TLS1_3));
+}
+
+#[test]
+fn connector_ocsp_stapling_required() {
+    let mut server = Server::builder();
+    server.ssl_cb(|ssl| ssl.set_ocsp_status(b"ocsp response").unwrap());
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let ssl = connector
+        .build()
+        .configure()
+        .unwrap()
+        .ocsp_stapling_policy(OcspStaplingPolicy::Validate(Arc::new(
+            |response: &[u8], _: &X509StoreContextRef| response == b"ocsp response",
+        )))
+        .into_ssl("foobar.com")
+        .unwrap();
+
+    let mut s = ssl.connect(server.connect_tcp()).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+    assert_eq!(s.ssl().ocsp_status(), Some(&b"ocsp response"[..]));
+}
+
+#[test]
+fn connector_ocsp_stapling_missing() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+
+    let ssl = connector
+        .build()
+        .configure()
+        .unwrap()
+        .ocsp_stapling_policy(OcspStaplingPolicy::RequirePresent)
+        .into_ssl("foobar.com")
+        .unwrap();
+
+    let err = ssl.connect(server.connect_tcp()).unwrap_err();
+    match err {
+        HandshakeError::Failure(s) => assert_eq!(
+            s.ssl().verify_result(),
+            X509VerifyResult::APPLICATION_VERIFICATION
+        ),
+        _ => panic!("unexpected error"),
+    }
+}
+
+#[test]
+fn connector_require_alpn() {
+    let mut server = Server::builder();
+    server.ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x02h2", client).ok_or(ssl::AlpnError::NO_APPLICATION_PROTOCOL)
+    });
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_alpn_protos(b"\x02h2").unwrap();
+
+    let ssl = connector
+        .build()
+        .configure()
+        .unwrap()
+        .require_alpn(true)
+        .into_ssl("foobar.com")
+        .unwrap();
+
+    let mut s = ssl.connect(server.connect_tcp()).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+    assert_eq!(s.ssl().selected_alpn_protocol(), Some(&b"h2"[..]));
+}
+
+#[test]
+fn connector_require_alpn_ignored() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_alpn_protos(b"\x02h2").unwrap();
+
+    let ssl = connector
+        .build()
+        .configure()
+        .unwrap()
+        .require_alpn(true)
+        .into_ssl("foobar.com")
+        .unwrap();
+
+    let err = ssl.connect(server.connect_tcp()).unwrap_err();
+    let s = match err {
+        HandshakeError::Failure(s) => s,
+        _ => panic!("unexpected error"),
+    };
+    assert_eq!(s.error().code(), ErrorCode::SSL);
+    assert_eq!(s.ssl().verify_result(), X509VerifyResult::OK);
+    assert_eq!(s.ssl().selected_alpn_protocol(), None);
+
+    // The server completes its handshake, and must be joined while the connection is open.
+    drop(server);
+}
+
+#[test]
+fn connector_verify_callback_chained() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_verify_callback(SslVerifyMode::PEER, |_, x509_ctx| {
+        x509_ctx.set_error(X509VerifyResult::CERT_REJECTED);
+        false
+    });
+
+    let ssl = connector
+        .build()
+        .configure()
+        .unwrap()
+        .ocsp_stapling_policy(OcspStaplingPolicy::RequirePresent)
+        .into_ssl("foobar.com")
+        .unwrap();
+
+    // The stapled response is missing, but the context's callback rejects the chain first.
+    let err = ssl.connect(server.connect_tcp()).unwrap_err();
+    match err {
+        HandshakeError::Failure(s) => {
+            assert_eq!(s.ssl().verify_result(), X509VerifyResult::CERT_REJECTED)
+        }
+        _ => panic!("unexpected error"),
+    }
+}
+
+fn sct_list() -> Vec<u8> {
+    let mut sct = vec![0];
+    sct.extend_from_slice(&[1; 32]);
+    sct.extend_from_slice(&1_600_000_000_000u64.to_be_bytes());
+    sct.extend_from_slice(&[0, 0, 4, 3, 0, 2, 0xaa, 0xbb]);
+
+    let mut list = ((sct.len() + 2) as u16).to_be_bytes().to_vec();
+    list.extend_from_slice(&(sct.len() as u16).to_be_bytes());
+    list.extend_from_slice(&sct);
+    list
+}
+
+#[test]
+fn ct_policy_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ssl_cb(|ssl| ssl.set_signed_cert_timestamp_list(&sct_list()).unwrap());
+    let server = server.build();
+
+    let mut client = server.client_with_root_ca();
+    client.ctx().set_ct_policy_callback(|ssl, _, scts| {
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        assert_eq!(ssl.signed_cert_timestamp_list(), Some(&sct_list()[..]));
+        assert_eq!(scts.len(), 1);
+        assert_eq!(scts[0].log_id(), &[1; 32]);
+        true
+    });
+
+    client.connect();
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn select_certificate_ocsp_response_and_scts() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_select_certificate_callback(|mut client_hello| {
+            let ssl = client_hello.ssl_mut();
+            ssl.set_ocsp_status(b"ocsp response").unwrap();
+            ssl.set_signed_cert_timestamp_list(&sct_list()).unwrap();
+            Ok(())
+        });
+    let server = server.build();
+
+    let mut client = server.client_with_root_ca();
+    client.ctx().enable_ocsp_stapling();
+    client.ctx().enable_signed_cert_timestamps();
+
+    let s = client.connect();
+    assert_eq!(s.ssl().ocsp_status(), Some(&b"ocsp response"[..]));
+    assert_eq!(s.ssl().signed_cert_timestamp_list(), Some(&sct_list()[..]));
+}
+
+#[test]
+fn ct_policy_callback_reject() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client_with_root_ca();
+    client
+        .ctx()
+        .set_ct_policy_callback(|_, _, scts| !scts.is_empty());
+
+    client.connect_err();
+}
+
+#[test]
+fn shutdown() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        assert_eq!(s.read(&mut [0]).unwrap(), 0);
+        assert_eq!(s.shutdown().unwrap(), ShutdownResult::Received);
+    });
+    let server = server.build();
+
+    let mut s = server.client().connect();
+
+    assert_eq!(s.get_shutdown(), ShutdownState::empty());
+    assert_eq!(s.shutdown().unwrap(), ShutdownResult::Sent);
+    assert_eq!(s.get_shutdown(), ShutdownState::SENT);
+    assert_eq!(s.shutdown().unwrap(), ShutdownResult::Received);
+    assert_eq!(
+        s.get_shutdown(),
+        ShutdownState::SENT | ShutdownState::RECEIVED
+    );
+}
+
+#[test]
+fn client_ca_list() {
+    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
+    assert_eq!(names.len(), 1);
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_client_ca_list(names);
+}
+
+#[test]
+fn ssl_client_ca_list() {
+    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_client_ca_list(names);
+
+    assert_eq!(ssl.client_ca_list().unwrap().len(), 1);
+}
+
+#[test]
+fn client_cert_signature_algorithms() {
+    let mut server = Server::builder();
+    server.ssl_cb(|ssl| {
+        ssl.set_verify_callback(SslVerifyMode::PEER, |_, x509| {
+            assert!(x509.current_cert().is_some());
+            true
+        });
+        ssl.set_verify_algorithm_prefs(&[SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256])
+            .unwrap();
+    });
+    let server = server.build();
+
+    let mut client = server.client_with_root_ca();
+    client
+        .ctx()
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    client
+        .ctx()
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    let mut client = client.build().builder();
+    client
+        .ssl()
+        .set_signing_algorithm_prefs(&[SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256])
+        .unwrap();
+
+    client.connect();
+}
+
+#[test]
+fn cert_store() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    let cert = X509::from_pem(ROOT_CERT).unwrap();
     client.ctx().cert_store_mut().add_cert(cert).unwrap();
     client.ctx().set_verify(SslVerifyMode::PEER);
 
@@ -895,77 +1856,632 @@ fn cert_store() {
 }
 
 #[test]
-fn idle_session() {
-    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
-    let ssl = Ssl::new(&ctx).unwrap();
-    assert!(ssl.session().is_none());
+fn idle_session() {
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.session().is_none());
+}
+
+#[test]
+fn active_session() {
+    let server = Server::builder().build();
+
+    let s = server.client().connect();
+
+    let session = s.ssl().session().unwrap();
+    let len = session.master_key_len();
+    let mut buf = vec![0; len - 1];
+    let copied = session.master_key(&mut buf);
+    assert_eq!(copied, buf.len());
+    let mut buf = vec![0; len + 1];
+    let copied = session.master_key(&mut buf);
+    assert_eq!(copied, len);
+}
+
+#[test]
+fn new_session_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_session_id_context(b"foo").unwrap();
+
+    let server = server.build();
+
+    let mut client = server.client();
+
+    client
+        .ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+    client
+        .ctx()
+        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
+
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn handshake_done_callback_false_start() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    server.ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x08http/1.1", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_mode(SslMode::ENABLE_FALSE_START);
+    client.ctx().set_alpn_protos(b"\x08http/1.1").unwrap();
+    client.ctx().set_handshake_done_callback(|ssl| {
+        assert!(!ssl.in_false_start());
+        CALLED_BACK.store(true, Ordering::SeqCst);
+    });
+
+    let s = client.connect();
+    assert!(!s.ssl().in_false_start());
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
 }
 
 #[test]
-fn active_session() {
+fn handshake_outcome_callback() {
+    static DONE: AtomicBool = AtomicBool::new(false);
+
     let server = Server::builder().build();
+    let outcomes = Arc::new(Mutex::new(vec![]));
 
-    let s = server.client().connect();
+    let client_with_callback = || {
+        let mut client = server.client();
+        client
+            .ctx()
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .unwrap();
+        client
+            .ctx()
+            .set_handshake_done_callback(|_| DONE.store(true, Ordering::SeqCst));
+        let outcomes = outcomes.clone();
+        client
+            .ctx()
+            .set_handshake_outcome_callback(move |ssl, outcome| {
+                assert!(!ssl.is_server());
+                outcomes.lock().unwrap().push(outcome.clone());
+            });
+        client.build().builder()
+    };
+
+    let s = client_with_callback().connect();
+    let session = s.ssl().session().unwrap().to_owned();
+
+    let mut client = client_with_callback();
+    unsafe { client.ssl().set_session(&session).unwrap() };
+    client.connect();
+
+    assert!(DONE.load(Ordering::SeqCst));
+
+    let outcomes = outcomes.lock().unwrap();
+    assert_eq!(outcomes.len(), 2);
+
+    assert!(!outcomes[0].is_server());
+    assert_eq!(outcomes[0].version(), Some(SslVersion::TLS1_2));
+    assert!(!outcomes[0].is_resumed());
+    assert!(!outcomes[0].early_data_accepted());
+    assert!(outcomes[0].group().is_some());
+    assert!(outcomes[0].peer_signature_algorithm().is_some());
+    assert!(outcomes[0].duration().is_some());
+
+    assert_eq!(outcomes[1].resumption(), Some(ResumptionMechanism::Ticket));
+}
+
+#[test]
+fn stats() {
+    let mut server = Server::builder();
+    server.ctx().enable_stats();
+    server.io_cb(|stream| {
+        let stats = stream.ssl().ssl_context().stats().unwrap();
+        assert_eq!(stats.accepts(), 1);
+        assert_eq!(stats.connects(), 0);
+        assert_eq!(stats.completed(), 1);
+    });
+    let server = server.build();
+
+    let mut failing_server = Server::builder();
+    failing_server.should_error();
+    let failing_server = failing_server.build();
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    assert!(ctx.build().stats().is_none());
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.enable_stats();
+    let ctx = ctx.build();
+
+    let mut stream = Ssl::new(&ctx)
+        .unwrap()
+        .connect(server.connect_tcp())
+        .unwrap();
+    stream.read_exact(&mut [0]).unwrap();
+
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_verify(SslVerifyMode::PEER);
+    assert!(ssl.connect(failing_server.connect_tcp()).is_err());
+
+    let stats = ctx.stats().unwrap();
+    assert_eq!(stats.accepts(), 0);
+    assert_eq!(stats.connects(), 2);
+    assert_eq!(stats.completed(), 1);
+    assert_eq!(stats.resumed(), 0);
+    assert_eq!(stats.total_failures(), 1);
+    assert!(stats
+        .failures()
+        .contains(&(HandshakeFailure::Certificate, 1)));
+    assert_eq!(stats.versions(), &[(SslVersion::TLS1_3, 1)]);
+    assert_eq!(stats.ciphers().len(), 1);
+}
+
+#[test]
+fn key_update() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        let mut buf = [0; 5];
+        s.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+        s.write_all(b"world").unwrap();
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    let mut s = client.connect();
+
+    s.ssl_mut().key_update(SslKeyUpdate::REQUESTED).unwrap();
+    s.write_all(b"hello").unwrap();
+
+    let mut buf = [0; 5];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"world");
+}
+
+#[test]
+fn key_update_tls12() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let mut s = client.connect();
+
+    assert!(s.ssl_mut().key_update(SslKeyUpdate::NOT_REQUESTED).is_err());
+}
+
+#[test]
+fn renegotiate_mode() {
+    let server = Server::builder().build();
+
+    let client = server.client().build();
+    let mut client = client.builder();
+    client
+        .ssl()
+        .set_renegotiate_mode(SslRenegotiateMode::IGNORE);
+    let s = client.connect();
+
+    assert!(!s.ssl().renegotiate_pending());
+    assert_eq!(s.ssl().total_renegotiations(), 0);
+}
+
+#[test]
+fn ticket_key_manager_previous_keys_shared() {
+    use crate::ssl::TicketKey;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let manager =
+        TicketKeyManager::from_master_secret(b"secret", Duration::from_secs(3600)).unwrap();
+    let epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        / 3600;
+    let previous = TicketKey::derive(b"secret", epoch - 1).unwrap();
+    assert!(manager.find_key(previous.name()).unwrap().is_some());
+
+    // Clones are handles to the same keys.
+    manager.clone().set_previous_keys(0);
+    assert_eq!(manager.find_key(previous.name()).unwrap(), None);
+}
+
+#[test]
+fn ticket_key_manager() {
+    let manager =
+        TicketKeyManager::from_master_secret(b"secret", Duration::from_secs(3600)).unwrap();
+    let key = manager.current_key().unwrap();
+    assert_eq!(
+        manager.find_key(key.name()).unwrap(),
+        Some((key.clone(), false))
+    );
+    assert_eq!(manager.find_key(&[0; 16]).unwrap(), None);
+
+    // Servers sharing the master secret share the keys.
+    let other = TicketKeyManager::from_master_secret(b"secret", Duration::from_secs(3600)).unwrap();
+    assert_eq!(other.current_key().unwrap(), key);
+
+    let mut server = Server::builder();
+    server.ctx().set_ticket_key_manager(manager);
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let s = client.connect();
+    assert!(!s.ssl().session_reused());
+    let session = s.ssl().session().unwrap().to_owned();
+
+    let mut server = Server::builder();
+    server.ctx().set_ticket_key_manager(other);
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let mut client = client.build().builder();
+    unsafe { client.ssl().set_session(&session).unwrap() };
+    let s = client.connect();
+    assert!(s.ssl().session_reused());
+}
+
+#[test]
+fn tls13_resumption_diagnostics() {
+    let session = Arc::new(Mutex::new(None));
+
+    let mut server = Server::builder();
+    server.ctx().set_ticket_keys(&[0; 48]).unwrap();
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT);
+    client.ctx().set_new_session_callback({
+        let session = session.clone();
+        move |_, new| *session.lock().unwrap() = Some(new)
+    });
+    let s = client.connect();
+    assert_eq!(s.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert!(!s.ssl().resumed_with_psk_dhe());
+
+    let session = session.lock().unwrap().take().unwrap();
+    assert_eq!(session.protocol_version(), SslVersion::TLS1_3);
+    assert!(session.ticket_lifetime_hint() > 0);
+    assert!(session.ticket_age_add().is_some());
+    let psk = session.resumption_psk().unwrap();
+    assert_eq!(psk.len(), session.master_key_len());
+
+    // The ticket age add survives serialization.
+    let der = SslSession::from_der(&session.to_der().unwrap()).unwrap();
+    assert_eq!(der.ticket_age_add(), session.ticket_age_add());
+    assert_eq!(der.resumption_psk(), Some(psk));
+
+    let mut server = Server::builder();
+    server.ctx().set_ticket_keys(&[0; 48]).unwrap();
+    server.io_cb(|s| {
+        assert!(s.ssl().session_reused());
+        assert!(s.ssl().resumed_with_psk_dhe());
+        assert!(s.ssl().ticket_age_skew().abs() <= 1);
+    });
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    // SAFETY: both client contexts use X.509 certificates.
+    unsafe { client.ssl().set_session_checked(&session).unwrap() };
+    let s = client.connect();
+    assert!(s.ssl().session_reused());
+    assert!(s.ssl().resumed_with_psk_dhe());
+}
+
+#[test]
+fn session_der_resumption() {
+    let server = Server::builder().build();
 
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let s = client.connect();
     let session = s.ssl().session().unwrap();
-    let len = session.master_key_len();
-    let mut buf = vec![0; len - 1];
-    let copied = session.master_key(&mut buf);
-    assert_eq!(copied, buf.len());
-    let mut buf = vec![0; len + 1];
-    let copied = session.master_key(&mut buf);
-    assert_eq!(copied, len);
+    assert!(session.is_resumable());
+    assert!(session.has_ticket());
+    assert!(!session.is_expired());
+    assert_eq!(session.max_early_data(), 0);
+    assert_eq!(session.resumption_psk(), None);
+    assert_eq!(session.ticket_age_add(), None);
+    assert_eq!(
+        session.cipher().unwrap().name(),
+        s.ssl().current_cipher().unwrap().name()
+    );
+
+    // Round-trip the session as a persistent cache would.
+    let der = session.to_der().unwrap();
+    let session = SslSession::from_der(&der).unwrap();
+    assert_eq!(session.protocol_version(), SslVersion::TLS1_2);
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let mut client = client.build().builder();
+    // SAFETY: both client contexts use X.509 certificates.
+    unsafe { client.ssl().set_session_checked(&session).unwrap() };
+    let s = client.connect();
+    assert!(s.ssl().session_reused());
+
+    // A client requiring TLS 1.3 cannot use the session.
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    let mut client = client.build().builder();
+    assert!(matches!(
+        unsafe { client.ssl().set_session_checked(&session) },
+        Err(SessionError::Version(SslVersion::TLS1_2))
+    ));
+}
+
+#[test]
+fn session_checked_dtls_versions() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let s = client.connect();
+    let der = s.ssl().session().unwrap().to_der().unwrap();
+
+    // SAFETY: the TLS and DTLS contexts both use X.509 certificates.
+
+    // A TLS session cannot be used on a DTLS connection.
+    let ctx = SslContext::builder(SslMethod::dtls()).unwrap().build();
+    let session = SslSession::from_der(&der).unwrap();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert!(matches!(
+        unsafe { ssl.set_session_checked(&session) },
+        Err(SessionError::Version(SslVersion::TLS1_2))
+    ));
+
+    // DTLS 1.2 lies between DTLS 1.0 and DTLS 1.3, although its version number is smaller.
+    let session = SslSession::from_der(&der).unwrap();
+    unsafe {
+        assert_eq!(
+            crate::ffi::SSL_SESSION_set_protocol_version(
+                session.as_ptr(),
+                crate::ffi::DTLS1_2_VERSION as _,
+            ),
+            1
+        );
+    }
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    unsafe { ssl.set_session_checked(&session).unwrap() };
+}
+
+#[test]
+fn new_session_callback_swapped_ctx() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_session_id_context(b"foo").unwrap();
+
+    let server = server.build();
+
+    let mut client = server.client();
+
+    client
+        .ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+    client
+        .ctx()
+        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
+
+    let mut client = client.build().builder();
+
+    let ctx = SslContextBuilder::new(SslMethod::tls()).unwrap().build();
+    client.ssl().set_ssl_context(&ctx).unwrap();
+
+    client.connect();
+
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn mem_ssl() {
+    fn transfer(from: &mut MemSsl, to: &mut MemSsl) {
+        let mut buf = vec![0; from.bio().pending()];
+        let n = from.bio_mut().read(&mut buf);
+        assert_eq!(to.bio_mut().write(&buf[..n]), n);
+    }
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    ssl.set_accept_state();
+    let mut server = MemSsl::new(ssl, 16 * 1024).unwrap();
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    ssl.set_connect_state();
+    let mut client = MemSsl::new(ssl, 16 * 1024).unwrap();
+
+    let state = client.ssl_mut().do_handshake_step().unwrap();
+    assert_eq!(state, HandshakeState::WantRead);
+    assert!(client.bio().pending() > 0);
+
+    loop {
+        transfer(&mut client, &mut server);
+        let server_state = server.ssl_mut().do_handshake_step().unwrap();
+        transfer(&mut server, &mut client);
+        let client_state = client.ssl_mut().do_handshake_step().unwrap();
+
+        if server_state == HandshakeState::Complete && client_state == HandshakeState::Complete {
+            break;
+        }
+    }
+
+    assert_eq!(client.ssl_write(b"hello").unwrap(), 5);
+    transfer(&mut client, &mut server);
+
+    let mut buf = [0; 5];
+    assert_eq!(server.ssl_read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    let err = server.ssl_read(&mut buf).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::WANT_READ);
+
+    // Without more ciphertext to come, the missing close_notify is an error.
+    server.bio_mut().shutdown();
+    let err = server.ssl_read(&mut buf).unwrap_err();
+    assert_ne!(err.code(), ErrorCode::WANT_READ);
 }
 
-#[test]
-fn new_session_callback() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+#[test]
+fn ssl_engine() {
+    fn transfer(from: &mut SslEngine, to: &mut SslEngine) {
+        let mut buf = vec![];
+        from.take_outbound(&mut buf).unwrap();
+        assert_eq!(to.process_inbound(&buf).unwrap(), buf.len());
+    }
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    let mut server = SslEngine::accept(Ssl::new(&ctx.build()).unwrap()).unwrap();
+    assert!(server.wants_read());
+    assert!(!server.wants_write());
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut client = SslEngine::connect(Ssl::new(&ctx).unwrap()).unwrap();
+    assert!(client.wants_write());
+
+    while client.is_handshaking() || server.is_handshaking() {
+        transfer(&mut client, &mut server);
+        transfer(&mut server, &mut client);
+    }
 
-    let mut server = Server::builder();
-    server.ctx().set_session_id_context(b"foo").unwrap();
+    let mut buf = [0; 5];
+    assert_eq!(server.read_plaintext(&mut buf).unwrap(), 0);
+    assert!(server.wants_read());
+
+    assert_eq!(client.write_plaintext(b"hello").unwrap(), 5);
+    assert!(client.wants_write());
+    transfer(&mut client, &mut server);
+    assert_eq!(server.read_plaintext(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    client.close().unwrap();
+    transfer(&mut client, &mut server);
+    assert_eq!(server.read_plaintext(&mut buf).unwrap(), 0);
+    assert!(server.is_closed());
+    assert!(!server.wants_read());
+}
 
-    let server = server.build();
+#[test]
+fn ssl_engine_small_buffers() {
+    fn transfer(from: &mut SslEngine, to: &mut SslEngine) {
+        let mut buf = vec![];
+        from.take_outbound(&mut buf).unwrap();
+
+        let mut consumed = to.process_inbound(&buf).unwrap();
+        while consumed < buf.len() {
+            // Session tickets sent after the handshake are only processed when reading.
+            assert_eq!(to.read_plaintext(&mut [0; 1]).unwrap(), 0);
+            consumed += to.process_inbound(&buf[consumed..]).unwrap();
+        }
+    }
 
-    let mut client = server.client();
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate_file(Path::new("test/cert.pem"), SslFiletype::PEM)
+        .unwrap();
+    ctx.set_private_key_file(Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut server = SslEngine::accept_with_buffer_size(ssl, 512).unwrap();
 
-    client
-        .ctx()
-        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
-    client
-        .ctx()
-        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let ssl = Ssl::new(&ctx).unwrap();
+    let mut client = SslEngine::connect_with_buffer_size(ssl, 512).unwrap();
 
-    client.connect();
+    // The server's flight is larger than the buffers.
+    while client.is_handshaking() || server.is_handshaking() {
+        transfer(&mut client, &mut server);
+        transfer(&mut server, &mut client);
+    }
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let mut buf = [0; 5];
+    assert_eq!(client.write_plaintext(b"hello").unwrap(), 5);
+    transfer(&mut client, &mut server);
+    assert_eq!(server.read_plaintext(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
 }
 
 #[test]
-fn new_session_callback_swapped_ctx() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
-
-    let mut server = Server::builder();
-    server.ctx().set_session_id_context(b"foo").unwrap();
-
-    let server = server.build();
+fn tls_unique() {
+    let server = Server::builder().build();
 
     let mut client = server.client();
-
-    client
-        .ctx()
-        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
     client
         .ctx()
-        .set_new_session_callback(|_, _| CALLED_BACK.store(true, Ordering::SeqCst));
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let s = client.connect();
 
-    let mut client = client.build().builder();
+    // In a full handshake, the first Finished message is the client's.
+    let mut finished = [0; 64];
+    let len = s.ssl().finished(&mut finished);
+    assert_eq!(s.ssl().tls_unique().unwrap(), &finished[..len]);
 
-    let ctx = SslContextBuilder::new(SslMethod::tls()).unwrap().build();
-    client.ssl().set_ssl_context(&ctx).unwrap();
+    let server = Server::builder().build();
+    let s = server.client().connect();
+    assert_eq!(s.ssl().version2(), Some(SslVersion::TLS1_3));
+    assert!(s.ssl().tls_unique().is_err());
+}
 
-    client.connect();
+#[test]
+fn typed_ex_data() {
+    struct Counter(u32);
 
-    assert!(CALLED_BACK.load(Ordering::SeqCst));
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.get::<Counter>().is_none());
+
+    ssl.set(Counter(1)).unwrap();
+    ssl.set(String::from("foo")).unwrap();
+    ssl.get_mut::<Counter>().unwrap().0 += 1;
+    assert_eq!(ssl.get::<Counter>().unwrap().0, 2);
+
+    ssl.set(Counter(5)).unwrap();
+    assert_eq!(ssl.take::<Counter>().unwrap().0, 5);
+    assert!(ssl.get::<Counter>().is_none());
+    assert!(ssl.take::<Counter>().is_none());
+    assert_eq!(ssl.get::<String>().unwrap(), "foo");
 }
 
 #[test]
@@ -1015,6 +2531,30 @@ fn keying_export() {
     assert_eq!(buf, buf2);
 }
 
+#[test]
+fn channel_bindings() {
+    let mut server = Server::builder();
+    server.io_cb(|s| {
+        let bindings = s.ssl().channel_bindings().unwrap();
+        assert_eq!(bindings.tls_exporter.unwrap().len(), 32);
+        assert_eq!(bindings.tls_server_end_point.unwrap().len(), 32);
+    });
+    let server = server.build();
+
+    let s = server.client().connect();
+    let bindings = s.ssl().channel_bindings().unwrap();
+
+    let cert = s.ssl().peer_certificate().unwrap();
+    let digest = cert.digest(MessageDigest::sha256()).unwrap();
+    assert_eq!(bindings.tls_server_end_point.as_deref(), Some(&digest[..]));
+
+    let mut exporter = [0; 32];
+    s.ssl()
+        .export_keying_material(&mut exporter, "EXPORTER-Channel-Binding", None)
+        .unwrap();
+    assert_eq!(bindings.tls_exporter.as_deref(), Some(&exporter[..]));
+}
+
 #[test]
 fn no_version_overlap() {
     let mut server = Server::builder();
@@ -1106,6 +2646,146 @@ fn sni_callback_swapped_ctx() {
     assert!(CALLED_BACK.load(Ordering::SeqCst));
 }
 
+#[test]
+fn sni_router_lookup() {
+    let exact = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let wildcard = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let default = SslContext::builder(SslMethod::tls()).unwrap().build();
+
+    let mut routes = SniRoutes::new();
+    routes
+        .insert("Foobar.com", exact.clone())
+        .insert("*.foobar.com", wildcard.clone());
+    let router = SniRouter::new(routes.clone());
+
+    let resolve = |name| router.resolve(name).map(|ctx| ctx.as_ptr());
+    assert_eq!(resolve(Some("foobar.com.")), Some(exact.as_ptr()));
+    assert_eq!(resolve(Some("www.FOOBAR.com")), Some(wildcard.as_ptr()));
+    assert_eq!(resolve(Some("a.www.foobar.com")), None);
+    assert_eq!(resolve(Some("example.com")), None);
+    assert_eq!(resolve(None), None);
+
+    routes.set_default(default.clone());
+    router.set_routes(routes);
+
+    let resolve = |name| router.resolve(name).map(|ctx| ctx.as_ptr());
+    assert_eq!(resolve(Some("example.com")), Some(default.as_ptr()));
+    assert_eq!(resolve(None), Some(default.as_ptr()));
+}
+
+#[test]
+fn sni_router() {
+    let mut server = Server::builder();
+
+    let keyed_ctx =
+        mem::replace(server.ctx(), SslContext::builder(SslMethod::tls()).unwrap()).build();
+
+    let mut routes = SniRoutes::new();
+    routes.insert("foobar.com", keyed_ctx);
+    server.ctx().set_sni_router(SniRouter::new(routes));
+
+    let server = server.build();
+
+    let mut client = server.client_with_root_ca().build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    client.connect();
+}
+
+#[test]
+fn sni_router_unrecognized_name() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_sni_router(SniRouter::new(SniRoutes::new()));
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    client.connect_err();
+}
+
+#[test]
+fn acme_tls_alpn_challenge() {
+    let challenges = AcmeChallenges::new();
+    challenges.insert("foobar.com", "token.thumbprint").unwrap();
+
+    let mut server = Server::builder();
+    server.ctx().set_acme_challenges(challenges);
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_alpn_protos(ACME_TLS_ALPN_PROTOCOL)
+        .unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    let s = client.connect();
+
+    assert_eq!(s.ssl().selected_alpn_protocol(), Some(&b"acme-tls/1"[..]));
+
+    let cert = s.ssl().peer_certificate().unwrap();
+    let alt_names = cert.subject_alt_names().unwrap();
+    assert_eq!(alt_names.len(), 1);
+    assert_eq!(alt_names[0].dnsname(), Some("foobar.com"));
+
+    let mut authorization = vec![0x04, 0x20];
+    authorization.extend_from_slice(&sha256(b"token.thumbprint"));
+    let identifier = cert
+        .extensions()
+        .find(|ext| ext.data().as_slice() == &authorization[..])
+        .unwrap();
+    assert!(identifier.critical());
+}
+
+#[test]
+fn acme_tls_alpn_unknown_domain() {
+    let challenges = AcmeChallenges::new();
+    challenges.insert("foobar.com", "token.thumbprint").unwrap();
+    assert!(challenges.remove("FOOBAR.com."));
+
+    let mut server = Server::builder();
+    server.ctx().set_acme_challenges(challenges);
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_alpn_protos(ACME_TLS_ALPN_PROTOCOL)
+        .unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_hostname("foobar.com").unwrap();
+    client.connect_err();
+}
+
+#[test]
+fn acme_tls_alpn_other_connections() {
+    let challenges = AcmeChallenges::new();
+    challenges.insert("foobar.com", "token.thumbprint").unwrap();
+
+    let mut server = Server::builder();
+    server.ctx().set_acme_challenges(challenges);
+    server.ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x02h2", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    let server = server.build();
+
+    let mut client = server.client_with_root_ca();
+    client
+        .ctx()
+        .set_alpn_protos(b"\x02h2\x0aacme-tls/1")
+        .unwrap();
+    let s = client.connect();
+
+    assert_eq!(s.ssl().selected_alpn_protocol(), Some(&b"h2"[..]));
+    assert_eq!(
+        s.ssl().peer_certificate().unwrap().to_der().unwrap(),
+        X509::from_pem(CERT).unwrap().to_der().unwrap()
+    );
+}
+
 #[test]
 fn session_cache_size() {
     let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
@@ -1114,6 +2794,44 @@ fn session_cache_size() {
     assert_eq!(ctx.session_cache_size(), 1234);
 }
 
+#[test]
+fn ssl_set_cipher_list() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+
+    let mut client = client.build().builder();
+    client
+        .ssl()
+        .set_strict_cipher_list("ECDHE-RSA-AES256-GCM-SHA384")
+        .unwrap();
+
+    let s = client.connect();
+    assert_eq!(
+        s.ssl().current_cipher().unwrap().name(),
+        "ECDHE-RSA-AES256-GCM-SHA384"
+    );
+
+    let mut ssl = Ssl::new(&SslContext::builder(SslMethod::tls()).unwrap().build()).unwrap();
+    ssl.set_strict_cipher_list("NOT-A-CIPHER").unwrap_err();
+}
+
+#[cfg(not(feature = "kx-safe-default"))]
+#[test]
+fn ssl_set_curves() {
+    let server = Server::builder().build();
+
+    let mut client = server.client().build().builder();
+    client.ssl().set_curves(&[SslCurve::SECP384R1]).unwrap();
+
+    let s = client.connect();
+    assert_eq!(s.ssl().negotiated_group(), Some("P-384"));
+}
+
 #[cfg(feature = "kx-safe-default")]
 #[test]
 fn client_set_default_curves_list() {
@@ -1133,3 +2851,168 @@ fn server_set_default_curves_list() {
     // Panics if Kyber768 missing in boringSSL.
     ssl.server_set_default_curves_list();
 }
+
+#[test]
+fn handshake_budget() {
+    let budget = HandshakeBudget::new(2);
+    let shared = budget.clone();
+
+    assert!(budget.try_take());
+    assert!(shared.try_take());
+    assert!(!budget.try_take());
+    assert_eq!(shared.remaining(), 0);
+
+    budget.add(1);
+    assert_eq!(shared.remaining(), 1);
+
+    budget.add(usize::MAX);
+    assert_eq!(budget.remaining(), usize::MAX);
+}
+
+#[test]
+fn dos_protection_admits_within_budget() {
+    let budget = HandshakeBudget::new(1);
+    let server_budget = budget.clone();
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_dos_protection_callback(|client_hello| HandshakeBudget::admit(&client_hello));
+    server.ssl_cb(move |ssl| ssl.set_handshake_budget(server_budget.clone()));
+    let server = server.build();
+
+    server.client().connect();
+
+    assert_eq!(budget.remaining(), 0);
+}
+
+#[test]
+fn dos_protection_rejects_exhausted_budget() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_dos_protection_callback(|client_hello| HandshakeBudget::admit(&client_hello));
+    server.ssl_cb(|ssl| {
+        ssl.set_handshake_budget(HandshakeBudget::new(0));
+        assert_eq!(ssl.handshake_budget().unwrap().remaining(), 0);
+    });
+    server.should_error();
+    let server = server.build();
+
+    server.client().connect_err();
+}
+
+#[test]
+fn dos_protection_client_hello_size() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_dos_protection_callback(|client_hello| client_hello.as_bytes().len() < 64);
+    server.should_error();
+    let server = server.build();
+
+    server.client().connect_err();
+}
+
+#[test]
+fn max_cert_list() {
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+
+    ssl.set_max_cert_list(4096);
+    assert_eq!(ssl.max_cert_list(), 4096);
+    ssl.set_max_send_fragment(512).unwrap();
+}
+
+#[test]
+fn error_kind_certificate_verify_failed() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let connector = SslConnector::builder(SslMethod::tls()).unwrap().build();
+
+    let s = server.connect_tcp();
+    let HandshakeError::Failure(mid_handshake) = connector.connect("foobar.com", s).unwrap_err()
+    else {
+        panic!("should be Failure");
+    };
+
+    let error = mid_handshake.error();
+    assert!(!error.is_retryable());
+    assert!(error
+        .ssl_error()
+        .unwrap()
+        .contains(crate::error::ErrorKind::CertificateVerifyFailed));
+}
+
+#[test]
+fn connector_trusted_certs() {
+    let server = Server::builder().build();
+
+    let roots = X509::stack_from_pem(ROOT_CERT).unwrap();
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_trusted_certs(roots).unwrap();
+
+    let s = server.connect_tcp();
+    let mut s = connector.build().connect("foobar.com", s).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_trusted_certs_replace_defaults() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("test/root-ca.pem").unwrap();
+    connector.set_trusted_certs(vec![]).unwrap();
+
+    let s = server.connect_tcp();
+    connector.build().connect("foobar.com", s).unwrap_err();
+}
+
+#[test]
+fn capture_client_hello() {
+    let capture = || {
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_alpn_protos(b"\x02h2\x08http/1.1").unwrap();
+        connector
+            .build()
+            .configure()
+            .unwrap()
+            .capture_client_hello("foobar.com")
+            .unwrap()
+    };
+
+    let records = capture();
+    // A handshake record.
+    assert_eq!(records[0], 22);
+
+    let extensions = |records: &[u8]| {
+        ClientHello::replay(records, |client_hello| {
+            assert_eq!(
+                client_hello.servername(ssl::NameType::HOST_NAME),
+                Some("foobar.com")
+            );
+            [
+                ExtensionType::SERVER_NAME,
+                ExtensionType::APPLICATION_LAYER_PROTOCOL_NEGOTIATION,
+                ExtensionType::SUPPORTED_VERSIONS,
+            ]
+            .map(|ext| client_hello.get_extension(ext).map(ToOwned::to_owned))
+        })
+        .unwrap()
+    };
+
+    let first = extensions(&records);
+    assert_eq!(
+        first[1].as_deref(),
+        Some(&b"\x00\x0c\x02h2\x08http/1.1"[..])
+    );
+    assert_eq!(first, extensions(&capture()));
+
+    let err = ClientHello::replay(&records[..records.len() - 1], |_| ()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::WANT_READ);
+}
@@ -7,14 +7,15 @@ use crate::pkey::PKey;
 use crate::rsa::Padding;
 use crate::sign::{RsaPssSaltlen, Signer};
 use crate::ssl::{
-    ErrorCode, HandshakeError, PrivateKeyMethod, PrivateKeyMethodError, SslRef,
+    ErrorCode, HandshakeError, PendingOperation, PrivateKeyMethod, PrivateKeyMethodError, SslRef,
     SslSignatureAlgorithm,
 };
 use crate::x509::X509;
 use std::cmp;
 use std::io::{Read, Write};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{mpsc, Arc};
+use std::thread;
 
 #[allow(clippy::type_complexity)]
 pub(super) struct Method {
@@ -159,6 +160,31 @@ fn test_sign_failure() {
     assert!(called_sign.load(Ordering::SeqCst));
 }
 
+#[test]
+fn test_sign_failure_reason() {
+    let mut builder = builder_with_private_key_method(
+        Method::new().sign(|_, _, _, _| Err(PrivateKeyMethodError::failure("key unavailable"))),
+    );
+
+    builder.err_cb(|error| {
+        assert_eq!(error.private_key_failure_reason(), Some("key unavailable"));
+
+        let HandshakeError::Failure(mid_handshake) = error else {
+            panic!("should be Failure");
+        };
+
+        let errors = mid_handshake.error().ssl_error().unwrap().errors();
+        assert!(errors
+            .iter()
+            .any(|error| error.data() == Some("key unavailable")));
+    });
+
+    let server = builder.build();
+    let client = server.client_with_root_ca();
+
+    client.connect_err();
+}
+
 #[test]
 fn test_sign_retry_complete_failure() {
     let called_complete = Arc::new(AtomicUsize::new(0));
@@ -268,6 +294,59 @@ fn test_sign_retry_complete_ok() {
     client.connect();
 }
 
+#[test]
+fn test_sign_pending_operation() {
+    let mut builder = builder_with_private_key_method(
+        Method::new()
+            .sign(|ssl, input, _, _| {
+                let operation = PendingOperation::new();
+                ssl.set_pending_private_key_operation(operation.clone());
+
+                let input = input.to_owned();
+                thread::spawn(move || {
+                    let mut output = vec![0; 256];
+                    let len = sign_with_default_config(&input, &mut output);
+                    output.truncate(len);
+
+                    operation.complete(Ok(output));
+                });
+
+                Err(PrivateKeyMethodError::RETRY)
+            })
+            .complete(|ssl, output| ssl.private_key_operation_complete(output)),
+    );
+
+    builder.err_cb(|error| {
+        let HandshakeError::WouldBlock(mid_handshake) = error else {
+            panic!("should be WouldBlock");
+        };
+
+        assert_eq!(
+            mid_handshake.error().code(),
+            ErrorCode::WANT_PRIVATE_KEY_OPERATION
+        );
+
+        let (tx, rx) = mpsc::channel();
+        mid_handshake
+            .ssl()
+            .pending_private_key_operation()
+            .unwrap()
+            .on_complete(move || tx.send(()).unwrap());
+        rx.recv().unwrap();
+
+        let mut socket = mid_handshake.handshake().unwrap();
+
+        assert!(socket.ssl().pending_private_key_operation().is_none());
+
+        socket.write_all(&[0]).unwrap();
+    });
+
+    let server = builder.build();
+    let client = server.client_with_root_ca();
+
+    client.connect();
+}
+
 fn sign_with_default_config(input: &[u8], output: &mut [u8]) -> usize {
     let pkey = PKey::private_key_from_pem(KEY).unwrap();
     let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
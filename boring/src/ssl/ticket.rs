@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::hkdf::hkdf;
+use crate::rand::rand_bytes;
+
+/// A key used to encrypt and authenticate session tickets.
+///
+/// Tickets are encrypted with AES-128-CBC and authenticated with HMAC-SHA256.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TicketKey {
+    name: [u8; 16],
+    hmac_key: [u8; 32],
+    aes_key: [u8; 16],
+}
+
+impl TicketKey {
+    /// Creates a key from its parts.
+    ///
+    /// The name identifies the key in the tickets it encrypts, and must be unique among the
+    /// keys in use.
+    pub fn new(name: [u8; 16], hmac_key: [u8; 32], aes_key: [u8; 16]) -> TicketKey {
+        TicketKey {
+            name,
+            hmac_key,
+            aes_key,
+        }
+    }
+
+    /// Generates a random key.
+    pub fn generate() -> Result<TicketKey, ErrorStack> {
+        let mut key = TicketKey::new([0; 16], [0; 32], [0; 16]);
+        rand_bytes(&mut key.name)?;
+        rand_bytes(&mut key.hmac_key)?;
+        rand_bytes(&mut key.aes_key)?;
+        Ok(key)
+    }
+
+    /// Derives a key from a master secret using HKDF-SHA256.
+    ///
+    /// Servers sharing `secret` derive the same key for a given `epoch`, which lets them resume
+    /// each other's sessions without distributing the keys themselves.
+    pub fn derive(secret: &[u8], epoch: u64) -> Result<TicketKey, ErrorStack> {
+        const INFO: &[u8] = b"boring session ticket key";
+
+        let mut info = INFO.to_vec();
+        info.extend_from_slice(&epoch.to_be_bytes());
+
+        let mut out = [0; 64];
+        hkdf(MessageDigest::sha256(), secret, &[], &info, &mut out)?;
+
+        let mut key = TicketKey::new([0; 16], [0; 32], [0; 16]);
+        key.name.copy_from_slice(&out[..16]);
+        key.hmac_key.copy_from_slice(&out[16..48]);
+        key.aes_key.copy_from_slice(&out[48..]);
+        Ok(key)
+    }
+
+    /// Returns the name of the key.
+    pub fn name(&self) -> &[u8; 16] {
+        &self.name
+    }
+
+    pub(super) fn hmac_key(&self) -> &[u8; 32] {
+        &self.hmac_key
+    }
+
+    pub(super) fn aes_key(&self) -> &[u8; 16] {
+        &self.aes_key
+    }
+}
+
+impl fmt::Debug for TicketKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TicketKey")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+type DeriveKey = dyn Fn(u64) -> Result<TicketKey, ErrorStack> + Sync + Send;
+
+struct State {
+    epoch: u64,
+    // The first key is the current one.
+    keys: VecDeque<TicketKey>,
+    previous_keys: usize,
+}
+
+/// Manages the keys used to encrypt session tickets, rotating them over time.
+///
+/// Time is divided into epochs of `rotation_interval`. At the start of each epoch, a new key
+/// becomes the one used to encrypt tickets, while the keys of a number of previous epochs are
+/// kept to decrypt tickets issued before the rotation. Tickets decrypted with a previous key are
+/// renewed.
+///
+/// Keys are either generated randomly by each server, or derived from the epoch number, e.g. from
+/// a master secret shared across a fleet with [`TicketKeyManager::from_master_secret`].
+///
+/// A manager is installed with [`SslContextBuilder::set_ticket_key_manager`]. Cloning a manager
+/// yields a handle to the same keys.
+///
+/// [`SslContextBuilder::set_ticket_key_manager`]: struct.SslContextBuilder.html#method.set_ticket_key_manager
+#[derive(Clone)]
+pub struct TicketKeyManager {
+    state: Arc<Mutex<State>>,
+    derive: Option<Arc<DeriveKey>>,
+    rotation_interval: Duration,
+}
+
+impl TicketKeyManager {
+    /// Creates a manager generating a random key every `rotation_interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rotation_interval` is zero.
+    pub fn new(rotation_interval: Duration) -> Result<TicketKeyManager, ErrorStack> {
+        TicketKeyManager::with_epoch_keys(rotation_interval, None)
+    }
+
+    /// Creates a manager deriving the key of each epoch from `secret`.
+    ///
+    /// See [`TicketKey::derive`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rotation_interval` is zero.
+    pub fn from_master_secret(
+        secret: &[u8],
+        rotation_interval: Duration,
+    ) -> Result<TicketKeyManager, ErrorStack> {
+        let secret = secret.to_vec();
+
+        TicketKeyManager::with_key_derivation(rotation_interval, move |epoch| {
+            TicketKey::derive(&secret, epoch)
+        })
+    }
+
+    /// Creates a manager calling `derive` to obtain the key of each epoch.
+    ///
+    /// `derive` is passed the number of `rotation_interval`s elapsed since the UNIX epoch, and
+    /// must return the same key for the same epoch on all servers sharing tickets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rotation_interval` is zero.
+    pub fn with_key_derivation<F>(
+        rotation_interval: Duration,
+        derive: F,
+    ) -> Result<TicketKeyManager, ErrorStack>
+    where
+        F: Fn(u64) -> Result<TicketKey, ErrorStack> + 'static + Sync + Send,
+    {
+        TicketKeyManager::with_epoch_keys(rotation_interval, Some(Arc::new(derive)))
+    }
+
+    fn with_epoch_keys(
+        rotation_interval: Duration,
+        derive: Option<Arc<DeriveKey>>,
+    ) -> Result<TicketKeyManager, ErrorStack> {
+        assert!(
+            !rotation_interval.is_zero(),
+            "rotation interval must not be zero"
+        );
+
+        let manager = TicketKeyManager {
+            state: Arc::new(Mutex::new(State {
+                epoch: 0,
+                keys: VecDeque::new(),
+                previous_keys: 1,
+            })),
+            derive,
+            rotation_interval,
+        };
+
+        let epoch = manager.epoch(SystemTime::now());
+        let mut state = manager.state.lock().unwrap();
+        manager.rotate_to(&mut state, epoch)?;
+        drop(state);
+
+        Ok(manager)
+    }
+
+    /// Sets the number of keys from previous epochs kept to decrypt tickets.
+    ///
+    /// This applies to all the clones of the manager. Defaults to 1.
+    pub fn set_previous_keys(&self, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.previous_keys = count;
+        state.keys.truncate(count + 1);
+    }
+
+    /// Returns the key currently used to encrypt tickets, rotating keys if needed.
+    pub fn current_key(&self) -> Result<TicketKey, ErrorStack> {
+        let state = self.refresh()?;
+        Ok(state.keys.front().unwrap().clone())
+    }
+
+    /// Returns the key with the given name, and whether tickets it decrypts should be renewed.
+    pub fn find_key(&self, name: &[u8]) -> Result<Option<(TicketKey, bool)>, ErrorStack> {
+        let state = self.refresh()?;
+
+        Ok(state
+            .keys
+            .iter()
+            .position(|key| key.name[..] == *name)
+            .map(|i| (state.keys[i].clone(), i != 0)))
+    }
+
+    fn refresh(&self) -> Result<MutexGuard<'_, State>, ErrorStack> {
+        let epoch = self.epoch(SystemTime::now());
+        let mut state = self.state.lock().unwrap();
+
+        if epoch > state.epoch {
+            self.rotate_to(&mut state, epoch)?;
+        }
+
+        Ok(state)
+    }
+
+    fn rotate_to(&self, state: &mut State, epoch: u64) -> Result<(), ErrorStack> {
+        match &self.derive {
+            // Derived keys are a function of the epoch, so all keys still in use are recomputed.
+            Some(derive) => {
+                let first = epoch.saturating_sub(state.previous_keys as u64);
+                let mut keys = VecDeque::new();
+                for epoch in first..=epoch {
+                    keys.push_front(derive(epoch)?);
+                }
+                state.keys = keys;
+            }
+            None => {
+                state.keys.push_front(TicketKey::generate()?);
+                state.keys.truncate(state.previous_keys + 1);
+            }
+        }
+
+        state.epoch = epoch;
+
+        Ok(())
+    }
+
+    fn epoch(&self, now: SystemTime) -> u64 {
+        let elapsed = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        (elapsed.as_nanos() / self.rotation_interval.as_nanos()) as u64
+    }
+}
@@ -0,0 +1,293 @@
+//! Loading the trusted root certificates of the platform.
+//!
+//! The roots are read from:
+//!
+//! * the file and directory named by the `SSL_CERT_FILE` and `SSL_CERT_DIR` environment variables
+//!   if set, or else the certificate bundle and `/etc/ssl/certs` directory maintained by the
+//!   distribution, on Unix systems other than macOS;
+//! * the user, administrator and system trust settings of Security.framework, on macOS;
+//! * the `ROOT` system store of the current user, on Windows.
+//!
+//! Certificates which fail to parse, or which the platform does not trust for TLS server
+//! authentication, are skipped.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use boring::ssl::{SslConnector, SslMethod};
+//! use boring::trust;
+//!
+//! let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+//! connector.set_cert_store(trust::native_store().unwrap());
+//! let connector = connector.build();
+//! ```
+
+use std::collections::HashSet;
+use std::io;
+
+use crate::x509::store::{X509Store, X509StoreBuilder};
+use crate::x509::X509;
+
+/// Loads the trusted root certificates of the platform.
+///
+/// Returns an error if no certificates could be found.
+pub fn load_native_certs() -> io::Result<Vec<X509>> {
+    let mut certs = vec![];
+    let mut seen = HashSet::new();
+
+    for cert in imp::load()? {
+        let der = cert
+            .to_der()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        if seen.insert(der) {
+            certs.push(cert);
+        }
+    }
+
+    if certs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no trusted root certificates found",
+        ));
+    }
+
+    Ok(certs)
+}
+
+/// Builds a certificate store from the trusted root certificates of the platform.
+///
+/// See [`load_native_certs`].
+pub fn native_store() -> io::Result<X509Store> {
+    let mut builder =
+        X509StoreBuilder::new().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    for cert in load_native_certs()? {
+        builder
+            .add_cert(cert)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    }
+
+    Ok(builder.build())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod imp {
+    use std::env;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use crate::x509::X509;
+
+    // The certificate bundles of the main distributions, in order of preference.
+    const CERT_FILES: &[&str] = &[
+        "/etc/ssl/certs/ca-certificates.crt",
+        "/etc/pki/tls/certs/ca-bundle.crt",
+        "/etc/pki/ca-trust/extracted/pem/tls-ca-bundle.pem",
+        "/etc/ssl/ca-bundle.pem",
+        "/etc/pki/tls/cacert.pem",
+        "/etc/ssl/cert.pem",
+    ];
+
+    const CERT_DIR: &str = "/etc/ssl/certs";
+
+    pub(super) fn load() -> io::Result<Vec<X509>> {
+        let file = env::var_os("SSL_CERT_FILE").map(PathBuf::from);
+        let dir = env::var_os("SSL_CERT_DIR").map(PathBuf::from);
+
+        if file.is_some() || dir.is_some() {
+            return load_locations(file.as_deref(), dir.as_deref());
+        }
+
+        let file = CERT_FILES.iter().map(Path::new).find(|path| path.is_file());
+
+        if file.is_some() {
+            return load_locations(file, None);
+        }
+
+        let dir = Path::new(CERT_DIR);
+        load_locations(None, Some(dir).filter(|dir| dir.is_dir()))
+    }
+
+    pub(super) fn load_locations(file: Option<&Path>, dir: Option<&Path>) -> io::Result<Vec<X509>> {
+        let mut certs = vec![];
+
+        if let Some(file) = file {
+            certs.extend(load_file(file)?);
+        }
+        if let Some(dir) = dir {
+            certs.extend(load_dir(dir)?);
+        }
+
+        Ok(certs)
+    }
+
+    fn load_file(path: &Path) -> io::Result<Vec<X509>> {
+        let pem = fs::read(path)?;
+        Ok(parse_pem_bundle(&pem))
+    }
+
+    /// Parses the certificates of a PEM bundle one block at a time, so that a malformed block
+    /// only loses its own certificate rather than the whole bundle.
+    pub(super) fn parse_pem_bundle(pem: &[u8]) -> Vec<X509> {
+        let mut certs = vec![];
+        let mut rest = pem;
+
+        while let Some(start) = find(rest, b"-----BEGIN ") {
+            let block = &rest[start..];
+            let end = match find(block, b"-----END ") {
+                Some(end) => match block[end..].iter().position(|&b| b == b'\n') {
+                    Some(newline) => end + newline + 1,
+                    None => block.len(),
+                },
+                None => block.len(),
+            };
+
+            // Other blocks, such as private keys, are skipped as well.
+            if let Ok(cert) = X509::from_pem(&block[..end]) {
+                certs.push(cert);
+            }
+
+            rest = &block[end..];
+        }
+
+        certs
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    fn load_dir(path: &Path) -> io::Result<Vec<X509>> {
+        let mut certs = vec![];
+
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            // Hashed directories contain symlinks to the certificates, so the same certificate is
+            // often found under several names; duplicates are removed by the caller.
+            if path.is_file() {
+                if let Ok(file_certs) = load_file(&path) {
+                    certs.extend(file_certs);
+                }
+            }
+        }
+
+        Ok(certs)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use security_framework::trust_settings::{Domain, TrustSettings, TrustSettingsForCertificate};
+    use std::io;
+
+    use crate::x509::X509;
+
+    pub(super) fn load() -> io::Result<Vec<X509>> {
+        let mut certs = vec![];
+
+        for domain in [Domain::User, Domain::Admin, Domain::System] {
+            let settings = TrustSettings::new(domain);
+            // A domain without trust settings is reported as an error.
+            let iter = match settings.iter() {
+                Ok(iter) => iter,
+                Err(_) => continue,
+            };
+
+            for cert in iter {
+                match settings.tls_trust_settings_for_certificate(&cert) {
+                    Ok(Some(TrustSettingsForCertificate::Deny)) | Err(_) => continue,
+                    Ok(_) => {}
+                }
+
+                if let Ok(cert) = X509::from_der(&cert.to_der()) {
+                    certs.push(cert);
+                }
+            }
+        }
+
+        Ok(certs)
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use schannel::cert_context::ValidUses;
+    use schannel::cert_store::CertStore;
+    use std::io;
+
+    use crate::x509::X509;
+
+    const SERVER_AUTH: &str = "1.3.6.1.5.5.7.3.1";
+
+    pub(super) fn load() -> io::Result<Vec<X509>> {
+        let store = CertStore::open_current_user("ROOT")?;
+        let mut certs = vec![];
+
+        for cert in store.certs() {
+            let trusted = match cert.valid_uses() {
+                Ok(ValidUses::All) => true,
+                Ok(ValidUses::Oids(oids)) => oids.iter().any(|oid| oid == SERVER_AUTH),
+                Err(_) => false,
+            };
+
+            if trusted {
+                if let Ok(cert) = X509::from_der(cert.to_der()) {
+                    certs.push(cert);
+                }
+            }
+        }
+
+        Ok(certs)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use std::io;
+
+    use crate::x509::X509;
+
+    pub(super) fn load() -> io::Result<Vec<X509>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "loading trusted root certificates is not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn load_locations() {
+        use std::path::Path;
+
+        let certs = imp::load_locations(Some(Path::new("test/certs.pem")), None).unwrap();
+        let expected = X509::stack_from_pem(include_bytes!("../test/certs.pem")).unwrap();
+        assert_eq!(certs.len(), expected.len());
+
+        let certs = imp::load_locations(None, Some(Path::new("test"))).unwrap();
+        assert!(certs.len() > expected.len());
+
+        assert!(imp::load_locations(Some(Path::new("test/nonexistent.pem")), None).is_err());
+    }
+
+    #[test]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn parse_pem_bundle_skips_malformed() {
+        let pem = include_str!("../test/certs.pem");
+        let expected = X509::stack_from_pem(pem.as_bytes()).unwrap();
+
+        let malformed = "-----BEGIN CERTIFICATE-----\nnot base64\n-----END CERTIFICATE-----\n";
+        let bundle = format!("{}{}{}", malformed, pem, malformed);
+        assert!(X509::stack_from_pem(bundle.as_bytes()).is_err());
+
+        let certs = imp::parse_pem_bundle(bundle.as_bytes());
+        assert_eq!(certs.len(), expected.len());
+    }
+}
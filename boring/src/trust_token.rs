@@ -0,0 +1,564 @@
+//! Trust Tokens, also known as Private State Tokens.
+//!
+//! Trust tokens allow an issuer to give a client tokens which the client can later redeem,
+//! without the issuer being able to link the redemption to the issuance. An issuer signs batches
+//! of blinded tokens with a [`TrustTokenIssuer`], and a [`TrustTokenClient`] unblinds them and
+//! later produces redemption requests.
+//!
+//! The messages exchanged between the client and the issuer are opaque byte strings, to be
+//! carried by the application protocol.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use boring::trust_token::{
+//!     TrustTokenClient, TrustTokenIssuer, TrustTokenKeyPair, TrustTokenMethod,
+//! };
+//!
+//! let method = TrustTokenMethod::pst_v1_voprf();
+//! let key = TrustTokenKeyPair::generate(method, 1).unwrap();
+//!
+//! let mut issuer = TrustTokenIssuer::new(method, 8).unwrap();
+//! issuer.add_key(key.private_key()).unwrap();
+//!
+//! let mut client = TrustTokenClient::new(method, 8).unwrap();
+//! client.add_key(key.public_key()).unwrap();
+//!
+//! // Issuance
+//! let request = client.begin_issuance(4).unwrap();
+//! let (response, _) = issuer.issue(&request, 1, false, 4).unwrap();
+//! let (tokens, _) = client.finish_issuance(&response).unwrap();
+//!
+//! // Redemption
+//! let request = client.begin_redemption(&tokens[0], b"client data", 0).unwrap();
+//! let redemption = issuer.redeem(&request).unwrap();
+//! assert_eq!(redemption.client_data(), b"client data");
+//! ```
+use crate::ffi;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_void};
+use std::ptr;
+use std::slice;
+
+use crate::error::ErrorStack;
+use crate::stack::{Stack, Stackable};
+use crate::{cvt, cvt_p};
+
+/// A trust token protocol version.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct TrustTokenMethod(*const ffi::TRUST_TOKEN_METHOD);
+
+unsafe impl Sync for TrustTokenMethod {}
+unsafe impl Send for TrustTokenMethod {}
+
+impl TrustTokenMethod {
+    /// The experimental version 2 protocol, using a VOPRF and no private metadata.
+    pub fn experiment_v2_voprf() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_experiment_v2_voprf()) }
+    }
+
+    /// The experimental version 2 protocol, using PMBTokens and supporting private metadata.
+    pub fn experiment_v2_pmb() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_experiment_v2_pmb()) }
+    }
+
+    /// The Private State Token version 1 protocol, using a VOPRF and no private metadata.
+    pub fn pst_v1_voprf() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_pst_v1_voprf()) }
+    }
+
+    /// The Private State Token version 1 protocol, using PMBTokens and supporting private
+    /// metadata.
+    pub fn pst_v1_pmb() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_pst_v1_pmb()) }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_ptr(&self) -> *const ffi::TRUST_TOKEN_METHOD {
+        self.0
+    }
+}
+
+/// An issuer key pair, in the serialized form used by BoringSSL.
+#[derive(Clone)]
+pub struct TrustTokenKeyPair {
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+impl TrustTokenKeyPair {
+    /// Generates a new key pair with key identifier `id`.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_generate_key`].
+    ///
+    /// [`TRUST_TOKEN_generate_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_generate_key
+    pub fn generate(method: TrustTokenMethod, id: u32) -> Result<TrustTokenKeyPair, ErrorStack> {
+        TrustTokenKeyPair::new(|private_key, private_len, public_key, public_len| unsafe {
+            ffi::TRUST_TOKEN_generate_key(
+                method.as_ptr(),
+                private_key,
+                private_len,
+                ffi::TRUST_TOKEN_MAX_PRIVATE_KEY_SIZE as usize,
+                public_key,
+                public_len,
+                ffi::TRUST_TOKEN_MAX_PUBLIC_KEY_SIZE as usize,
+                id,
+            )
+        })
+    }
+
+    /// Deterministically derives a key pair with key identifier `id` from `secret`.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_derive_key_from_secret`].
+    ///
+    /// [`TRUST_TOKEN_derive_key_from_secret`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_derive_key_from_secret
+    pub fn from_secret(
+        method: TrustTokenMethod,
+        id: u32,
+        secret: &[u8],
+    ) -> Result<TrustTokenKeyPair, ErrorStack> {
+        TrustTokenKeyPair::new(|private_key, private_len, public_key, public_len| unsafe {
+            ffi::TRUST_TOKEN_derive_key_from_secret(
+                method.as_ptr(),
+                private_key,
+                private_len,
+                ffi::TRUST_TOKEN_MAX_PRIVATE_KEY_SIZE as usize,
+                public_key,
+                public_len,
+                ffi::TRUST_TOKEN_MAX_PUBLIC_KEY_SIZE as usize,
+                id,
+                secret.as_ptr(),
+                secret.len(),
+            )
+        })
+    }
+
+    fn new<F>(f: F) -> Result<TrustTokenKeyPair, ErrorStack>
+    where
+        F: FnOnce(*mut u8, *mut usize, *mut u8, *mut usize) -> c_int,
+    {
+        ffi::init();
+
+        let mut private_key = vec![0; ffi::TRUST_TOKEN_MAX_PRIVATE_KEY_SIZE as usize];
+        let mut public_key = vec![0; ffi::TRUST_TOKEN_MAX_PUBLIC_KEY_SIZE as usize];
+        let mut private_len = 0;
+        let mut public_len = 0;
+        cvt(f(
+            private_key.as_mut_ptr(),
+            &mut private_len,
+            public_key.as_mut_ptr(),
+            &mut public_len,
+        ))?;
+        private_key.truncate(private_len);
+        public_key.truncate(public_len);
+
+        Ok(TrustTokenKeyPair {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// Returns the private key, to be added to a [`TrustTokenIssuer`].
+    pub fn private_key(&self) -> &[u8] {
+        &self.private_key
+    }
+
+    /// Returns the public key, to be added to a [`TrustTokenClient`].
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TRUST_TOKEN;
+    fn drop = ffi::TRUST_TOKEN_free;
+
+    /// A trust token, as obtained by a client from an issuer.
+    pub struct TrustToken;
+}
+
+impl Stackable for TrustToken {
+    type StackType = ffi::stack_st_TRUST_TOKEN;
+}
+
+impl TrustToken {
+    /// Creates a token from its serialized form.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_new`].
+    ///
+    /// [`TRUST_TOKEN_new`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_new
+    pub fn new(data: &[u8]) -> Result<TrustToken, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::TRUST_TOKEN_new(data.as_ptr(), data.len())).map(|p| TrustToken::from_ptr(p))
+        }
+    }
+}
+
+impl TrustTokenRef {
+    /// Returns the serialized form of the token.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            let token = &*self.as_ptr();
+            if token.len == 0 {
+                &[]
+            } else {
+                slice::from_raw_parts(token.data, token.len)
+            }
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TRUST_TOKEN_CLIENT;
+    fn drop = ffi::TRUST_TOKEN_CLIENT_free;
+
+    /// The client side of the trust token protocol.
+    pub struct TrustTokenClient;
+}
+
+impl TrustTokenClient {
+    /// Creates a client which requests at most `max_batchsize` tokens at once.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_CLIENT_new`].
+    ///
+    /// [`TRUST_TOKEN_CLIENT_new`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_CLIENT_new
+    pub fn new(
+        method: TrustTokenMethod,
+        max_batchsize: usize,
+    ) -> Result<TrustTokenClient, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::TRUST_TOKEN_CLIENT_new(method.as_ptr(), max_batchsize))
+                .map(|p| TrustTokenClient::from_ptr(p))
+        }
+    }
+}
+
+impl TrustTokenClientRef {
+    /// Adds an issuer public key, returning its index.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_CLIENT_add_key`].
+    ///
+    /// [`TRUST_TOKEN_CLIENT_add_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_CLIENT_add_key
+    pub fn add_key(&mut self, public_key: &[u8]) -> Result<usize, ErrorStack> {
+        unsafe {
+            let mut index = 0;
+            cvt(ffi::TRUST_TOKEN_CLIENT_add_key(
+                self.as_ptr(),
+                &mut index,
+                public_key.as_ptr(),
+                public_key.len(),
+            ))?;
+            Ok(index)
+        }
+    }
+
+    /// Returns an issuance request for `count` tokens, to be sent to the issuer.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_CLIENT_begin_issuance`].
+    ///
+    /// [`TRUST_TOKEN_CLIENT_begin_issuance`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_CLIENT_begin_issuance
+    pub fn begin_issuance(&mut self, count: usize) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut out = ptr::null_mut();
+            let mut out_len = 0;
+            cvt(ffi::TRUST_TOKEN_CLIENT_begin_issuance(
+                self.as_ptr(),
+                &mut out,
+                &mut out_len,
+                count,
+            ))?;
+            Ok(take_buf(out, out_len))
+        }
+    }
+
+    /// Processes the issuer's response to the pending issuance request, returning the issued
+    /// tokens and the index of the key that signed them.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_CLIENT_finish_issuance`].
+    ///
+    /// [`TRUST_TOKEN_CLIENT_finish_issuance`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_CLIENT_finish_issuance
+    pub fn finish_issuance(
+        &mut self,
+        response: &[u8],
+    ) -> Result<(Stack<TrustToken>, usize), ErrorStack> {
+        unsafe {
+            let mut index = 0;
+            let tokens = cvt_p(ffi::TRUST_TOKEN_CLIENT_finish_issuance(
+                self.as_ptr(),
+                &mut index,
+                response.as_ptr(),
+                response.len(),
+            ))?;
+            Ok((Stack::from_ptr(tokens), index))
+        }
+    }
+
+    /// Returns a request redeeming `token`, to be sent to the issuer.
+    ///
+    /// `data` is passed to the issuer as the client data of the redemption. `time` is the
+    /// redemption time in seconds since the Unix epoch, and is only used by the experimental
+    /// protocol versions.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_CLIENT_begin_redemption`].
+    ///
+    /// [`TRUST_TOKEN_CLIENT_begin_redemption`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_CLIENT_begin_redemption
+    pub fn begin_redemption(
+        &mut self,
+        token: &TrustTokenRef,
+        data: &[u8],
+        time: u64,
+    ) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut out = ptr::null_mut();
+            let mut out_len = 0;
+            cvt(ffi::TRUST_TOKEN_CLIENT_begin_redemption(
+                self.as_ptr(),
+                &mut out,
+                &mut out_len,
+                token.as_ptr(),
+                data.as_ptr(),
+                data.len(),
+                time,
+            ))?;
+            Ok(take_buf(out, out_len))
+        }
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::TRUST_TOKEN_ISSUER;
+    fn drop = ffi::TRUST_TOKEN_ISSUER_free;
+
+    /// The issuer side of the trust token protocol.
+    ///
+    /// Once configured, an issuer may process any number of requests concurrently.
+    pub struct TrustTokenIssuer;
+}
+
+impl TrustTokenIssuer {
+    /// Creates an issuer which issues at most `max_batchsize` tokens at once.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_ISSUER_new`].
+    ///
+    /// [`TRUST_TOKEN_ISSUER_new`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_ISSUER_new
+    pub fn new(
+        method: TrustTokenMethod,
+        max_batchsize: usize,
+    ) -> Result<TrustTokenIssuer, ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt_p(ffi::TRUST_TOKEN_ISSUER_new(method.as_ptr(), max_batchsize))
+                .map(|p| TrustTokenIssuer::from_ptr(p))
+        }
+    }
+}
+
+impl TrustTokenIssuerRef {
+    /// Adds an issuer private key.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_ISSUER_add_key`].
+    ///
+    /// [`TRUST_TOKEN_ISSUER_add_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_ISSUER_add_key
+    pub fn add_key(&mut self, private_key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_add_key(
+                self.as_ptr(),
+                private_key.as_ptr(),
+                private_key.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the key used to encrypt the private metadata bit in redemption responses.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_ISSUER_set_metadata_key`].
+    ///
+    /// [`TRUST_TOKEN_ISSUER_set_metadata_key`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_ISSUER_set_metadata_key
+    pub fn set_metadata_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::TRUST_TOKEN_ISSUER_set_metadata_key(
+                self.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Processes an issuance request, returning the response to send to the client and the
+    /// number of tokens issued.
+    ///
+    /// The tokens are signed with the key whose identifier is `public_metadata`, and embed
+    /// `private_metadata`, which must be `false` for methods without private metadata support.
+    /// At most `max_issuance` tokens are issued.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_ISSUER_issue`].
+    ///
+    /// [`TRUST_TOKEN_ISSUER_issue`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_ISSUER_issue
+    pub fn issue(
+        &self,
+        request: &[u8],
+        public_metadata: u32,
+        private_metadata: bool,
+        max_issuance: usize,
+    ) -> Result<(Vec<u8>, usize), ErrorStack> {
+        unsafe {
+            let mut out = ptr::null_mut();
+            let mut out_len = 0;
+            let mut issued = 0;
+            cvt(ffi::TRUST_TOKEN_ISSUER_issue(
+                self.as_ptr(),
+                &mut out,
+                &mut out_len,
+                &mut issued,
+                request.as_ptr(),
+                request.len(),
+                public_metadata,
+                private_metadata as u8,
+                max_issuance,
+            ))?;
+            Ok((take_buf(out, out_len), issued))
+        }
+    }
+
+    /// Processes a redemption request.
+    ///
+    /// The issuer is responsible for checking that the token has not been redeemed before.
+    ///
+    /// This corresponds to [`TRUST_TOKEN_ISSUER_redeem`].
+    ///
+    /// [`TRUST_TOKEN_ISSUER_redeem`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/trust_token.h.html#TRUST_TOKEN_ISSUER_redeem
+    pub fn redeem(&self, request: &[u8]) -> Result<TrustTokenRedemption, ErrorStack> {
+        unsafe {
+            let mut public_metadata = 0;
+            let mut private_metadata = 0;
+            let mut token = ptr::null_mut();
+            let mut client_data = ptr::null_mut();
+            let mut client_data_len = 0;
+            cvt(ffi::TRUST_TOKEN_ISSUER_redeem(
+                self.as_ptr(),
+                &mut public_metadata,
+                &mut private_metadata,
+                &mut token,
+                &mut client_data,
+                &mut client_data_len,
+                request.as_ptr(),
+                request.len(),
+            ))?;
+            Ok(TrustTokenRedemption {
+                public_metadata,
+                private_metadata: private_metadata != 0,
+                token: TrustToken::from_ptr(token),
+                client_data: take_buf(client_data, client_data_len),
+            })
+        }
+    }
+}
+
+/// The result of a successful redemption.
+pub struct TrustTokenRedemption {
+    public_metadata: u32,
+    private_metadata: bool,
+    token: TrustToken,
+    client_data: Vec<u8>,
+}
+
+impl TrustTokenRedemption {
+    /// Returns the identifier of the key which signed the token.
+    pub fn public_metadata(&self) -> u32 {
+        self.public_metadata
+    }
+
+    /// Returns the private metadata bit embedded in the token.
+    pub fn private_metadata(&self) -> bool {
+        self.private_metadata
+    }
+
+    /// Returns the redeemed token.
+    ///
+    /// It can be used to detect double redemption.
+    pub fn token(&self) -> &TrustTokenRef {
+        &self.token
+    }
+
+    /// Returns the client data included in the redemption request.
+    pub fn client_data(&self) -> &[u8] {
+        &self.client_data
+    }
+}
+
+/// Copies a buffer allocated by BoringSSL and frees it.
+unsafe fn take_buf(buf: *mut u8, len: usize) -> Vec<u8> {
+    if buf.is_null() {
+        return vec![];
+    }
+    let vec = slice::from_raw_parts(buf, len).to_vec();
+    ffi::OPENSSL_free(buf as *mut c_void);
+    vec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue_and_redeem(method: TrustTokenMethod, private_metadata: bool) {
+        let key1 = TrustTokenKeyPair::generate(method, 1).unwrap();
+        let key2 = TrustTokenKeyPair::from_secret(method, 2, b"secret").unwrap();
+
+        let mut issuer = TrustTokenIssuer::new(method, 10).unwrap();
+        issuer.add_key(key1.private_key()).unwrap();
+        issuer.add_key(key2.private_key()).unwrap();
+        issuer.set_metadata_key(&[0x42; 32]).unwrap();
+
+        let mut client = TrustTokenClient::new(method, 10).unwrap();
+        assert_eq!(client.add_key(key1.public_key()).unwrap(), 0);
+        assert_eq!(client.add_key(key2.public_key()).unwrap(), 1);
+
+        let request = client.begin_issuance(10).unwrap();
+        let (response, issued) = issuer.issue(&request, 2, private_metadata, 3).unwrap();
+        assert_eq!(issued, 3);
+        let (tokens, index) = client.finish_issuance(&response).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(tokens.len(), 3);
+
+        for token in &tokens {
+            let copy = TrustToken::new(token.as_bytes()).unwrap();
+            let request = client.begin_redemption(&copy, b"client data", 0).unwrap();
+
+            let redemption = issuer.redeem(&request).unwrap();
+            assert_eq!(redemption.public_metadata(), 2);
+            assert_eq!(redemption.private_metadata(), private_metadata);
+            assert_eq!(redemption.client_data(), b"client data");
+            assert!(!redemption.token().as_bytes().is_empty());
+
+            assert!(issuer.redeem(&request[1..]).is_err());
+        }
+    }
+
+    #[test]
+    fn pst_v1_voprf() {
+        issue_and_redeem(TrustTokenMethod::pst_v1_voprf(), false);
+    }
+
+    #[test]
+    fn pst_v1_pmb() {
+        issue_and_redeem(TrustTokenMethod::pst_v1_pmb(), true);
+    }
+
+    #[test]
+    fn unknown_key() {
+        let method = TrustTokenMethod::pst_v1_voprf();
+        let key = TrustTokenKeyPair::generate(method, 1).unwrap();
+
+        let mut issuer = TrustTokenIssuer::new(method, 10).unwrap();
+        issuer.add_key(key.private_key()).unwrap();
+        let mut client = TrustTokenClient::new(method, 10).unwrap();
+        client.add_key(key.public_key()).unwrap();
+
+        let request = client.begin_issuance(1).unwrap();
+        assert!(issuer.issue(&request, 2, false, 1).is_err());
+    }
+}
@@ -13,6 +13,7 @@
 
 use std::ffi::CStr;
 
+use crate::ffi;
 use crate::ffi::{
     OpenSSL_version, OpenSSL_version_num, OPENSSL_BUILT_ON, OPENSSL_CFLAGS, OPENSSL_DIR,
     OPENSSL_PLATFORM, OPENSSL_VERSION,
@@ -92,6 +93,45 @@ pub fn dir() -> &'static str {
     }
 }
 
+/// Metadata about the BoringSSL linked into this crate.
+///
+/// Returned by [`crate::version()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct VersionInfo {
+    /// The version string, as returned by [`version()`].
+    pub version: &'static str,
+    /// The value of `BORINGSSL_API_VERSION`, which is incremented on API changes.
+    pub api_version: u32,
+    /// The git commit of the BoringSSL sources, if it was known at build time.
+    pub commit: Option<&'static str>,
+    /// How BoringSSL was obtained: `vendored`, `source-path`, `prebuilt` or `pkg-config`.
+    pub source: &'static str,
+    /// The `boring-sys` features which affected how BoringSSL was built.
+    pub features: Vec<&'static str>,
+    /// Whether the library is running in FIPS mode.
+    pub fips: bool,
+    /// The SHA-256 digest of the precompiled FIPS module, if one was linked.
+    pub fips_module_sha256: Option<&'static str>,
+}
+
+/// Returns metadata about the BoringSSL linked into this crate.
+pub fn info() -> VersionInfo {
+    VersionInfo {
+        version: version(),
+        api_version: ffi::BORINGSSL_API_VERSION as u32,
+        commit: ffi::BORINGSSL_COMMIT,
+        source: ffi::BORINGSSL_SOURCE,
+        features: ffi::BORINGSSL_FEATURES
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .collect(),
+        fips: crate::fips::enabled(),
+        fips_module_sha256: crate::fips::precompiled_module_sha256(),
+    }
+}
+
 /// This test ensures that we do not segfault when calling the functions of this module
 /// and that the strings respect a reasonable format.
 #[test]
@@ -109,3 +149,16 @@ fn test_versions() {
     assert!(built_on().starts_with("built on:"));
     assert!(dir().starts_with("OPENSSLDIR:"));
 }
+
+#[test]
+fn test_info() {
+    let info = info();
+    println!("Info: {:?}", info);
+
+    assert_eq!(info.version, version());
+    assert!(info.api_version > 0);
+    assert!(info.commit.is_none_or(|commit| !commit.is_empty()));
+    assert!(["vendored", "source-path", "prebuilt", "pkg-config"].contains(&info.source));
+    assert_eq!(info.fips, crate::fips::enabled());
+    assert_eq!(info.features.contains(&"fips"), cfg!(feature = "fips"));
+}
@@ -0,0 +1,199 @@
+//! Building and validating certificate chains.
+//!
+//! # Example
+//!
+//! ```rust
+//! use boring::x509::chain::ChainBuilder;
+//! use boring::x509::store::X509StoreBuilder;
+//! use boring::x509::X509;
+//!
+//! let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+//! let leaf = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+//!
+//! let mut store = X509StoreBuilder::new().unwrap();
+//! store.add_cert(ca).unwrap();
+//! let store = store.build();
+//!
+//! let mut builder = ChainBuilder::new(&store).unwrap();
+//! // Aug 1st, 2020
+//! builder.set_time(1596240000);
+//!
+//! let chain = builder.build(&leaf).unwrap();
+//! assert_eq!(chain.len(), 2);
+//! ```
+
+use libc::time_t;
+use std::error::Error;
+use std::fmt;
+
+use crate::error::ErrorStack;
+use crate::stack::Stack;
+use crate::x509::store::X509StoreRef;
+use crate::x509::verify::X509VerifyFlags;
+use crate::x509::{X509Ref, X509StoreContext, X509TrustId, X509VerifyResult, X509};
+
+/// Builds a chain from a leaf certificate to a trusted root, and validates it.
+pub struct ChainBuilder<'a> {
+    store: &'a X509StoreRef,
+    intermediates: Stack<X509>,
+    flags: X509VerifyFlags,
+    time: Option<time_t>,
+    trust: Option<X509TrustId>,
+}
+
+impl<'a> ChainBuilder<'a> {
+    /// Creates a builder using the trusted certificates of `store`.
+    pub fn new(store: &'a X509StoreRef) -> Result<ChainBuilder<'a>, ErrorStack> {
+        Ok(ChainBuilder {
+            store,
+            intermediates: Stack::new()?,
+            flags: X509VerifyFlags::empty(),
+            time: None,
+            trust: None,
+        })
+    }
+
+    /// Adds an untrusted certificate which may be used to build the chain.
+    pub fn add_intermediate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        self.intermediates.push(cert.to_owned())
+    }
+
+    /// Sets verification flags, in addition to the ones set on the store.
+    pub fn set_flags(&mut self, flags: X509VerifyFlags) {
+        self.flags = flags;
+    }
+
+    /// Allows chains to end at any trusted certificate, rather than only at self-signed ones.
+    ///
+    /// This sets the [`X509VerifyFlags::PARTIAL_CHAIN`] flag.
+    pub fn set_partial_chain(&mut self, partial: bool) {
+        self.flags.set(X509VerifyFlags::PARTIAL_CHAIN, partial);
+    }
+
+    /// Sets the time, in seconds since the UNIX epoch, at which certificates are checked for
+    /// validity, instead of the current time.
+    pub fn set_time(&mut self, time: time_t) {
+        self.time = Some(time);
+    }
+
+    /// Checks the auxiliary trust information of the trust anchor against `trust`.
+    ///
+    /// Auxiliary trust information is attached to certificates loaded from
+    /// `-----BEGIN TRUSTED CERTIFICATE-----` PEM blocks.
+    pub fn set_trust(&mut self, trust: X509TrustId) {
+        self.trust = Some(trust);
+    }
+
+    /// Builds and validates a chain for `leaf`.
+    ///
+    /// On success, returns the chain ordered from `leaf` to the trust anchor.
+    pub fn build(&self, leaf: &X509Ref) -> Result<Vec<X509>, ChainError> {
+        let mut context = X509StoreContext::new()?;
+
+        context.init(self.store, leaf, &self.intermediates, |context| {
+            let param = context.verify_param_mut();
+            param.set_flags(self.flags)?;
+            if let Some(time) = self.time {
+                param.set_time(time);
+            }
+            if let Some(trust) = self.trust {
+                context.set_trust(trust)?;
+            }
+
+            let verified = context.verify_cert()?;
+            let chain = context
+                .chain()
+                .map(|chain| chain.iter().map(X509Ref::to_owned).collect())
+                .unwrap_or_default();
+
+            if verified {
+                return Ok(Ok(chain));
+            }
+
+            Ok(Err(ChainError::Verify(VerifyFailure {
+                result: context.error(),
+                depth: context.error_depth(),
+                cert: context.current_cert().map(X509Ref::to_owned),
+                chain,
+            })))
+        })?
+    }
+}
+
+/// An error building or validating a certificate chain.
+#[derive(Debug)]
+pub enum ChainError {
+    /// The chain failed validation.
+    Verify(VerifyFailure),
+    /// An internal error occurred.
+    Stack(ErrorStack),
+}
+
+impl From<ErrorStack> for ChainError {
+    fn from(e: ErrorStack) -> ChainError {
+        ChainError::Stack(e)
+    }
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChainError::Verify(failure) => fmt::Display::fmt(failure, fmt),
+            ChainError::Stack(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl Error for ChainError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ChainError::Verify(failure) => Some(&failure.result),
+            ChainError::Stack(e) => Some(e),
+        }
+    }
+}
+
+/// Describes why a certificate chain failed validation.
+#[derive(Debug)]
+pub struct VerifyFailure {
+    result: X509VerifyResult,
+    depth: u32,
+    cert: Option<X509>,
+    chain: Vec<X509>,
+}
+
+impl VerifyFailure {
+    /// Returns the check which failed.
+    pub fn result(&self) -> X509VerifyResult {
+        self.result
+    }
+
+    /// Returns the depth in the chain of the certificate which failed the check.
+    ///
+    /// Zero is the leaf certificate, one its issuer, and so on.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the certificate which failed the check, if the check concerns a certificate.
+    pub fn certificate(&self) -> Option<&X509Ref> {
+        self.cert.as_deref()
+    }
+
+    /// Returns the part of the chain built before the failure.
+    pub fn chain(&self) -> &[X509] {
+        &self.chain
+    }
+}
+
+impl fmt::Display for VerifyFailure {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "certificate verification failed at depth {}: {}",
+            self.depth, self.result
+        )
+    }
+}
+
+impl Error for VerifyFailure {}
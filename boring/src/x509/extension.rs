@@ -16,11 +16,14 @@
 //! let extension: X509Extension = bc.build().unwrap();
 //! ```
 use std::fmt::Write;
+use std::net::IpAddr;
 
 use crate::asn1::Asn1Object;
+use crate::cvt_p;
 use crate::error::ErrorStack;
+use crate::ffi;
 use crate::nid::Nid;
-use crate::x509::{GeneralName, Stack, X509Extension, X509v3Context};
+use crate::x509::{GeneralName, Stack, X509Extension, X509NameRef, X509v3Context};
 use foreign_types::ForeignType;
 
 /// An extension which indicates whether a certificate is a CA certificate.
@@ -416,6 +419,8 @@ enum RustGeneralName {
     Uri(String),
     Ip(String),
     Rid(String),
+    OtherName(Asn1Object, Vec<u8>),
+    Name(GeneralName),
 }
 
 /// An extension that allows additional identities to be bound to the subject
@@ -479,7 +484,7 @@ impl SubjectAlternativeName {
     /// Sets the `dirName` flag.
     ///
     /// Not currently actually supported, always panics.
-    #[deprecated = "dir_name is deprecated and always panics. Please file a bug if you have a use case for this."]
+    #[deprecated = "dir_name is deprecated and always panics. Use general_name with GeneralName::new_dir_name instead."]
     pub fn dir_name(&mut self, _dir_name: &str) -> &mut SubjectAlternativeName {
         unimplemented!(
             "This has not yet been adapted for the new internals. File a bug if you need this."
@@ -489,25 +494,46 @@ impl SubjectAlternativeName {
     /// Sets the `otherName` flag.
     ///
     /// Not currently actually supported, always panics.
-    #[deprecated = "other_name is deprecated and always panics. Please file a bug if you have a use case for this."]
+    #[deprecated = "other_name is deprecated and always panics. Use other_name2 instead."]
     pub fn other_name(&mut self, _other_name: &str) -> &mut SubjectAlternativeName {
         unimplemented!(
             "This has not yet been adapted for the new internals. File a bug if you need this."
         );
     }
 
+    /// Adds an `otherName` of type `oid`, whose value is the DER encoding `content`.
+    ///
+    /// See [`GeneralName::new_other_name`].
+    pub fn other_name2(&mut self, oid: Asn1Object, content: &[u8]) -> &mut SubjectAlternativeName {
+        self.items
+            .push(RustGeneralName::OtherName(oid, content.to_vec()));
+        self
+    }
+
+    /// Adds a name of any type, such as a `directoryName` or a user principal name.
+    pub fn general_name(&mut self, name: GeneralName) -> &mut SubjectAlternativeName {
+        self.items.push(RustGeneralName::Name(name));
+        self
+    }
+
     /// Return a `SubjectAlternativeName` extension as an `X509Extension`.
     pub fn build(&self, _ctx: &X509v3Context<'_>) -> Result<X509Extension, ErrorStack> {
         let mut stack = Stack::new()?;
         for item in &self.items {
             let gn = match item {
-                RustGeneralName::Dns(s) => GeneralName::new_dns(s.as_bytes())?,
-                RustGeneralName::Email(s) => GeneralName::new_email(s.as_bytes())?,
-                RustGeneralName::Uri(s) => GeneralName::new_uri(s.as_bytes())?,
+                RustGeneralName::Dns(s) => GeneralName::new_dns(s)?,
+                RustGeneralName::Email(s) => GeneralName::new_email(s)?,
+                RustGeneralName::Uri(s) => GeneralName::new_uri(s)?,
                 RustGeneralName::Ip(s) => {
                     GeneralName::new_ip(s.parse().map_err(|_| ErrorStack::get())?)?
                 }
                 RustGeneralName::Rid(s) => GeneralName::new_rid(Asn1Object::from_str(s)?)?,
+                RustGeneralName::OtherName(oid, content) => {
+                    GeneralName::new_other_name(oid, content)?
+                }
+                RustGeneralName::Name(name) => unsafe {
+                    GeneralName::from_ptr(cvt_p(ffi::GENERAL_NAME_dup(name.as_ptr()))?)
+                },
             };
             stack.push(gn)?;
         }
@@ -518,6 +544,489 @@ impl SubjectAlternativeName {
     }
 }
 
+/// An extension restricting the names that may appear in certificates issued by a CA.
+///
+/// A name is acceptable if it matches one of the permitted subtrees of its type, if any, and none
+/// of the excluded subtrees.
+pub struct NameConstraints {
+    critical: bool,
+    permitted: Vec<Vec<u8>>,
+    excluded: Vec<Vec<u8>>,
+}
+
+impl Default for NameConstraints {
+    fn default() -> NameConstraints {
+        NameConstraints::new()
+    }
+}
+
+impl NameConstraints {
+    /// Construct a new `NameConstraints` extension.
+    pub fn new() -> NameConstraints {
+        NameConstraints {
+            critical: false,
+            permitted: vec![],
+            excluded: vec![],
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut NameConstraints {
+        self.critical = true;
+        self
+    }
+
+    /// Permits DNS names within the given domain, e.g. `example.com`.
+    pub fn permitted_dns(&mut self, domain: &str) -> &mut NameConstraints {
+        self.permitted.push(der(0x82, domain.as_bytes()));
+        self
+    }
+
+    /// Permits email addresses matching the given mailbox, host or domain.
+    pub fn permitted_email(&mut self, email: &str) -> &mut NameConstraints {
+        self.permitted.push(der(0x81, email.as_bytes()));
+        self
+    }
+
+    /// Permits URIs whose host is within the given domain.
+    pub fn permitted_uri(&mut self, host: &str) -> &mut NameConstraints {
+        self.permitted.push(der(0x86, host.as_bytes()));
+        self
+    }
+
+    /// Permits IP addresses within the given network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is larger than the length of `addr` in bits.
+    pub fn permitted_ip(&mut self, addr: IpAddr, prefix_len: u8) -> &mut NameConstraints {
+        self.permitted.push(ip_subtree(addr, prefix_len));
+        self
+    }
+
+    /// Permits directory names within the given name.
+    pub fn permitted_dir_name(
+        &mut self,
+        name: &X509NameRef,
+    ) -> Result<&mut NameConstraints, ErrorStack> {
+        self.permitted.push(der(0xa4, &name.to_der()?));
+        Ok(self)
+    }
+
+    /// Excludes DNS names within the given domain, e.g. `example.com`.
+    pub fn excluded_dns(&mut self, domain: &str) -> &mut NameConstraints {
+        self.excluded.push(der(0x82, domain.as_bytes()));
+        self
+    }
+
+    /// Excludes email addresses matching the given mailbox, host or domain.
+    pub fn excluded_email(&mut self, email: &str) -> &mut NameConstraints {
+        self.excluded.push(der(0x81, email.as_bytes()));
+        self
+    }
+
+    /// Excludes URIs whose host is within the given domain.
+    pub fn excluded_uri(&mut self, host: &str) -> &mut NameConstraints {
+        self.excluded.push(der(0x86, host.as_bytes()));
+        self
+    }
+
+    /// Excludes IP addresses within the given network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is larger than the length of `addr` in bits.
+    pub fn excluded_ip(&mut self, addr: IpAddr, prefix_len: u8) -> &mut NameConstraints {
+        self.excluded.push(ip_subtree(addr, prefix_len));
+        self
+    }
+
+    /// Excludes directory names within the given name.
+    pub fn excluded_dir_name(
+        &mut self,
+        name: &X509NameRef,
+    ) -> Result<&mut NameConstraints, ErrorStack> {
+        self.excluded.push(der(0xa4, &name.to_der()?));
+        Ok(self)
+    }
+
+    /// Return the `NameConstraints` extension as an `X509Extension`.
+    ///
+    /// Fails if no subtree was added, as the extension must not be empty.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        if self.permitted.is_empty() && self.excluded.is_empty() {
+            return Err(invalid_value());
+        }
+
+        fn subtrees(tag: u8, names: &[Vec<u8>]) -> Vec<u8> {
+            if names.is_empty() {
+                return vec![];
+            }
+
+            der(
+                tag,
+                &names
+                    .iter()
+                    .flat_map(|name| der(0x30, name))
+                    .collect::<Vec<_>>(),
+            )
+        }
+
+        let mut value = subtrees(0xa0, &self.permitted);
+        value.extend(subtrees(0xa1, &self.excluded));
+
+        new_extension(Nid::NAME_CONSTRAINTS, self.critical, &der(0x30, &value))
+    }
+}
+
+fn ip_subtree(addr: IpAddr, prefix_len: u8) -> Vec<u8> {
+    let addr = match addr {
+        IpAddr::V4(addr) => addr.octets().to_vec(),
+        IpAddr::V6(addr) => addr.octets().to_vec(),
+    };
+    assert!(
+        usize::from(prefix_len) <= addr.len() * 8,
+        "prefix length larger than the address"
+    );
+
+    let mut mask = vec![0; addr.len()];
+    for bit in 0..usize::from(prefix_len) {
+        mask[bit / 8] |= 0x80 >> (bit % 8);
+    }
+
+    let mut value = addr
+        .iter()
+        .zip(&mask)
+        .map(|(a, m)| a & m)
+        .collect::<Vec<_>>();
+    value.extend(mask);
+
+    der(0x87, &value)
+}
+
+/// An extension constraining the certificate policies of the paths a CA certificate is part of.
+pub struct PolicyConstraints {
+    critical: bool,
+    require_explicit_policy: Option<u32>,
+    inhibit_policy_mapping: Option<u32>,
+}
+
+impl Default for PolicyConstraints {
+    fn default() -> PolicyConstraints {
+        PolicyConstraints::new()
+    }
+}
+
+impl PolicyConstraints {
+    /// Construct a new `PolicyConstraints` extension.
+    pub fn new() -> PolicyConstraints {
+        PolicyConstraints {
+            critical: false,
+            require_explicit_policy: None,
+            inhibit_policy_mapping: None,
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut PolicyConstraints {
+        self.critical = true;
+        self
+    }
+
+    /// Requires an acceptable policy after the given number of additional certificates.
+    pub fn require_explicit_policy(&mut self, skip_certs: u32) -> &mut PolicyConstraints {
+        self.require_explicit_policy = Some(skip_certs);
+        self
+    }
+
+    /// Forbids policy mapping after the given number of additional certificates.
+    pub fn inhibit_policy_mapping(&mut self, skip_certs: u32) -> &mut PolicyConstraints {
+        self.inhibit_policy_mapping = Some(skip_certs);
+        self
+    }
+
+    /// Return the `PolicyConstraints` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = vec![];
+        if let Some(skip_certs) = self.require_explicit_policy {
+            value.extend(der(0x80, &integer(skip_certs)));
+        }
+        if let Some(skip_certs) = self.inhibit_policy_mapping {
+            value.extend(der(0x81, &integer(skip_certs)));
+        }
+
+        new_extension(Nid::POLICY_CONSTRAINTS, self.critical, &der(0x30, &value))
+    }
+}
+
+/// An extension declaring policies of the issuing CA's domain equivalent to policies of the
+/// subject CA's domain.
+pub struct PolicyMappings {
+    critical: bool,
+    items: Vec<(String, String)>,
+}
+
+impl Default for PolicyMappings {
+    fn default() -> PolicyMappings {
+        PolicyMappings::new()
+    }
+}
+
+impl PolicyMappings {
+    /// Construct a new `PolicyMappings` extension.
+    pub fn new() -> PolicyMappings {
+        PolicyMappings {
+            critical: false,
+            items: vec![],
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut PolicyMappings {
+        self.critical = true;
+        self
+    }
+
+    /// Maps a policy of the issuer's domain to a policy of the subject's domain.
+    pub fn mapping(
+        &mut self,
+        issuer_domain_policy: &str,
+        subject_domain_policy: &str,
+    ) -> &mut PolicyMappings {
+        self.items.push((
+            issuer_domain_policy.to_string(),
+            subject_domain_policy.to_string(),
+        ));
+        self
+    }
+
+    /// Return the `PolicyMappings` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = vec![];
+        for (issuer, subject) in &self.items {
+            let mut mapping = oid(issuer)?;
+            mapping.extend(oid(subject)?);
+            value.extend(der(0x30, &mapping));
+        }
+
+        new_extension(Nid::POLICY_MAPPINGS, self.critical, &der(0x30, &value))
+    }
+}
+
+/// An extension listing where the CRLs covering a certificate can be obtained.
+pub struct CrlDistributionPoints {
+    critical: bool,
+    items: Vec<String>,
+}
+
+impl Default for CrlDistributionPoints {
+    fn default() -> CrlDistributionPoints {
+        CrlDistributionPoints::new()
+    }
+}
+
+impl CrlDistributionPoints {
+    /// Construct a new `CrlDistributionPoints` extension.
+    pub fn new() -> CrlDistributionPoints {
+        CrlDistributionPoints {
+            critical: false,
+            items: vec![],
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut CrlDistributionPoints {
+        self.critical = true;
+        self
+    }
+
+    /// Adds a distribution point with the given URI.
+    pub fn uri(&mut self, uri: &str) -> &mut CrlDistributionPoints {
+        self.items.push(uri.to_string());
+        self
+    }
+
+    /// Return the `CrlDistributionPoints` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = vec![];
+        for uri in &self.items {
+            let full_name = der(0xa0, &der(0x86, uri.as_bytes()));
+            value.extend(der(0x30, &der(0xa0, &full_name)));
+        }
+
+        new_extension(
+            Nid::CRL_DISTRIBUTION_POINTS,
+            self.critical,
+            &der(0x30, &value),
+        )
+    }
+}
+
+/// An extension describing how to access information and services of the certificate's issuer.
+pub struct AuthorityInformationAccess {
+    critical: bool,
+    items: Vec<(&'static str, String)>,
+}
+
+impl Default for AuthorityInformationAccess {
+    fn default() -> AuthorityInformationAccess {
+        AuthorityInformationAccess::new()
+    }
+}
+
+impl AuthorityInformationAccess {
+    /// Construct a new `AuthorityInformationAccess` extension.
+    pub fn new() -> AuthorityInformationAccess {
+        AuthorityInformationAccess {
+            critical: false,
+            items: vec![],
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut AuthorityInformationAccess {
+        self.critical = true;
+        self
+    }
+
+    /// Adds the URI of an OCSP responder for the certificate.
+    pub fn ocsp(&mut self, uri: &str) -> &mut AuthorityInformationAccess {
+        self.items.push(("1.3.6.1.5.5.7.48.1", uri.to_string()));
+        self
+    }
+
+    /// Adds the URI where the certificate of the issuer can be obtained.
+    pub fn ca_issuers(&mut self, uri: &str) -> &mut AuthorityInformationAccess {
+        self.items.push(("1.3.6.1.5.5.7.48.2", uri.to_string()));
+        self
+    }
+
+    /// Return the `AuthorityInformationAccess` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = vec![];
+        for (method, uri) in &self.items {
+            let mut description = oid(method)?;
+            description.extend(der(0x86, uri.as_bytes()));
+            value.extend(der(0x30, &description));
+        }
+
+        new_extension(Nid::INFO_ACCESS, self.critical, &der(0x30, &value))
+    }
+}
+
+/// An extension listing the policies under which a certificate was issued.
+pub struct CertificatePolicies {
+    critical: bool,
+    items: Vec<(String, Option<String>)>,
+}
+
+impl Default for CertificatePolicies {
+    fn default() -> CertificatePolicies {
+        CertificatePolicies::new()
+    }
+}
+
+impl CertificatePolicies {
+    /// Construct a new `CertificatePolicies` extension.
+    pub fn new() -> CertificatePolicies {
+        CertificatePolicies {
+            critical: false,
+            items: vec![],
+        }
+    }
+
+    /// Sets the `critical` flag to `true`. The extension will be critical.
+    pub fn critical(&mut self) -> &mut CertificatePolicies {
+        self.critical = true;
+        self
+    }
+
+    /// Adds a policy, identified by its OID.
+    pub fn policy(&mut self, oid: &str) -> &mut CertificatePolicies {
+        self.items.push((oid.to_string(), None));
+        self
+    }
+
+    /// Adds a policy along with the URI of its Certification Practice Statement.
+    pub fn policy_with_cps(&mut self, oid: &str, cps_uri: &str) -> &mut CertificatePolicies {
+        self.items
+            .push((oid.to_string(), Some(cps_uri.to_string())));
+        self
+    }
+
+    /// Return the `CertificatePolicies` extension as an `X509Extension`.
+    pub fn build(&self) -> Result<X509Extension, ErrorStack> {
+        let mut value = vec![];
+        for (policy, cps_uri) in &self.items {
+            let mut information = oid(policy)?;
+            if let Some(cps_uri) = cps_uri {
+                // The URI is an IA5String.
+                if !cps_uri.is_ascii() {
+                    return Err(invalid_value());
+                }
+
+                let mut qualifier = oid("1.3.6.1.5.5.7.2.1")?;
+                qualifier.extend(der(0x16, cps_uri.as_bytes()));
+                information.extend(der(0x30, &der(0x30, &qualifier)));
+            }
+            value.extend(der(0x30, &information));
+        }
+
+        new_extension(Nid::CERTIFICATE_POLICIES, self.critical, &der(0x30, &value))
+    }
+}
+
+fn new_extension(nid: Nid, critical: bool, value: &[u8]) -> Result<X509Extension, ErrorStack> {
+    let oid = Asn1Object::from_str(nid.short_name()?)?;
+    X509Extension::new_from_der(&oid, critical, value)
+}
+
+fn invalid_value() -> ErrorStack {
+    unsafe {
+        ffi::ERR_put_error(
+            ffi::ERR_LIB_X509V3.0 as _,
+            0,
+            ffi::X509V3_R_INVALID_VALUE as _,
+            concat!(file!(), "\0").as_ptr() as *const _,
+            line!() as _,
+        );
+    }
+    ErrorStack::get()
+}
+
+fn oid(txt: &str) -> Result<Vec<u8>, ErrorStack> {
+    Asn1Object::from_str(txt)?.to_der()
+}
+
+fn integer(value: u32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let start = bytes
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(bytes.len() - 1);
+    let mut out = vec![];
+    if bytes[start] & 0x80 != 0 {
+        out.push(0);
+    }
+    out.extend_from_slice(&bytes[start..]);
+    out
+}
+
+pub(crate) fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = contents.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let start = bytes.iter().position(|&b| b != 0).unwrap();
+        out.push(0x80 | (bytes.len() - start) as u8);
+        out.extend_from_slice(&bytes[start..]);
+    }
+    out.extend_from_slice(contents);
+    out
+}
+
 fn append(value: &mut String, first: &mut bool, should: bool, element: &str) {
     if !should {
         return;
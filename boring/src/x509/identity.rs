@@ -0,0 +1,233 @@
+//! Checking a certificate, its private key and its chain for misconfigurations.
+//!
+//! A server identity with a mismatched key, an unordered chain or an expired certificate is
+//! otherwise only reported at the first handshake, with an error which rarely names the culprit.
+//! [`validate_identity`] checks it up front and lists everything wrong with it.
+//!
+//! # Example
+//!
+//! ```rust
+//! use boring::pkey::PKey;
+//! use boring::x509::identity::validate_identity_at;
+//! use boring::x509::X509;
+//!
+//! let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+//! let key = PKey::private_key_from_pem(include_bytes!("../../test/key.pem")).unwrap();
+//! let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+//!
+//! // Aug 1st, 2020
+//! let findings = validate_identity_at(&cert, &key, &[root], 1596240000).unwrap();
+//! assert!(findings.is_empty());
+//! ```
+
+use libc::time_t;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::asn1::{Asn1Time, Asn1TimeRef};
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::pkey::{HasPrivate, Id, PKeyRef};
+use crate::x509::{X509Ref, X509VerifyResult, X509};
+
+/// Certificates expiring within this many days are reported with
+/// [`IdentityFinding::ExpiresSoon`].
+pub const EXPIRY_WARNING_DAYS: i32 = 30;
+
+/// The smallest RSA key size, in bits, not reported with [`IdentityFinding::WeakKey`].
+pub const MIN_RSA_BITS: u32 = 2048;
+
+/// A problem found in a certificate, its private key or its chain.
+///
+/// The `depth` of a certificate is its position in the identity: zero is the leaf certificate,
+/// one the first certificate of the chain, and so on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdentityFinding {
+    /// The private key does not match the public key of the leaf certificate.
+    KeyMismatch,
+    /// The private key is an RSA key smaller than [`MIN_RSA_BITS`].
+    WeakKey {
+        /// The size of the key, in bits.
+        bits: u32,
+    },
+    /// The certificate at `depth` did not issue the one before it, so the chain is unordered or
+    /// incomplete.
+    ChainOutOfOrder {
+        /// The depth of the certificate.
+        depth: usize,
+    },
+    /// The certificate at `depth` issued the one before it, but its key does not verify the
+    /// signature.
+    BadSignature {
+        /// The depth of the certificate.
+        depth: usize,
+    },
+    /// The certificate at `depth` is signed with an algorithm using MD2, MD5 or SHA-1.
+    WeakSignatureAlgorithm {
+        /// The depth of the certificate.
+        depth: usize,
+        /// The signature algorithm.
+        algorithm: Nid,
+    },
+    /// The certificate at `depth` is not valid yet.
+    NotYetValid {
+        /// The depth of the certificate.
+        depth: usize,
+    },
+    /// The certificate at `depth` expired.
+    Expired {
+        /// The depth of the certificate.
+        depth: usize,
+    },
+    /// The certificate at `depth` expires within [`EXPIRY_WARNING_DAYS`].
+    ExpiresSoon {
+        /// The depth of the certificate.
+        depth: usize,
+        /// The number of full days left before the certificate expires.
+        days: i32,
+    },
+}
+
+impl fmt::Display for IdentityFinding {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdentityFinding::KeyMismatch => {
+                fmt.write_str("the private key does not match the certificate")
+            }
+            IdentityFinding::WeakKey { bits } => {
+                write!(fmt, "the private key is a weak {}-bit RSA key", bits)
+            }
+            IdentityFinding::ChainOutOfOrder { depth } => write!(
+                fmt,
+                "the certificate at depth {} did not issue the previous one",
+                depth
+            ),
+            IdentityFinding::BadSignature { depth } => write!(
+                fmt,
+                "the key of the certificate at depth {} does not verify the previous one",
+                depth
+            ),
+            IdentityFinding::WeakSignatureAlgorithm { depth, algorithm } => write!(
+                fmt,
+                "the certificate at depth {} uses the weak signature algorithm {}",
+                depth,
+                algorithm.short_name().unwrap_or("unknown")
+            ),
+            IdentityFinding::NotYetValid { depth } => {
+                write!(fmt, "the certificate at depth {} is not valid yet", depth)
+            }
+            IdentityFinding::Expired { depth } => {
+                write!(fmt, "the certificate at depth {} expired", depth)
+            }
+            IdentityFinding::ExpiresSoon { depth, days } => write!(
+                fmt,
+                "the certificate at depth {} expires in {} days",
+                depth, days
+            ),
+        }
+    }
+}
+
+/// Checks a leaf certificate, its private key and the chain sent along with it.
+///
+/// `chain` holds the certificates sent after the leaf, ordered from the leaf's issuer towards the
+/// root. Returns the problems found, which is empty for a well-formed identity.
+///
+/// The validity periods are checked against the current time.
+pub fn validate_identity<T>(
+    cert: &X509Ref,
+    key: &PKeyRef<T>,
+    chain: &[X509],
+) -> Result<Vec<IdentityFinding>, ErrorStack>
+where
+    T: HasPrivate,
+{
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as time_t);
+
+    validate_identity_at(cert, key, chain, now)
+}
+
+/// Like [`validate_identity`], but checks the validity periods against `time`, in seconds since
+/// the UNIX epoch.
+pub fn validate_identity_at<T>(
+    cert: &X509Ref,
+    key: &PKeyRef<T>,
+    chain: &[X509],
+    time: time_t,
+) -> Result<Vec<IdentityFinding>, ErrorStack>
+where
+    T: HasPrivate,
+{
+    let mut findings = vec![];
+
+    if !cert.public_key()?.public_eq(key) {
+        findings.push(IdentityFinding::KeyMismatch);
+    }
+
+    if key.id() == Id::RSA && key.bits() < MIN_RSA_BITS {
+        findings.push(IdentityFinding::WeakKey { bits: key.bits() });
+    }
+
+    let now = Asn1Time::from_unix(time)?;
+    let certs = std::iter::once(cert).chain(chain.iter().map(|cert| &**cert));
+    let mut subject: Option<&X509Ref> = None;
+
+    for (depth, cert) in certs.enumerate() {
+        if let Some(subject) = subject {
+            if cert.issued(subject) != X509VerifyResult::OK {
+                findings.push(IdentityFinding::ChainOutOfOrder { depth });
+            } else if !subject.verify(&*cert.public_key()?)? {
+                findings.push(IdentityFinding::BadSignature { depth });
+            }
+        }
+
+        // The signature of a self-signed root is not checked by clients.
+        if cert.issued(cert) != X509VerifyResult::OK {
+            let algorithm = cert.signature_algorithm().object().nid();
+            if is_weak_signature_algorithm(algorithm) {
+                findings.push(IdentityFinding::WeakSignatureAlgorithm { depth, algorithm });
+            }
+        }
+
+        findings.extend(check_validity(cert, depth, &now)?);
+        subject = Some(cert);
+    }
+
+    Ok(findings)
+}
+
+fn check_validity(
+    cert: &X509Ref,
+    depth: usize,
+    now: &Asn1TimeRef,
+) -> Result<Option<IdentityFinding>, ErrorStack> {
+    if cert.not_before().compare(now)?.is_gt() {
+        return Ok(Some(IdentityFinding::NotYetValid { depth }));
+    }
+
+    if cert.not_after().compare(now)?.is_le() {
+        return Ok(Some(IdentityFinding::Expired { depth }));
+    }
+
+    let days = now.diff(cert.not_after())?.days;
+    if days < EXPIRY_WARNING_DAYS {
+        return Ok(Some(IdentityFinding::ExpiresSoon { depth, days }));
+    }
+
+    Ok(None)
+}
+
+fn is_weak_signature_algorithm(algorithm: Nid) -> bool {
+    matches!(
+        algorithm,
+        Nid::MD2WITHRSAENCRYPTION
+            | Nid::MD5WITHRSAENCRYPTION
+            | Nid::SHA1WITHRSAENCRYPTION
+            | Nid::SHA1WITHRSA
+            | Nid::ECDSA_WITH_SHA1
+            | Nid::DSAWITHSHA1
+    )
+}
@@ -0,0 +1,209 @@
+//! Structured descriptions of certificates.
+//!
+//! [`X509Info`] collects the commonly inspected fields of a certificate into plain Rust values,
+//! for logging, audit tooling or debug endpoints. With the `serde` feature enabled, it implements
+//! `Serialize`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use boring::x509::X509;
+//!
+//! let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+//! let info = cert.info().unwrap();
+//!
+//! assert_eq!(info.subject, "CN=foobar.com,O=Internet Widgits Pty Ltd,ST=Some-State,C=AU");
+//! assert_eq!(info.public_key.algorithm, "rsaEncryption");
+//! ```
+
+use crate::ffi;
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use std::fmt::Write;
+use std::net::IpAddr;
+
+use crate::asn1::Asn1ObjectRef;
+use crate::bio::MemBio;
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pkey::Id;
+use crate::x509::{GeneralNameRef, X509ExtensionRef, X509Ref};
+
+/// A structured description of a certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct X509Info {
+    /// The version of the certificate, e.g. `3`.
+    pub version: i32,
+    /// The serial number, as uppercase hexadecimal.
+    pub serial_number: String,
+    /// The signature algorithm, e.g. `sha256WithRSAEncryption`.
+    pub signature_algorithm: String,
+    /// The issuer name, as an RFC 4514 string.
+    pub issuer: String,
+    /// The subject name, as an RFC 4514 string.
+    pub subject: String,
+    /// The subject alternative names, e.g. `DNS:example.com` or `IP:127.0.0.1`.
+    pub subject_alt_names: Vec<String>,
+    /// The start of the validity period, e.g. `Aug 14 17:00:03 2016 GMT`.
+    pub not_before: String,
+    /// The end of the validity period, e.g. `Aug 12 17:00:03 2026 GMT`.
+    pub not_after: String,
+    /// The subject public key.
+    pub public_key: PublicKeyInfo,
+    /// The extensions, in the order they appear in the certificate.
+    pub extensions: Vec<ExtensionInfo>,
+    /// The SHA-256 digest of the DER encoding of the certificate, as lowercase hexadecimal.
+    pub sha256_fingerprint: String,
+}
+
+/// A structured description of the public key of a certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct PublicKeyInfo {
+    /// The key algorithm, e.g. `rsaEncryption` or `id-ecPublicKey`.
+    pub algorithm: String,
+    /// The size of the key, in bits.
+    pub bits: u32,
+    /// The curve of elliptic curve keys, e.g. `prime256v1`.
+    pub curve: Option<String>,
+}
+
+/// A structured description of a certificate extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct ExtensionInfo {
+    /// The dotted OID of the extension, e.g. `2.5.29.19`.
+    pub oid: String,
+    /// The long name of the extension, if known, e.g. `X509v3 Basic Constraints`.
+    pub name: Option<String>,
+    /// Whether the extension is critical.
+    pub critical: bool,
+    /// The value of the extension, as printed by `openssl x509 -text` if the extension is
+    /// supported, or else as lowercase hexadecimal DER.
+    pub value: String,
+}
+
+impl X509Info {
+    pub(crate) fn new(cert: &X509Ref) -> Result<X509Info, ErrorStack> {
+        let subject_alt_names = cert
+            .subject_alt_names()
+            .map(|names| names.iter().filter_map(general_name).collect())
+            .unwrap_or_default();
+
+        let extensions = cert
+            .extensions()
+            .map(ExtensionInfo::new)
+            .collect::<Result<_, _>>()?;
+
+        Ok(X509Info {
+            version: cert.version() + 1,
+            serial_number: cert.serial_number().to_bn()?.to_hex_str()?.to_string(),
+            signature_algorithm: object_name(cert.signature_algorithm().object().nid())
+                .unwrap_or_else(|| cert.signature_algorithm().object().to_string()),
+            issuer: cert.issuer_name().to_rfc4514_string()?,
+            subject: cert.subject_name().to_rfc4514_string()?,
+            subject_alt_names,
+            not_before: cert.not_before().to_string(),
+            not_after: cert.not_after().to_string(),
+            public_key: PublicKeyInfo::new(cert)?,
+            extensions,
+            sha256_fingerprint: hex(&cert.digest(MessageDigest::sha256())?),
+        })
+    }
+}
+
+impl PublicKeyInfo {
+    fn new(cert: &X509Ref) -> Result<PublicKeyInfo, ErrorStack> {
+        let key = cert.public_key()?;
+
+        let curve = if key.id() == Id::EC {
+            key.ec_key()?.group().curve_name().and_then(object_name)
+        } else {
+            None
+        };
+
+        Ok(PublicKeyInfo {
+            algorithm: object_name(Nid::from_raw(key.id().as_raw()))
+                .unwrap_or_else(|| "unknown".to_string()),
+            bits: key.bits(),
+            curve,
+        })
+    }
+}
+
+impl ExtensionInfo {
+    fn new(ext: &X509ExtensionRef) -> Result<ExtensionInfo, ErrorStack> {
+        let bio = MemBio::new()?;
+        let value = unsafe {
+            if ffi::X509V3_EXT_print(bio.as_ptr(), ext.as_ptr(), 0, 0) > 0 {
+                String::from_utf8_lossy(bio.get_buf())
+                    .trim_end()
+                    .to_string()
+            } else {
+                // Unsupported extensions leave an error on the queue.
+                ffi::ERR_clear_error();
+                hex(ext.data().as_slice())
+            }
+        };
+
+        Ok(ExtensionInfo {
+            oid: object_oid(ext.object()),
+            name: object_name(ext.object().nid()),
+            critical: ext.critical(),
+            value,
+        })
+    }
+}
+
+fn object_name(nid: Nid) -> Option<String> {
+    match nid {
+        Nid::UNDEF => None,
+        nid => nid.long_name().ok().map(str::to_string),
+    }
+}
+
+fn object_oid(object: &Asn1ObjectRef) -> String {
+    unsafe {
+        let mut buf = [0; 80];
+        let len = ffi::OBJ_obj2txt(
+            buf.as_mut_ptr() as *mut _,
+            buf.len() as c_int,
+            object.as_ptr(),
+            1,
+        );
+        let len = (len.max(0) as usize).min(buf.len() - 1);
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+}
+
+fn general_name(name: &GeneralNameRef) -> Option<String> {
+    if let Some(dns) = name.dnsname() {
+        Some(format!("DNS:{}", dns))
+    } else if let Some(email) = name.email() {
+        Some(format!("email:{}", email))
+    } else if let Some(uri) = name.uri() {
+        Some(format!("URI:{}", uri))
+    } else if let Some(ip) = name.ipaddress() {
+        let ip = match ip.len() {
+            4 => IpAddr::from(<[u8; 4]>::try_from(ip).unwrap()),
+            16 => IpAddr::from(<[u8; 16]>::try_from(ip).unwrap()),
+            _ => return Some(format!("IP:{}", hex(ip))),
+        };
+        Some(format!("IP:{}", ip))
+    } else {
+        None
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(s, "{:02x}", byte).unwrap();
+    }
+    s
+}
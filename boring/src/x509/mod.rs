@@ -9,7 +9,7 @@
 
 use crate::ffi;
 use foreign_types::{ForeignType, ForeignTypeRef};
-use libc::{c_int, c_long, c_void};
+use libc::{c_int, c_long, c_ulong, c_void};
 use std::convert::TryInto;
 use std::error::Error;
 use std::ffi::{CStr, CString};
@@ -26,19 +26,24 @@ use crate::asn1::{
     Asn1BitStringRef, Asn1IntegerRef, Asn1Object, Asn1ObjectRef, Asn1StringRef, Asn1TimeRef,
     Asn1Type,
 };
-use crate::bio::MemBioSlice;
+use crate::bio::{MemBio, MemBioSlice};
 use crate::conf::ConfRef;
 use crate::error::ErrorStack;
 use crate::ex_data::Index;
-use crate::hash::{DigestBytes, MessageDigest};
+use crate::hash::{hash, DigestBytes, MessageDigest};
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, HasPublic, PKey, PKeyRef, Public};
+#[cfg(boringssl_ssl)]
 use crate::ssl::SslRef;
 use crate::stack::{Stack, StackRef, Stackable};
 use crate::string::OpensslString;
+use crate::x509::verify::X509VerifyParamRef;
 use crate::{cvt, cvt_n, cvt_p};
 
+pub mod chain;
 pub mod extension;
+pub mod identity;
+pub mod info;
 pub mod store;
 pub mod verify;
 
@@ -56,6 +61,7 @@ foreign_type_and_impl_send_sync! {
 impl X509StoreContext {
     /// Returns the index which can be used to obtain a reference to the `Ssl` associated with a
     /// context.
+    #[cfg(boringssl_ssl)]
     pub fn ssl_idx() -> Result<Index<X509StoreContext, SslRef>, ErrorStack> {
         unsafe { cvt_n(ffi::SSL_get_ex_data_X509_STORE_CTX_idx()).map(|idx| Index::from_raw(idx)) }
     }
@@ -217,6 +223,29 @@ impl X509StoreContextRef {
             }
         }
     }
+
+    /// Returns a mutable reference to the verification parameters of the context.
+    ///
+    /// This will only work inside of a call to `init`.
+    ///
+    /// This corresponds to [`X509_STORE_CTX_get0_param`].
+    ///
+    /// [`X509_STORE_CTX_get0_param`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_CTX_get0_param.html
+    pub fn verify_param_mut(&mut self) -> &mut X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::X509_STORE_CTX_get0_param(self.as_ptr())) }
+    }
+
+    /// Sets the trust setting checked against the auxiliary trust information of the trust
+    /// anchor.
+    ///
+    /// This will only work inside of a call to `init`.
+    ///
+    /// This corresponds to [`X509_STORE_CTX_set_trust`].
+    ///
+    /// [`X509_STORE_CTX_set_trust`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_CTX_set_trust.html
+    pub fn set_trust(&mut self, trust: X509TrustId) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_CTX_set_trust(self.as_ptr(), trust.as_raw())).map(|_| ()) }
+    }
 }
 
 /// A builder used to construct an `X509`.
@@ -437,6 +466,21 @@ impl X509Ref {
         }
     }
 
+    /// Returns the SPIFFE ID of this certificate, if it is an X.509 SVID.
+    ///
+    /// An X.509 SVID holds its SPIFFE ID, a URI such as `spiffe://example.org/service`, as its
+    /// only URI subject alternative name. `None` is returned if the certificate has no URI
+    /// subject alternative name, several of them, or one which is not a SPIFFE ID.
+    pub fn spiffe_id(&self) -> Option<String> {
+        let names = self.subject_alt_names()?;
+        let mut uris = names.iter().filter_map(GeneralNameRef::uri);
+
+        match (uris.next(), uris.next()) {
+            (Some(uri), None) if uri.starts_with("spiffe://") => Some(uri.to_owned()),
+            _ => None,
+        }
+    }
+
     /// Returns this certificate's issuer alternative name entries, if they exist.
     ///
     /// This corresponds to [`X509_get_ext_d2i`] called with `NID_issuer_alt_name`.
@@ -489,6 +533,15 @@ impl X509Ref {
         }
     }
 
+    /// Returns the SHA-256 digest of the DER-encoded SubjectPublicKeyInfo of the certificate.
+    ///
+    /// This is the value used by public key pinning schemes such as HPKP, where it is
+    /// base64-encoded. See [`PinSet`](crate::ssl::PinSet).
+    pub fn spki_sha256(&self) -> Result<DigestBytes, ErrorStack> {
+        let spki = self.public_key()?.public_key_to_der()?;
+        hash(MessageDigest::sha256(), &spki)
+    }
+
     #[deprecated(since = "0.10.9", note = "renamed to digest")]
     pub fn fingerprint(&self, hash_type: MessageDigest) -> Result<Vec<u8>, ErrorStack> {
         self.digest(hash_type).map(|b| b.to_vec())
@@ -576,6 +629,69 @@ impl X509Ref {
         }
     }
 
+    /// Returns this certificate's version number.
+    ///
+    /// The version number is zero-based: version 3 certificates return `2`.
+    ///
+    /// This corresponds to [`X509_get_version`].
+    ///
+    /// [`X509_get_version`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_get_version.html
+    pub fn version(&self) -> i32 {
+        unsafe { ffi::X509_get_version(self.as_ptr()) as i32 }
+    }
+
+    /// Returns an iterator over this certificate's extensions.
+    ///
+    /// This corresponds to [`X509_get_ext`].
+    ///
+    /// [`X509_get_ext`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_get_ext.html
+    pub fn extensions(&self) -> X509Extensions<'_> {
+        X509Extensions { cert: self, loc: 0 }
+    }
+
+    /// Returns the first extension of the certificate with the object identifier `oid`, if any.
+    ///
+    /// Together with [`X509ExtensionRef::critical`] and [`X509ExtensionRef::data`], this gives
+    /// access to extensions BoringSSL does not parse, such as private ones.
+    ///
+    /// This corresponds to [`X509_get_ext_by_OBJ`].
+    ///
+    /// [`X509_get_ext_by_OBJ`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_get_ext_by_OBJ.html
+    pub fn extension(&self, oid: &Asn1ObjectRef) -> Option<&X509ExtensionRef> {
+        unsafe {
+            let loc = ffi::X509_get_ext_by_OBJ(self.as_ptr(), oid.as_ptr(), -1);
+            if loc < 0 {
+                return None;
+            }
+
+            let ext = ffi::X509_get_ext(self.as_ptr(), loc);
+            assert!(!ext.is_null());
+            Some(X509ExtensionRef::from_ptr(ext))
+        }
+    }
+
+    /// Returns a human-readable description of the certificate, as printed by
+    /// `openssl x509 -text`.
+    ///
+    /// This corresponds to [`X509_print`].
+    ///
+    /// [`X509_print`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_print.html
+    pub fn to_text(&self) -> Result<Vec<u8>, ErrorStack> {
+        let bio = MemBio::new()?;
+        unsafe {
+            cvt(ffi::X509_print(bio.as_ptr(), self.as_ptr()))?;
+        }
+
+        Ok(bio.get_buf().to_owned())
+    }
+
+    /// Returns a structured description of the certificate.
+    ///
+    /// See [`X509Info`](info::X509Info).
+    pub fn info(&self) -> Result<info::X509Info, ErrorStack> {
+        info::X509Info::new(self)
+    }
+
     to_pem! {
         /// Serializes the certificate into a PEM-encoded X509 structure.
         ///
@@ -610,6 +726,30 @@ impl ToOwned for X509Ref {
     }
 }
 
+/// An iterator over the extensions of an `X509`.
+pub struct X509Extensions<'a> {
+    cert: &'a X509Ref,
+    loc: c_int,
+}
+
+impl<'a> Iterator for X509Extensions<'a> {
+    type Item = &'a X509ExtensionRef;
+
+    fn next(&mut self) -> Option<&'a X509ExtensionRef> {
+        unsafe {
+            if self.loc >= ffi::X509_get_ext_count(self.cert.as_ptr()) {
+                return None;
+            }
+
+            let ext = ffi::X509_get_ext(self.cert.as_ptr(), self.loc);
+            assert!(!ext.is_null());
+            self.loc += 1;
+
+            Some(X509ExtensionRef::from_ptr(ext))
+        }
+    }
+}
+
 impl X509 {
     /// Returns a new builder.
     pub fn builder() -> Result<X509Builder, ErrorStack> {
@@ -830,6 +970,41 @@ impl X509Extension {
         }
     }
 
+    /// Constructs an extension from its object identifier and the DER encoding of its value.
+    ///
+    /// This allows adding extensions the extension module has no builder for, including private
+    /// ones.
+    ///
+    /// This corresponds to [`X509_EXTENSION_create_by_OBJ`].
+    ///
+    /// [`X509_EXTENSION_create_by_OBJ`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_EXTENSION_create_by_OBJ.html
+    pub fn new_from_der(
+        oid: &Asn1ObjectRef,
+        critical: bool,
+        der_contents: &[u8],
+    ) -> Result<X509Extension, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let value = cvt_p(ffi::ASN1_OCTET_STRING_new())?;
+            let extension = cvt(ffi::ASN1_OCTET_STRING_set(
+                value,
+                der_contents.as_ptr(),
+                der_contents.len().try_into().unwrap(),
+            ))
+            .and_then(|_| {
+                cvt_p(ffi::X509_EXTENSION_create_by_OBJ(
+                    ptr::null_mut(),
+                    oid.as_ptr(),
+                    critical as c_int,
+                    value,
+                ))
+            });
+            ffi::ASN1_OCTET_STRING_free(value);
+
+            extension.map(|p| X509Extension::from_ptr(p))
+        }
+    }
+
     pub(crate) unsafe fn new_internal(
         nid: Nid,
         critical: bool,
@@ -847,6 +1022,47 @@ impl X509ExtensionRef {
         to_der,
         ffi::i2d_X509_EXTENSION
     }
+
+    /// Returns the type of the extension.
+    ///
+    /// This corresponds to [`X509_EXTENSION_get_object`].
+    ///
+    /// [`X509_EXTENSION_get_object`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_EXTENSION_get_object.html
+    pub fn object(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_ptr(ffi::X509_EXTENSION_get_object(self.as_ptr())) }
+    }
+
+    /// Returns `true` if the extension is critical.
+    ///
+    /// This corresponds to [`X509_EXTENSION_get_critical`].
+    ///
+    /// [`X509_EXTENSION_get_critical`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_EXTENSION_get_critical.html
+    pub fn critical(&self) -> bool {
+        unsafe { ffi::X509_EXTENSION_get_critical(self.as_ptr()) != 0 }
+    }
+
+    /// Returns the DER-encoded value of the extension.
+    ///
+    /// This corresponds to [`X509_EXTENSION_get_data`].
+    ///
+    /// [`X509_EXTENSION_get_data`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_EXTENSION_get_data.html
+    pub fn data(&self) -> &Asn1StringRef {
+        unsafe { Asn1StringRef::from_ptr(ffi::X509_EXTENSION_get_data(self.as_ptr())) }
+    }
+
+    /// Returns `true` if BoringSSL understands the extension.
+    ///
+    /// Certificates with a critical extension which is not supported fail verification, unless
+    /// they are accepted with [`X509VerifyFlags::IGNORE_CRITICAL`] and checked by the
+    /// application. Registering a name with [`Nid::create`] does not make an extension supported.
+    ///
+    /// This corresponds to [`X509_supported_extension`].
+    ///
+    /// [`X509VerifyFlags::IGNORE_CRITICAL`]: crate::x509::verify::X509VerifyFlags::IGNORE_CRITICAL
+    /// [`X509_supported_extension`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/x509.h.html#X509_supported_extension
+    pub fn is_supported(&self) -> bool {
+        unsafe { ffi::X509_supported_extension(self.as_ptr()) != 0 }
+    }
 }
 
 /// A builder used to construct an `X509Name`.
@@ -957,6 +1173,29 @@ impl X509NameBuilder {
         }
     }
 
+    // Adds an entry to the last RDN when `set` is -1, or to a new RDN when it is 0.
+    fn append_entry_by_text_to_set(
+        &mut self,
+        field: &str,
+        value: &str,
+        set: c_int,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            let field = CString::new(field).unwrap();
+            assert!(value.len() <= ValueLen::max_value() as usize);
+            cvt(ffi::X509_NAME_add_entry_by_txt(
+                self.0.as_ptr(),
+                field.as_ptr() as *mut _,
+                ffi::MBSTRING_UTF8,
+                value.as_ptr(),
+                value.len() as ValueLen,
+                -1,
+                set,
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Return an `X509Name`.
     pub fn build(self) -> X509Name {
         // Round-trip through bytes because OpenSSL is not const correct and
@@ -988,6 +1227,7 @@ impl X509Name {
     /// Loads subject names from a file containing PEM-formatted certificates.
     ///
     /// This is commonly used in conjunction with `SslContextBuilder::set_client_ca_list`.
+    #[cfg(boringssl_ssl)]
     pub fn load_client_ca_file<P: AsRef<Path>>(file: P) -> Result<Stack<X509Name>, ErrorStack> {
         let file = CString::new(file.as_ref().as_os_str().to_str().unwrap()).unwrap();
         unsafe { cvt_p(ffi::SSL_load_client_CA_file(file.as_ptr())).map(|p| Stack::from_ptr(p)) }
@@ -1004,6 +1244,129 @@ impl X509Name {
         ffi::d2i_X509_NAME,
         ::libc::c_long
     }
+
+    /// Parses a distinguished name in the string representation of RFC 4514, such as
+    /// `CN=example.com,O=Example\, Inc.,C=US`.
+    ///
+    /// Attribute types are either short names, long names or dotted OIDs. Values are stored as
+    /// UTF-8 strings, or as the string type the attribute requires. Values in the hexadecimal
+    /// `#` form are not supported.
+    pub fn from_rfc4514(dn: &str) -> Result<X509Name, X509NameParseError> {
+        let rdns = parse_rfc4514(dn).ok_or(X509NameParseError::Syntax)?;
+
+        let mut builder = X509Name::builder()?;
+        // The string representation lists entries from the last one to the first one.
+        for rdn in rdns.iter().rev() {
+            for (i, (field, value)) in rdn.iter().rev().enumerate() {
+                let set = if i == 0 { 0 } else { -1 };
+                builder.append_entry_by_text_to_set(field, value, set)?;
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
+
+// Splits a distinguished name into RDNs, themselves split into attribute types and unescaped
+// values. Returns `None` if the string is malformed.
+fn parse_rfc4514(dn: &str) -> Option<Vec<Vec<(String, String)>>> {
+    let mut rdns = vec![];
+    if dn.trim().is_empty() {
+        return Some(rdns);
+    }
+
+    let mut rdn = vec![];
+    let mut chars = dn.chars().peekable();
+
+    loop {
+        let mut field = String::new();
+        loop {
+            match chars.next()? {
+                '=' => break,
+                c => field.push(c),
+            }
+        }
+
+        let field = field.trim();
+        if field.is_empty() {
+            return None;
+        }
+
+        let mut value = vec![];
+        if chars.peek() == Some(&'#') {
+            return None;
+        }
+
+        let end = loop {
+            let c = match chars.next() {
+                Some(c) => c,
+                None => break None,
+            };
+
+            match c {
+                ',' | '+' => break Some(c),
+                '\\' => match chars.next()? {
+                    c @ (' ' | '"' | '#' | '+' | ',' | ';' | '<' | '=' | '>' | '\\') => {
+                        value.push(c as u8)
+                    }
+                    hi => {
+                        let lo = chars.next()?;
+                        let hi = hi.to_digit(16)?;
+                        let lo = lo.to_digit(16)?;
+                        value.push((hi * 16 + lo) as u8);
+                    }
+                },
+                c => {
+                    let mut buf = [0; 4];
+                    value.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        };
+
+        rdn.push((field.to_string(), String::from_utf8(value).ok()?));
+
+        match end {
+            Some('+') => {}
+            Some(_) => rdns.push(mem::take(&mut rdn)),
+            None => {
+                rdns.push(rdn);
+                return Some(rdns);
+            }
+        }
+    }
+}
+
+/// An error parsing the string representation of a distinguished name.
+#[derive(Debug)]
+pub enum X509NameParseError {
+    /// The string is malformed.
+    Syntax,
+    /// An attribute was rejected, e.g. because its type is unknown.
+    Stack(ErrorStack),
+}
+
+impl From<ErrorStack> for X509NameParseError {
+    fn from(e: ErrorStack) -> X509NameParseError {
+        X509NameParseError::Stack(e)
+    }
+}
+
+impl fmt::Display for X509NameParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            X509NameParseError::Syntax => fmt.write_str("malformed distinguished name"),
+            X509NameParseError::Stack(e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl Error for X509NameParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            X509NameParseError::Syntax => None,
+            X509NameParseError::Stack(e) => Some(e),
+        }
+    }
 }
 
 impl Stackable for X509Name {
@@ -1029,6 +1392,43 @@ impl X509NameRef {
         }
     }
 
+    /// Returns the type and value of each entry, in order, with values converted to UTF-8.
+    ///
+    /// Values are converted from the string type they are encoded in, such as `BMPString`.
+    pub fn to_utf8_entries(&self) -> Result<Vec<(Nid, String)>, ErrorStack> {
+        self.entries()
+            .map(|entry| {
+                let value = entry.data().as_utf8()?;
+                Ok((entry.object().nid(), value.to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns the string representation of the name defined by RFC 4514, such as
+    /// `CN=example.com,O=Example\, Inc.,C=US`.
+    ///
+    /// Values are written as UTF-8, and entries with unknown types as a dotted OID and the
+    /// hexadecimal encoding of the value.
+    ///
+    /// This corresponds to [`X509_NAME_print_ex`] with the `XN_FLAG_RFC2253` flags.
+    ///
+    /// [`X509_NAME_print_ex`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_NAME_print_ex.html
+    pub fn to_rfc4514_string(&self) -> Result<String, ErrorStack> {
+        let bio = MemBio::new()?;
+        let flags = ffi::XN_FLAG_RFC2253 & !ffi::ASN1_STRFLGS_ESC_MSB;
+
+        unsafe {
+            cvt_n(ffi::X509_NAME_print_ex(
+                bio.as_ptr(),
+                self.as_ptr(),
+                0,
+                flags as c_ulong,
+            ))?;
+        }
+
+        Ok(String::from_utf8_lossy(bio.get_buf()).into_owned())
+    }
+
     to_der! {
         /// Serializes the certificate into a DER-encoded X509 name structure.
         ///
@@ -1215,6 +1615,32 @@ impl X509ReqBuilder {
         }
     }
 
+    /// Adds an attribute with a string value.
+    ///
+    /// The value is encoded with the string type the attribute requires.
+    ///
+    /// This corresponds to [`X509_REQ_add1_attr_by_NID`].
+    ///
+    /// [`X509_REQ_add1_attr_by_NID`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_add1_attr_by_NID.html
+    pub fn add_attribute_by_nid(&mut self, nid: Nid, value: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(value.len() <= c_int::max_value() as usize);
+            cvt(ffi::X509_REQ_add1_attr_by_NID(
+                self.0.as_ptr(),
+                nid.as_raw(),
+                ffi::MBSTRING_UTF8,
+                value.as_ptr(),
+                value.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the challenge password, which the requester may use to revoke the certificate.
+    pub fn set_challenge_password(&mut self, password: &str) -> Result<(), ErrorStack> {
+        self.add_attribute_by_nid(Nid::PKCS9_CHALLENGEPASSWORD, password)
+    }
+
     /// Sign the request using a private key.
     ///
     /// This corresponds to [`X509_REQ_sign`].
@@ -1360,66 +1786,795 @@ impl X509ReqRef {
             Ok(Stack::from_ptr(extensions))
         }
     }
-}
 
-/// The result of peer certificate verification.
-#[derive(Copy, Clone, PartialEq, Eq)]
-pub struct X509VerifyResult(c_int);
+    /// Returns the requested subject alternative name entries, if they exist.
+    ///
+    /// This corresponds to [`X509V3_get_d2i`] called on the requested extensions with
+    /// `NID_subject_alt_name`.
+    ///
+    /// [`X509V3_get_d2i`]: https://www.openssl.org/docs/man1.1.0/crypto/X509V3_get_d2i.html
+    pub fn subject_alt_names(&self) -> Option<Stack<GeneralName>> {
+        let extensions = self.extensions().ok()?;
 
-impl fmt::Debug for X509VerifyResult {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.debug_struct("X509VerifyResult")
-            .field("code", &self.0)
-            .field("error", &self.error_string())
-            .finish()
+        unsafe {
+            let stack = ffi::X509V3_get_d2i(
+                extensions.as_ptr(),
+                ffi::NID_subject_alt_name,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if stack.is_null() {
+                None
+            } else {
+                Some(Stack::from_ptr(stack as *mut _))
+            }
+        }
     }
-}
 
-impl fmt::Display for X509VerifyResult {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.write_str(self.error_string())
+    /// Returns an iterator over the attributes of the certificate request.
+    ///
+    /// The requested extensions are themselves stored in an attribute.
+    pub fn attributes(&self) -> X509ReqAttributes<'_> {
+        X509ReqAttributes { req: self, loc: 0 }
     }
-}
-
-impl Error for X509VerifyResult {}
 
-impl X509VerifyResult {
-    /// Creates an `X509VerifyResult` from a raw error number.
+    /// Returns the first attribute of the given type.
     ///
-    /// # Safety
+    /// This corresponds to [`X509_REQ_get_attr_by_NID`].
     ///
-    /// Some methods on `X509VerifyResult` are not thread safe if the error
-    /// number is invalid.
-    pub unsafe fn from_raw(err: c_int) -> X509VerifyResult {
-        X509VerifyResult(err)
+    /// [`X509_REQ_get_attr_by_NID`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REQ_get_attr_by_NID.html
+    pub fn attribute_by_nid(&self, nid: Nid) -> Option<&X509AttributeRef> {
+        unsafe {
+            let loc = ffi::X509_REQ_get_attr_by_NID(self.as_ptr(), nid.as_raw(), -1);
+            if loc < 0 {
+                return None;
+            }
+
+            let attr = ffi::X509_REQ_get_attr(self.as_ptr(), loc);
+            assert!(!attr.is_null());
+            Some(X509AttributeRef::from_ptr(attr))
+        }
     }
 
-    /// Return the integer representation of an `X509VerifyResult`.
-    #[allow(clippy::trivially_copy_pass_by_ref)]
-    pub fn as_raw(&self) -> c_int {
-        self.0
+    /// Returns the challenge password, if set.
+    pub fn challenge_password(&self) -> Option<String> {
+        let password = self
+            .attribute_by_nid(Nid::PKCS9_CHALLENGEPASSWORD)?
+            .string_value(0)?
+            .as_utf8()
+            .ok()?;
+
+        Some(password.to_string())
     }
+}
 
-    /// Return a human readable error string from the verification error.
-    ///
-    /// This corresponds to [`X509_verify_cert_error_string`].
-    ///
-    /// [`X509_verify_cert_error_string`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_verify_cert_error_string.html
-    #[allow(clippy::trivially_copy_pass_by_ref)]
-    pub fn error_string(&self) -> &'static str {
-        ffi::init();
+/// An iterator over the attributes of an `X509Req`.
+pub struct X509ReqAttributes<'a> {
+    req: &'a X509ReqRef,
+    loc: c_int,
+}
+
+impl<'a> Iterator for X509ReqAttributes<'a> {
+    type Item = &'a X509AttributeRef;
 
+    fn next(&mut self) -> Option<&'a X509AttributeRef> {
         unsafe {
-            let s = ffi::X509_verify_cert_error_string(self.0 as c_long);
-            str::from_utf8(CStr::from_ptr(s).to_bytes()).unwrap()
+            if self.loc >= ffi::X509_REQ_get_attr_count(self.req.as_ptr()) {
+                return None;
+            }
+
+            let attr = ffi::X509_REQ_get_attr(self.req.as_ptr(), self.loc);
+            assert!(!attr.is_null());
+            self.loc += 1;
+
+            Some(X509AttributeRef::from_ptr(attr))
         }
     }
+}
 
-    /// Successful peer certifiate verification.
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_ATTRIBUTE;
+    fn drop = ffi::X509_ATTRIBUTE_free;
+
+    /// An attribute of a certificate request.
+    pub struct X509Attribute;
+}
+
+impl X509AttributeRef {
+    /// Returns the type of the attribute.
+    ///
+    /// This corresponds to [`X509_ATTRIBUTE_get0_object`].
+    ///
+    /// [`X509_ATTRIBUTE_get0_object`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_ATTRIBUTE_get0_object.html
+    pub fn object(&self) -> &Asn1ObjectRef {
+        unsafe { Asn1ObjectRef::from_ptr(ffi::X509_ATTRIBUTE_get0_object(self.as_ptr())) }
+    }
+
+    /// Returns the number of values of the attribute.
+    ///
+    /// This corresponds to [`X509_ATTRIBUTE_count`].
+    ///
+    /// [`X509_ATTRIBUTE_count`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_ATTRIBUTE_count.html
+    pub fn count(&self) -> usize {
+        unsafe { ffi::X509_ATTRIBUTE_count(self.as_ptr()) as usize }
+    }
+
+    /// Returns the value at index `idx`, if it is a string.
+    ///
+    /// Values of constructed types, such as the requested extensions, are returned as their
+    /// DER encoding.
+    ///
+    /// This corresponds to [`X509_ATTRIBUTE_get0_type`].
+    ///
+    /// [`X509_ATTRIBUTE_get0_type`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_ATTRIBUTE_get0_type.html
+    pub fn string_value(&self, idx: usize) -> Option<&Asn1StringRef> {
+        unsafe {
+            let value = ffi::X509_ATTRIBUTE_get0_type(self.as_ptr(), idx.try_into().ok()?);
+            if value.is_null() {
+                return None;
+            }
+
+            match (*value).type_ {
+                ffi::V_ASN1_BOOLEAN | ffi::V_ASN1_NULL | ffi::V_ASN1_OBJECT => None,
+                _ => Some(Asn1StringRef::from_ptr((*value).value.asn1_string)),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for X509AttributeRef {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("X509Attribute")
+            .field("object", &self.object())
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+/// The reason a certificate was revoked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct X509CrlReason(c_int);
+
+impl X509CrlReason {
+    pub const UNSPECIFIED: X509CrlReason = X509CrlReason(ffi::CRL_REASON_UNSPECIFIED as c_int);
+    pub const KEY_COMPROMISE: X509CrlReason =
+        X509CrlReason(ffi::CRL_REASON_KEY_COMPROMISE as c_int);
+    pub const CA_COMPROMISE: X509CrlReason = X509CrlReason(ffi::CRL_REASON_CA_COMPROMISE as c_int);
+    pub const AFFILIATION_CHANGED: X509CrlReason =
+        X509CrlReason(ffi::CRL_REASON_AFFILIATION_CHANGED as c_int);
+    pub const SUPERSEDED: X509CrlReason = X509CrlReason(ffi::CRL_REASON_SUPERSEDED as c_int);
+    pub const CESSATION_OF_OPERATION: X509CrlReason =
+        X509CrlReason(ffi::CRL_REASON_CESSATION_OF_OPERATION as c_int);
+    pub const CERTIFICATE_HOLD: X509CrlReason =
+        X509CrlReason(ffi::CRL_REASON_CERTIFICATE_HOLD as c_int);
+    pub const REMOVE_FROM_CRL: X509CrlReason =
+        X509CrlReason(ffi::CRL_REASON_REMOVE_FROM_CRL as c_int);
+    pub const PRIVILEGE_WITHDRAWN: X509CrlReason =
+        X509CrlReason(ffi::CRL_REASON_PRIVILEGE_WITHDRAWN as c_int);
+    pub const AA_COMPROMISE: X509CrlReason = X509CrlReason(ffi::CRL_REASON_AA_COMPROMISE as c_int);
+
+    /// Constructs an `X509CrlReason` from a raw reason code.
+    pub fn from_raw(raw: c_int) -> X509CrlReason {
+        X509CrlReason(raw)
+    }
+
+    /// Returns the raw reason code.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_REVOKED;
+    fn drop = ffi::X509_REVOKED_free;
+
+    /// An entry of a certificate revocation list.
+    pub struct X509Revoked;
+}
+
+impl Stackable for X509Revoked {
+    type StackType = ffi::stack_st_X509_REVOKED;
+}
+
+impl X509Revoked {
+    /// Creates an entry revoking the certificate with the given serial number.
+    pub fn new(
+        serial_number: &Asn1IntegerRef,
+        revocation_date: &Asn1TimeRef,
+    ) -> Result<X509Revoked, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let revoked = X509Revoked::from_ptr(cvt_p(ffi::X509_REVOKED_new())?);
+            cvt(ffi::X509_REVOKED_set_serialNumber(
+                revoked.as_ptr(),
+                serial_number.as_ptr(),
+            ))?;
+            cvt(ffi::X509_REVOKED_set_revocationDate(
+                revoked.as_ptr(),
+                revocation_date.as_ptr(),
+            ))?;
+            Ok(revoked)
+        }
+    }
+}
+
+impl X509RevokedRef {
+    /// Returns the serial number of the revoked certificate.
+    ///
+    /// This corresponds to [`X509_REVOKED_get0_serialNumber`].
+    ///
+    /// [`X509_REVOKED_get0_serialNumber`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_get0_serialNumber.html
+    pub fn serial_number(&self) -> &Asn1IntegerRef {
+        unsafe {
+            let serial = ffi::X509_REVOKED_get0_serialNumber(self.as_ptr());
+            assert!(!serial.is_null());
+            Asn1IntegerRef::from_ptr(serial as *mut _)
+        }
+    }
+
+    /// Returns the time the certificate was revoked.
+    ///
+    /// This corresponds to [`X509_REVOKED_get0_revocationDate`].
+    ///
+    /// [`X509_REVOKED_get0_revocationDate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_REVOKED_get0_revocationDate.html
+    pub fn revocation_date(&self) -> &Asn1TimeRef {
+        unsafe {
+            let date = ffi::X509_REVOKED_get0_revocationDate(self.as_ptr());
+            assert!(!date.is_null());
+            Asn1TimeRef::from_ptr(date as *mut _)
+        }
+    }
+
+    /// Returns the reason the certificate was revoked, if the entry specifies one.
+    pub fn reason(&self) -> Option<X509CrlReason> {
+        unsafe {
+            let reason = ffi::X509_REVOKED_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_crl_reason,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ) as *mut ffi::ASN1_ENUMERATED;
+            if reason.is_null() {
+                return None;
+            }
+
+            let raw = ffi::ASN1_ENUMERATED_get(reason);
+            ffi::ASN1_ENUMERATED_free(reason);
+            Some(X509CrlReason(raw as c_int))
+        }
+    }
+
+    /// Sets the reason the certificate was revoked.
+    ///
+    /// This adds a `reasonCode` extension to the entry.
+    pub fn set_reason(&mut self, reason: X509CrlReason) -> Result<(), ErrorStack> {
+        unsafe {
+            let value = cvt_p(ffi::ASN1_ENUMERATED_new())?;
+            let r = cvt(ffi::ASN1_ENUMERATED_set(value, reason.0 as c_long)).and_then(|_| {
+                cvt(ffi::X509_REVOKED_add1_ext_i2d(
+                    self.as_ptr(),
+                    ffi::NID_crl_reason,
+                    value as *mut c_void,
+                    0,
+                    ffi::X509V3_ADD_REPLACE as c_ulong,
+                ))
+            });
+            ffi::ASN1_ENUMERATED_free(value);
+            r.map(|_| ())
+        }
+    }
+
+    /// Sets the time at which the key of the certificate is known or suspected to have been
+    /// compromised.
+    ///
+    /// This adds an `invalidityDate` extension to the entry.
+    pub fn set_invalidity_date(&mut self, date: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let value = cvt_p(ffi::ASN1_TIME_to_generalizedtime(
+                date.as_ptr(),
+                ptr::null_mut(),
+            ))?;
+            let r = cvt(ffi::X509_REVOKED_add1_ext_i2d(
+                self.as_ptr(),
+                ffi::NID_invalidity_date,
+                value as *mut c_void,
+                0,
+                ffi::X509V3_ADD_REPLACE as c_ulong,
+            ));
+            ffi::ASN1_GENERALIZEDTIME_free(value);
+            r.map(|_| ())
+        }
+    }
+}
+
+/// A builder used to construct an `X509Crl`.
+pub struct X509CrlBuilder(X509Crl);
+
+impl X509CrlBuilder {
+    /// Creates a new builder for a version 2 CRL.
+    pub fn new() -> Result<X509CrlBuilder, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let crl = X509Crl::from_ptr(cvt_p(ffi::X509_CRL_new())?);
+            cvt(ffi::X509_CRL_set_version(crl.as_ptr(), 1))?;
+            Ok(X509CrlBuilder(crl))
+        }
+    }
+
+    /// Sets the issuer name of the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_set_issuer_name`].
+    ///
+    /// [`X509_CRL_set_issuer_name`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_set_issuer_name.html
+    pub fn set_issuer_name(&mut self, issuer_name: &X509NameRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_set_issuer_name(
+                self.0.as_ptr(),
+                issuer_name.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the time the CRL was issued, its `thisUpdate` field.
+    ///
+    /// This corresponds to [`X509_CRL_set1_lastUpdate`].
+    ///
+    /// [`X509_CRL_set1_lastUpdate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_set1_lastUpdate.html
+    pub fn set_last_update(&mut self, last_update: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_set1_lastUpdate(
+                self.0.as_ptr(),
+                last_update.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the time by which the next CRL will be issued.
+    ///
+    /// This corresponds to [`X509_CRL_set1_nextUpdate`].
+    ///
+    /// [`X509_CRL_set1_nextUpdate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_set1_nextUpdate.html
+    pub fn set_next_update(&mut self, next_update: &Asn1TimeRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_set1_nextUpdate(
+                self.0.as_ptr(),
+                next_update.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a revoked certificate entry to the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_add0_revoked`].
+    ///
+    /// [`X509_CRL_add0_revoked`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_add0_revoked.html
+    pub fn add_revoked(&mut self, revoked: X509Revoked) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add0_revoked(
+                self.0.as_ptr(),
+                revoked.as_ptr(),
+            ))?;
+            mem::forget(revoked);
+            Ok(())
+        }
+    }
+
+    /// Sets the CRL number, a monotonically increasing sequence number for the CRLs of an
+    /// issuer.
+    ///
+    /// This adds a `cRLNumber` extension to the CRL.
+    pub fn set_crl_number(&mut self, crl_number: &Asn1IntegerRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add1_ext_i2d(
+                self.0.as_ptr(),
+                ffi::NID_crl_number,
+                crl_number.as_ptr() as *mut c_void,
+                0,
+                ffi::X509V3_ADD_REPLACE as c_ulong,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Return an `X509v3Context`. This context object can be used to construct
+    /// certain extensions of the CRL, such as the authority key identifier of `issuer`.
+    pub fn x509v3_context<'a>(
+        &'a self,
+        issuer: &'a X509Ref,
+        conf: Option<&'a ConfRef>,
+    ) -> X509v3Context<'a> {
+        unsafe {
+            let mut ctx = mem::zeroed();
+
+            ffi::X509V3_set_ctx(
+                &mut ctx,
+                issuer.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                self.0.as_ptr(),
+                0,
+            );
+
+            // nodb case taken care of since we zeroed ctx above
+            if let Some(conf) = conf {
+                ffi::X509V3_set_nconf(&mut ctx, conf.as_ptr());
+            }
+
+            X509v3Context(ctx, PhantomData)
+        }
+    }
+
+    /// Adds an extension to the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_add_ext`].
+    ///
+    /// [`X509_CRL_add_ext`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_add_ext.html
+    pub fn append_extension(&mut self, extension: &X509ExtensionRef) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_CRL_add_ext(
+                self.0.as_ptr(),
+                extension.as_ptr(),
+                -1,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Signs the CRL with a private key.
+    ///
+    /// The revoked entries are sorted by serial number before signing.
+    ///
+    /// This corresponds to [`X509_CRL_sign`].
+    ///
+    /// [`X509_CRL_sign`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_sign.html
+    pub fn sign<T>(&mut self, key: &PKeyRef<T>, hash: MessageDigest) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::X509_CRL_sort(self.0.as_ptr()))?;
+            cvt(ffi::X509_CRL_sign(
+                self.0.as_ptr(),
+                key.as_ptr(),
+                hash.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Consumes the builder, returning the CRL.
+    pub fn build(self) -> X509Crl {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::X509_CRL;
+    fn drop = ffi::X509_CRL_free;
+
+    /// A certificate revocation list.
+    pub struct X509Crl;
+}
+
+impl Stackable for X509Crl {
+    type StackType = ffi::stack_st_X509_CRL;
+}
+
+impl X509Crl {
+    /// A builder for `X509Crl`.
+    pub fn builder() -> Result<X509CrlBuilder, ErrorStack> {
+        X509CrlBuilder::new()
+    }
+
+    from_pem! {
+        /// Deserializes a PEM-encoded certificate revocation list.
+        ///
+        /// The input should have a header of `-----BEGIN X509 CRL-----`.
+        ///
+        /// This corresponds to [`PEM_read_bio_X509_CRL`].
+        ///
+        /// [`PEM_read_bio_X509_CRL`]: https://www.openssl.org/docs/man1.0.2/crypto/PEM_read_bio_X509_CRL.html
+        from_pem,
+        X509Crl,
+        ffi::PEM_read_bio_X509_CRL
+    }
+
+    from_der! {
+        /// Deserializes a DER-encoded certificate revocation list.
+        ///
+        /// This corresponds to [`d2i_X509_CRL`].
+        ///
+        /// [`d2i_X509_CRL`]: https://www.openssl.org/docs/man1.1.0/crypto/d2i_X509_CRL.html
+        from_der,
+        X509Crl,
+        ffi::d2i_X509_CRL,
+        ::libc::c_long
+    }
+}
+
+impl X509CrlRef {
+    to_pem! {
+        /// Serializes the certificate revocation list to a PEM-encoded structure.
+        ///
+        /// The output will have a header of `-----BEGIN X509 CRL-----`.
+        ///
+        /// This corresponds to [`PEM_write_bio_X509_CRL`].
+        ///
+        /// [`PEM_write_bio_X509_CRL`]: https://www.openssl.org/docs/man1.0.2/crypto/PEM_write_bio_X509_CRL.html
+        to_pem,
+        ffi::PEM_write_bio_X509_CRL
+    }
+
+    to_der! {
+        /// Serializes the certificate revocation list to a DER-encoded structure.
+        ///
+        /// This corresponds to [`i2d_X509_CRL`].
+        ///
+        /// [`i2d_X509_CRL`]: https://www.openssl.org/docs/man1.0.2/crypto/i2d_X509_CRL.html
+        to_der,
+        ffi::i2d_X509_CRL
+    }
+
+    /// Returns the issuer name of the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_get_issuer`].
+    ///
+    /// [`X509_CRL_get_issuer`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_issuer.html
+    pub fn issuer_name(&self) -> &X509NameRef {
+        unsafe {
+            let name = ffi::X509_CRL_get_issuer(self.as_ptr());
+            assert!(!name.is_null());
+            X509NameRef::from_ptr(name)
+        }
+    }
+
+    /// Returns the time the CRL was issued.
+    ///
+    /// This corresponds to [`X509_CRL_get0_lastUpdate`].
+    ///
+    /// [`X509_CRL_get0_lastUpdate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get0_lastUpdate.html
+    pub fn last_update(&self) -> &Asn1TimeRef {
+        unsafe {
+            let date = ffi::X509_CRL_get0_lastUpdate(self.as_ptr());
+            assert!(!date.is_null());
+            Asn1TimeRef::from_ptr(date as *mut _)
+        }
+    }
+
+    /// Returns the time by which the next CRL will be issued, if specified.
+    ///
+    /// This corresponds to [`X509_CRL_get0_nextUpdate`].
+    ///
+    /// [`X509_CRL_get0_nextUpdate`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get0_nextUpdate.html
+    pub fn next_update(&self) -> Option<&Asn1TimeRef> {
+        unsafe {
+            let date = ffi::X509_CRL_get0_nextUpdate(self.as_ptr());
+            if date.is_null() {
+                None
+            } else {
+                Some(Asn1TimeRef::from_ptr(date as *mut _))
+            }
+        }
+    }
+
+    /// Returns the revoked certificate entries of the CRL.
+    ///
+    /// This corresponds to [`X509_CRL_get_REVOKED`].
+    ///
+    /// [`X509_CRL_get_REVOKED`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get_REVOKED.html
+    pub fn revoked(&self) -> Option<&StackRef<X509Revoked>> {
+        unsafe {
+            let revoked = ffi::X509_CRL_get_REVOKED(self.as_ptr());
+            if revoked.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr(revoked))
+            }
+        }
+    }
+
+    /// Returns the entry revoking the certificate with the given serial number, if any.
+    ///
+    /// This corresponds to [`X509_CRL_get0_by_serial`].
+    ///
+    /// [`X509_CRL_get0_by_serial`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_get0_by_serial.html
+    pub fn get_by_serial(&self, serial_number: &Asn1IntegerRef) -> Option<&X509RevokedRef> {
+        unsafe {
+            let mut revoked = ptr::null_mut();
+            match ffi::X509_CRL_get0_by_serial(self.as_ptr(), &mut revoked, serial_number.as_ptr())
+            {
+                0 => None,
+                _ => Some(X509RevokedRef::from_ptr(revoked)),
+            }
+        }
+    }
+
+    /// Check if the CRL is signed using the given public key.
+    ///
+    /// Returns `true` if verification succeeds.
+    ///
+    /// This corresponds to [`X509_CRL_verify`].
+    ///
+    /// [`X509_CRL_verify`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_CRL_verify.html
+    pub fn verify<T>(&self, key: &PKeyRef<T>) -> Result<bool, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        unsafe { cvt_n(ffi::X509_CRL_verify(self.as_ptr(), key.as_ptr())).map(|n| n != 0) }
+    }
+}
+
+/// The result of peer certificate verification.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct X509VerifyResult(c_int);
+
+impl fmt::Debug for X509VerifyResult {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("X509VerifyResult")
+            .field("code", &self.0)
+            .field("error", &self.error_string())
+            .finish()
+    }
+}
+
+impl fmt::Display for X509VerifyResult {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.error_string())
+    }
+}
+
+impl Error for X509VerifyResult {}
+
+impl X509VerifyResult {
+    /// Creates an `X509VerifyResult` from a raw error number.
+    ///
+    /// # Safety
+    ///
+    /// Some methods on `X509VerifyResult` are not thread safe if the error
+    /// number is invalid.
+    pub unsafe fn from_raw(err: c_int) -> X509VerifyResult {
+        X509VerifyResult(err)
+    }
+
+    /// Return the integer representation of an `X509VerifyResult`.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+
+    /// Return a human readable error string from the verification error.
+    ///
+    /// This corresponds to [`X509_verify_cert_error_string`].
+    ///
+    /// [`X509_verify_cert_error_string`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_verify_cert_error_string.html
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn error_string(&self) -> &'static str {
+        ffi::init();
+
+        unsafe {
+            let s = ffi::X509_verify_cert_error_string(self.0 as c_long);
+            str::from_utf8(CStr::from_ptr(s).to_bytes()).unwrap()
+        }
+    }
+
+    /// Successful peer certifiate verification.
     pub const OK: X509VerifyResult = X509VerifyResult(ffi::X509_V_OK);
     /// Application verification failure.
     pub const APPLICATION_VERIFICATION: X509VerifyResult =
         X509VerifyResult(ffi::X509_V_ERR_APPLICATION_VERIFICATION);
+    /// Unable to get the issuer certificate.
+    pub const UNABLE_TO_GET_ISSUER_CERT: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT);
+    /// Unable to get the CRL of a certificate.
+    pub const UNABLE_TO_GET_CRL: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_UNABLE_TO_GET_CRL);
+    /// The signature of a certificate is invalid.
+    pub const CERT_SIGNATURE_FAILURE: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_CERT_SIGNATURE_FAILURE);
+    /// The signature of a CRL is invalid.
+    pub const CRL_SIGNATURE_FAILURE: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_CRL_SIGNATURE_FAILURE);
+    /// A certificate is not yet valid.
+    pub const CERT_NOT_YET_VALID: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_CERT_NOT_YET_VALID);
+    /// A certificate has expired.
+    pub const CERT_HAS_EXPIRED: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_CERT_HAS_EXPIRED);
+    /// A CRL is not yet valid.
+    pub const CRL_NOT_YET_VALID: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_CRL_NOT_YET_VALID);
+    /// A CRL has expired.
+    pub const CRL_HAS_EXPIRED: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_CRL_HAS_EXPIRED);
+    /// The leaf certificate is self-signed and not trusted.
+    pub const DEPTH_ZERO_SELF_SIGNED_CERT: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_DEPTH_ZERO_SELF_SIGNED_CERT);
+    /// The chain ends in a self-signed certificate which is not trusted.
+    pub const SELF_SIGNED_CERT_IN_CHAIN: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_SELF_SIGNED_CERT_IN_CHAIN);
+    /// The issuer of a certificate could not be found.
+    pub const UNABLE_TO_GET_ISSUER_CERT_LOCALLY: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY);
+    /// The chain consists of a single certificate which is not trusted.
+    pub const UNABLE_TO_VERIFY_LEAF_SIGNATURE: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_UNABLE_TO_VERIFY_LEAF_SIGNATURE);
+    /// The chain is longer than the maximum depth.
+    pub const CERT_CHAIN_TOO_LONG: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_CERT_CHAIN_TOO_LONG);
+    /// A certificate has been revoked.
+    pub const CERT_REVOKED: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_CERT_REVOKED);
+    /// A CA certificate is invalid.
+    pub const INVALID_CA: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_INVALID_CA);
+    /// The path length constraint of a CA certificate was exceeded.
+    pub const PATH_LENGTH_EXCEEDED: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_PATH_LENGTH_EXCEEDED);
+    /// A certificate cannot be used for the requested purpose.
+    pub const INVALID_PURPOSE: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_INVALID_PURPOSE);
+    /// The root certificate is not trusted for the requested purpose.
+    pub const CERT_UNTRUSTED: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_CERT_UNTRUSTED);
+    /// The root certificate is rejected for the requested purpose.
+    pub const CERT_REJECTED: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_CERT_REJECTED);
+    /// The leaf certificate does not match the expected host name.
+    pub const HOSTNAME_MISMATCH: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_HOSTNAME_MISMATCH);
+    /// The leaf certificate does not match the expected email address.
+    pub const EMAIL_MISMATCH: X509VerifyResult = X509VerifyResult(ffi::X509_V_ERR_EMAIL_MISMATCH);
+    /// The leaf certificate does not match the expected IP address.
+    pub const IP_ADDRESS_MISMATCH: X509VerifyResult =
+        X509VerifyResult(ffi::X509_V_ERR_IP_ADDRESS_MISMATCH);
+}
+
+/// The purpose a certificate is verified for.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct X509PurposeId(c_int);
+
+impl X509PurposeId {
+    pub const SSL_CLIENT: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SSL_CLIENT as _);
+    pub const SSL_SERVER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SSL_SERVER as _);
+    pub const NS_SSL_SERVER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_NS_SSL_SERVER as _);
+    pub const SMIME_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SMIME_SIGN as _);
+    pub const SMIME_ENCRYPT: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_SMIME_ENCRYPT as _);
+    pub const CRL_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_CRL_SIGN as _);
+    pub const ANY: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_ANY as _);
+    pub const OCSP_HELPER: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_OCSP_HELPER as _);
+    pub const TIMESTAMP_SIGN: X509PurposeId = X509PurposeId(ffi::X509_PURPOSE_TIMESTAMP_SIGN as _);
+
+    /// Constructs an `X509PurposeId` from a raw OpenSSL value.
+    pub fn from_raw(id: c_int) -> Self {
+        X509PurposeId(id)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// A trust setting, used to interpret the auxiliary trust information of certificates.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct X509TrustId(c_int);
+
+impl X509TrustId {
+    pub const COMPAT: X509TrustId = X509TrustId(ffi::X509_TRUST_COMPAT as _);
+    pub const SSL_CLIENT: X509TrustId = X509TrustId(ffi::X509_TRUST_SSL_CLIENT as _);
+    pub const SSL_SERVER: X509TrustId = X509TrustId(ffi::X509_TRUST_SSL_SERVER as _);
+    pub const EMAIL: X509TrustId = X509TrustId(ffi::X509_TRUST_EMAIL as _);
+    pub const OBJECT_SIGN: X509TrustId = X509TrustId(ffi::X509_TRUST_OBJECT_SIGN as _);
+    pub const TSA: X509TrustId = X509TrustId(ffi::X509_TRUST_TSA as _);
+
+    /// Constructs an `X509TrustId` from a raw OpenSSL value.
+    pub fn from_raw(id: c_int) -> Self {
+        X509TrustId(id)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -1447,19 +2602,23 @@ impl GeneralName {
         Ok(gn)
     }
 
-    pub(crate) fn new_email(email: &[u8]) -> Result<GeneralName, ErrorStack> {
-        unsafe { GeneralName::new(ffi::GEN_EMAIL, Asn1Type::IA5STRING, email) }
+    /// Creates an `rfc822Name` holding an email address.
+    pub fn new_email(email: &str) -> Result<GeneralName, ErrorStack> {
+        unsafe { GeneralName::new(ffi::GEN_EMAIL, Asn1Type::IA5STRING, email.as_bytes()) }
     }
 
-    pub(crate) fn new_dns(dns: &[u8]) -> Result<GeneralName, ErrorStack> {
-        unsafe { GeneralName::new(ffi::GEN_DNS, Asn1Type::IA5STRING, dns) }
+    /// Creates a `dNSName`.
+    pub fn new_dns(dns: &str) -> Result<GeneralName, ErrorStack> {
+        unsafe { GeneralName::new(ffi::GEN_DNS, Asn1Type::IA5STRING, dns.as_bytes()) }
     }
 
-    pub(crate) fn new_uri(uri: &[u8]) -> Result<GeneralName, ErrorStack> {
-        unsafe { GeneralName::new(ffi::GEN_URI, Asn1Type::IA5STRING, uri) }
+    /// Creates a `uniformResourceIdentifier`, such as a SPIFFE ID.
+    pub fn new_uri(uri: &str) -> Result<GeneralName, ErrorStack> {
+        unsafe { GeneralName::new(ffi::GEN_URI, Asn1Type::IA5STRING, uri.as_bytes()) }
     }
 
-    pub(crate) fn new_ip(ip: IpAddr) -> Result<GeneralName, ErrorStack> {
+    /// Creates an `iPAddress`.
+    pub fn new_ip(ip: IpAddr) -> Result<GeneralName, ErrorStack> {
         match ip {
             IpAddr::V4(addr) => unsafe {
                 GeneralName::new(ffi::GEN_IPADD, Asn1Type::OCTET_STRING, &addr.octets())
@@ -1470,7 +2629,8 @@ impl GeneralName {
         }
     }
 
-    pub(crate) fn new_rid(oid: Asn1Object) -> Result<GeneralName, ErrorStack> {
+    /// Creates a `registeredID`.
+    pub fn new_rid(oid: Asn1Object) -> Result<GeneralName, ErrorStack> {
         unsafe {
             ffi::init();
             let gn = cvt_p(ffi::GENERAL_NAME_new())?;
@@ -1482,8 +2642,72 @@ impl GeneralName {
             Ok(GeneralName::from_ptr(gn))
         }
     }
+
+    /// Creates a `directoryName`.
+    ///
+    /// This corresponds to [`GENERAL_NAME_set0_value`] with `GEN_DIRNAME`.
+    ///
+    /// [`GENERAL_NAME_set0_value`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/x509.h.html#GENERAL_NAME_set0_value
+    pub fn new_dir_name(name: &X509NameRef) -> Result<GeneralName, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let gn = GeneralName::from_ptr(cvt_p(ffi::GENERAL_NAME_new())?);
+            let name = cvt_p(ffi::X509_NAME_dup(name.as_ptr()))?;
+            ffi::GENERAL_NAME_set0_value(gn.as_ptr(), ffi::GEN_DIRNAME, name.cast());
+
+            Ok(gn)
+        }
+    }
+
+    /// Creates an `otherName` of type `oid`, whose value is the DER encoding `value`.
+    ///
+    /// `value` is the inner value, such as a `UTF8String`, without the explicit tag wrapping it
+    /// in the `otherName`.
+    ///
+    /// This corresponds to [`GENERAL_NAME_set0_othername`].
+    ///
+    /// [`GENERAL_NAME_set0_othername`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/x509.h.html#GENERAL_NAME_set0_othername
+    pub fn new_other_name(oid: &Asn1ObjectRef, value: &[u8]) -> Result<GeneralName, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let gn = GeneralName::from_ptr(cvt_p(ffi::GENERAL_NAME_new())?);
+
+            let mut ptr = value.as_ptr();
+            let value = cvt_p(ffi::d2i_ASN1_TYPE(
+                ptr::null_mut(),
+                &mut ptr,
+                value.len().try_into().unwrap(),
+            ))?;
+            let oid = match cvt_p(ffi::OBJ_dup(oid.as_ptr())) {
+                Ok(oid) => oid,
+                Err(e) => {
+                    ffi::ASN1_TYPE_free(value);
+                    return Err(e);
+                }
+            };
+
+            if ffi::GENERAL_NAME_set0_othername(gn.as_ptr(), oid, value) == 0 {
+                ffi::ASN1_OBJECT_free(oid);
+                ffi::ASN1_TYPE_free(value);
+                return Err(ErrorStack::get());
+            }
+
+            Ok(gn)
+        }
+    }
+
+    /// Creates an `otherName` holding a Microsoft user principal name, as used for smart card
+    /// logon.
+    pub fn new_upn(upn: &str) -> Result<GeneralName, ErrorStack> {
+        let oid = Asn1Object::from_str(UPN_OID)?;
+
+        GeneralName::new_other_name(&oid, &extension::der(0x0c, upn.as_bytes()))
+    }
 }
 
+/// The object identifier of the Microsoft user principal name `otherName`.
+const UPN_OID: &str = "1.3.6.1.4.1.311.20.2.3";
+
 impl GeneralNameRef {
     fn ia5_string(&self, ffi_type: c_int) -> Option<&str> {
         unsafe {
@@ -1517,6 +2741,60 @@ impl GeneralNameRef {
         self.ia5_string(ffi::GEN_URI)
     }
 
+    /// Returns the contents of this `GeneralName` if it is a `directoryName`.
+    pub fn directory_name(&self) -> Option<&X509NameRef> {
+        unsafe {
+            if (*self.as_ptr()).type_ != ffi::GEN_DIRNAME {
+                return None;
+            }
+
+            Some(X509NameRef::from_ptr((*self.as_ptr()).d.directoryName))
+        }
+    }
+
+    /// Returns the type and the DER-encoded value of this `GeneralName` if it is an `otherName`.
+    ///
+    /// This corresponds to [`GENERAL_NAME_get0_otherName`].
+    ///
+    /// [`GENERAL_NAME_get0_otherName`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/x509.h.html#GENERAL_NAME_get0_otherName
+    pub fn other_name(&self) -> Option<(&Asn1ObjectRef, Vec<u8>)> {
+        unsafe {
+            let mut oid = ptr::null_mut();
+            let mut value = ptr::null_mut();
+            if ffi::GENERAL_NAME_get0_otherName(self.as_ptr(), &mut oid, &mut value) == 0 {
+                return None;
+            }
+
+            let len = ffi::i2d_ASN1_TYPE(value, ptr::null_mut());
+            if len <= 0 {
+                return None;
+            }
+            let mut der = vec![0; len as usize];
+            ffi::i2d_ASN1_TYPE(value, &mut der.as_mut_ptr());
+
+            Some((Asn1ObjectRef::from_ptr(oid), der))
+        }
+    }
+
+    /// Returns the user principal name of this `GeneralName` if it is such an `otherName`.
+    pub fn upn(&self) -> Option<String> {
+        unsafe {
+            let mut oid = ptr::null_mut();
+            let mut value = ptr::null_mut();
+            if ffi::GENERAL_NAME_get0_otherName(self.as_ptr(), &mut oid, &mut value) == 0 {
+                return None;
+            }
+
+            if Asn1ObjectRef::from_ptr(oid).to_oid_string() != UPN_OID
+                || (*value).type_ != ffi::V_ASN1_UTF8STRING
+            {
+                return None;
+            }
+
+            Asn1StringRef::from_ptr((*value).value.utf8string).decode()
+        }
+    }
+
     /// Returns the contents of this `GeneralName` if it is an `iPAddress`.
     pub fn ipaddress(&self) -> Option<&[u8]> {
         unsafe {
@@ -42,11 +42,15 @@
 
 use crate::ffi;
 use foreign_types::{ForeignType, ForeignTypeRef};
+use std::ffi::CString;
 use std::mem;
+use std::path::Path;
+use std::ptr;
 
 use crate::error::ErrorStack;
 use crate::stack::StackRef;
-use crate::x509::{X509Object, X509};
+use crate::x509::verify::{X509VerifyFlags, X509VerifyParamRef};
+use crate::x509::{X509CrlRef, X509Object, X509PurposeId, X509TrustId, X509};
 use crate::{cvt, cvt_p};
 
 foreign_type_and_impl_send_sync! {
@@ -92,6 +96,91 @@ impl X509StoreBuilderRef {
     pub fn set_default_paths(&mut self) -> Result<(), ErrorStack> {
         unsafe { cvt(ffi::X509_STORE_set_default_paths(self.as_ptr())).map(|_| ()) }
     }
+
+    /// Adds a certificate revocation list to the certificate store.
+    ///
+    /// CRLs are only checked if the [`X509VerifyFlags::CRL_CHECK`] or
+    /// [`X509VerifyFlags::CRL_CHECK_ALL`] flags are set.
+    ///
+    /// This corresponds to [`X509_STORE_add_crl`].
+    ///
+    /// [`X509_STORE_add_crl`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_add_crl.html
+    pub fn add_crl(&mut self, crl: &X509CrlRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_add_crl(self.as_ptr(), crl.as_ptr())).map(|_| ()) }
+    }
+
+    /// Loads trusted certificates and CRLs from a PEM file, and configures a directory in which
+    /// they are looked up by subject name hash, as produced by `openssl rehash`.
+    ///
+    /// Either argument may be `None`, but not both.
+    ///
+    /// This corresponds to [`X509_STORE_load_locations`].
+    ///
+    /// [`X509_STORE_load_locations`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_load_locations.html
+    pub fn load_locations(
+        &mut self,
+        file: Option<&Path>,
+        dir: Option<&Path>,
+    ) -> Result<(), ErrorStack> {
+        let file = file.map(|file| CString::new(file.as_os_str().to_str().unwrap()).unwrap());
+        let dir = dir.map(|dir| CString::new(dir.as_os_str().to_str().unwrap()).unwrap());
+
+        unsafe {
+            cvt(ffi::X509_STORE_load_locations(
+                self.as_ptr(),
+                file.as_ref().map_or(ptr::null(), |file| file.as_ptr()),
+                dir.as_ref().map_or(ptr::null(), |dir| dir.as_ptr()),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets verification flags.
+    ///
+    /// This corresponds to [`X509_STORE_set_flags`].
+    ///
+    /// [`X509_STORE_set_flags`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_set_flags.html
+    pub fn set_flags(&mut self, flags: X509VerifyFlags) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_set_flags(self.as_ptr(), flags.bits())).map(|_| ()) }
+    }
+
+    /// Sets the purpose certificates are verified for.
+    ///
+    /// This corresponds to [`X509_STORE_set_purpose`].
+    ///
+    /// [`X509_STORE_set_purpose`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_set_purpose.html
+    pub fn set_purpose(&mut self, purpose: X509PurposeId) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_set_purpose(self.as_ptr(), purpose.as_raw())).map(|_| ()) }
+    }
+
+    /// Sets the trust setting checked against the auxiliary trust information of trust anchors.
+    ///
+    /// This corresponds to [`X509_STORE_set_trust`].
+    ///
+    /// [`X509_STORE_set_trust`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_set_trust.html
+    pub fn set_trust(&mut self, trust: X509TrustId) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_set_trust(self.as_ptr(), trust.as_raw())).map(|_| ()) }
+    }
+
+    /// Sets the maximum depth of certificate chains.
+    ///
+    /// This corresponds to [`X509_STORE_set_depth`].
+    ///
+    /// [`X509_STORE_set_depth`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_set_depth.html
+    pub fn set_depth(&mut self, depth: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::X509_STORE_set_depth(self.as_ptr(), depth as _)).map(|_| ()) }
+    }
+
+    /// Returns a mutable reference to the verification parameters of the store.
+    ///
+    /// They are the defaults of every verification using the store.
+    ///
+    /// This corresponds to [`X509_STORE_get0_param`].
+    ///
+    /// [`X509_STORE_get0_param`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_STORE_get0_param.html
+    pub fn param_mut(&mut self) -> &mut X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::X509_STORE_get0_param(self.as_ptr())) }
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -1,18 +1,25 @@
 use hex::{self, FromHex};
+use std::path::Path;
 
-use crate::asn1::Asn1Time;
+use crate::asn1::{Asn1Object, Asn1Time};
 use crate::bn::{BigNum, MsbOption};
 use crate::hash::MessageDigest;
 use crate::nid::Nid;
 use crate::pkey::{PKey, Private};
 use crate::rsa::Rsa;
 use crate::stack::Stack;
+use crate::x509::chain::{ChainBuilder, ChainError};
 use crate::x509::extension::{
-    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
-    SubjectKeyIdentifier,
+    AuthorityInformationAccess, AuthorityKeyIdentifier, BasicConstraints, CertificatePolicies,
+    CrlDistributionPoints, ExtendedKeyUsage, KeyUsage, NameConstraints, PolicyConstraints,
+    PolicyMappings, SubjectAlternativeName, SubjectKeyIdentifier,
 };
 use crate::x509::store::X509StoreBuilder;
-use crate::x509::{X509Extension, X509Name, X509Req, X509StoreContext, X509VerifyResult, X509};
+use crate::x509::verify::{X509CheckFlags, X509VerifyFlags, X509VerifyParamRef};
+use crate::x509::{
+    GeneralName, X509Crl, X509CrlReason, X509Extension, X509Name, X509NameParseError,
+    X509PurposeId, X509Ref, X509Req, X509Revoked, X509StoreContext, X509VerifyResult, X509,
+};
 
 fn pkey() -> PKey<Private> {
     let rsa = Rsa::generate(2048).unwrap();
@@ -177,6 +184,44 @@ fn test_subject_alt_name_iter() {
     assert!(subject_alt_names_iter.next().is_none());
 }
 
+#[test]
+fn test_spiffe_id() {
+    fn cert_with_alt_name(alt_name: &SubjectAlternativeName) -> X509 {
+        let pkey = pkey();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_pubkey(&pkey).unwrap();
+        let alt_name = alt_name.build(&builder.x509v3_context(None, None)).unwrap();
+        builder.append_extension(alt_name).unwrap();
+        builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    let cert =
+        cert_with_alt_name(SubjectAlternativeName::new().uri("spiffe://example.org/service"));
+    assert_eq!(
+        cert.spiffe_id().as_deref(),
+        Some("spiffe://example.org/service")
+    );
+
+    let cert = cert_with_alt_name(SubjectAlternativeName::new().dns("example.org"));
+    assert_eq!(cert.spiffe_id(), None);
+
+    let cert = cert_with_alt_name(SubjectAlternativeName::new().uri("https://example.org"));
+    assert_eq!(cert.spiffe_id(), None);
+
+    let cert = cert_with_alt_name(
+        SubjectAlternativeName::new()
+            .uri("spiffe://example.org/a")
+            .uri("spiffe://example.org/b"),
+    );
+    assert_eq!(cert.spiffe_id(), None);
+
+    let cert = X509::from_pem(include_bytes!("../../test/alt_name_cert.pem")).unwrap();
+    assert_eq!(cert.spiffe_id(), None);
+}
+
 #[test]
 fn x509_builder() {
     let pkey = pkey();
@@ -291,11 +336,207 @@ fn x509_extension_to_der() {
                 .unwrap(),
             b"0\x22\x06\x03U\x1d%\x04\x1b0\x19\x06\x08+\x06\x01\x05\x05\x07\x03\x01\x06\x03\x887\x01\x06\x08+\x06\x01\x05\x05\x07\x03\x02",
         ),
+        (
+            NameConstraints::new()
+                .critical()
+                .permitted_dns("example.com")
+                .excluded_ip("10.0.0.0".parse().unwrap(), 8)
+                .build()
+                .unwrap(),
+            b"0+\x06\x03U\x1d\x1e\x01\x01\xff\x04!0\x1f\xa0\x0f0\x0d\x82\x0bexample.com\xa1\x0c0\x0a\x87\x08\x0a\x00\x00\x00\xff\x00\x00\x00",
+        ),
+        (
+            PolicyConstraints::new()
+                .critical()
+                .require_explicit_policy(0)
+                .inhibit_policy_mapping(1)
+                .build()
+                .unwrap(),
+            b"0\x12\x06\x03U\x1d$\x01\x01\xff\x04\x080\x06\x80\x01\x00\x81\x01\x01",
+        ),
+        (
+            PolicyMappings::new()
+                .critical()
+                .mapping("1.2.3", "1.2.4")
+                .build()
+                .unwrap(),
+            b"0\x16\x06\x03U\x1d!\x01\x01\xff\x04\x0c0\x0a0\x08\x06\x02*\x03\x06\x02*\x04",
+        ),
+        (
+            CrlDistributionPoints::new()
+                .uri("http://crl.example.com/ca.crl")
+                .build()
+                .unwrap(),
+            b"0.\x06\x03U\x1d\x1f\x04'0%0#\xa0!\xa0\x1f\x86\x1dhttp://crl.example.com/ca.crl",
+        ),
+        (
+            AuthorityInformationAccess::new()
+                .ocsp("http://ocsp.example.com")
+                .ca_issuers("http://example.com/ca.crt")
+                .build()
+                .unwrap(),
+            b"0Z\x06\x08+\x06\x01\x05\x05\x07\x01\x01\x04N0L0#\x06\x08+\x06\x01\x05\x05\x070\x01\x86\x17http://ocsp.example.com0%\x06\x08+\x06\x01\x05\x05\x070\x02\x86\x19http://example.com/ca.crt",
+        ),
+        (
+            CertificatePolicies::new()
+                .policy_with_cps("2.23.140.1.2.1", "https://example.com/cps")
+                .policy("1.2.3.4")
+                .build()
+                .unwrap(),
+            b"0A\x06\x03U\x1d \x04:080/\x06\x06g\x81\x0c\x01\x02\x010%0#\x06\x08+\x06\x01\x05\x05\x07\x02\x01\x16\x17https://example.com/cps0\x05\x06\x03*\x03\x04",
+        ),
+        (
+            X509Extension::new_from_der(
+                &Asn1Object::from_str("1.3.6.1.4.1.11129.2.4.3").unwrap(),
+                true,
+                b"\x05\x00",
+            )
+            .unwrap(),
+            b"0\x13\x06\x0a+\x06\x01\x04\x01\xd6y\x02\x04\x03\x01\x01\xff\x04\x02\x05\x00",
+        ),
     ] {
         assert_eq!(&ext.to_der().unwrap(), expected);
     }
 }
 
+#[test]
+fn extension_builders_reject_invalid_values() {
+    let err = CertificatePolicies::new()
+        .policy_with_cps("2.23.140.1.2.1", "https://exämple.com/cps")
+        .build()
+        .unwrap_err();
+    assert!(!err.errors().is_empty());
+
+    let err = NameConstraints::new().critical().build().unwrap_err();
+    assert!(!err.errors().is_empty());
+}
+
+#[test]
+fn private_extension() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+
+    let oid = Asn1Object::from_str("1.3.6.1.4.1.55555.2").unwrap();
+    let key_usage = KeyUsage::new().digital_signature().build().unwrap();
+    builder.append_extension(key_usage).unwrap();
+    builder
+        .append_extension(X509Extension::new_from_der(&oid, true, b"\x05\x00").unwrap())
+        .unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let x509 = builder.build();
+
+    let extensions: Vec<_> = x509
+        .extensions()
+        .map(|ext| {
+            (
+                ext.object().to_oid_string(),
+                ext.critical(),
+                ext.is_supported(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        extensions,
+        [
+            ("2.5.29.15".to_string(), false, true),
+            ("1.3.6.1.4.1.55555.2".to_string(), true, false),
+        ]
+    );
+
+    let ext = x509.extension(&oid).unwrap();
+    assert_eq!(ext.data().as_slice(), b"\x05\x00");
+    assert!(x509
+        .extension(&Asn1Object::from_str("2.5.29.17").unwrap())
+        .is_none());
+
+    let nid = Nid::create("1.3.6.1.4.1.55555.2", "privateExt", "Private Extension").unwrap();
+    assert_eq!(ext.object().nid(), nid);
+    assert_eq!(ext.object().to_string(), "Private Extension");
+    assert_eq!(ext.object().to_oid_string(), "1.3.6.1.4.1.55555.2");
+    assert!(!ext.is_supported());
+
+    let text = String::from_utf8(x509.to_text().unwrap()).unwrap();
+    assert!(text.contains("Private Extension"));
+}
+
+#[test]
+fn subject_alt_name_general_names() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+
+    let subject_alt_name = SubjectAlternativeName::new()
+        .dns("example.com")
+        .general_name(GeneralName::new_ip("127.0.0.1".parse().unwrap()).unwrap())
+        .general_name(GeneralName::new_uri("spiffe://example.org/workload").unwrap())
+        .general_name(GeneralName::new_email("test@example.com").unwrap())
+        .general_name(GeneralName::new_dir_name(&name).unwrap())
+        .general_name(GeneralName::new_upn("user@example.com").unwrap())
+        .other_name2(
+            Asn1Object::from_str("1.3.6.1.4.1.55555.3").unwrap(),
+            b"\x04\x03abc",
+        )
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(subject_alt_name).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+
+    let x509 = X509::from_der(&builder.build().to_der().unwrap()).unwrap();
+    let names = x509.subject_alt_names().unwrap();
+    assert_eq!(names.len(), 7);
+
+    assert_eq!(names[0].dnsname(), Some("example.com"));
+    assert_eq!(names[1].ipaddress(), Some(&[127, 0, 0, 1][..]));
+    assert_eq!(names[2].uri(), Some("spiffe://example.org/workload"));
+    assert_eq!(names[3].email(), Some("test@example.com"));
+
+    let dir_name = names[4].directory_name().unwrap();
+    assert_eq!(dir_name.to_der().unwrap(), name.to_der().unwrap());
+    assert!(names[4].other_name().is_none());
+
+    assert_eq!(names[5].upn().as_deref(), Some("user@example.com"));
+    let (oid, value) = names[5].other_name().unwrap();
+    assert_eq!(oid.to_oid_string(), "1.3.6.1.4.1.311.20.2.3");
+    assert_eq!(value, b"\x0c\x10user@example.com");
+
+    assert!(names[6].upn().is_none());
+    let (oid, value) = names[6].other_name().unwrap();
+    assert_eq!(oid.to_oid_string(), "1.3.6.1.4.1.55555.3");
+    assert_eq!(value, b"\x04\x03abc");
+    assert!(names[6].directory_name().is_none());
+}
+
 #[test]
 fn eku_invalid_other() {
     assert!(ExtendedKeyUsage::new()
@@ -340,6 +581,82 @@ fn x509_req_builder() {
     assert!(req.verify(&pkey).unwrap());
 }
 
+#[test]
+fn x509_req_attributes() {
+    let pkey = pkey();
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder
+        .set_subject_name(&X509Name::from_rfc4514("CN=foobar.com").unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+
+    let mut extensions = Stack::new().unwrap();
+    extensions
+        .push(
+            KeyUsage::new()
+                .critical()
+                .digital_signature()
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+    extensions
+        .push(
+            SubjectAlternativeName::new()
+                .dns("example.com")
+                .ip("127.0.0.1")
+                .build(&builder.x509v3_context(None))
+                .unwrap(),
+        )
+        .unwrap();
+    builder.add_extensions(&extensions).unwrap();
+    builder.set_challenge_password("s3cret").unwrap();
+    builder
+        .add_attribute_by_nid(Nid::PKCS9_UNSTRUCTUREDNAME, "unstructured")
+        .unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+
+    let req = builder.build();
+    let req = X509Req::from_der(&req.to_der().unwrap()).unwrap();
+    assert!(req.verify(&pkey).unwrap());
+
+    assert_eq!(req.challenge_password().as_deref(), Some("s3cret"));
+    let unstructured = req.attribute_by_nid(Nid::PKCS9_UNSTRUCTUREDNAME).unwrap();
+    assert_eq!(unstructured.count(), 1);
+    assert_eq!(
+        unstructured.string_value(0).unwrap().as_slice(),
+        b"unstructured"
+    );
+    assert!(req.attribute_by_nid(Nid::PKCS9_EMAILADDRESS).is_none());
+
+    let attributes = req
+        .attributes()
+        .map(|attr| attr.object().nid())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        attributes,
+        [
+            Nid::EXT_REQ,
+            Nid::PKCS9_CHALLENGEPASSWORD,
+            Nid::PKCS9_UNSTRUCTUREDNAME
+        ]
+    );
+
+    let extensions = req.extensions().unwrap();
+    assert_eq!(extensions.len(), 2);
+    assert_eq!(extensions[0].object().nid(), Nid::KEY_USAGE);
+    assert!(extensions[0].critical());
+    assert_eq!(extensions[1].object().nid(), Nid::SUBJECT_ALT_NAME);
+    assert!(!extensions[1].critical());
+
+    let subject_alt_names = req.subject_alt_names().unwrap();
+    assert_eq!(subject_alt_names.len(), 2);
+    assert_eq!(subject_alt_names[0].dnsname(), Some("example.com"));
+    assert_eq!(subject_alt_names[1].ipaddress(), Some(&[127, 0, 0, 1][..]));
+}
+
 #[test]
 fn test_stack_from_pem() {
     let certs = include_bytes!("../../test/certs.pem");
@@ -434,6 +751,288 @@ fn test_verify_fails() {
         .unwrap());
 }
 
+// Aug 1st, 2020, when the test certificates are valid.
+const CHAIN_TEST_TIME: libc::time_t = 1596240000;
+
+#[test]
+fn test_chain_builder() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca.clone()).unwrap();
+    let store = store_bldr.build();
+
+    let mut builder = ChainBuilder::new(&store).unwrap();
+    builder.set_time(CHAIN_TEST_TIME);
+    let chain = builder.build(&cert).unwrap();
+
+    assert_eq!(chain.len(), 2);
+    assert_eq!(chain[0].to_der().unwrap(), cert.to_der().unwrap());
+    assert_eq!(chain[1].to_der().unwrap(), ca.to_der().unwrap());
+}
+
+#[test]
+fn test_chain_builder_untrusted() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let other = X509::from_pem(include_bytes!("../../test/alt_name_cert.pem")).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(other).unwrap();
+    let store = store_bldr.build();
+
+    let mut builder = ChainBuilder::new(&store).unwrap();
+    builder.set_time(CHAIN_TEST_TIME);
+
+    let failure = match builder.build(&cert) {
+        Err(ChainError::Verify(failure)) => failure,
+        r => panic!("unexpected result: {:?}", r),
+    };
+    assert_eq!(
+        failure.result(),
+        X509VerifyResult::UNABLE_TO_GET_ISSUER_CERT_LOCALLY
+    );
+    assert_eq!(failure.depth(), 0);
+    assert_eq!(
+        failure.certificate().unwrap().to_der().unwrap(),
+        cert.to_der().unwrap()
+    );
+    assert_eq!(failure.chain().len(), 1);
+}
+
+#[test]
+fn test_chain_builder_partial_chain() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(cert.clone()).unwrap();
+    let store = store_bldr.build();
+
+    let mut builder = ChainBuilder::new(&store).unwrap();
+    builder.set_time(CHAIN_TEST_TIME);
+    assert!(builder.build(&cert).is_err());
+
+    builder.set_partial_chain(true);
+    let chain = builder.build(&cert).unwrap();
+    assert_eq!(chain.len(), 1);
+}
+
+#[test]
+fn test_chain_builder_time() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(ca).unwrap();
+    let store = store_bldr.build();
+
+    let mut builder = ChainBuilder::new(&store).unwrap();
+    // Jan 1st, 2030
+    builder.set_time(1893456000);
+
+    let failure = match builder.build(&cert) {
+        Err(ChainError::Verify(failure)) => failure,
+        r => panic!("unexpected result: {:?}", r),
+    };
+    assert_eq!(failure.result(), X509VerifyResult::CERT_HAS_EXPIRED);
+
+    builder.set_flags(X509VerifyFlags::NO_CHECK_TIME);
+    assert_eq!(builder.build(&cert).unwrap().len(), 2);
+}
+
+fn revocation_list(revoked: Option<&X509Ref>) -> X509Crl {
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let key = PKey::private_key_from_pem(include_bytes!("../../test/root-ca.key")).unwrap();
+
+    // Jul 1st, 2020
+    let last_update = Asn1Time::from_unix(1593561600).unwrap();
+    let next_update = Asn1Time::from_unix(1593561600 + 365 * 24 * 60 * 60).unwrap();
+
+    let mut builder = X509Crl::builder().unwrap();
+    builder.set_issuer_name(ca.subject_name()).unwrap();
+    builder.set_last_update(&last_update).unwrap();
+    builder.set_next_update(&next_update).unwrap();
+    if let Some(cert) = revoked {
+        let revoked = X509Revoked::new(cert.serial_number(), &last_update).unwrap();
+        builder.add_revoked(revoked).unwrap();
+    }
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    builder.build()
+}
+
+#[test]
+fn test_store_crl_check() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    for (crl, expected) in [
+        (None, Some(X509VerifyResult::UNABLE_TO_GET_CRL)),
+        (Some(revocation_list(None)), None),
+        (
+            Some(revocation_list(Some(&cert))),
+            Some(X509VerifyResult::CERT_REVOKED),
+        ),
+    ] {
+        let mut store_bldr = X509StoreBuilder::new().unwrap();
+        store_bldr.add_cert(ca.clone()).unwrap();
+        if let Some(crl) = &crl {
+            store_bldr.add_crl(crl).unwrap();
+        }
+        store_bldr.set_flags(X509VerifyFlags::CRL_CHECK).unwrap();
+        store_bldr.set_purpose(X509PurposeId::SSL_SERVER).unwrap();
+        let store = store_bldr.build();
+
+        let mut builder = ChainBuilder::new(&store).unwrap();
+        builder.set_time(CHAIN_TEST_TIME);
+
+        match (builder.build(&cert), expected) {
+            (Ok(_), None) => {}
+            (Err(ChainError::Verify(failure)), Some(expected)) => {
+                assert_eq!(failure.result(), expected);
+                assert_eq!(failure.depth(), 0);
+            }
+            (r, _) => panic!("unexpected result: {:?}", r),
+        }
+    }
+}
+
+#[test]
+fn test_store_load_locations() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr
+        .load_locations(Some(Path::new("test/root-ca.pem")), None)
+        .unwrap();
+    let store = store_bldr.build();
+
+    let mut builder = ChainBuilder::new(&store).unwrap();
+    builder.set_time(CHAIN_TEST_TIME);
+    assert_eq!(builder.build(&cert).unwrap().len(), 2);
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    assert!(store_bldr
+        .load_locations(Some(Path::new("test/nonexistent.pem")), None)
+        .is_err());
+}
+
+#[test]
+fn test_store_verify_param() {
+    let cert = X509::from_pem(include_bytes!("../../test/alt_name_cert.pem")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    let verify = |configure: &dyn Fn(&mut X509VerifyParamRef)| {
+        let mut store_bldr = X509StoreBuilder::new().unwrap();
+        store_bldr.add_cert(ca.clone()).unwrap();
+        configure(store_bldr.param_mut());
+        let store = store_bldr.build();
+
+        let mut builder = ChainBuilder::new(&store).unwrap();
+        builder.set_time(CHAIN_TEST_TIME);
+        match builder.build(&cert) {
+            Ok(_) => X509VerifyResult::OK,
+            Err(ChainError::Verify(failure)) => failure.result(),
+            Err(e) => panic!("unexpected error: {}", e),
+        }
+    };
+
+    assert_eq!(
+        verify(&|param| param.set_email("test@example.com").unwrap()),
+        X509VerifyResult::OK
+    );
+    assert_eq!(
+        verify(&|param| param.set_email("other@example.com").unwrap()),
+        X509VerifyResult::EMAIL_MISMATCH
+    );
+    assert_eq!(
+        verify(&|param| param.set_ip_asc("::1").unwrap()),
+        X509VerifyResult::OK
+    );
+    assert_eq!(
+        verify(&|param| param.set_ip_asc("10.0.0.1").unwrap()),
+        X509VerifyResult::IP_ADDRESS_MISMATCH
+    );
+    assert_eq!(
+        verify(&|param| {
+            param.set_hostflags(X509CheckFlags::NEVER_CHECK_SUBJECT);
+            param.set_host("example.com").unwrap();
+        }),
+        X509VerifyResult::OK
+    );
+    assert_eq!(verify(&|param| param.set_depth(1)), X509VerifyResult::OK);
+    assert_ne!(verify(&|param| param.set_depth(0)), X509VerifyResult::OK);
+    assert_eq!(
+        verify(&|param| param.set_purpose(X509PurposeId::SSL_SERVER).unwrap()),
+        X509VerifyResult::OK
+    );
+    assert_eq!(
+        verify(&|param| param.set_purpose(X509PurposeId::CRL_SIGN).unwrap()),
+        X509VerifyResult::INVALID_PURPOSE
+    );
+}
+
+#[test]
+fn test_name_rfc4514() {
+    let name =
+        X509Name::from_rfc4514(r"CN=Example\, Inc.+UID=42,O=J\C3\BCrgen \+ Co,C=US").unwrap();
+
+    let entries = name.to_utf8_entries().unwrap();
+    assert_eq!(
+        entries[..2],
+        [
+            (Nid::COUNTRYNAME, "US".to_string()),
+            (Nid::ORGANIZATIONNAME, "Jürgen + Co".to_string()),
+        ]
+    );
+    // The entries of a multi-valued RDN are unordered.
+    assert_eq!(entries.len(), 4);
+    assert!(entries.contains(&(Nid::COMMONNAME, "Example, Inc.".to_string())));
+    assert!(entries.contains(&(Nid::USERID, "42".to_string())));
+
+    let dn = name.to_rfc4514_string().unwrap();
+    assert!(dn.contains(r"CN=Example\, Inc."));
+    assert!(dn.ends_with(r",O=Jürgen \+ Co,C=US"));
+    assert_eq!(
+        X509Name::from_rfc4514(&dn).unwrap().to_der().unwrap(),
+        name.to_der().unwrap()
+    );
+
+    let name = X509Name::from_rfc4514("CN=example.com, 2.5.4.10=Example").unwrap();
+    assert_eq!(
+        name.to_rfc4514_string().unwrap(),
+        "O=Example,CN=example.com"
+    );
+
+    assert_eq!(X509Name::from_rfc4514("").unwrap().entries().count(), 0);
+
+    for dn in ["CN", "CN=a,", "=a", r"CN=a\", r"CN=\zz", "CN=#0403616263"] {
+        assert!(matches!(
+            X509Name::from_rfc4514(dn),
+            Err(X509NameParseError::Syntax)
+        ));
+    }
+    assert!(matches!(
+        X509Name::from_rfc4514("bogus=a"),
+        Err(X509NameParseError::Stack(_))
+    ));
+}
+
+#[test]
+fn test_name_bmp_string() {
+    // CN=Jü, encoded as a BMPString.
+    const NAME: &[u8] = &[
+        0x30, 0x0f, 0x31, 0x0d, 0x30, 0x0b, 0x06, 0x03, 0x55, 0x04, 0x03, 0x1e, 0x04, 0x00, 0x4a,
+        0x00, 0xfc,
+    ];
+
+    let name = X509Name::from_der(NAME).unwrap();
+    assert_eq!(
+        name.to_utf8_entries().unwrap(),
+        vec![(Nid::COMMONNAME, "Jü".to_string())]
+    );
+    assert_eq!(name.to_rfc4514_string().unwrap(), "CN=Jü");
+}
+
 #[test]
 fn test_save_subject_der() {
     let cert = include_bytes!("../../test/cert.pem");
@@ -456,3 +1055,153 @@ fn test_load_subject_der() {
     ];
     X509Name::from_der(SUBJECT_DER).unwrap();
 }
+
+#[test]
+fn x509_crl_builder() {
+    let ca = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let key = PKey::private_key_from_pem(include_bytes!("../../test/root-ca.key")).unwrap();
+
+    let serial = BigNum::from_u32(42).unwrap().to_asn1_integer().unwrap();
+    let now = Asn1Time::days_from_now(0).unwrap();
+    let mut revoked = X509Revoked::new(&serial, &now).unwrap();
+    revoked.set_reason(X509CrlReason::KEY_COMPROMISE).unwrap();
+    revoked.set_invalidity_date(&now).unwrap();
+
+    let mut builder = X509Crl::builder().unwrap();
+    builder.set_issuer_name(ca.subject_name()).unwrap();
+    builder.set_last_update(&now).unwrap();
+    builder
+        .set_next_update(&Asn1Time::days_from_now(7).unwrap())
+        .unwrap();
+    builder.add_revoked(revoked).unwrap();
+    builder
+        .set_crl_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap())
+        .unwrap();
+    let authority_key_identifier = AuthorityKeyIdentifier::new()
+        .keyid(true)
+        .build(&builder.x509v3_context(&ca, None))
+        .unwrap();
+    builder.append_extension(&authority_key_identifier).unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    let crl = builder.build();
+
+    let crl = X509Crl::from_der(&crl.to_der().unwrap()).unwrap();
+    let crl = X509Crl::from_pem(&crl.to_pem().unwrap()).unwrap();
+    assert!(crl.verify(&ca.public_key().unwrap()).unwrap());
+    assert_eq!(
+        crl.issuer_name().to_der().unwrap(),
+        ca.subject_name().to_der().unwrap()
+    );
+    assert!(crl.next_update().is_some());
+    assert_eq!(crl.revoked().unwrap().len(), 1);
+
+    let entry = crl.get_by_serial(&serial).unwrap();
+    assert_eq!(
+        entry.serial_number().to_bn().unwrap(),
+        BigNum::from_u32(42).unwrap()
+    );
+    assert_eq!(entry.reason(), Some(X509CrlReason::KEY_COMPROMISE));
+
+    let other = BigNum::from_u32(43).unwrap().to_asn1_integer().unwrap();
+    assert!(crl.get_by_serial(&other).is_none());
+}
+
+#[test]
+fn test_to_text() {
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let text = String::from_utf8(cert.to_text().unwrap()).unwrap();
+
+    assert!(text.contains("Serial Number:"));
+    assert!(text.contains("87:71:f7:bd:ee:98:2f:a5"));
+    assert!(text.contains("Not Before: Aug 14 17:00:03 2016 GMT"));
+    assert!(text.contains("CN=foobar.com"));
+}
+
+#[test]
+fn test_info() {
+    let cert = include_bytes!("../../test/alt_name_cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    let info = cert.info().unwrap();
+
+    assert_eq!(info.version, 3);
+    assert_eq!(
+        info.serial_number,
+        cert.serial_number()
+            .to_bn()
+            .unwrap()
+            .to_hex_str()
+            .unwrap()
+            .to_string()
+    );
+    assert_eq!(info.signature_algorithm, "sha256WithRSAEncryption");
+    assert_eq!(info.issuer, cert.issuer_name().to_rfc4514_string().unwrap());
+    assert_eq!(
+        info.subject_alt_names,
+        [
+            "DNS:example.com",
+            "IP:127.0.0.1",
+            "IP:::1",
+            "email:test@example.com",
+            "URI:http://www.example.com",
+        ]
+    );
+    assert_eq!(info.not_before, cert.not_before().to_string());
+    assert_eq!(info.public_key.algorithm, "rsaEncryption");
+    assert_eq!(info.public_key.bits, 2048);
+    assert_eq!(info.public_key.curve, None);
+    assert_eq!(
+        info.sha256_fingerprint,
+        hex::encode(cert.digest(MessageDigest::sha256()).unwrap())
+    );
+
+    let key_usage = info
+        .extensions
+        .iter()
+        .find(|ext| ext.oid == "2.5.29.15")
+        .unwrap();
+    assert_eq!(key_usage.name.as_deref(), Some("X509v3 Key Usage"));
+    assert_eq!(key_usage.value, "Digital Signature, Key Encipherment");
+}
+
+#[test]
+fn validate_identity() {
+    use crate::x509::identity::{validate_identity_at, IdentityFinding};
+
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let key = PKey::private_key_from_pem(include_bytes!("../../test/key.pem")).unwrap();
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    // Aug 1st, 2020
+    let findings = validate_identity_at(&cert, &key, &[root.clone()], 1596240000).unwrap();
+    assert_eq!(findings, vec![]);
+
+    let findings = validate_identity_at(&cert, &pkey(), &[root.clone()], 1596240000).unwrap();
+    assert_eq!(findings, vec![IdentityFinding::KeyMismatch]);
+
+    let findings = validate_identity_at(&cert, &key, &[cert.clone()], 1596240000).unwrap();
+    assert_eq!(
+        findings,
+        vec![IdentityFinding::ChainOutOfOrder { depth: 1 }]
+    );
+
+    // Aug 1st, 2026
+    let findings = validate_identity_at(&cert, &key, &[root.clone()], 1785542400).unwrap();
+    assert_eq!(
+        findings,
+        vec![
+            IdentityFinding::ExpiresSoon { depth: 0, days: 11 },
+            IdentityFinding::ExpiresSoon { depth: 1, days: 11 },
+        ]
+    );
+
+    // Jan 1st, 2030
+    let findings = validate_identity_at(&cert, &key, &[root], 1893456000).unwrap();
+    assert_eq!(
+        findings,
+        vec![
+            IdentityFinding::Expired { depth: 0 },
+            IdentityFinding::Expired { depth: 1 },
+        ]
+    );
+}
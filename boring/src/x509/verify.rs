@@ -1,10 +1,15 @@
 use crate::ffi;
-use foreign_types::ForeignTypeRef;
-use libc::c_uint;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_uint, c_ulong, time_t};
+use std::ffi::CString;
+use std::mem;
 use std::net::IpAddr;
 
+use crate::asn1::Asn1Object;
 use crate::cvt;
 use crate::error::ErrorStack;
+use crate::stack::StackRef;
+use crate::x509::{X509PurposeId, X509TrustId};
 
 bitflags! {
     /// Flags used to check an `X509` certificate.
@@ -22,6 +27,24 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Flags used to verify an `X509` certificate chain.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+    pub struct X509VerifyFlags: c_ulong {
+        const USE_CHECK_TIME = ffi::X509_V_FLAG_USE_CHECK_TIME as _;
+        const CRL_CHECK = ffi::X509_V_FLAG_CRL_CHECK as _;
+        const CRL_CHECK_ALL = ffi::X509_V_FLAG_CRL_CHECK_ALL as _;
+        const IGNORE_CRITICAL = ffi::X509_V_FLAG_IGNORE_CRITICAL as _;
+        const X509_STRICT = ffi::X509_V_FLAG_X509_STRICT as _;
+        const EXPLICIT_POLICY = ffi::X509_V_FLAG_EXPLICIT_POLICY as _;
+        const INHIBIT_ANY = ffi::X509_V_FLAG_INHIBIT_ANY as _;
+        const INHIBIT_MAP = ffi::X509_V_FLAG_INHIBIT_MAP as _;
+        const TRUSTED_FIRST = ffi::X509_V_FLAG_TRUSTED_FIRST as _;
+        const PARTIAL_CHAIN = ffi::X509_V_FLAG_PARTIAL_CHAIN as _;
+        const NO_CHECK_TIME = ffi::X509_V_FLAG_NO_CHECK_TIME as _;
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_VERIFY_PARAM;
     fn drop = ffi::X509_VERIFY_PARAM_free;
@@ -31,6 +54,58 @@ foreign_type_and_impl_send_sync! {
 }
 
 impl X509VerifyParamRef {
+    /// Set verification flags.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_flags`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_flags`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set_flags.html
+    pub fn set_flags(&mut self, flags: X509VerifyFlags) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_flags(
+                self.as_ptr(),
+                flags.bits(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Clear verification flags.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_clear_flags`].
+    ///
+    /// [`X509_VERIFY_PARAM_clear_flags`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_clear_flags.html
+    pub fn clear_flags(&mut self, flags: X509VerifyFlags) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_clear_flags(
+                self.as_ptr(),
+                flags.bits(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Gets verification flags.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_get_flags`].
+    ///
+    /// [`X509_VERIFY_PARAM_get_flags`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_get_flags.html
+    pub fn flags(&self) -> X509VerifyFlags {
+        let bits = unsafe { ffi::X509_VERIFY_PARAM_get_flags(self.as_ptr()) };
+        X509VerifyFlags::from_bits_retain(bits)
+    }
+
+    /// Sets the time, in seconds since the UNIX epoch, at which certificates are checked for
+    /// validity, instead of the current time.
+    ///
+    /// This also sets the [`X509VerifyFlags::USE_CHECK_TIME`] flag.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_time`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_time`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set_time.html
+    pub fn set_time(&mut self, time: time_t) {
+        unsafe { ffi::X509_VERIFY_PARAM_set_time(self.as_ptr(), time) }
+    }
+
     /// Set the host flags.
     ///
     /// This corresponds to [`X509_VERIFY_PARAM_set_hostflags`].
@@ -84,4 +159,113 @@ impl X509VerifyParamRef {
             .map(|_| ())
         }
     }
+
+    /// Set the expected IPv4 or IPv6 address, in its textual form.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set1_ip_asc`].
+    ///
+    /// [`X509_VERIFY_PARAM_set1_ip_asc`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set1_ip_asc.html
+    pub fn set_ip_asc(&mut self, ip: &str) -> Result<(), ErrorStack> {
+        let ip = CString::new(ip).unwrap();
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1_ip_asc(
+                self.as_ptr(),
+                ip.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Set the expected email address.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set1_email`].
+    ///
+    /// [`X509_VERIFY_PARAM_set1_email`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set1_email.html
+    pub fn set_email(&mut self, email: &str) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1_email(
+                self.as_ptr(),
+                email.as_ptr() as *const _,
+                email.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Set the maximum depth of the certificate chain.
+    ///
+    /// A depth of zero only allows self-issued leaf certificates, one allows a leaf and its
+    /// issuer, and so on.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_depth`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_depth`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set_depth.html
+    pub fn set_depth(&mut self, depth: u32) {
+        unsafe { ffi::X509_VERIFY_PARAM_set_depth(self.as_ptr(), depth as c_int) }
+    }
+
+    /// Set the purpose certificates are verified for.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_purpose`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_purpose`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set_purpose.html
+    pub fn set_purpose(&mut self, purpose: X509PurposeId) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_purpose(
+                self.as_ptr(),
+                purpose.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Set the trust setting checked against the auxiliary trust information of trust anchors.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set_trust`].
+    ///
+    /// [`X509_VERIFY_PARAM_set_trust`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set_trust.html
+    pub fn set_trust(&mut self, trust: X509TrustId) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set_trust(
+                self.as_ptr(),
+                trust.as_raw(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Add an acceptable certificate policy.
+    ///
+    /// Policies are only checked if the [`X509VerifyFlags::EXPLICIT_POLICY`] flag is set.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_add0_policy`].
+    ///
+    /// [`X509_VERIFY_PARAM_add0_policy`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_add0_policy.html
+    pub fn add_policy(&mut self, policy: Asn1Object) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_add0_policy(
+                self.as_ptr(),
+                policy.as_ptr(),
+            ))?;
+            mem::forget(policy);
+            Ok(())
+        }
+    }
+
+    /// Set the acceptable certificate policies, replacing any previously set.
+    ///
+    /// Policies are only checked if the [`X509VerifyFlags::EXPLICIT_POLICY`] flag is set.
+    ///
+    /// This corresponds to [`X509_VERIFY_PARAM_set1_policies`].
+    ///
+    /// [`X509_VERIFY_PARAM_set1_policies`]: https://www.openssl.org/docs/man1.1.0/crypto/X509_VERIFY_PARAM_set1_policies.html
+    pub fn set_policies(&mut self, policies: &StackRef<Asn1Object>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_VERIFY_PARAM_set1_policies(
+                self.as_ptr(),
+                policies.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
 }
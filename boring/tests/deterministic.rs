@@ -0,0 +1,24 @@
+// The deterministic random number generator is global, so this runs in its own test binary
+// rather than alongside the unit tests drawing from it in parallel.
+#[cfg(feature = "unsafe-deterministic-rng")]
+mod test_deterministic {
+    use boring::ssl::{SslConnector, SslMethod};
+
+    #[test]
+    fn deterministic_for_testing() {
+        let capture = || {
+            let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+            connector.set_deterministic_for_testing();
+            let connector = connector.build();
+
+            boring::rand::reset_deterministic_rng();
+            connector
+                .configure()
+                .unwrap()
+                .capture_client_hello("foobar.com")
+                .unwrap()
+        };
+
+        assert_eq!(capture(), capture());
+    }
+}
@@ -0,0 +1,191 @@
+use boring::ssl::{self, ErrorCode, SslRef};
+use http::Method;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_boring::SslStream;
+
+/// Which requests are sent again if the server rejects the TLS 1.3 early data (0-RTT) they were
+/// sent in.
+///
+/// With early data enabled, the first request on a resumed connection is sent along with the
+/// handshake, saving a round trip. If the server rejects early data, the request is sent again
+/// once the handshake completed if its method is allowed by the policy, and fails otherwise.
+/// Since early data can also be replayed by an attacker, only idempotent methods should be
+/// allowed.
+///
+/// Only HTTP/1 requests are sent again: a rejection on an HTTP/2 connection fails it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EarlyDataPolicy {
+    methods: Vec<Method>,
+}
+
+impl Default for EarlyDataPolicy {
+    fn default() -> EarlyDataPolicy {
+        EarlyDataPolicy::new()
+    }
+}
+
+impl EarlyDataPolicy {
+    /// Creates a policy replaying the safe methods `GET`, `HEAD`, `OPTIONS` and `TRACE`.
+    pub fn new() -> EarlyDataPolicy {
+        EarlyDataPolicy {
+            methods: vec![Method::GET, Method::HEAD, Method::OPTIONS, Method::TRACE],
+        }
+    }
+
+    /// Allows requests with the given method to be replayed.
+    pub fn allow(mut self, method: Method) -> EarlyDataPolicy {
+        if !self.methods.contains(&method) {
+            self.methods.push(method);
+        }
+        self
+    }
+
+    /// Returns `true` if requests with the given method may be replayed.
+    pub fn is_replayable(&self, method: &Method) -> bool {
+        self.methods.contains(method)
+    }
+}
+
+/// The early data of a connection, stored in its `Ssl` until the handshake completed.
+pub(crate) struct EarlyData {
+    policy: Arc<EarlyDataPolicy>,
+    sent: Vec<u8>,
+    replayed: Option<usize>,
+}
+
+impl EarlyData {
+    pub(crate) fn new(policy: Arc<EarlyDataPolicy>) -> EarlyData {
+        EarlyData {
+            policy,
+            sent: vec![],
+            replayed: None,
+        }
+    }
+
+    fn is_replayable(&self) -> bool {
+        // Several requests can be in flight on HTTP/2 connections.
+        if self.sent.starts_with(b"PRI * HTTP/2.0") {
+            return false;
+        }
+
+        let method = match self.sent.iter().position(|&b| b == b' ') {
+            Some(end) => &self.sent[..end],
+            None => return false,
+        };
+
+        Method::from_bytes(method).is_ok_and(|method| self.policy.is_replayable(&method))
+    }
+}
+
+pub(crate) fn poll_read<T>(
+    mut stream: Pin<&mut SslStream<T>>,
+    ctx: &mut Context<'_>,
+    buf: &mut ReadBuf,
+) -> Poll<io::Result<()>>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        if poll_replay(stream.as_mut(), ctx)?.is_pending() {
+            return Poll::Pending;
+        }
+
+        match stream.as_mut().poll_read(ctx, buf) {
+            Poll::Ready(Err(e)) if is_rejection(&e) => reject(stream.ssl_mut())?,
+            poll => return poll,
+        }
+    }
+}
+
+pub(crate) fn poll_write<T>(
+    mut stream: Pin<&mut SslStream<T>>,
+    ctx: &mut Context<'_>,
+    buf: &[u8],
+) -> Poll<io::Result<usize>>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        if poll_replay(stream.as_mut(), ctx)?.is_pending() {
+            return Poll::Pending;
+        }
+
+        let in_early_data = stream.ssl().in_early_data();
+
+        match stream.as_mut().poll_write(ctx, buf) {
+            Poll::Ready(Ok(n)) if in_early_data => {
+                if let Some(early_data) = stream.ssl_mut().get_mut::<EarlyData>() {
+                    early_data.sent.extend_from_slice(&buf[..n]);
+                }
+                return Poll::Ready(Ok(n));
+            }
+            Poll::Ready(Err(e)) if is_rejection(&e) => reject(stream.ssl_mut())?,
+            poll => return poll,
+        }
+    }
+}
+
+/// Writes the rejected early data again after a rejection, and forgets it once the handshake
+/// completed.
+fn poll_replay<T>(mut stream: Pin<&mut SslStream<T>>, ctx: &mut Context<'_>) -> Poll<io::Result<()>>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut early_data = match stream.ssl_mut().take::<EarlyData>() {
+        Some(early_data) => early_data,
+        None => return Poll::Ready(Ok(())),
+    };
+
+    let mut replayed = match early_data.replayed {
+        Some(replayed) => replayed,
+        None if stream.ssl().in_early_data() => {
+            stream.ssl_mut().set(early_data)?;
+            return Poll::Ready(Ok(()));
+        }
+        None => return Poll::Ready(Ok(())),
+    };
+
+    while replayed < early_data.sent.len() {
+        match stream
+            .as_mut()
+            .poll_write(ctx, &early_data.sent[replayed..])
+        {
+            Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+            Poll::Ready(Ok(n)) => replayed += n,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => {
+                early_data.replayed = Some(replayed);
+                stream.ssl_mut().set(early_data)?;
+                return Poll::Pending;
+            }
+        }
+    }
+
+    Poll::Ready(Ok(()))
+}
+
+fn reject(ssl: &mut SslRef) -> io::Result<()> {
+    match ssl.take::<EarlyData>() {
+        Some(mut early_data) if early_data.is_replayable() => {
+            ssl.reset_early_data_reject();
+            early_data.replayed = Some(0);
+            ssl.set(early_data)?;
+
+            Ok(())
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the server rejected early data, and the request cannot be replayed",
+        )),
+    }
+}
+
+fn is_rejection(e: &io::Error) -> bool {
+    e.get_ref()
+        .and_then(|e| e.downcast_ref::<ssl::Error>())
+        .is_some_and(|e| e.code() == ErrorCode::EARLY_DATA_REJECTED)
+}
@@ -3,6 +3,7 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 use crate::cache::{SessionCache, SessionKey};
+use crate::early_data::EarlyData;
 use antidote::Mutex;
 use boring::error::ErrorStack;
 use boring::ex_data::Index;
@@ -23,14 +24,20 @@ use std::net;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Instant;
 use std::{error::Error, fmt};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio_boring::SslStream;
 use tower_layer::Layer;
 
+pub use crate::early_data::EarlyDataPolicy;
+pub use crate::timings::ConnectTimings;
+
 mod cache;
+mod early_data;
 #[cfg(test)]
 mod test;
+mod timings;
 
 fn key_index() -> Result<Index<Ssl, SessionKey>, ErrorStack> {
     static IDX: OnceCell<Index<Ssl, SessionKey>> = OnceCell::new();
@@ -45,6 +52,8 @@ struct Inner {
     callback: Option<
         Arc<dyn Fn(&mut ConnectConfiguration, &Uri) -> Result<(), ErrorStack> + Sync + Send>,
     >,
+    early_data: Option<Arc<EarlyDataPolicy>>,
+    timings_callback: Option<Arc<dyn Fn(&Uri, &ConnectTimings) + Sync + Send>>,
 }
 
 impl Inner {
@@ -69,6 +78,11 @@ impl Inner {
         let idx = key_index()?;
         conf.set_ex_data(idx, key);
 
+        if let Some(ref policy) = self.early_data {
+            conf.set_early_data_enabled(true);
+            conf.set(EarlyData::new(policy.clone()))?;
+        }
+
         Ok(conf)
     }
 }
@@ -117,6 +131,8 @@ impl HttpsLayer {
                 ssl: ssl.build(),
                 cache,
                 callback: None,
+                early_data: None,
+                timings_callback: None,
             },
         })
     }
@@ -128,6 +144,25 @@ impl HttpsLayer {
     {
         self.inner.callback = Some(Arc::new(callback));
     }
+
+    /// Enables TLS 1.3 early data (0-RTT) for requests allowed by `policy`.
+    ///
+    /// Requests sent on a resumed connection are then sent along with the handshake, and
+    /// transparently sent again if the server rejects early data. See [`EarlyDataPolicy`].
+    pub fn set_early_data_policy(&mut self, policy: EarlyDataPolicy) {
+        self.inner.early_data = Some(Arc::new(policy));
+    }
+
+    /// Registers a callback which is passed the time spent establishing each connection.
+    ///
+    /// The callback is called once the connection is established, and lets clients record
+    /// latency histograms for each phase. See [`ConnectTimings`].
+    pub fn set_timings_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&Uri, &ConnectTimings) + 'static + Sync + Send,
+    {
+        self.inner.timings_callback = Some(Arc::new(callback));
+    }
 }
 
 impl<S> Layer<S> for HttpsLayer {
@@ -188,6 +223,25 @@ where
     {
         self.inner.callback = Some(Arc::new(callback));
     }
+
+    /// Enables TLS 1.3 early data (0-RTT) for requests allowed by `policy`.
+    ///
+    /// Requests sent on a resumed connection are then sent along with the handshake, and
+    /// transparently sent again if the server rejects early data. See [`EarlyDataPolicy`].
+    pub fn set_early_data_policy(&mut self, policy: EarlyDataPolicy) {
+        self.inner.early_data = Some(Arc::new(policy));
+    }
+
+    /// Registers a callback which is passed the time spent establishing each connection.
+    ///
+    /// The callback is called once the connection is established, and lets clients record
+    /// latency histograms for each phase. See [`ConnectTimings`].
+    pub fn set_timings_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&Uri, &ConnectTimings) + 'static + Sync + Send,
+    {
+        self.inner.timings_callback = Some(Arc::new(callback));
+    }
 }
 
 impl<S> Service<Uri> for HttpsConnector<S>
@@ -218,14 +272,34 @@ where
             None
         };
 
+        let timings_callback = self
+            .inner
+            .timings_callback
+            .clone()
+            .map(|callback| (callback, uri.clone()));
+
+        let start = Instant::now();
         let connect = self.http.call(uri);
 
-        let f = async {
+        let f = async move {
             let conn = connect.await.map_err(Into::into)?;
+            let connected = Instant::now();
 
             let (inner, uri) = match tls_setup {
                 Some((inner, uri)) => (inner, uri),
-                None => return Ok(MaybeHttpsStream::Http(conn)),
+                None => {
+                    if let Some((callback, uri)) = timings_callback {
+                        let timings = ConnectTimings {
+                            connect: connected - start,
+                            handshake: None,
+                            alpn_protocol: None,
+                            session_reused: false,
+                        };
+                        callback(&uri, &timings);
+                    }
+
+                    return Ok(MaybeHttpsStream::Http(conn));
+                }
             };
 
             let mut host = uri.host().ok_or("URI missing host")?;
@@ -247,6 +321,16 @@ where
             let config = inner.setup_ssl(&uri, host)?;
             let stream = tokio_boring::connect(config, host, conn).await?;
 
+            if let Some((callback, uri)) = timings_callback {
+                let timings = ConnectTimings {
+                    connect: connected - start,
+                    handshake: Some(connected.elapsed()),
+                    alpn_protocol: stream.ssl().selected_alpn_protocol().map(<[u8]>::to_vec),
+                    session_reused: stream.ssl().session_reused(),
+                };
+                callback(&uri, &timings);
+            }
+
             Ok(MaybeHttpsStream::Https(stream))
         };
 
@@ -273,7 +357,7 @@ where
     ) -> Poll<io::Result<()>> {
         match &mut *self {
             MaybeHttpsStream::Http(s) => Pin::new(s).poll_read(ctx, buf),
-            MaybeHttpsStream::Https(s) => Pin::new(s).poll_read(ctx, buf),
+            MaybeHttpsStream::Https(s) => early_data::poll_read(Pin::new(s), ctx, buf),
         }
     }
 }
@@ -289,7 +373,7 @@ where
     ) -> Poll<io::Result<usize>> {
         match &mut *self {
             MaybeHttpsStream::Http(s) => Pin::new(s).poll_write(ctx, buf),
-            MaybeHttpsStream::Https(s) => Pin::new(s).poll_write(ctx, buf),
+            MaybeHttpsStream::Https(s) => early_data::poll_write(Pin::new(s), ctx, buf),
         }
     }
 
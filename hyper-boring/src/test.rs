@@ -136,3 +136,161 @@ async fn alpn_h2() {
     let mut body = resp.into_body();
     while body.next().await.transpose().unwrap().is_some() {}
 }
+
+#[tokio::test]
+async fn early_data_replayed_after_rejection() {
+    use boring::ssl::ExtensionType;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static EARLY_DATA_OFFERED: AtomicBool = AtomicBool::new(false);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let build_acceptor = |early_data: bool| {
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor
+            .set_private_key_file("test/key.pem", SslFiletype::PEM)
+            .unwrap();
+        acceptor
+            .set_certificate_chain_file("test/cert.pem")
+            .unwrap();
+        acceptor.set_early_data_enabled(early_data);
+        if !early_data {
+            acceptor.set_select_certificate_callback(|client_hello| {
+                if client_hello
+                    .get_extension(ExtensionType::EARLY_DATA)
+                    .is_some()
+                {
+                    EARLY_DATA_OFFERED.store(true, Ordering::SeqCst);
+                }
+                Ok(())
+            });
+        }
+        acceptor.build()
+    };
+
+    let server = async move {
+        // The second server does not know the session ticket issued by the first one, and so
+        // rejects the early data of the resumption attempt.
+        for acceptor in [build_acceptor(true), build_acceptor(false)] {
+            let stream = listener.accept().await.unwrap().0;
+            let stream = tokio_boring::accept(&acceptor, stream).await.unwrap();
+
+            let service =
+                service::service_fn(|_| async { Ok::<_, io::Error>(Response::new(Body::empty())) });
+
+            Http::new()
+                .http1_keep_alive(false)
+                .serve_connection(stream, service)
+                .await
+                .unwrap();
+        }
+    };
+    tokio::spawn(server);
+
+    let mut connector = HttpConnector::new();
+    connector.enforce_http(false);
+    let mut ssl = SslConnector::builder(SslMethod::tls()).unwrap();
+    ssl.set_ca_file("test/cert.pem").unwrap();
+
+    let mut ssl = HttpsConnector::with_connector(connector, ssl).unwrap();
+    ssl.set_early_data_policy(EarlyDataPolicy::new());
+    let client = Client::builder()
+        .pool_max_idle_per_host(0)
+        .build::<_, Body>(ssl);
+
+    for _ in 0..2 {
+        let resp = client
+            .get(format!("https://localhost:{}", port).parse().unwrap())
+            .await
+            .unwrap();
+        assert!(resp.status().is_success(), "{}", resp.status());
+        let mut body = resp.into_body();
+        while body.next().await.transpose().unwrap().is_some() {}
+    }
+
+    // The second request was sent as early data, rejected, and only succeeded by being sent
+    // again once the handshake completed.
+    assert!(EARLY_DATA_OFFERED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn early_data_policy() {
+    use http::Method;
+
+    let policy = EarlyDataPolicy::new();
+    assert!(policy.is_replayable(&Method::GET));
+    assert!(!policy.is_replayable(&Method::POST));
+
+    let policy = policy.allow(Method::PUT);
+    assert!(policy.is_replayable(&Method::PUT));
+}
+
+#[tokio::test]
+async fn timings_callback() {
+    use std::sync::Mutex;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let server = async move {
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor
+            .set_private_key_file("test/key.pem", SslFiletype::PEM)
+            .unwrap();
+        acceptor
+            .set_certificate_chain_file("test/cert.pem")
+            .unwrap();
+        let acceptor = acceptor.build();
+
+        let stream = listener.accept().await.unwrap().0;
+        let stream = tokio_boring::accept(&acceptor, stream).await.unwrap();
+
+        let service =
+            service::service_fn(|_| async { Ok::<_, io::Error>(Response::new(Body::empty())) });
+
+        Http::new()
+            .http1_keep_alive(false)
+            .serve_connection(stream, service)
+            .await
+            .unwrap();
+    };
+    tokio::spawn(server);
+
+    let mut connector = HttpConnector::new();
+    connector.enforce_http(false);
+    let mut ssl = SslConnector::builder(SslMethod::tls()).unwrap();
+    ssl.set_ca_file("test/cert.pem").unwrap();
+
+    let timings = Arc::new(Mutex::new(vec![]));
+
+    let mut ssl = HttpsConnector::with_connector(connector, ssl).unwrap();
+    ssl.set_timings_callback({
+        let timings = timings.clone();
+        move |uri, connect_timings| {
+            timings
+                .lock()
+                .unwrap()
+                .push((uri.clone(), connect_timings.clone()))
+        }
+    });
+    let client = Client::builder().build::<_, Body>(ssl);
+
+    let uri: Uri = format!("https://localhost:{}", port).parse().unwrap();
+    let resp = client.get(uri.clone()).await.unwrap();
+    assert!(resp.status().is_success(), "{}", resp.status());
+
+    let timings = timings.lock().unwrap();
+    assert_eq!(timings.len(), 1);
+
+    let (connected_uri, connect_timings) = &timings[0];
+    assert_eq!(*connected_uri, uri);
+    assert!(connect_timings.handshake().is_some());
+    assert_eq!(
+        connect_timings.total(),
+        connect_timings.connect() + connect_timings.handshake().unwrap()
+    );
+    assert_eq!(connect_timings.alpn_protocol(), None);
+    assert!(!connect_timings.session_reused());
+}
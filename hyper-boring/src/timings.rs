@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+/// The time spent in each phase of establishing a connection, passed to the callback set with
+/// [`HttpsConnector::set_timings_callback`].
+///
+/// [`HttpsConnector::set_timings_callback`]: crate::HttpsConnector::set_timings_callback
+#[derive(Debug, Clone)]
+pub struct ConnectTimings {
+    pub(crate) connect: Duration,
+    pub(crate) handshake: Option<Duration>,
+    pub(crate) alpn_protocol: Option<Vec<u8>>,
+    pub(crate) session_reused: bool,
+}
+
+impl ConnectTimings {
+    /// Returns the time spent by the wrapped connector.
+    ///
+    /// For the hyper `HttpConnector`, this covers both the DNS resolution and the TCP connection,
+    /// which it does not time separately. Wrap its resolver to time the resolution on its own.
+    pub fn connect(&self) -> Duration {
+        self.connect
+    }
+
+    /// Returns the time spent in the TLS handshake, or `None` for plain HTTP connections.
+    pub fn handshake(&self) -> Option<Duration> {
+        self.handshake
+    }
+
+    /// Returns the total time spent establishing the connection.
+    pub fn total(&self) -> Duration {
+        self.connect + self.handshake.unwrap_or_default()
+    }
+
+    /// Returns the protocol selected with ALPN, if any.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns `true` if the TLS handshake resumed a previous session.
+    pub fn session_reused(&self) -> bool {
+        self.session_reused
+    }
+}
@@ -1,9 +1,18 @@
 use boring::ex_data::Index;
-use boring::ssl::{self, ClientHello, PrivateKeyMethod, Ssl, SslContextBuilder};
+use boring::ssl::{
+    self, ClientHello, Identity, PrivateKeyMethod, SniError, Ssl, SslAlert, SslContext,
+    SslContextBuilder,
+};
+use boring::x509::{X509Name, X509};
 use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::task::{ready, Context, Poll, Waker};
+use tokio::sync::Notify;
 
 /// The type of futures to pass to [`SslContextBuilderExt::set_async_select_certificate_callback`].
 pub type BoxSelectCertFuture = ExDataFuture<Result<BoxSelectCertFinish, AsyncSelectCertError>>;
@@ -11,6 +20,17 @@ pub type BoxSelectCertFuture = ExDataFuture<Result<BoxSelectCertFinish, AsyncSel
 /// The type of callbacks returned by [`BoxSelectCertFuture`] methods.
 pub type BoxSelectCertFinish = Box<dyn FnOnce(ClientHello<'_>) -> Result<(), AsyncSelectCertError>>;
 
+/// The type of futures returned by the callbacks of an [`AsyncSelectCertChain`].
+pub type BoxSelectCertChainFuture =
+    ExDataFuture<Result<AsyncSelectCertOutcome, AsyncSelectCertError>>;
+
+type SelectCertChainCallback = dyn Fn(&mut ClientHello<'_>) -> Result<BoxSelectCertChainFuture, AsyncSelectCertError>
+    + Send
+    + Sync;
+
+/// The type of futures to pass to [`SslContextBuilderExt::set_async_servername_callback`].
+pub type BoxServernameFuture = ExDataFuture<Result<Arc<SslContext>, AsyncServernameError>>;
+
 /// The type of futures returned by [`AsyncPrivateKeyMethod`] methods.
 pub type BoxPrivateKeyMethodFuture =
     ExDataFuture<Result<BoxPrivateKeyMethodFinish, AsyncPrivateKeyMethodError>>;
@@ -19,6 +39,9 @@ pub type BoxPrivateKeyMethodFuture =
 pub type BoxPrivateKeyMethodFinish =
     Box<dyn FnOnce(&mut ssl::SslRef, &mut [u8]) -> Result<usize, AsyncPrivateKeyMethodError>>;
 
+/// The type of futures to pass to [`SslContextBuilderExt::set_async_client_cert_callback`].
+pub type BoxClientCertFuture = ExDataFuture<Result<Option<Identity>, AsyncClientCertError>>;
+
 /// Convenience alias for futures stored in [`Ssl`] ex data by [`SslContextBuilderExt`] methods.
 ///
 /// Public for documentation purposes.
@@ -28,9 +51,22 @@ pub(crate) static TASK_WAKER_INDEX: Lazy<Index<Ssl, Option<Waker>>> =
     Lazy::new(|| Ssl::new_ex_index().unwrap());
 pub(crate) static SELECT_CERT_FUTURE_INDEX: Lazy<Index<Ssl, Option<BoxSelectCertFuture>>> =
     Lazy::new(|| Ssl::new_ex_index().unwrap());
+pub(crate) static SELECT_CERT_CHAIN_FUTURE_INDEX: Lazy<
+    Index<Ssl, Option<BoxSelectCertChainFuture>>,
+> = Lazy::new(|| Ssl::new_ex_index().unwrap());
+pub(crate) static SELECT_CERT_CHAIN_STAGE_INDEX: Lazy<Index<Ssl, usize>> =
+    Lazy::new(|| Ssl::new_ex_index().unwrap());
 pub(crate) static SELECT_PRIVATE_KEY_METHOD_FUTURE_INDEX: Lazy<
     Index<Ssl, Option<BoxPrivateKeyMethodFuture>>,
 > = Lazy::new(|| Ssl::new_ex_index().unwrap());
+pub(crate) static SERVERNAME_FUTURE_INDEX: Lazy<Index<Ssl, Option<BoxServernameFuture>>> =
+    Lazy::new(|| Ssl::new_ex_index().unwrap());
+pub(crate) static CLIENT_CERT_FUTURE_INDEX: Lazy<Index<Ssl, Option<BoxClientCertFuture>>> =
+    Lazy::new(|| Ssl::new_ex_index().unwrap());
+static SERVERNAME_ALERT_INDEX: Lazy<Index<Ssl, SslAlert>> =
+    Lazy::new(|| Ssl::new_ex_index().unwrap());
+static CANCELLATION_INDEX: Lazy<Index<Ssl, CancelOnDrop>> =
+    Lazy::new(|| Ssl::new_ex_index().unwrap());
 
 /// Extensions to [`SslContextBuilder`].
 ///
@@ -53,10 +89,59 @@ pub trait SslContextBuilderExt: private::Sealed {
             + Sync
             + 'static;
 
+    /// Sets a chain of async select certificate callbacks, tried one after
+    /// the other until one of them selects a certificate.
+    ///
+    /// See [`AsyncSelectCertChain`] for more details.
+    fn set_async_select_certificate_chain(&mut self, chain: AsyncSelectCertChain);
+
     /// Configures a custom private key method on the context.
     ///
     /// See [`AsyncPrivateKeyMethod`] for more details.
     fn set_async_private_key_method(&mut self, method: impl AsyncPrivateKeyMethod);
+
+    /// Sets a callback resolving the context to use for a connection from its
+    /// [`ClientHello`], typically from the requested server name.
+    ///
+    /// The future returned by the callback resolves to the [`SslContext`]
+    /// replacing this one for the connection, or to an
+    /// [`AsyncServernameError`] whose alert aborts the handshake. This lets
+    /// multi-tenant servers look up the configuration of a tenant
+    /// asynchronously.
+    ///
+    /// The callback is driven by the select certificate callback, and replaces
+    /// any callback set with [`SslContextBuilder::set_select_certificate_callback`],
+    /// [`SslContextBuilderExt::set_async_select_certificate_callback`] or
+    /// [`SslContextBuilderExt::set_async_select_certificate_chain`]. It also
+    /// replaces the servername callback, which sends the alert.
+    fn set_async_servername_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut ClientHello<'_>) -> Result<BoxServernameFuture, AsyncServernameError>
+            + Send
+            + Sync
+            + 'static;
+
+    /// Sets a callback choosing the client certificate of a connection once the server requested
+    /// one.
+    ///
+    /// The callback is passed the [`ClientCertRequest`] of the server, and returns a future
+    /// resolving to the identity to authenticate with, or to `None` to continue without a
+    /// certificate. This lets the certificate be chosen by prompting the user, by querying a
+    /// smartcard, or by asking an agent, without blocking the runtime.
+    ///
+    /// The callback is not called for connections on which the server requests no certificate,
+    /// nor on the server side.
+    ///
+    /// See [`SslContextBuilder::set_cert_callback`] for the sync setter of this callback.
+    fn set_async_client_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(
+                &mut ssl::SslRef,
+                ClientCertRequest,
+            ) -> Result<BoxClientCertFuture, AsyncClientCertError>
+            + Send
+            + Sync
+            + 'static;
 }
 
 impl SslContextBuilderExt for SslContextBuilder {
@@ -86,21 +171,262 @@ impl SslContextBuilderExt for SslContextBuilder {
         })
     }
 
+    fn set_async_select_certificate_chain(&mut self, mut chain: AsyncSelectCertChain) {
+        chain
+            .callbacks
+            .sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+
+        let callbacks = chain
+            .callbacks
+            .into_iter()
+            .map(|(_, callback)| callback)
+            .collect::<Arc<[_]>>();
+
+        self.set_select_certificate_callback(move |mut client_hello| loop {
+            let ssl = client_hello.ssl_mut();
+            let stage = ssl
+                .ex_data(*SELECT_CERT_CHAIN_STAGE_INDEX)
+                .copied()
+                .unwrap_or(0);
+
+            let callback = match callbacks.get(stage) {
+                Some(callback) => callback,
+                // No callback matched, use the certificate configured on the context.
+                None => return Ok(()),
+            };
+
+            let fut_poll_result = with_ex_data_future(
+                &mut client_hello,
+                *SELECT_CERT_CHAIN_FUTURE_INDEX,
+                ClientHello::ssl_mut,
+                &**callback,
+            );
+
+            let outcome = match fut_poll_result {
+                Poll::Ready(fut_result) => fut_result,
+                Poll::Pending => return Err(ssl::SelectCertError::RETRY),
+            };
+
+            match outcome.or(Err(ssl::SelectCertError::ERROR))? {
+                AsyncSelectCertOutcome::Selected(finish) => {
+                    return finish(client_hello).or(Err(ssl::SelectCertError::ERROR));
+                }
+                AsyncSelectCertOutcome::NoMatch => {
+                    set_select_cert_chain_stage(client_hello.ssl_mut(), stage + 1);
+                }
+            }
+        })
+    }
+
     fn set_async_private_key_method(&mut self, method: impl AsyncPrivateKeyMethod) {
         self.set_private_key_method(AsyncPrivateKeyMethodBridge(Box::new(method)));
     }
+
+    fn set_async_servername_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut ClientHello<'_>) -> Result<BoxServernameFuture, AsyncServernameError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.set_select_certificate_callback(move |mut client_hello| {
+            let fut_poll_result = with_ex_data_future(
+                &mut client_hello,
+                *SERVERNAME_FUTURE_INDEX,
+                ClientHello::ssl_mut,
+                &callback,
+            );
+
+            let ssl = client_hello.ssl_mut();
+
+            match fut_poll_result {
+                Poll::Ready(Ok(ctx)) => ssl
+                    .set_ssl_context(&ctx)
+                    .or(Err(ssl::SelectCertError::ERROR)),
+                // The select certificate callback cannot choose the alert, so it is left for the
+                // servername callback to send.
+                Poll::Ready(Err(err)) => {
+                    ssl.set_ex_data(*SERVERNAME_ALERT_INDEX, err.alert());
+                    Ok(())
+                }
+                Poll::Pending => Err(ssl::SelectCertError::RETRY),
+            }
+        });
+
+        self.set_servername_callback(|ssl, alert| match ssl.ex_data(*SERVERNAME_ALERT_INDEX) {
+            Some(refusal) => {
+                *alert = *refusal;
+                Err(SniError::ALERT_FATAL)
+            }
+            None => Ok(()),
+        });
+    }
+    fn set_async_client_cert_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(
+                &mut ssl::SslRef,
+                ClientCertRequest,
+            ) -> Result<BoxClientCertFuture, AsyncClientCertError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.set_cert_callback(move |ssl| {
+            if ssl.is_server() {
+                return Ok(());
+            }
+
+            let fut_poll_result = with_ex_data_future(
+                ssl,
+                *CLIENT_CERT_FUTURE_INDEX,
+                |ssl| ssl,
+                |ssl| {
+                    let request = ClientCertRequest::new(ssl);
+                    callback(ssl, request)
+                },
+            );
+
+            match fut_poll_result {
+                Poll::Ready(Ok(Some(identity))) => ssl
+                    .set_identity(&identity)
+                    .or(Err(ssl::CertCallbackError::ERROR)),
+                Poll::Ready(Ok(None)) => Ok(()),
+                Poll::Ready(Err(_)) => Err(ssl::CertCallbackError::ERROR),
+                Poll::Pending => Err(ssl::CertCallbackError::RETRY),
+            }
+        });
+    }
+}
+
+fn set_select_cert_chain_stage(ssl: &mut ssl::SslRef, stage: usize) {
+    match ssl.ex_data_mut(*SELECT_CERT_CHAIN_STAGE_INDEX) {
+        Some(current) => *current = stage,
+        None => ssl.set_ex_data(*SELECT_CERT_CHAIN_STAGE_INDEX, stage),
+    }
 }
 
 /// A fatal error to be returned from async select certificate callbacks.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct AsyncSelectCertError;
 
+/// A fatal error to be returned from async client certificate callbacks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AsyncClientCertError;
+
+/// The certificate request of a server, passed to async client certificate callbacks.
+pub struct ClientCertRequest {
+    ca_names: Vec<X509Name>,
+    signature_algorithms: Vec<ssl::SslSignatureAlgorithm>,
+}
+
+impl ClientCertRequest {
+    fn new(ssl: &ssl::SslRef) -> Self {
+        let ca_names = ssl
+            .client_ca_list()
+            .into_iter()
+            .flatten()
+            .filter_map(|name| X509Name::from_der(&name.to_der().ok()?).ok())
+            .collect();
+
+        Self {
+            ca_names,
+            signature_algorithms: ssl.peer_verify_algorithms().to_vec(),
+        }
+    }
+
+    /// Returns the names of the certificate authorities accepted by the server.
+    ///
+    /// The list is empty if the server accepts any certificate authority.
+    pub fn ca_names(&self) -> &[X509Name] {
+        &self.ca_names
+    }
+
+    /// Returns the signature algorithms accepted by the server, in its order of preference.
+    pub fn signature_algorithms(&self) -> &[ssl::SslSignatureAlgorithm] {
+        &self.signature_algorithms
+    }
+}
+
+/// A refusal of the connection by an async servername callback, aborting the
+/// handshake with an alert.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AsyncServernameError(SslAlert);
+
+impl AsyncServernameError {
+    /// Refuses a connection for an unknown server name with an
+    /// `unrecognized_name` alert.
+    pub const UNRECOGNIZED_NAME: Self = Self(SslAlert::UNRECOGNIZED_NAME);
+
+    /// Refuses the connection with the given alert.
+    pub fn new(alert: SslAlert) -> Self {
+        Self(alert)
+    }
+
+    /// Returns the alert sent to the client.
+    pub fn alert(&self) -> SslAlert {
+        self.0
+    }
+}
+
+/// The result of a callback in an [`AsyncSelectCertChain`].
+pub enum AsyncSelectCertOutcome {
+    /// The callback handles the connection, and the returned closure is passed
+    /// the [`ClientHello`] to configure it.
+    Selected(BoxSelectCertFinish),
+    /// The callback does not handle the connection, and the next callback of
+    /// the chain is tried.
+    NoMatch,
+}
+
+/// An ordered list of async select certificate callbacks.
+///
+/// Each callback either selects a certificate for the connection, declines
+/// with [`AsyncSelectCertOutcome::NoMatch`] so that the next callback is
+/// tried, or fails the handshake with [`AsyncSelectCertError`]. This lets
+/// sources such as an in-memory cache, a remote certificate service and a
+/// default certificate be combined without embedding the fallback logic in a
+/// single callback.
+///
+/// Callbacks are tried in decreasing order of priority, and callbacks with
+/// the same priority in the order they were added. If every callback declines,
+/// the certificate configured on the context is used.
+///
+/// The chain is installed with
+/// [`SslContextBuilderExt::set_async_select_certificate_chain`].
+#[derive(Default)]
+pub struct AsyncSelectCertChain {
+    callbacks: Vec<(i32, Box<SelectCertChainCallback>)>,
+}
+
+impl AsyncSelectCertChain {
+    /// Creates an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a callback to the chain with the given priority.
+    pub fn push<F>(&mut self, priority: i32, callback: F) -> &mut Self
+    where
+        F: Fn(&mut ClientHello<'_>) -> Result<BoxSelectCertChainFuture, AsyncSelectCertError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.callbacks.push((priority, Box::new(callback)));
+        self
+    }
+}
+
 /// Describes async private key hooks. This is used to off-load signing
 /// operations to a custom, potentially asynchronous, backend. Metadata about the
 /// key such as the type and size are parsed out of the certificate.
 ///
 /// See [`PrivateKeyMethod`] for the sync version of those hooks.
 ///
+/// Operations still in flight when the handshake is abandoned, e.g. because the peer went away or
+/// because the handshake future was dropped on a timeout, can be cancelled through the
+/// [`CancellationToken`] of the connection.
+///
 /// [`ssl_private_key_method_st`]: https://commondatastorage.googleapis.com/chromium-boringssl-docs/ssl.h.html#ssl_private_key_method_st
 pub trait AsyncPrivateKeyMethod: Send + Sync + 'static {
     /// Signs the message `input` using the specified signature algorithm.
@@ -133,9 +459,173 @@ pub trait AsyncPrivateKeyMethod: Send + Sync + 'static {
     ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError>;
 }
 
+/// A handle which fires once the connection it was obtained for is dropped.
+///
+/// Async private key methods offloading operations to a remote backend, such as an HSM or a KMS,
+/// get the token of the connection with [`CancellationToken::for_ssl`] and use it to cancel the
+/// RPCs they started on their own tasks, which would otherwise run to completion for a
+/// connection nobody waits for. This covers handshakes abandoned after a failure, and handshake
+/// futures dropped by a timeout such as [`tokio::time::timeout`].
+///
+/// Cloning a token yields a handle to the same token.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boring::ssl::{SslRef, SslSignatureAlgorithm};
+/// use tokio_boring::{
+///     AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture,
+///     CancellationToken,
+/// };
+///
+/// # async fn remote_sign(_: Vec<u8>) -> Vec<u8> { vec![] }
+/// struct RemoteKey;
+///
+/// impl AsyncPrivateKeyMethod for RemoteKey {
+///     fn sign(
+///         &self,
+///         ssl: &mut SslRef,
+///         input: &[u8],
+///         _: SslSignatureAlgorithm,
+///         _: &mut [u8],
+///     ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+///         let token = CancellationToken::for_ssl(ssl);
+///         let rpc = tokio::spawn(remote_sign(input.to_vec()));
+///
+///         Ok(Box::pin(async move {
+///             tokio::select! {
+///                 signature = rpc => {
+///                     let signature = signature.map_err(|_| AsyncPrivateKeyMethodError::FAILURE)?;
+///
+///                     Ok(Box::new(move |_: &mut SslRef, output: &mut [u8]| {
+///                         output[..signature.len()].copy_from_slice(&signature);
+///                         Ok(signature.len())
+///                     }) as Box<_>)
+///                 }
+///                 _ = token.cancelled() => Err(AsyncPrivateKeyMethodError::new("cancelled")),
+///             }
+///         }))
+///     }
+///
+///     fn decrypt(
+///         &self,
+///         _: &mut SslRef,
+///         _: &[u8],
+///         _: &mut [u8],
+///     ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+///         Err(AsyncPrivateKeyMethodError::FAILURE)
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct CancellationToken(Arc<CancellationInner>);
+
+struct CancellationInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Returns the token of the connection, creating it on first use.
+    pub fn for_ssl(ssl: &mut ssl::SslRef) -> Self {
+        if let Some(guard) = ssl.ex_data(*CANCELLATION_INDEX) {
+            return guard.0.clone();
+        }
+
+        let token = CancellationToken(Arc::new(CancellationInner {
+            cancelled: AtomicBool::new(false),
+            notify: Notify::new(),
+        }));
+        ssl.set_ex_data(*CANCELLATION_INDEX, CancelOnDrop(token.clone()));
+
+        token
+    }
+
+    /// Returns `true` once the connection was dropped.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Waits until the connection is dropped.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.0.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+
+    fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        self.0.notify.notify_waiters();
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Cancels the token of a connection when its `Ssl` is freed.
+struct CancelOnDrop(CancellationToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
 /// A fatal error to be returned from async private key methods.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct AsyncPrivateKeyMethodError;
+///
+/// The reason, if any, is reported like that of [`ssl::PrivateKeyMethodError::failure`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsyncPrivateKeyMethodError {
+    reason: Option<Cow<'static, str>>,
+}
+
+impl AsyncPrivateKeyMethodError {
+    /// A fatal error without a reason.
+    pub const FAILURE: Self = Self { reason: None };
+
+    /// Returns a fatal error for the given reason, such as an unavailable key or a timed out
+    /// backend.
+    pub fn new(reason: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            reason: Some(reason.into()),
+        }
+    }
+
+    /// Returns the reason of the error, if one was given.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+}
+
+impl fmt::Display for AsyncPrivateKeyMethodError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match &self.reason {
+            Some(reason) => write!(fmt, "the private key operation failed: {}", reason),
+            None => fmt.write_str("the private key operation failed"),
+        }
+    }
+}
+
+impl std::error::Error for AsyncPrivateKeyMethodError {}
+
+impl From<AsyncPrivateKeyMethodError> for ssl::PrivateKeyMethodError {
+    fn from(err: AsyncPrivateKeyMethodError) -> Self {
+        match err.reason {
+            Some(reason) => ssl::PrivateKeyMethodError::failure(reason),
+            None => ssl::PrivateKeyMethodError::FAILURE,
+        }
+    }
+}
 
 struct AsyncPrivateKeyMethodBridge(Box<dyn AsyncPrivateKeyMethod>);
 
@@ -177,7 +667,7 @@ impl PrivateKeyMethod for AsyncPrivateKeyMethodBridge {
                 panic!("BUG: boring called complete without a pending operation");
             }
 
-            Err(AsyncPrivateKeyMethodError)
+            Err(AsyncPrivateKeyMethodError::FAILURE)
         })
     }
 }
@@ -208,9 +698,9 @@ fn with_private_key_method(
         Poll::Pending => return Err(ssl::PrivateKeyMethodError::RETRY),
     };
 
-    let finish = fut_result.or(Err(ssl::PrivateKeyMethodError::FAILURE))?;
+    let finish = fut_result?;
 
-    finish(ssl, output).or(Err(ssl::PrivateKeyMethodError::FAILURE))
+    Ok(finish(ssl, output)?)
 }
 
 /// Creates and drives a future stored in `ssl_handle`'s `Ssl` at ex data index `index`.
@@ -252,8 +742,34 @@ fn with_ex_data_future<H, T, E>(
     }
 }
 
+/// Extensions to [`Identity`].
+pub trait IdentityExt: private::Sealed {
+    /// Like [`Identity::with_private_key_method`], but signs with an
+    /// [`AsyncPrivateKeyMethod`].
+    fn with_async_private_key_method(
+        cert: X509,
+        chain: Vec<X509>,
+        method: impl AsyncPrivateKeyMethod,
+    ) -> Self;
+}
+
+impl IdentityExt for Identity {
+    fn with_async_private_key_method(
+        cert: X509,
+        chain: Vec<X509>,
+        method: impl AsyncPrivateKeyMethod,
+    ) -> Self {
+        Identity::with_private_key_method(
+            cert,
+            chain,
+            AsyncPrivateKeyMethodBridge(Box::new(method)),
+        )
+    }
+}
+
 mod private {
     pub trait Sealed {}
 }
 
 impl private::Sealed for SslContextBuilder {}
+impl private::Sealed for Identity {}
@@ -0,0 +1,271 @@
+//! Batched signing for server handshakes.
+//!
+//! Servers terminating many connections with keys held by an HSM or a KMS pay for a round trip per
+//! handshake when each signature is requested on its own. [`BatchingPrivateKeyMethod`] coalesces
+//! the signing requests of concurrent handshakes and hands them to a [`BatchSigner`] together, so
+//! that they can be submitted in a single batch RPC.
+//!
+//! A batch is flushed once it holds [`BatchPolicy::max_batch_size`] requests, or once its first
+//! request waited for [`BatchPolicy::max_delay`], whichever comes first. Requests of handshakes
+//! abandoned while waiting are dropped from the batch.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boring::ssl::{SslAcceptor, SslMethod};
+//! use std::time::Duration;
+//! use tokio_boring::batch::{
+//!     BatchPolicy, BatchSigner, BatchingPrivateKeyMethod, BoxBatchSignFuture, SignRequest,
+//! };
+//! use tokio_boring::SslContextBuilderExt;
+//!
+//! # async fn kms_sign_batch(_: Vec<SignRequest>) -> Vec<Vec<u8>> { vec![] }
+//! struct Kms;
+//!
+//! impl BatchSigner for Kms {
+//!     fn sign_batch(&self, requests: Vec<SignRequest>) -> BoxBatchSignFuture {
+//!         Box::pin(async move {
+//!             kms_sign_batch(requests).await.into_iter().map(Ok).collect()
+//!         })
+//!     }
+//! }
+//!
+//! let policy = BatchPolicy::new()
+//!     .max_batch_size(64)
+//!     .max_delay(Duration::from_millis(2));
+//!
+//! let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+//! acceptor.set_certificate_chain_file("cert.pem").unwrap();
+//! acceptor.set_async_private_key_method(BatchingPrivateKeyMethod::new(Kms, policy));
+//! ```
+use boring::ssl::{SslRef, SslSignatureAlgorithm};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time;
+
+use crate::{AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture};
+
+/// The type of futures returned by [`BatchSigner::sign_batch`].
+pub type BoxBatchSignFuture =
+    Pin<Box<dyn Future<Output = Vec<Result<Vec<u8>, AsyncPrivateKeyMethodError>>> + Send>>;
+
+/// A backend signing several handshakes' requests at once.
+pub trait BatchSigner: Send + Sync + 'static {
+    /// Signs a batch of requests.
+    ///
+    /// The returned future resolves to one result per request, in the order of `requests`.
+    /// Requests without a result fail.
+    fn sign_batch(&self, requests: Vec<SignRequest>) -> BoxBatchSignFuture;
+}
+
+/// A signing request of a handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignRequest {
+    input: Vec<u8>,
+    signature_algorithm: SslSignatureAlgorithm,
+}
+
+impl SignRequest {
+    /// Returns the message to sign.
+    pub fn input(&self) -> &[u8] {
+        &self.input
+    }
+
+    /// Returns the signature algorithm to sign with.
+    pub fn signature_algorithm(&self) -> SslSignatureAlgorithm {
+        self.signature_algorithm
+    }
+}
+
+/// When a [`BatchingPrivateKeyMethod`] flushes the requests it collected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchPolicy {
+    max_batch_size: usize,
+    max_delay: Duration,
+}
+
+impl Default for BatchPolicy {
+    fn default() -> BatchPolicy {
+        BatchPolicy::new()
+    }
+}
+
+impl BatchPolicy {
+    /// Creates a policy flushing batches of up to 32 requests, after at most 1 millisecond.
+    pub fn new() -> BatchPolicy {
+        BatchPolicy {
+            max_batch_size: 32,
+            max_delay: Duration::from_millis(1),
+        }
+    }
+
+    /// Sets the number of requests after which a batch is flushed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_batch_size` is zero.
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> BatchPolicy {
+        assert!(max_batch_size > 0, "batches must hold at least one request");
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Sets how long the first request of a batch waits for others before the batch is flushed.
+    pub fn max_delay(mut self, max_delay: Duration) -> BatchPolicy {
+        self.max_delay = max_delay;
+        self
+    }
+}
+
+struct Job {
+    request: SignRequest,
+    reply: oneshot::Sender<Result<Vec<u8>, AsyncPrivateKeyMethodError>>,
+}
+
+/// An [`AsyncPrivateKeyMethod`] coalescing the signing requests of concurrent handshakes into
+/// batches for a [`BatchSigner`].
+///
+/// Batches are collected by a task spawned on the tokio runtime driving the first handshake. If
+/// that runtime shuts down, the task is spawned again on the runtime of the next handshake.
+///
+/// Only signing is batched: decryption, which is only used by the RSA key exchange of TLS 1.2 and
+/// earlier, fails.
+pub struct BatchingPrivateKeyMethod {
+    signer: Arc<dyn BatchSigner>,
+    policy: BatchPolicy,
+    jobs: Mutex<Option<mpsc::UnboundedSender<Job>>>,
+}
+
+impl BatchingPrivateKeyMethod {
+    /// Creates a private key method batching requests for `signer` according to `policy`.
+    pub fn new(signer: impl BatchSigner, policy: BatchPolicy) -> Self {
+        Self {
+            signer: Arc::new(signer),
+            policy,
+            jobs: Mutex::new(None),
+        }
+    }
+
+    /// Returns the sender of the batching task, spawning the task on the current runtime if it
+    /// is not running.
+    fn jobs(&self) -> Result<mpsc::UnboundedSender<Job>, AsyncPrivateKeyMethodError> {
+        let mut jobs = self.jobs.lock().unwrap();
+
+        // The task stops with the runtime it was spawned on.
+        if let Some(tx) = jobs.as_ref().filter(|tx| !tx.is_closed()) {
+            return Ok(tx.clone());
+        }
+
+        let handle = Handle::try_current().map_err(|_| {
+            AsyncPrivateKeyMethodError::new("batched signing requires a tokio runtime")
+        })?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        handle.spawn(collect_batches(self.signer.clone(), self.policy, rx));
+
+        Ok(jobs.insert(tx).clone())
+    }
+}
+
+impl AsyncPrivateKeyMethod for BatchingPrivateKeyMethod {
+    fn sign(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let (reply, result) = oneshot::channel();
+        let job = Job {
+            request: SignRequest {
+                input: input.to_vec(),
+                signature_algorithm,
+            },
+            reply,
+        };
+
+        self.jobs()?
+            .send(job)
+            .map_err(|_| AsyncPrivateKeyMethodError::new("the batching task stopped"))?;
+
+        Ok(Box::pin(async move {
+            let signature = result
+                .await
+                .map_err(|_| AsyncPrivateKeyMethodError::new("the batch was dropped"))??;
+
+            Ok(Box::new(move |_: &mut SslRef, output: &mut [u8]| {
+                let output = output
+                    .get_mut(..signature.len())
+                    .ok_or_else(|| AsyncPrivateKeyMethodError::new("the signature is too large"))?;
+                output.copy_from_slice(&signature);
+
+                Ok(signature.len())
+            }) as Box<_>)
+        }))
+    }
+
+    fn decrypt(
+        &self,
+        _: &mut SslRef,
+        _: &[u8],
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        Err(AsyncPrivateKeyMethodError::new(
+            "decryption is not supported by batched private keys",
+        ))
+    }
+}
+
+async fn collect_batches(
+    signer: Arc<dyn BatchSigner>,
+    policy: BatchPolicy,
+    mut jobs: mpsc::UnboundedReceiver<Job>,
+) {
+    while let Some(job) = jobs.recv().await {
+        let mut batch = vec![job];
+
+        let deadline = time::sleep(policy.max_delay);
+        tokio::pin!(deadline);
+
+        while batch.len() < policy.max_batch_size {
+            tokio::select! {
+                job = jobs.recv() => match job {
+                    Some(job) => batch.push(job),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+
+        // Handshakes abandoned in the meantime no longer wait for their signature.
+        batch.retain(|job| !job.reply.is_closed());
+        if batch.is_empty() {
+            continue;
+        }
+
+        tokio::spawn(sign_batch(signer.clone(), batch));
+    }
+}
+
+async fn sign_batch(signer: Arc<dyn BatchSigner>, batch: Vec<Job>) {
+    let (requests, replies): (Vec<_>, Vec<_>) = batch
+        .into_iter()
+        .map(|job| (job.request, job.reply))
+        .unzip();
+
+    let mut results = signer.sign_batch(requests).await.into_iter();
+
+    for reply in replies {
+        let result = results.next().unwrap_or_else(|| {
+            Err(AsyncPrivateKeyMethodError::new(
+                "the batch signer returned too few results",
+            ))
+        });
+
+        let _ = reply.send(result);
+    }
+}
@@ -0,0 +1,227 @@
+//! Async DTLS over UDP.
+//!
+//! DTLS runs over an unreliable transport, so a peer retransmits its last flight of handshake
+//! messages when no answer arrives in time. [`connect`] and [`accept`] drive these
+//! retransmissions with a tokio timer, following [`SslRef::dtls_timeout`], so that a lost
+//! datagram does not stall the handshake.
+//!
+//! Both take a [`UdpStream`], a UDP socket connected to the peer on which each read and write is
+//! a single datagram. Its MTU, if set, is applied to the connection so that handshake messages
+//! are fragmented to fit in a datagram.
+//!
+//! BoringSSL does not implement the stateless cookie exchange of DTLS servers (the
+//! `HelloVerifyRequest` message), so servers cannot use it to verify the address of a client
+//! before keeping state for it. Servers exposed to spoofed traffic should instead bound the
+//! number of concurrent handshakes, e.g. with [`HandshakeBudget`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boring::ssl::{SslConnector, SslMethod};
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//! use tokio::net::UdpSocket;
+//! use tokio_boring::dtls::{self, UdpStream};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let socket = UdpSocket::bind("0.0.0.0:0").await?;
+//! socket.connect("example.com:4433").await?;
+//!
+//! let config = SslConnector::builder(SslMethod::dtls())?.build().configure()?;
+//! let mut stream = dtls::connect(config, "example.com", UdpStream::with_mtu(socket, 1200)).await?;
+//!
+//! stream.write_all(b"ping").await?;
+//!
+//! let mut buf = [0; 4];
+//! stream.read_exact(&mut buf).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`SslRef::dtls_timeout`]: boring::ssl::SslRef::dtls_timeout
+//! [`HandshakeBudget`]: boring::ssl::HandshakeBudget
+use boring::error::ErrorStack;
+use boring::ssl::{self, ConnectConfiguration, MidHandshakeSslStream, SslAcceptor};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::UdpSocket;
+use tokio::time::{self, Instant, Sleep};
+
+use crate::bridge::AsyncStreamBridge;
+use crate::{HandshakeError, SslStream};
+
+/// A UDP socket connected to a single peer, read and written one datagram at a time.
+#[derive(Debug)]
+pub struct UdpStream {
+    socket: UdpSocket,
+    mtu: Option<u32>,
+}
+
+impl UdpStream {
+    /// Wraps a socket, which must be connected to the peer.
+    ///
+    /// The MTU is left to BoringSSL's default.
+    pub fn new(socket: UdpSocket) -> Self {
+        Self { socket, mtu: None }
+    }
+
+    /// Wraps a socket, which must be connected to the peer, with the MTU of the path to the
+    /// peer.
+    ///
+    /// The MTU excludes the IP and UDP headers.
+    pub fn with_mtu(socket: UdpSocket, mtu: u32) -> Self {
+        Self {
+            socket,
+            mtu: Some(mtu),
+        }
+    }
+
+    /// Returns the MTU of the path to the peer, if set.
+    pub fn mtu(&self) -> Option<u32> {
+        self.mtu
+    }
+
+    /// Returns a shared reference to the socket.
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the socket.
+    pub fn into_inner(self) -> UdpSocket {
+        self.socket
+    }
+}
+
+impl AsyncRead for UdpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.socket.poll_recv(ctx, buf)
+    }
+}
+
+impl AsyncWrite for UdpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.socket.poll_send(ctx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Asynchronously performs a client-side DTLS handshake over the provided socket.
+pub async fn connect(
+    config: ConnectConfiguration,
+    domain: &str,
+    stream: UdpStream,
+) -> Result<SslStream<UdpStream>, HandshakeError<UdpStream>> {
+    handshake(|s| config.setup_connect(domain, s), stream).await
+}
+
+/// Asynchronously performs a server-side DTLS handshake over the provided socket.
+pub async fn accept(
+    acceptor: &SslAcceptor,
+    stream: UdpStream,
+) -> Result<SslStream<UdpStream>, HandshakeError<UdpStream>> {
+    handshake(|s| acceptor.setup_accept(s), stream).await
+}
+
+async fn handshake(
+    f: impl FnOnce(
+        AsyncStreamBridge<UdpStream>,
+    ) -> Result<MidHandshakeSslStream<AsyncStreamBridge<UdpStream>>, ErrorStack>,
+    stream: UdpStream,
+) -> Result<SslStream<UdpStream>, HandshakeError<UdpStream>> {
+    let mtu = stream.mtu;
+
+    let mut mid_handshake = f(AsyncStreamBridge::new(stream))
+        .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err)))?;
+
+    if let Some(mtu) = mtu {
+        mid_handshake
+            .ssl_mut()
+            .set_mtu(mtu)
+            .map_err(|err| HandshakeError(ssl::HandshakeError::SetupFailure(err)))?;
+    }
+
+    HandshakeFuture {
+        handshake: crate::HandshakeFuture(Some(mid_handshake)),
+        timer: None,
+    }
+    .await
+}
+
+/// Future for an ongoing DTLS handshake.
+///
+/// See [`connect`] and [`accept`].
+///
+/// If the peer does not answer after several retransmissions, the handshake fails with a
+/// [`HandshakeError`] carrying the error stack of [`SslRef::dtls_handle_timeout`].
+///
+/// [`SslRef::dtls_handle_timeout`]: boring::ssl::SslRef::dtls_handle_timeout
+pub struct HandshakeFuture {
+    handshake: crate::HandshakeFuture<UdpStream>,
+    timer: Option<Pin<Box<Sleep>>>,
+}
+
+impl Future for HandshakeFuture {
+    type Output = Result<SslStream<UdpStream>, HandshakeError<UdpStream>>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        loop {
+            if let Poll::Ready(result) = Pin::new(&mut this.handshake).poll(ctx) {
+                return Poll::Ready(result);
+            }
+
+            let mid_handshake = this
+                .handshake
+                .0
+                .as_mut()
+                .expect("BUG: pending handshake without a stream");
+
+            let timeout = match mid_handshake.ssl().dtls_timeout() {
+                Some(timeout) => timeout,
+                None => {
+                    this.timer = None;
+                    return Poll::Pending;
+                }
+            };
+
+            let deadline = Instant::now() + timeout;
+            let timer = this
+                .timer
+                .get_or_insert_with(|| Box::pin(time::sleep_until(deadline)));
+            timer.as_mut().reset(deadline);
+
+            if timer.as_mut().poll(ctx).is_pending() {
+                return Poll::Pending;
+            }
+
+            // The retransmission writes to the socket, which needs the task's context.
+            mid_handshake.get_mut().set_waker(Some(ctx));
+            let result = mid_handshake.ssl_mut().dtls_handle_timeout();
+            mid_handshake.get_mut().set_waker(None);
+
+            if let Err(err) = result {
+                this.handshake.0 = None;
+
+                return Poll::Ready(Err(HandshakeError(ssl::HandshakeError::SetupFailure(err))));
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use aws_sdk_kms::error::DisplayErrorContext;
+use aws_sdk_kms::primitives::Blob;
+use aws_sdk_kms::types::{MessageType, SigningAlgorithmSpec};
+use aws_sdk_kms::Client;
+use boring::ssl::{SslRef, SslSignatureAlgorithm};
+
+use super::{
+    decryption_unsupported, sign_with, DigestAlgorithm, DigestSignRequest, SignatureScheme,
+};
+use crate::{AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture};
+
+/// An [`AsyncPrivateKeyMethod`] signing with an asymmetric key of AWS KMS.
+///
+/// Digests are computed locally and sent with the `DIGEST` message type, so handshakes never
+/// send more than 64 bytes to KMS.
+#[derive(Debug, Clone)]
+pub struct AwsKmsSigner {
+    client: Client,
+    key_id: String,
+}
+
+impl AwsKmsSigner {
+    /// Creates a signer using the key identified by `key_id`, which may be a key ID, a key ARN,
+    /// an alias name or an alias ARN.
+    pub fn new(client: Client, key_id: impl Into<String>) -> Self {
+        Self {
+            client,
+            key_id: key_id.into(),
+        }
+    }
+}
+
+impl AsyncPrivateKeyMethod for AwsKmsSigner {
+    fn sign(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let request = DigestSignRequest::new(input, signature_algorithm)?;
+        let sign = self
+            .client
+            .sign()
+            .key_id(&self.key_id)
+            .message(Blob::new(request.digest()))
+            .message_type(MessageType::Digest)
+            .signing_algorithm(signing_algorithm(&request));
+
+        Ok(sign_with(async move {
+            let output = sign
+                .send()
+                .await
+                .map_err(|e| AsyncPrivateKeyMethodError::new(DisplayErrorContext(e).to_string()))?;
+
+            output
+                .signature()
+                .map(|signature| signature.as_ref().to_vec())
+                .ok_or_else(|| AsyncPrivateKeyMethodError::new("AWS KMS returned no signature"))
+        }))
+    }
+
+    fn decrypt(
+        &self,
+        _: &mut SslRef,
+        _: &[u8],
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        Err(decryption_unsupported())
+    }
+}
+
+fn signing_algorithm(request: &DigestSignRequest) -> SigningAlgorithmSpec {
+    match (request.scheme(), request.digest_algorithm()) {
+        (SignatureScheme::RsaPkcs1, DigestAlgorithm::Sha256) => {
+            SigningAlgorithmSpec::RsassaPkcs1V15Sha256
+        }
+        (SignatureScheme::RsaPkcs1, DigestAlgorithm::Sha384) => {
+            SigningAlgorithmSpec::RsassaPkcs1V15Sha384
+        }
+        (SignatureScheme::RsaPkcs1, DigestAlgorithm::Sha512) => {
+            SigningAlgorithmSpec::RsassaPkcs1V15Sha512
+        }
+        (SignatureScheme::RsaPss, DigestAlgorithm::Sha256) => SigningAlgorithmSpec::RsassaPssSha256,
+        (SignatureScheme::RsaPss, DigestAlgorithm::Sha384) => SigningAlgorithmSpec::RsassaPssSha384,
+        (SignatureScheme::RsaPss, DigestAlgorithm::Sha512) => SigningAlgorithmSpec::RsassaPssSha512,
+        (SignatureScheme::Ecdsa, DigestAlgorithm::Sha256) => SigningAlgorithmSpec::EcdsaSha256,
+        (SignatureScheme::Ecdsa, DigestAlgorithm::Sha384) => SigningAlgorithmSpec::EcdsaSha384,
+        (SignatureScheme::Ecdsa, DigestAlgorithm::Sha512) => SigningAlgorithmSpec::EcdsaSha512,
+    }
+}
@@ -0,0 +1,72 @@
+use boring::ssl::{SslRef, SslSignatureAlgorithm};
+use google_cloud_kms::client::Client;
+use google_cloud_kms::grpc::kms::v1::{digest, AsymmetricSignRequest, Digest};
+
+use super::{decryption_unsupported, sign_with, DigestAlgorithm, DigestSignRequest};
+use crate::{AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture};
+
+/// An [`AsyncPrivateKeyMethod`] signing with an asymmetric key version of Google Cloud KMS.
+///
+/// The signature scheme is fixed by the algorithm of the key version, so the signing algorithm
+/// preferences of the context must only list algorithms of that scheme. Digests are computed
+/// locally.
+#[derive(Clone)]
+pub struct GcpKmsSigner {
+    client: Client,
+    key_version: String,
+}
+
+impl GcpKmsSigner {
+    /// Creates a signer using the key version named `key_version`, of the form
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/*`.
+    pub fn new(client: Client, key_version: impl Into<String>) -> Self {
+        Self {
+            client,
+            key_version: key_version.into(),
+        }
+    }
+}
+
+impl AsyncPrivateKeyMethod for GcpKmsSigner {
+    fn sign(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let request = DigestSignRequest::new(input, signature_algorithm)?;
+        let digest = request.digest().to_vec();
+        let digest = match request.digest_algorithm() {
+            DigestAlgorithm::Sha256 => digest::Digest::Sha256(digest),
+            DigestAlgorithm::Sha384 => digest::Digest::Sha384(digest),
+            DigestAlgorithm::Sha512 => digest::Digest::Sha512(digest),
+        };
+        let request = AsymmetricSignRequest {
+            name: self.key_version.clone(),
+            digest: Some(Digest {
+                digest: Some(digest),
+            }),
+            ..Default::default()
+        };
+        let client = self.client.clone();
+
+        Ok(sign_with(async move {
+            let response = client
+                .asymmetric_sign(request, None)
+                .await
+                .map_err(|e| AsyncPrivateKeyMethodError::new(e.to_string()))?;
+
+            Ok(response.signature)
+        }))
+    }
+
+    fn decrypt(
+        &self,
+        _: &mut SslRef,
+        _: &[u8],
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        Err(decryption_unsupported())
+    }
+}
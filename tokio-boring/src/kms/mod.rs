@@ -0,0 +1,195 @@
+//! Handshake signatures with keys held by a KMS or an HSM.
+//!
+//! Remote signers sign a digest with a scheme fixed by the key, rather than a TLS signature
+//! algorithm. [`DigestSignRequest`] maps the signature algorithm negotiated by a handshake to the
+//! scheme and digest a backend expects, and the backends of this module build on it:
+//!
+//! * `AwsKmsSigner`, with the `aws-kms` feature, signs with an asymmetric key of AWS KMS.
+//! * `GcpKmsSigner`, with the `gcp-kms` feature, signs with a key version of Google Cloud KMS.
+//! * `Pkcs11Signer`, with the `pkcs11` feature, signs with a private key of a PKCS#11 token.
+//!
+//! Each of them is an [`AsyncPrivateKeyMethod`](crate::AsyncPrivateKeyMethod) for
+//! [`SslContextBuilderExt::set_async_private_key_method`](crate::SslContextBuilderExt::set_async_private_key_method).
+//! Only signing is supported: decryption, which is only used by the RSA key exchange of TLS 1.2
+//! and earlier, fails.
+//!
+//! The SHA-1 and MD5+SHA-1 algorithms of older clients and Ed25519 are not supported, so the
+//! signing algorithm preferences of a context using these backends should be restricted to the
+//! supported ones with [`SslContextBuilder::set_signing_algorithm_prefs`].
+//!
+//! [`SslContextBuilder::set_signing_algorithm_prefs`]: boring::ssl::SslContextBuilder::set_signing_algorithm_prefs
+use boring::hash::{hash, MessageDigest};
+use boring::ssl::{SslRef, SslSignatureAlgorithm};
+use std::future::Future;
+
+use crate::{AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture};
+
+#[cfg(feature = "aws-kms")]
+mod aws;
+#[cfg(feature = "gcp-kms")]
+mod gcp;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+
+#[cfg(feature = "aws-kms")]
+pub use self::aws::AwsKmsSigner;
+#[cfg(feature = "gcp-kms")]
+pub use self::gcp::GcpKmsSigner;
+#[cfg(feature = "pkcs11")]
+pub use self::pkcs11::Pkcs11Signer;
+
+/// The signature scheme of a [`DigestSignRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureScheme {
+    /// RSASSA-PKCS1-v1_5.
+    RsaPkcs1,
+    /// RSASSA-PSS, with MGF1 using the digest algorithm of the request, and a salt as long as
+    /// the digest.
+    RsaPss,
+    /// ECDSA, with a DER-encoded signature.
+    Ecdsa,
+}
+
+/// The digest algorithm of a [`DigestSignRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DigestAlgorithm {
+    /// SHA-256.
+    Sha256,
+    /// SHA-384.
+    Sha384,
+    /// SHA-512.
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    /// Returns the corresponding [`MessageDigest`].
+    pub fn message_digest(self) -> MessageDigest {
+        match self {
+            DigestAlgorithm::Sha256 => MessageDigest::sha256(),
+            DigestAlgorithm::Sha384 => MessageDigest::sha384(),
+            DigestAlgorithm::Sha512 => MessageDigest::sha512(),
+        }
+    }
+}
+
+/// A handshake signing request, in the form taken by remote signers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestSignRequest {
+    scheme: SignatureScheme,
+    digest_algorithm: DigestAlgorithm,
+    message: Vec<u8>,
+    digest: Vec<u8>,
+}
+
+impl DigestSignRequest {
+    /// Creates a request signing `input` with `signature_algorithm`.
+    ///
+    /// Fails if the signature algorithm is not supported.
+    pub fn new(
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+    ) -> Result<Self, AsyncPrivateKeyMethodError> {
+        let (scheme, digest_algorithm) = match signature_algorithm {
+            SslSignatureAlgorithm::RSA_PKCS1_SHA256 => {
+                (SignatureScheme::RsaPkcs1, DigestAlgorithm::Sha256)
+            }
+            SslSignatureAlgorithm::RSA_PKCS1_SHA384 => {
+                (SignatureScheme::RsaPkcs1, DigestAlgorithm::Sha384)
+            }
+            SslSignatureAlgorithm::RSA_PKCS1_SHA512 => {
+                (SignatureScheme::RsaPkcs1, DigestAlgorithm::Sha512)
+            }
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256 => {
+                (SignatureScheme::RsaPss, DigestAlgorithm::Sha256)
+            }
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA384 => {
+                (SignatureScheme::RsaPss, DigestAlgorithm::Sha384)
+            }
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA512 => {
+                (SignatureScheme::RsaPss, DigestAlgorithm::Sha512)
+            }
+            SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256 => {
+                (SignatureScheme::Ecdsa, DigestAlgorithm::Sha256)
+            }
+            SslSignatureAlgorithm::ECDSA_SECP384R1_SHA384 => {
+                (SignatureScheme::Ecdsa, DigestAlgorithm::Sha384)
+            }
+            SslSignatureAlgorithm::ECDSA_SECP521R1_SHA512 => {
+                (SignatureScheme::Ecdsa, DigestAlgorithm::Sha512)
+            }
+            _ => {
+                return Err(AsyncPrivateKeyMethodError::new(
+                    "the signature algorithm is not supported by remote signers",
+                ))
+            }
+        };
+
+        let digest = hash(digest_algorithm.message_digest(), input)
+            .map_err(|e| AsyncPrivateKeyMethodError::new(e.to_string()))?;
+
+        Ok(Self {
+            scheme,
+            digest_algorithm,
+            message: input.to_vec(),
+            digest: digest.to_vec(),
+        })
+    }
+
+    /// Returns the signature scheme to sign with.
+    pub fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+
+    /// Returns the algorithm of [`DigestSignRequest::digest`].
+    pub fn digest_algorithm(&self) -> DigestAlgorithm {
+        self.digest_algorithm
+    }
+
+    /// Returns the message to sign, for signers hashing it themselves.
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Returns the digest of the message to sign.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+}
+
+/// Runs `signature` on its own task, so that backends whose futures are not `Sync` can be stored
+/// by the handshake, and writes its output once it completes.
+#[cfg_attr(
+    not(any(feature = "aws-kms", feature = "gcp-kms", feature = "pkcs11")),
+    allow(dead_code)
+)]
+fn sign_with<F>(signature: F) -> BoxPrivateKeyMethodFuture
+where
+    F: Future<Output = Result<Vec<u8>, AsyncPrivateKeyMethodError>> + Send + 'static,
+{
+    let task = tokio::spawn(signature);
+
+    Box::pin(async move {
+        let signature = task
+            .await
+            .map_err(|_| AsyncPrivateKeyMethodError::new("the signing task panicked"))??;
+
+        Ok(Box::new(move |_: &mut SslRef, output: &mut [u8]| {
+            let output = output
+                .get_mut(..signature.len())
+                .ok_or_else(|| AsyncPrivateKeyMethodError::new("the signature is too large"))?;
+            output.copy_from_slice(&signature);
+
+            Ok(signature.len())
+        }) as Box<_>)
+    })
+}
+
+#[cfg_attr(
+    not(any(feature = "aws-kms", feature = "gcp-kms", feature = "pkcs11")),
+    allow(dead_code)
+)]
+fn decryption_unsupported() -> AsyncPrivateKeyMethodError {
+    AsyncPrivateKeyMethodError::new("decryption is not supported by remote signers")
+}
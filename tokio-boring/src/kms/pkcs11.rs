@@ -0,0 +1,55 @@
+use boring::ssl::{Pkcs11PrivateKey, SslRef, SslSignatureAlgorithm};
+use std::sync::Arc;
+
+use super::{decryption_unsupported, sign_with};
+use crate::{AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture};
+
+/// An [`AsyncPrivateKeyMethod`] signing with a private key of a PKCS#11 token.
+///
+/// This wraps a [`Pkcs11PrivateKey`], whose blocking calls run on tokio's blocking thread pool.
+/// Concurrent handshakes share its sessions, up to [`Pkcs11Config::max_sessions`].
+///
+/// [`Pkcs11Config::max_sessions`]: boring::ssl::Pkcs11Config::max_sessions
+#[derive(Clone)]
+pub struct Pkcs11Signer {
+    key: Arc<Pkcs11PrivateKey>,
+}
+
+impl Pkcs11Signer {
+    /// Creates a signer using the private key `key`.
+    pub fn new(key: Pkcs11PrivateKey) -> Self {
+        Self { key: Arc::new(key) }
+    }
+}
+
+impl AsyncPrivateKeyMethod for Pkcs11Signer {
+    fn sign(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let key = self.key.clone();
+        let input = input.to_vec();
+
+        let task = tokio::task::spawn_blocking(move || {
+            key.sign_to_vec(&input, signature_algorithm)
+                .map_err(|e| AsyncPrivateKeyMethodError::new(e.to_string()))
+        });
+
+        Ok(sign_with(async move {
+            task.await
+                .map_err(|_| AsyncPrivateKeyMethodError::new("the signing task panicked"))?
+        }))
+    }
+
+    fn decrypt(
+        &self,
+        _: &mut SslRef,
+        _: &[u8],
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        Err(decryption_unsupported())
+    }
+}
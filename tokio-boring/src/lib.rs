@@ -10,6 +10,20 @@
 //! be used by servers, and `connect` by clients. These augment the functionality provided by the
 //! [`boring`] crate, on which this crate is built. Configuration of TLS parameters is still
 //! primarily done through the [`boring`] crate.
+//!
+//! DTLS handshakes over UDP, which must retransmit lost messages, are supported by the [`dtls`]
+//! module.
+//!
+//! Connections upgraded to TLS after a plaintext exchange, as with `STARTTLS`, are supported by
+//! the [`starttls`] module.
+//!
+//! Signing requests of concurrent server handshakes can be coalesced into batches for an HSM or a
+//! KMS with the [`batch`] module, and handshake signatures with keys held in memory can be moved
+//! off the reactor with the [`offload`] module. Keys held by AWS KMS, Google Cloud KMS or a PKCS#11
+//! token sign handshakes with the backends of the [`kms`] module.
+//!
+//! Certificates approaching their expiry can be reported and renewed without restarting the
+//! server with the [`monitor`] module.
 #![warn(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
@@ -19,24 +33,39 @@ use boring::ssl::{
     SslRef,
 };
 use boring_sys as ffi;
+use std::cmp;
 use std::error::Error;
 use std::fmt;
 use std::future::Future;
 use std::io::{self, Read, Write};
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::task::{ready, Context, Poll};
+use std::time::Instant;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 mod async_callbacks;
+pub mod batch;
 mod bridge;
+pub mod dtls;
+pub mod kms;
+pub mod monitor;
+pub mod offload;
+mod pool;
+mod record_sizing;
+pub mod starttls;
 
 use self::async_callbacks::TASK_WAKER_INDEX;
 pub use self::async_callbacks::{
-    AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, AsyncSelectCertError,
-    BoxPrivateKeyMethodFinish, BoxPrivateKeyMethodFuture, BoxSelectCertFinish, BoxSelectCertFuture,
-    ExDataFuture, SslContextBuilderExt,
+    AsyncClientCertError, AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, AsyncSelectCertChain,
+    AsyncSelectCertError, AsyncSelectCertOutcome, AsyncServernameError, BoxClientCertFuture,
+    BoxPrivateKeyMethodFinish, BoxPrivateKeyMethodFuture, BoxSelectCertChainFuture,
+    BoxSelectCertFinish, BoxSelectCertFuture, BoxServernameFuture, CancellationToken,
+    ClientCertRequest, ExDataFuture, IdentityExt, SslContextBuilderExt,
 };
 use self::bridge::AsyncStreamBridge;
+pub use self::pool::BufferPool;
+pub use self::record_sizing::DynamicRecordSizing;
+use self::record_sizing::RecordSizer;
 
 /// Asynchronously performs a client-side TLS handshake over the provided stream.
 pub async fn connect<S>(
@@ -81,6 +110,14 @@ fn cvt<T>(r: io::Result<T>) -> Poll<io::Result<T>> {
     }
 }
 
+fn into_io_error(e: ssl::Error) -> io::Error {
+    e.into_io_error()
+        .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// The largest amount of plaintext passed to BoringSSL in one write, that of a single TLS record.
+pub(crate) const MAX_WRITE_SIZE: usize = 16 * 1024;
+
 /// A wrapper around an underlying raw stream which implements the SSL
 /// protocol.
 ///
@@ -88,48 +125,115 @@ fn cvt<T>(r: io::Result<T>) -> Poll<io::Result<T>> {
 /// and both the server and the client are ready for receiving and sending
 /// data. Bytes read from a `SslStream` are decrypted from `S` and bytes written
 /// to a `SslStream` are encrypted when passing through to `S`.
+///
+/// Writes are accepted as soon as BoringSSL has encrypted them, even if the transport could not
+/// take the resulting records yet. Those are written before any further write, and
+/// [`poll_flush`] returns once all records, including handshake messages generated while
+/// reading and `KeyUpdate` messages, were written to the transport and the transport flushed.
+///
+/// [`poll_flush`]: AsyncWrite::poll_flush
 #[derive(Debug)]
-pub struct SslStream<S>(ssl::SslStream<AsyncStreamBridge<S>>);
+pub struct SslStream<S> {
+    inner: ssl::SslStream<AsyncStreamBridge<S>>,
+    /// Plaintext accepted by `poll_write` whose records BoringSSL could not write yet, and which
+    /// must be passed to it again to finish writing them.
+    pending_write: Vec<u8>,
+    buffer_pool: Option<BufferPool>,
+    record_sizer: Option<RecordSizer>,
+}
 
 impl<S> SslStream<S> {
     /// Returns a shared reference to the `Ssl` object associated with this stream.
     pub fn ssl(&self) -> &SslRef {
-        self.0.ssl()
+        self.inner.ssl()
     }
 
     /// Returns a mutable reference to the `Ssl` object associated with this stream.
     pub fn ssl_mut(&mut self) -> &mut SslRef {
-        self.0.ssl_mut()
+        self.inner.ssl_mut()
     }
 
     /// Returns a shared reference to the underlying stream.
     pub fn get_ref(&self) -> &S {
-        &self.0.get_ref().stream
+        &self.inner.get_ref().stream
     }
 
     /// Returns a mutable reference to the underlying stream.
     pub fn get_mut(&mut self) -> &mut S {
-        &mut self.0.get_mut().stream
+        &mut self.inner.get_mut().stream
     }
 
-    fn run_in_context<F, R>(&mut self, ctx: &mut Context<'_>, f: F) -> R
+    /// Returns a pinned mutable reference to the underlying stream.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S>
     where
-        F: FnOnce(&mut ssl::SslStream<AsyncStreamBridge<S>>) -> R,
+        S: Unpin,
     {
-        self.0.get_mut().set_waker(Some(ctx));
+        Pin::new(Pin::into_inner(self).get_mut())
+    }
 
-        let result = f(&mut self.0);
+    /// Sets the pool from which the stream takes the buffer holding a write until the transport
+    /// took its records.
+    ///
+    /// Without a pool, the buffer is allocated when a write blocks and freed once it completed.
+    pub fn set_buffer_pool(&mut self, pool: BufferPool) {
+        self.buffer_pool = Some(pool);
+    }
 
-        // NOTE(nox): This should also be executed when `f` panics,
-        // but it's not that important as boring segfaults on panics
-        // and we always set the context prior to doing anything with
-        // the inner async stream.
-        self.0.get_mut().set_waker(None);
+    /// Enables dynamic record sizing, starting with small records after idle periods.
+    ///
+    /// By default, full records are written. The size of all records can otherwise be limited
+    /// with [`SslRef::set_max_send_fragment`].
+    pub fn set_dynamic_record_sizing(&mut self, policy: DynamicRecordSizing) {
+        self.record_sizer = Some(RecordSizer::new(policy));
+    }
+
+    /// Detaches BoringSSL from the underlying stream, returning it along with the application
+    /// data which was decrypted but not read yet.
+    ///
+    /// BoringSSL does not read past the end of the TLS record it is processing, so the bytes
+    /// the peer sent after its last record, e.g. after its `close_notify` alert, are left unread
+    /// in the returned stream. This allows protocol upgrades and downgrades to continue on the
+    /// same transport.
+    ///
+    /// Written data whose records were not written to the transport yet is lost, so the stream
+    /// should be flushed first.
+    pub fn into_inner(self) -> (S, Vec<u8>) {
+        let (bridge, buffered) = self.inner.into_parts();
+
+        (bridge.stream, buffered)
+    }
 
-        result
+    fn new(inner: ssl::SslStream<AsyncStreamBridge<S>>) -> Self {
+        Self {
+            inner,
+            pending_write: vec![],
+            buffer_pool: None,
+            record_sizer: None,
+        }
     }
 }
 
+fn run_in_context<S, F, R>(
+    stream: &mut ssl::SslStream<AsyncStreamBridge<S>>,
+    ctx: &mut Context<'_>,
+    f: F,
+) -> R
+where
+    F: FnOnce(&mut ssl::SslStream<AsyncStreamBridge<S>>) -> R,
+{
+    stream.get_mut().set_waker(Some(ctx));
+
+    let result = f(stream);
+
+    // NOTE(nox): This should also be executed when `f` panics,
+    // but it's not that important as boring segfaults on panics
+    // and we always set the context prior to doing anything with
+    // the inner async stream.
+    stream.get_mut().set_waker(None);
+
+    result
+}
+
 impl<S> SslStream<S>
 where
     S: AsyncRead + AsyncWrite + Unpin,
@@ -142,11 +246,35 @@ where
     ///
     /// The caller must ensure the pointer is valid.
     pub unsafe fn from_raw_parts(ssl: *mut ffi::SSL, stream: S) -> Self {
-        Self(ssl::SslStream::from_raw_parts(
+        Self::new(ssl::SslStream::from_raw_parts(
             ssl,
             AsyncStreamBridge::new(stream),
         ))
     }
+
+    /// Writes the records of the pending write, then any other record BoringSSL generated but
+    /// could not write to the transport yet.
+    fn poll_flush_records(&mut self, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let pending_write = &mut self.pending_write;
+        let buffer_pool = self.buffer_pool.as_ref();
+
+        run_in_context(&mut self.inner, ctx, |s| {
+            if !pending_write.is_empty() {
+                while !pending_write.is_empty() {
+                    match ready!(cvt(s.write(&pending_write[..])))? {
+                        0 => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                        n => {
+                            pending_write.drain(..n);
+                        }
+                    }
+                }
+
+                pool::release(pending_write, buffer_pool);
+            }
+
+            cvt(s.flush_records().map_err(into_io_error))
+        })
+    }
 }
 
 impl<S> AsyncRead for SslStream<S>
@@ -158,7 +286,7 @@ where
         ctx: &mut Context<'_>,
         buf: &mut ReadBuf,
     ) -> Poll<io::Result<()>> {
-        self.run_in_context(ctx, |s| {
+        run_in_context(&mut self.inner, ctx, |s| {
             // This isn't really "proper", but rust-openssl doesn't currently expose a suitable interface even though
             // OpenSSL itself doesn't require the buffer to be initialized. So this is good enough for now.
             let slice = unsafe {
@@ -188,28 +316,66 @@ where
         ctx: &mut Context,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        self.run_in_context(ctx, |s| cvt(s.write(buf)))
+        // Records of earlier writes are written first, so that a slow transport pushes back.
+        ready!(self.poll_flush_records(ctx))?;
+
+        let now = Instant::now();
+        let max_size = match &mut self.record_sizer {
+            Some(sizer) => sizer.next_record_size(now),
+            None => MAX_WRITE_SIZE,
+        };
+        let buf = &buf[..cmp::min(buf.len(), max_size)];
+
+        let result = run_in_context(&mut self.inner, ctx, |s| loop {
+            match s.ssl_write(buf) {
+                Err(ref e) if e.code() == ErrorCode::WANT_READ && e.io_error().is_none() => {}
+                result => return result,
+            }
+        });
+
+        let written = match result {
+            Ok(n) => n,
+            // BoringSSL may already have encrypted `buf`, and must be passed it again to finish
+            // writing its records, so it is kept until then rather than left to the caller.
+            Err(ref e) if e.code() == ErrorCode::WANT_WRITE => {
+                if let Some(pool) = &self.buffer_pool {
+                    self.pending_write = pool.take();
+                }
+                self.pending_write.extend_from_slice(buf);
+                buf.len()
+            }
+            Err(ref e) if e.code() == ErrorCode::WANT_READ => return Poll::Pending,
+            Err(e) => return Poll::Ready(Err(into_io_error(e))),
+        };
+
+        if let Some(sizer) = &mut self.record_sizer {
+            if written > 0 {
+                sizer.record_written(now);
+            }
+        }
+
+        Poll::Ready(Ok(written))
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, ctx: &mut Context) -> Poll<io::Result<()>> {
-        self.run_in_context(ctx, |s| cvt(s.flush()))
+        ready!(self.poll_flush_records(ctx))?;
+
+        run_in_context(&mut self.inner, ctx, |s| cvt(s.get_mut().flush()))
     }
 
     fn poll_shutdown(mut self: Pin<&mut Self>, ctx: &mut Context) -> Poll<io::Result<()>> {
-        match self.run_in_context(ctx, |s| s.shutdown()) {
+        ready!(self.poll_flush_records(ctx))?;
+
+        match run_in_context(&mut self.inner, ctx, |s| s.shutdown()) {
             Ok(ShutdownResult::Sent) | Ok(ShutdownResult::Received) => {}
             Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => {}
             Err(ref e) if e.code() == ErrorCode::WANT_READ || e.code() == ErrorCode::WANT_WRITE => {
                 return Poll::Pending;
             }
-            Err(e) => {
-                return Poll::Ready(Err(e
-                    .into_io_error()
-                    .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e))));
-            }
+            Err(e) => return Poll::Ready(Err(into_io_error(e))),
         }
 
-        Pin::new(&mut self.0.get_mut().stream).poll_shutdown(ctx)
+        Pin::new(&mut self.inner.get_mut().stream).poll_shutdown(ctx)
     }
 }
 
@@ -256,6 +422,13 @@ impl<S> HandshakeError<S> {
             _ => None,
         }
     }
+
+    /// Returns the reason given by the private key method which failed the handshake, if any.
+    ///
+    /// See [`AsyncPrivateKeyMethodError::new`].
+    pub fn private_key_failure_reason(&self) -> Option<&str> {
+        self.0.private_key_failure_reason()
+    }
 }
 
 impl<S> fmt::Debug for HandshakeError<S>
@@ -306,7 +479,7 @@ where
                 stream.get_mut().set_waker(None);
                 stream.ssl_mut().set_ex_data(*TASK_WAKER_INDEX, None);
 
-                Poll::Ready(Ok(SslStream(stream)))
+                Poll::Ready(Ok(SslStream::new(stream)))
             }
             Err(ssl::HandshakeError::WouldBlock(mut mid_handshake)) => {
                 mid_handshake.get_mut().set_waker(None);
@@ -0,0 +1,467 @@
+//! Certificate expiry monitoring and renewal.
+//!
+//! A [`CertMonitor`] keeps the identities served by an acceptor, reports those getting close to
+//! their expiry as [`CertEvent`]s, and renews them with a callback, typically requesting a new
+//! certificate from an ACME server or an internal CA. Renewed identities are swapped into a
+//! [`ReloadableAcceptor`] whose configuration function reads them from the monitor, so that new
+//! handshakes use them while established connections are left alone.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boring::ssl::{Identity, ReloadableAcceptor, SslAcceptor, SslMethod};
+//! use std::time::Duration;
+//! use tokio_boring::monitor::{CertMonitor, CertRenewalError, ExpiryPolicy};
+//!
+//! # async fn request_certificate(_: &str) -> Result<Identity, CertRenewalError> { todo!() }
+//! # async fn run() {
+//! let identity = Identity::from_pem(
+//!     &std::fs::read("chain.pem").unwrap(),
+//!     &std::fs::read("key.pem").unwrap(),
+//! )
+//! .unwrap();
+//!
+//! let policy = ExpiryPolicy::new().renew_before(Duration::from_secs(14 * 86400));
+//! let monitor = CertMonitor::new(policy);
+//! monitor.insert("example.com", identity);
+//! monitor.set_renewal_callback(|name, _| {
+//!     let name = name.to_owned();
+//!     Box::pin(async move { request_certificate(&name).await })
+//! });
+//!
+//! let acceptor = ReloadableAcceptor::new({
+//!     let monitor = monitor.clone();
+//!
+//!     move || {
+//!         let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+//!         acceptor.set_identity(&monitor.identity("example.com").unwrap())?;
+//!         Ok(acceptor.build())
+//!     }
+//! })
+//! .unwrap();
+//!
+//! let mut events = monitor.subscribe();
+//! let _task = monitor.watch(acceptor.clone());
+//!
+//! while let Ok(event) = events.recv().await {
+//!     eprintln!("{:?}", event);
+//! }
+//! # }
+//! ```
+use boring::asn1::{Asn1Time, Asn1TimeRef};
+use boring::error::ErrorStack;
+use boring::ssl::{Identity, ReloadableAcceptor};
+use boring::x509::X509Ref;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// The type of futures returned by renewal callbacks.
+pub type BoxRenewFuture = Pin<Box<dyn Future<Output = Result<Identity, CertRenewalError>> + Send>>;
+
+type RenewalCallback = dyn Fn(&str, &Identity) -> BoxRenewFuture + Send + Sync;
+
+/// When a [`CertMonitor`] reports and renews identities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiryPolicy {
+    thresholds: Vec<Duration>,
+    renew_before: Duration,
+    check_interval: Duration,
+}
+
+impl Default for ExpiryPolicy {
+    fn default() -> ExpiryPolicy {
+        ExpiryPolicy::new()
+    }
+}
+
+impl ExpiryPolicy {
+    /// Creates a policy reporting identities expiring within 30, 7 and 1 days, renewing them 30
+    /// days before they expire, and checking them every hour.
+    pub fn new() -> ExpiryPolicy {
+        ExpiryPolicy {
+            thresholds: vec![
+                Duration::from_secs(30 * DAY),
+                Duration::from_secs(7 * DAY),
+                Duration::from_secs(DAY),
+            ],
+            renew_before: Duration::from_secs(30 * DAY),
+            check_interval: Duration::from_secs(60 * 60),
+        }
+    }
+
+    /// Sets the times left before expiry at which [`CertEvent::ExpiresSoon`] is reported.
+    pub fn thresholds<I>(mut self, thresholds: I) -> ExpiryPolicy
+    where
+        I: IntoIterator<Item = Duration>,
+    {
+        self.thresholds = thresholds.into_iter().collect();
+        self.thresholds.sort_unstable_by(|a, b| b.cmp(a));
+        self.thresholds.dedup();
+        self
+    }
+
+    /// Sets the time left before expiry from which identities are renewed.
+    pub fn renew_before(mut self, renew_before: Duration) -> ExpiryPolicy {
+        self.renew_before = renew_before;
+        self
+    }
+
+    /// Sets how often [`CertMonitor::watch`] checks identities.
+    pub fn check_interval(mut self, check_interval: Duration) -> ExpiryPolicy {
+        self.check_interval = check_interval;
+        self
+    }
+}
+
+/// An event reported by a [`CertMonitor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CertEvent {
+    /// The identity crossed one of the thresholds of the policy.
+    ExpiresSoon {
+        /// The name of the identity.
+        name: String,
+        /// The time left before its leaf certificate expires.
+        remaining: Duration,
+    },
+    /// The leaf certificate of the identity expired.
+    Expired {
+        /// The name of the identity.
+        name: String,
+    },
+    /// The identity was renewed, and swapped into the acceptor.
+    Renewed {
+        /// The name of the identity.
+        name: String,
+    },
+    /// The identity could not be renewed. Renewal is retried on the next check.
+    RenewalFailed {
+        /// The name of the identity.
+        name: String,
+        /// The reason of the failure.
+        error: CertRenewalError,
+    },
+    /// The time left before the identity expires could not be computed. It is checked again on
+    /// the next check.
+    CheckFailed {
+        /// The name of the identity.
+        name: String,
+        /// The reason of the failure.
+        reason: String,
+    },
+}
+
+/// An error returned by renewal callbacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertRenewalError {
+    reason: Cow<'static, str>,
+}
+
+impl CertRenewalError {
+    /// Returns an error for the given reason, such as a rejected certificate request.
+    pub fn new(reason: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+
+    /// Returns the reason of the error.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for CertRenewalError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "the certificate renewal failed: {}", self.reason)
+    }
+}
+
+impl std::error::Error for CertRenewalError {}
+
+/// Identities tracked for expiry, and the callback renewing them.
+///
+/// Cloning a `CertMonitor` yields a handle to the same identities.
+#[derive(Clone)]
+pub struct CertMonitor(Arc<Inner>);
+
+struct Inner {
+    policy: ExpiryPolicy,
+    entries: RwLock<BTreeMap<String, Entry>>,
+    renew: RwLock<Option<Arc<RenewalCallback>>>,
+    events: broadcast::Sender<CertEvent>,
+}
+
+struct Entry {
+    identity: Identity,
+    thresholds_crossed: usize,
+    expired: bool,
+}
+
+impl Entry {
+    fn new(identity: Identity) -> Entry {
+        Entry {
+            identity,
+            thresholds_crossed: 0,
+            expired: false,
+        }
+    }
+}
+
+impl CertMonitor {
+    /// Creates a monitor without identities.
+    pub fn new(policy: ExpiryPolicy) -> Self {
+        let (events, _) = broadcast::channel(64);
+
+        Self(Arc::new(Inner {
+            policy,
+            entries: RwLock::new(BTreeMap::new()),
+            renew: RwLock::new(None),
+            events,
+        }))
+    }
+
+    /// Starts tracking an identity under `name`, replacing the one tracked under that name, if
+    /// any.
+    pub fn insert(&self, name: impl Into<String>, identity: Identity) {
+        let mut entries = self.0.entries.write().unwrap();
+        entries.insert(name.into(), Entry::new(identity));
+    }
+
+    /// Stops tracking the identity tracked under `name`, returning it.
+    pub fn remove(&self, name: &str) -> Option<Identity> {
+        let mut entries = self.0.entries.write().unwrap();
+        entries.remove(name).map(|entry| entry.identity)
+    }
+
+    /// Returns the identity currently tracked under `name`.
+    pub fn identity(&self, name: &str) -> Option<Identity> {
+        let entries = self.0.entries.read().unwrap();
+        entries.get(name).map(|entry| entry.identity.clone())
+    }
+
+    /// Sets the callback renewing identities, passed the name and the current identity.
+    ///
+    /// Without a callback, identities are only reported.
+    pub fn set_renewal_callback<F>(&self, callback: F)
+    where
+        F: Fn(&str, &Identity) -> BoxRenewFuture + Send + Sync + 'static,
+    {
+        *self.0.renew.write().unwrap() = Some(Arc::new(callback));
+    }
+
+    /// Returns a receiver of the events reported from now on.
+    ///
+    /// Receivers lagging more than 64 events behind miss the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<CertEvent> {
+        self.0.events.subscribe()
+    }
+
+    /// Checks every identity, reporting those crossing a threshold and renewing those within
+    /// [`ExpiryPolicy::renew_before`] of their expiry.
+    ///
+    /// Renewed identities replace the tracked ones and `acceptor` is reloaded. If the reload
+    /// fails, the previous identity is restored and [`CertEvent::RenewalFailed`] is reported.
+    ///
+    /// Identities whose expiry cannot be computed are reported with [`CertEvent::CheckFailed`],
+    /// and the others are still checked.
+    pub async fn check(&self, acceptor: &ReloadableAcceptor) -> Result<(), ErrorStack> {
+        self.check_at(acceptor, SystemTime::now()).await
+    }
+
+    /// Like [`CertMonitor::check`], but computes the time left before expiry from `now`.
+    pub async fn check_at(
+        &self,
+        acceptor: &ReloadableAcceptor,
+        now: SystemTime,
+    ) -> Result<(), ErrorStack> {
+        let secs = now
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs());
+        let now = Asn1Time::from_unix(secs as _)?;
+
+        let mut renewals = vec![];
+        {
+            let mut entries = self.0.entries.write().unwrap();
+
+            for (name, entry) in entries.iter_mut() {
+                let remaining = match remaining(entry.identity.certificate(), &now) {
+                    Ok(remaining) => remaining,
+                    Err(e) => {
+                        self.send(CertEvent::CheckFailed {
+                            name: name.clone(),
+                            reason: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                self.report(name, entry, remaining);
+
+                if remaining.is_none_or(|remaining| remaining <= self.0.policy.renew_before) {
+                    renewals.push((name.clone(), entry.identity.clone()));
+                }
+            }
+        }
+
+        let renew = self.0.renew.read().unwrap().clone();
+        if let Some(renew) = renew {
+            for (name, identity) in renewals {
+                self.renew(acceptor, &name, &identity, &*renew).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the identities every [`ExpiryPolicy::check_interval`], starting immediately, on a
+    /// task spawned on the current tokio runtime.
+    ///
+    /// The task runs until the returned [`CertMonitorTask`] is dropped.
+    pub fn watch(&self, acceptor: ReloadableAcceptor) -> CertMonitorTask {
+        let monitor = self.clone();
+
+        CertMonitorTask(tokio::spawn(async move {
+            let mut interval = time::interval(monitor.0.policy.check_interval);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+
+                // Failures are reported per identity; only computing the current time can fail
+                // here, and it is retried on the next check.
+                let _ = monitor.check(&acceptor).await;
+            }
+        }))
+    }
+
+    fn report(&self, name: &str, entry: &mut Entry, remaining: Option<Duration>) {
+        let remaining = match remaining {
+            Some(remaining) => remaining,
+            None => {
+                if !entry.expired {
+                    entry.expired = true;
+                    self.send(CertEvent::Expired {
+                        name: name.to_owned(),
+                    });
+                }
+                return;
+            }
+        };
+
+        let crossed = self
+            .0
+            .policy
+            .thresholds
+            .iter()
+            .filter(|&&threshold| remaining <= threshold)
+            .count();
+
+        if crossed > entry.thresholds_crossed {
+            entry.thresholds_crossed = crossed;
+            self.send(CertEvent::ExpiresSoon {
+                name: name.to_owned(),
+                remaining,
+            });
+        }
+    }
+
+    async fn renew(
+        &self,
+        acceptor: &ReloadableAcceptor,
+        name: &str,
+        current: &Identity,
+        renew: &RenewalCallback,
+    ) {
+        let event = match renew(name, current).await {
+            Ok(renewed) => match self.swap(acceptor, name, renewed) {
+                Ok(()) => CertEvent::Renewed {
+                    name: name.to_owned(),
+                },
+                Err(e) => CertEvent::RenewalFailed {
+                    name: name.to_owned(),
+                    error: CertRenewalError::new(e.to_string()),
+                },
+            },
+            Err(error) => CertEvent::RenewalFailed {
+                name: name.to_owned(),
+                error,
+            },
+        };
+
+        self.send(event);
+    }
+
+    fn swap(
+        &self,
+        acceptor: &ReloadableAcceptor,
+        name: &str,
+        renewed: Identity,
+    ) -> Result<(), ErrorStack> {
+        let previous = {
+            let mut entries = self.0.entries.write().unwrap();
+            match entries.get_mut(name) {
+                Some(entry) => std::mem::replace(entry, Entry::new(renewed)),
+                // The identity was removed while it was being renewed.
+                None => return Ok(()),
+            }
+        };
+
+        acceptor.reload().map_err(|e| {
+            let mut entries = self.0.entries.write().unwrap();
+            if let Some(entry) = entries.get_mut(name) {
+                *entry = previous;
+            }
+
+            e
+        })
+    }
+
+    fn send(&self, event: CertEvent) {
+        // Events are dropped when nobody subscribed.
+        let _ = self.0.events.send(event);
+    }
+}
+
+impl fmt::Debug for CertMonitor {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.0.entries.read().unwrap();
+
+        fmt.debug_struct("CertMonitor")
+            .field("policy", &self.0.policy)
+            .field("identities", &entries.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A task checking the identities of a [`CertMonitor`], started with [`CertMonitor::watch`].
+///
+/// Dropping the task stops it.
+#[derive(Debug)]
+pub struct CertMonitorTask(JoinHandle<()>);
+
+impl Drop for CertMonitorTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Returns the time left before `cert` expires, or `None` if it expired.
+fn remaining(cert: &X509Ref, now: &Asn1TimeRef) -> Result<Option<Duration>, ErrorStack> {
+    let diff = now.diff(cert.not_after())?;
+    let secs = i64::from(diff.days) * DAY as i64 + i64::from(diff.secs);
+
+    Ok(u64::try_from(secs)
+        .ok()
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs))
+}
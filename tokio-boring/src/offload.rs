@@ -0,0 +1,178 @@
+//! Offloading handshake signatures from the tokio reactor.
+//!
+//! The signature of a server handshake is its most expensive operation, and by default it runs on
+//! the task driving the handshake, stalling every other task of its worker thread. Under a
+//! handshake storm, this starves established connections. [`LocalKeyAsyncSigner`] is an
+//! [`AsyncPrivateKeyMethod`] signing with a key held in memory on tokio's blocking thread pool,
+//! or on the blocking pool of a dedicated runtime, while the handshake task yields.
+//!
+//! Only private key operations are offloaded: BoringSSL generates key shares synchronously, with
+//! no hook to run them elsewhere.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boring::pkey::PKey;
+//! use boring::ssl::{SslAcceptor, SslMethod};
+//! use tokio_boring::offload::LocalKeyAsyncSigner;
+//! use tokio_boring::SslContextBuilderExt;
+//!
+//! let key = PKey::private_key_from_pem(&std::fs::read("key.pem").unwrap()).unwrap();
+//!
+//! let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+//! acceptor.set_certificate_chain_file("cert.pem").unwrap();
+//! acceptor.set_async_private_key_method(LocalKeyAsyncSigner::new(key));
+//! ```
+use boring::error::ErrorStack;
+use boring::hash::MessageDigest;
+use boring::pkey::{PKey, PKeyRef, Private};
+use boring::rsa::Padding;
+use boring::sign::{RsaPssSaltlen, Signer};
+use boring::ssl::{SslRef, SslSignatureAlgorithm};
+use tokio::runtime::Handle;
+
+use crate::{AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture};
+
+/// An [`AsyncPrivateKeyMethod`] performing the private key operations of handshakes with a key
+/// held in memory, on a blocking thread pool.
+///
+/// Signatures use the RSA PKCS#1, RSA-PSS, ECDSA and Ed25519 algorithms. Decryption, which is
+/// only used by the RSA key exchange of TLS 1.2 and earlier, requires an RSA key.
+#[derive(Debug, Clone)]
+pub struct LocalKeyAsyncSigner {
+    key: PKey<Private>,
+    handle: Option<Handle>,
+}
+
+impl LocalKeyAsyncSigner {
+    /// Creates a signer running on the blocking thread pool of the runtime driving each
+    /// handshake.
+    pub fn new(key: PKey<Private>) -> Self {
+        Self { key, handle: None }
+    }
+
+    /// Creates a signer running on the blocking thread pool of the runtime of `handle`.
+    ///
+    /// A runtime dedicated to handshakes caps the threads they use, independently of the blocking
+    /// tasks of the rest of the application.
+    pub fn with_handle(key: PKey<Private>, handle: Handle) -> Self {
+        Self {
+            key,
+            handle: Some(handle),
+        }
+    }
+
+    fn offload<F>(&self, operation: F) -> BoxPrivateKeyMethodFuture
+    where
+        F: FnOnce(&PKeyRef<Private>) -> Result<Vec<u8>, ErrorStack> + Send + 'static,
+    {
+        let key = self.key.clone();
+        let operation = move || operation(&key);
+
+        let task = match &self.handle {
+            Some(handle) => handle.spawn_blocking(operation),
+            None => tokio::task::spawn_blocking(operation),
+        };
+
+        Box::pin(async move {
+            let output = task
+                .await
+                .map_err(|_| AsyncPrivateKeyMethodError::new("the offloaded operation panicked"))?
+                .map_err(|e| AsyncPrivateKeyMethodError::new(e.to_string()))?;
+
+            Ok(Box::new(move |_: &mut SslRef, buf: &mut [u8]| {
+                let buf = buf.get_mut(..output.len()).ok_or_else(|| {
+                    AsyncPrivateKeyMethodError::new("the output buffer is too small")
+                })?;
+                buf.copy_from_slice(&output);
+
+                Ok(output.len())
+            }) as Box<_>)
+        })
+    }
+}
+
+impl AsyncPrivateKeyMethod for LocalKeyAsyncSigner {
+    fn sign(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: SslSignatureAlgorithm,
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let params = SignatureParams::new(signature_algorithm).ok_or_else(|| {
+            AsyncPrivateKeyMethodError::new("the signature algorithm is not supported")
+        })?;
+        let input = input.to_vec();
+
+        Ok(self.offload(move |key| params.sign(key, &input)))
+    }
+
+    fn decrypt(
+        &self,
+        _: &mut SslRef,
+        input: &[u8],
+        _: &mut [u8],
+    ) -> Result<BoxPrivateKeyMethodFuture, AsyncPrivateKeyMethodError> {
+        let input = input.to_vec();
+
+        Ok(self.offload(move |key| {
+            let rsa = key.rsa()?;
+            let mut output = vec![0; rsa.size() as usize];
+            let len = rsa.private_decrypt(&input, &mut output, Padding::NONE)?;
+            output.truncate(len);
+
+            Ok(output)
+        }))
+    }
+}
+
+/// How a signature algorithm signs.
+struct SignatureParams {
+    digest: Option<MessageDigest>,
+    pss: bool,
+}
+
+impl SignatureParams {
+    fn new(signature_algorithm: SslSignatureAlgorithm) -> Option<Self> {
+        let (digest, pss) = match signature_algorithm {
+            SslSignatureAlgorithm::RSA_PKCS1_MD5_SHA1 => (Some(MessageDigest::md5_sha1()), false),
+            SslSignatureAlgorithm::RSA_PKCS1_SHA1 | SslSignatureAlgorithm::ECDSA_SHA1 => {
+                (Some(MessageDigest::sha1()), false)
+            }
+            SslSignatureAlgorithm::RSA_PKCS1_SHA256
+            | SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256 => {
+                (Some(MessageDigest::sha256()), false)
+            }
+            SslSignatureAlgorithm::RSA_PKCS1_SHA384
+            | SslSignatureAlgorithm::ECDSA_SECP384R1_SHA384 => {
+                (Some(MessageDigest::sha384()), false)
+            }
+            SslSignatureAlgorithm::RSA_PKCS1_SHA512
+            | SslSignatureAlgorithm::ECDSA_SECP521R1_SHA512 => {
+                (Some(MessageDigest::sha512()), false)
+            }
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256 => (Some(MessageDigest::sha256()), true),
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA384 => (Some(MessageDigest::sha384()), true),
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA512 => (Some(MessageDigest::sha512()), true),
+            SslSignatureAlgorithm::ED25519 => (None, false),
+            _ => return None,
+        };
+
+        Some(Self { digest, pss })
+    }
+
+    fn sign(&self, key: &PKeyRef<Private>, input: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut signer = match self.digest {
+            Some(digest) => Signer::new(digest, key)?,
+            None => Signer::new_without_digest(key)?,
+        };
+
+        if self.pss {
+            signer.set_rsa_padding(Padding::PKCS1_PSS)?;
+            signer.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+        }
+
+        signer.sign_oneshot_to_vec(input)
+    }
+}
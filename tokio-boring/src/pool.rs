@@ -0,0 +1,75 @@
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use crate::MAX_WRITE_SIZE;
+
+/// A pool of write buffers shared by streams.
+///
+/// An [`SslStream`] only needs a buffer while the transport cannot take the records of a write,
+/// and the buffers of idle streams are released, so servers with many mostly idle connections
+/// can share a small pool set with [`SslStream::set_buffer_pool`] rather than allocating and
+/// freeing a buffer each time a write blocks.
+///
+/// Cloning a pool returns a handle to the same buffers.
+///
+/// [`SslStream`]: crate::SslStream
+/// [`SslStream::set_buffer_pool`]: crate::SslStream::set_buffer_pool
+#[derive(Clone)]
+pub struct BufferPool(Arc<Inner>);
+
+struct Inner {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_idle: usize,
+}
+
+impl BufferPool {
+    /// Creates a pool keeping up to `max_idle` unused buffers for later use.
+    pub fn new(max_idle: usize) -> Self {
+        Self(Arc::new(Inner {
+            buffers: Mutex::new(vec![]),
+            max_idle,
+        }))
+    }
+
+    /// Returns the number of unused buffers kept by the pool.
+    pub fn idle(&self) -> usize {
+        self.0.buffers.lock().unwrap().len()
+    }
+
+    pub(crate) fn take(&self) -> Vec<u8> {
+        self.0
+            .buffers
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(|| Vec::with_capacity(MAX_WRITE_SIZE))
+    }
+
+    pub(crate) fn put(&self, mut buffer: Vec<u8>) {
+        buffer.clear();
+
+        let mut buffers = self.0.buffers.lock().unwrap();
+        if buffers.len() < self.0.max_idle {
+            buffers.push(buffer);
+        }
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BufferPool")
+            .field("idle", &self.idle())
+            .field("max_idle", &self.0.max_idle)
+            .finish()
+    }
+}
+
+/// Releases the buffer of a completed write, returning it to `pool` if any.
+pub(crate) fn release(buffer: &mut Vec<u8>, pool: Option<&BufferPool>) {
+    let buffer = mem::take(buffer);
+
+    if let Some(pool) = pool {
+        pool.put(buffer);
+    }
+}
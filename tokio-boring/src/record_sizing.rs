@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use crate::MAX_WRITE_SIZE;
+
+/// How an [`SslStream`] sizes the TLS records it writes, set with
+/// [`SslStream::set_dynamic_record_sizing`].
+///
+/// A client can only decrypt a record once it received all of it, so full 16 KiB records spread
+/// over many TCP segments delay the first bytes of a response while the congestion window is
+/// still small. With dynamic record sizing, a stream starts with records fitting a single TCP
+/// segment, for low latency, and switches to full records after a number of them, for
+/// throughput. It starts over with small records after being idle, as the congestion window
+/// shrinks again.
+///
+/// [`SslStream`]: crate::SslStream
+/// [`SslStream::set_dynamic_record_sizing`]: crate::SslStream::set_dynamic_record_sizing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicRecordSizing {
+    small_record_size: usize,
+    small_records: usize,
+    idle_timeout: Duration,
+}
+
+impl Default for DynamicRecordSizing {
+    fn default() -> DynamicRecordSizing {
+        DynamicRecordSizing::new()
+    }
+}
+
+impl DynamicRecordSizing {
+    /// Creates a policy writing 40 records of 1369 bytes of plaintext, which fit a TCP segment
+    /// on a path with a 1500 bytes MTU, before switching to full records, and starting over after
+    /// 1 second without writes.
+    pub fn new() -> DynamicRecordSizing {
+        DynamicRecordSizing {
+            small_record_size: 1369,
+            small_records: 40,
+            idle_timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the maximum amount of plaintext in small records.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `small_record_size` is zero.
+    pub fn small_record_size(mut self, small_record_size: usize) -> DynamicRecordSizing {
+        assert!(small_record_size > 0, "records must hold at least one byte");
+        self.small_record_size = small_record_size;
+        self
+    }
+
+    /// Sets the number of small records written before switching to full records.
+    pub fn small_records(mut self, small_records: usize) -> DynamicRecordSizing {
+        self.small_records = small_records;
+        self
+    }
+
+    /// Sets how long a stream must be idle to start over with small records.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> DynamicRecordSizing {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+}
+
+/// The state of the dynamic record sizing of a stream.
+#[derive(Debug)]
+pub(crate) struct RecordSizer {
+    policy: DynamicRecordSizing,
+    records: usize,
+    last_write: Option<Instant>,
+}
+
+impl RecordSizer {
+    pub(crate) fn new(policy: DynamicRecordSizing) -> RecordSizer {
+        RecordSizer {
+            policy,
+            records: 0,
+            last_write: None,
+        }
+    }
+
+    /// Returns the maximum amount of plaintext of the next record.
+    pub(crate) fn next_record_size(&mut self, now: Instant) -> usize {
+        if let Some(last_write) = self.last_write {
+            if now.saturating_duration_since(last_write) >= self.policy.idle_timeout {
+                self.records = 0;
+            }
+        }
+
+        if self.records < self.policy.small_records {
+            self.policy.small_record_size.min(MAX_WRITE_SIZE)
+        } else {
+            MAX_WRITE_SIZE
+        }
+    }
+
+    /// Records that a record was written.
+    pub(crate) fn record_written(&mut self, now: Instant) {
+        self.records = self.records.saturating_add(1);
+        self.last_write = Some(now);
+    }
+}
@@ -0,0 +1,156 @@
+//! Opportunistic TLS over an already used stream.
+//!
+//! Protocols such as SMTP, IMAP or PostgreSQL start in plaintext and upgrade the connection to TLS
+//! once both peers agreed to, e.g. after a `STARTTLS` command. A buffered reader used for the
+//! plaintext part of the protocol may then already have read the beginning of the handshake,
+//! such as a ClientHello sent by an eager client right after the command. [`connect`] and
+//! [`accept`] take these bytes back and feed them to the handshake before reading from the
+//! stream again.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use boring::ssl::SslAcceptor;
+//! use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+//! use tokio::net::TcpStream;
+//! use tokio_boring::starttls;
+//!
+//! # async fn run(acceptor: SslAcceptor, stream: TcpStream) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut reader = BufReader::new(stream);
+//!
+//! let mut line = String::new();
+//! reader.read_line(&mut line).await?;
+//! assert_eq!(line, "STARTTLS\r\n");
+//! reader.get_mut().write_all(b"220 Ready to start TLS\r\n").await?;
+//!
+//! let residual = reader.buffer().to_vec();
+//! let stream = starttls::accept(&acceptor, reader.into_inner(), &residual).await?;
+//! # Ok(())
+//! # }
+//! ```
+use boring::ssl::{ConnectConfiguration, SslAcceptor};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{HandshakeError, SslStream};
+
+/// A stream which yields bytes read ahead of time before reading from the underlying stream.
+#[derive(Debug)]
+pub struct StartTlsStream<S> {
+    residual: Vec<u8>,
+    pos: usize,
+    stream: S,
+}
+
+impl<S> StartTlsStream<S> {
+    /// Wraps a stream, to be read after `residual`.
+    pub fn new(stream: S, residual: &[u8]) -> Self {
+        Self {
+            residual: residual.to_vec(),
+            pos: 0,
+            stream,
+        }
+    }
+
+    /// Returns the bytes read ahead of time which have not been read yet.
+    pub fn residual(&self) -> &[u8] {
+        &self.residual[self.pos..]
+    }
+
+    /// Returns a shared reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the underlying stream, along with the bytes read ahead of time which have not
+    /// been read yet.
+    pub fn into_inner(mut self) -> (S, Vec<u8>) {
+        self.residual.drain(..self.pos);
+
+        (self.stream, self.residual)
+    }
+}
+
+impl<S> AsyncRead for StartTlsStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        let residual = &this.residual[this.pos..];
+        if residual.is_empty() {
+            return Pin::new(&mut this.stream).poll_read(ctx, buf);
+        }
+
+        let len = residual.len().min(buf.remaining());
+        buf.put_slice(&residual[..len]);
+        this.pos += len;
+
+        if this.pos == this.residual.len() {
+            this.residual = Vec::new();
+            this.pos = 0;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncWrite for StartTlsStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(ctx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(ctx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(ctx)
+    }
+}
+
+/// Asynchronously performs a client-side TLS handshake over a stream used for plaintext so far,
+/// reading `residual` before the stream.
+pub async fn connect<S>(
+    config: ConnectConfiguration,
+    domain: &str,
+    stream: S,
+    residual: &[u8],
+) -> Result<SslStream<StartTlsStream<S>>, HandshakeError<StartTlsStream<S>>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    crate::connect(config, domain, StartTlsStream::new(stream, residual)).await
+}
+
+/// Asynchronously performs a server-side TLS handshake over a stream used for plaintext so far,
+/// reading `residual` before the stream.
+pub async fn accept<S>(
+    acceptor: &SslAcceptor,
+    stream: S,
+    residual: &[u8],
+) -> Result<SslStream<StartTlsStream<S>>, HandshakeError<StartTlsStream<S>>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    crate::accept(acceptor, StartTlsStream::new(stream, residual)).await
+}
@@ -0,0 +1,103 @@
+use boring::ssl::{Identity, SslVerifyMode};
+use boring::x509::X509Name;
+use futures::future;
+use tokio::task::yield_now;
+use tokio_boring::{AsyncClientCertError, SslContextBuilderExt};
+
+mod common;
+
+use self::common::{connect, create_server};
+
+#[tokio::test]
+async fn test_async_client_cert_callback() {
+    let (stream, addr) = create_server(|builder| {
+        builder.set_ca_file("tests/cert.pem").unwrap();
+        builder.set_client_ca_list(X509Name::load_client_ca_file("tests/cert.pem").unwrap());
+        builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    });
+
+    let server = async {
+        let stream = stream.await.unwrap();
+        assert!(stream.ssl().peer_certificate().is_some());
+    };
+
+    let client = async {
+        connect(addr, |builder| {
+            builder.set_ca_file("tests/cert.pem")?;
+            builder.set_async_client_cert_callback(|_, request| {
+                assert_eq!(request.ca_names().len(), 1);
+                assert!(!request.signature_algorithms().is_empty());
+
+                Ok(Box::pin(async {
+                    yield_now().await;
+
+                    let identity =
+                        Identity::from_pem(include_bytes!("cert.pem"), include_bytes!("key.pem"))
+                            .unwrap();
+                    Ok(Some(identity))
+                }))
+            });
+            Ok(())
+        })
+        .await
+        .unwrap();
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn test_async_client_cert_callback_without_certificate() {
+    let (stream, addr) = create_server(|builder| {
+        builder.set_ca_file("tests/cert.pem").unwrap();
+        builder.set_verify(SslVerifyMode::PEER);
+    });
+
+    let server = async {
+        let stream = stream.await.unwrap();
+        assert!(stream.ssl().peer_certificate().is_none());
+    };
+
+    let client = async {
+        connect(addr, |builder| {
+            builder.set_ca_file("tests/cert.pem")?;
+            builder.set_async_client_cert_callback(|_, _| {
+                Ok(Box::pin(async {
+                    yield_now().await;
+                    Ok(None)
+                }))
+            });
+            Ok(())
+        })
+        .await
+        .unwrap();
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn test_async_client_cert_callback_error() {
+    let (stream, addr) = create_server(|builder| {
+        builder.set_verify(SslVerifyMode::PEER);
+    });
+
+    let server = async {
+        assert!(stream.await.is_err());
+    };
+
+    let client = async {
+        let result = connect(addr, |builder| {
+            builder.set_ca_file("tests/cert.pem")?;
+            builder.set_async_client_cert_callback(|_, _| {
+                Ok(Box::pin(async { Err(AsyncClientCertError) }))
+            });
+            Ok(())
+        })
+        .await;
+
+        assert!(result.is_err());
+    };
+
+    future::join(server, client).await;
+}
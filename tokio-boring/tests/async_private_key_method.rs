@@ -4,10 +4,12 @@ use boring::rsa::Padding;
 use boring::sign::{RsaPssSaltlen, Signer};
 use boring::ssl::{SslRef, SslSignatureAlgorithm};
 use futures::future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::task::yield_now;
 use tokio_boring::{
     AsyncPrivateKeyMethod, AsyncPrivateKeyMethodError, BoxPrivateKeyMethodFuture,
-    SslContextBuilderExt,
+    CancellationToken, SslContextBuilderExt,
 };
 
 mod common;
@@ -106,7 +108,7 @@ impl AsyncPrivateKeyMethod for Method {
 #[tokio::test]
 async fn test_sign_failure() {
     with_async_private_key_method_error(
-        Method::new().sign(|_, _, _, _| Err(AsyncPrivateKeyMethodError)),
+        Method::new().sign(|_, _, _, _| Err(AsyncPrivateKeyMethodError::FAILURE)),
     )
     .await;
 }
@@ -114,18 +116,45 @@ async fn test_sign_failure() {
 #[tokio::test]
 async fn test_sign_future_failure() {
     with_async_private_key_method_error(
-        Method::new().sign(|_, _, _, _| Ok(Box::pin(async { Err(AsyncPrivateKeyMethodError) }))),
+        Method::new()
+            .sign(|_, _, _, _| Ok(Box::pin(async { Err(AsyncPrivateKeyMethodError::FAILURE) }))),
     )
     .await;
 }
 
+#[tokio::test]
+async fn test_sign_future_failure_reason() {
+    let method = Method::new().sign(|_, _, _, _| {
+        Ok(Box::pin(async {
+            Err(AsyncPrivateKeyMethodError::new("key unavailable"))
+        }))
+    });
+
+    let (stream, addr) = create_server(move |builder| {
+        builder.set_async_private_key_method(method);
+    });
+
+    let server = async {
+        let err = stream.await.unwrap_err();
+        assert_eq!(err.private_key_failure_reason(), Some("key unavailable"));
+    };
+
+    let client = async {
+        let _err = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap_err();
+    };
+
+    future::join(server, client).await;
+}
+
 #[tokio::test]
 async fn test_sign_future_yield_failure() {
     with_async_private_key_method_error(Method::new().sign(|_, _, _, _| {
         Ok(Box::pin(async {
             yield_now().await;
 
-            Err(AsyncPrivateKeyMethodError)
+            Err(AsyncPrivateKeyMethodError::FAILURE)
         }))
     }))
     .await;
@@ -154,6 +183,38 @@ async fn test_sign_ok() {
     .await;
 }
 
+#[tokio::test]
+async fn test_sign_cancelled_on_timeout() {
+    let token = Arc::new(Mutex::new(None));
+    let token2 = token.clone();
+
+    let (stream, addr) = create_server(move |builder| {
+        builder.set_async_private_key_method(Method::new().sign(move |ssl, _, _, _| {
+            *token2.lock().unwrap() = Some(CancellationToken::for_ssl(ssl));
+
+            Ok(Box::pin(future::pending()))
+        }));
+    });
+
+    let server = async {
+        let _err = tokio::time::timeout(Duration::from_millis(100), stream)
+            .await
+            .unwrap_err();
+    };
+
+    let client = async {
+        let _err = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap_err();
+    };
+
+    future::join(server, client).await;
+
+    let token = token.lock().unwrap().take().unwrap();
+    assert!(token.is_cancelled());
+    token.cancelled().await;
+}
+
 fn sign_with_default_config(input: &[u8], output: &mut [u8]) -> usize {
     let pkey = PKey::private_key_from_pem(include_bytes!("key.pem")).unwrap();
     let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
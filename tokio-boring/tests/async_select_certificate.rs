@@ -2,7 +2,8 @@ use boring::ssl::ClientHello;
 use futures::future;
 use tokio::task::yield_now;
 use tokio_boring::{
-    AsyncSelectCertError, BoxSelectCertFinish, BoxSelectCertFuture, SslContextBuilderExt,
+    AsyncSelectCertChain, AsyncSelectCertError, AsyncSelectCertOutcome, BoxSelectCertFinish,
+    BoxSelectCertFuture, SslContextBuilderExt,
 };
 
 mod common;
@@ -72,6 +73,90 @@ async fn test_async_select_certificate_callback_finish_error() {
     .await;
 }
 
+#[tokio::test]
+async fn test_async_select_certificate_chain_fallback() {
+    with_trivial_client_server_exchange(|builder| {
+        let mut chain = AsyncSelectCertChain::new();
+
+        chain
+            .push(0, |_| Ok(Box::pin(async { Err(AsyncSelectCertError) })))
+            .push(2, |_| {
+                Ok(Box::pin(async {
+                    yield_now().await;
+
+                    Ok(AsyncSelectCertOutcome::NoMatch)
+                }))
+            })
+            .push(1, |_| {
+                Ok(Box::pin(async {
+                    yield_now().await;
+
+                    Ok(AsyncSelectCertOutcome::Selected(Box::new(
+                        |_: ClientHello<'_>| Ok(()),
+                    )))
+                }))
+            });
+
+        builder.set_async_select_certificate_chain(chain);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_async_select_certificate_chain_no_match() {
+    with_trivial_client_server_exchange(|builder| {
+        let mut chain = AsyncSelectCertChain::new();
+
+        chain
+            .push(0, |_| {
+                Ok(Box::pin(async { Ok(AsyncSelectCertOutcome::NoMatch) }))
+            })
+            .push(0, |_| {
+                Ok(Box::pin(async {
+                    yield_now().await;
+
+                    Ok(AsyncSelectCertOutcome::NoMatch)
+                }))
+            });
+
+        builder.set_async_select_certificate_chain(chain);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_async_select_certificate_chain_error() {
+    let (stream, addr) = create_server(|builder| {
+        let mut chain = AsyncSelectCertChain::new();
+
+        chain
+            .push(1, |_| {
+                Ok(Box::pin(async { Ok(AsyncSelectCertOutcome::NoMatch) }))
+            })
+            .push(0, |_| {
+                Ok(Box::pin(async {
+                    yield_now().await;
+
+                    Err(AsyncSelectCertError)
+                }))
+            });
+
+        builder.set_async_select_certificate_chain(chain);
+    });
+
+    let server = async {
+        let _err = stream.await.unwrap_err();
+    };
+
+    let client = async {
+        let _err = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap_err();
+    };
+
+    future::join(server, client).await;
+}
+
 async fn with_async_select_certificate_callback_error(
     callback: impl Fn(&mut ClientHello<'_>) -> Result<BoxSelectCertFuture, AsyncSelectCertError>
         + Send
@@ -0,0 +1,84 @@
+use boring::ex_data::Index;
+use boring::ssl::{NameType, SslContext, SslFiletype, SslMethod};
+use futures::future;
+use std::sync::Arc;
+use tokio::task::yield_now;
+use tokio_boring::{AsyncServernameError, SslContextBuilderExt};
+
+mod common;
+
+use self::common::{connect, create_server};
+
+fn tenant_context(index: Index<SslContext, &'static str>) -> Arc<SslContext> {
+    let mut builder = SslContext::builder(SslMethod::tls()).unwrap();
+    builder
+        .set_private_key_file("tests/key.pem", SslFiletype::PEM)
+        .unwrap();
+    builder
+        .set_certificate_chain_file("tests/cert.pem")
+        .unwrap();
+    builder.set_ex_data(index, "tenant");
+
+    Arc::new(builder.build())
+}
+
+#[tokio::test]
+async fn test_async_servername_callback_switches_context() {
+    let index = SslContext::new_ex_index().unwrap();
+    let tenant = tenant_context(index);
+
+    let (stream, addr) = create_server(move |builder| {
+        builder.set_async_servername_callback(move |client_hello| {
+            assert_eq!(
+                client_hello.servername(NameType::HOST_NAME),
+                Some("localhost")
+            );
+
+            let tenant = tenant.clone();
+
+            Ok(Box::pin(async move {
+                yield_now().await;
+
+                Ok(tenant)
+            }))
+        });
+    });
+
+    let server = async {
+        let stream = stream.await.unwrap();
+        assert_eq!(stream.ssl().ssl_context().ex_data(index), Some(&"tenant"));
+    };
+
+    let client = async {
+        connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn test_async_servername_callback_refusal() {
+    let (stream, addr) = create_server(|builder| {
+        builder.set_async_servername_callback(|_| {
+            Ok(Box::pin(async {
+                yield_now().await;
+
+                Err(AsyncServernameError::UNRECOGNIZED_NAME)
+            }))
+        });
+    });
+
+    let server = async {
+        let _err = stream.await.unwrap_err();
+    };
+
+    let client = async {
+        let _err = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap_err();
+    };
+
+    future::join(server, client).await;
+}
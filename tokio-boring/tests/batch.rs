@@ -0,0 +1,153 @@
+use boring::hash::MessageDigest;
+use boring::pkey::PKey;
+use boring::rsa::Padding;
+use boring::sign::{RsaPssSaltlen, Signer};
+use boring::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod, SslSignatureAlgorithm};
+use futures::future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_boring::batch::{
+    BatchPolicy, BatchSigner, BatchingPrivateKeyMethod, BoxBatchSignFuture, SignRequest,
+};
+use tokio_boring::SslContextBuilderExt;
+
+#[derive(Clone, Default)]
+struct RecordingSigner {
+    batch_sizes: Arc<Mutex<Vec<usize>>>,
+}
+
+impl BatchSigner for RecordingSigner {
+    fn sign_batch(&self, requests: Vec<SignRequest>) -> BoxBatchSignFuture {
+        self.batch_sizes.lock().unwrap().push(requests.len());
+
+        Box::pin(async move {
+            requests
+                .iter()
+                .map(|request| {
+                    assert_eq!(
+                        request.signature_algorithm(),
+                        SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256
+                    );
+
+                    Ok(sign(request.input()))
+                })
+                .collect()
+        })
+    }
+}
+
+fn sign(input: &[u8]) -> Vec<u8> {
+    let pkey = PKey::private_key_from_pem(include_bytes!("key.pem")).unwrap();
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+
+    signer.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+    signer
+        .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+        .unwrap();
+
+    signer.update(input).unwrap();
+    signer.sign_to_vec().unwrap()
+}
+
+async fn accept(acceptor: &SslAcceptor, stream: TcpStream) {
+    let mut stream = tokio_boring::accept(acceptor, stream).await.unwrap();
+    stream.write_all(b"asdf").await.unwrap();
+}
+
+async fn connect(connector: &SslConnector, addr: SocketAddr) {
+    let config = connector.configure().unwrap();
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let mut stream = tokio_boring::connect(config, "localhost", stream)
+        .await
+        .unwrap();
+
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"asdf");
+}
+
+#[tokio::test]
+async fn concurrent_handshakes_are_batched() {
+    let signer = RecordingSigner::default();
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    acceptor
+        .set_private_key_file("tests/key.pem", SslFiletype::PEM)
+        .unwrap();
+    acceptor
+        .set_certificate_chain_file("tests/cert.pem")
+        .unwrap();
+    acceptor.set_async_private_key_method(BatchingPrivateKeyMethod::new(
+        signer.clone(),
+        BatchPolicy::new()
+            .max_batch_size(2)
+            .max_delay(Duration::from_secs(5)),
+    ));
+    let acceptor = acceptor.build();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("tests/cert.pem").unwrap();
+    let connector = connector.build();
+
+    let server = async {
+        let first = listener.accept().await.unwrap().0;
+        let second = listener.accept().await.unwrap().0;
+
+        // Both handshakes must be in flight for their signatures to share a batch.
+        future::join(accept(&acceptor, first), accept(&acceptor, second)).await;
+    };
+
+    future::join3(server, connect(&connector, addr), connect(&connector, addr)).await;
+
+    assert_eq!(*signer.batch_sizes.lock().unwrap(), [2]);
+}
+
+#[test]
+fn batching_task_follows_runtimes() {
+    let signer = RecordingSigner::default();
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    acceptor
+        .set_private_key_file("tests/key.pem", SslFiletype::PEM)
+        .unwrap();
+    acceptor
+        .set_certificate_chain_file("tests/cert.pem")
+        .unwrap();
+    acceptor.set_async_private_key_method(BatchingPrivateKeyMethod::new(
+        signer.clone(),
+        BatchPolicy::new().max_batch_size(1),
+    ));
+    let acceptor = acceptor.build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_ca_file("tests/cert.pem").unwrap();
+    let connector = connector.build();
+
+    // The batching task spawned by the first runtime stops when it is dropped.
+    for _ in 0..2 {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+
+            let server = async {
+                let stream = listener.accept().await.unwrap().0;
+                accept(&acceptor, stream).await;
+            };
+
+            future::join(server, connect(&connector, addr)).await;
+        });
+    }
+
+    assert_eq!(*signer.batch_sizes.lock().unwrap(), [1, 1]);
+}
@@ -1,8 +1,10 @@
-use boring::ssl::{SslConnector, SslMethod};
+use boring::ssl::{SslConnector, SslKeyUpdate, SslMethod};
 use futures::future;
 use std::net::ToSocketAddrs;
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
+use tokio_boring::{BufferPool, DynamicRecordSizing};
 
 mod common;
 
@@ -57,3 +59,162 @@ async fn handshake_error() {
 
     future::join(server, client).await;
 }
+
+#[tokio::test]
+async fn into_inner_returns_buffered_data() {
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        stream.write_all(b"asdf").await.unwrap();
+
+        let (mut stream, buffered) = stream.into_inner();
+        assert!(buffered.is_empty());
+
+        stream.write_all(b"plain").await.unwrap();
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        let mut buf = [0; 2];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"as");
+
+        let (mut stream, buffered) = stream.into_inner();
+        assert_eq!(buffered, b"df");
+
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"plain");
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn flush_writes_key_update() {
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+
+        // The record is read off the transport, without BoringSSL consuming it.
+        let mut buf = [0; 64];
+        let n = tokio::time::timeout(Duration::from_secs(5), stream.get_mut().read(&mut buf))
+            .await
+            .expect("the KeyUpdate message was not flushed")
+            .unwrap();
+        assert!(n > 0);
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        stream
+            .ssl_mut()
+            .key_update(SslKeyUpdate::NOT_REQUESTED)
+            .unwrap();
+        stream.flush().await.unwrap();
+
+        stream
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn write_with_backpressure() {
+    let data = (0..8 * 1024 * 1024).map(|i| i as u8).collect::<Vec<u8>>();
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        stream.write_all(&data).await.unwrap();
+        stream.flush().await.unwrap();
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        // Let the server fill the socket buffers first.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut buf = vec![0; data.len()];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert!(buf == data);
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn buffer_pool() {
+    let data = vec![7; 16 * 1024 * 1024];
+    let pool = BufferPool::new(4);
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        stream.set_buffer_pool(pool.clone());
+
+        stream.write_all(&data).await.unwrap();
+        stream.flush().await.unwrap();
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        // Let the server fill the socket buffers first, so that its writes block.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut buf = vec![0; data.len()];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert!(buf == data);
+    };
+
+    future::join(server, client).await;
+
+    // The buffer went back to the pool once the server's last write completed.
+    assert_eq!(pool.idle(), 1);
+}
+
+#[tokio::test]
+async fn dynamic_record_sizing() {
+    let data = vec![7; 1000];
+    let (stream, addr) = create_server(|_| ());
+
+    let server = async {
+        let mut stream = stream.await.unwrap();
+        stream.set_dynamic_record_sizing(
+            DynamicRecordSizing::new()
+                .small_record_size(100)
+                .small_records(2)
+                .idle_timeout(Duration::from_secs(60)),
+        );
+
+        assert_eq!(stream.write(&data).await.unwrap(), 100);
+        assert_eq!(stream.write(&data).await.unwrap(), 100);
+        assert_eq!(stream.write(&data).await.unwrap(), 1000);
+        stream.flush().await.unwrap();
+    };
+
+    let client = async {
+        let mut stream = connect(addr, |builder| builder.set_ca_file("tests/cert.pem"))
+            .await
+            .unwrap();
+
+        let mut buf = vec![0; 1200];
+        stream.read_exact(&mut buf).await.unwrap();
+    };
+
+    future::join(server, client).await;
+}
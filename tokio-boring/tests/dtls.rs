@@ -0,0 +1,111 @@
+use boring::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod};
+use futures::future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UdpSocket;
+use tokio_boring::dtls::{self, UdpStream};
+
+async fn connected_pair() -> (UdpSocket, UdpSocket) {
+    let a = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    a.connect(b.local_addr().unwrap()).await.unwrap();
+    b.connect(a.local_addr().unwrap()).await.unwrap();
+
+    (a, b)
+}
+
+/// Forwards datagrams from `from` to `to`, dropping the first `drop` ones.
+fn forward(from: Arc<UdpSocket>, to: Arc<UdpSocket>, mut drop: usize) {
+    tokio::spawn(async move {
+        let mut buf = vec![0; 65536];
+
+        loop {
+            let n = from.recv(&mut buf).await.unwrap();
+            if drop > 0 {
+                drop -= 1;
+                continue;
+            }
+
+            let _ = to.send(&buf[..n]).await;
+        }
+    });
+}
+
+async fn exchange(client: UdpStream, server: UdpStream) {
+    let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::dtls()).unwrap();
+    acceptor
+        .set_private_key_file("tests/key.pem", SslFiletype::PEM)
+        .unwrap();
+    acceptor
+        .set_certificate_chain_file("tests/cert.pem")
+        .unwrap();
+    let acceptor = acceptor.build();
+
+    let server = async {
+        let mut stream = dtls::accept(&acceptor, server).await.unwrap();
+
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"asdf");
+
+        stream.write_all(b"jkl;").await.unwrap();
+    };
+
+    let client = async {
+        let mut connector = SslConnector::builder(SslMethod::dtls()).unwrap();
+        connector.set_ca_file("tests/cert.pem").unwrap();
+
+        let mut config = connector.build().configure().unwrap();
+        config.set_dtls_initial_timeout(Duration::from_millis(100));
+
+        let mut stream = dtls::connect(config, "localhost", client).await.unwrap();
+
+        stream.write_all(b"asdf").await.unwrap();
+
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"jkl;");
+    };
+
+    future::join(server, client).await;
+}
+
+#[tokio::test]
+async fn handshake() {
+    let (client, server) = connected_pair().await;
+
+    exchange(UdpStream::with_mtu(client, 1200), UdpStream::new(server)).await;
+}
+
+#[tokio::test]
+async fn handshake_retransmits_lost_flight() {
+    let (client, proxy_client) = connected_pair().await;
+    let (server, proxy_server) = connected_pair().await;
+
+    let proxy_client = Arc::new(proxy_client);
+    let proxy_server = Arc::new(proxy_server);
+
+    // The first ClientHello is lost, and must be retransmitted by the client.
+    forward(proxy_client.clone(), proxy_server.clone(), 1);
+    forward(proxy_server, proxy_client, 0);
+
+    exchange(UdpStream::new(client), UdpStream::new(server)).await;
+}
+
+#[tokio::test]
+async fn mtu_too_small() {
+    let (client, _server) = connected_pair().await;
+
+    let config = SslConnector::builder(SslMethod::dtls())
+        .unwrap()
+        .build()
+        .configure()
+        .unwrap();
+
+    let err = dtls::connect(config, "localhost", UdpStream::with_mtu(client, 1))
+        .await
+        .unwrap_err();
+    assert!(err.code().is_none());
+}
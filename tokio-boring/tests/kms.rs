@@ -0,0 +1,47 @@
+use boring::hash::hash;
+use boring::ssl::SslSignatureAlgorithm;
+use tokio_boring::kms::{DigestAlgorithm, DigestSignRequest, SignatureScheme};
+
+#[test]
+fn maps_signature_algorithms() {
+    let cases = [
+        (
+            SslSignatureAlgorithm::RSA_PKCS1_SHA256,
+            SignatureScheme::RsaPkcs1,
+            DigestAlgorithm::Sha256,
+        ),
+        (
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA384,
+            SignatureScheme::RsaPss,
+            DigestAlgorithm::Sha384,
+        ),
+        (
+            SslSignatureAlgorithm::ECDSA_SECP521R1_SHA512,
+            SignatureScheme::Ecdsa,
+            DigestAlgorithm::Sha512,
+        ),
+    ];
+
+    for (signature_algorithm, scheme, digest_algorithm) in cases {
+        let request = DigestSignRequest::new(b"handshake", signature_algorithm).unwrap();
+
+        assert_eq!(request.scheme(), scheme);
+        assert_eq!(request.digest_algorithm(), digest_algorithm);
+        assert_eq!(request.message(), b"handshake");
+        assert_eq!(
+            request.digest(),
+            &*hash(digest_algorithm.message_digest(), b"handshake").unwrap()
+        );
+    }
+}
+
+#[test]
+fn rejects_unsupported_algorithms() {
+    for signature_algorithm in [
+        SslSignatureAlgorithm::RSA_PKCS1_SHA1,
+        SslSignatureAlgorithm::RSA_PKCS1_MD5_SHA1,
+        SslSignatureAlgorithm::ED25519,
+    ] {
+        assert!(DigestSignRequest::new(b"handshake", signature_algorithm).is_err());
+    }
+}
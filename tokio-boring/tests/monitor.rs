@@ -0,0 +1,204 @@
+use boring::asn1::Asn1Time;
+use boring::hash::MessageDigest;
+use boring::pkey::PKey;
+use boring::rsa::Rsa;
+use boring::ssl::{Identity, ReloadableAcceptor, SslAcceptor, SslMethod};
+use boring::x509::{X509Name, X509};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio_boring::monitor::{CertEvent, CertMonitor, CertRenewalError, ExpiryPolicy};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn identity(days: u32) -> Identity {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_text("CN", "example.com").unwrap();
+    let name = name.build();
+
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(days).unwrap())
+        .unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+    Identity::new(cert.build(), vec![], key)
+}
+
+fn acceptor(monitor: &CertMonitor) -> ReloadableAcceptor {
+    let monitor = monitor.clone();
+
+    ReloadableAcceptor::new(move || {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+        acceptor.set_identity(&monitor.identity("example.com").unwrap())?;
+        Ok(acceptor.build())
+    })
+    .unwrap()
+}
+
+fn served_certificate(acceptor: &ReloadableAcceptor) -> Vec<u8> {
+    let acceptor = acceptor.acceptor();
+    acceptor.context().certificate().unwrap().to_der().unwrap()
+}
+
+#[tokio::test]
+async fn reports_thresholds_once() {
+    let monitor = CertMonitor::new(ExpiryPolicy::new().thresholds([7 * DAY, DAY]));
+    monitor.insert("example.com", identity(3));
+    let acceptor = acceptor(&monitor);
+    let mut events = monitor.subscribe();
+
+    let now = SystemTime::now();
+    monitor.check_at(&acceptor, now).await.unwrap();
+    assert!(matches!(
+        events.try_recv().unwrap(),
+        CertEvent::ExpiresSoon { name, remaining }
+            if name == "example.com" && remaining <= 3 * DAY
+    ));
+
+    monitor.check_at(&acceptor, now).await.unwrap();
+    assert_eq!(events.try_recv().unwrap_err(), TryRecvError::Empty);
+
+    monitor
+        .check_at(&acceptor, now + 2 * DAY + DAY / 2)
+        .await
+        .unwrap();
+    assert!(matches!(
+        events.try_recv().unwrap(),
+        CertEvent::ExpiresSoon { remaining, .. } if remaining <= DAY
+    ));
+
+    monitor.check_at(&acceptor, now + 4 * DAY).await.unwrap();
+    assert_eq!(
+        events.try_recv().unwrap(),
+        CertEvent::Expired {
+            name: "example.com".to_owned()
+        }
+    );
+
+    monitor.check_at(&acceptor, now + 5 * DAY).await.unwrap();
+    assert_eq!(events.try_recv().unwrap_err(), TryRecvError::Empty);
+}
+
+#[tokio::test]
+async fn renews_and_swaps_identity() {
+    let monitor = CertMonitor::new(ExpiryPolicy::new().renew_before(7 * DAY));
+    let current = identity(3);
+    let renewed = identity(90);
+    monitor.insert("example.com", current.clone());
+    let acceptor = acceptor(&monitor);
+    let mut events = monitor.subscribe();
+
+    monitor.set_renewal_callback({
+        let renewed = renewed.clone();
+
+        move |name, identity| {
+            assert_eq!(name, "example.com");
+            assert_eq!(
+                identity.certificate().to_der().unwrap(),
+                current.certificate().to_der().unwrap()
+            );
+
+            let renewed = renewed.clone();
+            Box::pin(async move { Ok(renewed) })
+        }
+    });
+
+    monitor.check(&acceptor).await.unwrap();
+    assert!(matches!(
+        events.try_recv().unwrap(),
+        CertEvent::ExpiresSoon { .. }
+    ));
+    assert_eq!(
+        events.try_recv().unwrap(),
+        CertEvent::Renewed {
+            name: "example.com".to_owned()
+        }
+    );
+
+    let served = served_certificate(&acceptor);
+    assert_eq!(served, renewed.certificate().to_der().unwrap());
+    assert_eq!(
+        monitor
+            .identity("example.com")
+            .unwrap()
+            .certificate()
+            .to_der()
+            .unwrap(),
+        served
+    );
+
+    monitor.check(&acceptor).await.unwrap();
+    assert_eq!(events.try_recv().unwrap_err(), TryRecvError::Empty);
+}
+
+#[tokio::test]
+async fn retries_failed_renewals() {
+    let monitor = CertMonitor::new(ExpiryPolicy::new().thresholds([]));
+    let current = identity(3);
+    monitor.insert("example.com", current.clone());
+    let acceptor = acceptor(&monitor);
+    let mut events = monitor.subscribe();
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    monitor.set_renewal_callback({
+        let attempts = attempts.clone();
+
+        move |_, _| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Err(CertRenewalError::new("the CA is unavailable")) })
+        }
+    });
+
+    monitor.check(&acceptor).await.unwrap();
+    monitor.check(&acceptor).await.unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    for _ in 0..2 {
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CertEvent::RenewalFailed {
+                name: "example.com".to_owned(),
+                error: CertRenewalError::new("the CA is unavailable"),
+            }
+        );
+    }
+
+    assert_eq!(
+        served_certificate(&acceptor),
+        current.certificate().to_der().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn watch_checks_periodically() {
+    let policy = ExpiryPolicy::new()
+        .thresholds([])
+        .check_interval(Duration::from_millis(10));
+    let monitor = CertMonitor::new(policy);
+    monitor.insert("example.com", identity(3));
+    let acceptor = acceptor(&monitor);
+    let mut events = monitor.subscribe();
+
+    monitor.set_renewal_callback(|_, _| {
+        Box::pin(async { Err(CertRenewalError::new("the CA is unavailable")) })
+    });
+
+    let task = monitor.watch(acceptor);
+    for _ in 0..3 {
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            CertEvent::RenewalFailed { .. }
+        ));
+    }
+
+    drop(task);
+}
@@ -0,0 +1,63 @@
+use boring::pkey::PKey;
+use boring::ssl::{SslSignatureAlgorithm, SslVersion};
+use tokio_boring::offload::LocalKeyAsyncSigner;
+use tokio_boring::SslContextBuilderExt;
+
+mod common;
+
+use self::common::with_trivial_client_server_exchange;
+
+fn key() -> PKey<boring::pkey::Private> {
+    PKey::private_key_from_pem(include_bytes!("key.pem")).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn offloaded_signature() {
+    with_trivial_client_server_exchange(|builder| {
+        builder.set_async_private_key_method(LocalKeyAsyncSigner::new(key()));
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn offloaded_signature_rsa_pkcs1() {
+    with_trivial_client_server_exchange(|builder| {
+        builder
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .unwrap();
+        builder
+            .set_signing_algorithm_prefs(&[SslSignatureAlgorithm::RSA_PKCS1_SHA256])
+            .unwrap();
+        builder.set_async_private_key_method(LocalKeyAsyncSigner::new(key()));
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn offloaded_decryption() {
+    with_trivial_client_server_exchange(|builder| {
+        builder
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .unwrap();
+        builder.set_cipher_list("AES128-SHA").unwrap();
+        builder.set_async_private_key_method(LocalKeyAsyncSigner::new(key()));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn offloaded_to_dedicated_runtime() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .max_blocking_threads(1)
+        .build()
+        .unwrap();
+    let handle = runtime.handle().clone();
+
+    with_trivial_client_server_exchange(|builder| {
+        builder.set_async_private_key_method(LocalKeyAsyncSigner::with_handle(key(), handle));
+    })
+    .await;
+
+    runtime.shutdown_background();
+}
@@ -0,0 +1,61 @@
+use boring::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod};
+use futures::future;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_boring::starttls;
+
+#[tokio::test]
+async fn handshake_after_plaintext() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = async {
+        let mut acceptor = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        acceptor
+            .set_private_key_file("tests/key.pem", SslFiletype::PEM)
+            .unwrap();
+        acceptor
+            .set_certificate_chain_file("tests/cert.pem")
+            .unwrap();
+        let acceptor = acceptor.build();
+
+        let stream = listener.accept().await.unwrap().0;
+
+        // Let the client send the command and its ClientHello.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "STARTTLS\r\n");
+
+        let residual = reader.buffer().to_vec();
+        assert!(!residual.is_empty());
+
+        let mut stream = starttls::accept(&acceptor, reader.into_inner(), &residual)
+            .await
+            .unwrap();
+        assert!(stream.get_ref().residual().is_empty());
+
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"asdf");
+    };
+
+    let client = async {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"STARTTLS\r\n").await.unwrap();
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_ca_file("tests/cert.pem").unwrap();
+        let config = connector.build().configure().unwrap();
+
+        let mut stream = starttls::connect(config, "localhost", stream, &[])
+            .await
+            .unwrap();
+        stream.write_all(b"asdf").await.unwrap();
+    };
+
+    future::join(server, client).await;
+}